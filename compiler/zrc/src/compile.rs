@@ -6,8 +6,11 @@
 use std::path::Path;
 
 use zrc_codegen::{DebugLevel, OptimizationLevel};
+use zrc_diagnostics::diagnostic::group_by_file;
 use zrc_parser::parser;
-use zrc_typeck::typeck;
+use zrc_typeck::{callgraph::CallGraph, explain::explain_types, typeck, used_types::used_types};
+
+use crate::metadata::ModuleMetadata;
 
 /// The list of possible outputs `zrc` can emit in
 ///
@@ -32,10 +35,76 @@ pub enum OutputFormat {
     ///
     /// This usually looks like your code with a bunch of parenthesis added.
     Tast,
+    /// The Zirco TAST, stringified to Zirco code without the type of every
+    /// expression, but with each implicit coercion shown as `coerce<T>(x)`
+    TastPretty,
     /// Assembly
     Asm,
     /// Object file
     Object,
+    /// The symbols the generated code references but does not define, one per
+    /// line
+    ///
+    /// This is used as a first pass before emitting an object file to report
+    /// what it must be linked against.
+    UndefinedSymbols,
+    /// The size and alignment of every declared type on the target, one per
+    /// line and sorted by name
+    ///
+    /// This is used as a first pass to verify type layouts against the ABI.
+    TypeLayouts,
+    /// Every distinct type the program uses and the LLVM type it is emitted
+    /// as, one per line in order of first use
+    ///
+    /// This is used as a first pass to debug how types are lowered.
+    LlvmTypes,
+    /// JSON describing the functions this module defines, to be loaded by
+    /// other modules with `--extern`
+    Metadata,
+    /// The Zirco TAST serialized to JSON, with every statement, expression,
+    /// type and span, on a single line
+    ///
+    /// This is used as a first pass for external tools to analyze the typed
+    /// program.
+    TastJson,
+    /// Whether each function is reachable from `main`, one per line in
+    /// declaration order
+    ///
+    /// This is used as a first pass to find dead code before codegen.
+    Reachability,
+    /// JSON with a node for every function and an edge for every call
+    /// between them, for visualizing the call graph
+    CallGraph,
+    /// The source file with a comment after each line giving the type of
+    /// every expression that ends on it
+    ExplainTypes,
+}
+
+/// The producer recorded in debug info when compiling with `reproducible`, in
+/// place of the full version string
+const REPRODUCIBLE_PRODUCER: &str = "zrc";
+
+/// Rewrite the start of `path` with the last `(from, to)` pair in
+/// `remap_path_prefixes` whose `from` it starts with, so that later mappings
+/// take precedence like in Clang and rustc.
+///
+/// Prefixes are matched by whole path components, so `/home/al` does not
+/// match `/home/alice`. Paths matching no prefix are returned unchanged.
+#[must_use]
+pub fn remap_path_prefix(path: &str, remap_path_prefixes: &[(String, String)]) -> String {
+    remap_path_prefixes
+        .iter()
+        .rev()
+        .find_map(|(from, to)| {
+            Path::new(path)
+                .strip_prefix(from)
+                .ok()
+                .map(|rest| Path::new(to).join(rest))
+        })
+        .map_or_else(
+            || path.to_string(),
+            |remapped| remapped.to_string_lossy().into_owned(),
+        )
 }
 
 /// Drive the compilation process.
@@ -43,7 +112,9 @@ pub enum OutputFormat {
 /// This function takes the source code as input and processes it through
 /// the various stages of compilation: parsing, type checking, and code
 /// generation. Depending on the specified output format, it can return the AST,
-/// TAST, LLVM IR, assembly, or object code.
+/// TAST (also as JSON, or as the source annotated with its types), LLVM IR,
+/// assembly, object code, the list of undefined symbols, the layouts of
+/// declared types, or the module metadata.
 ///
 /// # Arguments
 ///
@@ -61,6 +132,24 @@ pub enum OutputFormat {
 /// * `cpu` - The target CPU for code generation.
 /// * `forbid_unlisted_includes` - Whether to restrict includes to search paths
 ///   only.
+/// * `reproducible` - Whether to keep the compilation directory, compiler
+///   version and command line out of the output, so that it only depends on
+///   the source code.
+/// * `remap_path_prefixes` - `(from, to)` pairs rewriting the start of the
+///   source paths recorded in debug info. See [`remap_path_prefix`].
+/// * `overflow_checks` - Whether constant integer expressions that overflow
+///   their type are rejected instead of wrapping around.
+/// * `warn_unused_parameters` - Whether function parameters that are never
+///   read are warned about.
+/// * `codegen_options` - Options that change what is emitted into the module,
+///   such as the build identifier. Its disabled built-ins are also not
+///   recognized by the type checker.
+/// * `externs` - The metadata of other modules whose functions this module
+///   may call.
+/// * `warnings` - Where to collect the warnings produced during compilation.
+///   These are collected even if compilation fails afterward. When parsing
+///   finds several errors, possibly in different included files, they are
+///   grouped by file and all but the last one returned are collected here.
 ///
 /// # Errors
 ///
@@ -68,6 +157,7 @@ pub enum OutputFormat {
 /// compilation fails.
 #[expect(
     clippy::too_many_arguments,
+    clippy::fn_params_excessive_bools,
     clippy::wildcard_enum_match_arm,
     clippy::result_large_err
 )]
@@ -84,6 +174,13 @@ pub fn compile(
     triple: &zrc_codegen::TargetTriple,
     cpu: &str,
     forbid_unlisted_includes: bool,
+    reproducible: bool,
+    remap_path_prefixes: &[(String, String)],
+    overflow_checks: bool,
+    warn_unused_parameters: bool,
+    codegen_options: &zrc_codegen::CodegenOptions,
+    externs: &[ModuleMetadata],
+    warnings: &mut Vec<zrc_diagnostics::Diagnostic>,
 ) -> Result<Box<[u8]>, zrc_diagnostics::Diagnostic> {
     // === PREPROCESSOR ===
     let chunks = zrc_preprocessor::preprocess(
@@ -95,10 +192,26 @@ pub fn compile(
     )?;
 
     // === PARSER ===
+    // declarations loaded with `--extern` come first, as if they were included
     let mut ast = Vec::new();
+    for module in externs {
+        ast.extend(module.parse()?);
+    }
+    // every chunk is parsed even after one fails, so each included file reports
+    // its own errors
+    let mut parse_errors = Vec::new();
     for chunk in &chunks {
-        let chunk_decls = parser::parse_source_chunk(chunk)?;
-        ast.extend(chunk_decls);
+        match parser::parse_source_chunk_recovering(chunk) {
+            Ok(chunk_decls) => ast.extend(chunk_decls),
+            Err(diagnostics) => parse_errors.extend(diagnostics),
+        }
+    }
+    // every error but the last is reported alongside the warnings, so all of
+    // them are shown grouped by the file they are in
+    group_by_file(&mut parse_errors);
+    if let Some(last) = parse_errors.pop() {
+        warnings.extend(parse_errors);
+        return Err(last);
     }
 
     // display the AST if the user wants it
@@ -124,13 +237,28 @@ pub fn compile(
 
     // otherwise, move on:
     // === TYPE CHECKER ===
-    let mut global_scope = typeck::GlobalScope::new();
-    let typed_ast = typeck::type_program(&mut global_scope, ast)?;
+    let mut global_scope = typeck::GlobalScope {
+        cfg: typeck::CfgOptions::from_target_triple(&triple.as_str().to_string_lossy()),
+        overflow_checks,
+        warn_unused_parameters,
+        disabled_builtins: codegen_options.disabled_builtins.clone(),
+        ..typeck::GlobalScope::new()
+    };
+    let typed_ast = typeck::type_program(&mut global_scope, ast);
+    warnings.extend(global_scope.take_warnings());
+    let typed_ast = typed_ast?;
 
     // display the TAST if the user wants it
     if matches!(
         emit,
-        OutputFormat::TastDebug | OutputFormat::TastDebugPretty | OutputFormat::Tast,
+        OutputFormat::TastDebug
+            | OutputFormat::TastDebugPretty
+            | OutputFormat::Tast
+            | OutputFormat::TastPretty
+            | OutputFormat::TastJson
+            | OutputFormat::Reachability
+            | OutputFormat::CallGraph
+            | OutputFormat::ExplainTypes,
     ) {
         return Ok(match *emit {
             OutputFormat::TastDebug => format!("{typed_ast:?}"),
@@ -140,6 +268,40 @@ pub fn compile(
                 .map(|x| x.to_string())
                 .collect::<Vec<_>>()
                 .join("\n"),
+            OutputFormat::TastPretty => typed_ast
+                .into_iter()
+                .map(|x| format!("{x:#}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            OutputFormat::TastJson => {
+                serde_json::to_string(&typed_ast).expect("the TAST should serialize to JSON") + "\n"
+            }
+            OutputFormat::Reachability => CallGraph::from_program(&typed_ast)
+                .reachability()
+                .into_iter()
+                .map(|(name, reachable)| {
+                    format!(
+                        "{name}: {}\n",
+                        if reachable {
+                            "reachable"
+                        } else {
+                            "unreachable"
+                        }
+                    )
+                })
+                .collect(),
+            OutputFormat::CallGraph => {
+                serde_json::to_string_pretty(&CallGraph::from_program(&typed_ast))
+                    .expect("the call graph should serialize to JSON")
+                    + "\n"
+            }
+            OutputFormat::ExplainTypes => explain_types(
+                &typed_ast,
+                &Path::new(parent_directory)
+                    .join(file_name)
+                    .to_string_lossy(),
+                content,
+            ),
 
             // unreachable because we test above
             _ => unreachable!(),
@@ -151,6 +313,18 @@ pub fn compile(
     // otherwise, move on:
     // === CODE GENERATOR ===
 
+    // these end up in the debug info, and differ between machines and builds
+    let remapped_directory = remap_path_prefix(parent_directory, remap_path_prefixes);
+    let (frontend_version_string, parent_directory, cli_args) = if reproducible {
+        (REPRODUCIBLE_PRODUCER, ".", "")
+    } else {
+        (
+            frontend_version_string,
+            remapped_directory.as_str(),
+            cli_args,
+        )
+    };
+
     match *emit {
         OutputFormat::Asm => Ok(zrc_codegen::cg_program_to_buffer(
             frontend_version_string,
@@ -164,6 +338,7 @@ pub fn compile(
             debug_mode,
             triple,
             cpu,
+            codegen_options,
         )
         .as_slice()
         .into()),
@@ -179,6 +354,7 @@ pub fn compile(
             debug_mode,
             triple,
             cpu,
+            codegen_options,
         )
         .as_slice()
         .into()),
@@ -194,11 +370,248 @@ pub fn compile(
             debug_mode,
             triple,
             cpu,
+            codegen_options,
         )
         .as_bytes()
         .into()),
 
+        OutputFormat::UndefinedSymbols => Ok(zrc_codegen::cg_program_undefined_symbols(
+            frontend_version_string,
+            parent_directory,
+            file_name,
+            cli_args,
+            content,
+            typed_ast,
+            optimization_level,
+            triple,
+            cpu,
+            codegen_options,
+        )
+        .into_iter()
+        .map(|symbol| symbol + "\n")
+        .collect::<String>()
+        .as_bytes()
+        .into()),
+
+        OutputFormat::TypeLayouts => {
+            Ok(
+                zrc_codegen::type_layouts(global_scope.types.iter_declared(), triple, cpu)
+                    .into_iter()
+                    .map(|layout| {
+                        format!(
+                            "{}: size {}, align {}\n",
+                            layout.name, layout.size, layout.align
+                        )
+                    })
+                    .collect::<String>()
+                    .as_bytes()
+                    .into(),
+            )
+        }
+
+        OutputFormat::LlvmTypes => {
+            Ok(
+                zrc_codegen::llvm_type_names(&used_types(&typed_ast), triple, cpu)
+                    .into_iter()
+                    .map(|(ty, llvm_ty)| format!("{ty}: {llvm_ty}\n"))
+                    .collect::<String>()
+                    .as_bytes()
+                    .into(),
+            )
+        }
+
+        OutputFormat::Metadata => Ok(ModuleMetadata::from_global_scope(file_name, &global_scope)
+            .to_json()
+            .as_bytes()
+            .into()),
+
         // unreachable because we return in the above cases
         _ => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use zrc_codegen::{DebugLevel, OptimizationLevel};
+
+    use super::{OutputFormat, compile, remap_path_prefix};
+
+    /// Compile a program to an object file as if it was in `directory`
+    fn compile_object_in(directory: &str, reproducible: bool) -> Box<[u8]> {
+        compile(
+            &format!("zrc test build from {directory}"),
+            &[],
+            &OutputFormat::Object,
+            directory,
+            "test.zr",
+            &format!("zrc -g {directory}/test.zr"),
+            "fn add(a: i32, b: i32) -> i32 { return a + b; }\nfn main() -> i32 { return add(1, 2); }",
+            OptimizationLevel::None,
+            DebugLevel::Full,
+            &zrc_codegen::get_native_triple(),
+            "generic",
+            false,
+            reproducible,
+            &[],
+            false,
+            false,
+            &zrc_codegen::CodegenOptions::default(),
+            &[],
+            &mut Vec::new(),
+        )
+        .expect("compilation should succeed")
+    }
+
+    #[test]
+    fn reproducible_object_output_does_not_depend_on_the_build_environment() {
+        assert_eq!(
+            compile_object_in("/home/alice/project", true),
+            compile_object_in("/build/zrc-1234", true)
+        );
+        assert_ne!(
+            compile_object_in("/home/alice/project", false),
+            compile_object_in("/build/zrc-1234", false)
+        );
+    }
+
+    #[test]
+    fn parse_errors_in_each_included_file_name_that_file() {
+        let directory = std::env::temp_dir().join("zrc_multi_file_diagnostics");
+        std::fs::create_dir_all(&directory).expect("creating the directory should succeed");
+        std::fs::write(
+            directory.join("other.zr"),
+            "fn other() -> i32 { return 1 }\n",
+        )
+        .expect("writing other.zr should succeed");
+        let main_source = "fn before() -> i32 { return 2 }\n\
+                           #include \"other.zr\"\n\
+                           fn main() -> i32 { return 0 }\n";
+        std::fs::write(directory.join("main.zr"), main_source)
+            .expect("writing main.zr should succeed");
+
+        let mut warnings = Vec::new();
+        let error = compile(
+            "zrc test build",
+            &[],
+            &OutputFormat::Llvm,
+            &directory.to_string_lossy(),
+            "main.zr",
+            "zrc main.zr",
+            main_source,
+            OptimizationLevel::None,
+            DebugLevel::None,
+            &zrc_codegen::get_native_triple(),
+            "generic",
+            false,
+            false,
+            &[],
+            false,
+            false,
+            &zrc_codegen::CodegenOptions::default(),
+            &[],
+            &mut warnings,
+        )
+        .expect_err("compilation should fail");
+
+        let main_file = directory.join("main.zr").to_string_lossy().into_owned();
+        let other_file = directory.join("other.zr").to_string_lossy().into_owned();
+
+        // TEST: every file's errors are reported, with the errors in `main.zr`
+        // grouped together even though `other.zr` is included between them
+        let files = warnings
+            .iter()
+            .chain(std::iter::once(&error))
+            .map(|diagnostic| diagnostic.kind.span().file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            vec![main_file.as_str(), main_file.as_str(), other_file.as_str()]
+        );
+
+        // TEST: each diagnostic renders the file it is in
+        assert!(error.print(None).contains(&format!("{other_file}:1:")));
+        assert!(warnings[0].print(None).contains(&format!("{main_file}:1:")));
+    }
+
+    #[test]
+    fn reachability_reports_functions_never_called_from_main() {
+        let report = compile(
+            "zrc test build",
+            &[],
+            &OutputFormat::Reachability,
+            ".",
+            "test.zr",
+            "zrc --dump-reachability test.zr",
+            "fn used() {}\nfn dead() {}\nfn main() -> i32 { used(); return 0; }",
+            OptimizationLevel::None,
+            DebugLevel::None,
+            &zrc_codegen::get_native_triple(),
+            "generic",
+            false,
+            false,
+            &[],
+            false,
+            false,
+            &zrc_codegen::CodegenOptions::default(),
+            &[],
+            &mut Vec::new(),
+        )
+        .expect("compilation should succeed");
+
+        assert_eq!(
+            String::from_utf8_lossy(&report),
+            "used: reachable\ndead: unreachable\nmain: reachable\n"
+        );
+    }
+
+    #[test]
+    fn later_remap_path_prefixes_take_precedence() {
+        let remaps = [
+            ("/home/alice".to_string(), "/src".to_string()),
+            ("/home/alice/project".to_string(), ".".to_string()),
+        ];
+
+        assert_eq!(
+            remap_path_prefix("/home/alice/project/lib", &remaps),
+            "./lib"
+        );
+        assert_eq!(
+            remap_path_prefix("/home/alice/other", &remaps),
+            "/src/other"
+        );
+        assert_eq!(remap_path_prefix("/home/alicia", &remaps), "/home/alicia");
+    }
+
+    #[test]
+    fn debug_info_paths_are_remapped() {
+        let ir = compile(
+            "zrc test build",
+            &[],
+            &OutputFormat::Llvm,
+            "/home/alice/project",
+            "test.zr",
+            "zrc -g test.zr",
+            "fn main() -> i32 { return 0; }",
+            OptimizationLevel::None,
+            DebugLevel::Full,
+            &zrc_codegen::get_native_triple(),
+            "generic",
+            false,
+            false,
+            &[("/home/alice".to_string(), "/src".to_string())],
+            false,
+            false,
+            &zrc_codegen::CodegenOptions::default(),
+            &[],
+            &mut Vec::new(),
+        )
+        .expect("compilation should succeed");
+        let ir = String::from_utf8_lossy(&ir);
+
+        assert!(
+            ir.contains(r#"!DIFile(filename: "test.zr", directory: "/src/project")"#),
+            "{ir}"
+        );
+        assert!(!ir.contains("/home/alice"), "{ir}");
+    }
+}
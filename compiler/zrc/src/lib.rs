@@ -56,7 +56,8 @@
 )]
 
 pub mod compile;
-pub use compile::{OutputFormat, compile};
+pub mod metadata;
+pub use compile::{OutputFormat, compile, remap_path_prefix};
 pub use zrc_codegen as codegen;
 pub use zrc_diagnostics as diagnostics;
 pub use zrc_parser as parser;
@@ -0,0 +1,185 @@
+//! Module metadata for separate compilation
+//!
+//! `--emit metadata` describes the functions a module defines as JSON. Another
+//! module can then load it with `--extern name=path.json` so that its calls into
+//! those functions are type checked against the real definitions.
+//!
+//! Each function is stored as the Zirco source of its declaration, like
+//! `fn add(a: i32, b: i32) -> i32;`, so loading metadata is just parsing those
+//! declarations in front of the program.
+
+use std::{error::Error, fmt};
+
+use zrc_diagnostics::Diagnostic;
+use zrc_parser::{ast::stmt::Declaration, parser};
+use zrc_typeck::{tast::ty::Type, typeck::GlobalScope};
+use zrc_utils::span::Spanned;
+
+/// The metadata of a compiled module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleMetadata {
+    /// The name of the module
+    pub name: String,
+    /// The declaration of every function the module defines, as Zirco source
+    pub declarations: Vec<String>,
+}
+
+/// An error produced while loading [`ModuleMetadata`]
+#[derive(Debug)]
+pub enum MetadataError {
+    /// The metadata was not valid JSON
+    InvalidJson(serde_json::Error),
+    /// The JSON was valid, but was not shaped like `--emit metadata` output
+    InvalidShape,
+}
+impl fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidJson(err) => write!(f, "invalid metadata JSON: {err}"),
+            Self::InvalidShape => write!(f, "metadata must be `--emit metadata` output"),
+        }
+    }
+}
+impl Error for MetadataError {}
+
+impl ModuleMetadata {
+    /// Collect the metadata of every function implemented in a type checked
+    /// [`GlobalScope`], sorted by name
+    #[must_use]
+    pub fn from_global_scope(name: &str, global_scope: &GlobalScope<'_>) -> Self {
+        let mut functions = global_scope
+            .declarations
+            .iter()
+            .filter(|(_, metadata)| metadata.has_implementation)
+            .collect::<Vec<_>>();
+        functions.sort_by_key(|(name, _)| **name);
+
+        Self {
+            name: name.to_string(),
+            declarations: functions
+                .into_iter()
+                .map(|(name, metadata)| {
+                    let arguments = &metadata.fn_type.arguments;
+                    match &*metadata.fn_type.returns {
+                        returns if *returns == Type::unit() => format!("fn {name}({arguments});"),
+                        returns => format!("fn {name}({arguments}) -> {returns};"),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Serialize this metadata to JSON, the format of `--emit metadata`
+    ///
+    /// # Panics
+    /// Panics if the metadata cannot be serialized, which should not happen.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&serde_json::json!({
+            "name": self.name,
+            "declarations": self.declarations,
+        }))
+        .expect("metadata should be serializable to JSON")
+    }
+
+    /// Load metadata emitted by `--emit metadata`, naming the module `name`
+    ///
+    /// # Errors
+    /// Errors if `json` is not valid `--emit metadata` output.
+    pub fn from_json(name: &str, json: &str) -> Result<Self, MetadataError> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(MetadataError::InvalidJson)?;
+
+        let declarations = value
+            .get("declarations")
+            .and_then(serde_json::Value::as_array)
+            .ok_or(MetadataError::InvalidShape)?
+            .iter()
+            .map(|declaration| {
+                declaration
+                    .as_str()
+                    .map(ToString::to_string)
+                    .ok_or(MetadataError::InvalidShape)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            name: name.to_string(),
+            declarations,
+        })
+    }
+
+    /// Parse the declarations of this module, to be placed in front of the
+    /// program that uses it
+    ///
+    /// # Errors
+    /// Errors if a declaration is not valid Zirco.
+    #[expect(clippy::result_large_err)]
+    pub fn parse(&self) -> Result<Vec<Spanned<Declaration<'_>>>, Diagnostic> {
+        // Spans must have a static file name, so leak the module name
+        let file_name: &'static str = Box::leak(format!("<extern {}>", self.name).into_boxed_str());
+
+        let mut declarations = Vec::new();
+        for declaration in &self.declarations {
+            declarations.extend(parser::parse_program(declaration, file_name)?);
+        }
+
+        Ok(declarations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zrc_parser::{ast::stmt::Declaration, parser};
+    use zrc_typeck::typeck::{GlobalScope, type_program};
+    use zrc_utils::span::Spanned;
+
+    use super::ModuleMetadata;
+
+    /// Parse a test program
+    fn parse(source: &'static str) -> Vec<Spanned<Declaration<'static>>> {
+        parser::parse_program(source, "<test>").expect("parsing should succeed")
+    }
+
+    #[test]
+    fn metadata_round_trips_through_json() {
+        let mut global_scope = GlobalScope::new();
+        type_program(
+            &mut global_scope,
+            parse("fn add(a: i32, b: i32) -> i32 { return a + b; }\nfn log() {}\nfn undefined();"),
+        )
+        .expect("typeck should succeed");
+
+        // TEST: only implemented functions are described
+        let metadata = ModuleMetadata::from_global_scope("math", &global_scope);
+        assert_eq!(
+            metadata.declarations,
+            vec![
+                "fn add(a: i32, b: i32) -> i32;".to_string(),
+                "fn log();".to_string()
+            ]
+        );
+        assert_eq!(
+            ModuleMetadata::from_json("math", &metadata.to_json()).expect("metadata should load"),
+            metadata
+        );
+    }
+
+    #[test]
+    fn calls_into_extern_functions_are_type_checked() {
+        let metadata = ModuleMetadata::from_json(
+            "math",
+            r#"{ "name": "math", "declarations": ["fn add(a: i32, b: i32) -> i32;"] }"#,
+        )
+        .expect("metadata should load");
+
+        let type_checks = |source| {
+            let mut program = metadata.parse().expect("metadata should parse");
+            program.extend(parse(source));
+            type_program(&mut GlobalScope::new(), program).is_ok()
+        };
+
+        assert!(type_checks("fn main() -> i32 { return add(1, 2); }"));
+        assert!(!type_checks("fn main() -> i32 { return add(true, 2); }"));
+    }
+}
@@ -6,7 +6,10 @@ use std::{
 };
 
 use clap::Parser;
-use zrc::{OutputFormat, codegen::OptimizationLevel};
+use zrc::{
+    OutputFormat,
+    codegen::{FramePointer, ObjectFormat, OptimizationLevel},
+};
 
 /// The official Zirco compiler
 #[derive(Parser)]
@@ -33,6 +36,11 @@ pub struct Cli {
     #[arg(short, long)]
     pub target: Option<String>,
 
+    /// Write object files in this format instead of the one the target
+    /// normally uses
+    #[arg(long)]
+    pub object_format: Option<FrontendObjectFormat>,
+
     /// Set the target CPU to generate output for.
     #[arg(long)]
     #[clap(default_value = "generic")]
@@ -56,10 +64,139 @@ pub struct Cli {
     #[arg(long)]
     pub forbid_unlisted_includes: bool,
 
+    /// Keep the compilation directory, compiler version and command line out
+    /// of the output, so that identical sources always compile to identical
+    /// bytes
+    #[arg(long)]
+    pub reproducible: bool,
+
+    /// Rewrite source paths starting with `FROM` to start with `TO` instead,
+    /// given as `FROM=TO`, in debug info and diagnostics. When several
+    /// prefixes match a path, the last one given is used
+    #[arg(long, value_name = "FROM=TO", action = clap::ArgAction::Append)]
+    pub remap_path_prefix: Vec<String>,
+
+    /// Reject constant integer expressions that overflow their type, such as
+    /// `100i8 + 100i8`, instead of letting them wrap around like at runtime
+    #[arg(long)]
+    pub overflow_checks: bool,
+
+    /// Warn about function parameters that are never read, like unused local
+    /// variables. Parameters whose names start with `_` are not warned about
+    #[arg(long)]
+    pub warn_unused_parameters: bool,
+
+    /// Embed an identifier for this build, like `build-123`, in the module's
+    /// `llvm.ident` metadata so it ends up in the `.comment` section of the
+    /// object file
+    #[arg(long)]
+    pub module_id: Option<String>,
+
+    /// Probe the stack as large stack frames are allocated, so that deep
+    /// recursion or big local arrays hit the guard page instead of skipping it
+    #[arg(long)]
+    pub stack_probes: bool,
+
+    /// Embed the module's LLVM bitcode in the emitted object file, in the
+    /// `.llvmbc` section, so a linker can perform link-time optimization
+    #[arg(long)]
+    pub embed_bitcode: bool,
+
+    /// Keep a frame pointer in every function (`all`), only in functions that
+    /// call others (`non-leaf`), or in none, for profilers and backtraces.
+    /// Defaults to the target's convention
+    #[arg(long)]
+    pub frame_pointer: Option<FrontendFramePointer>,
+
+    /// Emit `main` as a C `int main(int argc, char **argv)` which calls your
+    /// `main`, so the object links directly against a C runtime
+    #[arg(long)]
+    pub entry_wrapper: bool,
+
+    /// Skip code generation for every function that is not reachable from
+    /// `main`, a constructor or a global, for smaller output
+    #[arg(long)]
+    pub gc_functions: bool,
+
+    /// Instrument the program to record how often each of its branches is
+    /// taken, writing a raw profile into DIR when it exits. Link it against
+    /// LLVM's profile runtime, such as with `clang -fprofile-generate`
+    #[arg(long, value_name = "DIR", conflicts_with = "profile_use")]
+    pub profile_generate: Option<PathBuf>,
+
+    /// Optimize using the profile in FILE, made by merging the raw profiles
+    /// of a `--profile-generate` build with `llvm-profdata merge`
+    #[arg(long, value_name = "FILE")]
+    pub profile_use: Option<PathBuf>,
+
+    /// Do not recognize compiler built-ins, so functions with their names are
+    /// called like any other. Given as `--no-builtin=NAME`, only disable the
+    /// built-in or C library function `NAME`; this may be repeated
+    #[arg(
+        long,
+        value_name = "NAME",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        action = clap::ArgAction::Append
+    )]
+    pub no_builtin: Vec<String>,
+
+    /// Load the `--emit metadata` output of another module as `name=path`, so
+    /// calls into its functions are type checked against their definitions
+    #[arg(long = "extern", action = clap::ArgAction::Append)]
+    pub externs: Vec<String>,
+
+    /// Before emitting, print the symbols the output references but does not
+    /// define to stderr, so you know what to link against
+    #[arg(long)]
+    pub list_undefined_symbols: bool,
+
+    /// Before emitting, print the size and alignment of every declared type
+    /// on the target to stderr, so you can verify them against the ABI
+    #[arg(long)]
+    pub dump_types: bool,
+
+    /// Before emitting, print the LLVM type each Zirco type used by the
+    /// program is lowered to on stderr, to debug type lowering
+    #[arg(long)]
+    pub dump_llvm_types: bool,
+
+    /// Before emitting, print the type checked AST as JSON to stderr, with
+    /// every statement, expression, type and span, for external tools
+    #[arg(long)]
+    pub dump_tast_json: bool,
+
+    /// Before emitting, print whether each function is reachable from `main`
+    /// to stderr, so you can find dead code
+    #[arg(long)]
+    pub dump_reachability: bool,
+
+    /// Print the LLVM optimization passes run at the `-O` level to stderr, in
+    /// order, then exit without compiling anything
+    #[arg(long)]
+    pub list_passes: bool,
+
+    /// Print LLVM's timing report for its optimization passes to stderr,
+    /// to find which pass dominates compile time
+    #[arg(long)]
+    pub time_passes: bool,
+
+    /// With `--emit llvm`, instead of the whole module write each function's
+    /// IR to `<name>.ll` in this directory and the global variables to
+    /// `globals.ll`, for diffing large modules
+    #[arg(long, value_name = "DIR")]
+    pub split_functions: Option<PathBuf>,
+
     /// Diagnostic output format
     #[arg(long)]
     #[clap(default_value = "human")]
     pub diagnostic_format: DiagFormat,
+
+    /// Fail without writing any output if compilation emits a warning. Each
+    /// warning is still reported as a warning
+    #[arg(long)]
+    pub deny_warnings: bool,
 }
 
 /// Configuration for diagnostic display formats
@@ -100,6 +237,50 @@ impl From<FrontendOptLevel> for OptimizationLevel {
     }
 }
 
+/// Which functions keep a frame pointer
+#[derive(Debug, Clone, Copy, clap::ValueEnum, PartialEq, Eq)]
+pub enum FrontendFramePointer {
+    /// Every function keeps a frame pointer
+    All,
+    /// Only functions that call other functions keep a frame pointer
+    NonLeaf,
+    /// Frame pointers may be omitted everywhere
+    None,
+}
+impl From<FrontendFramePointer> for FramePointer {
+    fn from(val: FrontendFramePointer) -> Self {
+        match val {
+            FrontendFramePointer::All => Self::All,
+            FrontendFramePointer::NonLeaf => Self::NonLeaf,
+            FrontendFramePointer::None => Self::None,
+        }
+    }
+}
+
+/// The object file formats `zrc` can write
+#[derive(Debug, Clone, Copy, clap::ValueEnum, PartialEq, Eq)]
+pub enum FrontendObjectFormat {
+    /// ELF
+    Elf,
+    /// COFF
+    Coff,
+    /// Mach-O
+    #[value(name = "macho")]
+    MachO,
+    /// WebAssembly
+    Wasm,
+}
+impl From<FrontendObjectFormat> for ObjectFormat {
+    fn from(val: FrontendObjectFormat) -> Self {
+        match val {
+            FrontendObjectFormat::Elf => Self::Elf,
+            FrontendObjectFormat::Coff => Self::Coff,
+            FrontendObjectFormat::MachO => Self::MachO,
+            FrontendObjectFormat::Wasm => Self::Wasm,
+        }
+    }
+}
+
 /// The list of possible outputs `zrc` can emit in
 ///
 /// Usually you will want to use `llvm`.
@@ -123,10 +304,20 @@ pub enum FrontendOutputFormat {
     ///
     /// This usually looks like your code with a bunch of parenthesis added.
     Tast,
+    /// The Zirco TAST, stringified to Zirco code without the type of every
+    /// expression, but with each implicit coercion shown as `coerce<T>(x)`
+    TastPretty,
     /// Assembly
     Asm,
     /// Object file
     Object,
+    /// JSON describing the functions this module defines, for `--extern`
+    Metadata,
+    /// JSON with the functions as nodes and the calls between them as edges
+    Callgraph,
+    /// Your code with a comment after each line giving the type of every
+    /// expression on it
+    ExplainTypes,
 }
 impl Display for FrontendOutputFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -141,8 +332,12 @@ impl Display for FrontendOutputFormat {
                 Self::TastDebug => "tast-debug",
                 Self::TastDebugPretty => "tast-debug-pretty",
                 Self::Tast => "tast",
+                Self::TastPretty => "tast-pretty",
                 Self::Asm => "asm",
                 Self::Object => "object",
+                Self::Metadata => "metadata",
+                Self::Callgraph => "callgraph",
+                Self::ExplainTypes => "explain-types",
             }
         )
     }
@@ -158,8 +353,12 @@ impl From<FrontendOutputFormat> for OutputFormat {
             FrontendOutputFormat::TastDebug => Self::TastDebug,
             FrontendOutputFormat::TastDebugPretty => Self::TastDebugPretty,
             FrontendOutputFormat::Tast => Self::Tast,
+            FrontendOutputFormat::TastPretty => Self::TastPretty,
             FrontendOutputFormat::Asm => Self::Asm,
             FrontendOutputFormat::Object => Self::Object,
+            FrontendOutputFormat::Metadata => Self::Metadata,
+            FrontendOutputFormat::Callgraph => Self::CallGraph,
+            FrontendOutputFormat::ExplainTypes => Self::ExplainTypes,
         }
     }
 }
@@ -209,3 +408,55 @@ pub fn get_include_paths(cli: &Cli) -> Vec<&'static Path> {
 
     include_paths
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::Cli;
+
+    #[test]
+    fn time_passes_flag_parses() {
+        let cli = Cli::try_parse_from(["zrc", "--time-passes", "main.zr"])
+            .expect("arguments should parse");
+        assert!(cli.time_passes);
+
+        let cli = Cli::try_parse_from(["zrc", "main.zr"]).expect("arguments should parse");
+        assert!(!cli.time_passes);
+    }
+
+    #[test]
+    fn remap_path_prefix_flag_can_be_repeated() {
+        let cli = Cli::try_parse_from([
+            "zrc",
+            "--remap-path-prefix",
+            "/home/alice=/src",
+            "--remap-path-prefix=/tmp=.",
+            "main.zr",
+        ])
+        .expect("arguments should parse");
+        assert_eq!(cli.remap_path_prefix, vec!["/home/alice=/src", "/tmp=."]);
+    }
+
+    #[test]
+    fn list_passes_does_not_need_an_input_file() {
+        let cli =
+            Cli::try_parse_from(["zrc", "--list-passes", "-O3"]).expect("arguments should parse");
+        assert!(cli.list_passes);
+        assert!(cli.path.is_none());
+    }
+
+    #[test]
+    fn extern_flag_can_be_repeated() {
+        let cli = Cli::try_parse_from([
+            "zrc",
+            "--extern",
+            "math=math.json",
+            "--extern",
+            "io=io.json",
+            "main.zr",
+        ])
+        .expect("arguments should parse");
+        assert_eq!(cli.externs, vec!["math=math.json", "io=io.json"]);
+    }
+}
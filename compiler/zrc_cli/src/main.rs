@@ -50,7 +50,7 @@
     clippy::doc_comment_double_space_linebreaks
 )]
 
-use std::{error::Error, fmt};
+use std::{error::Error, fmt, fs, path::Path};
 
 use mimalloc::MiMalloc;
 /// Use the mimalloc allocator as the global allocator, as LLVM is heavy on heap
@@ -65,7 +65,15 @@ mod ice;
 
 use clap::Parser;
 use cli::Cli;
-use zrc::{codegen::DebugLevel, compile, utils::io};
+use zrc::{
+    OutputFormat,
+    codegen::DebugLevel,
+    compile,
+    diagnostics::{Diagnostic, DiagnosticCounts},
+    metadata::ModuleMetadata,
+    typeck::typeck::DisabledBuiltins,
+    utils::io,
+};
 
 use crate::cli::{DiagFormat, FrontendOutputFormat};
 
@@ -84,6 +92,45 @@ pub(crate) fn version_string() -> String {
     zrc_buildinfo::generate_version_string(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
 }
 
+/// Load the metadata of a module given to `--extern` as `name=path`
+fn load_extern(spec: &str) -> Result<ModuleMetadata, Box<dyn Error>> {
+    let Some((name, path)) = spec.split_once('=') else {
+        return Err(Box::new(CliError(format!(
+            "--extern `{spec}` must be written as `name=path`"
+        ))));
+    };
+
+    Ok(ModuleMetadata::from_json(name, &fs::read_to_string(path)?)?)
+}
+
+/// Parse a `--remap-path-prefix` given as `from=to`
+fn parse_remap_path_prefix(spec: &str) -> Result<(String, String), CliError> {
+    spec.split_once('=')
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .ok_or_else(|| {
+            CliError(format!(
+                "--remap-path-prefix `{spec}` must be written as `from=to`"
+            ))
+        })
+}
+
+/// Write the LLVM IR of a module to `directory`, with one file per function
+/// definition and a shared `globals.ll` for everything else
+fn write_split_functions(directory: &Path, ir: &[u8]) -> Result<(), Box<dyn Error>> {
+    let split =
+        zrc::codegen::split_llvm_ir_by_function(&String::from_utf8_lossy(ir)).map_err(CliError)?;
+
+    fs::create_dir_all(directory)?;
+    fs::write(directory.join("globals.ll"), split.globals)?;
+    for (name, function_ir) in split.functions {
+        // method names like `Rect::area` are not valid file names everywhere
+        let file_name = name.replace(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_', "_");
+        fs::write(directory.join(format!("{file_name}.ll")), function_ir)?;
+    }
+
+    Ok(())
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     ice::setup_panic_hook();
 
@@ -94,6 +141,24 @@ fn main() -> Result<(), Box<dyn Error>> {
         return Ok(());
     }
 
+    let triple = cli
+        .target
+        .as_ref()
+        .map_or_else(zrc::codegen::get_native_triple, |triple| {
+            zrc::codegen::TargetTriple::create(triple)
+        });
+    let triple = match cli.object_format {
+        Some(object_format) => zrc::codegen::ObjectFormat::from(object_format)
+            .apply_to_triple(&triple)
+            .map_err(CliError)?,
+        None => triple,
+    };
+
+    if cli.list_passes {
+        zrc::codegen::print_pass_pipeline(cli.opt_level.clone().into(), &triple, &cli.cpu);
+        return Ok(());
+    }
+
     let Some(ref path) = cli.path else {
         return Err(Box::new(CliError("No input file provided".into())));
     };
@@ -121,40 +186,151 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     });
 
-    let result = compile(
-        &version_string(),
-        &cli::get_include_paths(&cli),
-        &emit.into(),
-        &directory_name,
-        &file_name,
-        &std::env::args().collect::<Vec<_>>().join(" "),
-        &source_content,
-        cli.opt_level.into(),
-        if cli.debug {
-            DebugLevel::Full
+    if cli.split_functions.is_some() && emit != FrontendOutputFormat::Llvm {
+        return Err(Box::new(CliError(
+            "--split-functions can only be used with `--emit llvm`".into(),
+        )));
+    }
+
+    let include_paths = cli::get_include_paths(&cli);
+    let cli_args = std::env::args().collect::<Vec<_>>().join(" ");
+
+    let remap_path_prefixes = cli
+        .remap_path_prefix
+        .iter()
+        .map(|spec| parse_remap_path_prefix(spec))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let externs = cli
+        .externs
+        .iter()
+        .map(|spec| load_extern(spec))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(module_id) = &cli.module_id
+        && !module_id
+            .chars()
+            .all(|ch| ch.is_ascii_graphic() || ch == ' ')
+    {
+        return Err(Box::new(CliError(format!(
+            "--module-id {module_id:?} must only contain printable ASCII characters"
+        ))));
+    }
+    if let Some(profile) = &cli.profile_use
+        && !profile.is_file()
+    {
+        return Err(Box::new(CliError(format!(
+            "--profile-use {} is not a file",
+            profile.display()
+        ))));
+    }
+    let profile = match (&cli.profile_generate, &cli.profile_use) {
+        (Some(directory), _) => Some(zrc::codegen::Profile::Generate(
+            directory.to_string_lossy().into_owned(),
+        )),
+        (None, Some(profile)) => Some(zrc::codegen::Profile::Use(
+            profile.to_string_lossy().into_owned(),
+        )),
+        (None, None) => None,
+    };
+
+    let codegen_options = zrc::codegen::CodegenOptions {
+        module_id: cli.module_id.clone(),
+        stack_probes: cli.stack_probes,
+        embed_bitcode: cli.embed_bitcode,
+        frame_pointer: cli.frame_pointer.map(Into::into),
+        entry_wrapper: cli.entry_wrapper,
+        gc_functions: cli.gc_functions,
+        disabled_builtins: if cli.no_builtin.is_empty() {
+            DisabledBuiltins::None
+        } else if cli.no_builtin.iter().any(String::is_empty) {
+            DisabledBuiltins::All
         } else {
-            DebugLevel::None
+            DisabledBuiltins::Named(cli.no_builtin.clone())
         },
-        &cli.target
-            .map_or_else(zrc::codegen::get_native_triple, |triple| {
-                zrc::codegen::TargetTriple::create(&triple)
-            }),
-        &cli.cpu,
-        cli.forbid_unlisted_includes,
-    );
+        profile,
+    };
 
-    match result {
-        Err(diagnostic) => {
-            if cli.diagnostic_format == DiagFormat::Json {
-                eprintln!("{}", diagnostic.print_json());
+    if cli.time_passes {
+        zrc::codegen::enable_pass_timing();
+    }
+
+    let run = |emit: &OutputFormat, warnings: &mut Vec<_>| {
+        compile(
+            &version_string(),
+            &include_paths,
+            emit,
+            &directory_name,
+            &file_name,
+            &cli_args,
+            &source_content,
+            cli.opt_level.clone().into(),
+            if cli.debug {
+                DebugLevel::Full
             } else {
-                eprintln!("{}", diagnostic.print(Some(&source_content)));
-            }
-            std::process::exit(1);
-        }
-        Ok(x) => {
-            io::open_output(&cli.out_file)?.write_all(&x)?;
+                DebugLevel::None
+            },
+            &triple,
+            &cli.cpu,
+            cli.forbid_unlisted_includes,
+            cli.reproducible,
+            &remap_path_prefixes,
+            cli.overflow_checks,
+            cli.warn_unused_parameters,
+            &codegen_options,
+            &externs,
+            warnings,
+        )
+    };
+
+    // first passes: report information about the output to stderr before
+    // emitting it, such as what it will need to be linked against. Only the
+    // final pass reports its warnings, so they are not printed repeatedly.
+    let mut warnings = Vec::new();
+    let first_passes = [
+        (cli.dump_types, OutputFormat::TypeLayouts),
+        (cli.dump_llvm_types, OutputFormat::LlvmTypes),
+        (cli.dump_tast_json, OutputFormat::TastJson),
+        (cli.dump_reachability, OutputFormat::Reachability),
+        (cli.list_undefined_symbols, OutputFormat::UndefinedSymbols),
+    ];
+    let result = first_passes
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .try_for_each(|(_, pass)| {
+            run(pass, &mut Vec::new()).map(|output| eprint!("{}", String::from_utf8_lossy(&output)))
+        })
+        .and_then(|()| run(&emit.into(), &mut warnings));
+
+    let remap_path = |path: &str| zrc::remap_path_prefix(path, &remap_path_prefixes);
+    let print_diagnostic = |diagnostic: &Diagnostic| {
+        if cli.diagnostic_format == DiagFormat::Json {
+            eprintln!("{}", diagnostic.print_json_remapped(remap_path));
+        } else {
+            eprintln!(
+                "{}",
+                diagnostic.print_remapped(Some(&source_content), remap_path)
+            );
         }
+    };
+    for warning in &warnings {
+        print_diagnostic(warning);
+    }
+    if let Err(diagnostic) = &result {
+        print_diagnostic(diagnostic);
+    }
+
+    let counts = DiagnosticCounts::tally(warnings.iter().chain(result.as_ref().err()));
+    if cli.diagnostic_format == DiagFormat::Human && !counts.is_empty() {
+        eprintln!("{counts}");
+    }
+
+    match result {
+        Ok(x) if !counts.fails(cli.deny_warnings) => match &cli.split_functions {
+            Some(directory) => write_split_functions(directory, &x)?,
+            None => io::open_output(&cli.out_file)?.write_all(&x)?,
+        },
+        _ => std::process::exit(1),
     }
 
     Ok(())
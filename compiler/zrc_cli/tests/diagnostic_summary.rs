@@ -0,0 +1,70 @@
+//! Tests for the diagnostic summary and `zrc --deny-warnings`
+
+use std::{fs, path::PathBuf, process::Command};
+
+/// A program that compiles with a single constant condition warning
+const WARNS_ONCE: &str = "fn main() -> i32 { if (1 == 1) return 1; return 0; }\n";
+
+/// Write `source` to a file named `name` in a fresh temporary directory,
+/// returning its path
+fn write_source(name: &str, source: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("zrc_diagnostic_summary_{name}"));
+    fs::create_dir_all(&directory).expect("temporary directory should be created");
+    let path = directory.join(format!("{name}.zr"));
+    fs::write(&path, source).expect("source file should be written");
+    path
+}
+
+/// Compile `path` with extra `args`, returning the exit status and stderr
+fn compile(path: &PathBuf, args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_zrc"))
+        .arg(path)
+        .args(["-o", "-", "--emit", "llvm"])
+        .args(args)
+        .output()
+        .expect("zrc should run");
+
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn warnings_are_counted_at_the_end_of_compilation() {
+    let path = write_source("warning", WARNS_ONCE);
+
+    let (success, stderr) = compile(&path, &[]);
+    assert!(success, "{stderr}");
+    assert!(stderr.ends_with("1 warning emitted\n"), "{stderr}");
+}
+
+#[test]
+fn errors_are_counted_with_the_warnings_before_them() {
+    let path = write_source(
+        "error",
+        "fn main() -> i32 { if (1 == 1) return 1; return true; }\n",
+    );
+
+    let (success, stderr) = compile(&path, &[]);
+    assert!(!success, "{stderr}");
+    assert!(stderr.ends_with("1 warning, 1 error emitted\n"), "{stderr}");
+}
+
+#[test]
+fn deny_warnings_fails_on_any_warning() {
+    let path = write_source("deny", WARNS_ONCE);
+
+    let (success, stderr) = compile(&path, &["--deny-warnings"]);
+    assert!(!success, "{stderr}");
+    assert!(stderr.ends_with("1 warning emitted\n"), "{stderr}");
+}
+
+#[test]
+fn summary_is_omitted_without_diagnostics() {
+    let path = write_source("clean", "fn main() -> i32 { return 0; }\n");
+
+    let (success, stderr) = compile(&path, &["--deny-warnings"]);
+    assert!(success, "{stderr}");
+    assert!(!stderr.contains("emitted"), "{stderr}");
+}
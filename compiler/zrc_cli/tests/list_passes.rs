@@ -0,0 +1,29 @@
+//! Tests for `zrc --list-passes`
+
+use std::process::Command;
+
+/// Count the passes `zrc --list-passes` reports at an optimization level
+fn passes_at(opt_level: &str) -> usize {
+    let output = Command::new(env!("CARGO_BIN_EXE_zrc"))
+        .args(["--list-passes", "-O", opt_level])
+        .output()
+        .expect("zrc should run");
+    assert!(output.status.success(), "{output:?}");
+
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter(|line| line.starts_with("Running pass:"))
+        .count()
+}
+
+#[test]
+fn higher_optimization_levels_run_more_passes() {
+    let none = passes_at("0");
+    let aggressive = passes_at("3");
+
+    assert!(none > 0);
+    assert!(
+        none < aggressive,
+        "-O0 ran {none} passes, -O3 ran {aggressive}"
+    );
+}
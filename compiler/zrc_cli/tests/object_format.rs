@@ -0,0 +1,50 @@
+//! Tests for `zrc --object-format`
+
+use std::{fs, path::PathBuf, process::Command};
+
+/// Write a small program to a fresh temporary directory, returning its path
+fn write_source() -> PathBuf {
+    let directory = std::env::temp_dir().join("zrc_object_format");
+    fs::create_dir_all(&directory).expect("temporary directory should be created");
+    let path = directory.join("main.zr");
+    fs::write(&path, "fn main() -> i32 { return 0; }\n").expect("source file should be written");
+    path
+}
+
+/// Compile an object for `x86_64-unknown-linux-gnu` in `object_format`,
+/// returning the output on success and stderr on failure
+fn compile_object(object_format: &str) -> Result<Vec<u8>, String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_zrc"))
+        .arg(write_source())
+        .args(["-o", "-", "--emit", "object"])
+        .args(["--target", "x86_64-unknown-linux-gnu"])
+        .args(["--object-format", object_format])
+        .output()
+        .expect("zrc should run");
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+}
+
+#[test]
+fn objects_are_written_in_the_requested_format() {
+    let elf = compile_object("elf").expect("an ELF object should be emitted");
+    assert!(elf.starts_with(b"\x7fELF"), "{elf:x?}");
+
+    // the machine field of a COFF header, `IMAGE_FILE_MACHINE_AMD64`
+    let coff = compile_object("coff").expect("a COFF object should be emitted");
+    assert!(coff.starts_with(&[0x64, 0x86]), "{coff:x?}");
+
+    // `MH_MAGIC_64`, in little endian
+    let macho = compile_object("macho").expect("a Mach-O object should be emitted");
+    assert!(macho.starts_with(&[0xcf, 0xfa, 0xed, 0xfe]), "{macho:x?}");
+}
+
+#[test]
+fn formats_the_target_cannot_use_are_rejected() {
+    let stderr = compile_object("wasm").expect_err("x86_64 cannot emit wasm objects");
+    assert!(stderr.contains("not supported"), "{stderr}");
+}
@@ -0,0 +1,70 @@
+//! Alignment-aware memory access
+//!
+//! LLVM assigns loads and stores an alignment from the module's data layout
+//! when none is given, which may not match the target. All loads and stores in
+//! the code generator go through the functions in this module so that they are
+//! annotated with the ABI alignment of the accessed type on the target, which
+//! keeps accesses correct on strict-alignment targets.
+//!
+//! No access carries `!tbaa` metadata. A union is read through fields of other
+//! types than the one last written, so type-based alias analysis would let the
+//! optimizer reorder a union read before the write it depends on. If TBAA is
+//! ever added, accesses through union fields must be left without it.
+
+use inkwell::{
+    builder::BuilderError,
+    types::{AnyType, BasicType, BasicTypeEnum},
+    values::{BasicValue, BasicValueEnum, InstructionValue, PointerValue},
+};
+
+use crate::ctx::AsCompilationUnitCtx;
+
+/// Get the ABI alignment of an LLVM type on the current target, in bytes
+pub fn abi_alignment<'ctx: 'a, 'a>(
+    ctx: &impl AsCompilationUnitCtx<'ctx, 'a>,
+    ty: &dyn AnyType<'ctx>,
+) -> u32 {
+    ctx.target_machine().get_target_data().get_abi_alignment(ty)
+}
+
+/// Build a `load` of `ty` from `ptr` with the ABI alignment of `ty`
+///
+/// # Errors
+/// Errors if the underlying `load` fails to build.
+pub fn build_aligned_load<'ctx: 'a, 'a>(
+    ctx: &impl AsCompilationUnitCtx<'ctx, 'a>,
+    ty: impl BasicType<'ctx>,
+    ptr: PointerValue<'ctx>,
+    name: &str,
+) -> Result<BasicValueEnum<'ctx>, BuilderError> {
+    let ty = ty.as_basic_type_enum();
+    let loaded = ctx.builder().build_load(ty, ptr, name)?;
+
+    loaded
+        .as_instruction_value()
+        .expect("load should produce an instruction")
+        .set_alignment(abi_alignment(ctx, &ty))
+        .expect("ABI alignment should be a power of two");
+
+    Ok(loaded)
+}
+
+/// Build a `store` of `value` into `ptr` with the ABI alignment of the type of
+/// `value`
+///
+/// # Errors
+/// Errors if the underlying `store` fails to build.
+pub fn build_aligned_store<'ctx: 'a, 'a>(
+    ctx: &impl AsCompilationUnitCtx<'ctx, 'a>,
+    ptr: PointerValue<'ctx>,
+    value: impl BasicValue<'ctx>,
+) -> Result<InstructionValue<'ctx>, BuilderError> {
+    let ty: BasicTypeEnum<'ctx> = value.as_basic_value_enum().get_type();
+    let store = ctx.builder().build_store(ptr, value)?;
+
+    store
+        .set_alignment(abi_alignment(ctx, &ty))
+        .expect("ABI alignment should be a power of two");
+
+    Ok(store)
+}
@@ -122,9 +122,19 @@ pub(crate) fn cg_expr<'ctx, 'input, 'a>(
         }
 
         TypedExprKind::Ternary(cond, lhs, rhs) => control::cg_ternary(ce, cond, lhs, rhs),
+        TypedExprKind::Elvis(value, fallback) => control::cg_elvis(ce, value, fallback),
+        TypedExprKind::Match(spilled, scrutinee, arms) => {
+            control::cg_match(ce, spilled, scrutinee, arms)
+        }
         TypedExprKind::Cast(x, ty) => misc::cg_cast(ce, x, &ty),
         TypedExprKind::SizeOf(ty) => misc::cg_size_of(ce, &ty),
+        TypedExprKind::AlignOf(ty) => misc::cg_align_of(ce, &ty),
+        TypedExprKind::IntegerConversion(conversion, x, ty) => {
+            misc::cg_integer_conversion(ce, conversion, x, &ty)
+        }
+        TypedExprKind::Rotate(rotation, x, amount) => misc::cg_rotate(ce, rotation, x, amount),
         TypedExprKind::StructConstruction(fields) => misc::cg_struct_construction(ce, &fields),
         TypedExprKind::ArrayLiteral(elements) => literals::cg_array_literal(ce, elements),
+        TypedExprKind::ArrayRepeat(value, count) => literals::cg_array_repeat(ce, value, count),
     }
 }
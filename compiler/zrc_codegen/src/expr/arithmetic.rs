@@ -13,7 +13,7 @@ use crate::{
     bb::{BasicBlockAnd, BasicBlockExt},
     ctx::BlockCtx,
     expr::{CgExprArgs, cg_expr},
-    ty::llvm_basic_type,
+    ty::{llvm_basic_type, llvm_int_type},
     unpack,
 };
 
@@ -23,11 +23,17 @@ pub fn build_binary_bitwise<'ctx>(
     op: BinaryBitwise,
     lhs: IntValue<'ctx>,
     rhs: IntValue<'ctx>,
+    result_is_signed: bool,
 ) -> Result<IntValue<'ctx>, BuilderError> {
     match op {
         BinaryBitwise::And => cg.builder.build_and(lhs, rhs, "and"),
         BinaryBitwise::Or => cg.builder.build_or(lhs, rhs, "or"),
         BinaryBitwise::Xor => cg.builder.build_xor(lhs, rhs, "xor"),
+        BinaryBitwise::Shl => cg.builder.build_left_shift(lhs, rhs, "shl"),
+        // signed integers shift in copies of their sign bit, unsigned ones zeroes
+        BinaryBitwise::Shr => cg
+            .builder
+            .build_right_shift(lhs, rhs, result_is_signed, "shr"),
     }
 }
 /// Build the required instruction for an [`Arithmetic`] operation
@@ -55,7 +61,12 @@ pub fn build_arithmetic<'ctx>(
 
 /// Code generate a binary bitwise operation
 pub fn cg_binary_bitwise<'ctx, 'input>(
-    CgExprArgs { cg, mut bb, .. }: CgExprArgs<'ctx, 'input, '_>,
+    CgExprArgs {
+        cg,
+        mut bb,
+        inferred_type,
+        ..
+    }: CgExprArgs<'ctx, 'input, '_>,
     op: BinaryBitwise,
     lhs: Box<TypedExpr<'input>>,
     rhs: Box<TypedExpr<'input>>,
@@ -63,8 +74,14 @@ pub fn cg_binary_bitwise<'ctx, 'input>(
     let lhs = unpack!(bb = cg_expr(cg, bb, *lhs));
     let rhs = unpack!(bb = cg_expr(cg, bb, *rhs));
 
-    let reg = build_binary_bitwise(cg, op, lhs.into_int_value(), rhs.into_int_value())
-        .expect("binary bitwise operation should have compiled successfully");
+    let reg = build_binary_bitwise(
+        cg,
+        op,
+        lhs.into_int_value(),
+        rhs.into_int_value(),
+        inferred_type.is_signed_integer(),
+    )
+    .expect("binary bitwise operation should have compiled successfully");
 
     bb.and(reg.as_basic_value_enum())
 }
@@ -82,10 +99,46 @@ pub fn cg_arithmetic<'ctx, 'input>(
     rhs: Box<TypedExpr<'input>>,
 ) -> BasicBlockAnd<'ctx, BasicValueEnum<'ctx>> {
     let lhs_ty = lhs.inferred_type.clone();
+    let rhs_is_pointer = matches!(rhs.inferred_type, Type::Ptr(_));
     let lhs = unpack!(bb = cg_expr(cg, bb, *lhs));
     let rhs = unpack!(bb = cg_expr(cg, bb, *rhs));
 
-    if let Type::Ptr(pointee) = lhs_ty {
+    if let Type::Ptr(pointee) = &lhs_ty
+        && rhs_is_pointer
+    {
+        // `p - q` is the distance between the addresses divided by the element size.
+        // The type checker only allows subtraction here.
+        let isize_type = llvm_int_type(&cg, &Type::Isize).0;
+        let lhs_addr = cg
+            .builder
+            .build_ptr_to_int(lhs.into_pointer_value(), isize_type, "lhs_addr")
+            .expect("ptrtoint should have compiled successfully");
+        let rhs_addr = cg
+            .builder
+            .build_ptr_to_int(rhs.into_pointer_value(), isize_type, "rhs_addr")
+            .expect("ptrtoint should have compiled successfully");
+        let byte_distance = cg
+            .builder
+            .build_int_sub(lhs_addr, rhs_addr, "ptr_diff_bytes")
+            .expect("sub should have compiled successfully");
+
+        // zero-sized elements have no meaningful distance, so measure it in bytes
+        let element_size = cg
+            .target_machine
+            .get_target_data()
+            .get_abi_size(&llvm_basic_type(&cg, pointee).0)
+            .max(1);
+        let reg = cg
+            .builder
+            .build_int_exact_signed_div(
+                byte_distance,
+                isize_type.const_int(element_size, false),
+                "ptr_diff",
+            )
+            .expect("sdiv should have compiled successfully");
+
+        bb.and(reg.as_basic_value_enum())
+    } else if let Type::Ptr(pointee) = lhs_ty {
         // Most languages make incrementing a pointer increase the address by the size
         // of the pointee type, hence our use of `gep`.
         // REVIEW: Is this the approach we want to take?
@@ -176,7 +229,10 @@ mod tests {
 
     use indoc::indoc;
 
-    use crate::cg_snapshot_test;
+    use crate::{
+        cg_snapshot_test,
+        test_utils::{function_body, ir_of},
+    };
 
     #[test]
     fn pointer_arithmetic_generates_proper_gep() {
@@ -192,6 +248,19 @@ mod tests {
             "#});
     }
 
+    #[test]
+    fn pointer_difference_generates_element_distance() {
+        cg_snapshot_test!(indoc! {"
+                fn test() {
+                    let p: *i32;
+                    let q: *i32;
+
+                    // TEST: should subtract the addresses and divide by sizeof(i32)
+                    let d = p - q;
+                }
+            "});
+    }
+
     #[test]
     fn arithmetic_operators_generate() {
         cg_snapshot_test!(indoc! {"
@@ -254,4 +323,33 @@ mod tests {
                 }
             "});
     }
+
+    #[test]
+    fn right_shifts_follow_the_signedness_of_their_operands() {
+        let source = indoc! {"
+            fn signed(x: i32, n: i32) -> i32 {
+                let y = x >> n;
+                y >>= n;
+                return y;
+            }
+            fn unsigned(x: u32, n: u32) -> u32 {
+                let y = x >> n;
+                y >>= n;
+                return y;
+            }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: signed integers shift in copies of their sign bit, both in `>>`
+        // and in `>>=`
+        let signed = function_body(&ir, "signed");
+        assert_eq!(signed.matches(" = ashr i32").count(), 2, "{ir}");
+        assert!(!signed.contains("lshr"), "{ir}");
+
+        // TEST: unsigned integers shift in zeroes
+        let unsigned = function_body(&ir, "unsigned");
+        assert_eq!(unsigned.matches(" = lshr i32").count(), 2, "{ir}");
+        assert!(!unsigned.contains("ashr"), "{ir}");
+    }
 }
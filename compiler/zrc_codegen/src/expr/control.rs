@@ -1,11 +1,19 @@
 //! code generation for control flow expressions
 
-use inkwell::values::{BasicValue, BasicValueEnum};
-use zrc_typeck::tast::expr::{Place, TypedExpr};
+use inkwell::{
+    IntPredicate,
+    values::{BasicValue, BasicValueEnum, PointerValue},
+};
+use zrc_typeck::tast::{
+    expr::{MatchArm, Place, TypedExpr},
+    ty::Type,
+};
 
 use super::place::cg_place;
 use crate::{
+    align::build_aligned_store,
     bb::{BasicBlockAnd, BasicBlockExt},
+    ctx::BlockCtx,
     expr::{CgExprArgs, cg_expr},
     ty::{llvm_basic_type, llvm_type},
     unpack,
@@ -125,6 +133,205 @@ pub fn cg_ternary<'ctx, 'input>(
     end_bb.and(result_reg.as_basic_value())
 }
 
+/// Code generate a match expression, a switch over `scrutinee` in which each
+/// arm yields a value
+pub fn cg_match<'ctx, 'input>(
+    CgExprArgs {
+        cg,
+        mut bb,
+        inferred_type,
+        ..
+    }: CgExprArgs<'ctx, 'input, '_>,
+    spilled: Option<(&'input str, Box<TypedExpr<'input>>)>,
+    scrutinee: Box<TypedExpr<'input>>,
+    arms: Vec<MatchArm<'input>>,
+) -> BasicBlockAnd<'ctx, BasicValueEnum<'ctx>> {
+    // The code generated will look like:
+    //   entry:
+    //       ...
+    //       %scrutinee = ...
+    //       switch TY %scrutinee, label %default [ TY 0, label %arm ... ]
+    //   arm:
+    //       ... yields %a
+    //       br label %end
+    //   default:
+    //       ... yields %b, or is unreachable without a `default` arm
+    //       br label %end
+    //   end:
+    //       %yield = phi TY [ %a, %arm ], [ %b, %default ]
+
+    // an enum is stored once, and the switch and bindings read it from there
+    let mut match_scope = cg.scope.clone();
+    if let Some((name, value)) = spilled {
+        let value = unpack!(bb = cg_expr(cg, bb, *value));
+        match_scope.insert(name, store_in_local(cg, name, value));
+    }
+    let cg = BlockCtx {
+        scope: &match_scope,
+        ..cg
+    };
+
+    let scrutinee = unpack!(bb = cg_expr(cg, bb, *scrutinee));
+
+    let mut default_bb = None;
+    let mut cases = Vec::new();
+    let mut arm_bbs = Vec::new();
+    for MatchArm {
+        trigger,
+        binding,
+        value,
+    } in arms
+    {
+        let arm_bb = cg.ctx.append_basic_block(cg.fn_value, "arm");
+        match trigger {
+            Some(trigger) => {
+                let trigger = unpack!(bb = cg_expr(cg, bb, trigger));
+                cases.push((trigger.into_int_value(), arm_bb));
+            }
+            None => default_bb = Some(arm_bb),
+        }
+        arm_bbs.push((arm_bb, binding, value));
+    }
+
+    // every value is matched by an arm if there is no `default` arm
+    let default_bb = default_bb.unwrap_or_else(|| {
+        let unreachable_bb = cg.ctx.append_basic_block(cg.fn_value, "default");
+        cg.builder.position_at_end(unreachable_bb);
+        cg.builder
+            .build_unreachable()
+            .expect("unreachable should generate successfully");
+        unreachable_bb
+    });
+    let end_bb = cg.ctx.append_basic_block(cg.fn_value, "end");
+
+    cg.builder.position_at_end(bb);
+    cg.builder
+        .build_switch(scrutinee.into_int_value(), default_bb, &cases)
+        .expect("switch should generate successfully");
+
+    let mut incoming = Vec::new();
+    for (mut arm_bb, binding, value) in arm_bbs {
+        cg.builder.position_at_end(arm_bb);
+
+        // `Variant: x => ...` stores the value of the variant into `x`
+        let mut arm_scope = cg.scope.clone();
+        if let Some((name, binding)) = binding {
+            let binding = unpack!(arm_bb = cg_expr(cg, arm_bb, binding));
+            arm_scope.insert(name, store_in_local(cg, name, binding));
+        }
+
+        let arm_cg = BlockCtx {
+            scope: &arm_scope,
+            ..cg
+        };
+        let value = unpack!(arm_bb = cg_expr(arm_cg, arm_bb, value));
+        cg.builder
+            .build_unconditional_branch(end_bb)
+            .expect("unconditional branch should have been created successfully");
+        incoming.push((value, arm_bb));
+    }
+
+    cg.builder.position_at_end(end_bb);
+    let result_reg = cg
+        .builder
+        .build_phi(llvm_basic_type(&cg, &inferred_type).0, "yield")
+        .expect("phi node should have been created successfully");
+    for (value, arm_bb) in &incoming {
+        result_reg.add_incoming(&[(value, *arm_bb)]);
+    }
+
+    end_bb.and(result_reg.as_basic_value())
+}
+
+/// Store `value` in a new local named `name`, allocated in the entry block of
+/// the function, and return a pointer to it
+fn store_in_local<'ctx>(
+    cg: BlockCtx<'ctx, '_, '_>,
+    name: &str,
+    value: BasicValueEnum<'ctx>,
+) -> PointerValue<'ctx> {
+    let entry_block_builder = cg.ctx.create_builder();
+    let first_bb = cg
+        .fn_value
+        .get_first_basic_block()
+        .expect("function should have at least one basic block");
+    match first_bb.get_first_instruction() {
+        Some(first_instruction) => entry_block_builder.position_before(&first_instruction),
+        None => entry_block_builder.position_at_end(first_bb),
+    }
+
+    let ptr = entry_block_builder
+        .build_alloca(value.get_type(), &format!("let_{name}"))
+        .expect("alloca should generate successfully");
+    build_aligned_store(&cg, ptr, value).expect("store should generate successfully");
+    ptr
+}
+
+/// Code generate an Elvis expression, `value ?: fallback`
+pub fn cg_elvis<'ctx, 'input>(
+    CgExprArgs {
+        cg,
+        mut bb,
+        inferred_type,
+        ..
+    }: CgExprArgs<'ctx, 'input, '_>,
+    value: Box<TypedExpr<'input>>,
+    fallback: Box<TypedExpr<'input>>,
+) -> BasicBlockAnd<'ctx, BasicValueEnum<'ctx>> {
+    // `value` is evaluated once, and reused as the result if it is not zero:
+    //   entry:
+    //       ... yields %value
+    //       %nonzero = icmp ne TY %value, 0
+    //       br i1 %nonzero, label %end, label %if_zero
+    //   if_zero:
+    //       ... yields %fallback
+    //       br label %end
+    //   end:
+    //       %yield = phi TY [ %value, %entry ], [ %fallback, %if_zero ]
+    let is_bool = value.inferred_type == Type::Bool;
+    let value = unpack!(bb = cg_expr(cg, bb, *value));
+
+    let is_nonzero = if is_bool {
+        value.into_int_value()
+    } else if value.is_pointer_value() {
+        cg.builder
+            .build_is_not_null(value.into_pointer_value(), "nonzero")
+            .expect("null check should have compiled successfully")
+    } else {
+        let value = value.into_int_value();
+        cg.builder
+            .build_int_compare(
+                IntPredicate::NE,
+                value,
+                value.get_type().const_zero(),
+                "nonzero",
+            )
+            .expect("comparison should have compiled successfully")
+    };
+
+    let mut if_zero_bb = cg.ctx.append_basic_block(cg.fn_value, "if_zero");
+    let end_bb = cg.ctx.append_basic_block(cg.fn_value, "end");
+
+    cg.builder
+        .build_conditional_branch(is_nonzero, end_bb, if_zero_bb)
+        .expect("conditional branch should have been created successfully");
+
+    cg.builder.position_at_end(if_zero_bb);
+    let fallback = unpack!(if_zero_bb = cg_expr(cg, if_zero_bb, *fallback));
+    cg.builder
+        .build_unconditional_branch(end_bb)
+        .expect("unconditional branch should have been created successfully");
+
+    cg.builder.position_at_end(end_bb);
+    let result_reg = cg
+        .builder
+        .build_phi(llvm_basic_type(&cg, &inferred_type).0, "yield")
+        .expect("phi node should have been created successfully");
+    result_reg.add_incoming(&[(&value, bb), (&fallback, if_zero_bb)]);
+
+    end_bb.and(result_reg.as_basic_value())
+}
+
 #[cfg(test)]
 mod tests {
     // Please read the "Common patterns in tests" section of crate::test_utils for
@@ -132,7 +339,10 @@ mod tests {
 
     use indoc::indoc;
 
-    use crate::cg_snapshot_test;
+    use crate::{
+        cg_snapshot_test,
+        test_utils::{function_body, ir_of},
+    };
 
     #[test]
     fn comma_yields_right_value() {
@@ -160,4 +370,103 @@ mod tests {
                 }
             "});
     }
+
+    #[test]
+    fn functions_are_called_through_their_address() {
+        let source = indoc! {"
+            fn add_one(x: i32) -> i32 { return x + 1; }
+
+            fn test() -> i32 {
+                let f = &add_one;
+                // TEST: both calls load the pointer stored in `f` and call it
+                return f(1) + (*f)(2);
+            }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: `&add_one` is the function itself, stored as a pointer
+        assert!(ir.contains("store ptr @add_one, ptr %let_f"), "{ir}");
+        assert_eq!(ir.matches("call i32 %").count(), 2, "{ir}");
+        assert!(!ir.contains("call i32 @add_one"), "{ir}");
+    }
+
+    #[test]
+    fn functions_are_called_through_a_table_of_their_addresses() {
+        let source = indoc! {"
+            fn f() -> i32 { return 1; }
+            fn g() -> i32 { return 2; }
+            fn h() -> i32 { return 3; }
+
+            fn test(i: usize) -> i32 {
+                let table: [3]*(fn() -> i32) = [f, g, h];
+                // TEST: the call loads the indexed pointer and calls it
+                return table[i]();
+            }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: each element of the table is the address of its function
+        for name in ["f", "g", "h"] {
+            assert!(ir.contains(&format!("store ptr @{name}, ptr")), "{ir}");
+        }
+        assert_eq!(ir.matches("call i32 %").count(), 1, "{ir}");
+        assert!(!ir.contains("call i32 @"), "{ir}");
+    }
+
+    #[test]
+    fn elvis_evaluates_its_value_once() {
+        let source = indoc! {"
+            fn get_int() -> i32;
+            fn get_bool() -> bool;
+            fn get_ptr() -> *u8;
+
+            fn int_or_default() -> i32 { return get_int() ?: 3; }
+            fn bool_or_default(b: bool) -> bool { return get_bool() ?: b; }
+            fn ptr_or_default(p: *u8) -> *u8 { return get_ptr() ?: p; }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: an integer is compared against zero, and the value itself is the
+        // result if it is not zero, so it is only evaluated once
+        let int_body = function_body(&ir, "int_or_default");
+        assert_eq!(int_body.matches("@get_int()").count(), 1, "{ir}");
+        assert!(int_body.contains("%nonzero = icmp ne i32"), "{ir}");
+        assert!(int_body.contains("phi i32"), "{ir}");
+
+        // TEST: a bool is used as the condition directly
+        let bool_body = function_body(&ir, "bool_or_default");
+        assert_eq!(bool_body.matches("@get_bool()").count(), 1, "{ir}");
+        assert!(!bool_body.contains("icmp"), "{ir}");
+        assert!(bool_body.contains("phi i1"), "{ir}");
+
+        // TEST: a pointer is compared against null
+        let ptr_body = function_body(&ir, "ptr_or_default");
+        assert_eq!(ptr_body.matches("@get_ptr()").count(), 1, "{ir}");
+        assert!(ptr_body.contains("%nonzero = icmp ne ptr"), "{ir}");
+        assert!(ptr_body.contains("phi ptr"), "{ir}");
+    }
+
+    #[test]
+    fn match_expressions_evaluate_their_scrutinee_once() {
+        let source = indoc! {"
+            enum E { A: i32, B: i32 }
+            fn f() -> E;
+
+            fn test() -> i32 {
+                return match (f()) { A: x => x, B: y => y + 1 };
+            }
+        "};
+
+        // TEST: the scrutinee is stored once, and the switch and both bindings
+        // read it back
+        let ir = ir_of(source);
+        let body = function_body(&ir, "test");
+        assert_eq!(body.matches("@f()").count(), 1, "{ir}");
+        assert!(body.contains("%let___match_scrutinee__ = alloca"), "{ir}");
+        assert!(body.contains("%let_x = alloca i32"), "{ir}");
+        assert!(body.contains("%let_y = alloca i32"), "{ir}");
+    }
 }
@@ -5,6 +5,7 @@ use zrc_typeck::tast::expr::Place;
 
 use super::place::cg_place;
 use crate::{
+    align::{build_aligned_load, build_aligned_store},
     bb::{BasicBlockAnd, BasicBlockExt},
     expr::CgExprArgs,
     ty::{llvm_basic_type, llvm_int_type},
@@ -24,10 +25,13 @@ pub fn cg_prefix_increment<'ctx, 'input>(
     let place_ptr = unpack!(bb = cg_place(cg, bb, place));
 
     // Load current value
-    let current = cg
-        .builder
-        .build_load(llvm_basic_type(&cg, &inferred_type).0, place_ptr, "load")
-        .expect("prefix increment load should have compiled successfully");
+    let current = build_aligned_load(
+        &cg,
+        llvm_basic_type(&cg, &inferred_type).0,
+        place_ptr,
+        "load",
+    )
+    .expect("prefix increment load should have compiled successfully");
 
     // Add 1
     let one = llvm_int_type(&cg, &inferred_type).0.const_int(1, false);
@@ -37,8 +41,7 @@ pub fn cg_prefix_increment<'ctx, 'input>(
         .expect("prefix increment add should have compiled successfully");
 
     // Store back
-    cg.builder
-        .build_store(place_ptr, new_value)
+    build_aligned_store(&cg, place_ptr, new_value)
         .expect("prefix increment store should have compiled successfully");
 
     // Return new value
@@ -58,10 +61,13 @@ pub fn cg_prefix_decrement<'ctx, 'input>(
     let place_ptr = unpack!(bb = cg_place(cg, bb, place));
 
     // Load current value
-    let current = cg
-        .builder
-        .build_load(llvm_basic_type(&cg, &inferred_type).0, place_ptr, "load")
-        .expect("prefix decrement load should have compiled successfully");
+    let current = build_aligned_load(
+        &cg,
+        llvm_basic_type(&cg, &inferred_type).0,
+        place_ptr,
+        "load",
+    )
+    .expect("prefix decrement load should have compiled successfully");
 
     // Subtract 1
     let one = llvm_int_type(&cg, &inferred_type).0.const_int(1, false);
@@ -71,8 +77,7 @@ pub fn cg_prefix_decrement<'ctx, 'input>(
         .expect("prefix decrement sub should have compiled successfully");
 
     // Store back
-    cg.builder
-        .build_store(place_ptr, new_value)
+    build_aligned_store(&cg, place_ptr, new_value)
         .expect("prefix decrement store should have compiled successfully");
 
     // Return new value
@@ -92,10 +97,13 @@ pub fn cg_postfix_increment<'ctx, 'input>(
     let place_ptr = unpack!(bb = cg_place(cg, bb, place));
 
     // Load current value
-    let current = cg
-        .builder
-        .build_load(llvm_basic_type(&cg, &inferred_type).0, place_ptr, "load")
-        .expect("postfix increment load should have compiled successfully");
+    let current = build_aligned_load(
+        &cg,
+        llvm_basic_type(&cg, &inferred_type).0,
+        place_ptr,
+        "load",
+    )
+    .expect("postfix increment load should have compiled successfully");
 
     // Add 1
     let one = llvm_int_type(&cg, &inferred_type).0.const_int(1, false);
@@ -105,8 +113,7 @@ pub fn cg_postfix_increment<'ctx, 'input>(
         .expect("postfix increment add should have compiled successfully");
 
     // Store back
-    cg.builder
-        .build_store(place_ptr, new_value)
+    build_aligned_store(&cg, place_ptr, new_value)
         .expect("postfix increment store should have compiled successfully");
 
     // Return old value
@@ -126,10 +133,13 @@ pub fn cg_postfix_decrement<'ctx, 'input>(
     let place_ptr = unpack!(bb = cg_place(cg, bb, place));
 
     // Load current value
-    let current = cg
-        .builder
-        .build_load(llvm_basic_type(&cg, &inferred_type).0, place_ptr, "load")
-        .expect("postfix decrement load should have compiled successfully");
+    let current = build_aligned_load(
+        &cg,
+        llvm_basic_type(&cg, &inferred_type).0,
+        place_ptr,
+        "load",
+    )
+    .expect("postfix decrement load should have compiled successfully");
 
     // Subtract 1
     let one = llvm_int_type(&cg, &inferred_type).0.const_int(1, false);
@@ -139,8 +149,7 @@ pub fn cg_postfix_decrement<'ctx, 'input>(
         .expect("postfix decrement sub should have compiled successfully");
 
     // Store back
-    cg.builder
-        .build_store(place_ptr, new_value)
+    build_aligned_store(&cg, place_ptr, new_value)
         .expect("postfix decrement store should have compiled successfully");
 
     // Return old value
@@ -1,8 +1,10 @@
 //! code generation for literal expressions
 
 use inkwell::{
-    types::StringRadix,
-    values::{BasicValue, BasicValueEnum},
+    IntPredicate,
+    basic_block::BasicBlock,
+    types::{BasicTypeEnum, StringRadix},
+    values::{BasicValue, BasicValueEnum, IntValue, PointerValue},
 };
 use zrc_typeck::tast::{
     expr::{NumberLiteral, Place, PlaceKind, StringTok, TypedExpr, ZrcString},
@@ -12,7 +14,9 @@ use zrc_utils::span::Spannable;
 
 use super::{cg_expr, place::cg_place};
 use crate::{
+    align::{abi_alignment, build_aligned_load, build_aligned_store},
     bb::{BasicBlockAnd, BasicBlockExt},
+    ctx::BlockCtx,
     expr::CgExprArgs,
     ty::{llvm_basic_type, llvm_int_type},
     unpack,
@@ -23,6 +27,7 @@ const fn number_literal_radix(n: &NumberLiteral) -> StringRadix {
     match n {
         NumberLiteral::Decimal(_) => StringRadix::Decimal,
         NumberLiteral::Binary(_) => StringRadix::Binary,
+        NumberLiteral::Octal(_) => StringRadix::Octal,
         NumberLiteral::Hexadecimal(_) => StringRadix::Hexadecimal,
     }
 }
@@ -108,9 +113,7 @@ pub fn cg_identifier<'ctx, 'input>(
         )
     );
 
-    let reg = cg
-        .builder
-        .build_load(llvm_basic_type(&cg, &inferred_type).0, place, "load")
+    let reg = build_aligned_load(&cg, llvm_basic_type(&cg, &inferred_type).0, place, "load")
         .expect("ident load should have built successfully");
 
     bb.and(reg.as_basic_value_enum())
@@ -175,19 +178,147 @@ pub fn cg_array_literal<'ctx, 'input>(
         }
         .expect("GEP should succeed");
 
-        cg.builder
-            .build_store(elem_ptr, elem_value)
-            .expect("store should succeed");
+        build_aligned_store(&cg, elem_ptr, elem_value).expect("store should succeed");
     }
 
     // Return the array value (loaded from alloca)
-    let array_value = cg
+    let array_value = build_aligned_load(&cg, array_type, array_alloca, "array_value")
+        .expect("load should succeed");
+
+    bb.and(array_value.as_basic_value_enum())
+}
+/// The byte to `memset` an array with so that each element holds `value`, if
+/// every byte of `value` is the same: a single byte, or any constant zero
+#[expect(clippy::wildcard_enum_match_arm)]
+fn fill_byte<'ctx>(
+    cg: BlockCtx<'ctx, '_, '_>,
+    value: BasicValueEnum<'ctx>,
+) -> Option<IntValue<'ctx>> {
+    let is_zero = match value {
+        BasicValueEnum::IntValue(int) if int.get_type().get_bit_width() == 8 => return Some(int),
+        BasicValueEnum::IntValue(int) => int.is_null(),
+        BasicValueEnum::PointerValue(ptr) => ptr.is_null(),
+        BasicValueEnum::ArrayValue(array) => array.is_null(),
+        BasicValueEnum::StructValue(structure) => structure.is_null(),
+        _ => false,
+    };
+
+    is_zero.then(|| cg.ctx.i8_type().const_zero())
+}
+
+/// Store `value` into each of the `count` elements of the array at `array`
+/// with a loop, returning the block after the loop
+fn cg_fill_loop<'ctx>(
+    cg: BlockCtx<'ctx, '_, '_>,
+    bb: BasicBlock<'ctx>,
+    array_type: BasicTypeEnum<'ctx>,
+    array: PointerValue<'ctx>,
+    value: BasicValueEnum<'ctx>,
+    count: u64,
+) -> BasicBlock<'ctx> {
+    let index_type = cg.ctx.i64_type();
+    let header = cg.ctx.append_basic_block(cg.fn_value, "repeat_header");
+    let body = cg.ctx.append_basic_block(cg.fn_value, "repeat_body");
+    let end = cg.ctx.append_basic_block(cg.fn_value, "repeat_end");
+
+    cg.builder
+        .build_unconditional_branch(header)
+        .expect("branch should succeed");
+
+    cg.builder.position_at_end(header);
+    let index = cg
+        .builder
+        .build_phi(index_type, "repeat_index")
+        .expect("phi should succeed");
+    let index_value = index.as_basic_value().into_int_value();
+    let done = cg
+        .builder
+        .build_int_compare(
+            IntPredicate::EQ,
+            index_value,
+            index_type.const_int(count, false),
+            "repeat_done",
+        )
+        .expect("comparison should succeed");
+    cg.builder
+        .build_conditional_branch(done, end, body)
+        .expect("branch should succeed");
+
+    cg.builder.position_at_end(body);
+    // SAFETY: the loop only runs while `index` is less than `count`, the length
+    // of the array
+    let elem_ptr = unsafe {
+        cg.builder.build_gep(
+            array_type,
+            array,
+            &[index_type.const_zero(), index_value],
+            "repeat_elem_ptr",
+        )
+    }
+    .expect("GEP should succeed");
+    build_aligned_store(&cg, elem_ptr, value).expect("store should succeed");
+    let next = cg
+        .builder
+        .build_int_add(index_value, index_type.const_int(1, false), "repeat_next")
+        .expect("add should succeed");
+    cg.builder
+        .build_unconditional_branch(header)
+        .expect("branch should succeed");
+
+    index.add_incoming(&[(&index_type.const_zero(), bb), (&next, body)]);
+
+    cg.builder.position_at_end(end);
+    end
+}
+
+/// Code generate an array repeat expression like `[0; 8]`.
+///
+/// `value` is evaluated once. If every byte of it is the same, the array is
+/// filled with a `memset`, otherwise it is stored into each element in a loop.
+pub fn cg_array_repeat<'ctx, 'input>(
+    ce: CgExprArgs<'ctx, 'input, '_>,
+    value: Box<TypedExpr<'input>>,
+    count: u64,
+) -> BasicBlockAnd<'ctx, BasicValueEnum<'ctx>> {
+    let CgExprArgs {
+        cg,
+        mut bb,
+        inferred_type,
+        ..
+    } = ce;
+
+    let array_type = llvm_basic_type(&cg, &inferred_type).0;
+    let array_alloca = cg
         .builder
-        .build_load(array_type, array_alloca, "array_value")
+        .build_alloca(array_type, "array_repeat")
+        .expect("alloca should succeed");
+
+    let value = unpack!(bb = cg_expr(cg, bb, *value));
+
+    if let Some(byte) = fill_byte(cg, value) {
+        cg.builder
+            .build_memset(
+                array_alloca,
+                abi_alignment(&cg, &array_type),
+                byte,
+                cg.ctx.i64_type().const_int(
+                    cg.target_machine
+                        .get_target_data()
+                        .get_abi_size(&array_type),
+                    false,
+                ),
+            )
+            .expect("memset should succeed");
+    } else {
+        bb = cg_fill_loop(cg, bb, array_type, array_alloca, value, count);
+    }
+
+    let array_value = build_aligned_load(&cg, array_type, array_alloca, "array_value")
         .expect("load should succeed");
 
     bb.and(array_value.as_basic_value_enum())
 }
+
 #[cfg(test)]
 mod tests {
     // Please read the "Common patterns in tests" section of crate::test_utils for
@@ -195,7 +326,7 @@ mod tests {
 
     use indoc::indoc;
 
-    use crate::cg_snapshot_test;
+    use crate::{cg_snapshot_test, test_utils::ir_of};
 
     #[test]
     fn typed_integers_generate_properly() {
@@ -217,6 +348,25 @@ mod tests {
             "#});
     }
 
+    #[test]
+    fn adjacent_string_literals_are_one_global() {
+        let ir = ir_of(indoc! {r#"
+            fn puts(s: *u8);
+
+            fn test() {
+                puts("foo" "bar");
+            }
+        "#});
+
+        // TEST: a single global holds the joined string
+        assert!(ir.contains(r#"[7 x i8] c"foobar\00""#), "{ir}");
+        assert_eq!(
+            ir.matches("private unnamed_addr constant").count(),
+            1,
+            "{ir}"
+        );
+    }
+
     /// Tests to ensure non-decimal integer literals
     /// 1. don't panic
     /// 2. are valid.
@@ -231,4 +381,75 @@ mod tests {
                 }
             "});
     }
+
+    #[test]
+    fn zero_array_repeats_are_memset() {
+        let ir = ir_of(indoc! {"
+            fn take(x: [8]i32);
+
+            fn test() {
+                take([0; 8]);
+            }
+        "});
+
+        // TEST: the whole array is zeroed at once, without a loop
+        assert!(
+            ir.contains(
+                "call void @llvm.memset.p0.i64(ptr align 4 %array_repeat, i8 0, i64 32, i1 false)"
+            ),
+            "{ir}"
+        );
+        assert!(!ir.contains("repeat_header"), "{ir}");
+    }
+
+    #[test]
+    fn other_array_repeats_are_stored_in_a_loop() {
+        let ir = ir_of(indoc! {"
+            fn f() -> i32;
+            fn take(x: [4]i32);
+
+            fn test() {
+                take([f(); 4]);
+            }
+        "});
+
+        // TEST: `f` is only called once, and its result is stored into each
+        // element in a loop
+        assert_eq!(ir.matches("call i32 @f()").count(), 1, "{ir}");
+        assert!(ir.contains("icmp eq i64 %repeat_index, 4"), "{ir}");
+        assert!(ir.contains("store i32 %call, ptr %repeat_elem_ptr"), "{ir}");
+        assert!(!ir.contains("memset"), "{ir}");
+    }
+
+    #[test]
+    fn negated_literal_elements_are_stored_at_the_element_width() {
+        let ir = ir_of(indoc! {"
+            fn test() {
+                let a: [2]i8 = [-1, 2];
+                let b: [2]i64 = [-1, 2];
+            }
+        "});
+
+        // TEST: `-1` is negated in the element type, so each store writes exactly
+        // one element
+        assert!(ir.contains("store i8 -1, ptr %array_elem_ptr"), "{ir}");
+        assert!(ir.contains("store i64 -1, ptr %array_elem_ptr"), "{ir}");
+        assert!(!ir.contains("store i32"), "{ir}");
+    }
+
+    #[test]
+    fn negated_repeated_values_are_stored_at_the_element_width() {
+        let ir = ir_of(indoc! {"
+            fn test() {
+                let a: [4]i8 = [-1; 4];
+                let b: [4]i64 = [-1; 4];
+            }
+        "});
+
+        // TEST: the repeated `-1` is negated in the element type, so the loop
+        // stores exactly one element at a time
+        assert!(ir.contains("store i8 -1, ptr %repeat_elem_ptr"), "{ir}");
+        assert!(ir.contains("store i64 -1, ptr %repeat_elem_ptr"), "{ir}");
+        assert!(!ir.contains("store i32"), "{ir}");
+    }
 }
@@ -2,12 +2,13 @@
 
 use inkwell::{
     IntPredicate,
-    values::{BasicValue, BasicValueEnum},
+    values::{BasicValue, BasicValueEnum, IntValue},
 };
 use zrc_typeck::tast::expr::{Comparison, Equality, Logical, TypedExpr};
 
 use crate::{
     bb::{BasicBlockAnd, BasicBlockExt},
+    ctx::BlockCtx,
     expr::{CgExprArgs, cg_expr},
     unpack,
 };
@@ -43,8 +44,24 @@ pub fn cg_equality<'ctx, 'input>(
     let lhs = unpack!(bb = cg_expr(cg, bb, *lhs));
     let rhs = unpack!(bb = cg_expr(cg, bb, *rhs));
 
-    // For pointer comparisons, convert pointers to integers first
-    let (lhs_int, rhs_int) = if lhs.is_pointer_value() && rhs.is_pointer_value() {
+    let (lhs_int, rhs_int) = operands_as_ints(cg, lhs, rhs);
+
+    let reg = cg
+        .builder
+        .build_int_compare(int_predicate_for_equality(op), lhs_int, rhs_int, "cmp")
+        .expect("equality comparison should have compiled successfully");
+
+    bb.and(reg.as_basic_value_enum())
+}
+
+/// Get the operands of a comparison as integers. Pointers are converted to the
+/// integer value of their address first.
+fn operands_as_ints<'ctx>(
+    cg: BlockCtx<'ctx, '_, '_>,
+    lhs: BasicValueEnum<'ctx>,
+    rhs: BasicValueEnum<'ctx>,
+) -> (IntValue<'ctx>, IntValue<'ctx>) {
+    if lhs.is_pointer_value() && rhs.is_pointer_value() {
         let target_data = cg.target_machine.get_target_data();
         let ptr_sized_int = cg.ctx.ptr_sized_int_type(&target_data, None);
         let lhs_int = cg
@@ -58,37 +75,31 @@ pub fn cg_equality<'ctx, 'input>(
         (lhs_int, rhs_int)
     } else {
         (lhs.into_int_value(), rhs.into_int_value())
-    };
-
-    let reg = cg
-        .builder
-        .build_int_compare(int_predicate_for_equality(op), lhs_int, rhs_int, "cmp")
-        .expect("equality comparison should have compiled successfully");
-
-    bb.and(reg.as_basic_value_enum())
+    }
 }
 
 /// Code generate a comparison expression
 pub fn cg_comparison<'ctx, 'input>(
-    CgExprArgs {
-        cg,
-        mut bb,
-        inferred_type,
-        ..
-    }: CgExprArgs<'ctx, 'input, '_>,
+    CgExprArgs { cg, mut bb, .. }: CgExprArgs<'ctx, 'input, '_>,
     op: Comparison,
     lhs: Box<TypedExpr<'input>>,
     rhs: Box<TypedExpr<'input>>,
 ) -> BasicBlockAnd<'ctx, BasicValueEnum<'ctx>> {
+    // the result is a bool, so the signedness comes from the operands. Pointer
+    // addresses are unsigned.
+    let signed = lhs.inferred_type.is_signed_integer();
+
     let lhs = unpack!(bb = cg_expr(cg, bb, *lhs));
     let rhs = unpack!(bb = cg_expr(cg, bb, *rhs));
 
+    let (lhs_int, rhs_int) = operands_as_ints(cg, lhs, rhs);
+
     let reg = cg
         .builder
         .build_int_compare(
-            int_predicate_for_comparison(op, inferred_type.is_signed_integer()),
-            lhs.into_int_value(),
-            rhs.into_int_value(),
+            int_predicate_for_comparison(op, signed),
+            lhs_int,
+            rhs_int,
             "cmp",
         )
         .expect("comparison should have compiled successfully");
@@ -235,7 +246,10 @@ mod tests {
 
     use indoc::indoc;
 
-    use crate::cg_snapshot_test;
+    use crate::{
+        cg_snapshot_test,
+        test_utils::{function_body, ir_of},
+    };
 
     #[test]
     fn equality_operators_generate() {
@@ -293,4 +307,53 @@ mod tests {
                 }
             "});
     }
+
+    #[test]
+    fn pointer_ordering_compares_addresses_as_unsigned_integers() {
+        let source = indoc! {"
+            fn before(p: *u8, q: *u8) -> bool { return p < q; }
+            fn not_before(p: *u8, q: *u8) -> bool { return p >= q; }
+            fn less(a: i32, b: i32) -> bool { return a < b; }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: both pointers are converted to their addresses, which are then
+        // compared unsigned
+        assert_eq!(ir.matches("ptrtoint ptr").count(), 4, "{ir}");
+        assert!(ir.contains(" = icmp ult i"), "{ir}");
+        assert!(ir.contains(" = icmp uge i"), "{ir}");
+
+        // TEST: signed integers are still compared signed
+        assert!(ir.contains(" = icmp slt i32"), "{ir}");
+    }
+
+    #[test]
+    fn ordering_comparisons_follow_the_signedness_of_their_operands() {
+        let source = indoc! {"
+            fn signed(a: i32, b: i32) -> bool {
+                return a < b && a > b && a <= b && a >= b;
+            }
+            fn unsigned(a: u32, b: u32) -> bool {
+                return a < b && a > b && a <= b && a >= b;
+            }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: signed operands use the signed predicates, even though the
+        // result of the comparison is an unsigned `bool`
+        let signed = function_body(&ir, "signed");
+        for predicate in ["slt", "sgt", "sle", "sge"] {
+            assert!(signed.contains(&format!("icmp {predicate} i32")), "{ir}");
+        }
+        assert!(!signed.contains("icmp u"), "{ir}");
+
+        // TEST: unsigned operands use the unsigned predicates
+        let unsigned = function_body(&ir, "unsigned");
+        for predicate in ["ult", "ugt", "ule", "uge"] {
+            assert!(unsigned.contains(&format!("icmp {predicate} i32")), "{ir}");
+        }
+        assert!(!unsigned.contains("icmp s"), "{ir}");
+    }
 }
@@ -6,6 +6,7 @@ use zrc_utils::span::{Spannable, Spanned};
 
 use super::place::cg_place;
 use crate::{
+    align::{build_aligned_load, build_aligned_store},
     bb::{BasicBlockAnd, BasicBlockExt},
     expr::{CgExprArgs, cg_expr},
     ty::llvm_basic_type,
@@ -34,9 +35,7 @@ pub fn cg_index<'ctx, 'input>(
         )
     );
 
-    let loaded = cg
-        .builder
-        .build_load(llvm_basic_type(&cg, &inferred_type).0, ptr, "load")
+    let loaded = build_aligned_load(&cg, llvm_basic_type(&cg, &inferred_type).0, ptr, "load")
         .expect("index load should have compiled successfully");
 
     bb.and(loaded.as_basic_value_enum())
@@ -64,9 +63,7 @@ pub fn cg_dot<'ctx, 'input>(
         )
     );
 
-    let loaded = cg
-        .builder
-        .build_load(llvm_basic_type(&cg, &inferred_type).0, ptr, "load")
+    let loaded = build_aligned_load(&cg, llvm_basic_type(&cg, &inferred_type).0, ptr, "load")
         .expect("dot load should have compiled successfully");
 
     bb.and(loaded.as_basic_value_enum())
@@ -84,14 +81,13 @@ pub fn cg_deref<'ctx, 'input>(
 ) -> BasicBlockAnd<'ctx, BasicValueEnum<'ctx>> {
     let ptr = unpack!(bb = cg_expr(cg, bb, *ptr));
 
-    let reg = cg
-        .builder
-        .build_load(
-            llvm_basic_type(&cg, &inferred_type).0,
-            ptr.into_pointer_value(),
-            "load",
-        )
-        .expect("dereference should have compiled successfully");
+    let reg = build_aligned_load(
+        &cg,
+        llvm_basic_type(&cg, &inferred_type).0,
+        ptr.into_pointer_value(),
+        "load",
+    )
+    .expect("dereference should have compiled successfully");
 
     bb.and(reg.as_basic_value_enum())
 }
@@ -105,8 +101,7 @@ pub fn cg_assignment<'ctx, 'input>(
     let value = unpack!(bb = cg_expr(cg, bb, *value));
     let place = unpack!(bb = cg_place(cg, bb, place));
 
-    cg.builder
-        .build_store(place, value)
+    build_aligned_store(&cg, place, value)
         .expect("store instruction in assignment should have built successfully");
 
     bb.and(value)
@@ -193,4 +188,16 @@ mod tests {
                 }
             "});
     }
+    #[test]
+    fn stores_use_abi_alignment() {
+        cg_snapshot_test!(indoc! {"
+                fn test() -> usize {
+                    let x: i64 = 1;
+
+                    // TEST: both `i64` stores must be `align 8`, matching `alignof_expr`
+                    x = 2;
+                    return alignof_expr(x);
+                }
+            "});
+    }
 }
@@ -1,16 +1,17 @@
 //! code generation for misc expressions
 
 use inkwell::{
-    types::BasicType,
+    intrinsics::Intrinsic,
     values::{BasicValue, BasicValueEnum},
 };
 use zrc_typeck::tast::{
-    expr::TypedExpr,
+    expr::{IntegerConversion, Rotation, TypedExpr},
     ty::{OrderedValueFields, Type},
 };
 use zrc_utils::span::Spanned;
 
 use crate::{
+    align::{abi_alignment, build_aligned_load, build_aligned_store},
     bb::{BasicBlockAnd, BasicBlockExt},
     expr::{CgExprArgs, cg_expr},
     ty::{llvm_basic_type, llvm_int_type},
@@ -33,11 +34,26 @@ pub fn cg_cast<'ctx, 'input>(
     // ptr -> int = ptrtoint
     // int -> fn = inttoptr
     // fn -> int = ptrtoint
+    // struct -> struct = no-op
+    // enum -> int = extractvalue of the tag, then cast as the tag's integer type
 
-    let x_ty_is_signed_integer = x.inferred_type.is_signed_integer();
+    let x_ty_is_signed_integer = x.inferred_type.is_signed_integer()
+        || x.inferred_type
+            .enum_tag_type()
+            .is_some_and(Type::is_signed_integer);
+    let x_ty_is_enum = x.inferred_type.enum_variants().is_some();
 
     let x = unpack!(bb = cg_expr(cg, bb, *x));
 
+    // the tag is the first field of an enum's struct, and is an integer
+    let x = if x_ty_is_enum && ty.value().is_integer() {
+        cg.builder
+            .build_extract_value(x.into_struct_value(), 0, "tag")
+            .expect("extractvalue should have compiled successfully")
+    } else {
+        x
+    };
+
     let reg = match (
         x.get_type().is_pointer_type(),
         matches!(ty.value(), Type::Ptr(_)),
@@ -68,6 +84,9 @@ pub fn cg_cast<'ctx, 'input>(
             )
             .expect("inttoptr should have compiled successfully")
             .as_basic_value_enum(),
+        // the type checker only allows struct casts between identical layouts, which
+        // share a single LLVM type, so the value can be reused as-is
+        (false, false) if x.get_type().is_struct_type() => x,
         (false, false) if x.get_type().is_int_type() && ty.value().is_integer() => {
             // Cast between two integers
             let src_int = x.into_int_value();
@@ -119,15 +138,104 @@ pub fn cg_cast<'ctx, 'input>(
     bb.and(reg)
 }
 
+/// Generate LLVM IR for a `truncate`, `sign_extend` or `zero_extend` call
+pub fn cg_integer_conversion<'ctx, 'input>(
+    CgExprArgs { cg, mut bb, .. }: CgExprArgs<'ctx, 'input, '_>,
+    conversion: IntegerConversion,
+    x: Box<TypedExpr<'input>>,
+    ty: &Spanned<Type<'input>>,
+) -> BasicBlockAnd<'ctx, BasicValueEnum<'ctx>> {
+    let x = unpack!(bb = cg_expr(cg, bb, *x)).into_int_value();
+    let target_int_type = llvm_int_type(&cg, ty.value()).0;
+
+    // the type checker guarantees the widths move in the right direction
+    let reg = match conversion {
+        IntegerConversion::Truncate => cg
+            .builder
+            .build_int_truncate(x, target_int_type, "trunc")
+            .expect("trunc should have compiled successfully"),
+        IntegerConversion::SignExtend => cg
+            .builder
+            .build_int_s_extend(x, target_int_type, "sext")
+            .expect("sext should have compiled successfully"),
+        IntegerConversion::ZeroExtend => cg
+            .builder
+            .build_int_z_extend(x, target_int_type, "zext")
+            .expect("zext should have compiled successfully"),
+    };
+
+    bb.and(reg.as_basic_value_enum())
+}
+
+/// Generate LLVM IR for a `rotate_left` or `rotate_right` call
+///
+/// A rotate is a funnel shift of `x` concatenated with itself, so this calls
+/// `llvm.fshl` or `llvm.fshr` with `x` as both of its operands.
+pub fn cg_rotate<'ctx, 'input>(
+    CgExprArgs { cg, mut bb, .. }: CgExprArgs<'ctx, 'input, '_>,
+    rotation: Rotation,
+    x: Box<TypedExpr<'input>>,
+    amount: Box<TypedExpr<'input>>,
+) -> BasicBlockAnd<'ctx, BasicValueEnum<'ctx>> {
+    let x = unpack!(bb = cg_expr(cg, bb, *x)).into_int_value();
+    let amount = unpack!(bb = cg_expr(cg, bb, *amount)).into_int_value();
+
+    // funnel shifts take all operands in one type and the amount modulo the bit
+    // width, so the amount can be zero extended or truncated to the width of `x`
+    let amount = cg
+        .builder
+        .build_int_cast_sign_flag(amount, x.get_type(), false, "rotate_amount")
+        .expect("int cast should have compiled successfully");
+
+    let intrinsic = Intrinsic::find(match rotation {
+        Rotation::Left => "llvm.fshl",
+        Rotation::Right => "llvm.fshr",
+    })
+    .expect("funnel shift intrinsics should exist");
+    let function = intrinsic
+        .get_declaration(cg.module, &[x.get_type().into()])
+        .expect("funnel shift intrinsics should be overloaded on integer types");
+
+    let reg = cg
+        .builder
+        .build_call(function, &[x.into(), x.into(), amount.into()], "rotate")
+        .expect("call should have compiled successfully")
+        .try_as_basic_value()
+        .expect_basic("funnel shifts should return an integer");
+
+    bb.and(reg)
+}
+
 /// Generate LLVM IR for a sizeof expression
 pub fn cg_size_of<'ctx, 'input>(
     CgExprArgs { cg, bb, .. }: CgExprArgs<'ctx, 'input, '_>,
     ty: &Type<'input>,
 ) -> BasicBlockAnd<'ctx, BasicValueEnum<'ctx>> {
-    let reg = llvm_basic_type(&cg, ty)
+    // use the same ABI size that `--dump-types` and pointer arithmetic use
+    let size = cg
+        .target_machine
+        .get_target_data()
+        .get_abi_size(&llvm_basic_type(&cg, ty).0);
+
+    let reg = llvm_int_type(&cg, &Type::Usize)
         .0
-        .size_of()
-        .expect("size_of should have compiled successfully")
+        .const_int(size, false)
+        .as_basic_value_enum();
+
+    bb.and(reg)
+}
+
+/// Generate LLVM IR for an alignof expression
+pub fn cg_align_of<'ctx, 'input>(
+    CgExprArgs { cg, bb, .. }: CgExprArgs<'ctx, 'input, '_>,
+    ty: &Type<'input>,
+) -> BasicBlockAnd<'ctx, BasicValueEnum<'ctx>> {
+    // use the same ABI alignment that loads and stores of this type are built with
+    let alignment = abi_alignment(&cg, &llvm_basic_type(&cg, ty).0);
+
+    let reg = llvm_int_type(&cg, &Type::Usize)
+        .0
+        .const_int(u64::from(alignment), false)
         .as_basic_value_enum();
 
     bb.and(reg)
@@ -168,16 +276,13 @@ pub fn cg_struct_construction<'ctx, 'input>(
                         .expect("struct GEP should have compiled successfully");
 
                     // Store the value
-                    cg.builder
-                        .build_store(field_ptr, field_value)
+                    build_aligned_store(&cg, field_ptr, field_value)
                         .expect("store should have compiled successfully");
                 }
             }
 
             // Load the complete struct value
-            let reg = cg
-                .builder
-                .build_load(struct_type, struct_ptr, "struct_val")
+            let reg = build_aligned_load(&cg, struct_type, struct_ptr, "struct_val")
                 .expect("load should have compiled successfully");
 
             bb.and(reg)
@@ -212,8 +317,7 @@ pub fn cg_struct_construction<'ctx, 'input>(
                         .into_pointer_value();
 
                     // Store the value
-                    cg.builder
-                        .build_store(field_ptr, field_value)
+                    build_aligned_store(&cg, field_ptr, field_value)
                         .expect("store should have compiled successfully");
 
                     // Only initialize one field for a union
@@ -222,9 +326,7 @@ pub fn cg_struct_construction<'ctx, 'input>(
             }
 
             // Load the complete union value
-            let reg = cg
-                .builder
-                .build_load(union_type, union_ptr, "union_val")
+            let reg = build_aligned_load(&cg, union_type, union_ptr, "union_val")
                 .expect("load should have compiled successfully");
 
             bb.and(reg)
@@ -257,7 +359,10 @@ mod tests {
 
     use indoc::indoc;
 
-    use crate::cg_snapshot_test;
+    use crate::{
+        cg_snapshot_test,
+        test_utils::{function_body, ir_of},
+    };
 
     #[test]
     fn cast_truncation_generates_properly() {
@@ -318,4 +423,113 @@ mod tests {
             }
         "});
     }
+
+    #[test]
+    fn integer_conversion_builtins_generate_properly() {
+        cg_snapshot_test!(indoc! {"
+            fn test_truncate() -> u8 {
+                let x: i32 = 65;
+                return truncate(x, u8);
+            }
+
+            fn test_sign_extend() -> i64 {
+                let x: i8 = 1i8;
+                return sign_extend(x, i64);
+            }
+
+            fn test_zero_extend() -> u32 {
+                let x: u8 = 255u8;
+                return zero_extend(x, u32);
+            }
+        "});
+    }
+
+    #[test]
+    fn rotates_are_funnel_shifts_of_a_value_with_itself() {
+        let source = indoc! {"
+            fn rotl(x: u32, n: u32) -> u32 { return rotate_left(x, n); }
+            fn rotr(x: u64, n: u8) -> u64 { return rotate_right(x, n); }
+        "};
+
+        let ir = ir_of(source);
+
+        // the operands of the call to `intrinsic`
+        let operands_of = |intrinsic: &str| -> Vec<String> {
+            let call = ir
+                .lines()
+                .find_map(|line| line.split_once(&format!("call {intrinsic}(")))
+                .unwrap_or_else(|| panic!("`{intrinsic}` should be called in:\n{ir}"))
+                .1;
+            call.trim_end_matches(')')
+                .split(", ")
+                .map(str::to_string)
+                .collect()
+        };
+
+        // TEST: `rotate_left` is `llvm.fshl` with `x` as both halves
+        let operands = operands_of("i32 @llvm.fshl.i32");
+        assert_eq!(operands.len(), 3, "{ir}");
+        assert_eq!(operands[0], operands[1], "{ir}");
+
+        // TEST: `rotate_right` is `llvm.fshr`, and its narrower amount is zero
+        // extended to the width of `x`
+        let operands = operands_of("i64 @llvm.fshr.i64");
+        assert_eq!(operands.len(), 3, "{ir}");
+        assert_eq!(operands[0], operands[1], "{ir}");
+        assert_eq!(operands[2], "i64 %rotate_amount", "{ir}");
+        assert!(ir.contains("%rotate_amount = zext i8"), "{ir}");
+    }
+
+    #[test]
+    fn enum_tags_with_negative_discriminants_are_sign_extended() {
+        let source = indoc! {"
+            enum Level { Low: u8 = -1, High: u8 = 1 }
+            fn tag(l: Level) -> i32 { return l as i32; }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: the tag of `Level` is an `i8`, which holds -1 and is sign extended
+        assert!(ir.contains("sext i8 %tag"), "{ir}");
+    }
+
+    #[test]
+    fn casts_between_signedness_of_one_width_are_no_ops() {
+        let source = indoc! {"
+            fn to_unsigned(x: i32) -> u32 { return x as u32; }
+            fn to_signed(x: u64) -> i64 { return x as i64; }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: LLVM integers have no sign, so the value is returned unchanged
+        for instruction in ["sext", "zext", "trunc", "bitcast"] {
+            assert!(!ir.contains(instruction), "{ir}");
+        }
+    }
+
+    #[test]
+    fn sizeof_function_and_array_types_uses_their_abi_size() {
+        let source = indoc! {"
+            fn function_size() -> usize { return sizeof fn(x: i32) -> i32; }
+            fn function_pointer_size() -> usize { return sizeof *(fn(x: i32) -> i32); }
+            fn array_size() -> usize { return sizeof [4]i32; }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: a function type has the size of a pointer, like a pointer to a
+        // function, which is 8 bytes on a 64-bit target
+        assert!(
+            function_body(&ir, "function_size").contains("i64 8"),
+            "{ir}"
+        );
+        assert!(
+            function_body(&ir, "function_pointer_size").contains("i64 8"),
+            "{ir}"
+        );
+
+        // TEST: `[4]i32` is four 4-byte integers
+        assert!(function_body(&ir, "array_size").contains("i64 16"), "{ir}");
+    }
 }
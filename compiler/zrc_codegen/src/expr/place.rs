@@ -110,6 +110,8 @@ pub fn cg_place<'ctx>(
             Type::Union(_) => {
                 // All we need to do is cast the pointer, but there's no `bitcast` anymore,
                 // so just return it and it'll take on the correct type
+                // The accesses through this pointer must not have `!tbaa`, as reading a
+                // field other than the one last written is how unions are type punned
 
                 let value = unpack!(bb = cg_place(cg, bb, *x));
 
@@ -127,7 +129,7 @@ mod tests {
 
     use indoc::indoc;
 
-    use crate::cg_snapshot_test;
+    use crate::{cg_snapshot_test, test_utils::ir_of};
 
     // Remember: In all of these tests, cg_place returns a *pointer* to the data in
     // the place.
@@ -187,6 +189,18 @@ mod tests {
             "});
     }
 
+    #[test]
+    fn nested_array_indexing_in_place_position() {
+        cg_snapshot_test!(indoc! {"
+                fn test() {
+                    let m: [2][3]i32;
+
+                    // TEST: `m[1]` GEPs to the `[3 x i32]` row and `[2]` then GEPs to its `i32`
+                    m[1][2] = 5;
+                }
+            "});
+    }
+
     #[test]
     fn struct_property_access_in_place_position() {
         cg_snapshot_test!(indoc! {"
@@ -218,4 +232,29 @@ mod tests {
                 }
             "});
     }
+
+    #[test]
+    fn union_field_accesses_carry_no_aliasing_metadata() {
+        let source = indoc! {"
+            union U { x: i32, y: i8 }
+
+            fn low_byte(bits: i32) -> i8 {
+                let u: U;
+                u.x = bits;
+                return u.y;
+            }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: the `i32` store and the `i8` load go through the same pointer
+        assert!(ir.contains("store i32"), "{ir}");
+        assert!(ir.contains("load i8"), "{ir}");
+
+        // TEST: neither may be tagged in a way that lets the optimizer assume the two
+        // fields do not alias
+        for metadata in ["!tbaa", "!alias.scope", "!noalias"] {
+            assert!(!ir.contains(metadata), "{ir}");
+        }
+    }
 }
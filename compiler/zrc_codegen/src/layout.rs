@@ -0,0 +1,215 @@
+//! Reporting of type layouts on the target
+//!
+//! This is used by `--dump-types` to verify that the sizes and alignments of
+//! declared types match what other code compiled for the same target expects,
+//! and by `--dump-llvm-types` to show the LLVM type each Zirco type is lowered
+//! to.
+
+use inkwell::{
+    context::Context,
+    targets::{CodeModel, InitializationConfig, RelocMode, Target, TargetTriple},
+};
+use zrc_typeck::tast::ty::Type;
+use zrc_utils::line_finder::LineLookup;
+
+use crate::{
+    OptimizationLevel, align::abi_alignment, ctx::CompilationUnitCtx, ty::llvm_basic_type,
+};
+
+/// The size and alignment of a named type on the target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeLayout {
+    /// The name the type was declared with
+    pub name: String,
+    /// The ABI size of the type, in bytes
+    pub size: u64,
+    /// The ABI alignment of the type, in bytes
+    pub align: u32,
+}
+
+/// Run `f` with a [`CompilationUnitCtx`] for the given target, in which types
+/// can be lowered
+///
+/// # Panics
+/// Panics if the target machine cannot be created.
+fn with_target_unit<T>(
+    triple: &TargetTriple,
+    cpu: &str,
+    f: impl FnOnce(&CompilationUnitCtx<'_, '_>) -> T,
+) -> T {
+    let ctx = Context::create();
+
+    Target::initialize_all(&InitializationConfig::default());
+    let target = Target::from_triple(triple).expect("target should be ready and exist");
+
+    let target_machine = target
+        .create_target_machine(
+            triple,
+            cpu,
+            "",
+            OptimizationLevel::None,
+            RelocMode::PIC,
+            CodeModel::Default,
+        )
+        .expect("target machine should be created successfully");
+
+    let builder = ctx.create_builder();
+    let module = ctx.create_module("layout");
+    let line_lookup = LineLookup::new("");
+
+    f(&CompilationUnitCtx {
+        ctx: &ctx,
+        target_machine: &target_machine,
+        builder: &builder,
+        line_lookup: &line_lookup,
+        dbg_builder: None,
+        compilation_unit: None,
+        module: &module,
+    })
+}
+
+/// Compute the [`TypeLayout`] of every named type on the given target, sorted
+/// by name
+///
+/// # Panics
+/// Panics if the target machine cannot be created.
+#[must_use]
+pub fn type_layouts<'a>(
+    types: impl IntoIterator<Item = (&'a str, &'a Type<'a>)>,
+    triple: &TargetTriple,
+    cpu: &str,
+) -> Vec<TypeLayout> {
+    with_target_unit(triple, cpu, |unit| {
+        let target_data = unit.target_machine.get_target_data();
+
+        let mut layouts = types
+            .into_iter()
+            .map(|(name, ty)| {
+                let (llvm_ty, _) = llvm_basic_type(unit, ty);
+
+                TypeLayout {
+                    name: name.to_string(),
+                    size: target_data.get_abi_size(&llvm_ty),
+                    align: abi_alignment(unit, &llvm_ty),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        layouts.sort_by(|a, b| a.name.cmp(&b.name));
+
+        layouts
+    })
+}
+
+/// Lower each of `types` on the given target, returning each type as it is
+/// written in Zirco paired with the LLVM type it is emitted as, in the order
+/// they were given in
+///
+/// # Panics
+/// Panics if the target machine cannot be created.
+#[must_use]
+pub fn llvm_type_names<'a>(
+    types: impl IntoIterator<Item = &'a Type<'a>>,
+    triple: &TargetTriple,
+    cpu: &str,
+) -> Vec<(String, String)> {
+    with_target_unit(triple, cpu, |unit| {
+        types
+            .into_iter()
+            .map(|ty| {
+                let (llvm_ty, _) = llvm_basic_type(unit, ty);
+                (ty.to_string(), llvm_ty.print_to_string().to_string())
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeLayout;
+
+    #[test]
+    fn struct_layouts_include_padding() {
+        let mut global_scope = zrc_typeck::typeck::GlobalScope::new();
+        zrc_typeck::typeck::type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program(
+                "struct Padded { a: u8, b: i32, c: u16 }\ntype Byte = u8;",
+                "<test>",
+            )
+            .expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        // TEST: primitives are not reported, and declared types are sorted by
+        // name with `Padded` padded out to the alignment of `i32`
+        assert_eq!(
+            super::type_layouts(
+                global_scope.types.iter_declared(),
+                &crate::get_native_triple(),
+                ""
+            ),
+            vec![
+                TypeLayout {
+                    name: "Byte".to_string(),
+                    size: 1,
+                    align: 1,
+                },
+                TypeLayout {
+                    name: "Padded".to_string(),
+                    size: 12,
+                    align: 4,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_array_size_is_the_product_of_its_dimensions() {
+        let mut global_scope = zrc_typeck::typeck::GlobalScope::new();
+        zrc_typeck::typeck::type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program("type Matrix = [2][3]i32;", "<test>")
+                .expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        assert_eq!(
+            super::type_layouts(
+                global_scope.types.iter_declared(),
+                &crate::get_native_triple(),
+                ""
+            ),
+            vec![TypeLayout {
+                name: "Matrix".to_string(),
+                size: 24,
+                align: 4,
+            }]
+        );
+    }
+
+    #[test]
+    fn structs_are_lowered_to_literal_struct_types() {
+        let program = zrc_typeck::typeck::type_program(
+            &mut zrc_typeck::typeck::GlobalScope::new(),
+            zrc_parser::parser::parse_program(
+                "struct P { x: i32, y: i32 }\nfn f(p: P) -> i32 { return p.x; }",
+                "<test>",
+            )
+            .expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+        let types = zrc_typeck::used_types::used_types(&program);
+
+        assert_eq!(
+            super::llvm_type_names(&types, &crate::get_native_triple(), ""),
+            vec![
+                (
+                    "struct { x: i32, y: i32 }".to_string(),
+                    "{ i32, i32 }".to_string()
+                ),
+                ("i32".to_string(), "i32".to_string()),
+            ]
+        );
+    }
+}
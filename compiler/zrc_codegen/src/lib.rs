@@ -59,12 +59,18 @@
 // Ordering matters! Declared here so other modules have access to `unpack!`
 mod bb;
 
-use inkwell::targets::TargetMachine;
+use std::sync::Once;
 
+use inkwell::{llvm_sys::support::LLVMParseCommandLineOptions, targets::TargetMachine};
+
+mod align;
 mod ctx;
 mod expr;
+mod layout;
+mod object_format;
 mod program;
 mod scope;
+mod split;
 mod stmt;
 #[cfg(test)]
 mod test_utils;
@@ -75,10 +81,35 @@ pub use inkwell::{
     debug_info::DWARFEmissionKind as DebugLevel,
     targets::{FileType, TargetTriple},
 };
-pub use program::{cg_program, cg_program_to_buffer, cg_program_to_string};
+pub use layout::{TypeLayout, llvm_type_names, type_layouts};
+pub use object_format::ObjectFormat;
+pub use program::{
+    CodegenOptions, FramePointer, Profile, cg_program, cg_program_to_buffer, cg_program_to_string,
+    cg_program_undefined_symbols, print_pass_pipeline, undefined_symbols,
+};
+pub use split::{SplitModule, split_llvm_ir_by_function};
 
 /// Gets the native [`TargetTriple`].
 #[must_use]
 pub fn get_native_triple() -> TargetTriple {
     TargetMachine::get_default_triple()
 }
+
+/// Enable LLVM's pass timing report, which is printed to stderr after the
+/// optimization passes run.
+///
+/// This sets a global LLVM option, so it affects every module compiled
+/// afterwards and should be called before code generation begins. LLVM exits
+/// if an option is given twice, so calls after the first do nothing.
+pub fn enable_pass_timing() {
+    static PASS_TIMING: Once = Once::new();
+    PASS_TIMING.call_once(|| {
+        let args = [c"zrc".as_ptr(), c"-time-passes".as_ptr()];
+
+        // SAFETY: `args` holds two NUL-terminated strings that outlive the call
+        // and LLVM does not retain the pointers.
+        unsafe {
+            LLVMParseCommandLineOptions(2, args.as_ptr(), c"".as_ptr());
+        }
+    });
+}
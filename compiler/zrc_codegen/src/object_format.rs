@@ -0,0 +1,137 @@
+//! Choosing the object file format to emit for a target
+//!
+//! LLVM infers the object file format from the target triple, such as COFF for
+//! `x86_64-pc-windows-msvc` and ELF for `x86_64-unknown-linux-gnu`. It can be
+//! overridden by ending the environment of the triple with the name of another
+//! format, as in `x86_64-pc-windows-msvc-elf`, which is all that
+//! [`ObjectFormat::apply_to_triple`] does.
+
+use inkwell::targets::{TargetMachine, TargetTriple};
+
+/// The object formats LLVM may write an object file in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectFormat {
+    /// ELF, used by Linux and most other Unix-like systems
+    Elf,
+    /// COFF, used by Windows
+    Coff,
+    /// Mach-O, used by macOS and iOS
+    MachO,
+    /// The WebAssembly binary format
+    Wasm,
+}
+
+/// Every object format name LLVM recognizes at the end of an environment, so an
+/// existing override can be replaced
+const FORMAT_SUFFIXES: [&str; 6] = ["xcoff", "goff", "coff", "elf", "macho", "wasm"];
+
+impl ObjectFormat {
+    /// The name LLVM recognizes for this format at the end of a triple
+    const fn triple_suffix(self) -> &'static str {
+        match self {
+            Self::Elf => "elf",
+            Self::Coff => "coff",
+            Self::MachO => "macho",
+            Self::Wasm => "wasm",
+        }
+    }
+
+    /// Returns whether LLVM can write objects in this format for the
+    /// architecture `arch`
+    fn supports_arch(self, arch: &str) -> bool {
+        let is_wasm = arch.starts_with("wasm");
+        let is_x86 = matches!(arch, "i386" | "i486" | "i586" | "i686" | "x86" | "x86_64");
+        let is_arm = arch.starts_with("arm") || arch.starts_with("thumb") || arch == "aarch64";
+
+        match self {
+            Self::Elf => !is_wasm,
+            Self::Coff | Self::MachO => is_x86 || is_arm,
+            Self::Wasm => is_wasm,
+        }
+    }
+
+    /// Get the triple which makes LLVM emit objects for `triple` in this
+    /// format.
+    ///
+    /// # Errors
+    /// Errors if LLVM cannot write objects in this format for the architecture
+    /// of `triple`.
+    pub fn apply_to_triple(self, triple: &TargetTriple) -> Result<TargetTriple, String> {
+        let normalized = TargetMachine::normalize_triple(triple);
+        let normalized = normalized.as_str().to_string_lossy();
+
+        // the environment is everything after the third component, and may
+        // itself contain dashes
+        let mut components = normalized.splitn(4, '-');
+        let arch = components.next().unwrap_or_default();
+        let vendor = components.next().unwrap_or("unknown");
+        let os = components.next().unwrap_or("unknown");
+        let environment = components.next().unwrap_or_default();
+
+        if !self.supports_arch(arch) {
+            return Err(format!(
+                "the {} object format is not supported for target `{normalized}`",
+                self.triple_suffix()
+            ));
+        }
+
+        let environment = FORMAT_SUFFIXES
+            .iter()
+            .find_map(|suffix| environment.strip_suffix(suffix))
+            .map_or(environment, |stripped| stripped.trim_end_matches('-'));
+
+        Ok(TargetTriple::create(&if environment.is_empty() {
+            format!("{arch}-{vendor}-{os}-{}", self.triple_suffix())
+        } else {
+            format!(
+                "{arch}-{vendor}-{os}-{environment}-{}",
+                self.triple_suffix()
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply `format` to the triple `triple` and return the result as a string
+    fn applied(format: ObjectFormat, triple: &str) -> Result<String, String> {
+        format
+            .apply_to_triple(&TargetTriple::create(triple))
+            .map(|triple| triple.as_str().to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn the_format_is_appended_to_the_environment() {
+        assert_eq!(
+            applied(ObjectFormat::Coff, "x86_64-unknown-linux-gnu"),
+            Ok("x86_64-unknown-linux-gnu-coff".to_string())
+        );
+        assert_eq!(
+            applied(ObjectFormat::Elf, "x86_64-apple-darwin"),
+            Ok("x86_64-apple-darwin-elf".to_string())
+        );
+    }
+
+    #[test]
+    fn an_existing_format_is_replaced() {
+        assert_eq!(
+            applied(ObjectFormat::MachO, "x86_64-unknown-linux-gnu-coff"),
+            Ok("x86_64-unknown-linux-gnu-macho".to_string())
+        );
+        assert_eq!(
+            applied(ObjectFormat::Elf, "x86_64-unknown-linux-coff"),
+            Ok("x86_64-unknown-linux-elf".to_string())
+        );
+    }
+
+    #[test]
+    fn formats_are_checked_against_the_architecture() {
+        assert!(applied(ObjectFormat::Wasm, "wasm32-unknown-unknown").is_ok());
+        assert!(applied(ObjectFormat::Wasm, "x86_64-unknown-linux-gnu").is_err());
+        assert!(applied(ObjectFormat::Elf, "wasm32-unknown-unknown").is_err());
+        assert!(applied(ObjectFormat::MachO, "riscv64-unknown-linux-gnu").is_err());
+        assert!(applied(ObjectFormat::Coff, "aarch64-pc-windows-msvc").is_ok());
+    }
+}
@@ -5,27 +5,36 @@
 //! intermediate representation (IR), which can then be optimized and compiled
 //! to machine code.
 
+use std::{ffi::CString, sync::OnceLock};
+
 use inkwell::{
-    OptimizationLevel,
+    AddressSpace, GlobalVisibility, OptimizationLevel,
+    attributes::{Attribute, AttributeLoc},
     context::Context,
     debug_info::{AsDIScope, DISubprogram, DWARFEmissionKind, DWARFSourceLanguage},
+    llvm_sys::support::LLVMParseCommandLineOptions,
     memory_buffer::MemoryBuffer,
-    module::{FlagBehavior, Module},
+    module::{FlagBehavior, Linkage, Module},
     passes::PassBuilderOptions,
     targets::{
         CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine, TargetTriple,
     },
     types::{AnyType, BasicMetadataTypeEnum, BasicTypeEnum},
-    values::{BasicValue, BasicValueEnum, FunctionValue},
+    values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, FunctionValue},
 };
-use zrc_typeck::tast::{
-    stmt::{ArgumentDeclaration, TypedDeclaration},
-    ty::Type,
+use zrc_typeck::{
+    callgraph::CallGraph,
+    tast::{
+        stmt::{ArgumentDeclaration, FunctionAttributes, TypedDeclaration},
+        ty::Type,
+    },
+    typeck::DisabledBuiltins,
 };
 use zrc_utils::{line_finder::LineLookup, span::Spanned};
 
 use super::stmt::cg_block;
 use crate::{
+    align::build_aligned_store,
     ctx::{CompilationUnitCtx, FunctionCtx},
     scope::CgScope,
     ty::{create_fn, llvm_basic_type, llvm_type},
@@ -51,12 +60,14 @@ fn eval_const_expr<'ctx>(
             let no_underscores = match n {
                 NumberLiteral::Decimal(string)
                 | NumberLiteral::Binary(string)
+                | NumberLiteral::Octal(string)
                 | NumberLiteral::Hexadecimal(string) => string.replace('_', ""),
             };
 
             let radix = match n {
                 NumberLiteral::Decimal(_) => StringRadix::Decimal,
                 NumberLiteral::Binary(_) => StringRadix::Binary,
+                NumberLiteral::Octal(_) => StringRadix::Octal,
                 NumberLiteral::Hexadecimal(_) => StringRadix::Hexadecimal,
             };
 
@@ -95,6 +106,34 @@ fn eval_const_expr<'ctx>(
             .i8_type()
             .const_int(ch.as_byte().into(), false)
             .as_basic_value_enum(),
+        TypedExprKind::ArrayLiteral(elements) => {
+            let Type::Array { element_type, .. } = ty else {
+                panic!("internal compiler error: array literal initializes a non-array type");
+            };
+
+            const_array(
+                unit,
+                element_type,
+                elements
+                    .iter()
+                    .map(|element| eval_const_expr(unit, element, element_type))
+                    .collect(),
+            )
+        }
+        TypedExprKind::ArrayRepeat(value, count) => {
+            let Type::Array { element_type, .. } = ty else {
+                panic!("internal compiler error: array repeat initializes a non-array type");
+            };
+
+            const_array(
+                unit,
+                element_type,
+                vec![
+                    eval_const_expr(unit, value, element_type);
+                    usize::try_from(*count).expect("array length should fit in usize")
+                ],
+            )
+        }
         _ => {
             // This should never happen as the type checker validates constant expressions
             panic!(
@@ -105,6 +144,39 @@ fn eval_const_expr<'ctx>(
     }
 }
 
+/// Build a constant array of `element_type` from constant `values`
+#[expect(clippy::wildcard_enum_match_arm)]
+fn const_array<'ctx>(
+    unit: &CompilationUnitCtx<'ctx, '_>,
+    element_type: &Type,
+    values: Vec<BasicValueEnum<'ctx>>,
+) -> BasicValueEnum<'ctx> {
+    match llvm_basic_type(unit, element_type).0 {
+        BasicTypeEnum::IntType(int_ty) => int_ty.const_array(
+            &values
+                .into_iter()
+                .map(BasicValueEnum::into_int_value)
+                .collect::<Vec<_>>(),
+        ),
+        BasicTypeEnum::PointerType(ptr_ty) => ptr_ty.const_array(
+            &values
+                .into_iter()
+                .map(BasicValueEnum::into_pointer_value)
+                .collect::<Vec<_>>(),
+        ),
+        BasicTypeEnum::ArrayType(array_ty) => array_ty.const_array(
+            &values
+                .into_iter()
+                .map(BasicValueEnum::into_array_value)
+                .collect::<Vec<_>>(),
+        ),
+        other => {
+            panic!("internal compiler error: unsupported constant array element type: {other:?}")
+        }
+    }
+    .as_basic_value_enum()
+}
+
 /// Initialize the LLVM [`FunctionValue`] for a given function prototype
 /// This should only be used when generating **extern** declarations, as
 /// it does not produce the needed [`DISubprogram`] for debugging.
@@ -223,25 +295,467 @@ pub fn cg_init_fn<'ctx>(
     (fn_val, fn_subprogram)
 }
 
+/// Apply the LLVM function attributes that correspond to a function's
+/// [`FunctionAttributes`] and to the [`CodegenOptions`] it is compiled with.
+fn apply_function_attributes(
+    ctx: &Context,
+    fn_value: FunctionValue<'_>,
+    attributes: &FunctionAttributes,
+    options: &CodegenOptions,
+) {
+    // LLVM requires every `optnone` function to also be `noinline` and rejects
+    // modules which combine `optnone` with `optsize` or `noinline` with
+    // `alwaysinline`, so `noinline` and `optnone` win over the others
+    let noinline = attributes.noinline || attributes.optnone;
+    let enum_attributes = [
+        ("alwaysinline", attributes.inline && !noinline),
+        ("noinline", noinline),
+        ("optsize", attributes.optsize && !attributes.optnone),
+        ("optnone", attributes.optnone),
+    ];
+    for (kind, _) in enum_attributes.into_iter().filter(|(_, applies)| *applies) {
+        fn_value.add_attribute(
+            AttributeLoc::Function,
+            ctx.create_enum_attribute(Attribute::get_named_enum_kind_id(kind), 0),
+        );
+    }
+
+    if options.stack_probes {
+        fn_value.add_attribute(
+            AttributeLoc::Function,
+            ctx.create_string_attribute("probe-stack", "inline-asm"),
+        );
+    }
+
+    if let Some(frame_pointer) = options.frame_pointer {
+        fn_value.add_attribute(
+            AttributeLoc::Function,
+            ctx.create_string_attribute("frame-pointer", frame_pointer.attribute_value()),
+        );
+    }
+
+    // like Clang's `-fno-builtin`, this stops LLVM from assuming that calls to
+    // functions like `memcpy` behave like the C library's and replacing them
+    match &options.disabled_builtins {
+        DisabledBuiltins::None => {}
+        DisabledBuiltins::All => {
+            fn_value.add_attribute(
+                AttributeLoc::Function,
+                ctx.create_string_attribute("no-builtins", ""),
+            );
+        }
+        DisabledBuiltins::Named(names) => {
+            for name in names {
+                fn_value.add_attribute(
+                    AttributeLoc::Function,
+                    ctx.create_string_attribute(&format!("no-builtin-{name}"), ""),
+                );
+            }
+        }
+    }
+}
+
+/// Mark the `noalias` parameters of a function with LLVM's `noalias`
+/// attribute, so that loads and stores through them can be reordered.
+///
+/// This is only sound because callers promise never to pass aliasing pointers
+/// to them, which the type checker warns about in obvious cases.
+fn apply_parameter_attributes(
+    ctx: &Context,
+    fn_value: FunctionValue<'_>,
+    parameters: &[ArgumentDeclaration<'_>],
+) {
+    for (n, parameter) in parameters.iter().enumerate() {
+        if parameter.noalias {
+            fn_value.add_attribute(
+                AttributeLoc::Param(
+                    n.try_into()
+                        .expect("over u32::MAX parameters in a function"),
+                ),
+                ctx.create_enum_attribute(Attribute::get_named_enum_kind_id("noalias"), 0),
+            );
+        }
+    }
+}
+
+/// The priority of a `constructor` function written without one, which runs
+/// after all constructors that have a priority
+const DEFAULT_CONSTRUCTOR_PRIORITY: u16 = u16::MAX;
+
+/// Add `constructors` to `llvm.global_ctors` so that they run before `main`.
+///
+/// Each entry is a `{ i32 priority, ptr function, ptr data }` triple, where
+/// `data` is always null.
+fn emit_global_ctors<'ctx>(
+    ctx: &'ctx Context,
+    module: &Module<'ctx>,
+    constructors: &[(u16, FunctionValue<'ctx>)],
+) {
+    if constructors.is_empty() {
+        return;
+    }
+
+    let ptr_type = ctx.ptr_type(AddressSpace::default());
+    let entry_type = ctx.struct_type(
+        &[ctx.i32_type().into(), ptr_type.into(), ptr_type.into()],
+        false,
+    );
+
+    let entries = constructors
+        .iter()
+        .map(|(priority, fn_value)| {
+            entry_type.const_named_struct(&[
+                ctx.i32_type().const_int(u64::from(*priority), false).into(),
+                fn_value.as_global_value().as_pointer_value().into(),
+                ptr_type.const_null().into(),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    let global = module.add_global(
+        entry_type.array_type(
+            entries
+                .len()
+                .try_into()
+                .expect("over u32::MAX constructors? HOW?"),
+        ),
+        None,
+        "llvm.global_ctors",
+    );
+    global.set_linkage(Linkage::Appending);
+    global.set_initializer(&entry_type.const_array(&entries));
+}
+
+/// The symbol the user's `main` is renamed to when an entry wrapper takes its
+/// place as `main`
+const USER_MAIN_SYMBOL: &str = "__zrc_user_main";
+
+/// The LLVM symbol a function named `name` is emitted as
+fn symbol_name<'a>(name: &'a str, options: &CodegenOptions) -> &'a str {
+    if options.entry_wrapper && name == "main" {
+        USER_MAIN_SYMBOL
+    } else {
+        name
+    }
+}
+
+/// Emit a C-compatible `int main(int argc, char **argv)` which calls the
+/// user's `main`, so the object can be linked directly against a C runtime.
+///
+/// The user's `main` is made internal, and is passed `argc` and `argv` if it
+/// takes them, with `argc` widened to a `usize`.
+fn emit_entry_wrapper<'ctx>(
+    ctx: &'ctx Context,
+    module: &Module<'ctx>,
+    target_machine: &TargetMachine,
+    user_main: FunctionValue<'ctx>,
+    options: &CodegenOptions,
+) {
+    user_main.as_global_value().set_linkage(Linkage::Internal);
+
+    let wrapper = module.add_function(
+        "main",
+        ctx.i32_type().fn_type(
+            &[
+                ctx.i32_type().into(),
+                ctx.ptr_type(AddressSpace::default()).into(),
+            ],
+            false,
+        ),
+        None,
+    );
+    apply_function_attributes(ctx, wrapper, &FunctionAttributes::default(), options);
+
+    let argc = wrapper
+        .get_nth_param(0)
+        .expect("wrapper should take argc")
+        .into_int_value();
+    argc.set_name("argc");
+    let argv = wrapper.get_nth_param(1).expect("wrapper should take argv");
+    argv.set_name("argv");
+
+    // a fresh builder, so no debug location of the user's functions leaks into
+    // the wrapper, which has no debug info
+    let builder = ctx.create_builder();
+    builder.position_at_end(ctx.append_basic_block(wrapper, "entry"));
+
+    let arguments: Vec<BasicMetadataValueEnum> = if user_main.count_params() == 2 {
+        let usize_type = ctx.ptr_sized_int_type(&target_machine.get_target_data(), None);
+        let argc = builder
+            .build_int_z_extend(argc, usize_type, "argc_usize")
+            .expect("argc should widen to usize");
+        vec![argc.into(), argv.into()]
+    } else {
+        vec![]
+    };
+
+    let result = builder
+        .build_call(user_main, &arguments, "result")
+        .expect("call to the user's main should generate successfully")
+        .try_as_basic_value()
+        .expect_basic("main should return i32");
+    builder
+        .build_return(Some(&result))
+        .expect("return from the entry wrapper should generate successfully");
+}
+
+/// Embed the bitcode of `module` into itself as a global in the section LLVM
+/// reserves for it, like Clang's `-fembed-bitcode`.
+///
+/// This is `.llvmbc` on most object formats and `__LLVM,__bitcode` on Mach-O.
+/// The bitcode is written before the global is added, so it describes the
+/// module as it was when this was called.
+fn embed_bitcode<'ctx>(ctx: &'ctx Context, module: &Module<'ctx>, target_machine: &TargetMachine) {
+    let bitcode = module.write_bitcode_to_memory();
+    let contents = ctx.const_string(bitcode.as_slice(), false);
+
+    let triple = target_machine.get_triple();
+    let triple = triple.as_str().to_string_lossy();
+    let section = if triple.contains("apple") || triple.contains("darwin") {
+        "__LLVM,__bitcode"
+    } else {
+        ".llvmbc"
+    };
+
+    let global = module.add_global(contents.get_type(), None, "llvm.embedded.module");
+    global.set_linkage(Linkage::Private);
+    global.set_constant(true);
+    global.set_alignment(1);
+    global.set_section(Some(section));
+    global.set_initializer(&contents);
+
+    // keep the otherwise unused global from being dropped
+    let ptr_type = ctx.ptr_type(AddressSpace::default());
+    let used = module.add_global(ptr_type.array_type(1), None, "llvm.compiler.used");
+    used.set_linkage(Linkage::Appending);
+    used.set_section(Some("llvm.metadata"));
+    used.set_initializer(&ptr_type.const_array(&[global.as_pointer_value()]));
+}
+
+/// Name the file an instrumented program writes its raw profile to, which is
+/// `default_<signature>.profraw` in `directory` like Clang's
+/// `-fprofile-generate=<directory>`. The profile runtime replaces `%m` with a
+/// signature of the program, so different programs do not overwrite each
+/// other's profiles.
+fn emit_profile_file_name<'ctx>(ctx: &'ctx Context, module: &Module<'ctx>, directory: &str) {
+    let file_name = format!("{directory}/default_%m.profraw");
+    let contents = ctx.const_string(file_name.as_bytes(), true);
+
+    // the runtime defines a weak `__llvm_profile_filename` of its own, which
+    // this takes the place of
+    let global = module.add_global(contents.get_type(), None, "__llvm_profile_filename");
+    global.set_linkage(Linkage::WeakAny);
+    global.set_visibility(GlobalVisibility::Hidden);
+    global.set_constant(true);
+    global.set_initializer(&contents);
+}
+
+/// Point LLVM's `pgo-instr-use` pass at the profile in `path`.
+///
+/// The C API has no way to give the pass builder a profile, so this sets
+/// `-pgo-test-profile-file`, the global LLVM option the pass falls back to
+/// when it is not given one. That is what `opt` uses to read a profile. LLVM
+/// exits if an option is given twice, so the option is only set the first time
+/// and every module afterwards is optimized with that profile.
+///
+/// # Panics
+/// Panics if a different profile was already used in this process, since LLVM
+/// would silently keep optimizing with the first one.
+fn read_profile_from(path: &str) {
+    static PROFILE: OnceLock<String> = OnceLock::new();
+    let first = PROFILE.get_or_init(|| {
+        let option = CString::new(format!("-pgo-test-profile-file={path}"))
+            .expect("profile path should not contain NUL bytes");
+        let args = [c"zrc".as_ptr(), option.as_ptr()];
+
+        // SAFETY: `args` holds two NUL-terminated strings that outlive the call
+        // and LLVM copies the option's value.
+        unsafe {
+            LLVMParseCommandLineOptions(2, args.as_ptr(), c"".as_ptr());
+        }
+
+        path.to_string()
+    });
+
+    assert_eq!(
+        first, path,
+        "only one profile can be used per process, but `{first}` was already used"
+    );
+}
+
+/// The LLVM pass pipeline run at an optimization level
+const fn default_pipeline(optimization_level: OptimizationLevel) -> &'static str {
+    match optimization_level {
+        OptimizationLevel::None => "default<O0>",
+        OptimizationLevel::Less => "default<O1>",
+        OptimizationLevel::Default => "default<O2>",
+        OptimizationLevel::Aggressive => "default<O3>",
+    }
+}
+
 /// Run optimizations on the given program.
-fn optimize_module(module: &Module<'_>, tm: &TargetMachine, optimization_level: OptimizationLevel) {
+///
+/// Even at [`OptimizationLevel::None`], the `default<O0>` pipeline runs the
+/// always-inliner, so `inline fn`s are inlined without enabling any other
+/// optimizations.
+///
+/// With a [`Profile`], its passes run on the unoptimized module before the
+/// rest of the pipeline. The functions the profile is read for then have the
+/// same control flow as the ones that were instrumented, whatever the
+/// optimization levels of the two builds.
+fn optimize_module(
+    module: &Module<'_>,
+    tm: &TargetMachine,
+    optimization_level: OptimizationLevel,
+    profile: Option<&Profile>,
+) {
+    let profile_passes = match profile {
+        // `instrprof` lowers the counters `pgo-instr-gen` inserts into
+        // globals and the calls that write them out
+        Some(Profile::Generate(_)) => "pgo-instr-gen,instrprof,",
+        Some(Profile::Use(path)) => {
+            read_profile_from(path);
+            "pgo-instr-use,"
+        }
+        None => "",
+    };
+
     module
         .run_passes(
-            match optimization_level {
-                OptimizationLevel::None => "default<O0>",
-                OptimizationLevel::Less => "default<O1>",
-                OptimizationLevel::Default => "default<O2>",
-                OptimizationLevel::Aggressive => "default<O3>",
-            },
+            &format!("{profile_passes}{}", default_pipeline(optimization_level)),
             tm,
             PassBuilderOptions::create(),
         )
         .expect("optimizing module should succeed");
 }
 
+/// Print the optimization passes run at `optimization_level` to stderr, in
+/// the order they run.
+///
+/// LLVM's C API cannot describe a pipeline without running it, so this runs
+/// the pipeline with LLVM's pass logging enabled on a module holding a single
+/// empty function. Each pass is printed as a `Running pass: <name> on <ir>`
+/// line, interleaved with the analyses the passes request.
+///
+/// # Panics
+/// Panics if the target machine cannot be created.
+pub fn print_pass_pipeline(
+    optimization_level: OptimizationLevel,
+    triple: &TargetTriple,
+    cpu: &str,
+) {
+    let ctx = Context::create();
+
+    Target::initialize_all(&InitializationConfig::default());
+    let target = Target::from_triple(triple).expect("target should be ready and exist");
+    let target_machine = target
+        .create_target_machine(
+            triple,
+            cpu,
+            "",
+            optimization_level,
+            RelocMode::PIC,
+            CodeModel::Default,
+        )
+        .expect("target machine should be created successfully");
+
+    // function and loop passes are only run, and so only printed, on
+    // functions that have a body
+    let module = ctx.create_module("zrc");
+    let builder = ctx.create_builder();
+    let function = module.add_function("f", ctx.void_type().fn_type(&[], false), None);
+    builder.position_at_end(ctx.append_basic_block(function, "entry"));
+    builder
+        .build_return(None)
+        .expect("return should generate successfully");
+
+    let options = PassBuilderOptions::create();
+    options.set_debug_logging(true);
+    module
+        .run_passes(
+            default_pipeline(optimization_level),
+            &target_machine,
+            options,
+        )
+        .expect("optimizing module should succeed");
+}
+
+/// Options that change what is emitted into a module, beyond its target and
+/// its optimization and debug levels
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodegenOptions {
+    /// An identifier for the build that produced the module, embedded in its
+    /// `llvm.ident` metadata so it ends up in the `.comment` section of the
+    /// object file
+    pub module_id: Option<String>,
+    /// Probe every page of a function's stack frame as it is allocated, so
+    /// that large frames cannot skip past the guard page below the stack
+    pub stack_probes: bool,
+    /// Embed the optimized module's bitcode in object files, so that a linker
+    /// can run link-time optimization across them
+    pub embed_bitcode: bool,
+    /// Which functions keep a frame pointer, or [`None`] to follow the
+    /// target's convention
+    pub frame_pointer: Option<FramePointer>,
+    /// Emit `main` as a C-compatible `int main(int argc, char **argv)` which
+    /// calls the user's `main`, for linking directly against a C runtime
+    pub entry_wrapper: bool,
+    /// Skip every function that is not reachable from `main`, a constructor
+    /// or a global, so it is not emitted at all
+    pub gc_functions: bool,
+    /// The built-ins that are not recognized, so functions with their names
+    /// are never treated as the C library function of the same name
+    pub disabled_builtins: DisabledBuiltins,
+    /// Instrument the module to record a profile, or optimize it with one
+    pub profile: Option<Profile>,
+}
+
+/// A stage of profile-guided optimization, where a program is first built to
+/// record how often each of its branches is taken and then rebuilt using that
+/// profile to decide what to optimize for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Profile {
+    /// Instrument the module to write a raw profile into this directory when
+    /// the program exits. The program must be linked against LLVM's profile
+    /// runtime, such as with `clang -fprofile-generate`.
+    Generate(String),
+    /// Optimize the module with the profile in this file, which is made by
+    /// merging raw profiles with `llvm-profdata merge`. LLVM reads it from a
+    /// global option, so every module optimized with a profile in one process
+    /// must use the same file.
+    Use(String),
+}
+
+/// Which functions keep a frame pointer, as set by LLVM's `frame-pointer`
+/// function attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramePointer {
+    /// Every function keeps a frame pointer
+    All,
+    /// Only functions that call other functions keep a frame pointer
+    NonLeaf,
+    /// No function needs to keep a frame pointer
+    None,
+}
+impl FramePointer {
+    /// The value of the `frame-pointer` attribute for this setting
+    const fn attribute_value(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::NonLeaf => "non-leaf",
+            Self::None => "none",
+        }
+    }
+}
+
 /// Code generate and verify a program given a [`Context`] and return the final
 /// LLVM [`Module`] as a result, sans optimization.
 ///
+/// Declarations are generated in the order of `program`, which is their order
+/// in the source, so functions and globals always appear in the module in the
+/// order they were written. Nothing here iterates a hash map, keeping the
+/// emitted IR stable across compilations for diffs and snapshot tests.
+///
 /// # Panics
 /// Panics if code generation fails. This can be caused by an invalid TAST being
 /// passed, so make sure to type check it so invariants are upheld.
@@ -257,7 +771,14 @@ fn cg_program_without_optimization<'ctx>(
     file_name: &str,
     line_lookup: &LineLookup,
     program: Vec<Spanned<TypedDeclaration<'_>>>,
+    options: &CodegenOptions,
 ) -> Module<'ctx> {
+    let program = if options.gc_functions {
+        CallGraph::remove_unreachable_functions(program)
+    } else {
+        program
+    };
+
     let builder = ctx.create_builder();
     let module = ctx.create_module(file_name);
 
@@ -329,12 +850,14 @@ fn cg_program_without_optimization<'ctx>(
     };
 
     let mut global_scope = CgScope::new();
+    let mut constructors = Vec::new();
 
     for declaration in program {
         let span = declaration.span();
 
         match declaration.into_value() {
             TypedDeclaration::FunctionDeclaration {
+                attributes,
                 name,
                 parameters,
                 return_type,
@@ -344,7 +867,7 @@ fn cg_program_without_optimization<'ctx>(
 
                 let (fn_value, fn_subprogram) = cg_init_fn(
                     &unit,
-                    name.value(),
+                    symbol_name(name.value(), options),
                     line_lookup.lookup_from_index(span.start()).line,
                     return_type.value(),
                     parameters
@@ -356,6 +879,14 @@ fn cg_program_without_optimization<'ctx>(
                         .as_slice(),
                     parameters.value().is_variadic(),
                 );
+                apply_function_attributes(ctx, fn_value, &attributes, options);
+                apply_parameter_attributes(ctx, fn_value, parameters.value().as_arguments());
+                if let Some(constructor) = &attributes.constructor {
+                    constructors.push((
+                        constructor.priority.unwrap_or(DEFAULT_CONSTRUCTOR_PRIORITY),
+                        fn_value,
+                    ));
+                }
                 global_scope.insert(name.value(), fn_value.as_global_value().as_pointer_value());
                 // must come after the insert call so that recursion is valid
                 let mut fn_scope = global_scope.clone();
@@ -389,7 +920,7 @@ fn cg_program_without_optimization<'ctx>(
                     })
                     .unzip();
 
-                for (n, ArgumentDeclaration { name, ty }) in
+                for (n, ArgumentDeclaration { name, ty, .. }) in
                     parameters.value().as_arguments().iter().enumerate()
                 {
                     if entry.get_first_instruction().is_some() {
@@ -408,17 +939,17 @@ fn cg_program_without_optimization<'ctx>(
 
                     builder.position_at_end(entry);
 
-                    builder
-                        .build_store::<BasicValueEnum>(
-                            alloc,
-                            fn_value
-                                .get_nth_param(
-                                    n.try_into()
-                                        .expect("over u32::MAX parameters in a function? HOW?"),
-                                )
-                                .expect("nth parameter from fn type should exist in fn value"),
-                        )
-                        .expect("store should generate successfully");
+                    build_aligned_store(
+                        &unit,
+                        alloc,
+                        fn_value
+                            .get_nth_param(
+                                n.try_into()
+                                    .expect("over u32::MAX parameters in a function? HOW?"),
+                            )
+                            .expect("nth parameter from fn type should exist in fn value"),
+                    )
+                    .expect("store should generate successfully");
 
                     // let ident_line_col = line_lookup.lookup_from_index(name.start());
 
@@ -454,14 +985,22 @@ fn cg_program_without_optimization<'ctx>(
                     fn_scope.insert(name.value(), alloc);
                 }
 
-                cg_block(
+                // the type checker only lets a body fall off its end when that end can never
+                // be reached, like after an infinite loop, but the block still needs a
+                // terminator
+                if let Some(bb) = cg_block(
                     FunctionCtx::from_unit_and_fn(unit, fn_value),
                     entry,
                     &fn_scope,
                     lexical_block,
                     body,
-                    &None,
-                );
+                    &[],
+                ) {
+                    builder.position_at_end(bb);
+                    builder
+                        .build_unreachable()
+                        .expect("unreachable should generate successfully");
+                }
             }
             // We do not attach debugging information to extern functions, this is clang's behavior
             // so I assume it's correct.
@@ -470,10 +1009,11 @@ fn cg_program_without_optimization<'ctx>(
                 parameters,
                 return_type,
                 body: None,
+                ..
             } => {
                 let fn_value = cg_init_extern_fn(
                     &unit,
-                    name.value(),
+                    symbol_name(name.value(), options),
                     return_type.value(),
                     parameters
                         .value()
@@ -484,6 +1024,7 @@ fn cg_program_without_optimization<'ctx>(
                         .as_slice(),
                     parameters.value().is_variadic(),
                 );
+                apply_parameter_attributes(ctx, fn_value, parameters.value().as_arguments());
                 global_scope.insert(name.value(), fn_value.as_global_value().as_pointer_value());
             }
             TypedDeclaration::GlobalLetDeclaration(declarations) => {
@@ -500,12 +1041,39 @@ fn cg_program_without_optimization<'ctx>(
                     );
                     global.set_initializer(&initializer);
 
+                    // constants are never written to, so they can live in read-only data
+                    if let_declaration.is_constant {
+                        global.set_constant(true);
+                    }
+
                     global_scope.insert(let_declaration.name.value(), global.as_pointer_value());
                 }
             }
         }
     }
 
+    emit_global_ctors(ctx, &module, &constructors);
+
+    if options.entry_wrapper
+        && let Some(user_main) = module.get_function(USER_MAIN_SYMBOL)
+        && user_main.count_basic_blocks() > 0
+    {
+        emit_entry_wrapper(ctx, &module, target_machine, user_main, options);
+    }
+
+    if let Some(Profile::Generate(directory)) = &options.profile {
+        emit_profile_file_name(ctx, &module, directory);
+    }
+
+    if let Some(module_id) = &options.module_id {
+        module
+            .add_global_metadata(
+                "llvm.ident",
+                &ctx.metadata_node(&[ctx.metadata_string(module_id).into()]),
+            )
+            .expect("llvm.ident should be a metadata node");
+    }
+
     if let Some(dbg_builder) = dbg_builder {
         dbg_builder.finalize();
     }
@@ -545,6 +1113,7 @@ pub fn cg_program<'ctx>(
     file_name: &str,
     line_lookup: &LineLookup,
     program: Vec<Spanned<TypedDeclaration<'_>>>,
+    options: &CodegenOptions,
 ) -> Module<'ctx> {
     let module = cg_program_without_optimization(
         frontend_version_string,
@@ -556,9 +1125,15 @@ pub fn cg_program<'ctx>(
         file_name,
         line_lookup,
         program,
+        options,
     );
 
-    optimize_module(&module, target_machine, optimization_level);
+    optimize_module(
+        &module,
+        target_machine,
+        optimization_level,
+        options.profile.as_ref(),
+    );
 
     module
 }
@@ -580,6 +1155,7 @@ pub fn cg_program_to_string(
     debug_level: DWARFEmissionKind,
     triple: &TargetTriple,
     cpu: &str,
+    options: &CodegenOptions,
 ) -> String {
     let ctx = Context::create();
 
@@ -610,6 +1186,7 @@ pub fn cg_program_to_string(
         file_name,
         &LineLookup::new(source),
         program,
+        options,
     );
 
     module.print_to_string().to_string()
@@ -634,6 +1211,7 @@ pub fn cg_program_to_string_without_optimization(
     debug_level: DWARFEmissionKind,
     triple: &TargetTriple,
     cpu: &str,
+    options: &CodegenOptions,
 ) -> String {
     let ctx = Context::create();
 
@@ -664,6 +1242,7 @@ pub fn cg_program_to_string_without_optimization(
         file_name,
         &LineLookup::new(source),
         program,
+        options,
     );
 
     module.print_to_string().to_string()
@@ -688,6 +1267,7 @@ pub fn cg_program_to_buffer(
     debug_level: DWARFEmissionKind,
     triple: &TargetTriple,
     cpu: &str,
+    options: &CodegenOptions,
 ) -> MemoryBuffer<'static> {
     let ctx = Context::create();
 
@@ -718,21 +1298,109 @@ pub fn cg_program_to_buffer(
         file_name,
         &LineLookup::new(source),
         program,
+        options,
     );
 
+    if options.embed_bitcode && file_type == FileType::Object {
+        embed_bitcode(&ctx, &module, &target_machine);
+    }
+
     target_machine
         .write_to_memory_buffer(&module, file_type)
         .expect("writing to memory buffer should succeed")
 }
 
+/// List the functions a module references but does not define, sorted by name.
+///
+/// These are the symbols that must be provided by another object file or
+/// library when linking. Functions that are declared but never used, as well as
+/// LLVM intrinsics, are not included.
+#[must_use]
+pub fn undefined_symbols(module: &Module<'_>) -> Vec<String> {
+    let mut symbols: Vec<String> = module
+        .get_functions()
+        .filter(|function| {
+            function.get_intrinsic_id() == 0
+                && function.as_global_value().is_declaration()
+                && function.as_global_value().get_first_use().is_some()
+        })
+        .map(|function| function.get_name().to_string_lossy().into_owned())
+        .collect();
+
+    symbols.sort();
+    symbols
+}
+
+/// Code generate a LLVM program and list the symbols it leaves undefined.
+///
+/// This is intended to be run as a first pass before emitting an object file,
+/// so the user knows what the output must be linked against. See
+/// [`undefined_symbols`].
+///
+/// # Panics
+/// Panics on internal code generation failure.
+#[must_use]
+#[expect(clippy::too_many_arguments)]
+pub fn cg_program_undefined_symbols(
+    frontend_version_string: &str,
+    parent_directory: &str,
+    file_name: &str,
+    cli_args: &str,
+    source: &str,
+    program: Vec<Spanned<TypedDeclaration<'_>>>,
+    optimization_level: OptimizationLevel,
+    triple: &TargetTriple,
+    cpu: &str,
+    options: &CodegenOptions,
+) -> Vec<String> {
+    let ctx = Context::create();
+
+    Target::initialize_all(&InitializationConfig::default());
+    let target = Target::from_triple(triple).expect("target should be ready and exist");
+
+    let target_machine = target
+        .create_target_machine(
+            triple,
+            cpu,
+            "",
+            optimization_level,
+            RelocMode::PIC,
+            CodeModel::Default,
+        )
+        .expect("target machine should be created successfully");
+
+    // optimize so that calls removed by the optimizer are not reported, as they
+    // will not be in the emitted output either
+    let module = cg_program(
+        frontend_version_string,
+        cli_args,
+        &ctx,
+        &target_machine,
+        optimization_level,
+        DWARFEmissionKind::None,
+        parent_directory,
+        file_name,
+        &LineLookup::new(source),
+        program,
+        options,
+    );
+
+    undefined_symbols(&module)
+}
+
 #[cfg(test)]
 mod tests {
     // Please read the "Common patterns in tests" section of crate::test_utils for
     // more information on how code generator tests are structured.
 
     use indoc::indoc;
+    use inkwell::OptimizationLevel;
+    use zrc_typeck::tast::stmt::{FunctionAttributes, TypedDeclaration};
 
-    use crate::cg_snapshot_test;
+    use crate::{
+        cg_snapshot_test,
+        test_utils::{TEST_TRIPLE, ir_of, ir_with_options, typed},
+    };
 
     #[test]
     fn function_parameters_are_properly_generated() {
@@ -743,6 +1411,147 @@ mod tests {
             "});
     }
 
+    #[test]
+    fn inline_functions_are_inlined_without_optimization() {
+        // TEST: `one` is inlined into `test` at -O0, `two` is still called
+        cg_snapshot_test!(
+            indoc! {"
+                inline fn one() -> i32 {
+                    return 1;
+                }
+
+                fn two() -> i32 {
+                    return 2;
+                }
+
+                fn test() -> i32 {
+                    return one() + two();
+                }
+            "},
+            OptimizationLevel::None
+        );
+    }
+
+    #[test]
+    fn optimization_attributes_are_applied_to_their_functions() {
+        let source = indoc! {"
+            optsize fn small() -> i32 {
+                return 1;
+            }
+
+            optnone fn unoptimized() -> i32 {
+                return 2;
+            }
+
+            fn plain() -> i32 {
+                return small() + unoptimized();
+            }
+        "};
+
+        let ir = ir_of(source);
+
+        // the attribute group applied to the function `name`, if any
+        let attributes_of = |name: &str| -> String {
+            let define = ir
+                .lines()
+                .find(|line| line.starts_with("define") && line.contains(&format!("@{name}(")))
+                .unwrap_or_else(|| panic!("`{name}` should be defined in:\n{ir}"));
+            let Some(group) = define.split_whitespace().find(|word| word.starts_with('#')) else {
+                return String::new();
+            };
+            ir.lines()
+                .find(|line| line.starts_with(&format!("attributes {group} = ")))
+                .unwrap_or_else(|| panic!("attribute group {group} should exist in:\n{ir}"))
+                .to_string()
+        };
+
+        // TEST: each function only carries the attributes it was declared with,
+        // and `optnone` brings the `noinline` LLVM requires along with it
+        let small = attributes_of("small");
+        assert!(small.contains("optsize"), "{ir}");
+        assert!(!small.contains("optnone"), "{ir}");
+
+        let unoptimized = attributes_of("unoptimized");
+        assert!(unoptimized.contains("optnone"), "{ir}");
+        assert!(unoptimized.contains("noinline"), "{ir}");
+        assert!(!unoptimized.contains("optsize"), "{ir}");
+
+        let plain = attributes_of("plain");
+        assert!(
+            !plain.contains("optsize") && !plain.contains("optnone"),
+            "{ir}"
+        );
+    }
+
+    #[test]
+    fn noinline_takes_precedence_over_inline() {
+        let source = indoc! {"
+            noinline fn never() -> i32 {
+                return 1;
+            }
+
+            optnone noinline fn unoptimized() -> i32 {
+                return 2;
+            }
+
+            fn test() -> i32 {
+                return never() + unoptimized();
+            }
+        "};
+
+        // the type checker rejects `inline noinline`, but a conflicting TAST
+        // must still produce a valid module
+        let program = typed(source)
+            .into_iter()
+            .map(|declaration| {
+                declaration.map(|declaration| match declaration {
+                    TypedDeclaration::FunctionDeclaration {
+                        attributes,
+                        name,
+                        parameters,
+                        return_type,
+                        body,
+                    } => TypedDeclaration::FunctionDeclaration {
+                        attributes: FunctionAttributes {
+                            inline: true,
+                            ..attributes
+                        },
+                        name,
+                        parameters,
+                        return_type,
+                        body,
+                    },
+                    TypedDeclaration::GlobalLetDeclaration(declarations) => {
+                        TypedDeclaration::GlobalLetDeclaration(declarations)
+                    }
+                })
+            })
+            .collect();
+
+        let ir = ir_with_options(source, program, &super::CodegenOptions::default());
+
+        // TEST: `noinline` and `optnone` functions are never `alwaysinline`,
+        // while the plain function keeps the `inline` it was given
+        let groups = ir
+            .lines()
+            .filter(|line| line.starts_with("attributes #"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups
+                .iter()
+                .filter(|group| group.contains("alwaysinline"))
+                .count(),
+            1,
+            "{ir}"
+        );
+        assert!(
+            groups
+                .iter()
+                .all(|group| !(group.contains("alwaysinline") && group.contains("noinline"))),
+            "{ir}"
+        );
+    }
+
     /// Regression test for <https://github.com/zirco-lang/zrc/issues/441>
     /// Global string variables should compile without ICE
     #[test]
@@ -756,4 +1565,472 @@ mod tests {
             }
         "#});
     }
+
+    #[test]
+    fn const_arrays_are_constant_globals() {
+        cg_snapshot_test!(indoc! {"
+            const LUT: [4]u8 = [1, 2, 3, 4];
+
+            fn get(i: usize) -> u8 {
+                // TEST: `LUT` is a `constant` global that is indexed in place
+                return LUT[i];
+            }
+        "});
+    }
+
+    #[test]
+    fn folded_initializers_are_emitted_as_constants() {
+        let source = indoc! {"
+            static counter: i32 = 4 * 1024;
+            const OFFSET: i64 = -(2i64 * 3);
+
+            fn next() -> i32 {
+                counter += 1;
+                return counter;
+            }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: the initializers are computed at compile time, and `counter` is
+        // read and written through its global
+        assert!(ir.contains("@counter = global i32 4096"), "{ir}");
+        assert!(ir.contains("@OFFSET = constant i64 -6"), "{ir}");
+        assert!(ir.contains("load i32, ptr @counter"), "{ir}");
+        assert!(
+            ir.lines()
+                .any(|line| line.trim_start().starts_with("store i32")
+                    && line.contains(", ptr @counter")),
+            "{ir}"
+        );
+    }
+
+    #[test]
+    fn constructors_are_added_to_global_ctors() {
+        let source = indoc! {"
+            constructor fn early_init() {}
+            constructor(101) fn first_init() {}
+            fn not_a_constructor() {}
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: both constructors are registered with their priority, and no
+        // other function is
+        assert!(
+            ir.contains(
+                "@llvm.global_ctors = appending global [2 x { i32, ptr, ptr }] \
+                 [{ i32, ptr, ptr } { i32 65535, ptr @early_init, ptr null }, \
+                 { i32, ptr, ptr } { i32 101, ptr @first_init, ptr null }]"
+            ),
+            "{ir}"
+        );
+        assert!(!ir.contains("@not_a_constructor, ptr null"), "{ir}");
+    }
+
+    #[test]
+    fn gc_functions_skips_unreachable_functions() {
+        let source = indoc! {"
+            fn used() -> i32 { return 1; }
+            fn dead() -> i32 { return 2; }
+            constructor fn init() {}
+            fn main() -> i32 { return used(); }
+        "};
+
+        let ir_with = |gc_functions| {
+            ir_with_options(
+                source,
+                typed(source),
+                &crate::CodegenOptions {
+                    gc_functions,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // TEST: `dead` is only emitted without the flag, and everything
+        // reachable from `main` or a constructor is always emitted
+        let collected = ir_with(true);
+        assert!(!collected.contains("@dead("), "{collected}");
+        for name in ["@used(", "@init(", "@main("] {
+            assert!(collected.contains(name), "{collected}");
+        }
+        assert!(ir_with(false).contains("@dead("));
+    }
+
+    #[test]
+    fn module_id_is_embedded_in_llvm_ident() {
+        let source = "fn f() {}";
+
+        let ir = ir_with_options(
+            source,
+            typed(source),
+            &crate::CodegenOptions {
+                module_id: Some("build-123".to_string()),
+                ..Default::default()
+            },
+        );
+
+        // TEST: the identifier is the only entry in `llvm.ident`
+        assert!(ir.contains("!llvm.ident = !{!0}"), "{ir}");
+        assert!(ir.contains("!0 = !{!\"build-123\"}"), "{ir}");
+    }
+
+    #[test]
+    fn profile_generate_instruments_the_module() {
+        let source = "fn test(x: bool) -> i32 { if (x) { return 1; } return 2; }";
+
+        let ir_with = |profile| {
+            super::cg_program_to_string(
+                "zrc test runner",
+                "/fake/path",
+                "test.zr",
+                "zrc --fake-args",
+                source,
+                typed(source),
+                inkwell::OptimizationLevel::None,
+                inkwell::debug_info::DWARFEmissionKind::None,
+                &inkwell::targets::TargetTriple::create(TEST_TRIPLE),
+                "",
+                &super::CodegenOptions {
+                    profile,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // TEST: the function's counters are incremented as it runs, and the
+        // profile is written into the directory that was given
+        let ir = ir_with(Some(super::Profile::Generate("/tmp/profiles".to_string())));
+        assert!(ir.contains("@__profc_test = "), "{ir}");
+        assert!(ir.contains("load i64, ptr @__profc_test"), "{ir}");
+        assert!(
+            ir.contains(
+                "@__llvm_profile_filename = weak hidden constant [33 x i8] \
+                 c\"/tmp/profiles/default_%m.profraw\\00\""
+            ),
+            "{ir}"
+        );
+
+        // TEST: nothing is instrumented without a profile
+        let ir = ir_with(None);
+        assert!(!ir.contains("__prof"), "{ir}");
+    }
+
+    #[test]
+    #[should_panic(expected = "only one profile can be used per process")]
+    fn a_second_profile_cannot_be_used_in_the_same_process() {
+        // TEST: the same profile can be used any number of times
+        super::read_profile_from("first.profdata");
+        super::read_profile_from("first.profdata");
+
+        // TEST: LLVM would keep reading `first.profdata`, so this panics
+        super::read_profile_from("second.profdata");
+    }
+
+    #[test]
+    fn functions_are_emitted_in_source_order() {
+        let source = indoc! {"
+            fn zeta();
+            fn beta() -> i32 { return 2; }
+            struct Point { x: i32 }
+            impl Point {
+                fn get(self) -> i32 { return self.x; }
+            }
+            fn alpha() -> i32 { return beta(); }
+            fn mu();
+            fn omega() { zeta(); mu(); }
+        "};
+
+        let order = || {
+            ir_of(source)
+                .lines()
+                .filter(|line| line.starts_with("define ") || line.starts_with("declare "))
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        let first = order();
+
+        // TEST: functions appear in the order they were written, not sorted by
+        // name or in hash map order
+        assert_eq!(
+            first,
+            [
+                "declare {} @zeta()",
+                "define i32 @beta() {",
+                "define i32 @\"Point::get\"({ i32 } %0) {",
+                "define i32 @alpha() {",
+                "declare {} @mu()",
+                "define {} @omega() {",
+            ]
+        );
+
+        // TEST: compiling the same input again gives the same order
+        assert_eq!(order(), first);
+    }
+
+    #[test]
+    fn noalias_parameters_get_the_llvm_attribute() {
+        let source = indoc! {"
+            fn copy(noalias dst: *i32, noalias src: *i32, n: usize);
+
+            fn fill(noalias dst: *i32, value: i32) {
+                *dst = value;
+            }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: only the parameters marked `noalias` get the attribute, on both
+        // declarations and definitions
+        assert!(
+            ir.contains("declare {} @copy(ptr noalias, ptr noalias, i64)"),
+            "{ir}"
+        );
+        assert!(
+            ir.contains("define {} @fill(ptr noalias %0, i32 %1)"),
+            "{ir}"
+        );
+    }
+
+    #[test]
+    fn stack_probes_are_requested_on_every_function_definition() {
+        let source = indoc! {"
+            fn external();
+
+            fn big() {
+                let buffer: [65536]u8;
+                external();
+            }
+        "};
+
+        let ir_with_stack_probes = |stack_probes| {
+            ir_with_options(
+                source,
+                typed(source),
+                &crate::CodegenOptions {
+                    stack_probes,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // TEST: the definition gets the attribute, the declaration does not
+        let ir = ir_with_stack_probes(true);
+        assert!(ir.contains("define {} @big() #0"), "{ir}");
+        assert!(ir.contains("declare {} @external()\n"), "{ir}");
+        assert!(
+            ir.contains("attributes #0 = { \"probe-stack\"=\"inline-asm\" }"),
+            "{ir}"
+        );
+
+        // TEST: nothing is added without the flag
+        let ir = ir_with_stack_probes(false);
+        assert!(!ir.contains("probe-stack"), "{ir}");
+    }
+
+    #[test]
+    fn frame_pointer_is_set_on_every_function_definition() {
+        let source = indoc! {"
+            fn external();
+
+            fn caller() { external(); }
+        "};
+
+        let ir_with_frame_pointer = |frame_pointer| {
+            ir_with_options(
+                source,
+                typed(source),
+                &crate::CodegenOptions {
+                    frame_pointer,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // TEST: `all` is passed straight through to LLVM on definitions
+        let ir = ir_with_frame_pointer(Some(super::FramePointer::All));
+        assert!(ir.contains("define {} @caller() #0"), "{ir}");
+        assert!(ir.contains("declare {} @external()\n"), "{ir}");
+        assert!(
+            ir.contains("attributes #0 = { \"frame-pointer\"=\"all\" }"),
+            "{ir}"
+        );
+
+        let ir = ir_with_frame_pointer(Some(super::FramePointer::NonLeaf));
+        assert!(ir.contains("\"frame-pointer\"=\"non-leaf\""), "{ir}");
+
+        // TEST: the target's convention is kept by default
+        let ir = ir_with_frame_pointer(None);
+        assert!(!ir.contains("frame-pointer"), "{ir}");
+    }
+
+    #[test]
+    fn disabled_builtins_are_marked_on_every_function_definition() {
+        let source = indoc! {"
+            fn memcpy(dst: *u8, src: *u8, n: usize) -> *u8 {
+                for (let i: usize = 0; i < n; i += 1) { dst[i] = src[i]; }
+                return dst;
+            }
+
+            fn copy(dst: *u8, src: *u8) -> *u8 { return memcpy(dst, src, 4); }
+        "};
+
+        let ir_with_disabled_builtins = |disabled_builtins| {
+            let mut global_scope = zrc_typeck::typeck::GlobalScope {
+                disabled_builtins: DisabledBuiltins::clone(&disabled_builtins),
+                ..zrc_typeck::typeck::GlobalScope::new()
+            };
+            let program = zrc_typeck::typeck::type_program(
+                &mut global_scope,
+                zrc_parser::parser::parse_program(source, "<test>")
+                    .expect("parsing should succeed"),
+            )
+            .expect("typeck should succeed");
+
+            ir_with_options(
+                source,
+                program,
+                &super::CodegenOptions {
+                    disabled_builtins,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // TEST: `memcpy` is the user's function, called like any other
+        let ir = ir_with_disabled_builtins(DisabledBuiltins::Named(vec!["memcpy".to_string()]));
+        assert!(
+            ir.contains("define ptr @memcpy(ptr %0, ptr %1, i64 %2) #0"),
+            "{ir}"
+        );
+        assert!(ir.contains("call ptr @memcpy("), "{ir}");
+        assert!(
+            ir.contains("attributes #0 = { \"no-builtin-memcpy\" }"),
+            "{ir}"
+        );
+
+        let ir = ir_with_disabled_builtins(DisabledBuiltins::All);
+        assert!(ir.contains("attributes #0 = { \"no-builtins\" }"), "{ir}");
+
+        // TEST: nothing is added without the flag
+        let ir = ir_with_disabled_builtins(DisabledBuiltins::None);
+        assert!(!ir.contains("no-builtin"), "{ir}");
+    }
+
+    #[test]
+    fn objects_embed_bitcode_when_requested() {
+        let source = "fn add(a: i32, b: i32) -> i32 { return a + b; }";
+
+        let object_with_embed_bitcode = |embed_bitcode| {
+            super::cg_program_to_buffer(
+                "zrc test runner",
+                "/fake/path",
+                "test.zr",
+                "zrc --fake-args",
+                source,
+                typed(source),
+                inkwell::targets::FileType::Object,
+                inkwell::OptimizationLevel::None,
+                inkwell::debug_info::DWARFEmissionKind::None,
+                &inkwell::targets::TargetTriple::create(TEST_TRIPLE),
+                "",
+                &super::CodegenOptions {
+                    embed_bitcode,
+                    ..Default::default()
+                },
+            )
+            .as_slice()
+            .to_vec()
+        };
+        let contains = |haystack: &[u8], needle: &[u8]| {
+            haystack
+                .windows(needle.len())
+                .any(|window| window == needle)
+        };
+
+        // TEST: the object has a `.llvmbc` section holding a bitcode file, which
+        // starts with the `BC 0xC0DE` magic
+        let object = object_with_embed_bitcode(true);
+        assert!(contains(&object, b".llvmbc"));
+        assert!(contains(&object, b"BC\xC0\xDE"));
+
+        // TEST: nothing is embedded without the flag
+        let object = object_with_embed_bitcode(false);
+        assert!(!contains(&object, b".llvmbc"));
+        assert!(!contains(&object, b"BC\xC0\xDE"));
+    }
+
+    #[test]
+    fn entry_wrapper_calls_the_user_main() {
+        let ir_with_entry_wrapper = |source: &str, entry_wrapper: bool| {
+            ir_with_options(
+                source,
+                typed(source),
+                &crate::CodegenOptions {
+                    entry_wrapper,
+                    ..Default::default()
+                },
+            )
+        };
+
+        // TEST: without the flag, the user's main is emitted as is
+        let ir = ir_with_entry_wrapper("fn main() -> i32 { return 0; }", false);
+        assert!(ir.contains("define i32 @main()"), "{ir}");
+        assert!(!ir.contains("__zrc_user_main"), "{ir}");
+
+        // TEST: with it, `main` has the C signature and calls the user's main,
+        // which is no longer exported
+        let ir = ir_with_entry_wrapper("fn main() -> i32 { return 0; }", true);
+        assert!(
+            ir.contains("define i32 @main(i32 %argc, ptr %argv)"),
+            "{ir}"
+        );
+        assert!(
+            ir.contains("define internal i32 @__zrc_user_main()"),
+            "{ir}"
+        );
+        assert!(ir.contains("%result = call i32 @__zrc_user_main()"), "{ir}");
+
+        // TEST: argc and argv are passed on, with argc widened to a usize
+        let ir = ir_with_entry_wrapper(
+            "fn main(argc: usize, argv: **u8) -> i32 { return 0; }",
+            true,
+        );
+        assert!(ir.contains("%argc_usize = zext i32 %argc to i"), "{ir}");
+        assert!(ir.contains(" %argc_usize, ptr %argv)"), "{ir}");
+    }
+
+    #[test]
+    fn referenced_extern_functions_are_undefined_symbols() {
+        let source = indoc! {"
+            fn external(x: i32);
+            fn unused_external();
+
+            fn defined() {}
+
+            fn test() {
+                external(1);
+                defined();
+            }
+        "};
+
+        // TEST: only `external` is both referenced and left undefined
+        assert_eq!(
+            super::cg_program_undefined_symbols(
+                "zrc test runner",
+                "/fake/path",
+                "test.zr",
+                "zrc --fake-args",
+                source,
+                typed(source),
+                OptimizationLevel::None,
+                &crate::get_native_triple(),
+                "",
+                &super::CodegenOptions::default(),
+            ),
+            vec!["external".to_string()]
+        );
+    }
 }
@@ -0,0 +1,180 @@
+//! Splitting LLVM IR into one module per function
+//!
+//! Large modules are hard to diff as a whole, so `--split-functions` writes
+//! every function definition to its own file. Each function is moved into a
+//! copy of the module in which every other function body is deleted, leaving
+//! only the declarations it needs to refer to its callees.
+
+use std::ptr;
+
+use inkwell::{
+    context::Context,
+    llvm_sys::{
+        core::{LLVMGetPoison, LLVMReplaceAllUsesWith, LLVMSetInitializer, LLVMTypeOf},
+        debuginfo::LLVMSetSubprogram,
+    },
+    memory_buffer::MemoryBuffer,
+    module::{Linkage, Module},
+    values::{AsValueRef, FunctionValue},
+};
+
+/// A module split into one module per function definition, printed as IR
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitModule {
+    /// The global variables of the module, along with a declaration of every
+    /// function
+    pub globals: String,
+    /// The name of every function definition and the module containing only
+    /// its body, in the order they are defined
+    pub functions: Vec<(String, String)>,
+}
+
+/// Turn `function` into a declaration by deleting its body
+fn delete_body(function: FunctionValue<'_>) {
+    let blocks = function.get_basic_blocks();
+
+    // detach every instruction from its users first, so the instructions can be
+    // erased in any order
+    for instruction in blocks.iter().flat_map(|block| block.get_instructions()) {
+        if !instruction.get_type().is_void_type() {
+            let value = instruction.as_value_ref();
+            // SAFETY: `value` is a valid instruction
+            let ty = unsafe { LLVMTypeOf(value) };
+            // SAFETY: `value` is not void, so `ty` can have a poison value
+            let poison = unsafe { LLVMGetPoison(ty) };
+            // SAFETY: `poison` has the same type as `value`
+            unsafe { LLVMReplaceAllUsesWith(value, poison) };
+        }
+    }
+
+    for block in &blocks {
+        for instruction in block.get_instructions().collect::<Vec<_>>() {
+            instruction.erase_from_basic_block();
+        }
+    }
+
+    for block in blocks {
+        // SAFETY: the block is empty, so nothing refers to it anymore
+        unsafe { block.delete() }.expect("basic block should be in a function");
+    }
+
+    // a declaration cannot be attached to the debug info of a definition
+    // SAFETY: a null subprogram removes the attachment
+    unsafe { LLVMSetSubprogram(function.as_value_ref(), ptr::null_mut()) };
+}
+
+/// Copy `module`, deleting the body of every function except `keep`
+fn module_with_only<'ctx>(module: &Module<'ctx>, keep: Option<&str>) -> Module<'ctx> {
+    let copy = module.clone();
+
+    for function in copy.get_functions() {
+        if function.count_basic_blocks() > 0 && function.get_name().to_str().ok() != keep {
+            delete_body(function);
+        }
+    }
+
+    copy
+}
+
+/// Split the IR of a module into one module per function definition.
+///
+/// Global variables are defined in [`SplitModule::globals`] and only declared
+/// in the module of each function. Private and internal globals, like string
+/// literals, cannot be declared across modules, so they are kept in every
+/// module that may use them.
+///
+/// # Errors
+/// Errors with LLVM's message if `ir` is not valid LLVM IR.
+pub fn split_llvm_ir_by_function(ir: &str) -> Result<SplitModule, String> {
+    let ctx = Context::create();
+    let module = ctx
+        .create_module_from_ir(MemoryBuffer::create_from_memory_range_copy(
+            ir.as_bytes(),
+            "split",
+        ))
+        .map_err(|error| error.to_string())?;
+
+    let functions = module
+        .get_functions()
+        .filter(|function| function.count_basic_blocks() > 0)
+        .map(|function| {
+            let name = function.get_name().to_string_lossy().into_owned();
+            let function_module = module_with_only(&module, Some(name.as_str()));
+
+            for global in function_module.get_globals().collect::<Vec<_>>() {
+                #[expect(clippy::wildcard_enum_match_arm)]
+                match global.get_linkage() {
+                    Linkage::Private | Linkage::Internal => {}
+                    // `llvm.global_ctors` and friends only belong in one module
+                    Linkage::Appending => {
+                        // SAFETY: nothing in the module refers to these arrays
+                        unsafe { global.delete() };
+                    }
+                    _ => {
+                        // SAFETY: a null initializer turns the global into a
+                        // declaration
+                        unsafe { LLVMSetInitializer(global.as_value_ref(), ptr::null_mut()) };
+                    }
+                }
+            }
+
+            (name, function_module.print_to_string().to_string())
+        })
+        .collect();
+
+    Ok(SplitModule {
+        globals: module_with_only(&module, None)
+            .print_to_string()
+            .to_string(),
+        functions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::split_llvm_ir_by_function;
+    use crate::test_utils::ir_of;
+
+    #[test]
+    fn every_function_definition_gets_its_own_module() {
+        let source = indoc! {"
+            let counter: i32 = 4;
+            fn external();
+
+            fn first() -> i32 { return counter; }
+            fn second() { external(); first(); }
+        "};
+
+        let ir = ir_of(source);
+
+        let split = split_llvm_ir_by_function(&ir).expect("IR should be valid");
+
+        // TEST: there are two function definitions, so two modules
+        let names: Vec<_> = split
+            .functions
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["first", "second"]);
+
+        // TEST: each module defines only its own function, and declares the
+        // rest of what it refers to
+        let (_, second) = &split.functions[1];
+        assert!(second.contains("define {} @second()"), "{second}");
+        assert!(second.contains("declare i32 @first()"), "{second}");
+        assert!(second.contains("declare {} @external()"), "{second}");
+
+        let (_, first) = &split.functions[0];
+        assert!(first.contains("@counter = external global i32"), "{first}");
+
+        // TEST: the global itself is defined in the shared module
+        assert!(
+            split.globals.contains("@counter = global i32 4"),
+            "{}",
+            split.globals
+        );
+        assert!(!split.globals.contains("define"), "{}", split.globals);
+    }
+}
@@ -38,16 +38,51 @@ use crate::{
 };
 
 /// Consists of the [`BasicBlock`]s to `br` to when encountering certain
-/// instructions. It is passed to [`cg_block`] to allow it to properly handle
-/// break and continue.
+/// instructions. [`cg_block`] is passed one for each enclosing loop, innermost
+/// last, to allow it to properly handle break and continue.
 #[derive(PartialEq, Eq, Debug, Clone)]
 #[expect(clippy::redundant_pub_crate)]
-pub(crate) struct LoopBreakaway<'ctx> {
+pub(crate) struct LoopBreakaway<'ctx, 'input> {
+    /// The label of the loop, which `break label;` and `continue label;` use
+    /// to refer to it from within nested loops.
+    label: Option<&'input str>,
     /// Points to the exit basic block.
     on_break: BasicBlock<'ctx>,
-    /// For `for` loops, points to the latch. For `while` loops, points to the
-    /// header.
-    on_continue: BasicBlock<'ctx>,
+    /// For `for` loops with a `post`, points to the latch. For `while` loops
+    /// and `for` loops without one, points to the header. Inside a `match`
+    /// outside of any loop, where only `break` is valid, this is [`None`].
+    on_continue: Option<BasicBlock<'ctx>>,
+}
+impl<'ctx, 'input> LoopBreakaway<'ctx, 'input> {
+    /// The breakaways inside of a loop: those of the `enclosing` loops, then
+    /// that of the loop itself.
+    fn nested(
+        enclosing: &[Self],
+        label: Option<&'input str>,
+        on_break: BasicBlock<'ctx>,
+        on_continue: Option<BasicBlock<'ctx>>,
+    ) -> Vec<Self> {
+        let mut breakaways = enclosing.to_vec();
+        breakaways.push(Self {
+            label,
+            on_break,
+            on_continue,
+        });
+        breakaways
+    }
+
+    /// Find what a `break` or `continue` with `label` jumps out of: the loop
+    /// with that label, or the innermost one if there is no label.
+    fn find<'b>(breakaways: &'b [Self], label: Option<&str>) -> &'b Self {
+        match label {
+            None => breakaways.last(),
+            Some(label) => breakaways
+                .iter()
+                .rev()
+                .find(|breakaway| breakaway.label == Some(label)),
+        }
+        .expect("a breakaway should exist all places `break` and `continue` are valid")
+    }
 }
 
 /// Process a vector of [`TypedStmt`]s (a block) and handle each statement.
@@ -57,7 +92,6 @@ pub(crate) struct LoopBreakaway<'ctx> {
 #[expect(
     clippy::too_many_lines,
     clippy::redundant_pub_crate,
-    clippy::ref_option,
     clippy::needless_pass_by_value
 )]
 pub(crate) fn cg_block<'ctx, 'input, 'a>(
@@ -66,7 +100,7 @@ pub(crate) fn cg_block<'ctx, 'input, 'a>(
     parent_scope: &'a CgScope<'input, 'ctx>,
     parent_lexical_block: Option<DILexicalBlock<'ctx>>,
     block: Spanned<BlockMetadata<'input>>,
-    breakaway: &Option<LoopBreakaway<'ctx>>,
+    breakaways: &[LoopBreakaway<'ctx, 'input>],
 ) -> Option<BasicBlock<'ctx>> {
     let mut scope = parent_scope.clone();
     let block_span = block.span();
@@ -112,17 +146,21 @@ pub(crate) fn cg_block<'ctx, 'input, 'a>(
             TypedStmtKind::SwitchCase {
                 scrutinee,
                 default,
+                default_binding,
                 cases,
+                exits_on_break,
             } => Some(switch::cg_switch_stmt(
                 cg,
                 bb,
                 &scope,
                 lexical_block,
-                breakaway,
+                breakaways,
                 stmt_span,
                 scrutinee.clone(),
                 default.clone(),
+                *default_binding,
                 cases.clone(),
+                *exits_on_break,
             )),
 
             TypedStmtKind::ExprStmt(expr) => {
@@ -136,7 +174,7 @@ pub(crate) fn cg_block<'ctx, 'input, 'a>(
                 bb,
                 &scope,
                 lexical_block,
-                breakaway,
+                breakaways,
                 cond.clone(),
                 then.clone(),
                 then_else.clone(),
@@ -148,7 +186,7 @@ pub(crate) fn cg_block<'ctx, 'input, 'a>(
                 &scope,
                 lexical_block,
                 block.clone().in_span(stmt_span),
-                breakaway,
+                breakaways,
             ),
 
             TypedStmtKind::ReturnStmt(Some(expr)) => {
@@ -173,27 +211,21 @@ pub(crate) fn cg_block<'ctx, 'input, 'a>(
                 None
             }
 
-            TypedStmtKind::ContinueStmt => {
+            TypedStmtKind::ContinueStmt(label) => {
                 cg.builder
                     .build_unconditional_branch(
-                        breakaway
-                            .as_ref()
-                            .expect("`breakaway` should exist all places `continue` is valid")
-                            .on_continue,
+                        LoopBreakaway::find(breakaways, *label)
+                            .on_continue
+                            .expect("`on_continue` should exist all places `continue` is valid"),
                     )
                     .expect("branch should generate successfully");
 
                 None
             }
 
-            TypedStmtKind::BreakStmt => {
+            TypedStmtKind::BreakStmt(label) => {
                 cg.builder
-                    .build_unconditional_branch(
-                        breakaway
-                            .as_ref()
-                            .expect("`breakaway` should exist all places `break` is valid")
-                            .on_break,
-                    )
+                    .build_unconditional_branch(LoopBreakaway::find(breakaways, *label).on_break)
                     .expect("branch should generate successfully");
 
                 None
@@ -207,43 +239,27 @@ pub(crate) fn cg_block<'ctx, 'input, 'a>(
                 declarations.clone(),
             )),
 
-            TypedStmtKind::ForStmt {
-                init,
-                cond,
-                post,
-                body,
-            } => Some(loops::cg_for_stmt(
+            TypedStmtKind::ForStmt { .. }
+            | TypedStmtKind::FourStmt(_)
+            | TypedStmtKind::WhileStmt(_, _)
+            | TypedStmtKind::DoWhileStmt(_, _) => Some(loops::cg_loop(
                 cg,
                 bb,
                 &scope,
                 lexical_block,
-                init.clone(),
-                cond.clone(),
-                post.clone(),
-                body.clone(),
-            )),
-            TypedStmtKind::FourStmt(body) => Some(loops::cg_four_stmt(
-                cg,
-                bb,
-                &scope,
-                lexical_block,
-                body.clone(),
-            )),
-
-            TypedStmtKind::WhileStmt(cond, body) => Some(loops::cg_while_stmt(
-                cg,
-                &scope,
-                lexical_block,
-                cond.clone(),
-                body.clone(),
+                breakaways,
+                None,
+                stmt.kind.value(),
             )),
 
-            TypedStmtKind::DoWhileStmt(body, cond) => Some(loops::cg_do_while_stmt(
+            TypedStmtKind::LabeledLoop(label, body) => Some(loops::cg_loop(
                 cg,
+                bb,
                 &scope,
                 lexical_block,
-                body.clone(),
-                cond.clone(),
+                breakaways,
+                Some(*label),
+                body.kind.value(),
             )),
         }
     })
@@ -268,4 +284,21 @@ mod tests {
             }
         "});
     }
+
+    #[test]
+    fn nested_blocks_generate_nested_lexical_scopes() {
+        // each block must get its own DILexicalBlock parented to the enclosing
+        // block's, so that the shadowed `x`s are in distinct scopes
+        cg_snapshot_test!(indoc! {"
+            fn test() {
+                let x: i32 = 1;
+                {
+                    let x: i32 = 2;
+                    {
+                        let x: i32 = 3;
+                    }
+                }
+            }
+        "});
+    }
 }
@@ -18,13 +18,13 @@ use crate::{
 };
 
 /// Code generates a switch statement
-#[expect(clippy::too_many_arguments, clippy::ref_option)]
+#[expect(clippy::too_many_arguments)]
 pub fn cg_if_stmt<'ctx, 'input, 'a>(
     cg: FunctionCtx<'ctx, 'a>,
     bb: BasicBlock<'ctx>,
     scope: &'a CgScope<'input, 'ctx>,
     lexical_block: Option<DILexicalBlock<'ctx>>,
-    breakaway: &Option<LoopBreakaway<'ctx>>,
+    breakaways: &[LoopBreakaway<'ctx, 'input>],
     cond: TypedExpr<'input>,
     then: Spanned<BlockMetadata<'input>>,
     then_else: Option<Spanned<BlockMetadata<'input>>>,
@@ -60,10 +60,17 @@ pub fn cg_if_stmt<'ctx, 'input, 'a>(
         .expect("conditional branch should generate successfully");
 
     cg.builder.position_at_end(then_bb);
-    let maybe_then_bb = cg_block(cg, then_bb, scope, lexical_block, then, breakaway);
+    let maybe_then_bb = cg_block(cg, then_bb, scope, lexical_block, then, breakaways);
 
     cg.builder.position_at_end(then_else_bb);
-    let maybe_then_else_bb = cg_block(cg, then_else_bb, scope, lexical_block, then_else, breakaway);
+    let maybe_then_else_bb = cg_block(
+        cg,
+        then_else_bb,
+        scope,
+        lexical_block,
+        then_else,
+        breakaways,
+    );
 
     match (maybe_then_bb, maybe_then_else_bb) {
         (None, None) => None,
@@ -2,7 +2,10 @@
 
 use inkwell::{basic_block::BasicBlock, debug_info::DILexicalBlock};
 use zrc_typeck::{
-    tast::{expr::TypedExpr, stmt::LetDeclaration},
+    tast::{
+        expr::TypedExpr,
+        stmt::{LetDeclaration, TypedStmtKind},
+    },
     typeck::BlockMetadata,
 };
 use zrc_utils::span::Spanned;
@@ -15,13 +18,90 @@ use crate::{
     stmt::{LoopBreakaway, cg_block},
 };
 
+/// Code generates a loop, which `break` and `continue` with `label` within it
+/// jump out of if it has one. Every other `break` and `continue` jumps out of
+/// the innermost loop as usual.
+///
+/// # Panics
+/// Panics if `stmt` is not a loop.
+#[expect(clippy::wildcard_enum_match_arm)]
+pub fn cg_loop<'ctx, 'input, 'a>(
+    cg: FunctionCtx<'ctx, 'a>,
+    bb: BasicBlock<'ctx>,
+    scope: &'a CgScope<'input, 'ctx>,
+    lexical_block: Option<DILexicalBlock<'ctx>>,
+    breakaways: &[LoopBreakaway<'ctx, 'input>],
+    label: Option<&'input str>,
+    stmt: &TypedStmtKind<'input>,
+) -> BasicBlock<'ctx> {
+    let enclosing = Enclosing { breakaways, label };
+    match stmt {
+        TypedStmtKind::ForStmt {
+            init,
+            cond,
+            post,
+            body,
+        } => cg_for_stmt(
+            cg,
+            bb,
+            scope,
+            lexical_block,
+            &enclosing,
+            init.clone(),
+            cond.clone(),
+            post.clone(),
+            body.clone(),
+        ),
+        TypedStmtKind::FourStmt(body) => {
+            cg_four_stmt(cg, bb, scope, lexical_block, &enclosing, body.clone())
+        }
+        TypedStmtKind::WhileStmt(cond, body) => cg_while_stmt(
+            cg,
+            scope,
+            lexical_block,
+            &enclosing,
+            cond.clone(),
+            body.clone(),
+        ),
+        TypedStmtKind::DoWhileStmt(body, cond) => cg_do_while_stmt(
+            cg,
+            scope,
+            lexical_block,
+            &enclosing,
+            body.clone(),
+            cond.clone(),
+        ),
+        _ => panic!("only loops can be labeled"),
+    }
+}
+
+/// The loops a loop is nested in, and the label of the loop itself
+struct Enclosing<'b, 'ctx, 'input> {
+    /// The breakaways of the enclosing loops, innermost last
+    breakaways: &'b [LoopBreakaway<'ctx, 'input>],
+    /// The label of the loop being generated
+    label: Option<&'input str>,
+}
+impl<'ctx, 'input> Enclosing<'_, 'ctx, 'input> {
+    /// The breakaways for the body of the loop, which exits to `on_break` and
+    /// starts its next iteration at `on_continue`
+    fn body_breakaways(
+        &self,
+        on_break: BasicBlock<'ctx>,
+        on_continue: BasicBlock<'ctx>,
+    ) -> Vec<LoopBreakaway<'ctx, 'input>> {
+        LoopBreakaway::nested(self.breakaways, self.label, on_break, Some(on_continue))
+    }
+}
+
 /// Code generates a for statement
 #[expect(clippy::too_many_arguments, clippy::box_collection)]
-pub fn cg_for_stmt<'ctx, 'input, 'a>(
+fn cg_for_stmt<'ctx, 'input, 'a>(
     cg: FunctionCtx<'ctx, 'a>,
     bb: BasicBlock<'ctx>,
     scope: &'a CgScope<'input, 'ctx>,
     lexical_block: Option<DILexicalBlock<'ctx>>,
+    enclosing: &Enclosing<'_, 'ctx, 'input>,
     init: Option<Box<Vec<Spanned<LetDeclaration<'input>>>>>,
     cond: Option<TypedExpr<'input>>,
     post: Option<TypedExpr<'input>>,
@@ -33,8 +113,9 @@ pub fn cg_for_stmt<'ctx, 'input, 'a>(
     // body. The body, where most of the body runs. Breaks to
     // the latch. `break` transfers to the exit by force and `continue` transfers to
     // the latch by force. The latch, where `post` runs and
-    // breaks back to the header The exit, which is the basic
-    // block we return.
+    // breaks back to the header. Without a `post` the latch would be empty, so it
+    // is left out and the body and `continue` go straight to the header instead.
+    // The exit, which is the basic block we return.
 
     // loops lie in an implicit subscope
     let mut scope = scope.clone();
@@ -49,7 +130,9 @@ pub fn cg_for_stmt<'ctx, 'input, 'a>(
 
     let header = cg.ctx.append_basic_block(cg.fn_value, "header");
     let body_bb = cg.ctx.append_basic_block(cg.fn_value, "body");
-    let latch = cg.ctx.append_basic_block(cg.fn_value, "latch");
+    let latch = post
+        .is_some()
+        .then(|| cg.ctx.append_basic_block(cg.fn_value, "latch"));
     let exit = cg.ctx.append_basic_block(cg.fn_value, "exit");
 
     // Branch to the header from the preheader.
@@ -80,28 +163,25 @@ pub fn cg_for_stmt<'ctx, 'input, 'a>(
         &scope,
         lexical_block,
         body,
-        &Some(LoopBreakaway {
-            on_break: exit,
-            on_continue: latch,
-        }),
+        &enclosing.body_breakaways(exit, latch.unwrap_or(header)),
     );
 
-    // The body breaks to latch
+    // The body breaks to latch, or to the header if there is no latch
     if body_bb.is_some() {
         cg.builder
-            .build_unconditional_branch(latch)
+            .build_unconditional_branch(latch.unwrap_or(header))
             .expect("branch should generate successfully");
     }
 
     // Latch runs post and then breaks right back to the header.
-    cg.builder.position_at_end(latch);
-    if let Some(post) = post {
+    if let (Some(latch), Some(post)) = (latch, post) {
+        cg.builder.position_at_end(latch);
         cg_expr(expr_cg, latch, post);
-    }
 
-    cg.builder
-        .build_unconditional_branch(header)
-        .expect("branch should generate successfully");
+        cg.builder
+            .build_unconditional_branch(header)
+            .expect("branch should generate successfully");
+    }
 
     cg.builder.position_at_end(exit);
 
@@ -110,11 +190,12 @@ pub fn cg_for_stmt<'ctx, 'input, 'a>(
 
 /// Code generates a four statement
 #[allow(clippy::needless_pass_by_value)]
-pub fn cg_four_stmt<'ctx, 'input, 'a>(
+fn cg_four_stmt<'ctx, 'input, 'a>(
     cg: FunctionCtx<'ctx, 'a>,
     bb: BasicBlock<'ctx>,
     scope: &'a CgScope<'input, 'ctx>,
     lexical_block: Option<DILexicalBlock<'ctx>>,
+    enclosing: &Enclosing<'_, 'ctx, 'input>,
     body: Spanned<BlockMetadata<'input>>,
 ) -> BasicBlock<'ctx> {
     let mut current_bb = bb;
@@ -150,10 +231,8 @@ pub fn cg_four_stmt<'ctx, 'input, 'a>(
             scope,
             lexical_block,
             body.clone(),
-            &Some(LoopBreakaway {
-                on_break: exit,
-                on_continue, // continue jumps to the next iteration's body
-            }),
+            // continue jumps to the next iteration's body
+            &enclosing.body_breakaways(exit, on_continue),
         );
 
         if let Some(next_bb) = next_bb {
@@ -173,10 +252,11 @@ pub fn cg_four_stmt<'ctx, 'input, 'a>(
 }
 
 /// Code generates a while statement
-pub fn cg_while_stmt<'ctx, 'input, 'a>(
+fn cg_while_stmt<'ctx, 'input, 'a>(
     cg: FunctionCtx<'ctx, 'a>,
     scope: &'a CgScope<'input, 'ctx>,
     lexical_block: Option<DILexicalBlock<'ctx>>,
+    enclosing: &Enclosing<'_, 'ctx, 'input>,
     cond: TypedExpr<'input>,
     body: Spanned<BlockMetadata<'input>>,
 ) -> BasicBlock<'ctx> {
@@ -217,10 +297,7 @@ pub fn cg_while_stmt<'ctx, 'input, 'a>(
         scope,
         lexical_block,
         body,
-        &Some(LoopBreakaway {
-            on_break: exit,
-            on_continue: header,
-        }),
+        &enclosing.body_breakaways(exit, header),
     );
 
     if body_bb.is_some() {
@@ -234,10 +311,11 @@ pub fn cg_while_stmt<'ctx, 'input, 'a>(
 }
 
 /// Code generates a do..while statement
-pub fn cg_do_while_stmt<'ctx, 'input, 'a>(
+fn cg_do_while_stmt<'ctx, 'input, 'a>(
     cg: FunctionCtx<'ctx, 'a>,
     scope: &'a CgScope<'input, 'ctx>,
     lexical_block: Option<DILexicalBlock<'ctx>>,
+    enclosing: &Enclosing<'_, 'ctx, 'input>,
     body: Spanned<BlockMetadata<'input>>,
     cond: TypedExpr<'input>,
 ) -> BasicBlock<'ctx> {
@@ -270,10 +348,7 @@ pub fn cg_do_while_stmt<'ctx, 'input, 'a>(
         scope,
         lexical_block,
         body,
-        &Some(LoopBreakaway {
-            on_break: exit,
-            on_continue: header,
-        }),
+        &enclosing.body_breakaways(exit, header),
     );
 
     if body_bb.is_some() {
@@ -302,7 +377,10 @@ mod tests {
 
     use indoc::indoc;
 
-    use crate::cg_snapshot_test;
+    use crate::{
+        cg_snapshot_test,
+        test_utils::{function_body, ir_of},
+    };
 
     #[test]
     fn unreachable_statement_generates_properly() {
@@ -378,6 +456,129 @@ mod tests {
                 "});
     }
 
+    #[test]
+    fn break_and_continue_deep_inside_a_loop_target_the_loop() {
+        let source = indoc! {"
+            fn get_bool() -> bool;
+
+            fn test(x: i32) {
+                while (get_bool()) {
+                    switch (x) {
+                        1 => if (get_bool()) { { break; } } else { { continue; } }
+                        default => {
+                            if (get_bool()) { continue; }
+                        }
+                    }
+                }
+            }
+        "};
+
+        // TEST: the loop is passed through every block, if and switch arm, so
+        // generating this does not panic
+        let ir = ir_of(source);
+
+        // TEST: `break` jumps to the exit block, and both `continue`s jump to
+        // the header along with the loop's entry and back edge
+        assert_eq!(ir.matches("br label %exit").count(), 1, "{ir}");
+        assert_eq!(ir.matches("br label %header").count(), 4, "{ir}");
+    }
+
+    #[test]
+    fn labeled_break_and_continue_target_the_labeled_loop() {
+        let source = indoc! {"
+            fn get_bool() -> bool;
+
+            fn test() {
+                outer: while (get_bool()) {
+                    while (get_bool()) {
+                        if (get_bool()) break outer;
+                        continue outer;
+                    }
+                }
+            }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: `break outer` jumps to the outer loop's exit, and `continue outer`
+        // to its header along with its entry and back edge. The inner loop's
+        // blocks are renamed by LLVM as their names are taken.
+        assert_eq!(ir.matches("br label %exit\n").count(), 1, "{ir}");
+        assert_eq!(ir.matches("br label %header\n").count(), 3, "{ir}");
+    }
+
+    #[test]
+    fn break_and_continue_in_desugared_loops_target_the_loop() {
+        let source = indoc! {"
+            enum Opt { Some: i32, None: u8 }
+            fn next() -> Opt;
+
+            fn for_in(values: [3]i32) {
+                for (x in values) {
+                    if (x == 1) continue;
+                    if (x == 2) break;
+                }
+            }
+
+            fn while_let() {
+                while let (Some: x = next()) {
+                    if (x == 1) continue;
+                    if (x == 2) break;
+                }
+            }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: a `for (x in values)` is a `for`, so `continue` jumps to the
+        // latch that steps to the next element, along with the end of the body
+        let for_in = function_body(&ir, "for_in");
+        assert_eq!(for_in.matches("br label %latch\n").count(), 2, "{ir}");
+        assert_eq!(for_in.matches("br label %exit\n").count(), 1, "{ir}");
+
+        // TEST: a `while let` is a `while (true)`, so `continue` jumps to the
+        // header that computes the next value, along with the entry and the end
+        // of the body. `break` jumps to the exit, along with the check of the
+        // variant
+        let while_let = function_body(&ir, "while_let");
+        assert_eq!(while_let.matches("br label %header\n").count(), 3, "{ir}");
+        assert_eq!(while_let.matches("br label %exit\n").count(), 2, "{ir}");
+    }
+
+    #[test]
+    fn for_loops_without_post_continue_to_the_header() {
+        let source = indoc! {"
+            fn get_int() -> i32;
+
+            fn test() {
+                for (let i = 0; i < get_int(); ) {
+                    if (i == get_int()) continue;
+                }
+            }
+        "};
+
+        let ir = ir_of(source);
+
+        // TEST: there is nothing for a latch to run, so no latch is generated
+        assert!(!ir.contains("latch"), "{ir}");
+
+        // TEST: `continue` and the end of the body jump straight to the header,
+        // along with the preheader
+        assert_eq!(ir.matches("br label %header\n").count(), 3, "{ir}");
+    }
+
+    #[test]
+    fn function_ending_in_an_infinite_loop_has_no_return() {
+        let source = "fn test() { while (true) {} }\n";
+
+        let ir = ir_of(source);
+
+        // TEST: the loop's exit block can never be reached, so it is terminated
+        // with `unreachable` instead of a `return`
+        assert!(!ir.contains("ret "), "{ir}");
+        assert!(ir.contains("unreachable"), "{ir}");
+    }
+
     #[test]
     fn switch_statements_generate_as_expected() {
         cg_snapshot_test!(indoc! {"
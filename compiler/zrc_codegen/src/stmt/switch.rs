@@ -1,10 +1,20 @@
 //! Code generation for switch statements
 
-use inkwell::{basic_block::BasicBlock, debug_info::DILexicalBlock};
-use zrc_typeck::{tast::expr::TypedExpr, typeck::BlockMetadata};
-use zrc_utils::span::{Span, Spannable};
+use inkwell::{
+    AddressSpace, IntPredicate,
+    basic_block::BasicBlock,
+    debug_info::DILexicalBlock,
+    types::FunctionType,
+    values::{BasicValueEnum, FunctionValue, PointerValue},
+};
+use zrc_typeck::{
+    tast::expr::{TypedExpr, TypedExprKind},
+    typeck::BlockMetadata,
+};
+use zrc_utils::span::{Span, Spannable, Spanned};
 
 use crate::{
+    align::build_aligned_store,
     bb::BasicBlockAnd,
     ctx::{BlockCtx, FunctionCtx},
     expr::cg_expr,
@@ -13,56 +23,218 @@ use crate::{
     unpack,
 };
 
+/// Get the C library function `name`, declaring it with `fn_type` if the
+/// module does not already declare it
+fn libc_function<'ctx>(
+    cg: FunctionCtx<'ctx, '_>,
+    name: &str,
+    fn_type: FunctionType<'ctx>,
+) -> FunctionValue<'ctx> {
+    cg.module
+        .get_function(name)
+        .unwrap_or_else(|| cg.module.add_function(name, fn_type, None))
+}
+
+/// Returns the length in bytes of the string literal `trigger`, a case of a
+/// switch over a string
+///
+/// # Panics
+/// Panics if `trigger` is not a string literal, which the type checker ensures.
+fn string_case_length(trigger: &TypedExpr<'_>) -> u64 {
+    let TypedExprKind::StringLiteral(literal) = trigger.kind.value() else {
+        panic!("every case of a switch over a string should be a string literal");
+    };
+    u64::try_from(literal.as_bytes().len()).expect("string literal length should fit in a u64")
+}
+
+/// Branch from `bb` to the block of the first case whose string is equal to
+/// `scrutinee`, or to `default_bb` if there is none.
+///
+/// The length of `scrutinee` is found once with `strlen`, and only the cases
+/// of that length are compared with `memcmp`, so it is never read past its
+/// end.
+fn cg_string_comparisons<'ctx>(
+    cg: FunctionCtx<'ctx, '_>,
+    bb: BasicBlock<'ctx>,
+    scrutinee: PointerValue<'ctx>,
+    default_bb: BasicBlock<'ctx>,
+    cases: &[(BasicBlock<'ctx>, BasicValueEnum<'ctx>, u64)],
+) {
+    let ptr_type = cg.ctx.ptr_type(AddressSpace::default());
+    let usize_type = cg.ctx.ptr_sized_int_type(
+        &cg.target_machine.get_target_data(),
+        Some(AddressSpace::default()),
+    );
+    let strlen = libc_function(cg, "strlen", usize_type.fn_type(&[ptr_type.into()], false));
+    let memcmp = libc_function(
+        cg,
+        "memcmp",
+        cg.ctx.i32_type().fn_type(
+            &[ptr_type.into(), ptr_type.into(), usize_type.into()],
+            false,
+        ),
+    );
+
+    cg.builder.position_at_end(bb);
+    let length = cg
+        .builder
+        .build_call(strlen, &[scrutinee.into()], "length")
+        .expect("call should generate successfully")
+        .try_as_basic_value()
+        .expect_basic("strlen should return an integer")
+        .into_int_value();
+
+    for (case_bb, literal, literal_length) in cases {
+        let compare_bb = cg.ctx.append_basic_block(cg.fn_value, "compare");
+        let next_bb = cg.ctx.append_basic_block(cg.fn_value, "next_case");
+
+        let literal_length = usize_type.const_int(*literal_length, false);
+        let length_matches = cg
+            .builder
+            .build_int_compare(IntPredicate::EQ, length, literal_length, "length_matches")
+            .expect("icmp should generate successfully");
+        cg.builder
+            .build_conditional_branch(length_matches, compare_bb, next_bb)
+            .expect("br should generate successfully");
+
+        cg.builder.position_at_end(compare_bb);
+        let difference = cg
+            .builder
+            .build_call(
+                memcmp,
+                &[scrutinee.into(), (*literal).into(), literal_length.into()],
+                "difference",
+            )
+            .expect("call should generate successfully")
+            .try_as_basic_value()
+            .expect_basic("memcmp should return an integer")
+            .into_int_value();
+        let matches = cg
+            .builder
+            .build_int_compare(
+                IntPredicate::EQ,
+                difference,
+                cg.ctx.i32_type().const_zero(),
+                "matches",
+            )
+            .expect("icmp should generate successfully");
+        cg.builder
+            .build_conditional_branch(matches, *case_bb, next_bb)
+            .expect("br should generate successfully");
+
+        cg.builder.position_at_end(next_bb);
+    }
+
+    cg.builder
+        .build_unconditional_branch(default_bb)
+        .expect("br should generate successfully");
+}
+
 /// Code generates a switch statement
-#[expect(clippy::too_many_arguments, clippy::ref_option)]
+///
+/// If `exits_on_break` is set, `break` in any case jumps past the switch rather
+/// than out of an enclosing loop, as is done for a `match` outside of any loop.
+/// A switch over a string is lowered to [comparisons of its contents with each
+/// case](cg_string_comparisons).
+#[expect(clippy::too_many_arguments)]
 pub fn cg_switch_stmt<'ctx, 'input, 'a>(
     cg: FunctionCtx<'ctx, 'a>,
     mut bb: BasicBlock<'ctx>,
     scope: &'a CgScope<'input, 'ctx>,
     lexical_block: Option<DILexicalBlock<'ctx>>,
-    breakaway: &Option<LoopBreakaway<'ctx>>,
+    breakaways: &[LoopBreakaway<'ctx, 'input>],
     stmt_span: Span,
     scrutinee: TypedExpr<'input>,
     default: BlockMetadata<'input>,
+    default_binding: Option<Spanned<&'input str>>,
     cases: Vec<(TypedExpr<'input>, BlockMetadata<'input>)>,
+    exits_on_break: bool,
 ) -> BasicBlock<'ctx> {
     let expr_cg = BlockCtx::new(cg, scope, lexical_block);
 
+    let is_string_switch = scrutinee.inferred_type.is_string();
     let scrutinee = unpack!(bb = cg_expr(expr_cg, bb, scrutinee));
 
     let default_bb = cg.ctx.append_basic_block(cg.fn_value, "default");
     let return_bb = cg.ctx.append_basic_block(cg.fn_value, "post");
 
+    let match_breakaways =
+        exits_on_break.then(|| LoopBreakaway::nested(breakaways, None, return_bb, None));
+    let breakaways = match_breakaways.as_deref().unwrap_or(breakaways);
+
     let cases: Vec<_> = cases
         .into_iter()
         .map(|(trigger, stmt)| {
+            let length = is_string_switch.then(|| string_case_length(&trigger));
             (
                 cg.ctx.append_basic_block(cg.fn_value, "case"),
                 unpack!(bb = cg_expr(expr_cg, bb, trigger)),
+                length,
                 stmt,
             )
         })
         .collect();
 
-    cg.builder
-        .build_switch(
-            scrutinee.into_int_value(),
+    if is_string_switch {
+        cg_string_comparisons(
+            cg,
+            bb,
+            scrutinee.into_pointer_value(),
             default_bb,
             &cases
                 .iter()
-                .map(|(bb, val, _)| (val.into_int_value(), *bb))
+                .map(|(bb, val, length, _)| {
+                    (
+                        *bb,
+                        *val,
+                        length.expect("string cases should have a length"),
+                    )
+                })
                 .collect::<Vec<_>>(),
-        )
-        .expect("switch should generate successfully");
+        );
+    } else {
+        cg.builder
+            .build_switch(
+                scrutinee.into_int_value(),
+                default_bb,
+                &cases
+                    .iter()
+                    .map(|(bb, val, _, _)| (val.into_int_value(), *bb))
+                    .collect::<Vec<_>>(),
+            )
+            .expect("switch should generate successfully");
+    }
 
     cg.builder.position_at_end(default_bb);
+
+    // `default(x) => ...` stores the already evaluated scrutinee into `x`
+    let mut default_scope = scope.clone();
+    if let Some(binding) = default_binding {
+        let entry_block_builder = cg.ctx.create_builder();
+        let first_bb = cg
+            .fn_value
+            .get_first_basic_block()
+            .expect("function should have at least one basic block");
+        match first_bb.get_first_instruction() {
+            Some(first_instruction) => entry_block_builder.position_before(&first_instruction),
+            None => entry_block_builder.position_at_end(first_bb),
+        }
+
+        let ptr = entry_block_builder
+            .build_alloca(scrutinee.get_type(), &format!("let_{}", binding.value()))
+            .expect("alloca should generate successfully");
+        build_aligned_store(&cg, ptr, scrutinee).expect("store should generate successfully");
+
+        default_scope.insert(binding.value(), ptr);
+    }
+
     let default_bb = cg_block(
         cg,
         default_bb,
-        scope,
+        &default_scope,
         lexical_block,
         default.in_span(stmt_span),
-        breakaway,
+        breakaways,
     );
     if default_bb.is_some() {
         cg.builder
@@ -70,7 +242,7 @@ pub fn cg_switch_stmt<'ctx, 'input, 'a>(
             .expect("br should generate successfully");
     }
 
-    for (case_bb, _, stmt) in cases {
+    for (case_bb, _, _, stmt) in cases {
         cg.builder.position_at_end(case_bb);
         let case_bb = cg_block(
             cg,
@@ -78,7 +250,7 @@ pub fn cg_switch_stmt<'ctx, 'input, 'a>(
             scope,
             lexical_block,
             stmt.in_span(stmt_span),
-            breakaway,
+            breakaways,
         );
 
         if case_bb.is_some() {
@@ -100,7 +272,7 @@ mod tests {
 
     use indoc::indoc;
 
-    use crate::cg_snapshot_test;
+    use crate::{cg_snapshot_test, test_utils::ir_of};
 
     #[test]
     fn enum_match_generates_as_expected() {
@@ -126,4 +298,109 @@ mod tests {
             }
         "});
     }
+
+    /// Generate the IR of `main` in `source` without any debug info, returning
+    /// the instructions of the basic block `label`
+    fn ir_of_block(source: &str, label: &str) -> String {
+        let ir = ir_of(source);
+        ir.split("\n\n")
+            .find(|block| block.starts_with(&format!("{label}:")))
+            .unwrap_or_else(|| panic!("block `{label}` should exist in:\n{ir}"))
+            .trim_end_matches("\n}")
+            .to_string()
+    }
+
+    #[test]
+    fn break_in_a_match_outside_of_a_loop_exits_the_match() {
+        let source = indoc! {"
+            enum VarInt { I32: i32, I64: i64 }
+            fn f() -> VarInt;
+            fn after();
+
+            fn main() {
+                match (f()) {
+                    I32: x => break;
+                    I64: y => {}
+                }
+                after();
+            }
+        "};
+
+        // TEST: the first arm's `break` jumps to the block right after the match
+        let case = ir_of_block(source, "case");
+        assert!(case.ends_with("br label %post"), "{case}");
+
+        let post = ir_of_block(source, "post");
+        assert!(post.contains("call {} @after()"), "{post}");
+    }
+
+    #[test]
+    fn string_switch_compares_lengths_then_contents() {
+        let source = indoc! {r#"
+            fn run();
+            fn stop();
+            fn unknown();
+
+            fn main(cmd: *u8) {
+                switch (cmd) {
+                    "run" => run();
+                    "stop" => stop();
+                    default => unknown();
+                }
+            }
+        "#};
+
+        // TEST: the length is found once, before any case is compared
+        let ir = ir_of(source);
+        assert_eq!(ir.matches("call i64 @strlen(ptr").count(), 1, "{ir}");
+        assert!(
+            ir.contains("%length_matches = icmp eq i64 %length, 3"),
+            "{ir}"
+        );
+
+        // TEST: the contents are only compared once the length matches
+        let compare = ir_of_block(source, "compare");
+        assert!(compare.contains("call i32 @memcmp(ptr"), "{compare}");
+        assert!(compare.contains(", i64 3)"), "{compare}");
+        assert!(
+            compare.ends_with("br i1 %matches, label %case, label %next_case"),
+            "{compare}"
+        );
+        let second = ir_of_block(source, "next_case");
+        assert!(
+            second.contains("%length_matches1 = icmp eq i64 %length, 4"),
+            "{second}"
+        );
+
+        // TEST: a string no case matches falls to the default case
+        let last = ir_of_block(source, "next_case1");
+        assert!(last.ends_with("br label %default"), "{last}");
+        let default = ir_of_block(source, "default");
+        assert!(default.contains("call {} @unknown()"), "{default}");
+    }
+
+    #[test]
+    fn break_and_continue_in_a_match_inside_a_loop_refer_to_the_loop() {
+        let source = indoc! {"
+            enum VarInt { I32: i32, I64: i64 }
+            fn f() -> VarInt;
+
+            fn main() {
+                while (true) {
+                    match (f()) {
+                        I32: x => break;
+                        I64: y => continue;
+                    }
+                }
+            }
+        "};
+
+        // TEST: `break` leaves the loop, not just the match
+        let case = ir_of_block(source, "case");
+        assert!(case.ends_with("br label %exit"), "{case}");
+
+        // TEST: `continue` starts the next iteration
+        let case = ir_of_block(source, "case1");
+        assert!(case.ends_with("br label %header"), "{case}");
+    }
 }
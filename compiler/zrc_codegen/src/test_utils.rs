@@ -19,11 +19,61 @@
 //!
 //! Read any test for an example of how they flow. We recommend `cargo-insta` be
 //! installed so you can `cargo insta review` any changed snapshots.
+//!
+//! Tests which only care about a few instructions use [`ir_of`] instead, and
+//! assert on the parts of the IR they are about.
+
+use inkwell::{debug_info::DWARFEmissionKind, targets::TargetTriple};
+use zrc_typeck::tast::stmt::TypedDeclaration;
+use zrc_utils::span::Spanned;
+
+use crate::CodegenOptions;
 
 /// Creates a snapshot test given a valid input program
+///
+/// If an [`OptimizationLevel`](inkwell::OptimizationLevel) is passed, the
+/// module is run through the optimization pipeline for that level before being
+/// snapshotted. Otherwise no optimization pipeline is run at all, not even the
+/// one for [`OptimizationLevel::None`](inkwell::OptimizationLevel::None).
 #[macro_export]
 macro_rules! cg_snapshot_test {
     ($source:expr) => {
+        $crate::cg_snapshot_test!(@snapshot $source, |typed, debug_level, triple, options| {
+            $crate::program::cg_program_to_string_without_optimization(
+                "zrc test runner",
+                "/fake/path",
+                "test.zr",
+                // do not use real args because the text executables have a hash in their
+                // name and this would mess up snapshots
+                "zrc --fake-args",
+                $source,
+                typed,
+                debug_level,
+                triple,
+                "",
+                options,
+            )
+        })
+    };
+    ($source:expr, $optimization_level:expr) => {
+        $crate::cg_snapshot_test!(@snapshot $source, |typed, debug_level, triple, options| {
+            $crate::program::cg_program_to_string(
+                "zrc test runner",
+                "/fake/path",
+                "test.zr",
+                // see above
+                "zrc --fake-args",
+                $source,
+                typed,
+                $optimization_level,
+                debug_level,
+                triple,
+                "",
+                options,
+            )
+        })
+    };
+    (@snapshot $source:expr, $generate:expr) => {
         let mut __zrc_codegen_test_gs = ::zrc_typeck::typeck::GlobalScope::new();
         let __zrc_codegen_typed = ::zrc_typeck::typeck::type_program(
             &mut __zrc_codegen_test_gs,
@@ -32,18 +82,11 @@ macro_rules! cg_snapshot_test {
         )
         .expect("typeck should succeed");
 
-        let resulting_ir = $crate::program::cg_program_to_string_without_optimization(
-            "zrc test runner",
-            "/fake/path",
-            "test.zr",
-            // do not use real args because the text executables have a hash in their name and
-            // this would mess up snapshots
-            "zrc --fake-args",
-            $source,
+        let resulting_ir = ($generate)(
             __zrc_codegen_typed,
             ::inkwell::debug_info::DWARFEmissionKind::Full,
             &$crate::get_native_triple(),
-            "",
+            &$crate::CodegenOptions::default(),
         );
 
         insta::with_settings!({
@@ -53,3 +96,70 @@ macro_rules! cg_snapshot_test {
         });
     };
 }
+
+/// The target the IR of [`ir_of`] is generated for. Tests that look at the sizes
+/// of pointers and `usize` in the IR would otherwise depend on the host they
+/// run on, so a fixed 64-bit target is used instead of the native one.
+pub const TEST_TRIPLE: &str = "x86_64-unknown-linux-gnu";
+
+/// Parse and type check `source`
+///
+/// # Panics
+/// Panics if `source` does not parse or type check.
+#[must_use]
+pub fn typed(source: &str) -> Vec<Spanned<TypedDeclaration<'_>>> {
+    zrc_typeck::typeck::type_program(
+        &mut zrc_typeck::typeck::GlobalScope::new(),
+        zrc_parser::parser::parse_program(source, "<test>").expect("parsing should succeed"),
+    )
+    .expect("typeck should succeed")
+}
+
+/// Generate `program`, type checked from `source`, with `options` and return
+/// its IR. See [`ir_of`].
+#[must_use]
+pub fn ir_with_options(
+    source: &str,
+    program: Vec<Spanned<TypedDeclaration<'_>>>,
+    options: &CodegenOptions,
+) -> String {
+    crate::program::cg_program_to_string_without_optimization(
+        "zrc test runner",
+        "/fake/path",
+        "test.zr",
+        "zrc --fake-args",
+        source,
+        program,
+        DWARFEmissionKind::None,
+        &TargetTriple::create(TEST_TRIPLE),
+        "",
+        options,
+    )
+}
+
+/// Type check and generate `source` and return its IR, for tests that look
+/// for particular instructions rather than snapshotting the whole module.
+///
+/// No optimization pipeline is run and no debug info is emitted, so
+/// instructions are not followed by `!dbg` attachments.
+///
+/// # Panics
+/// Panics if `source` does not parse or type check.
+#[must_use]
+pub fn ir_of(source: &str) -> String {
+    ir_with_options(source, typed(source), &CodegenOptions::default())
+}
+
+/// The definition of the function `name` in `ir`, from its name up to (but not
+/// including) its closing brace
+///
+/// # Panics
+/// Panics if `ir` does not define `name`.
+#[must_use]
+pub fn function_body<'ir>(ir: &'ir str, name: &str) -> &'ir str {
+    let start = ir
+        .find(&format!("@{name}("))
+        .unwrap_or_else(|| panic!("`{name}` should be defined in:\n{ir}"));
+    let body = &ir[start..];
+    &body[..body.find("\n}").unwrap_or(body.len())]
+}
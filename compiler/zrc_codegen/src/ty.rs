@@ -171,7 +171,19 @@ pub fn llvm_basic_type<'ctx: 'a, 'a>(
                 }),
             )
         }
-        Type::Fn(_) => panic!("function is not a basic type"),
+        // functions are only ever used through pointers, so a function type
+        // takes the size of a pointer to it
+        Type::Fn(_) => (
+            ctx.ctx()
+                .ptr_type(AddressSpace::default())
+                .as_basic_type_enum(),
+            ctx.dbg_builder().map(|dbg_builder| {
+                dbg_builder
+                    .create_basic_type(&ty.to_string(), 0, 0, 0)
+                    .expect("basic type should be valid")
+                    .as_type()
+            }),
+        ),
         Type::Opaque(name) => {
             panic!("opaque type '{name}' reached code generation, should be resolved in typeck")
         }
@@ -179,6 +179,17 @@ where
     /// or if the provided piped source is required but not provided.
     #[must_use]
     pub fn print_json(&self) -> String {
+        self.print_json_remapped(ToString::to_string)
+    }
+
+    /// Print this diagnostic as JSON like [`Self::print_json`], rendering the
+    /// path of every span through `remap_path`.
+    ///
+    /// # Panics
+    /// This function may panic if the diagnostic contains non-serializable
+    /// data.
+    #[must_use]
+    pub fn print_json_remapped(&self, remap_path: impl Fn(&str) -> String) -> String {
         serde_json::to_string(&serde_json::json!({
             "severity": self.severity.to_string(),
             "code": self.kind.value().error_code(),
@@ -187,7 +198,7 @@ where
                 serde_json::json!({
                     "message": label.kind.to_string(),
                     "span": {
-                        "file_name": label.kind.span().file_name(),
+                        "file_name": remap_path(label.kind.span().file_name()),
                         "start": label.kind.span().start(),
                         "end": label.kind.span().end(),
                     }
@@ -208,43 +219,62 @@ where
     /// buffer fails.
     #[must_use]
     pub fn print(&self, piped_source: Option<&str>) -> String {
+        self.print_remapped(piped_source, ToString::to_string)
+    }
+
+    /// Convert this [`Diagnostic`] to a printable string like [`Self::print`],
+    /// rendering the path of each source file through `remap_path`. The
+    /// sources are still read from their original paths.
+    ///
+    /// Every label is shown in the file its span is in, which may not be the
+    /// file of the diagnostic itself.
+    ///
+    /// # Panics
+    /// This function may panic if the span is invalid or if writing to the
+    /// buffer fails.
+    #[must_use]
+    pub fn print_remapped(
+        &self,
+        piped_source: Option<&str>,
+        remap_path: impl Fn(&str) -> String,
+    ) -> String {
         let span = self.kind.span();
         let message = self.kind.to_string();
 
-        // read the source from the path inside of the span. if it is <stdin>, use
-        // the provided piped source.
-        let (source, path) = match span.file_name() {
-            "/dev/<stdin>" => (
-                piped_source
-                    .expect("piped source must be provided for <stdin>")
-                    .to_string(),
-                "<stdin>",
-            ),
-            "<unknown>" => (
-                piped_source
-                    .expect("piped source must be provided for <unknown>")
-                    .to_string(),
-                "<unknown>",
-            ),
-            path => {
-                let source = std::fs::read_to_string(path)
-                    .unwrap_or_else(|_| panic!("failed to read source file {path} for diagnostic"));
-                (source, path)
+        // every file a span points into, with the path shown for it and its
+        // source, so labels in other files (such as an included one) are
+        // rendered against the right source
+        let mut files: Vec<(&str, String, String)> = Vec::new();
+        let mut source_id = |file_name| {
+            if let Some((_, id, _)) = files.iter().find(|(name, _, _)| *name == file_name) {
+                return id.clone();
             }
+            let (source, path) = read_source(file_name, piped_source);
+            let id = remap_path(path);
+            files.push((file_name, id.clone(), source));
+            id
         };
 
-        // Create ariadne report using (filename, range) as the span type
-        let mut report = Report::build(
-            self.severity.to_report_kind(),
-            (path, span.start()..span.end()),
-        )
-        .with_code(self.kind.value().error_code())
-        .with_message(message)
-        .with_labels(self.labels.iter().map(|label| {
-            Label::new((path, label.kind.span().start()..label.kind.span().end()))
+        let primary = (source_id(span.file_name()), span.start()..span.end());
+        let labels = self
+            .labels
+            .iter()
+            .map(|label| {
+                let label_span = label.kind.span();
+                Label::new((
+                    source_id(label_span.file_name()),
+                    label_span.start()..label_span.end(),
+                ))
                 .with_message(label.kind.to_string())
                 .with_color(label.severity.color())
-        }));
+            })
+            .collect::<Vec<_>>();
+
+        // Create ariadne report using (filename, range) as the span type
+        let mut report = Report::build(self.severity.to_report_kind(), primary)
+            .with_code(self.kind.value().error_code())
+            .with_message(message)
+            .with_labels(labels);
 
         report.with_notes(
             self.notes
@@ -264,7 +294,10 @@ where
         // Write report to string
         let mut buffer = Vec::new();
         report
-            .write((path, ariadne::Source::from(source)), &mut buffer)
+            .write(
+                ariadne::sources(files.into_iter().map(|(_, id, source)| (id, source))),
+                &mut buffer,
+            )
             .expect("failed to write diagnostic");
 
         String::from_utf8(buffer).expect("diagnostic output should be valid UTF-8")
@@ -321,6 +354,119 @@ where
 {
 }
 
+/// Reorder `diagnostics` so the diagnostics in each file are next to each other.
+///
+/// Files keep the order their first diagnostic was reported in, and the
+/// diagnostics within a file keep the order they were reported in.
+pub fn group_by_file(diagnostics: &mut [Diagnostic]) {
+    let mut files: Vec<&'static str> = Vec::new();
+    for diagnostic in diagnostics.iter() {
+        let file_name = diagnostic.kind.span().file_name();
+        if !files.contains(&file_name) {
+            files.push(file_name);
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| {
+        files
+            .iter()
+            .position(|file_name| *file_name == diagnostic.kind.span().file_name())
+    });
+}
+
+/// The number of diagnostics of each [`Severity`] a compilation emitted,
+/// displayed like `3 warnings, 1 error emitted` to summarize them at its end
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DiagnosticCounts {
+    /// The number of warnings
+    pub warnings: usize,
+    /// The number of errors
+    pub errors: usize,
+}
+impl DiagnosticCounts {
+    /// Count the diagnostics of each severity in `diagnostics`
+    pub fn tally<'a>(diagnostics: impl IntoIterator<Item = &'a Diagnostic>) -> Self {
+        diagnostics
+            .into_iter()
+            .fold(Self::default(), |counts, diagnostic| {
+                match diagnostic.severity {
+                    Severity::Error => Self {
+                        errors: counts.errors + 1,
+                        ..counts
+                    },
+                    Severity::Warning => Self {
+                        warnings: counts.warnings + 1,
+                        ..counts
+                    },
+                }
+            })
+    }
+
+    /// Returns whether no diagnostics were emitted
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.warnings == 0 && self.errors == 0
+    }
+
+    /// Returns whether compilation failed, either because of an error or, if
+    /// `deny_warnings` is set, because of a warning
+    #[must_use]
+    pub const fn fails(&self, deny_warnings: bool) -> bool {
+        self.errors > 0 || (deny_warnings && self.warnings > 0)
+    }
+}
+impl Display for DiagnosticCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |count: usize, noun: &str| {
+            if count == 1 {
+                format!("{count} {noun}")
+            } else {
+                format!("{count} {noun}s")
+            }
+        };
+        let counts = [(self.warnings, "warning"), (self.errors, "error")]
+            .into_iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, noun)| plural(count, noun))
+            .collect::<Vec<_>>();
+
+        if counts.is_empty() {
+            write!(f, "no diagnostics emitted")
+        } else {
+            write!(f, "{} emitted", counts.join(", "))
+        }
+    }
+}
+
+/// Read the source of the file `file_name` to render a diagnostic in it,
+/// returning it with the path to show for the file. `<stdin>` and `<unknown>`
+/// use `piped_source` instead.
+///
+/// # Panics
+/// Panics if the file cannot be read, or if `piped_source` is needed but not
+/// provided.
+fn read_source<'a>(file_name: &'a str, piped_source: Option<&str>) -> (String, &'a str) {
+    match file_name {
+        "/dev/<stdin>" => (
+            piped_source
+                .expect("piped source must be provided for <stdin>")
+                .to_string(),
+            "<stdin>",
+        ),
+        "<unknown>" => (
+            piped_source
+                .expect("piped source must be provided for <unknown>")
+                .to_string(),
+            "<unknown>",
+        ),
+        path => {
+            let source = std::fs::read_to_string(path)
+                .unwrap_or_else(|_| panic!("failed to read source file {path} for diagnostic"));
+            (source, path)
+        }
+    }
+}
+
 /// A diagnostic message produced by the Zirco compiler
 pub type Diagnostic = GenericDiagnostic<DiagnosticKind, LabelKind, NoteKind, HelpKind>;
 
@@ -366,4 +512,110 @@ mod tests {
         assert!(output.contains("Error"));
         assert!(output.contains("invalid token"));
     }
+
+    #[test]
+    fn print_json_remapped_rewrites_label_paths() {
+        let diagnostic = Diagnostic::error(spanned!(
+            4,
+            DiagnosticKind::InvalidToken,
+            5,
+            "/home/alice/main.zr"
+        ))
+        .with_label(GenericLabel::error(spanned!(
+            4,
+            LabelKind::InvalidToken,
+            5,
+            "/home/alice/main.zr"
+        )));
+        let output = diagnostic.print_json_remapped(|path| path.replace("/home/alice", "/src"));
+
+        assert!(output.contains(r#""file_name":"/src/main.zr""#), "{output}");
+    }
+
+    #[test]
+    fn labels_are_rendered_in_their_own_file() {
+        let directory = std::env::temp_dir().join("zrc_diagnostics_multi_file");
+        std::fs::create_dir_all(&directory).expect("creating the directory should succeed");
+        let main_path = directory.join("main.zr");
+        let other_path = directory.join("other.zr");
+        std::fs::write(&main_path, "fn f() {}\n").expect("writing main.zr should succeed");
+        std::fs::write(&other_path, "fn g() {}\nfn f(x: i32) {}\n")
+            .expect("writing other.zr should succeed");
+        let main_file: &'static str =
+            Box::leak(main_path.to_string_lossy().into_owned().into_boxed_str());
+        let other_file: &'static str =
+            Box::leak(other_path.to_string_lossy().into_owned().into_boxed_str());
+
+        let diagnostic = Diagnostic::error(spanned!(
+            13,
+            DiagnosticKind::IdentifierAlreadyInUse("f".to_string()),
+            14,
+            other_file
+        ))
+        .with_label(GenericLabel::error(spanned!(
+            13,
+            LabelKind::IdentifierAlreadyInUse("f".to_string()),
+            14,
+            other_file
+        )))
+        .with_label(GenericLabel::note(spanned!(
+            3,
+            LabelKind::LocalDeclaredHere("f".to_string()),
+            4,
+            main_file
+        )));
+        let output = diagnostic.print(None);
+
+        // TEST: each label is shown at its line in its own file, including the
+        // one in a different file than the diagnostic
+        assert!(output.contains(&format!("{other_file}:2:4")), "{output}");
+        assert!(output.contains(&format!("{main_file}:1:4")), "{output}");
+    }
+
+    #[test]
+    fn diagnostics_are_grouped_by_file() {
+        let mut diagnostics = vec![
+            Diagnostic::error(spanned!(0, DiagnosticKind::InvalidToken, 1, "a.zr")),
+            Diagnostic::error(spanned!(2, DiagnosticKind::InvalidToken, 3, "b.zr")),
+            Diagnostic::error(spanned!(4, DiagnosticKind::InvalidToken, 5, "a.zr")),
+        ];
+        group_by_file(&mut diagnostics);
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| {
+                    let span = diagnostic.kind.span();
+                    (span.file_name(), span.start())
+                })
+                .collect::<Vec<_>>(),
+            vec![("a.zr", 0), ("a.zr", 4), ("b.zr", 2)]
+        );
+    }
+
+    #[test]
+    fn diagnostic_counts_are_tallied_by_severity() {
+        let warning = || Diagnostic::warning(spanned_test!(0, DiagnosticKind::InvalidToken, 1));
+        let error = || Diagnostic::error(spanned_test!(0, DiagnosticKind::InvalidToken, 1));
+
+        let counts = DiagnosticCounts::tally(&[warning(), error(), warning(), warning()]);
+        assert_eq!(
+            counts,
+            DiagnosticCounts {
+                warnings: 3,
+                errors: 1
+            }
+        );
+        assert_eq!(counts.to_string(), "3 warnings, 1 error emitted");
+        assert!(counts.fails(false));
+
+        let counts = DiagnosticCounts::tally(&[warning()]);
+        assert_eq!(counts.to_string(), "1 warning emitted");
+        assert!(!counts.fails(false));
+        assert!(counts.fails(true));
+
+        let counts = DiagnosticCounts::tally(&[]);
+        assert!(counts.is_empty());
+        assert!(!counts.fails(true));
+    }
 }
@@ -25,6 +25,8 @@ pub enum DiagnosticKind {
     UnknownEscapeSequence,
     #[error("JavaScript user detected")]
     JavascriptUserDetected,
+    #[error("malformed number literal `{0}`")]
+    MalformedNumberLiteral(String),
 
     // PARSER ERRORS
     /// Generic parser error
@@ -36,6 +38,8 @@ pub enum DiagnosticKind {
     UnrecognizedToken(String),
     #[error("unexpected extra token `{0}`")]
     ExtraToken(String),
+    #[error("missing semicolon before `{0}`")]
+    MissingSemicolon(String),
 
     // TYPE CHECKER ERRORS
     #[error("unable to resolve `{0}` to a type")]
@@ -118,10 +122,8 @@ pub enum DiagnosticKind {
     GlobalInitializerMustBeConstant,
     #[error("match on non-enum type `{0}`")]
     MatchOnNonEnum(String),
-    #[error("there must be exactly one match arm for every enum variant")]
-    MatchCaseCountMismatch,
-    #[error("there must be a match arm for every enum variant")]
-    NonExhaustiveMatchCases,
+    #[error("match does not handle {0}")]
+    NonExhaustiveMatch(String),
     #[error("main() function must have return type `i32`, got `{0}`")]
     MainFunctionMustReturnI32(String),
     #[error(
@@ -136,6 +138,133 @@ pub enum DiagnosticKind {
     InvalidNumberLiteral(String),
     #[error("multiple default cases found")]
     MultipleDefaultCases,
+    #[error("`{builtin}` requires the target type to be {relation} than `{from}`, got `{to}`")]
+    InvalidIntegerConversion {
+        builtin: String,
+        from: String,
+        to: String,
+        relation: String,
+    },
+    #[error("unknown attribute `{0}`")]
+    UnknownAttribute(String),
+    #[error("attribute `{0}` specified more than once")]
+    DuplicateAttribute(String),
+    #[error("invalid arguments to attribute `{attribute}`: expected {expected}")]
+    InvalidAttributeArguments { attribute: String, expected: String },
+    #[error("cannot cast `{from}` to `{to}` because their layouts differ at field `{field}`")]
+    StructLayoutMismatch {
+        from: String,
+        to: String,
+        field: String,
+    },
+    #[error("`switch` on `{0}` has no cases, so it does not handle every value")]
+    EmptySwitch(String),
+    #[error("use of deprecated function `{0}`")]
+    DeprecatedFunction(String),
+    #[error("unknown `cfg` key `{0}`")]
+    UnknownCfgKey(String),
+    #[error("`{to}` is too small to hold the tag of an enum with {variants} variants")]
+    EnumTagDoesNotFit {
+        from: String,
+        to: String,
+        variants: usize,
+    },
+    #[error("`{0}` is used before the assignment its type is inferred from")]
+    UsedBeforeTypeInferred(String),
+    #[error("expected the first parameter of a method to be `self` or `*self`, found `{0}`")]
+    InvalidMethodReceiver(String),
+    #[error("methods can only be defined on structs, but `{0}` is not a struct")]
+    MethodsOnNonStruct(String),
+    #[error("constructor function `{0}` must take no parameters and return nothing")]
+    InvalidConstructorSignature(String),
+    #[error("index {index} is out of bounds for an array of length {len}")]
+    ConstantIndexOutOfBounds { index: String, len: u64 },
+    #[error("enum `{ty}` has no variant `{variant}`")]
+    UnknownEnumVariant { ty: String, variant: String },
+    #[error("constant expression overflows `{0}`")]
+    ConstantOverflow(String),
+    #[error("`noalias` can only be applied to pointer parameters, not `{0}`")]
+    NoaliasOnNonPointer(String),
+    #[error("`{0}` is passed to more than one `noalias` parameter")]
+    AliasedNoaliasArguments(String),
+    #[error("the length of an array repeat expression must be a constant, non-negative integer")]
+    NonConstantArrayRepeatCount,
+    #[error("{0} by a constant zero")]
+    DivisionByZero(String),
+    #[error("this condition is always `{0}`")]
+    ConstantCondition(String),
+    #[error("this function returns both `{0}` and `{1}`")]
+    ConflictingReturnTypes(String, String),
+    #[error("{0}")]
+    CompileError(String),
+    #[error("{0}")]
+    CompileWarning(String),
+    #[error("variant `{variant}` has the same discriminant `{discriminant}` as `{other}`")]
+    DuplicateEnumDiscriminant {
+        variant: String,
+        other: String,
+        discriminant: i128,
+    },
+    #[error("discriminant `{0}` does not fit in a 64-bit integer")]
+    EnumDiscriminantOutOfRange(i128),
+    #[error("`{to}` is too small to hold the discriminant `{discriminant}` of `{from}`")]
+    EnumDiscriminantDoesNotFit {
+        from: String,
+        to: String,
+        discriminant: i128,
+    },
+    #[error("the argument to `comptime` parameter `{0}` must be a constant")]
+    ComptimeArgumentNotConstant(String),
+    #[error("`comptime` parameters must be integers or `bool`, not `{0}`")]
+    InvalidComptimeParameterType(String),
+    #[error("`{0}` has `comptime` parameters, so it must have a body")]
+    ComptimeParametersWithoutBody(String),
+    #[error("function types cannot have `comptime` parameters")]
+    ComptimeParameterInFunctionType,
+    #[error("returning a pointer to the local variable `{0}`")]
+    ReturnsAddressOfLocal(String),
+    #[error("`{0}` cannot be compared to zero, so it cannot be the left side of `?:`")]
+    ElvisValueNotTestable(String),
+    #[error("attribute `{attribute}` cannot be combined with `{other}`")]
+    ConflictingAttributes { attribute: String, other: String },
+    #[error("every case of a `switch` over a string must be a string literal")]
+    StringSwitchCaseNotLiteral,
+    #[error("cast from `{from}` to `{to}` reinterprets a struct as one of a different size")]
+    PointerCastBetweenDifferentlySizedStructs { from: String, to: String },
+    #[error("no enclosing loop is labeled `{0}`")]
+    UnknownLoopLabel(String),
+    #[error("`{0}` is not an enum, so it has no variants")]
+    VariantOfNonEnum(String),
+    #[error("variant `{variant}` holds a `{ty}`, so it must be given a value")]
+    EnumVariantHoldsValue { variant: String, ty: String },
+    #[error("a `{ty}` can never match `{pattern}`")]
+    MatchPatternMismatch { pattern: String, ty: String },
+    #[error("this match arm can never be reached")]
+    UnreachableMatchArm,
+    #[error("the `{0}` branch of this `if` is empty")]
+    EmptyBranch(String),
+    #[error("unreachable statement")]
+    UnreachableStatement,
+    #[error("unused variable `{0}`")]
+    UnusedVariable(String),
+    #[error("shift by {0} is not less than the width of `{1}`")]
+    ShiftAmountTooLarge(String, String),
+    #[error("global variable `{0}` cannot be used in its own initializer")]
+    GlobalInitializerReferencesItself(String),
+    #[error("cannot `switch` over a `{0}`, only over an integer, a `bool` or a string")]
+    InvalidSwitchScrutinee(String),
+    #[error("a case of a `switch` over a string cannot contain a NUL byte")]
+    StringSwitchCaseContainsNul,
+    #[error("shift by negative amount {0}")]
+    NegativeShiftAmount(String),
+    #[error("`{0}` has the same fields as `{1}`, so their methods cannot be told apart")]
+    MethodsOnSameShapedTypes(String, String),
+    #[error("`{0}` has `comptime` parameters, so it can only be called directly")]
+    ComptimeFunctionNotCalled(String),
+    #[error("the return type of `{0}` is used before it is inferred")]
+    ReturnTypeNotInferred(String),
+    #[error("every case of a `switch` over an enum must be one of its variants")]
+    EnumSwitchCaseNotVariant,
 
     // PREPROCESSOR ERRORS
     #[error("unterminated include directive")]
@@ -162,14 +291,25 @@ impl DiagnosticKind {
     pub fn error_in(self, span: Span) -> Diagnostic {
         Diagnostic::error(self.in_span(span))
     }
+
+    /// Create a [warning] diagnostic in a given [`Span`].
+    ///
+    /// [warning]: [`Severity::Warning`]
+    #[must_use]
+    #[inline]
+    pub fn warning_in(self, span: Span) -> Diagnostic {
+        Diagnostic::warning(self.in_span(span))
+    }
 }
 impl ErrorCode for DiagnosticKind {
+    #[expect(clippy::too_many_lines)]
     fn error_code(&self) -> &'static str {
         // 0xxx - (reserved for driver)
         // 1xxx - Preprocessor
         // 2xxx - Lexer and Parser
         // 3xxx - Typeck
         // 4xxx-9xxx - (reserved for future use)
+        // Warnings are numbered the same way, but with a `W` prefix
         match self {
             Self::PreprocessorCannotFindIncludeFile => "E1001",
             Self::PreprocessorCannotReadIncludeFile => "E1002",
@@ -185,9 +325,11 @@ impl ErrorCode for DiagnosticKind {
             Self::UnknownEscapeSequence => "E2004",
             Self::JavascriptUserDetected => "E2005",
             Self::InvalidToken => "E2006",
+            Self::MalformedNumberLiteral(_) => "E2007",
             Self::UnexpectedEof => "E2101",
             Self::UnrecognizedToken(_) => "E2102",
             Self::ExtraToken(_) => "E2103",
+            Self::MissingSemicolon(_) => "E2104",
 
             Self::UnableToResolveType(_) => "E3001",
             Self::UnableToResolveIdentifier(_) => "E3002",
@@ -224,14 +366,66 @@ impl ErrorCode for DiagnosticKind {
             Self::NumberLiteralOutOfBounds(_, _, _, _) => "E3035",
             Self::GlobalInitializerMustBeConstant => "E3036",
             Self::MatchOnNonEnum(_) => "E3037",
-            Self::MatchCaseCountMismatch => "E3038",
-            Self::NonExhaustiveMatchCases => "E3039",
+            Self::NonExhaustiveMatch(_) => "E3039",
             Self::MainFunctionMustReturnI32(_) => "E3040",
             Self::MainFunctionInvalidParameters => "E3041",
             Self::AssignmentToConstant(_) => "E3042",
             Self::FunctionNotFirstClass => "E3043",
             Self::InvalidNumberLiteral(_) => "E3044",
             Self::MultipleDefaultCases => "E3045",
+            Self::InvalidIntegerConversion { .. } => "E3046",
+            Self::UnknownAttribute(_) => "E3047",
+            Self::DuplicateAttribute(_) => "E3048",
+            Self::InvalidAttributeArguments { .. } => "E3049",
+            Self::StructLayoutMismatch { .. } => "E3050",
+            Self::EmptySwitch(_) => "E3051",
+            Self::DeprecatedFunction(_) => "W3001",
+            Self::UnknownCfgKey(_) => "E3052",
+            Self::EnumTagDoesNotFit { .. } => "E3053",
+            Self::UsedBeforeTypeInferred(_) => "E3054",
+            Self::InvalidMethodReceiver(_) => "E3055",
+            Self::MethodsOnNonStruct(_) => "E3056",
+            Self::InvalidConstructorSignature(_) => "E3057",
+            Self::ConstantIndexOutOfBounds { .. } => "E3058",
+            Self::UnknownEnumVariant { .. } => "E3059",
+            Self::ConstantOverflow(_) => "E3060",
+            Self::NoaliasOnNonPointer(_) => "E3061",
+            Self::AliasedNoaliasArguments(_) => "W3002",
+            Self::NonConstantArrayRepeatCount => "E3062",
+            Self::DivisionByZero(_) => "E3063",
+            Self::ConstantCondition(_) => "W3003",
+            Self::ConflictingReturnTypes(_, _) => "E3064",
+            Self::CompileError(_) => "E3065",
+            Self::CompileWarning(_) => "W3004",
+            Self::DuplicateEnumDiscriminant { .. } => "E3066",
+            Self::EnumDiscriminantOutOfRange(_) => "E3067",
+            Self::EnumDiscriminantDoesNotFit { .. } => "E3068",
+            Self::ComptimeArgumentNotConstant(_) => "E3069",
+            Self::InvalidComptimeParameterType(_) => "E3070",
+            Self::ComptimeParametersWithoutBody(_) => "E3071",
+            Self::ComptimeParameterInFunctionType => "E3072",
+            Self::ReturnsAddressOfLocal(_) => "W3005",
+            Self::ElvisValueNotTestable(_) => "E3073",
+            Self::ConflictingAttributes { .. } => "E3074",
+            Self::StringSwitchCaseNotLiteral => "E3075",
+            Self::PointerCastBetweenDifferentlySizedStructs { .. } => "W3006",
+            Self::UnknownLoopLabel(_) => "E3076",
+            Self::VariantOfNonEnum(_) => "E3077",
+            Self::EnumVariantHoldsValue { .. } => "E3078",
+            Self::MatchPatternMismatch { .. } => "E3079",
+            Self::UnreachableMatchArm => "W3007",
+            Self::EmptyBranch(_) => "W3008",
+            Self::UnreachableStatement => "W3009",
+            Self::UnusedVariable(_) => "W3010",
+            Self::ShiftAmountTooLarge(_, _) => "W3011",
+            Self::GlobalInitializerReferencesItself(_) => "E3080",
+            Self::InvalidSwitchScrutinee(_) => "E3081",
+            Self::StringSwitchCaseContainsNul => "E3082",
+            Self::NegativeShiftAmount(_) => "W3012",
+            Self::MethodsOnSameShapedTypes(_, _) => "E3083",
+            Self::ComptimeFunctionNotCalled(_) => "E3084",
+            Self::ReturnTypeNotInferred(_) => "E3085",
+            Self::EnumSwitchCaseNotVariant => "E3086",
         }
     }
 }
@@ -252,6 +446,8 @@ pub enum LabelKind {
     UnknownEscapeSequence,
     #[error("JavaScript user detected (unknown token)")]
     JavascriptUserDetected,
+    #[error("expected one or more of the digits `0` to `{0:x}` after the prefix")]
+    MalformedNumberLiteral(u32),
     #[error("invalid token")]
     InvalidToken,
     #[error("unexpected end of file")]
@@ -260,6 +456,10 @@ pub enum LabelKind {
     UnrecognizedToken(String),
     #[error("unexpected extra token `{0}`")]
     ExtraToken(String),
+    #[error("expected `;` here")]
+    MissingSemicolon,
+    #[error("the next statement starts here")]
+    NextStatementStartsHere,
     #[error("invalid include syntax")]
     PreprocessorInvalidIncludeSyntax,
     #[error("could not locate `{0}` in your include path")]
@@ -352,10 +552,8 @@ pub enum LabelKind {
     GlobalInitializerMustBeConstant,
     #[error("match on non-enum type `{0}`")]
     MatchOnNonEnum(String),
-    #[error("there must be exactly one match arm for every enum variant")]
-    MatchCaseCountMismatch,
-    #[error("there must be a match arm for every enum variant")]
-    NonExhaustiveMatchCases,
+    #[error("{0} not handled")]
+    NonExhaustiveMatch(String),
     #[error("main() function must have return type `i32`, got `{0}`")]
     MainFunctionMustReturnI32(String),
     #[error(
@@ -370,6 +568,120 @@ pub enum LabelKind {
     InvalidNumberLiteral(String),
     #[error("multiple default cases found")]
     MultipleDefaultCases,
+    #[error("`{to}` is not {relation} than `{from}`")]
+    InvalidIntegerConversion {
+        from: String,
+        to: String,
+        relation: String,
+    },
+    #[error("unknown attribute `{0}`")]
+    UnknownAttribute(String),
+    #[error("`{0}` specified again here")]
+    DuplicateAttribute(String),
+    #[error("expected {0}")]
+    InvalidAttributeArguments(String),
+    #[error("the layouts first differ at field `{0}`")]
+    StructLayoutMismatch(String),
+    #[error("this `switch` has no cases")]
+    EmptySwitch,
+    #[error("`{0}` is deprecated")]
+    DeprecatedFunction(String),
+    #[error("expected `target_os` or `target_arch`")]
+    UnknownCfgKey,
+    #[error("cast to `{0}` here")]
+    EnumTagDoesNotFit(String),
+    #[error("type not yet known here")]
+    UsedBeforeTypeInferred,
+    #[error("this must be named `self`")]
+    InvalidMethodReceiver,
+    #[error("not a struct")]
+    MethodsOnNonStruct,
+    #[error("constructors must have the signature `fn()`")]
+    InvalidConstructorSignature,
+    #[error("this index is not in `0..{0}`")]
+    ConstantIndexOutOfBounds(u64),
+    #[error("`{0}` is not a variant of this enum")]
+    UnknownEnumVariant(String),
+    #[error("this does not fit in `{0}`")]
+    ConstantOverflow(String),
+    #[error("this parameter has type `{0}`")]
+    NoaliasOnNonPointer(String),
+    #[error("passed to a `noalias` parameter here")]
+    AliasedNoaliasArgument,
+    #[error("this length is not known at compile time")]
+    NonConstantArrayRepeatCount,
+    #[error("this divisor is always zero")]
+    DivisionByZero,
+    #[error("this always evaluates to `{0}`")]
+    ConstantCondition(String),
+    #[error("this returns `{0}`")]
+    ReturnsType(String),
+    #[error("`{0}` is already the discriminant of another variant")]
+    DuplicateEnumDiscriminant(i128),
+    #[error("this discriminant is out of range")]
+    EnumDiscriminantOutOfRange,
+    #[error("this is not known at compile time")]
+    ComptimeArgumentNotConstant,
+    #[error("this parameter has type `{0}`")]
+    InvalidComptimeParameterType(String),
+    #[error("this function has no body")]
+    ComptimeParametersWithoutBody,
+    #[error("`comptime` is not allowed here")]
+    ComptimeParameterInFunctionType,
+    #[error("this points to `{0}`")]
+    ReturnsAddressOfLocal(String),
+    #[error("`{0}` is declared here")]
+    LocalDeclaredHere(String),
+    #[error("this is `{0}`, not a bool, integer or pointer")]
+    ElvisValueNotTestable(String),
+    #[error("conflicts with `{0}`")]
+    ConflictingAttribute(String),
+    #[error("`{0}` specified here")]
+    ConflictingAttributeSpecifiedHere(String),
+    #[error("expected a string literal")]
+    StringSwitchCaseNotLiteral,
+    #[error("`{0}` is first declared here")]
+    FieldFirstDeclaredHere(String),
+    #[error("the pointed-to structs are not the same size")]
+    PointerCastBetweenDifferentlySizedStructs,
+    #[error("unknown loop label `{0}`")]
+    UnknownLoopLabel(String),
+    #[error("`{0}` is not an enum")]
+    VariantOfNonEnum(String),
+    #[error("this variant holds a `{0}`")]
+    EnumVariantHoldsValue(String),
+    #[error("this can never match a `{0}`")]
+    MatchPatternMismatch(String),
+    #[error("`{0}` is already handled by an earlier arm")]
+    MatchArmAlreadyHandled(String),
+    #[error("every value is already handled by an earlier arm")]
+    MatchArmAlwaysHandled,
+    #[error("this branch does nothing")]
+    EmptyBranch,
+    #[error("this statement can never run")]
+    UnreachableStatement,
+    #[error("any code after this is unreachable")]
+    NeverCompletes,
+    #[error("this variable is never read")]
+    UnusedVariable,
+    #[error("`{0}` is only {1} bits wide")]
+    ShiftAmountTooLarge(String, u32),
+    #[error("`{0}` is not initialized yet")]
+    GlobalInitializerReferencesItself(String),
+    #[error("this is a `{0}`")]
+    InvalidSwitchScrutinee(String),
+    #[error("the string being switched over ends at its first NUL byte, so this can never match")]
+    StringSwitchCaseContainsNul,
+    #[error("this is negative")]
+    NegativeShiftAmount,
+    #[error("methods are also defined on `{0}` here")]
+    MethodsOnSameShapedTypes(String),
+    #[error("a copy of the function is only made for each direct call")]
+    ComptimeFunctionNotCalled,
+    #[error("return type not yet inferred here")]
+    ReturnTypeNotInferred,
+    #[error("expected a variant, like `Enum::Variant`")]
+    EnumSwitchCaseNotVariant,
     #[error("invalid shebang")]
     PreprocessorInvalidShebang,
 }
@@ -412,6 +724,34 @@ pub enum NoteKind {
     PointerArithmeticRequiresUsize,
     #[error("a shebang must end in a linefeed")]
     ShebangMustEndWithNewline,
+    #[error("this built-in is called as `{0}`")]
+    BuiltinSignature(String),
+    #[error("the width of `usize` and `isize` depends on the target; use `as` to convert them")]
+    PointerSizedIntegerWidth,
+    #[error("signed and unsigned integers cannot be mixed without an explicit cast")]
+    MixedSignedness,
+    #[error("{0}")]
+    DeprecationMessage(String),
+    #[error("not every integer is a valid enum variant, so integers cannot be cast to enums")]
+    IntegerToEnumCast,
+    #[error(
+        "functions assume `noalias` parameters never alias, so this call is undefined behavior"
+    )]
+    NoaliasArgumentsMustNotAlias,
+    #[error(
+        "array lengths are fixed at compile time, so only literals and arithmetic on them can be used"
+    )]
+    ArrayRepeatCountIsConstant,
+    #[error("dividing by zero is undefined behavior")]
+    DivisionByZeroIsUndefined,
+    #[error(
+        "local variables no longer exist once their function returns, so the pointer is dangling"
+    )]
+    LocalsDoNotOutliveTheirFunction,
+    #[error("shifting by the width of a type or more has an undefined result")]
+    ShiftAmountTooLargeIsUndefined,
+    #[error("shifting by a negative amount has an undefined result")]
+    NegativeShiftAmountIsUndefined,
 }
 
 /// The list of possible help messages attached to a [`Diagnostic`]
@@ -424,4 +764,28 @@ pub enum HelpKind {
     UseNormalDotAccess,
     #[error("consider casting: `value as {0}`")]
     ConsiderCasting(String),
+    #[error("consider adding a `default => ...` case")]
+    AddDefaultCase,
+    #[error("construct the enum from one of its variants instead")]
+    ConstructEnumVariant,
+    #[error("if this is intended, write `{0}` instead")]
+    WriteConstantCondition(String),
+    #[error("declare the return type of the function to convert the returned values to it")]
+    DeclareReturnType,
+    #[error("add a `;` to end the statement")]
+    InsertSemicolon,
+    #[error("construct it with `{ty} {{ {variant}: value }}`")]
+    ConstructEnumVariantWithValue { ty: String, variant: String },
+    #[error(
+        "if the statement after this `;` should only run when the condition holds, remove the `;`"
+    )]
+    RemoveSemicolonAfterCondition,
+    #[error("if this is intentional, name it `{0}` instead")]
+    PrefixWithUnderscore(String),
+    #[error(
+        "structs are compared by their fields, so give one of them a field the other does not have"
+    )]
+    GiveTypesDistinctFieldsForMethods,
+    #[error("declare the return type of `{0}`")]
+    DeclareReturnTypeOf(String),
 }
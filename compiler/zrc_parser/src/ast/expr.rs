@@ -3,12 +3,13 @@
 //! The main thing within this module you will need is the [`Expr`] struct.
 
 use derive_more::Display;
+use serde::Serialize;
 use zrc_utils::{
     span::{Span, Spannable, Spanned},
     spanned,
 };
 
-use super::ty::Type;
+use super::{stmt::MatchCase, ty::Type};
 use crate::lexer::{NumberLiteral, StringTok, ZrcString};
 
 /// Arithmetic operators
@@ -20,7 +21,7 @@ use crate::lexer::{NumberLiteral, StringTok, ZrcString};
 /// - The result type is the same as the operand types (or a pointer, for
 ///   pointer arithmetic)
 /// - Performs some mathematical operation
-#[derive(PartialEq, Eq, Debug, Clone, Copy, Display)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Display, Serialize)]
 pub enum Arithmetic {
     /// `+`
     #[display("+")]
@@ -63,7 +64,10 @@ pub enum Assignment {
 /// - Both operands must be the same type
 /// - The result type is the same as the operand types
 /// - Performs some bitwise operation
-#[derive(PartialEq, Eq, Debug, Clone, Copy, Display)]
+///
+/// This includes the shifts, where the right operand is the number of bits to
+/// shift the left one by.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Display, Serialize)]
 pub enum BinaryBitwise {
     /// `&`
     #[display("&")]
@@ -74,6 +78,13 @@ pub enum BinaryBitwise {
     /// `^`
     #[display("^")]
     Xor,
+    /// `<<`
+    #[display("<<")]
+    Shl,
+    /// `>>`, which is arithmetic on signed integers and logical on unsigned
+    /// ones
+    #[display(">>")]
+    Shr,
 }
 
 /// Logical operators
@@ -83,7 +94,7 @@ pub enum BinaryBitwise {
 /// - Operates on two booleans
 /// - The result type is a boolean
 /// - Performs some logical operation
-#[derive(PartialEq, Eq, Debug, Clone, Copy, Display)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Display, Serialize)]
 pub enum Logical {
     /// `&&`
     #[display("&&")]
@@ -100,7 +111,7 @@ pub enum Logical {
 /// - Operates on two values of the same type
 /// - The result type is a boolean
 /// - Performs some equality or inequality check
-#[derive(PartialEq, Eq, Debug, Clone, Copy, Display)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Display, Serialize)]
 pub enum Equality {
     /// `==`
     #[display("==")]
@@ -118,7 +129,7 @@ pub enum Equality {
 /// - Both operands must be the same type
 /// - The result type is a boolean
 /// - Performs some comparison or order check
-#[derive(PartialEq, Eq, Debug, Clone, Copy, Display)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Display, Serialize)]
 pub enum Comparison {
     /// `>`
     #[display(">")]
@@ -161,18 +172,20 @@ enum Precedence {
     Equality = 9,
     /// Comparison operators
     Comparison = 10,
+    /// Shift operators
+    Shift = 11,
     /// Addition and subtraction
-    Term = 11,
+    Term = 12,
     /// Multiplication, division, modulo
-    Factor = 12,
+    Factor = 13,
     /// Cast operator
-    Cast = 13,
+    Cast = 14,
     /// Unary operators
-    Unary = 14,
+    Unary = 15,
     /// Postfix operators (highest precedence)
-    Postfix = 15,
+    Postfix = 16,
     /// Primary expressions (literals, identifiers, parenthesized)
-    Primary = 16,
+    Primary = 17,
 }
 
 /// The enum representing the different kinds of expressions in Zirco
@@ -227,6 +240,8 @@ pub enum ExprKind<'input> {
 
     /// `a ? b : c`
     Ternary(Box<Expr<'input>>, Box<Expr<'input>>, Box<Expr<'input>>),
+    /// `a ?: b` - `a` if it is not zero, otherwise `b`
+    Elvis(Box<Expr<'input>>, Box<Expr<'input>>),
 
     /// `x as T`
     Cast(Box<Expr<'input>>, Type<'input>),
@@ -242,8 +257,20 @@ pub enum ExprKind<'input> {
         Spanned<Vec<Spanned<(Spanned<&'input str>, Expr<'input>)>>>,
     ),
 
+    /// Enum variant: `Enum::Variant`, a variant of an enum that holds no value
+    EnumVariant(Spanned<&'input str>, Spanned<&'input str>),
+
+    /// Match expression: `match (x) { A => 1, B: y => y }`, yielding the value
+    /// of the arm whose pattern matches
+    Match(
+        Box<Expr<'input>>,
+        Vec<Spanned<MatchCase<'input, Expr<'input>>>>,
+    ),
+
     /// Array literal: `[expr1, expr2, expr3, ...]`
     ArrayLiteral(Spanned<Vec<Expr<'input>>>),
+    /// Array repeat: `[value; count]`, an array of `count` copies of `value`
+    ArrayRepeat(Box<Expr<'input>>, Box<Expr<'input>>),
 
     /// Any numeric literal.
     NumberLiteral(NumberLiteral<'input>, Option<Type<'input>>),
@@ -263,12 +290,13 @@ impl ExprKind<'_> {
         match self {
             Self::Comma(_, _) => Precedence::Comma,
             Self::Assignment(_, _, _) => Precedence::Assignment,
-            Self::Ternary(_, _, _) => Precedence::Ternary,
+            Self::Ternary(_, _, _) | Self::Elvis(_, _) => Precedence::Ternary,
             Self::Logical(Logical::Or, _, _) => Precedence::LogicalOr,
             Self::Logical(Logical::And, _, _) => Precedence::LogicalAnd,
             Self::BinaryBitwise(BinaryBitwise::Or, _, _) => Precedence::BitwiseOr,
             Self::BinaryBitwise(BinaryBitwise::Xor, _, _) => Precedence::BitwiseXor,
             Self::BinaryBitwise(BinaryBitwise::And, _, _) => Precedence::BitwiseAnd,
+            Self::BinaryBitwise(BinaryBitwise::Shl | BinaryBitwise::Shr, _, _) => Precedence::Shift,
             Self::Equality(_, _, _) => Precedence::Equality,
             Self::Comparison(_, _, _) => Precedence::Comparison,
             Self::Arithmetic(Arithmetic::Addition | Arithmetic::Subtraction, _, _) => {
@@ -301,7 +329,10 @@ impl ExprKind<'_> {
             | Self::Identifier(_)
             | Self::BooleanLiteral(_)
             | Self::StructConstruction(_, _)
-            | Self::ArrayLiteral(_) => Precedence::Primary,
+            | Self::EnumVariant(_, _)
+            | Self::Match(_, _)
+            | Self::ArrayLiteral(_)
+            | Self::ArrayRepeat(_, _) => Precedence::Primary,
         }
     }
 
@@ -454,6 +485,14 @@ impl std::fmt::Display for ExprKind<'_> {
                 write!(f, " : ")?;
                 Self::fmt_child(f, if_false, prec, true)
             }
+            Self::Elvis(value, fallback) => {
+                let prec = self.precedence();
+                // `?:` groups to the right, so only the value needs parentheses
+                // around another `?:`
+                Self::fmt_child(f, value, prec, true)?;
+                write!(f, " ?: ")?;
+                Self::fmt_child(f, fallback, prec, false)
+            }
             Self::Cast(expr, ty) => {
                 let prec = self.precedence();
                 Self::fmt_child(f, expr, prec, false)?;
@@ -474,6 +513,19 @@ impl std::fmt::Display for ExprKind<'_> {
                 write!(f, "{}", field_list.join(", "))?;
                 write!(f, " }}")
             }
+            Self::EnumVariant(ty, variant) => write!(f, "{}::{}", ty.value(), variant.value()),
+            Self::Match(scrutinee, arms) => {
+                write!(f, "match ({scrutinee}) {{ ")?;
+                for (index, arm) in arms.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} => ", arm.value().pattern)?;
+                    // arms are separated by commas, so a comma operator needs parentheses
+                    Self::fmt_child(f, &arm.value().body, Precedence::Assignment, false)?;
+                }
+                write!(f, " }}")
+            }
             Self::ArrayLiteral(elements) => {
                 write!(f, "[")?;
                 let element_list: Vec<String> =
@@ -481,6 +533,7 @@ impl std::fmt::Display for ExprKind<'_> {
                 write!(f, "{}", element_list.join(", "))?;
                 write!(f, "]")
             }
+            Self::ArrayRepeat(value, count) => write!(f, "[{value}; {count}]"),
             Self::NumberLiteral(num, ty) => {
                 write!(
                     f,
@@ -575,6 +628,14 @@ impl<'input> Expr<'input> {
     pub fn build_bit_xor(lhs: Self, rhs: Self) -> Self {
         Self::build_binary_bitwise(BinaryBitwise::Xor, lhs, rhs)
     }
+    #[must_use]
+    pub fn build_shl(lhs: Self, rhs: Self) -> Self {
+        Self::build_binary_bitwise(BinaryBitwise::Shl, lhs, rhs)
+    }
+    #[must_use]
+    pub fn build_shr(lhs: Self, rhs: Self) -> Self {
+        Self::build_binary_bitwise(BinaryBitwise::Shr, lhs, rhs)
+    }
 
     fn build_logical(op: Logical, lhs: Self, rhs: Self) -> Self {
         Self(spanned!(
@@ -750,6 +811,15 @@ impl<'input> Expr<'input> {
         ))
     }
     #[must_use]
+    pub fn build_elvis(value: Self, fallback: Self) -> Self {
+        Self(spanned!(
+            value.0.start(),
+            ExprKind::Elvis(Box::new(value), Box::new(fallback)),
+            fallback.0.end(),
+            value.0.span().file_name()
+        ))
+    }
+    #[must_use]
     pub fn build_cast(expr: Self, ty: Type<'input>) -> Self {
         Self(spanned!(
             expr.0.start(),
@@ -795,6 +865,10 @@ impl<'input> Expr<'input> {
         Self::build_number(lit.map(NumberLiteral::Binary), ty)
     }
     #[must_use]
+    pub fn build_number_oct(lit: Spanned<&'input str>, ty: Option<Spanned<Type<'input>>>) -> Self {
+        Self::build_number(lit.map(NumberLiteral::Octal), ty)
+    }
+    #[must_use]
     pub fn build_string(lit: Spanned<ZrcString<'input>>) -> Self {
         let span = lit.span();
         Self(spanned!(
@@ -881,6 +955,8 @@ mod tests {
             "a &= b",
             "a |= b",
             "a ^= b",
+            "a <<= b",
+            "a >>= b",
             "a && b",
             "a || b",
             "a == b",
@@ -889,6 +965,8 @@ mod tests {
             "a >= b",
             "a < b",
             "a <= b",
+            "a << b",
+            "a >> b",
             "a + b",
             "a - b",
             "a * b",
@@ -916,6 +994,11 @@ mod tests {
             "'a'",
             "a",
             "true",
+            "[a, b]",
+            "[a; 4]",
+            "E::A",
+            "match (a) { A => 1, B: b => b, default => 0 }",
+            "match (a) { true => (b, c), false => d }",
         ];
 
         for input in test_cases {
@@ -939,6 +1022,12 @@ mod tests {
             // Comparison binds tighter than logical
             ("a < b && c > d", "a < b && c > d"),
             ("(a < b) && (c > d)", "a < b && c > d"), // Extra parens removed
+            // Shifts bind looser than addition but tighter than comparison
+            ("a << b + c", "a << b + c"),
+            ("(a << b) + c", "(a << b) + c"),
+            ("a < b >> c", "a < b >> c"),
+            ("(a < b) >> c", "(a < b) >> c"),
+            ("a & b << c", "a & b << c"),
             // Logical AND binds tighter than OR
             ("a || b && c", "a || b && c"),
             ("(a || b) && c", "(a || b) && c"),
@@ -947,6 +1036,8 @@ mod tests {
             ("(a = b) + c", "(a = b) + c"),
             // Ternary precedence
             ("a = b ? c : d", "a = b ? c : d"),
+            ("a = b ?: c ?: d", "a = b ?: c ?: d"),
+            ("(a ?: b) ?: c", "(a ?: b) ?: c"),
             // Multiple operators of same precedence (left associative)
             ("a - b - c", "a - b - c"),
             ("a - (b - c)", "a - (b - c)"),
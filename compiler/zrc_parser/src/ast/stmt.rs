@@ -10,6 +10,7 @@ use derive_more::Display;
 use zrc_utils::{code_fmt::indent_lines, span::Spanned};
 
 use super::{expr::Expr, ty::Type};
+use crate::lexer::ZrcString;
 
 /// A Zirco statement
 #[derive(PartialEq, Debug, Clone, Display)]
@@ -22,9 +23,10 @@ pub enum SwitchTrigger<'input> {
     /// An expression used, e.g. `2 => ...`
     #[display("{_0}")]
     Expr(Expr<'input>),
-    /// The `default` keyword was used
-    #[display("default")]
-    Default,
+    /// The `default` keyword was used, optionally binding the scrutinee to a
+    /// name as in `default(x) => ...`
+    #[display("{}", _0.map_or_else(|| "default".to_string(), |name| format!("default({name})")))]
+    Default(Option<Spanned<&'input str>>),
 }
 impl<'input> SwitchTrigger<'input> {
     /// Extract the [`Expr`] from the [`SwitchTrigger::Expr`] variant, or
@@ -33,7 +35,7 @@ impl<'input> SwitchTrigger<'input> {
     pub fn into_expr_value(self) -> Option<Expr<'input>> {
         match self {
             Self::Expr(x) => Some(x),
-            Self::Default => None,
+            Self::Default(_) => None,
         }
     }
 }
@@ -43,20 +45,32 @@ impl<'input> SwitchTrigger<'input> {
 #[display("{_0} => {_1}")]
 pub struct SwitchCase<'input>(pub SwitchTrigger<'input>, pub Stmt<'input>);
 
-/// Represents a matcher within a `match` statement.
-#[derive(PartialEq, Debug, Clone)]
-pub struct MatchCase<'input> {
-    /// The variant to be matched over
-    pub variant: &'input str,
-    /// The variable binding for the value within the variant
-    pub var: &'input str,
-    /// The body to execute if this case is matched
-    pub body: Stmt<'input>,
+/// Represents the pattern (portion before the `=>`) in a [`MatchCase`].
+#[expect(variant_size_differences)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Display)]
+pub enum MatchPattern<'input> {
+    /// A variant of an enum, optionally with a binding for the value within
+    /// it, e.g. `Some: x => ...` or `None => ...`
+    #[display("{_0}{}", _1.map_or_else(String::new, |binding| format!(": {binding}")))]
+    Variant(&'input str, Option<&'input str>),
+    /// `true` or `false`, when matching over a `bool`
+    #[display("{_0}")]
+    Bool(bool),
+    /// The `default` keyword was used, matching every value not matched by
+    /// another case
+    #[display("default")]
+    Default,
 }
-impl Display for MatchCase<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {} => {}", self.variant, self.var, self.body)
-    }
+
+/// Represents a matcher within a `match` statement, or an arm of a `match`
+/// expression when `B` is an [`Expr`].
+#[derive(PartialEq, Eq, Debug, Clone, Display)]
+#[display("{pattern} => {body}")]
+pub struct MatchCase<'input, B = Stmt<'input>> {
+    /// The pattern to be matched against
+    pub pattern: MatchPattern<'input>,
+    /// The body to execute, or the value to yield, if this case is matched
+    pub body: B,
 }
 
 /// The enum representing all the different kinds of statements in Zirco
@@ -69,6 +83,17 @@ pub enum StmtKind<'input> {
     IfStmt(Expr<'input>, Box<Stmt<'input>>, Option<Box<Stmt<'input>>>),
     /// `while (x) y`
     WhileStmt(Expr<'input>, Box<Stmt<'input>>),
+    /// `while let (Variant: binding = scrutinee) body`
+    WhileLetStmt {
+        /// The enum variant that keeps the loop running
+        variant: Spanned<&'input str>,
+        /// The name the variant's value is bound to in the body
+        binding: Spanned<&'input str>,
+        /// The enum evaluated before each iteration of the loop
+        scrutinee: Expr<'input>,
+        /// The body of the loop.
+        body: Box<Stmt<'input>>,
+    },
     /// `do x while (y)`
     DoWhileStmt(Box<Stmt<'input>>, Expr<'input>),
     /// `for (init; cond; post) body`
@@ -85,22 +110,43 @@ pub enum StmtKind<'input> {
         /// The body of the loop.
         body: Box<Stmt<'input>>,
     },
+    /// `for (binding in iterable) body`
+    ForInStmt {
+        /// The name each element is copied into for an iteration
+        binding: Spanned<&'input str>,
+        /// The array whose elements are iterated over
+        iterable: Expr<'input>,
+        /// The body of the loop.
+        body: Box<Stmt<'input>>,
+    },
     /// `four body`
     FourStmt(Box<Stmt<'input>>),
+    /// `label: loop`, a loop which `break label;` and `continue label;` refer
+    /// to from within nested loops
+    LabeledLoop {
+        /// The name of the loop
+        label: Spanned<&'input str>,
+        /// The loop itself
+        body: Box<Stmt<'input>>,
+    },
     /// `{ ... }`
     BlockStmt(Vec<Stmt<'input>>),
     /// `x;`
     ExprStmt(Expr<'input>),
     /// `;`
     EmptyStmt,
-    /// `continue;`
-    ContinueStmt,
-    /// `break;`
-    BreakStmt,
+    /// `continue;` or `continue label;`
+    ContinueStmt(Option<Spanned<&'input str>>),
+    /// `break;` or `break label;`
+    BreakStmt(Option<Spanned<&'input str>>),
     /// `return;` or `return x;`
     ReturnStmt(Option<Expr<'input>>),
     /// `unreachable;`
     UnreachableStmt,
+    /// `@compile_error("message");`
+    CompileErrorStmt(Spanned<ZrcString<'input>>),
+    /// `@compile_warning("message");`
+    CompileWarningStmt(Spanned<ZrcString<'input>>),
     /// A let declaration
     DeclarationList(Spanned<Vec<Spanned<LetDeclaration<'input>>>>),
     /// A switch case
@@ -118,7 +164,13 @@ pub enum StmtKind<'input> {
         cases: Vec<Spanned<MatchCase<'input>>>,
     },
 }
+/// The ` label` written after `break` or `continue`, if there is a label
+fn labeled(label: Option<&Spanned<&str>>) -> String {
+    label.map_or_else(String::new, |label| format!(" {label}"))
+}
+
 impl Display for StmtKind<'_> {
+    #[expect(clippy::too_many_lines)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::IfStmt(cond, if_true, None) => write!(f, "if ({cond}) {if_true}"),
@@ -126,6 +178,12 @@ impl Display for StmtKind<'_> {
                 write!(f, "if ({cond}) {if_true} else {if_false}")
             }
             Self::WhileStmt(cond, body) => write!(f, "while ({cond}) {body}"),
+            Self::WhileLetStmt {
+                variant,
+                binding,
+                scrutinee,
+                body,
+            } => write!(f, "while let ({variant}: {binding} = {scrutinee}) {body}"),
             Self::DoWhileStmt(body, cond) => write!(f, "do {body} while ({cond});"),
             Self::ForStmt {
                 init,
@@ -151,7 +209,13 @@ impl Display for StmtKind<'_> {
                     post.as_ref().map_or(String::new(), ToString::to_string),
                 )
             }
+            Self::ForInStmt {
+                binding,
+                iterable,
+                body,
+            } => write!(f, "for ({binding} in {iterable}) {body}"),
             Self::FourStmt(body) => write!(f, "four {body}"),
+            Self::LabeledLoop { label, body } => write!(f, "{label}: {body}"),
 
             Self::BlockStmt(stmts) => {
                 if stmts.is_empty() {
@@ -168,13 +232,19 @@ impl Display for StmtKind<'_> {
                     )
                 }
             }
+            // a statement beginning with `match` is parsed as a match statement
+            Self::ExprStmt(expr) if expr.to_string().starts_with("match (") => {
+                write!(f, "({expr});")
+            }
             Self::ExprStmt(expr) => write!(f, "{expr};"),
             Self::EmptyStmt => write!(f, ";"),
-            Self::ContinueStmt => write!(f, "continue;"),
-            Self::BreakStmt => write!(f, "break;"),
+            Self::ContinueStmt(label) => write!(f, "continue{};", labeled(label.as_ref())),
+            Self::BreakStmt(label) => write!(f, "break{};", labeled(label.as_ref())),
             Self::ReturnStmt(Some(expr)) => write!(f, "return {expr};"),
             Self::ReturnStmt(None) => write!(f, "return;"),
             Self::UnreachableStmt => write!(f, "unreachable;"),
+            Self::CompileErrorStmt(message) => write!(f, "@compile_error(\"{message}\");"),
+            Self::CompileWarningStmt(message) => write!(f, "@compile_warning(\"{message}\");"),
             Self::DeclarationList(list) => {
                 write!(
                     f,
@@ -215,6 +285,8 @@ impl Display for StmtKind<'_> {
 pub enum Declaration<'input> {
     /// A declaration of a function
     FunctionDeclaration {
+        /// The attributes written before `fn`, like `inline`.
+        attributes: Vec<Spanned<Attribute<'input>>>,
         /// The name of the function.
         name: Spanned<&'input str>,
         /// The parameters of the function.
@@ -234,20 +306,36 @@ pub enum Declaration<'input> {
         /// The type to associate.
         ty: Type<'input>,
     },
+    /// A named enum (`enum E { A: i32, B = 5, C }`)
+    EnumDeclaration {
+        /// The name of the enum.
+        name: Spanned<&'input str>,
+        /// The variants of the enum, in declaration order.
+        variants: Spanned<Vec<Spanned<EnumVariant<'input>>>>,
+    },
     /// A global let declaration
     GlobalLetDeclaration(Spanned<Vec<Spanned<LetDeclaration<'input>>>>),
+    /// A list of methods on a named struct (`impl S { ... }`)
+    ImplDeclaration {
+        /// The name of the type the methods are defined on.
+        ty: Spanned<&'input str>,
+        /// The methods defined on the type.
+        methods: Vec<Spanned<Method<'input>>>,
+    },
 }
 impl Display for Declaration<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::FunctionDeclaration {
+                attributes,
                 name,
                 parameters,
                 return_type: Some(return_ty),
                 body: Some(body),
             } => write!(
                 f,
-                "fn {name}({parameters}) -> {return_ty} {{\n{}\n}}",
+                "{}fn {name}({parameters}) -> {return_ty} {{\n{}\n}}",
+                fmt_attributes(attributes),
                 body.value()
                     .iter()
                     .map(|stmt| indent_lines(&stmt.to_string(), "    "))
@@ -255,19 +343,26 @@ impl Display for Declaration<'_> {
                     .join("\n")
             ),
             Self::FunctionDeclaration {
+                attributes,
                 name,
                 parameters,
                 return_type: Some(return_ty),
                 body: None,
-            } => write!(f, "fn {name}({parameters}) -> {return_ty};"),
+            } => write!(
+                f,
+                "{}fn {name}({parameters}) -> {return_ty};",
+                fmt_attributes(attributes)
+            ),
             Self::FunctionDeclaration {
+                attributes,
                 name,
                 parameters,
                 return_type: None,
                 body: Some(body),
             } => write!(
                 f,
-                "fn {name}({parameters}) {{\n{}\n}}",
+                "{}fn {name}({parameters}) {{\n{}\n}}",
+                fmt_attributes(attributes),
                 body.value()
                     .iter()
                     .map(|stmt| indent_lines(&stmt.to_string(), "    "))
@@ -275,14 +370,26 @@ impl Display for Declaration<'_> {
                     .join("\n")
             ),
             Self::FunctionDeclaration {
+                attributes,
                 name,
                 parameters,
                 return_type: None,
                 body: None,
-            } => write!(f, "fn {name}({parameters});"),
+            } => write!(f, "{}fn {name}({parameters});", fmt_attributes(attributes)),
 
             Self::TypeAliasDeclaration { name, ty } => write!(f, "type {name} = {ty};"),
 
+            Self::EnumDeclaration { name, variants } => write!(
+                f,
+                "enum {name} {{ {} }}",
+                variants
+                    .value()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+
             Self::GlobalLetDeclaration(list) => write!(
                 f,
                 "let {};",
@@ -292,10 +399,147 @@ impl Display for Declaration<'_> {
                     .collect::<Vec<_>>()
                     .join(", ")
             ),
+
+            Self::ImplDeclaration { ty, methods } => write!(
+                f,
+                "impl {ty} {{\n{}\n}}",
+                methods
+                    .iter()
+                    .map(|method| indent_lines(&method.to_string(), "    "))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ),
         }
     }
 }
 
+/// A variant of an `enum` declaration, like `A: i32`, `B = 5` or `C`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct EnumVariant<'input> {
+    /// The name of the variant.
+    pub name: Spanned<&'input str>,
+    /// The type of the value the variant holds. If set to [`None`], the
+    /// variant holds no value.
+    pub ty: Option<Type<'input>>,
+    /// The explicit discriminant of the variant. If set to [`None`], it is one
+    /// more than the previous variant's.
+    pub discriminant: Option<Spanned<i128>>,
+}
+impl Display for EnumVariant<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(ty) = &self.ty {
+            write!(f, ": {ty}")?;
+        }
+        if let Some(discriminant) = &self.discriminant {
+            write!(f, " = {discriminant}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A method written in an `impl` block, which is a function whose first
+/// parameter is the value it is called on
+#[derive(PartialEq, Debug, Clone)]
+pub struct Method<'input> {
+    /// The attributes written before `fn`, like `inline`.
+    pub attributes: Vec<Spanned<Attribute<'input>>>,
+    /// The name of the method.
+    pub name: Spanned<&'input str>,
+    /// The first parameter, `self` or `*self`.
+    pub receiver: Spanned<Receiver<'input>>,
+    /// The parameters after the receiver.
+    pub parameters: Spanned<ArgumentDeclarationList<'input>>,
+    /// The return type of the method. If set to [`None`], the method is void.
+    pub return_type: Option<Type<'input>>,
+    /// The body of the method.
+    pub body: Spanned<Vec<Stmt<'input>>>,
+}
+impl Display for Method<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (ArgumentDeclarationList::Variadic(parameters)
+        | ArgumentDeclarationList::NonVariadic(parameters)) = self.parameters.value();
+
+        write!(
+            f,
+            "{}fn {}({}{}{})",
+            fmt_attributes(&self.attributes),
+            self.name,
+            self.receiver,
+            if parameters.is_empty() { "" } else { ", " },
+            self.parameters
+        )?;
+        if let Some(return_type) = &self.return_type {
+            write!(f, " -> {return_type}")?;
+        }
+        write!(
+            f,
+            " {{\n{}\n}}",
+            self.body
+                .value()
+                .iter()
+                .map(|stmt| indent_lines(&stmt.to_string(), "    "))
+                .collect::<Vec<String>>()
+                .join("\n")
+        )
+    }
+}
+
+/// The first parameter of a [`Method`]
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Receiver<'input> {
+    /// The name written for the receiver, which must be `self`.
+    pub name: Spanned<&'input str>,
+    /// If the method takes a pointer to the value (`*self`) instead of a copy
+    /// of it (`self`).
+    pub by_pointer: bool,
+}
+impl Display for Receiver<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", if self.by_pointer { "*" } else { "" }, self.name)
+    }
+}
+
+/// An attribute written before a declaration, such as `inline` or
+/// `deprecated("use g instead")`
+///
+/// The parser accepts any identifier here; which attributes exist and what
+/// arguments they take is checked by the type checker.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Attribute<'input> {
+    /// The name of the attribute
+    pub name: Spanned<&'input str>,
+    /// The parenthesized arguments, if any were written
+    pub arguments: Option<Spanned<Vec<Expr<'input>>>>,
+}
+impl Display for Attribute<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.arguments {
+            None => write!(f, "{}", self.name),
+            Some(arguments) => write!(
+                f,
+                "{}({})",
+                self.name,
+                arguments
+                    .value()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// Stringify a list of attributes with a trailing space, to be placed before
+/// the declaration they apply to
+fn fmt_attributes(attributes: &[Spanned<Attribute<'_>>]) -> String {
+    attributes
+        .iter()
+        .map(|attribute| attribute.to_string() + " ")
+        .collect()
+}
+
 /// The list of arguments on a [`Declaration::FunctionDeclaration`]
 ///
 /// May be variadic or not.
@@ -361,14 +605,23 @@ impl Display for LetDeclaration<'_> {
 }
 
 /// A special form of [`LetDeclaration`] used for function parameters.
-#[derive(PartialEq, Eq, Debug, Clone, Display)]
-#[display("{name}: {ty}")]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct ArgumentDeclaration<'input> {
+    /// The attributes written before the parameter, like `noalias`
+    pub attributes: Vec<Spanned<Attribute<'input>>>,
     /// The name of the parameter.
     pub name: Spanned<&'input str>,
     /// The type of the parameter.
     pub ty: Type<'input>,
 }
+impl Display for ArgumentDeclaration<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for attribute in &self.attributes {
+            write!(f, "{attribute} ")?;
+        }
+        write!(f, "{}: {}", self.name, self.ty)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -381,6 +634,8 @@ mod tests {
             "return;",
             "break;",
             "continue;",
+            "break outer;",
+            "continue outer;",
             "return 4;",
             "f(x);",
             "{}",
@@ -388,19 +643,27 @@ mod tests {
             "if (true) {\n    ;\n}",
             "if (true) {\n    ;\n} else {\n    ;\n}",
             "while (true) {\n    ;\n}",
+            "while let (Some: x = next()) {\n    f(x);\n}",
             "do {\n    ;\n} while (true);",
             "for (; ; ) {\n    ;\n}",
             "for (let x = 4; ; ) {\n    ;\n}",
             "for (let x = 4, y = 5; ; ) {\n    ;\n}",
             "for (let x = 4; true; ) {\n    ;\n}",
+            "for (x in values) {\n    f(x);\n}",
             "four {\n    ;\n}",
             "four return;",
+            "outer: while (true) {\n    break outer;\n}",
+            "rows: for (x in values) {\n    continue rows;\n}",
             "let x;",
             "let x = 4;",
             "let x: i32;",
             "let x: i32 = 4;",
             "{\n    let x = 4;\n}",
             "switch (7) { 4 => false; default => {\n    12;\n} }",
+            "switch (7) { 4 => false; default(x) => f(x); }",
+            "match (x) { Some: y => f(y); None => ; }",
+            "let x = match (y) { A => 1, B => 2 };",
+            "(match (x) { true => a, false => b });",
         ];
 
         for input in test_cases {
@@ -419,7 +682,7 @@ mod tests {
 
     #[test]
     fn functions_stringify_to_their_canonical_form() {
-        let test_case = indoc::indoc! {"
+        let test_case = indoc::indoc! {r#"
             fn add(a: i32, b: i32) -> bool;
             fn add(a: i32, b: i32) -> i32 {
                 return a + b;
@@ -427,7 +690,20 @@ mod tests {
             fn no_return_extern();
             fn no_return() {
 
-            }"};
+            }
+            inline fn attributed() {
+
+            }
+            deprecated("use add") fn old_add(a: i32, b: i32) -> i32;
+            fn copy(noalias dst: *u8, noalias src: *u8, n: usize);
+            impl Rect {
+                fn area(self) -> i32 {
+                    return self.w * self.h;
+                }
+                inline fn grow(*self, by: i32) {
+                    self.w += by;
+                }
+            }"#};
 
         assert_eq!(
             crate::parser::parse_program(test_case, "<test>")
@@ -35,6 +35,7 @@
 
 use derive_more::Display;
 use logos::{Lexer, Logos};
+use serde::Serialize;
 use zrc_utils::span::{Span, Spannable, Spanned};
 
 /// The error enum passed to the internal logos [`Lexer`]. Will be converted to
@@ -61,6 +62,9 @@ pub enum InternalLexicalError {
     /// `===` or `!==` was found in the input
     /// Parameter will be `==` or `!=` for what was expected.
     JavascriptUserDetected(&'static str),
+    /// A number literal with a base prefix had no digits or a digit too large
+    /// for its base. Parameter is the base.
+    MalformedNumberLiteral(u32),
 }
 
 /// An error encountered during lexing. You will usually find this wrapped in a
@@ -79,11 +83,18 @@ pub enum LexicalError<'input> {
     ///
     /// The span is the starting delimiter of the block comment
     UnterminatedBlockComment(Span),
-    /// Produced from [`InternalLexicalError::NoMatchingRule`]
-    UnknownEscapeSequence,
+    /// An invalid escape sequence was found in a string literal.
+    ///
+    /// The span is the escape sequence itself, inside of the string literal
+    UnknownEscapeSequence(Span),
     /// `===` or `!==` was found in the input
     /// Parameter will be `==` or `!=` for what was expected.
     JavascriptUserDetected(&'static str),
+    /// A number literal with a base prefix, like `0o8` or `0b`, had no digits
+    /// or a digit too large for its base.
+    ///
+    /// Parameters are the literal and its base
+    MalformedNumberLiteral(&'input str, u32),
 }
 
 /// A lexer callback helper to obtain the currently matched token slice.
@@ -131,18 +142,23 @@ fn lex_string_contents<'input>(
     while let Some(token) = string_lexer.next() {
         let span = string_lexer.span();
 
-        match token {
-            Ok(token_inner) => tokens.push(token_inner),
-            Err(()) => {
-                return Err(InternalLexicalError::UnknownEscapeSequence(
-                    Span::from_positions_and_file(
-                        span.start + start_offset,
-                        span.end + start_offset,
-                        "<unknown>",
-                    ),
-                ));
-            }
-        }
+        let Ok(token_inner) = token else {
+            // only the backslash fails to match, but the escape sequence
+            // includes the character after it
+            let escaped_len = contents[span.end..]
+                .chars()
+                .next()
+                .map_or(0, char::len_utf8);
+
+            return Err(InternalLexicalError::UnknownEscapeSequence(
+                Span::from_positions_and_file(
+                    span.start + start_offset,
+                    span.end + escaped_len + start_offset,
+                    "<unknown>",
+                ),
+            ));
+        };
+        tokens.push(token_inner);
     }
 
     Ok(tokens)
@@ -211,7 +227,7 @@ fn handle_block_comment_start<'input>(
 }
 
 /// A valid number literal in Zirco
-#[derive(Debug, Clone, PartialEq, Eq, Display)]
+#[derive(Debug, Clone, PartialEq, Eq, Display, Serialize)]
 pub enum NumberLiteral<'input> {
     /// A decimal number literal
     #[display("{_0}")]
@@ -222,23 +238,27 @@ pub enum NumberLiteral<'input> {
     /// A binary number literal
     #[display("0b{_0}")]
     Binary(&'input str),
+    /// An octal number literal
+    #[display("0o{_0}")]
+    Octal(&'input str),
 }
 impl<'input> NumberLiteral<'input> {
     /// Get the text content of this [`NumberLiteral`]
     #[must_use]
     pub const fn text_content(&self) -> &'input str {
         match self {
-            Self::Decimal(n) | Self::Hexadecimal(n) | Self::Binary(n) => n,
+            Self::Decimal(n) | Self::Hexadecimal(n) | Self::Binary(n) | Self::Octal(n) => n,
         }
     }
 
-    /// Convert a [`NumberLiteral`] into its radix (2, 10, or 16)
+    /// Convert a [`NumberLiteral`] into its radix (2, 8, 10, or 16)
     #[must_use]
     pub const fn radix(&self) -> u32 {
         match self {
             Self::Decimal(_) => 10,
             Self::Hexadecimal(_) => 16,
             Self::Binary(_) => 2,
+            Self::Octal(_) => 8,
         }
     }
 }
@@ -353,6 +373,14 @@ pub enum Tok<'input> {
     #[token("~")]
     #[display("~")]
     BitwiseNot,
+    /// The token `<<`
+    #[token("<<")]
+    #[display("<<")]
+    ShiftLeft,
+    /// The token `>>`
+    #[token(">>")]
+    #[display(">>")]
+    ShiftRight,
 
     // === ASSIGNMENT OPERATORS ===
     /// The token `=`
@@ -391,6 +419,14 @@ pub enum Tok<'input> {
     #[token("^=")]
     #[display("^=")]
     BitwiseXorAssign,
+    /// The token `<<=`
+    #[token("<<=")]
+    #[display("<<=")]
+    ShiftLeftAssign,
+    /// The token `>>=`
+    #[token(">>=")]
+    #[display(">>=")]
+    ShiftRightAssign,
 
     // === OTHER TOKENS ===
     /// The token `;`
@@ -477,6 +513,10 @@ pub enum Tok<'input> {
     #[token("four")]
     #[display("four")]
     Four,
+    /// The keyword `in`
+    #[token("in")]
+    #[display("in")]
+    In,
     /// The keyword `break`
     #[token("break")]
     #[display("break")]
@@ -497,6 +537,10 @@ pub enum Tok<'input> {
     #[token("const")]
     #[display("const")]
     Const,
+    /// The keyword `static`
+    #[token("static")]
+    #[display("static")]
+    Static,
     /// The keyword `fn`
     #[token("fn")]
     #[display("fn")]
@@ -545,6 +589,18 @@ pub enum Tok<'input> {
     #[token("unreachable")]
     #[display("unreachable")]
     Unreachable,
+    /// The built-in `@compile_error`
+    #[token("@compile_error")]
+    #[display("@compile_error")]
+    CompileError,
+    /// The built-in `@compile_warning`
+    #[token("@compile_warning")]
+    #[display("@compile_warning")]
+    CompileWarning,
+    /// The keyword `impl`
+    #[token("impl")]
+    #[display("impl")]
+    Impl,
     /// The operator `->`
     #[token("->")]
     #[display("->")]
@@ -587,6 +643,13 @@ pub enum Tok<'input> {
     #[regex(r"[0-9][0-9\._]*", |lex| NumberLiteral::Decimal(lex.slice()))]
     #[regex(r"0x[0-9a-fA-F_]+", |lex| NumberLiteral::Hexadecimal(&lex.slice()[2..]))]
     #[regex(r"0b[01_]+", |lex| NumberLiteral::Binary(&lex.slice()[2..]))]
+    #[regex(r"0o[0-7_]+", |lex| NumberLiteral::Octal(&lex.slice()[2..]))]
+    // without these, `0o8` would be lexed as `0` with the type suffix `o8`
+    #[regex(r"0o[0-7_]*[89][0-9_]*", |_| Err(InternalLexicalError::MalformedNumberLiteral(8)))]
+    #[regex(r"0b[01_]*[2-9][0-9_]*", |_| Err(InternalLexicalError::MalformedNumberLiteral(2)))]
+    #[token("0o", |_| Err(InternalLexicalError::MalformedNumberLiteral(8)))]
+    #[token("0b", |_| Err(InternalLexicalError::MalformedNumberLiteral(2)))]
+    #[token("0x", |_| Err(InternalLexicalError::MalformedNumberLiteral(16)))]
     #[display("{_0}")]
     NumberLiteral(NumberLiteral<'input>),
     /// Any identifier
@@ -598,7 +661,7 @@ pub enum Tok<'input> {
 /// The compiler's representation of a string literal in Zirco
 ///
 /// Enum representing the lexed contents of a string literal
-#[derive(Logos, Debug, Clone, PartialEq, Eq, Display)]
+#[derive(Logos, Debug, Clone, PartialEq, Eq, Display, Serialize)]
 pub enum StringTok<'input> {
     /// `\n`
     #[token("\\n")]
@@ -681,7 +744,7 @@ impl StringTok<'_> {
 }
 
 /// A representation of a string literal in the source code
-#[derive(Debug, Clone, PartialEq, Eq, Display)]
+#[derive(Debug, Clone, PartialEq, Eq, Display, Serialize)]
 #[display("{}", self.0.iter().map(ToString::to_string).collect::<String>())]
 pub struct ZrcString<'input>(pub Vec<StringTok<'input>>);
 impl ZrcString<'_> {
@@ -739,12 +802,19 @@ impl<'input> Iterator for ZircoLexer<'input> {
                     InternalLexicalError::UnterminatedStringLiteral => {
                         LexicalError::UnterminatedStringLiteral
                     }
-                    InternalLexicalError::UnknownEscapeSequence(_) => {
-                        LexicalError::UnknownEscapeSequence
+                    InternalLexicalError::UnknownEscapeSequence(escape) => {
+                        LexicalError::UnknownEscapeSequence(Span::from_positions_and_file(
+                            escape.start(),
+                            escape.end(),
+                            self.file_name,
+                        ))
                     }
                     InternalLexicalError::JavascriptUserDetected(expected) => {
                         LexicalError::JavascriptUserDetected(expected)
                     }
+                    InternalLexicalError::MalformedNumberLiteral(base) => {
+                        LexicalError::MalformedNumberLiteral(self.lex.slice(), base)
+                    }
                 })
                 .in_span(span),
         )
@@ -823,16 +893,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn number_literals_with_bad_digits_should_error() {
+        let lexer = ZircoLexer::new("0o8 0o17_9 0b102 0o 0x 0o17u8", "<test>");
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            tokens,
+            vec![
+                spanned!(
+                    0,
+                    Err(LexicalError::MalformedNumberLiteral("0o8", 8)),
+                    3,
+                    "<test>"
+                ),
+                spanned!(
+                    4,
+                    Err(LexicalError::MalformedNumberLiteral("0o17_9", 8)),
+                    10,
+                    "<test>"
+                ),
+                spanned!(
+                    11,
+                    Err(LexicalError::MalformedNumberLiteral("0b102", 2)),
+                    16,
+                    "<test>"
+                ),
+                spanned!(
+                    17,
+                    Err(LexicalError::MalformedNumberLiteral("0o", 8)),
+                    19,
+                    "<test>"
+                ),
+                spanned!(
+                    20,
+                    Err(LexicalError::MalformedNumberLiteral("0x", 16)),
+                    22,
+                    "<test>"
+                ),
+                // TEST: a valid literal can still be followed by a type suffix
+                spanned!(
+                    23,
+                    Ok(Tok::NumberLiteral(NumberLiteral::Octal("17"))),
+                    27,
+                    "<test>"
+                ),
+                spanned!(27, Ok(Tok::Identifier("u8")), 29, "<test>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_escapes_should_error_at_the_escape() {
+        let lexer = ZircoLexer::new("\"ab\\qc\"", "<test>");
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            tokens,
+            vec![spanned!(
+                0,
+                Err(LexicalError::UnknownEscapeSequence(
+                    Span::from_positions_and_file(3, 5, "<test>")
+                )),
+                7,
+                "<test>"
+            ),]
+        );
+    }
+
     /// Tests that all tokens can be properly lexed, and that they all impl
     /// [`Display`] correctly.
     #[test]
+    #[expect(clippy::too_many_lines)]
     fn all_tokens_lex_and_display_correctly() {
         let input = concat!(
-            "++ -- + - * / % == != > >= < <= && || ! & | ^ ~",
-            " = += -= *= /= %= &= |= ^= ; ,",
+            "++ -- + - * / % == != > >= < <= && || ! & | ^ ~ << >>",
+            " = += -= *= /= %= &= |= ^= <<= >>= ; ,",
             " . : :: ? ( ) [ ] { } true false if else while do for break continue return let fn as",
             r#" struct union enum match sizeof type switch default four -> => "str" 7_000 0xF_A"#,
-            " 0b1_0 abc const"
+            " 0b1_0 0o1_7 abc const static impl in"
         );
         let tokens: Vec<Tok> = vec![
             Tok::PlusPlus,
@@ -855,6 +992,8 @@ mod tests {
             Tok::BitwiseOr,
             Tok::BitwiseXor,
             Tok::BitwiseNot,
+            Tok::ShiftLeft,
+            Tok::ShiftRight,
             Tok::Assign,
             Tok::PlusAssign,
             Tok::MinusAssign,
@@ -864,6 +1003,8 @@ mod tests {
             Tok::BitwiseAndAssign,
             Tok::BitwiseOrAssign,
             Tok::BitwiseXorAssign,
+            Tok::ShiftLeftAssign,
+            Tok::ShiftRightAssign,
             Tok::Semicolon,
             Tok::Comma,
             Tok::Dot,
@@ -908,8 +1049,12 @@ mod tests {
             Tok::NumberLiteral(NumberLiteral::Decimal("7_000")),
             Tok::NumberLiteral(NumberLiteral::Hexadecimal("F_A")),
             Tok::NumberLiteral(NumberLiteral::Binary("1_0")),
+            Tok::NumberLiteral(NumberLiteral::Octal("1_7")),
             Tok::Identifier("abc"),
             Tok::Const,
+            Tok::Static,
+            Tok::Impl,
+            Tok::In,
         ];
 
         assert_eq!(
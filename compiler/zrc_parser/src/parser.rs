@@ -36,20 +36,22 @@ use crate::{
     lexer::LexicalError,
 };
 
-/// Converts from a LALRPOP [`ParseError`] to a corresponding [`Diagnostic`].
+/// Converts from a LALRPOP [`ParseError`] in the file `file_name` to a
+/// corresponding [`Diagnostic`].
 fn parser_error_to_diagnostic(
     error: ParseError<usize, lexer::Tok, Spanned<LexicalError>>,
+    file_name: &'static str,
 ) -> Diagnostic {
     match error {
         ParseError::InvalidToken { location } => {
-            let sp = Span::from_positions_and_file(location, location, "<unknown>");
+            let sp = Span::from_positions_and_file(location, location, file_name);
             DiagnosticKind::InvalidToken
                 .error_in(sp)
                 .with_label(GenericLabel::error(LabelKind::InvalidToken.in_span(sp)))
         }
 
         ParseError::UnrecognizedEof { location, expected } => {
-            let sp = Span::from_positions_and_file(location, location, "<unknown>");
+            let sp = Span::from_positions_and_file(location, location, file_name);
             DiagnosticKind::UnexpectedEof
                 .error_in(sp)
                 .with_label(GenericLabel::error(LabelKind::UnexpectedEof.in_span(sp)))
@@ -60,7 +62,7 @@ fn parser_error_to_diagnostic(
             token: (start, token, end),
             expected,
         } => {
-            let sp = Span::from_positions_and_file(start, end, "<unknown>");
+            let sp = Span::from_positions_and_file(start, end, file_name);
             DiagnosticKind::UnrecognizedToken(token.to_string())
                 .error_in(sp)
                 .with_label(GenericLabel::error(
@@ -72,7 +74,7 @@ fn parser_error_to_diagnostic(
         ParseError::ExtraToken {
             token: (start, token, end),
         } => {
-            let sp = Span::from_positions_and_file(start, end, "<unknown>");
+            let sp = Span::from_positions_and_file(start, end, file_name);
             DiagnosticKind::ExtraToken(token.to_string())
                 .error_in(sp)
                 .with_label(GenericLabel::error(
@@ -108,11 +110,13 @@ fn parser_error_to_diagnostic(
                             LabelKind::UnterminatedStringLiteral.in_span(sp),
                         ))
                 }
-                LexicalError::UnknownEscapeSequence => DiagnosticKind::UnknownEscapeSequence
-                    .error_in(sp)
-                    .with_label(GenericLabel::error(
-                        LabelKind::UnknownEscapeSequence.in_span(sp),
-                    )),
+                LexicalError::UnknownEscapeSequence(escape_sp) => {
+                    DiagnosticKind::UnknownEscapeSequence
+                        .error_in(escape_sp)
+                        .with_label(GenericLabel::error(
+                            LabelKind::UnknownEscapeSequence.in_span(escape_sp),
+                        ))
+                }
                 LexicalError::JavascriptUserDetected(expected) => {
                     DiagnosticKind::JavascriptUserDetected
                         .error_in(sp)
@@ -121,6 +125,13 @@ fn parser_error_to_diagnostic(
                         ))
                         .with_help(HelpKind::JavascriptUserDetected(expected))
                 }
+                LexicalError::MalformedNumberLiteral(literal, base) => {
+                    DiagnosticKind::MalformedNumberLiteral(literal.to_string())
+                        .error_in(sp)
+                        .with_label(GenericLabel::error(
+                            LabelKind::MalformedNumberLiteral(base - 1).in_span(sp),
+                        ))
+                }
             }
         }
     }
@@ -138,6 +149,156 @@ fn zirco_lexer_span_to_lalrpop_span<'input>(
     })
 }
 
+/// A token in the form LALRPOP expects, or the lexer error found in its place
+type LalrpopToken<'input> =
+    Result<(usize, lexer::Tok<'input>, usize), Spanned<LexicalError<'input>>>;
+
+/// The error type of the LALRPOP parsers
+type LalrpopError<'input> = ParseError<usize, lexer::Tok<'input>, Spanned<LexicalError<'input>>>;
+
+/// Returns whether a token can only begin a statement or declaration, so a
+/// `;` expected before it most likely ends the previous statement.
+const fn starts_statement(token: &lexer::Tok<'_>) -> bool {
+    matches!(
+        token,
+        lexer::Tok::Let
+            | lexer::Tok::Const
+            | lexer::Tok::Static
+            | lexer::Tok::Return
+            | lexer::Tok::If
+            | lexer::Tok::While
+            | lexer::Tok::Do
+            | lexer::Tok::For
+            | lexer::Tok::Break
+            | lexer::Tok::Continue
+            | lexer::Tok::Switch
+            | lexer::Tok::Unreachable
+            | lexer::Tok::CompileError
+            | lexer::Tok::CompileWarning
+            | lexer::Tok::Fn
+            | lexer::Tok::Type
+            | lexer::Tok::Impl
+    )
+}
+
+/// If `error` is most likely caused by a missing semicolon, create its
+/// [`DiagnosticKind::MissingSemicolon`] and return the index in `tokens` the
+/// `;` should be inserted at.
+///
+/// A semicolon is considered missing when the parser expects one, but finds a
+/// token that [starts a new statement](starts_statement) or is on a later line
+/// than the previous token. `source` is the text `tokens` were lexed from,
+/// which starts at `byte_offset` in the file `file_name`.
+fn missing_semicolon(
+    source: &str,
+    file_name: &'static str,
+    byte_offset: usize,
+    tokens: &[LalrpopToken<'_>],
+    error: &LalrpopError<'_>,
+) -> Option<(usize, Diagnostic)> {
+    let ParseError::UnrecognizedToken {
+        token: (start, token, end),
+        expected,
+    } = error
+    else {
+        return None;
+    };
+    if !expected.iter().any(|expected| expected == "\";\"") {
+        return None;
+    }
+
+    let index = tokens.iter().position(
+        |candidate| matches!(candidate, Ok((candidate_start, _, _)) if candidate_start == start),
+    )?;
+    let Some(Ok((_, _, previous_end))) = index
+        .checked_sub(1)
+        .and_then(|previous| tokens.get(previous))
+    else {
+        return None;
+    };
+    let on_later_line = previous_end
+        .checked_sub(byte_offset)
+        .zip(start.checked_sub(byte_offset))
+        .and_then(|(between_start, between_end)| source.get(between_start..between_end))
+        .is_some_and(|between| between.contains('\n'));
+    if !starts_statement(token) && !on_later_line {
+        return None;
+    }
+
+    let semicolon_span = Span::from_positions_and_file(*previous_end, *previous_end, file_name);
+    let token_span = Span::from_positions_and_file(*start, *end, file_name);
+    Some((
+        index,
+        DiagnosticKind::MissingSemicolon(token.to_string())
+            .error_in(semicolon_span)
+            .with_label(GenericLabel::error(
+                LabelKind::MissingSemicolon.in_span(semicolon_span),
+            ))
+            .with_label(GenericLabel::note(
+                LabelKind::NextStatementStartsHere.in_span(token_span),
+            ))
+            .with_help(HelpKind::InsertSemicolon),
+    ))
+}
+
+/// Parse `tokens` with `parse`, recovering from missing semicolons.
+///
+/// Each time a [semicolon is missing](missing_semicolon), the diagnostic is
+/// recorded and the `;` is inserted, then parsing starts over so that later
+/// errors can be found too. `source` is the text `tokens` were lexed from,
+/// which starts at `byte_offset` in the file `file_name`.
+///
+/// # Errors
+/// Errors with every diagnostic found, in the order they appear in the source.
+/// Parsing stops at the first error that is not a missing semicolon.
+fn parse_recovering<'input, T>(
+    source: &str,
+    file_name: &'static str,
+    byte_offset: usize,
+    mut tokens: Vec<LalrpopToken<'input>>,
+    parse: impl Fn(Vec<LalrpopToken<'input>>) -> Result<T, LalrpopError<'input>>,
+) -> Result<T, Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    // the index of the token the last `;` was inserted before
+    let mut last_recovery = None;
+    loop {
+        let error = match parse(tokens.clone()) {
+            Ok(result) if diagnostics.is_empty() => return Ok(result),
+            Ok(_) => return Err(diagnostics),
+            Err(error) => error,
+        };
+
+        match missing_semicolon(source, file_name, byte_offset, &tokens, &error) {
+            // inserting a `;` before the same token twice would loop forever
+            Some((index, diagnostic)) if last_recovery != Some(index) => {
+                let semicolon_position = diagnostic.kind.start();
+                tokens.insert(
+                    index,
+                    Ok((
+                        semicolon_position,
+                        lexer::Tok::Semicolon,
+                        semicolon_position,
+                    )),
+                );
+                diagnostics.push(diagnostic);
+                last_recovery = Some(index + 1);
+            }
+            Some(_) | None => {
+                diagnostics.push(parser_error_to_diagnostic(error, file_name));
+                return Err(diagnostics);
+            }
+        }
+    }
+}
+
+/// Returns the first of the diagnostics from a failed [`parse_recovering`]
+fn first_diagnostic(diagnostics: Vec<Diagnostic>) -> Diagnostic {
+    diagnostics
+        .into_iter()
+        .next()
+        .expect("a failed parse should produce a diagnostic")
+}
+
 /// Parses a Zirco program with a specific file name, yielding a list of
 /// [`Declaration`]s.
 ///
@@ -155,18 +316,45 @@ fn zirco_lexer_span_to_lalrpop_span<'input>(
 ///
 /// # Errors
 /// This function returns [`Err`] with a [`Diagnostic`] if any error was
-/// encountered while parsing the input program.
+/// encountered while parsing the input program. If there are several, only the
+/// first is returned; use [`parse_program_recovering`] to get all of them.
 #[expect(clippy::result_large_err)]
 pub fn parse_program<'input>(
     input: &'input str,
     file_name: &'static str,
 ) -> Result<Vec<Spanned<Declaration<'input>>>, Diagnostic> {
-    internal_parser::ProgramParser::new()
-        .parse(
-            file_name,
-            lexer::ZircoLexer::new(input, file_name).map(zirco_lexer_span_to_lalrpop_span),
-        )
-        .map_err(parser_error_to_diagnostic)
+    parse_program_recovering(input, file_name).map_err(first_diagnostic)
+}
+
+/// Parses a Zirco program like [`parse_program`], but keeps parsing after a
+/// missing semicolon to report any later errors as well.
+///
+/// # Example
+/// ```
+/// use zrc_parser::parser::parse_program_recovering;
+/// let diagnostics = parse_program_recovering(
+///     "fn main() { let x = 1 return x }",
+///     "<test>",
+/// );
+/// assert_eq!(diagnostics.map_err(|diagnostics| diagnostics.len()), Err(2));
+/// ```
+///
+/// # Errors
+/// This function returns [`Err`] with every [`Diagnostic`] found while parsing
+/// the input program, in the order they appear in the source.
+pub fn parse_program_recovering<'input>(
+    input: &'input str,
+    file_name: &'static str,
+) -> Result<Vec<Spanned<Declaration<'input>>>, Vec<Diagnostic>> {
+    parse_recovering(
+        input,
+        file_name,
+        0,
+        lexer::ZircoLexer::new(input, file_name)
+            .map(zirco_lexer_span_to_lalrpop_span)
+            .collect(),
+        |tokens| internal_parser::ProgramParser::new().parse(file_name, tokens),
+    )
 }
 
 /// Parses a singular Zirco statement list, yielding a vector of AST [`Stmt`]
@@ -192,15 +380,17 @@ pub fn parse_stmt_list<'input>(
     input: &'input str,
     file_name: &'static str,
 ) -> Result<Spanned<Vec<Stmt<'input>>>, Diagnostic> {
-    internal_parser::StmtListParser::new()
-        .parse(
-            file_name,
-            lexer::ZircoLexer::new(input, file_name).map(zirco_lexer_span_to_lalrpop_span),
-        )
-        .map(|stmt_list| {
-            stmt_list.in_span(Span::from_positions_and_file(0, input.len(), file_name))
-        })
-        .map_err(parser_error_to_diagnostic)
+    parse_recovering(
+        input,
+        file_name,
+        0,
+        lexer::ZircoLexer::new(input, file_name)
+            .map(zirco_lexer_span_to_lalrpop_span)
+            .collect(),
+        |tokens| internal_parser::StmtListParser::new().parse(file_name, tokens),
+    )
+    .map(|stmt_list| stmt_list.in_span(Span::from_positions_and_file(0, input.len(), file_name)))
+    .map_err(first_diagnostic)
 }
 
 /// Parses a singular Zirco type, yielding an AST [`Type`] node.
@@ -230,7 +420,7 @@ pub fn parse_type<'input>(
             file_name,
             lexer::ZircoLexer::new(input, file_name).map(zirco_lexer_span_to_lalrpop_span),
         )
-        .map_err(parser_error_to_diagnostic)
+        .map_err(|error| parser_error_to_diagnostic(error, file_name))
 }
 
 /// Parses a singular Zirco expression, yielding an AST [`Expr`] node.
@@ -260,7 +450,7 @@ pub fn parse_expr<'input>(
             file_name,
             lexer::ZircoLexer::new(input, file_name).map(zirco_lexer_span_to_lalrpop_span),
         )
-        .map_err(parser_error_to_diagnostic)
+        .map_err(|error| parser_error_to_diagnostic(error, file_name))
 }
 
 /// Parses a single source chunk from the preprocessor.
@@ -270,33 +460,59 @@ pub fn parse_expr<'input>(
 ///
 /// # Errors
 /// This function returns [`Err`] with a diagnostic if any error was
-/// encountered while parsing the chunk.
+/// encountered while parsing the chunk. If there are several, only the first
+/// is returned; use [`parse_source_chunk_recovering`] to get all of them.
 #[expect(clippy::result_large_err)]
 pub fn parse_source_chunk(
     chunk: &zrc_preprocessor::SourceChunk,
 ) -> Result<Vec<Spanned<Declaration<'_>>>, Diagnostic> {
+    parse_source_chunk_recovering(chunk).map_err(first_diagnostic)
+}
+
+/// Parses a single source chunk like [`parse_source_chunk`], but keeps parsing
+/// after a missing semicolon to report any later errors as well.
+///
+/// # Errors
+/// This function returns [`Err`] with every diagnostic found while parsing the
+/// chunk, in the order they appear in the source.
+pub fn parse_source_chunk_recovering(
+    chunk: &zrc_preprocessor::SourceChunk,
+) -> Result<Vec<Spanned<Declaration<'_>>>, Vec<Diagnostic>> {
     // Convert String to &'static str using Box::leak
     let file_name: &'static str = Box::leak(chunk.file_name.clone().into_boxed_str());
 
     // Create a lexer that adjusts spans by the byte offset
     let byte_offset = chunk.byte_offset;
-    let adjusted_lexer = lexer::ZircoLexer::new(&chunk.content, file_name).map(move |spanned| {
-        // Adjust the span by adding the byte offset
-        let span = spanned.span();
-        let adjusted_span = Span::from_positions_and_file(
+    let adjust = move |span: Span| {
+        Span::from_positions_and_file(
             span.start() + byte_offset,
             span.end() + byte_offset,
             span.file_name(),
-        );
-        adjusted_span.containing(spanned.into_value())
+        )
+    };
+    let adjusted_lexer = lexer::ZircoLexer::new(&chunk.content, file_name).map(move |spanned| {
+        // Adjust the span by adding the byte offset, including the span some
+        // lexer errors point at
+        let span = spanned.span();
+        let value = spanned.into_value().map_err(|error| {
+            if let LexicalError::UnterminatedBlockComment(opened) = error {
+                LexicalError::UnterminatedBlockComment(adjust(opened))
+            } else {
+                error
+            }
+        });
+        adjust(span).containing(value)
     });
 
-    internal_parser::ProgramParser::new()
-        .parse(
-            file_name,
-            adjusted_lexer.map(zirco_lexer_span_to_lalrpop_span),
-        )
-        .map_err(parser_error_to_diagnostic)
+    parse_recovering(
+        &chunk.content,
+        file_name,
+        byte_offset,
+        adjusted_lexer
+            .map(zirco_lexer_span_to_lalrpop_span)
+            .collect(),
+        |tokens| internal_parser::ProgramParser::new().parse(file_name, tokens),
+    )
 }
 
 #[cfg(test)]
@@ -488,6 +704,27 @@ mod tests {
                 );
             }
 
+            #[test]
+            fn adjacent_string_literals_are_joined() {
+                assert_eq!(
+                    parse_expr("\"foo\" \"bar\\n\"\n\"\"", "<test>"),
+                    Ok(Expr::build_string(spanned_test!(
+                        0,
+                        ZrcString(
+                            "foobar"
+                                .split_inclusive(|_| true)
+                                .map(StringTok::Text)
+                                .chain([StringTok::EscapedNewline])
+                                .collect()
+                        ),
+                        16
+                    )))
+                );
+
+                // TEST: anything else between the literals is still an error
+                assert!(parse_expr("\"foo\" x \"bar\"", "<test>").is_err());
+            }
+
             #[test]
             fn identifiers_parse_as_expected() {
                 assert_eq!(
@@ -576,4 +813,68 @@ mod tests {
     mod stmt_list {}
 
     mod program {}
+
+    mod recovery {
+        use super::*;
+
+        /// Parse `source` and return the kind of every diagnostic found
+        fn diagnostics_in(source: &str) -> Vec<DiagnosticKind> {
+            parse_program_recovering(source, "<test>")
+                .expect_err("parsing should fail")
+                .into_iter()
+                .map(|diagnostic| diagnostic.kind.into_value())
+                .collect()
+        }
+
+        #[test]
+        fn missing_semicolon_before_a_statement_is_reported_where_it_belongs() {
+            let diagnostics = parse_program_recovering("fn f() { let x = 1 return; }", "<test>")
+                .expect_err("parsing should fail");
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(
+                diagnostics[0].kind.value(),
+                &DiagnosticKind::MissingSemicolon("return".to_string())
+            );
+            assert_eq!(
+                (diagnostics[0].kind.start(), diagnostics[0].kind.end()),
+                (18, 18)
+            );
+            assert_eq!(diagnostics[0].helps, vec![HelpKind::InsertSemicolon]);
+        }
+
+        #[test]
+        fn missing_semicolon_at_the_end_of_a_line_is_reported() {
+            assert_eq!(
+                diagnostics_in("fn f() {\n    x = g()\n    y = 2\n    return;\n}"),
+                vec![
+                    DiagnosticKind::MissingSemicolon("y".to_string()),
+                    DiagnosticKind::MissingSemicolon("return".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn parsing_recovers_to_find_later_errors() {
+            assert_eq!(
+                diagnostics_in("fn f() { let x = 1 let y = ; }"),
+                vec![
+                    DiagnosticKind::MissingSemicolon("let".to_string()),
+                    DiagnosticKind::UnrecognizedToken(";".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn other_unexpected_tokens_are_not_missing_semicolons() {
+            assert_eq!(
+                diagnostics_in("fn f() { let x = 1 2; }"),
+                vec![DiagnosticKind::UnrecognizedToken("2".to_string())]
+            );
+            assert_eq!(
+                parse_program("fn f() { let x = 1 return; }", "<test>")
+                    .map_err(|diagnostic| diagnostic.kind.into_value()),
+                Err(DiagnosticKind::MissingSemicolon("return".to_string()))
+            );
+        }
+    }
 }
@@ -0,0 +1,609 @@
+//! The functions each function refers to, and which are reachable from `main`
+//!
+//! This is a whole-program analysis over the [TAST](crate::tast). A function
+//! refers to another by naming it anywhere in its body, whether it is called
+//! or only has its address taken, so a function only reachable through a
+//! function pointer is still reachable.
+//!
+//! References are found by name, so a local variable with the same name as a
+//! function is treated as a reference to it. This only ever marks more
+//! functions as reachable, never fewer.
+//!
+//! The calls each function makes are also recorded, so the graph can be
+//! serialized as JSON nodes and edges for `--emit callgraph`.
+
+use serde::{Serialize, Serializer};
+use zrc_utils::span::Spanned;
+
+use crate::{
+    tast::{
+        expr::{Place, PlaceKind, TypedExpr, TypedExprKind},
+        stmt::{LetDeclaration, TypedDeclaration, TypedStmt, TypedStmtKind},
+        ty::Type,
+    },
+    typeck::BlockMetadata,
+};
+
+/// A function in a [`CallGraph`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionNode<'input> {
+    /// The name of the function
+    pub name: &'input str,
+    /// If any declaration of the function has a body
+    pub is_defined: bool,
+    /// If the function is a `constructor`, so it runs before `main`
+    pub is_constructor: bool,
+    /// The functions named in the body of this function, in the order they
+    /// first appear
+    pub references: Vec<&'input str>,
+    /// The functions called in the body of this function, in the order they
+    /// are first called
+    pub calls: Vec<Callee<'input>>,
+}
+
+/// What a call in a [`FunctionNode`] calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Callee<'input> {
+    /// A call to the function with this name
+    Direct(&'input str),
+    /// A call through a function pointer, whose target is not known
+    Indirect,
+}
+
+/// What is found while walking the body of a function
+#[derive(Debug, Default)]
+struct BodyReferences<'input> {
+    /// The functions named, in the order they first appear
+    functions: Vec<&'input str>,
+    /// The functions called, in the order they are first called
+    calls: Vec<Callee<'input>>,
+}
+
+/// The functions of a program and the functions each of them refers to
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CallGraph<'input> {
+    /// Every function, in the order it was first declared
+    pub functions: Vec<FunctionNode<'input>>,
+    /// The functions named outside of any function, such as in the
+    /// initializer of a global
+    pub global_references: Vec<&'input str>,
+}
+
+impl<'input> CallGraph<'input> {
+    /// Build the call graph of a type checked program
+    #[must_use]
+    pub fn from_program(program: &[Spanned<TypedDeclaration<'input>>]) -> Self {
+        let mut graph = Self::default();
+
+        // every function is known before any body is walked, so references to
+        // functions declared later are found
+        for declaration in program {
+            if let TypedDeclaration::FunctionDeclaration {
+                attributes,
+                name,
+                body,
+                ..
+            } = declaration.value()
+            {
+                let node = graph.node_mut(name.value());
+                node.is_defined |= body.is_some();
+                node.is_constructor |= attributes.constructor.is_some();
+            }
+        }
+
+        for declaration in program {
+            match declaration.value() {
+                TypedDeclaration::FunctionDeclaration {
+                    name,
+                    body: Some(body),
+                    ..
+                } => {
+                    let mut references = BodyReferences::default();
+                    graph.block_references(body.value(), &mut references);
+                    let node = graph.node_mut(name.value());
+                    node.references.extend(references.functions);
+                    node.calls.extend(references.calls);
+                }
+                TypedDeclaration::FunctionDeclaration { body: None, .. } => {}
+                TypedDeclaration::GlobalLetDeclaration(declarations) => {
+                    let mut references = BodyReferences::default();
+                    graph.let_references(declarations, &mut references);
+                    graph.global_references.extend(references.functions);
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Get the node of `name`, adding it if it does not exist
+    fn node_mut(&mut self, name: &'input str) -> &mut FunctionNode<'input> {
+        let index = self
+            .functions
+            .iter()
+            .position(|node| node.name == name)
+            .unwrap_or_else(|| {
+                self.functions.push(FunctionNode {
+                    name,
+                    is_defined: false,
+                    is_constructor: false,
+                    references: Vec::new(),
+                    calls: Vec::new(),
+                });
+                self.functions.len() - 1
+            });
+        &mut self.functions[index]
+    }
+
+    /// Get the node of `name`
+    fn node(&self, name: &str) -> Option<&FunctionNode<'input>> {
+        self.functions.iter().find(|node| node.name == name)
+    }
+
+    /// The functions every reachable function is reached from: `main`, each
+    /// constructor and each function named outside of a function.
+    ///
+    /// A program without a `main` is a library whose functions may all be
+    /// called from outside, so every function is a root.
+    #[must_use]
+    pub fn roots(&self) -> Vec<&'input str> {
+        if self.node("main").is_none() {
+            return self.functions.iter().map(|node| node.name).collect();
+        }
+
+        let mut roots = vec!["main"];
+        for name in self
+            .functions
+            .iter()
+            .filter(|node| node.is_constructor)
+            .map(|node| node.name)
+            .chain(self.global_references.iter().copied())
+        {
+            if !roots.contains(&name) {
+                roots.push(name);
+            }
+        }
+        roots
+    }
+
+    /// Returns whether each function is reachable from the [roots](Self::roots)
+    /// through the functions they refer to, in the order of
+    /// [`Self::functions`]
+    #[must_use]
+    pub fn reachability(&self) -> Vec<(&'input str, bool)> {
+        let mut reachable = self.roots();
+        let mut next = 0;
+        while let Some(name) = reachable.get(next).copied() {
+            next += 1;
+            let Some(node) = self.node(name) else {
+                continue;
+            };
+            for reference in &node.references {
+                if !reachable.contains(reference) {
+                    reachable.push(reference);
+                }
+            }
+        }
+
+        self.functions
+            .iter()
+            .map(|node| (node.name, reachable.contains(&node.name)))
+            .collect()
+    }
+
+    /// Remove every declaration of a function that is not
+    /// [reachable](Self::reachability), keeping the other declarations in
+    /// order
+    #[must_use]
+    pub fn remove_unreachable_functions(
+        program: Vec<Spanned<TypedDeclaration<'input>>>,
+    ) -> Vec<Spanned<TypedDeclaration<'input>>> {
+        let unreachable = Self::from_program(&program)
+            .reachability()
+            .into_iter()
+            .filter(|(_, reachable)| !reachable)
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+
+        program
+            .into_iter()
+            .filter(|declaration| {
+                !matches!(
+                    declaration.value(),
+                    TypedDeclaration::FunctionDeclaration { name, .. }
+                        if unreachable.contains(name.value())
+                )
+            })
+            .collect()
+    }
+
+    /// Record `name` in `references` if it is a function that is not already
+    /// there
+    fn reference(&self, name: &'input str, ty: &Type<'_>, references: &mut BodyReferences<'input>) {
+        if matches!(ty, Type::Fn(_))
+            && self.node(name).is_some()
+            && !references.functions.contains(&name)
+        {
+            references.functions.push(name);
+        }
+    }
+
+    /// Record a call to `callee` in `references` if it is not already there.
+    /// Only a call naming a function directly is [`Callee::Direct`].
+    fn call(&self, callee: &Place<'input>, references: &mut BodyReferences<'input>) {
+        let callee = match callee.kind.value() {
+            PlaceKind::Variable(name)
+                if matches!(callee.inferred_type, Type::Fn(_)) && self.node(name).is_some() =>
+            {
+                Callee::Direct(name)
+            }
+            PlaceKind::Variable(_)
+            | PlaceKind::Deref(_)
+            | PlaceKind::Index(_, _)
+            | PlaceKind::Dot(_, _) => Callee::Indirect,
+        };
+        if !references.calls.contains(&callee) {
+            references.calls.push(callee);
+        }
+    }
+
+    /// Find the functions named in a block
+    fn block_references(
+        &self,
+        block: &BlockMetadata<'input>,
+        references: &mut BodyReferences<'input>,
+    ) {
+        for stmt in &block.stmts {
+            self.stmt_references(stmt, references);
+        }
+    }
+
+    /// Find the functions named in let declarations
+    fn let_references(
+        &self,
+        declarations: &[Spanned<LetDeclaration<'input>>],
+        references: &mut BodyReferences<'input>,
+    ) {
+        for declaration in declarations {
+            if let Some(value) = &declaration.value().value {
+                self.expr_references(value, references);
+            }
+        }
+    }
+
+    /// Find the functions named in a statement
+    fn stmt_references(&self, stmt: &TypedStmt<'input>, references: &mut BodyReferences<'input>) {
+        match stmt.kind.value() {
+            TypedStmtKind::IfStmt(cond, then, otherwise) => {
+                self.expr_references(cond, references);
+                self.block_references(then.value(), references);
+                if let Some(otherwise) = otherwise {
+                    self.block_references(otherwise.value(), references);
+                }
+            }
+            TypedStmtKind::WhileStmt(cond, body) | TypedStmtKind::DoWhileStmt(body, cond) => {
+                self.expr_references(cond, references);
+                self.block_references(body.value(), references);
+            }
+            TypedStmtKind::ForStmt {
+                init,
+                cond,
+                post,
+                body,
+            } => {
+                if let Some(init) = init {
+                    self.let_references(init, references);
+                }
+                for expr in cond.iter().chain(post) {
+                    self.expr_references(expr, references);
+                }
+                self.block_references(body.value(), references);
+            }
+            TypedStmtKind::FourStmt(body) => self.block_references(body.value(), references),
+            TypedStmtKind::LabeledLoop(_, body) => self.stmt_references(body, references),
+            TypedStmtKind::SwitchCase {
+                scrutinee,
+                default,
+                cases,
+                ..
+            } => {
+                self.expr_references(scrutinee, references);
+                for (value, body) in cases {
+                    self.expr_references(value, references);
+                    self.block_references(body, references);
+                }
+                self.block_references(default, references);
+            }
+            TypedStmtKind::BlockStmt(body) => self.block_references(body, references),
+            TypedStmtKind::ExprStmt(expr) | TypedStmtKind::ReturnStmt(Some(expr)) => {
+                self.expr_references(expr, references);
+            }
+            TypedStmtKind::DeclarationList(declarations) => {
+                self.let_references(declarations, references);
+            }
+            TypedStmtKind::ContinueStmt(_)
+            | TypedStmtKind::BreakStmt(_)
+            | TypedStmtKind::ReturnStmt(None)
+            | TypedStmtKind::UnreachableStmt => {}
+        }
+    }
+
+    /// Find the functions named in a place
+    fn place_references(&self, place: &Place<'input>, references: &mut BodyReferences<'input>) {
+        match place.kind.value() {
+            PlaceKind::Variable(name) => self.reference(name, &place.inferred_type, references),
+            PlaceKind::Deref(expr) => self.expr_references(expr, references),
+            PlaceKind::Index(array, index) => {
+                self.expr_references(array, references);
+                self.expr_references(index, references);
+            }
+            PlaceKind::Dot(place, _) => self.place_references(place, references),
+        }
+    }
+
+    /// Find the functions named in an expression
+    fn expr_references(&self, expr: &TypedExpr<'input>, references: &mut BodyReferences<'input>) {
+        match expr.kind.value() {
+            TypedExprKind::Identifier(name) => {
+                self.reference(name, &expr.inferred_type, references);
+            }
+            TypedExprKind::Comma(lhs, rhs)
+            | TypedExprKind::BinaryBitwise(_, lhs, rhs)
+            | TypedExprKind::Logical(_, lhs, rhs)
+            | TypedExprKind::Equality(_, lhs, rhs)
+            | TypedExprKind::Comparison(_, lhs, rhs)
+            | TypedExprKind::Arithmetic(_, lhs, rhs)
+            | TypedExprKind::Index(lhs, rhs)
+            | TypedExprKind::Elvis(lhs, rhs)
+            | TypedExprKind::Rotate(_, lhs, rhs) => {
+                self.expr_references(lhs, references);
+                self.expr_references(rhs, references);
+            }
+            TypedExprKind::Assignment(place, value) => {
+                self.place_references(place, references);
+                self.expr_references(value, references);
+            }
+            TypedExprKind::UnaryNot(value)
+            | TypedExprKind::UnaryBitwiseNot(value)
+            | TypedExprKind::UnaryMinus(value)
+            | TypedExprKind::UnaryDereference(value)
+            | TypedExprKind::Cast(value, _)
+            | TypedExprKind::IntegerConversion(_, value, _)
+            | TypedExprKind::ArrayRepeat(value, _) => self.expr_references(value, references),
+            TypedExprKind::UnaryAddressOf(place)
+            | TypedExprKind::PrefixIncrement(place)
+            | TypedExprKind::PrefixDecrement(place)
+            | TypedExprKind::PostfixIncrement(place)
+            | TypedExprKind::PostfixDecrement(place)
+            | TypedExprKind::Dot(place, _) => self.place_references(place, references),
+            TypedExprKind::Call(callee, arguments) => {
+                self.call(callee, references);
+                self.place_references(callee, references);
+                for argument in arguments {
+                    self.expr_references(argument, references);
+                }
+            }
+            TypedExprKind::Ternary(cond, then, otherwise) => {
+                self.expr_references(cond, references);
+                self.expr_references(then, references);
+                self.expr_references(otherwise, references);
+            }
+            TypedExprKind::Match(spilled, scrutinee, arms) => {
+                if let Some((_, value)) = spilled {
+                    self.expr_references(value, references);
+                }
+                self.expr_references(scrutinee, references);
+                for arm in arms {
+                    let binding = arm.binding.as_ref().map(|(_, value)| value);
+                    for expr in arm.trigger.iter().chain(binding) {
+                        self.expr_references(expr, references);
+                    }
+                    self.expr_references(&arm.value, references);
+                }
+            }
+            TypedExprKind::StructConstruction(fields) => {
+                for (_, value) in fields.iter() {
+                    self.expr_references(value, references);
+                }
+            }
+            TypedExprKind::ArrayLiteral(elements) => {
+                for element in elements {
+                    self.expr_references(element, references);
+                }
+            }
+            TypedExprKind::SizeOf(_)
+            | TypedExprKind::AlignOf(_)
+            | TypedExprKind::NumberLiteral(_, _)
+            | TypedExprKind::StringLiteral(_)
+            | TypedExprKind::CharLiteral(_)
+            | TypedExprKind::BooleanLiteral(_) => {}
+        }
+    }
+}
+
+/// A function in the JSON form of a [`CallGraph`]
+#[derive(Serialize)]
+struct JsonNode<'input> {
+    /// The name of the function
+    name: &'input str,
+    /// If any declaration of the function has a body
+    defined: bool,
+}
+
+/// A call in the JSON form of a [`CallGraph`]
+#[derive(Serialize)]
+struct JsonEdge<'input> {
+    /// The function making the call
+    from: &'input str,
+    /// The function called, or `null` for an indirect call
+    to: Option<&'input str>,
+    /// If the call is through a function pointer
+    indirect: bool,
+}
+
+/// The JSON form of a [`CallGraph`]
+#[derive(Serialize)]
+struct JsonCallGraph<'input> {
+    /// Every function, in the order it was first declared
+    nodes: Vec<JsonNode<'input>>,
+    /// Every call, grouped by the calling function
+    edges: Vec<JsonEdge<'input>>,
+}
+
+/// A call graph is serialized as its `nodes`, the functions, and its
+/// `edges`, the calls between them. An indirect call has a `null` target.
+impl Serialize for CallGraph<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        JsonCallGraph {
+            nodes: self
+                .functions
+                .iter()
+                .map(|node| JsonNode {
+                    name: node.name,
+                    defined: node.is_defined,
+                })
+                .collect(),
+            edges: self
+                .functions
+                .iter()
+                .flat_map(|node| {
+                    node.calls.iter().map(|callee| match *callee {
+                        Callee::Direct(name) => JsonEdge {
+                            from: node.name,
+                            to: Some(name),
+                            indirect: false,
+                        },
+                        Callee::Indirect => JsonEdge {
+                            from: node.name,
+                            to: None,
+                            indirect: true,
+                        },
+                    })
+                })
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CallGraph;
+    use crate::{
+        tast::stmt::TypedDeclaration,
+        typeck::{GlobalScope, type_program},
+    };
+
+    /// Type check `source` and return whether each function is reachable
+    fn reachability_of(source: &str) -> Vec<(&str, bool)> {
+        let program = type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(source, "<test>").expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+        CallGraph::from_program(&program).reachability()
+    }
+
+    #[test]
+    fn functions_never_called_from_main_are_unreachable() {
+        assert_eq!(
+            reachability_of(
+                "fn leaf() -> i32 { return 1; }
+                fn helper() -> i32 { return leaf(); }
+                fn dead() -> i32 { return helper(); }
+                fn main() -> i32 { return helper(); }"
+            ),
+            vec![
+                ("leaf", true),
+                ("helper", true),
+                ("dead", false),
+                ("main", true)
+            ]
+        );
+    }
+
+    #[test]
+    fn functions_used_as_values_and_constructors_are_reachable() {
+        assert_eq!(
+            reachability_of(
+                "fn pointee() -> i32 { return 1; }
+                constructor fn init() {}
+                fn dead() {}
+                fn main() -> i32 { let f = &pointee; return (*f)(); }"
+            ),
+            vec![
+                ("pointee", true),
+                ("init", true),
+                ("dead", false),
+                ("main", true)
+            ]
+        );
+    }
+
+    #[test]
+    fn unreachable_functions_are_removed() {
+        let program = type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(
+                "fn dead();
+                fn used() {}
+                fn dead() { used(); }
+                let x: i32 = 1;
+                fn main() -> i32 { used(); return x; }",
+                "<test>",
+            )
+            .expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        // TEST: both declarations of `dead` are removed, and the global is kept
+        let kept = CallGraph::remove_unreachable_functions(program)
+            .into_iter()
+            .map(|declaration| match declaration.into_value() {
+                TypedDeclaration::FunctionDeclaration { name, .. } => (*name.value()).to_string(),
+                TypedDeclaration::GlobalLetDeclaration(_) => "<global>".to_string(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(kept, vec!["used", "<global>", "main"]);
+    }
+
+    #[test]
+    fn every_function_of_a_library_is_reachable() {
+        assert_eq!(
+            reachability_of("fn a() {} fn b() {}"),
+            vec![("a", true), ("b", true)]
+        );
+    }
+
+    #[test]
+    fn call_graph_serializes_calls_as_edges() {
+        let program = type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(
+                "fn b() -> i32 { return 1; }
+                fn a() -> i32 { b(); let f = &b; return (*f)() + b(); }
+                fn c();",
+                "<test>",
+            )
+            .expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        // TEST: each call is one edge, and the call through `f` is indirect
+        assert_eq!(
+            serde_json::to_value(CallGraph::from_program(&program))
+                .expect("the call graph should serialize"),
+            serde_json::json!({
+                "nodes": [
+                    { "name": "b", "defined": true },
+                    { "name": "a", "defined": true },
+                    { "name": "c", "defined": false },
+                ],
+                "edges": [
+                    { "from": "a", "to": "b", "indirect": false },
+                    { "from": "a", "to": null, "indirect": true },
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,337 @@
+//! Reprinting a source file with the type inferred for each expression
+//!
+//! This is what `--emit explain-types` prints. Every line of the file is
+//! followed by one comment for each expression ending on it, giving the source
+//! text of the expression and its [`Type`]. Subexpressions come before the
+//! expressions containing them, so `a + b * 2` is explained as `a`, `b`, `2`,
+//! `b * 2` and then `a + b * 2`.
+//!
+//! Only expressions whose spans are in the file being explained are shown, so
+//! the contents of included files are skipped.
+
+use std::{cmp::Reverse, fmt::Write};
+
+use zrc_utils::span::{Span, Spanned};
+
+use crate::{
+    tast::{
+        expr::{Place, PlaceKind, TypedExpr, TypedExprKind},
+        stmt::{LetDeclaration, TypedDeclaration, TypedStmt, TypedStmtKind},
+        ty::Type,
+    },
+    typeck::BlockMetadata,
+};
+
+/// An expression in the explained file and the type inferred for it
+#[derive(Debug, PartialEq, Eq)]
+struct Annotation {
+    /// Where the expression starts in the file
+    start: usize,
+    /// Where the expression ends in the file
+    end: usize,
+    /// The inferred type, as it is written in Zirco
+    ty: String,
+}
+
+/// The expressions found while walking a program
+#[derive(Debug)]
+struct Annotations<'a> {
+    /// The file whose expressions are kept
+    file_name: &'a str,
+    /// Every expression found, without duplicates
+    found: Vec<Annotation>,
+}
+
+impl Annotations<'_> {
+    /// Record that the expression at `span` has the type `ty`, if it is in
+    /// the explained file. Desugaring may produce several expressions with
+    /// the same span and type, which are only recorded once.
+    fn annotate(&mut self, span: Span, ty: &Type<'_>) {
+        if span.file_name() != self.file_name || span.start() == span.end() {
+            return;
+        }
+
+        let annotation = Annotation {
+            start: span.start(),
+            end: span.end(),
+            ty: ty.to_string(),
+        };
+        if !self.found.contains(&annotation) {
+            self.found.push(annotation);
+        }
+    }
+
+    /// Find the expressions in a block
+    fn block(&mut self, block: &BlockMetadata<'_>) {
+        for stmt in &block.stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    /// Find the expressions in let declarations
+    fn lets(&mut self, declarations: &[Spanned<LetDeclaration<'_>>]) {
+        for declaration in declarations {
+            if let Some(value) = &declaration.value().value {
+                self.expr(value);
+            }
+        }
+    }
+
+    /// Find the expressions in a statement
+    fn stmt(&mut self, stmt: &TypedStmt<'_>) {
+        match stmt.kind.value() {
+            TypedStmtKind::IfStmt(cond, then, otherwise) => {
+                self.expr(cond);
+                self.block(then.value());
+                if let Some(otherwise) = otherwise {
+                    self.block(otherwise.value());
+                }
+            }
+            TypedStmtKind::WhileStmt(cond, body) => {
+                self.expr(cond);
+                self.block(body.value());
+            }
+            TypedStmtKind::DoWhileStmt(body, cond) => {
+                self.block(body.value());
+                self.expr(cond);
+            }
+            TypedStmtKind::ForStmt {
+                init,
+                cond,
+                post,
+                body,
+            } => {
+                if let Some(init) = init {
+                    self.lets(init);
+                }
+                for expr in cond.iter().chain(post) {
+                    self.expr(expr);
+                }
+                self.block(body.value());
+            }
+            TypedStmtKind::FourStmt(body) => self.block(body.value()),
+            TypedStmtKind::LabeledLoop(_, body) => self.stmt(body),
+            TypedStmtKind::SwitchCase {
+                scrutinee,
+                default,
+                cases,
+                ..
+            } => {
+                self.expr(scrutinee);
+                for (value, body) in cases {
+                    self.expr(value);
+                    self.block(body);
+                }
+                self.block(default);
+            }
+            TypedStmtKind::BlockStmt(body) => self.block(body),
+            TypedStmtKind::ExprStmt(expr) | TypedStmtKind::ReturnStmt(Some(expr)) => {
+                self.expr(expr);
+            }
+            TypedStmtKind::DeclarationList(declarations) => self.lets(declarations),
+            TypedStmtKind::ContinueStmt(_)
+            | TypedStmtKind::BreakStmt(_)
+            | TypedStmtKind::ReturnStmt(None)
+            | TypedStmtKind::UnreachableStmt => {}
+        }
+    }
+
+    /// Find the expressions in a place, and the place itself
+    fn place(&mut self, place: &Place<'_>) {
+        match place.kind.value() {
+            PlaceKind::Variable(_) => {}
+            PlaceKind::Deref(expr) => self.expr(expr),
+            PlaceKind::Index(array, index) => {
+                self.expr(array);
+                self.expr(index);
+            }
+            PlaceKind::Dot(place, _) => self.place(place),
+        }
+        self.annotate(place.kind.span(), &place.inferred_type);
+    }
+
+    /// Find the subexpressions of an expression, and the expression itself
+    fn expr(&mut self, expr: &TypedExpr<'_>) {
+        match expr.kind.value() {
+            TypedExprKind::Comma(lhs, rhs)
+            | TypedExprKind::BinaryBitwise(_, lhs, rhs)
+            | TypedExprKind::Logical(_, lhs, rhs)
+            | TypedExprKind::Equality(_, lhs, rhs)
+            | TypedExprKind::Comparison(_, lhs, rhs)
+            | TypedExprKind::Arithmetic(_, lhs, rhs)
+            | TypedExprKind::Index(lhs, rhs)
+            | TypedExprKind::Elvis(lhs, rhs)
+            | TypedExprKind::Rotate(_, lhs, rhs) => {
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            TypedExprKind::Assignment(place, value) => {
+                self.place(place);
+                self.expr(value);
+            }
+            TypedExprKind::UnaryNot(value)
+            | TypedExprKind::UnaryBitwiseNot(value)
+            | TypedExprKind::UnaryMinus(value)
+            | TypedExprKind::UnaryDereference(value)
+            | TypedExprKind::Cast(value, _)
+            | TypedExprKind::IntegerConversion(_, value, _)
+            | TypedExprKind::ArrayRepeat(value, _) => self.expr(value),
+            TypedExprKind::UnaryAddressOf(place)
+            | TypedExprKind::PrefixIncrement(place)
+            | TypedExprKind::PrefixDecrement(place)
+            | TypedExprKind::PostfixIncrement(place)
+            | TypedExprKind::PostfixDecrement(place)
+            | TypedExprKind::Dot(place, _) => self.place(place),
+            TypedExprKind::Call(callee, arguments) => {
+                self.place(callee);
+                for argument in arguments {
+                    self.expr(argument);
+                }
+            }
+            TypedExprKind::Ternary(cond, then, otherwise) => {
+                self.expr(cond);
+                self.expr(then);
+                self.expr(otherwise);
+            }
+            TypedExprKind::Match(spilled, scrutinee, arms) => {
+                if let Some((_, value)) = spilled {
+                    self.expr(value);
+                }
+                self.expr(scrutinee);
+                for arm in arms {
+                    let binding = arm.binding.as_ref().map(|(_, value)| value);
+                    for expr in arm.trigger.iter().chain(binding) {
+                        self.expr(expr);
+                    }
+                    self.expr(&arm.value);
+                }
+            }
+            TypedExprKind::StructConstruction(fields) => {
+                for (_, value) in fields.iter() {
+                    self.expr(value);
+                }
+            }
+            TypedExprKind::ArrayLiteral(elements) => {
+                for element in elements {
+                    self.expr(element);
+                }
+            }
+            TypedExprKind::SizeOf(_)
+            | TypedExprKind::AlignOf(_)
+            | TypedExprKind::NumberLiteral(_, _)
+            | TypedExprKind::StringLiteral(_)
+            | TypedExprKind::CharLiteral(_)
+            | TypedExprKind::Identifier(_)
+            | TypedExprKind::BooleanLiteral(_) => {}
+        }
+        self.annotate(expr.kind.span(), &expr.inferred_type);
+    }
+}
+
+/// Reprint `source`, the contents of the file `file_name`, with a comment
+/// after each line giving the type of every expression of `program` that ends
+/// on it
+#[must_use]
+pub fn explain_types(
+    program: &[Spanned<TypedDeclaration<'_>>],
+    file_name: &str,
+    source: &str,
+) -> String {
+    let mut annotations = Annotations {
+        file_name,
+        found: Vec::new(),
+    };
+    for declaration in program {
+        match declaration.value() {
+            TypedDeclaration::FunctionDeclaration {
+                body: Some(body), ..
+            } => annotations.block(body.value()),
+            TypedDeclaration::FunctionDeclaration { body: None, .. } => {}
+            TypedDeclaration::GlobalLetDeclaration(declarations) => {
+                annotations.lets(declarations);
+            }
+        }
+    }
+
+    // the sort is stable, so expressions with the same span stay in the order
+    // they were found in
+    let mut found = annotations.found;
+    found.sort_by_key(|annotation| (annotation.end, Reverse(annotation.start)));
+    let mut found = found.into_iter().peekable();
+
+    let mut output = String::new();
+    let mut line_end = 0;
+    for line in source.split_inclusive('\n') {
+        line_end += line.len();
+        output.push_str(line);
+        if !line.ends_with('\n') {
+            output.push('\n');
+        }
+
+        let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        while let Some(annotation) = found.next_if(|annotation| annotation.end <= line_end) {
+            if let Some(text) = source.get(annotation.start..annotation.end) {
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                writeln!(output, "{indent}// {text}: {}", annotation.ty)
+                    .expect("writing to a string should succeed");
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::explain_types;
+    use crate::typeck::{GlobalScope, type_program};
+
+    /// Type check `source` and explain the types in it
+    fn explained(source: &str) -> String {
+        let program = type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(source, "<test>").expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+        explain_types(&program, "<test>", source)
+    }
+
+    #[test]
+    fn each_expression_is_followed_by_its_type() {
+        assert_eq!(
+            explained(concat!(
+                "fn main() -> i32 {\n",
+                "    let x: u8 = 1;\n",
+                "    return (x as i32) + 2 * 3;\n",
+                "}\n"
+            )),
+            concat!(
+                "fn main() -> i32 {\n",
+                "    let x: u8 = 1;\n",
+                "    // 1: u8\n",
+                "    return (x as i32) + 2 * 3;\n",
+                "    // x: u8\n",
+                "    // x as i32: i32\n",
+                "    // 2: i32\n",
+                "    // 3: i32\n",
+                "    // 2 * 3: i32\n",
+                "    // (x as i32) + 2 * 3: i32\n",
+                "}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn expressions_spanning_lines_are_explained_after_their_last_line() {
+        assert_eq!(
+            explained("fn f(a: i32, b: i32) -> bool { return a ==\n    b; }"),
+            concat!(
+                "fn f(a: i32, b: i32) -> bool { return a ==\n",
+                "// a: i32\n",
+                "    b; }\n",
+                "    // b: i32\n",
+                "    // a == b: bool\n",
+            )
+        );
+    }
+}
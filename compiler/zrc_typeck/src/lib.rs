@@ -62,5 +62,8 @@
     clippy::large_enum_variant
 )]
 
+pub mod callgraph;
+pub mod explain;
 pub mod tast;
 pub mod typeck;
+pub mod used_types;
@@ -3,6 +3,22 @@
 //! This is similar to the [standard AST](zrc_parser::ast), but with type
 //! information attached to it.
 
+use std::fmt::Display;
+
 pub mod expr;
 pub mod stmt;
 pub mod ty;
+
+/// Render a TAST node, keeping the alternate (`{:#}`) flag of the formatter it
+/// is nested in.
+///
+/// The alternate form of a TAST omits the type of every expression, and
+/// instead marks each implicit coercion inserted by the type checker as
+/// `coerce<T>(x)`.
+fn render(node: &impl Display, alternate: bool) -> String {
+    if alternate {
+        format!("{node:#}")
+    } else {
+        node.to_string()
+    }
+}
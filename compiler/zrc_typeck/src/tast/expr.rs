@@ -2,17 +2,67 @@
 
 use std::fmt::Display;
 
+use serde::Serialize;
 pub use zrc_parser::{
     ast::expr::{Arithmetic, BinaryBitwise, Comparison, Equality, Logical},
     lexer::{NumberLiteral, StringTok, ZrcString},
 };
 use zrc_utils::span::Spanned;
 
-use super::ty::Type;
+use super::{render, ty::Type};
 use crate::tast::ty::OrderedValueFields;
 
+/// The explicit integer width conversions, invoked as built-in functions
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+pub enum IntegerConversion {
+    /// `truncate(x, T)` - discard the high bits of `x`
+    Truncate,
+    /// `sign_extend(x, T)` - widen `x`, copying its sign bit
+    SignExtend,
+    /// `zero_extend(x, T)` - widen `x`, filling with zeros
+    ZeroExtend,
+}
+
+impl Display for IntegerConversion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Truncate => "truncate",
+                Self::SignExtend => "sign_extend",
+                Self::ZeroExtend => "zero_extend",
+            }
+        )
+    }
+}
+
+/// The directions of the integer rotations, invoked as built-in functions
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize)]
+pub enum Rotation {
+    /// `rotate_left(x, n)` - shift `x` left by `n`, wrapping the high bits
+    /// around into the low bits
+    Left,
+    /// `rotate_right(x, n)` - shift `x` right by `n`, wrapping the low bits
+    /// around into the high bits
+    Right,
+}
+
+impl Display for Rotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Left => "rotate_left",
+                Self::Right => "rotate_right",
+            }
+        )
+    }
+}
+
 /// The left hand side of an assignment.
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize)]
 pub struct Place<'input> {
     /// The inferred [`Type`] of this node
     pub inferred_type: Type<'input>,
@@ -26,7 +76,7 @@ pub struct Place<'input> {
 /// - A variable or an property access of a place
 /// - A dereference or index into any expression yielding a pointer
 #[expect(variant_size_differences)]
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize)]
 pub enum PlaceKind<'input> {
     /// `*x`
     Deref(Box<TypedExpr<'input>>),
@@ -40,7 +90,7 @@ pub enum PlaceKind<'input> {
 
 /// An [expression kind](TypedExprKind) with its yielded [result
 /// type](super::ty::Type) attached to it.
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize)]
 pub struct TypedExpr<'input> {
     /// The inferred [`Type`] of this node
     pub inferred_type: Type<'input>,
@@ -48,8 +98,21 @@ pub struct TypedExpr<'input> {
     pub kind: Spanned<TypedExprKind<'input>>,
 }
 
+/// An arm of a [`TypedExprKind::Match`]
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct MatchArm<'input> {
+    /// The value of the scrutinee this arm is taken for, or [`None`] for the
+    /// `default` arm, which is always last
+    pub trigger: Option<TypedExpr<'input>>,
+    /// The name the value of the matched variant is bound to, and the
+    /// expression reading that value
+    pub binding: Option<(&'input str, TypedExpr<'input>)>,
+    /// The value the match yields when this arm is taken
+    pub value: TypedExpr<'input>,
+}
+
 /// The kind of a [`TypedExpr`]
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize)]
 pub enum TypedExprKind<'input> {
     /// `a, b`
     Comma(Box<TypedExpr<'input>>, Box<TypedExpr<'input>>),
@@ -106,17 +169,42 @@ pub enum TypedExprKind<'input> {
         Box<TypedExpr<'input>>,
         Box<TypedExpr<'input>>,
     ),
+    /// `a ?: b` - `a` if it is not zero, otherwise `b`. `a` is evaluated once.
+    Elvis(Box<TypedExpr<'input>>, Box<TypedExpr<'input>>),
+    /// `match (x) { A => a, B: y => b }`, desugared to a switch over the
+    /// discriminant of `x` (or `x` itself, for a `bool`) that yields the value
+    /// of the arm taken. An enum scrutinee is first stored in the named hidden
+    /// local, which the switched value and the bindings read, so that it is
+    /// only evaluated once.
+    Match(
+        Option<(&'input str, Box<TypedExpr<'input>>)>,
+        Box<TypedExpr<'input>>,
+        Vec<MatchArm<'input>>,
+    ),
 
     /// `x as T`
     Cast(Box<TypedExpr<'input>>, Spanned<Type<'input>>),
     /// `sizeof(T)`
     SizeOf(Type<'input>),
+    /// `alignof_expr(x)`, pre-resolved to the type of `x`
+    AlignOf(Type<'input>),
+    /// `truncate(x, T)`, `sign_extend(x, T)` or `zero_extend(x, T)`
+    IntegerConversion(
+        IntegerConversion,
+        Box<TypedExpr<'input>>,
+        Spanned<Type<'input>>,
+    ),
+    /// `rotate_left(x, n)` or `rotate_right(x, n)`
+    Rotate(Rotation, Box<TypedExpr<'input>>, Box<TypedExpr<'input>>),
 
     /// `new Type { field1: value1, field2: value2, ... }`
     StructConstruction(OrderedValueFields<'input>),
 
     /// `[expr1, expr2, expr3, ...]` - array literal
     ArrayLiteral(Vec<TypedExpr<'input>>),
+    /// `[value; count]` - an array of `count` copies of `value`, which is
+    /// evaluated once
+    ArrayRepeat(Box<TypedExpr<'input>>, u64),
 
     /// Any numeric literal.
     NumberLiteral(NumberLiteral<'input>, Type<'input>),
@@ -153,32 +241,49 @@ enum Precedence {
     Equality = 9,
     /// Comparison operators
     Comparison = 10,
+    /// Shift operators
+    Shift = 11,
     /// Addition and subtraction
-    Term = 11,
+    Term = 12,
     /// Multiplication, division, modulo
-    Factor = 12,
+    Factor = 13,
     /// Cast operator
-    Cast = 13,
+    Cast = 14,
     /// Unary operators
-    Unary = 14,
+    Unary = 15,
     /// Postfix operators (highest precedence)
-    Postfix = 15,
+    Postfix = 16,
     /// Primary expressions (literals, identifiers)
-    Primary = 16,
+    Primary = 17,
 }
 
-impl TypedExprKind<'_> {
+impl<'input> TypedExprKind<'input> {
+    /// The type this expression has before any implicit coercion, if it can be
+    /// told from the expression alone
+    #[expect(clippy::wildcard_enum_match_arm)]
+    fn uncoerced_type(&self) -> Option<Type<'input>> {
+        match self {
+            Self::NumberLiteral(_, ty) => Some(ty.clone()),
+            Self::StringLiteral(_) => Some(Type::Ptr(Box::new(Type::U8))),
+            Self::UnaryMinus(x) | Self::UnaryBitwiseNot(x) => Some(x.inferred_type.clone()),
+            Self::UnaryAddressOf(place) => Some(Type::Ptr(Box::new(place.inferred_type.clone()))),
+            Self::Cast(_, ty) | Self::IntegerConversion(_, _, ty) => Some(ty.value().clone()),
+            _ => None,
+        }
+    }
+
     /// Get the precedence level of this typed expression kind
     const fn precedence(&self) -> Precedence {
         match self {
             Self::Comma(_, _) => Precedence::Comma,
             Self::Assignment(_, _) => Precedence::Assignment,
-            Self::Ternary(_, _, _) => Precedence::Ternary,
+            Self::Ternary(_, _, _) | Self::Elvis(_, _) => Precedence::Ternary,
             Self::Logical(Logical::Or, _, _) => Precedence::LogicalOr,
             Self::Logical(Logical::And, _, _) => Precedence::LogicalAnd,
             Self::BinaryBitwise(BinaryBitwise::Or, _, _) => Precedence::BitwiseOr,
             Self::BinaryBitwise(BinaryBitwise::Xor, _, _) => Precedence::BitwiseXor,
             Self::BinaryBitwise(BinaryBitwise::And, _, _) => Precedence::BitwiseAnd,
+            Self::BinaryBitwise(BinaryBitwise::Shl | BinaryBitwise::Shr, _, _) => Precedence::Shift,
             Self::Equality(_, _, _) => Precedence::Equality,
             Self::Comparison(_, _, _) => Precedence::Comparison,
             Self::Arithmetic(Arithmetic::Addition | Arithmetic::Subtraction, _, _) => {
@@ -197,10 +302,13 @@ impl TypedExprKind<'_> {
             | Self::UnaryDereference(_)
             | Self::PrefixIncrement(_)
             | Self::PrefixDecrement(_)
-            | Self::SizeOf(_) => Precedence::Unary,
+            | Self::SizeOf(_)
+            | Self::AlignOf(_) => Precedence::Unary,
             Self::Index(_, _)
             | Self::Dot(_, _)
             | Self::Call(_, _)
+            | Self::IntegerConversion(_, _, _)
+            | Self::Rotate(_, _, _)
             | Self::PostfixIncrement(_)
             | Self::PostfixDecrement(_) => Precedence::Postfix,
             Self::NumberLiteral(_, _)
@@ -209,7 +317,9 @@ impl TypedExprKind<'_> {
             | Self::Identifier(_)
             | Self::BooleanLiteral(_)
             | Self::StructConstruction(_)
-            | Self::ArrayLiteral(_) => Precedence::Primary,
+            | Self::Match(_, _, _)
+            | Self::ArrayLiteral(_)
+            | Self::ArrayRepeat(_, _) => Precedence::Primary,
         }
     }
 
@@ -232,39 +342,52 @@ impl TypedExprKind<'_> {
         };
 
         if needs_parens {
-            write!(f, "({child})")
+            write!(f, "({})", render(child, f.alternate()))
         } else {
-            write!(f, "{child}")
+            write!(f, "{}", render(child, f.alternate()))
         }
     }
 }
 
 impl Display for Place<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.kind.value())
+        write!(f, "{}", render(self.kind.value(), f.alternate()))
     }
 }
 
 impl Display for PlaceKind<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let alt = f.alternate();
         match self {
-            Self::Deref(expr) => write!(f, "*{expr}"),
+            Self::Deref(expr) => write!(f, "*{}", render(expr, alt)),
             Self::Variable(name) => write!(f, "{name}"),
-            Self::Index(lhs, rhs) => write!(f, "{lhs}[{rhs}]"),
-            Self::Dot(place, field) => write!(f, "{place}.{field}"),
+            Self::Index(lhs, rhs) => write!(f, "{}[{}]", render(lhs, alt), render(rhs, alt)),
+            Self::Dot(place, field) => write!(f, "{}.{field}", render(place, alt)),
         }
     }
 }
 
 impl Display for TypedExpr<'_> {
+    /// Writes `(x as T)`, or with `{:#}` just `x`, wrapped in `coerce<T>(x)`
+    /// if the type checker implicitly converted it to `T`
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({} as {})", self.kind.value(), self.inferred_type)
+        if !f.alternate() {
+            return write!(f, "({} as {})", self.kind.value(), self.inferred_type);
+        }
+
+        match self.kind.value().uncoerced_type() {
+            Some(uncoerced) if uncoerced != self.inferred_type => {
+                write!(f, "coerce<{}>({:#})", self.inferred_type, self.kind.value())
+            }
+            _ => write!(f, "{:#}", self.kind.value()),
+        }
     }
 }
 
 impl Display for TypedExprKind<'_> {
     #[expect(clippy::too_many_lines)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let alt = f.alternate();
         match self {
             Self::Comma(lhs, rhs) => {
                 let prec = self.precedence();
@@ -274,7 +397,7 @@ impl Display for TypedExprKind<'_> {
             }
             Self::Assignment(place, rhs) => {
                 let prec = self.precedence();
-                write!(f, "{place} = ")?;
+                write!(f, "{} = ", render(place, alt))?;
                 Self::fmt_child(f, rhs, prec, true)
             }
             Self::BinaryBitwise(op, lhs, rhs) => {
@@ -319,47 +442,84 @@ impl Display for TypedExprKind<'_> {
                 write!(f, "-")?;
                 Self::fmt_child(f, expr, Precedence::Unary, true)
             }
-            Self::UnaryAddressOf(place) => write!(f, "&{place}"),
+            Self::UnaryAddressOf(place) => write!(f, "&{}", render(place, alt)),
             Self::UnaryDereference(expr) => {
                 write!(f, "*")?;
                 Self::fmt_child(f, expr, Precedence::Unary, true)
             }
-            Self::PrefixIncrement(place) => write!(f, "++{place}"),
-            Self::PrefixDecrement(place) => write!(f, "--{place}"),
+            Self::PrefixIncrement(place) => write!(f, "++{}", render(place, alt)),
+            Self::PrefixDecrement(place) => write!(f, "--{}", render(place, alt)),
             Self::Index(lhs, rhs) => {
                 Self::fmt_child(f, lhs, Precedence::Postfix, false)?;
-                write!(f, "[{rhs}]")
+                write!(f, "[{}]", render(rhs, alt))
             }
-            Self::Dot(place, field) => write!(f, "{place}.{field}"),
+            Self::Dot(place, field) => write!(f, "{}.{field}", render(place, alt)),
             Self::Call(place, args) => write!(
                 f,
-                "{place}({})",
+                "{}({})",
+                render(place, alt),
                 args.iter()
-                    .map(ToString::to_string)
+                    .map(|arg| render(arg, alt))
                     .collect::<Vec<String>>()
                     .join(", ")
             ),
-            Self::PostfixIncrement(place) => write!(f, "{place}++"),
-            Self::PostfixDecrement(place) => write!(f, "{place}--"),
+            Self::PostfixIncrement(place) => write!(f, "{}++", render(place, alt)),
+            Self::PostfixDecrement(place) => write!(f, "{}--", render(place, alt)),
             Self::Ternary(cond, if_true, if_false) => {
                 let prec = self.precedence();
                 Self::fmt_child(f, cond, prec, false)?;
-                write!(f, " ? {if_true} : ")?;
+                write!(f, " ? {} : ", render(if_true, alt))?;
                 Self::fmt_child(f, if_false, prec, true)
             }
+            Self::Elvis(value, fallback) => {
+                let prec = self.precedence();
+                // `?:` groups to the right, so only the value needs parentheses
+                // around another `?:`
+                Self::fmt_child(f, value, prec, true)?;
+                write!(f, " ?: ")?;
+                Self::fmt_child(f, fallback, prec, false)
+            }
+            Self::Match(spilled, scrutinee, arms) => write!(
+                f,
+                "match ({}) {{ {} }}",
+                spilled
+                    .as_ref()
+                    .map_or_else(|| render(scrutinee, alt), |(_, value)| render(value, alt)),
+                arms.iter()
+                    .map(|arm| {
+                        let trigger = arm
+                            .trigger
+                            .as_ref()
+                            .map_or_else(|| "default".to_string(), |x| render(x, alt));
+                        let binding = arm
+                            .binding
+                            .as_ref()
+                            .map_or_else(String::new, |(name, _)| format!(": {name}"));
+                        format!("{trigger}{binding} => {}", render(&arm.value, alt))
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
             Self::Cast(expr, ty) => {
                 let prec = self.precedence();
                 Self::fmt_child(f, expr, prec, false)?;
                 write!(f, " as {ty}")
             }
             Self::SizeOf(ty) => write!(f, "sizeof {ty}"),
+            Self::AlignOf(ty) => write!(f, "alignof {ty}"),
+            Self::IntegerConversion(conversion, expr, ty) => {
+                write!(f, "{conversion}({}, {ty})", render(expr, alt))
+            }
+            Self::Rotate(rotation, x, amount) => {
+                write!(f, "{rotation}({}, {})", render(x, alt), render(amount, alt))
+            }
             Self::StructConstruction(fields) => {
                 write!(
                     f,
                     "{{ {} }}",
                     fields
                         .iter()
-                        .map(|(name, expr)| format!("{name}: ({expr})"))
+                        .map(|(name, expr)| format!("{name}: ({})", render(expr, alt)))
                         .collect::<Vec<String>>()
                         .join(", ")
                 )
@@ -370,11 +530,12 @@ impl Display for TypedExprKind<'_> {
                     "[{}]",
                     elements
                         .iter()
-                        .map(ToString::to_string)
+                        .map(|element| render(element, alt))
                         .collect::<Vec<String>>()
                         .join(", ")
                 )
             }
+            Self::ArrayRepeat(value, count) => write!(f, "[{}; {count}]", render(value, alt)),
             Self::NumberLiteral(num, _ty) => write!(f, "{num}"),
             Self::StringLiteral(string) => write!(f, "\"{string}\""),
             Self::CharLiteral(ch) => write!(f, "'{ch}'"),
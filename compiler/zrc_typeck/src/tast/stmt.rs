@@ -2,14 +2,14 @@
 
 use std::fmt::Display;
 
-use derive_more::Display;
+use serde::Serialize;
 use zrc_utils::{code_fmt::indent_lines, span::Spanned};
 
-use super::{expr::TypedExpr, ty::Type};
+use super::{expr::TypedExpr, render, ty::Type};
 use crate::typeck::BlockMetadata;
 
 /// A declaration created with `let`.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct LetDeclaration<'input> {
     /// The name of the identifier.
     pub name: Spanned<&'input str>,
@@ -24,7 +24,7 @@ pub struct LetDeclaration<'input> {
 }
 
 /// A zirco statement after typeck
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct TypedStmt<'input> {
     /// The actual statement kind with its span.
     pub kind: Spanned<TypedStmtKind<'input>>,
@@ -37,7 +37,7 @@ pub struct TypedStmt<'input> {
 
 /// The enum representing all of the different kinds of statements in Zirco
 /// after type checking
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum TypedStmtKind<'input> {
     // all of the Box<Stmt>s for "possibly blocks" have been desugared into vec[single stmt] here
     // (basically if (x) y has become if (x) {y})
@@ -66,23 +66,32 @@ pub enum TypedStmtKind<'input> {
     },
     /// `four body`
     FourStmt(Spanned<BlockMetadata<'input>>),
+    /// `label: loop`, where the loop is always a [`Self::WhileStmt`],
+    /// [`Self::DoWhileStmt`], [`Self::ForStmt`] or [`Self::FourStmt`]
+    LabeledLoop(&'input str, Box<TypedStmt<'input>>),
     /// `switch`
     SwitchCase {
         /// The value to be switched over (`x` in `switch (x) {}`)
         scrutinee: TypedExpr<'input>,
         /// The default case
         default: BlockMetadata<'input>,
+        /// The name the scrutinee is bound to in the default case, as in
+        /// `default(x) => ...`
+        default_binding: Option<Spanned<&'input str>>,
         /// The list of other cases
         cases: Vec<(TypedExpr<'input>, BlockMetadata<'input>)>,
+        /// Whether `break` in a case exits this switch rather than an enclosing
+        /// loop, which is the case for a `match` outside of any loop
+        exits_on_break: bool,
     },
     /// `{ ... }`
     BlockStmt(BlockMetadata<'input>),
     /// `x;`
     ExprStmt(TypedExpr<'input>),
-    /// `continue;`
-    ContinueStmt,
-    /// `break;`
-    BreakStmt,
+    /// `continue;` or `continue label;`
+    ContinueStmt(Option<&'input str>),
+    /// `break;` or `break label;`
+    BreakStmt(Option<&'input str>),
     /// `return;` or `return x;`. `return;` is the same as a `return
     /// CONST_UNIT;`
     ReturnStmt(Option<TypedExpr<'input>>),
@@ -93,10 +102,12 @@ pub enum TypedStmtKind<'input> {
 }
 
 /// A struct or function declaration at the top level of a file
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum TypedDeclaration<'input> {
     /// A declaration of a function
     FunctionDeclaration {
+        /// The attributes applied to the function.
+        attributes: FunctionAttributes,
         /// The name of the function.
         name: Spanned<&'input str>,
         /// The parameters of the function.
@@ -111,10 +122,86 @@ pub enum TypedDeclaration<'input> {
     GlobalLetDeclaration(Vec<Spanned<LetDeclaration<'input>>>),
 }
 
+/// The validated attributes of a [`TypedDeclaration::FunctionDeclaration`]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+#[expect(clippy::struct_excessive_bools)] // one for each attribute that can be written
+pub struct FunctionAttributes {
+    /// `inline` - calls to this function are always inlined, even when
+    /// optimizations are disabled
+    pub inline: bool,
+    /// `noinline` - calls to this function are never inlined
+    pub noinline: bool,
+    /// `optsize` - this function is optimized for size rather than speed
+    pub optsize: bool,
+    /// `optnone` - this function is never optimized or inlined, even when the
+    /// rest of the program is
+    pub optnone: bool,
+    /// `deprecated` or `deprecated("message")` - calls to this function
+    /// produce a warning
+    pub deprecated: Option<Deprecation>,
+    /// `constructor` or `constructor(priority)` - this function is run before
+    /// `main`
+    pub constructor: Option<Constructor>,
+}
+impl Display for FunctionAttributes {
+    /// Writes each attribute followed by a space, so this can be placed
+    /// directly before `fn`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.inline {
+            write!(f, "inline ")?;
+        }
+
+        if self.noinline {
+            write!(f, "noinline ")?;
+        }
+
+        if self.optsize {
+            write!(f, "optsize ")?;
+        }
+
+        if self.optnone {
+            write!(f, "optnone ")?;
+        }
+
+        match &self.deprecated {
+            None => {}
+            Some(Deprecation { message: None }) => write!(f, "deprecated ")?,
+            Some(Deprecation {
+                message: Some(message),
+            }) => write!(f, "deprecated({message:?}) ")?,
+        }
+
+        match &self.constructor {
+            None => {}
+            Some(Constructor { priority: None }) => write!(f, "constructor ")?,
+            Some(Constructor {
+                priority: Some(priority),
+            }) => write!(f, "constructor({priority}) ")?,
+        }
+
+        Ok(())
+    }
+}
+
+/// The data of a `deprecated` attribute
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Deprecation {
+    /// The message explaining the deprecation, shown at each call site
+    pub message: Option<String>,
+}
+
+/// The data of a `constructor` attribute
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Constructor {
+    /// The priority of the constructor. Constructors with a lower priority run
+    /// first, and those without one run last.
+    pub priority: Option<u16>,
+}
+
 /// The list of arguments on a [`TypedDeclaration::FunctionDeclaration`]
 ///
 /// May be variadic or not. Variadic only exists on extern.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum ArgumentDeclarationList<'input> {
     /// `(a, b, ...)`
     Variadic(Vec<ArgumentDeclaration<'input>>),
@@ -173,20 +260,30 @@ impl Display for ArgumentDeclarationList<'_> {
 }
 
 /// A special form of [`LetDeclaration`] used for function parameters.
-#[derive(PartialEq, Debug, Clone, Display)]
-#[display("{name}: {ty}")]
+#[derive(PartialEq, Debug, Clone, Serialize)]
 pub struct ArgumentDeclaration<'input> {
+    /// If the parameter is marked `noalias`, promising that no other pointer
+    /// used by the function aliases it
+    pub noalias: bool,
     /// The name of the parameter.
     pub name: Spanned<&'input str>,
     /// The type of the parameter.
     pub ty: Spanned<Type<'input>>,
 }
+impl Display for ArgumentDeclaration<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.noalias {
+            write!(f, "noalias ")?;
+        }
+        write!(f, "{}: {}", self.name, self.ty)
+    }
+}
 
 impl Display for LetDeclaration<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}: {}", self.name.value(), self.ty)?;
         if let Some(value) = &self.value {
-            write!(f, " = {value}")?;
+            write!(f, " = {}", render(value, f.alternate()))?;
         }
         Ok(())
     }
@@ -194,23 +291,25 @@ impl Display for LetDeclaration<'_> {
 
 impl Display for TypedStmt<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.kind.value())
+        write!(f, "{}", render(self.kind.value(), f.alternate()))
     }
 }
 
 impl Display for TypedStmtKind<'_> {
     #[expect(clippy::too_many_lines)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let alt = f.alternate();
         match self {
             Self::IfStmt(cond, if_true, None) => {
                 write!(
                     f,
-                    "if ({cond}) {{\n{}\n}}",
+                    "if ({}) {{\n{}\n}}",
+                    render(cond, alt),
                     if_true
                         .value()
                         .stmts
                         .iter()
-                        .map(|stmt: &TypedStmt<'_>| indent_lines(&stmt.to_string(), "    "))
+                        .map(|stmt: &TypedStmt<'_>| indent_lines(&render(stmt, alt), "    "))
                         .collect::<Vec<_>>()
                         .join("\n")
                 )
@@ -218,19 +317,20 @@ impl Display for TypedStmtKind<'_> {
             Self::IfStmt(cond, if_true, Some(if_false)) => {
                 write!(
                     f,
-                    "if ({cond}) {{\n{}\n}} else {{\n{}\n}}",
+                    "if ({}) {{\n{}\n}} else {{\n{}\n}}",
+                    render(cond, alt),
                     if_true
                         .value()
                         .stmts
                         .iter()
-                        .map(|stmt: &TypedStmt<'_>| indent_lines(&stmt.to_string(), "    "))
+                        .map(|stmt: &TypedStmt<'_>| indent_lines(&render(stmt, alt), "    "))
                         .collect::<Vec<_>>()
                         .join("\n"),
                     if_false
                         .value()
                         .stmts
                         .iter()
-                        .map(|stmt: &TypedStmt<'_>| indent_lines(&stmt.to_string(), "    "))
+                        .map(|stmt: &TypedStmt<'_>| indent_lines(&render(stmt, alt), "    "))
                         .collect::<Vec<_>>()
                         .join("\n")
                 )
@@ -242,19 +342,21 @@ impl Display for TypedStmtKind<'_> {
                     body.value()
                         .stmts
                         .iter()
-                        .map(|stmt: &TypedStmt<'_>| indent_lines(&stmt.to_string(), "    "))
+                        .map(|stmt: &TypedStmt<'_>| indent_lines(&render(stmt, alt), "    "))
                         .collect::<Vec<_>>()
                         .join("\n")
                 )
             }
+            Self::LabeledLoop(label, body) => write!(f, "{label}: {}", render(body, alt)),
             Self::WhileStmt(cond, body) => {
                 write!(
                     f,
-                    "while ({cond}) {{\n{}\n}}",
+                    "while ({}) {{\n{}\n}}",
+                    render(cond, alt),
                     body.value()
                         .stmts
                         .iter()
-                        .map(|stmt: &TypedStmt<'_>| indent_lines(&stmt.to_string(), "    "))
+                        .map(|stmt: &TypedStmt<'_>| indent_lines(&render(stmt, alt), "    "))
                         .collect::<Vec<_>>()
                         .join("\n")
                 )
@@ -262,13 +364,14 @@ impl Display for TypedStmtKind<'_> {
             Self::DoWhileStmt(body, cond) => {
                 write!(
                     f,
-                    "do {{\n{}\n}} while ({cond});",
+                    "do {{\n{}\n}} while ({});",
                     body.value()
                         .stmts
                         .iter()
-                        .map(|stmt: &TypedStmt<'_>| indent_lines(&stmt.to_string(), "    "))
+                        .map(|stmt: &TypedStmt<'_>| indent_lines(&render(stmt, alt), "    "))
                         .collect::<Vec<_>>()
-                        .join("\n")
+                        .join("\n"),
+                    render(cond, alt),
                 )
             }
             Self::ForStmt {
@@ -285,17 +388,17 @@ impl Display for TypedStmtKind<'_> {
                         |x| format!(
                             "let {};",
                             x.iter()
-                                .map(ToString::to_string)
+                                .map(|x| render(x, alt))
                                 .collect::<Vec<_>>()
                                 .join(", ")
                         )
                     ),
-                    cond.as_ref().map_or(String::new(), ToString::to_string),
-                    post.as_ref().map_or(String::new(), ToString::to_string),
+                    cond.as_ref().map_or(String::new(), |x| render(x, alt)),
+                    post.as_ref().map_or(String::new(), |x| render(x, alt)),
                     body.value()
                         .stmts
                         .iter()
-                        .map(|stmt: &TypedStmt<'_>| indent_lines(&stmt.to_string(), "    "))
+                        .map(|stmt: &TypedStmt<'_>| indent_lines(&render(stmt, alt), "    "))
                         .collect::<Vec<_>>()
                         .join("\n")
                 )
@@ -303,18 +406,20 @@ impl Display for TypedStmtKind<'_> {
             Self::SwitchCase {
                 scrutinee,
                 default,
+                default_binding,
                 cases,
+                ..
             } => {
-                write!(f, "switch ({scrutinee}) {{")?;
+                write!(f, "switch ({}) {{", render(scrutinee, alt))?;
                 for (case_expr, case_stmts) in cases {
                     write!(
                         f,
                         " {} => {{\n{}\n}}",
-                        case_expr,
+                        render(case_expr, alt),
                         case_stmts
                             .stmts
                             .iter()
-                            .map(|stmt: &TypedStmt<'_>| indent_lines(&stmt.to_string(), "    "))
+                            .map(|stmt: &TypedStmt<'_>| indent_lines(&render(stmt, alt), "    "))
                             .collect::<Vec<_>>()
                             .join("\n")
                     )?;
@@ -322,11 +427,15 @@ impl Display for TypedStmtKind<'_> {
                 if !default.stmts.is_empty() {
                     write!(
                         f,
-                        " default => {{\n{}\n}}",
+                        " {} => {{\n{}\n}}",
+                        default_binding.map_or_else(
+                            || "default".to_string(),
+                            |name| format!("default({name})")
+                        ),
                         default
                             .stmts
                             .iter()
-                            .map(|stmt: &TypedStmt<'_>| indent_lines(&stmt.to_string(), "    "))
+                            .map(|stmt: &TypedStmt<'_>| indent_lines(&render(stmt, alt), "    "))
                             .collect::<Vec<_>>()
                             .join("\n")
                     )?;
@@ -343,16 +452,18 @@ impl Display for TypedStmtKind<'_> {
                         stmts
                             .stmts
                             .iter()
-                            .map(|stmt: &TypedStmt<'_>| indent_lines(&stmt.to_string(), "    "))
+                            .map(|stmt: &TypedStmt<'_>| indent_lines(&render(stmt, alt), "    "))
                             .collect::<Vec<_>>()
                             .join("\n")
                     )
                 }
             }
-            Self::ExprStmt(expr) => write!(f, "{expr};"),
-            Self::ContinueStmt => write!(f, "continue;"),
-            Self::BreakStmt => write!(f, "break;"),
-            Self::ReturnStmt(Some(expr)) => write!(f, "return {expr};"),
+            Self::ExprStmt(expr) => write!(f, "{};", render(expr, alt)),
+            Self::ContinueStmt(None) => write!(f, "continue;"),
+            Self::ContinueStmt(Some(label)) => write!(f, "continue {label};"),
+            Self::BreakStmt(None) => write!(f, "break;"),
+            Self::BreakStmt(Some(label)) => write!(f, "break {label};"),
+            Self::ReturnStmt(Some(expr)) => write!(f, "return {};", render(expr, alt)),
             Self::ReturnStmt(None) => write!(f, "return;"),
             Self::UnreachableStmt => write!(f, "unreachable;"),
             Self::DeclarationList(list) => {
@@ -360,7 +471,7 @@ impl Display for TypedStmtKind<'_> {
                     f,
                     "let {};",
                     list.iter()
-                        .map(ToString::to_string)
+                        .map(|x| render(x, alt))
                         .collect::<Vec<_>>()
                         .join(", ")
                 )
@@ -371,34 +482,37 @@ impl Display for TypedStmtKind<'_> {
 
 impl Display for TypedDeclaration<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let alt = f.alternate();
         match self {
             Self::FunctionDeclaration {
+                attributes,
                 name,
                 parameters,
                 return_type,
                 body: Some(body),
             } => write!(
                 f,
-                "fn {name}({parameters}) -> {return_type} {{\n{}\n}}",
+                "{attributes}fn {name}({parameters}) -> {return_type} {{\n{}\n}}",
                 body.value()
                     .stmts
                     .iter()
-                    .map(|stmt: &TypedStmt<'_>| indent_lines(&stmt.to_string(), "    "))
+                    .map(|stmt: &TypedStmt<'_>| indent_lines(&render(stmt, alt), "    "))
                     .collect::<Vec<String>>()
                     .join("\n")
             ),
             Self::FunctionDeclaration {
+                attributes,
                 name,
                 parameters,
                 return_type,
                 body: None,
-            } => write!(f, "fn {name}({parameters}) -> {return_type};"),
+            } => write!(f, "{attributes}fn {name}({parameters}) -> {return_type};"),
             Self::GlobalLetDeclaration(list) => {
                 write!(
                     f,
                     "let {};",
                     list.iter()
-                        .map(ToString::to_string)
+                        .map(|x| render(x, alt))
                         .collect::<Vec<_>>()
                         .join(", ")
                 )
@@ -444,7 +558,7 @@ mod tests {
     #[test]
     fn typed_stmt_continue_displays_correctly() {
         let stmt = TypedStmt {
-            kind: spanned_test!(0, TypedStmtKind::ContinueStmt, 8),
+            kind: spanned_test!(0, TypedStmtKind::ContinueStmt(None), 8),
             return_actuality: BlockReturnActuality::NeverReturns,
         };
         assert_eq!(stmt.to_string(), "continue;");
@@ -453,10 +567,16 @@ mod tests {
     #[test]
     fn typed_stmt_break_displays_correctly() {
         let stmt = TypedStmt {
-            kind: spanned_test!(0, TypedStmtKind::BreakStmt, 5),
+            kind: spanned_test!(0, TypedStmtKind::BreakStmt(None), 5),
             return_actuality: BlockReturnActuality::NeverReturns,
         };
         assert_eq!(stmt.to_string(), "break;");
+
+        let stmt = TypedStmt {
+            kind: spanned_test!(0, TypedStmtKind::BreakStmt(Some("outer")), 11),
+            return_actuality: BlockReturnActuality::NeverReturns,
+        };
+        assert_eq!(stmt.to_string(), "break outer;");
     }
 
     #[test]
@@ -471,6 +591,7 @@ mod tests {
     #[test]
     fn argument_declaration_displays_correctly() {
         let arg = ArgumentDeclaration {
+            noalias: false,
             name: spanned_test!(0, "x", 1),
             ty: spanned_test!(3, Type::I32, 6),
         };
@@ -481,10 +602,12 @@ mod tests {
     fn argument_declaration_list_non_variadic_displays_correctly() {
         let list = ArgumentDeclarationList::NonVariadic(vec![
             ArgumentDeclaration {
+                noalias: false,
                 name: spanned_test!(0, "x", 1),
                 ty: spanned_test!(3, Type::I32, 6),
             },
             ArgumentDeclaration {
+                noalias: false,
                 name: spanned_test!(8, "y", 9),
                 ty: spanned_test!(11, Type::Bool, 15),
             },
@@ -495,6 +618,7 @@ mod tests {
     #[test]
     fn argument_declaration_list_variadic_displays_correctly() {
         let list = ArgumentDeclarationList::Variadic(vec![ArgumentDeclaration {
+            noalias: false,
             name: spanned_test!(0, "x", 1),
             ty: spanned_test!(3, Type::I32, 6),
         }]);
@@ -511,4 +635,45 @@ mod tests {
         assert!(variadic.is_variadic());
         assert!(!non_variadic.is_variadic());
     }
+
+    #[test]
+    fn alternate_display_shows_implicit_coercions() {
+        let program = crate::typeck::type_program(
+            &mut crate::typeck::GlobalScope::new(),
+            zrc_parser::parser::parse_program(
+                "fn f(x: i64) -> i64 { let y: i64 = x; return 5; }",
+                "<test>",
+            )
+            .expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        // TEST: only the literal was coerced, and no other type is written
+        assert_eq!(
+            format!("{:#}", program[0].value()),
+            "fn f(x: i64) -> i64 {\n    let y: i64 = x;\n    return coerce<i64>(5);\n}"
+        );
+        assert_eq!(
+            program[0].value().to_string(),
+            "fn f(x: i64) -> i64 {\n    let y: i64 = (x as i64);\n    return (5 as i64);\n}"
+        );
+    }
+
+    #[test]
+    fn tast_json_matches_the_golden_file() {
+        let program = crate::typeck::type_program(
+            &mut crate::typeck::GlobalScope::new(),
+            zrc_parser::parser::parse_program(
+                "fn add(a: i32, b: i32) -> i32 { return a + b * 2; }",
+                "<test>",
+            )
+            .expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        assert_eq!(
+            serde_json::to_string_pretty(&program).expect("the TAST should serialize") + "\n",
+            include_str!("snapshots/add.json")
+        );
+    }
 }
@@ -8,12 +8,13 @@
 use std::fmt::Display;
 
 use derive_more::Display;
+use serde::Serialize;
 use zrc_utils::ordered_fields::OrderedFields;
 
 use super::stmt::ArgumentDeclarationList;
 
 /// Data attached to a [`Type::Fn`]
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, Display, Serialize)]
 #[display("(fn({arguments}) -> {returns})")]
 pub struct Fn<'input> {
     /// The function's arguments
@@ -64,7 +65,7 @@ impl Fn<'_> {
 }
 
 /// Auxillary data attached to a [`Fn`] in the Global Scope
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct FunctionDeclarationGlobalMetadata<'input> {
     /// The corresponding [`Fn`] we wrap
     pub fn_type: Fn<'input>,
@@ -80,7 +81,7 @@ pub type OrderedTypeFields<'input> = OrderedFields<'input, Type<'input>>;
 pub type OrderedValueFields<'input> = OrderedFields<'input, super::expr::TypedExpr<'input>>;
 
 /// The possible Zirco types
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize)]
 pub enum Type<'input> {
     // WHENEVER ADDING NEW PRIMITIVES HERE, ADD THEM TO THE TYPE SCOPE IN
     // [`zrc_typeck::typeck::scope::TypeScope::new`].
@@ -201,6 +202,69 @@ impl<'input> Type<'input> {
         matches!(self, U8 | U16 | U32 | U64 | Usize)
     }
 
+    /// Returns `true` if this is `*u8`, the type of string literals.
+    #[must_use]
+    pub fn is_string(&self) -> bool {
+        matches!(self, Type::Ptr(pointee) if **pointee == Type::U8)
+    }
+
+    /// Get the width in bits of a fixed-width integer type like [`Type::I8`].
+    ///
+    /// Returns [`None`] for non-integer types, `{int}`, and the pointer-sized
+    /// [`Type::Usize`] and [`Type::Isize`], whose width depends on the target.
+    #[must_use]
+    #[expect(clippy::wildcard_enum_match_arm)]
+    pub const fn integer_bit_width(&self) -> Option<u32> {
+        match self {
+            Type::I8 | Type::U8 => Some(8),
+            Type::I16 | Type::U16 => Some(16),
+            Type::I32 | Type::U32 => Some(32),
+            Type::I64 | Type::U64 => Some(64),
+            _ => None,
+        }
+    }
+
+    /// Get the variants of an enum type, or [`None`] if this is not an enum.
+    ///
+    /// Enums are desugared into
+    /// `struct { __discriminant__: usize, __value__: union { ... } }`, so this
+    /// recognizes that shape and returns the fields of the inner union. Enums
+    /// with explicit discriminants use a smaller integer than `usize` for
+    /// `__discriminant__`, and have a zero-sized `__enum__` field naming the
+    /// enum.
+    #[must_use]
+    pub fn enum_variants(&self) -> Option<&OrderedTypeFields<'input>> {
+        self.enum_parts().map(|(_, variants)| variants)
+    }
+
+    /// Get the type of the tag of an enum type, or [`None`] if this is not an
+    /// enum. See [`Type::enum_variants`].
+    #[must_use]
+    pub fn enum_tag_type(&self) -> Option<&Self> {
+        self.enum_parts().map(|(tag, _)| tag)
+    }
+
+    /// Split an enum type into its tag type and variants
+    fn enum_parts(&self) -> Option<(&Self, &OrderedTypeFields<'input>)> {
+        let Type::Struct(fields) = self else {
+            return None;
+        };
+
+        let marked = matches!(
+            fields.get("__enum__"),
+            Some(Type::Struct(name)) if name.len() == 1
+        );
+        match (fields.get("__discriminant__"), fields.get("__value__")) {
+            (Some(tag), Some(Type::Union(variants)))
+                if fields.len() == 2 + usize::from(marked)
+                    && (*tag == Type::Usize || tag.integer_bit_width().is_some()) =>
+            {
+                Some((tag, variants))
+            }
+            _ => None,
+        }
+    }
+
     /// Try to get the value we point at, or None if not a pointer.
     #[must_use]
     #[expect(clippy::wildcard_enum_match_arm)]
@@ -316,6 +380,23 @@ mod tests {
         assert!(!i32_ptr.can_implicitly_cast_to(&bool_ptr));
     }
 
+    #[test]
+    fn integers_only_implicitly_cast_from_untyped_literals() {
+        // TEST: changing the signedness or width of an integer needs an `as`
+        for (from, to) in [
+            (Type::I32, Type::U32),
+            (Type::U32, Type::I32),
+            (Type::U8, Type::U16),
+            (Type::Isize, Type::Usize),
+        ] {
+            assert!(!from.can_implicitly_cast_to(&to), "{from} -> {to}");
+        }
+
+        for to in [Type::U8, Type::U16, Type::U32, Type::U64, Type::I64] {
+            assert!(Type::Int.can_implicitly_cast_to(&to), "{{int}} -> {to}");
+        }
+    }
+
     #[test]
     fn type_display_works_for_primitives() {
         assert_eq!(Type::I8.to_string(), "i8");
@@ -453,10 +534,12 @@ mod tests {
         let fn1 = Fn {
             arguments: ArgumentDeclarationList::NonVariadic(vec![
                 ArgumentDeclaration {
+                    noalias: false,
                     name: spanned_test!(5, "buffer", 11),
                     ty: spanned_test!(13, Type::Ptr(Box::new(Type::U8)), 16),
                 },
                 ArgumentDeclaration {
+                    noalias: false,
                     name: spanned_test!(18, "start", 23),
                     ty: spanned_test!(25, Type::Usize, 30),
                 },
@@ -467,10 +550,12 @@ mod tests {
         let fn2 = Fn {
             arguments: ArgumentDeclarationList::NonVariadic(vec![
                 ArgumentDeclaration {
+                    noalias: false,
                     name: spanned_test!(55, "buffer", 61),
                     ty: spanned_test!(63, Type::Ptr(Box::new(Type::U8)), 66),
                 },
                 ArgumentDeclaration {
+                    noalias: false,
                     name: spanned_test!(68, "start", 73),
                     ty: spanned_test!(75, Type::Usize, 80),
                 },
@@ -491,6 +576,7 @@ mod tests {
 
         let fn1 = Fn {
             arguments: ArgumentDeclarationList::NonVariadic(vec![ArgumentDeclaration {
+                noalias: false,
                 name: spanned_test!(5, "x", 6),
                 ty: spanned_test!(8, Type::I32, 11),
             }]),
@@ -499,6 +585,7 @@ mod tests {
 
         let fn2 = Fn {
             arguments: ArgumentDeclarationList::NonVariadic(vec![ArgumentDeclaration {
+                noalias: false,
                 name: spanned_test!(55, "x", 56),
                 ty: spanned_test!(58, Type::U32, 61), // Different type
             }]),
@@ -554,6 +641,7 @@ mod tests {
         // Create two function types with same types but different parameter names
         let fn1 = Fn {
             arguments: ArgumentDeclarationList::NonVariadic(vec![ArgumentDeclaration {
+                noalias: false,
                 name: spanned_test!(5, "s", 6),
                 ty: spanned_test!(8, Type::I32, 11),
             }]),
@@ -562,6 +650,7 @@ mod tests {
 
         let fn2 = Fn {
             arguments: ArgumentDeclarationList::NonVariadic(vec![ArgumentDeclaration {
+                noalias: false,
                 name: spanned_test!(55, "x", 56),
                 ty: spanned_test!(58, Type::I32, 61),
             }]),
@@ -582,6 +671,7 @@ mod tests {
         // Create two function types with same types but different parameter names
         let type1 = Type::Fn(Fn {
             arguments: ArgumentDeclarationList::NonVariadic(vec![ArgumentDeclaration {
+                noalias: false,
                 name: spanned_test!(5, "s", 6),
                 ty: spanned_test!(8, Type::I32, 11),
             }]),
@@ -590,6 +680,7 @@ mod tests {
 
         let type2 = Type::Fn(Fn {
             arguments: ArgumentDeclarationList::NonVariadic(vec![ArgumentDeclaration {
+                noalias: false,
                 name: spanned_test!(55, "x", 56),
                 ty: spanned_test!(58, Type::I32, 61),
             }]),
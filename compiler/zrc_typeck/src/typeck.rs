@@ -1,19 +1,23 @@
 //! Zirco programming language type checker
 
 mod block;
+mod cfg;
 mod declaration;
 mod expr;
 mod scope;
 mod ty;
 
 pub use block::{
-    BlockMetadata, BlockReturnAbility, BlockReturnActuality, coerce_stmt_into_block,
-    has_duplicates, type_block,
+    BlockMetadata, BlockReturnAbility, BlockReturnActuality, BreakContinueAbility, InferredReturns,
+    coerce_stmt_into_block, has_duplicates, type_block,
 };
+pub use cfg::CfgOptions;
 pub use declaration::process_declaration;
-pub use expr::type_expr;
+pub use expr::{DisabledBuiltins, type_expr};
 pub use scope::{GlobalScope, Scope, ValueEntry};
+use std::collections::HashMap;
 pub use ty::resolve_type;
+
 use zrc_parser::ast::stmt::Declaration as AstDeclaration;
 use zrc_utils::span::Spanned;
 
@@ -21,23 +25,78 @@ use crate::tast::stmt::TypedDeclaration;
 
 /// # Errors
 /// Errors with type checker errors.
+///
+/// # Panics
+/// Panics if a specialization is requested for a function that is not
+/// `comptime`, which is an internal bug.
 pub fn type_program<'input, 'gs>(
     global_scope: &'gs mut GlobalScope<'input>,
     program: Vec<Spanned<AstDeclaration<'input>>>,
 ) -> Result<Vec<Spanned<TypedDeclaration<'input>>>, zrc_diagnostics::Diagnostic> {
+    // Phase 0: desugar the methods of `impl` blocks into functions and named
+    // enums into type aliases, then drop declarations whose `cfg` does not
+    // match the target
+    let mut desugared = Vec::with_capacity(program.len());
+    for declaration in program {
+        let span = declaration.span();
+        #[expect(clippy::wildcard_enum_match_arm)]
+        match declaration.into_value() {
+            AstDeclaration::ImplDeclaration { ty, methods } => desugared.extend(
+                declaration::desugar_impl_declaration(&mut global_scope.methods, ty, methods)?,
+            ),
+            AstDeclaration::EnumDeclaration { name, variants } => {
+                desugared.push(Spanned::from_span_and_value(
+                    span,
+                    declaration::desugar_enum_declaration(
+                        &mut global_scope.enums,
+                        span,
+                        name,
+                        variants,
+                    )?,
+                ));
+            }
+            declaration => desugared.push(Spanned::from_span_and_value(span, declaration)),
+        }
+    }
+
+    let program = desugared
+        .into_iter()
+        .filter_map(|declaration| {
+            cfg::is_enabled(declaration.value(), &global_scope.cfg)
+                .map(|enabled| enabled.then_some(declaration))
+                .transpose()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
     // Phase 1: register all declarations (mutating the global scope)
     for declaration in &program {
         declaration::register_declaration_value(global_scope, declaration.value())?;
     }
+    declaration::check_method_types(global_scope)?;
+
+    // Phase 1.5: infer the return types of functions that omit them
+    declaration::infer_return_types(global_scope, &program)?;
 
     // Phase 2: finalize all declarations (read-only access to the scope)
     let mut results: Vec<Result<Spanned<TypedDeclaration<'input>>, zrc_diagnostics::Diagnostic>> =
         Vec::with_capacity(program.len());
+    // where the specializations of each `comptime` function go, so they are
+    // declared everywhere the function itself could be called
+    let mut comptime_positions = HashMap::new();
 
     for declaration in program {
         let span = declaration.span();
         let ast_decl = declaration.into_value();
 
+        if let AstDeclaration::FunctionDeclaration { name, .. } = &ast_decl
+            && global_scope
+                .comptime_functions
+                .iter()
+                .any(|function| function.name.value() == name.value())
+        {
+            comptime_positions.insert(*name.value(), results.len());
+        }
+
         match declaration::finalize_declaration_value(global_scope, ast_decl) {
             Ok(Some(typed_decl)) => {
                 results.push(Ok(Spanned::from_span_and_value(span, typed_decl)));
@@ -47,5 +106,51 @@ pub fn type_program<'input, 'gs>(
         }
     }
 
+    // Phase 3: generate the specializations of `comptime` functions, which
+    // may call for more specializations in turn
+    let mut specialized = Vec::new();
+    let mut next = 0;
+    loop {
+        let Some(specialization) = global_scope.specializations.borrow().get(next).cloned() else {
+            break;
+        };
+        next += 1;
+        let function = global_scope
+            .comptime_functions
+            .iter()
+            .find(|function| *function.name.value() == specialization.function)
+            .expect("specializations should only be made of comptime functions")
+            .clone();
+        let span = function.name.span();
+
+        let reported = global_scope.warnings.borrow().len();
+        let result = process_declaration(
+            global_scope,
+            declaration::specialize_function(&function, &specialization),
+        )
+        .map(|typed_decl| {
+            Spanned::from_span_and_value(
+                span,
+                typed_decl.expect("specializations should produce a function"),
+            )
+        });
+        specialized.push((comptime_positions[specialization.function], result));
+
+        // every specialization has the same body, so a warning about it is only
+        // reported for the first one it applies to
+        let mut warnings = global_scope.warnings.borrow_mut();
+        for warning in warnings.split_off(reported) {
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+    }
+
+    // insert from the back so earlier positions stay valid, keeping the
+    // specializations of each function in the order they were called for
+    for (position, result) in specialized.into_iter().rev() {
+        results.insert(position, result);
+    }
+
     results.into_iter().collect()
 }
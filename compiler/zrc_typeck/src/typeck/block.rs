@@ -6,27 +6,37 @@ mod cfa;
 mod loops;
 mod switch_match;
 
-use std::fmt::Display;
+use std::{cell::RefCell, fmt::Display, rc::Rc};
 
+pub(super) use block_utils::warn_if_unused;
 pub use block_utils::{coerce_stmt_into_block, has_duplicates};
-pub use cfa::{BlockReturnAbility, BlockReturnActuality};
+use block_utils::{diverges, warn_if_returning_local_address, warn_if_unreachable};
+pub use cfa::{BlockReturnAbility, BlockReturnActuality, BreakContinueAbility, InferredReturns};
+use serde::Serialize;
+pub(super) use switch_match::type_match_expr;
 use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, diagnostic::GenericLabel};
 use zrc_parser::ast::stmt::{Stmt, StmtKind};
 use zrc_utils::span::{Span, Spannable, Spanned};
 
-use super::{declaration::process_let_declaration, expr::try_coerce_to, scope::Scope, type_expr};
+use super::{
+    declaration::process_let_declaration,
+    expr::try_coerce_to,
+    scope::{Scope, ValueEntry},
+    type_expr,
+};
 use crate::tast::{
-    stmt::{TypedStmt, TypedStmtKind},
+    stmt::{LetDeclaration, TypedStmt, TypedStmtKind},
     ty::Type as TastType,
 };
 
 /// The result returned by [`type_block`].
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct BlockMetadata<'input> {
     /// The typed statements within the block.
     pub stmts: Vec<TypedStmt<'input>>,
 
     /// The local scope after type checking the block.
+    #[serde(skip)]
     pub scope: Scope<'input>,
 
     /// The return actuality of the block.
@@ -47,8 +57,8 @@ impl Display for BlockMetadata<'_> {
 /// It performs a small desugaring where all statements become implicit blocks.
 ///
 /// This function must be provided a block of statements, and a few bits of
-/// information about the parent scope in form of booleans that toggle certain
-/// statements like `break` and a [`BlockReturnAbility`].
+/// information about the parent scope in form of a [`BreakContinueAbility`]
+/// that toggles `break` and `continue` and a [`BlockReturnAbility`].
 ///
 /// # Behavior of block returns
 /// In many cases, a block [MUST return](BlockReturnAbility::MustReturn). For
@@ -82,13 +92,21 @@ impl Display for BlockMetadata<'_> {
 pub fn type_block<'input>(
     parent_scope: &Scope<'input>,
     input_block: Spanned<Vec<Stmt<'input>>>,
-    can_use_break_continue: bool,
+    break_continue: BreakContinueAbility,
     return_ability: BlockReturnAbility<'input>,
 ) -> Result<BlockMetadata<'input>, Diagnostic> {
     let mut scope: Scope<'input> = parent_scope.clone();
 
     let input_block_span = input_block.span();
 
+    // the `let x;` declarations in this block whose type comes from their first
+    // assignment
+    let mut awaiting_assignment = Vec::new();
+
+    // the variables declared in this block, which are warned about if they are
+    // never read. Each entry is kept even once it is shadowed.
+    let mut locals = Vec::new();
+
     // At first, the block does not return.
     let (mut tast_block, return_actualities): (Vec<_>, Vec<_>) = input_block
         .into_value()
@@ -100,27 +118,51 @@ pub fn type_block<'input>(
                     || -> Result<Option<(TypedStmt<'_>, BlockReturnActuality)>, Diagnostic> {
                         match stmt.0.into_value() {
                             StmtKind::EmptyStmt => Ok(None),
-                            StmtKind::BreakStmt if can_use_break_continue => Ok(Some((
-                                TypedStmt {
-                                    kind: TypedStmtKind::BreakStmt.in_span(stmt_span),
-                                    return_actuality: BlockReturnActuality::NeverReturns,
-                                },
-                                BlockReturnActuality::NeverReturns,
-                            ))),
-                            StmtKind::BreakStmt => Err(DiagnosticKind::CannotUseBreakOutsideOfLoop
-                                .error_in(stmt_span)
-                                .with_label(GenericLabel::error(
-                                    LabelKind::CannotUseBreakOutsideOfLoop.in_span(stmt_span),
-                                ))),
+                            StmtKind::BreakStmt(Some(label))
+                            | StmtKind::ContinueStmt(Some(label))
+                                if !scope.loop_labels.contains(label.value()) =>
+                            {
+                                Err(
+                                    DiagnosticKind::UnknownLoopLabel((*label.value()).to_string())
+                                        .error_in(label.span())
+                                        .with_label(GenericLabel::error(
+                                            LabelKind::UnknownLoopLabel(
+                                                (*label.value()).to_string(),
+                                            )
+                                            .in_span(label.span()),
+                                        )),
+                                )
+                            }
 
-                            StmtKind::ContinueStmt if can_use_break_continue => Ok(Some((
+                            StmtKind::BreakStmt(label) if break_continue.can_break() => Ok(Some((
                                 TypedStmt {
-                                    kind: TypedStmtKind::ContinueStmt.in_span(stmt_span),
+                                    kind: TypedStmtKind::BreakStmt(label.map(Spanned::into_value))
+                                        .in_span(stmt_span),
                                     return_actuality: BlockReturnActuality::NeverReturns,
                                 },
                                 BlockReturnActuality::NeverReturns,
                             ))),
-                            StmtKind::ContinueStmt => {
+                            StmtKind::BreakStmt(_) => {
+                                Err(DiagnosticKind::CannotUseBreakOutsideOfLoop
+                                    .error_in(stmt_span)
+                                    .with_label(GenericLabel::error(
+                                        LabelKind::CannotUseBreakOutsideOfLoop.in_span(stmt_span),
+                                    )))
+                            }
+
+                            StmtKind::ContinueStmt(label) if break_continue.can_continue() => {
+                                Ok(Some((
+                                    TypedStmt {
+                                        kind: TypedStmtKind::ContinueStmt(
+                                            label.map(Spanned::into_value),
+                                        )
+                                        .in_span(stmt_span),
+                                        return_actuality: BlockReturnActuality::NeverReturns,
+                                    },
+                                    BlockReturnActuality::NeverReturns,
+                                )))
+                            }
+                            StmtKind::ContinueStmt(_) => {
                                 Err(DiagnosticKind::CannotUseContinueOutsideOfLoop
                                     .error_in(stmt_span)
                                     .with_label(GenericLabel::error(
@@ -134,6 +176,8 @@ pub fn type_block<'input>(
                                     &mut scope,
                                     scrutinee,
                                     &cases,
+                                    break_continue,
+                                    false,
                                     &return_ability,
                                     stmt_span,
                                 )
@@ -143,7 +187,7 @@ pub fn type_block<'input>(
                                 &mut scope,
                                 scrutinee,
                                 cases,
-                                can_use_break_continue,
+                                break_continue,
                                 &return_ability,
                                 stmt_span,
                             ),
@@ -159,25 +203,66 @@ pub fn type_block<'input>(
                                 BlockReturnActuality::AlwaysReturns,
                             ))),
 
-                            StmtKind::DeclarationList(declarations) => Ok(Some((
-                                TypedStmt {
-                                    kind: TypedStmtKind::DeclarationList(process_let_declaration(
-                                        &mut scope,
-                                        declarations.clone().into_value(),
-                                    )?)
-                                    .in_span(stmt_span),
-                                    // because expressions can't return...
-                                    return_actuality: BlockReturnActuality::NeverReturns,
-                                },
-                                BlockReturnActuality::NeverReturns,
-                            ))),
+                            StmtKind::CompileErrorStmt(message) => {
+                                Err(DiagnosticKind::CompileError(message.value().as_bytes())
+                                    .error_in(stmt_span))
+                            }
+
+                            StmtKind::CompileWarningStmt(message) => {
+                                scope.warn(
+                                    DiagnosticKind::CompileWarning(message.value().as_bytes())
+                                        .warning_in(stmt_span),
+                                );
+                                Ok(None)
+                            }
+
+                            StmtKind::DeclarationList(declarations) => {
+                                let declarations = process_let_declaration(
+                                    &mut scope,
+                                    declarations.clone().into_value(),
+                                    true,
+                                )?;
+
+                                let entries = declarations
+                                    .iter()
+                                    .filter_map(|declaration| {
+                                        let name = declaration.value().name.value();
+                                        scope
+                                            .values
+                                            .resolve(name)
+                                            .map(|entry| (declaration.span(), *name, entry))
+                                    })
+                                    .collect::<Vec<_>>();
+
+                                // remember `let x;` bindings to fill in their types once
+                                // they have been assigned
+                                awaiting_assignment.extend(
+                                    entries
+                                        .iter()
+                                        .filter(|(_, _, entry)| entry.borrow().awaiting_assignment)
+                                        .map(|(span, _, entry)| (*span, Rc::clone(entry))),
+                                );
+                                locals.extend(
+                                    entries.into_iter().map(|(_, name, entry)| (name, entry)),
+                                );
+
+                                Ok(Some((
+                                    TypedStmt {
+                                        kind: TypedStmtKind::DeclarationList(declarations)
+                                            .in_span(stmt_span),
+                                        // because expressions can't return...
+                                        return_actuality: BlockReturnActuality::NeverReturns,
+                                    },
+                                    BlockReturnActuality::NeverReturns,
+                                )))
+                            }
 
                             StmtKind::IfStmt(cond, then, then_else) => branch::type_if(
                                 &mut scope,
                                 cond,
                                 then,
                                 then_else,
-                                can_use_break_continue,
+                                break_continue,
                                 &return_ability,
                                 stmt_span,
                             ),
@@ -189,6 +274,20 @@ pub fn type_block<'input>(
                                 &return_ability,
                                 stmt_span,
                             ),
+                            StmtKind::WhileLetStmt {
+                                variant,
+                                binding,
+                                scrutinee,
+                                body,
+                            } => loops::type_while_let(
+                                &mut scope,
+                                variant,
+                                binding,
+                                scrutinee,
+                                body,
+                                &return_ability,
+                                stmt_span,
+                            ),
                             StmtKind::DoWhileStmt(body, cond) => loops::type_do_while(
                                 &mut scope,
                                 body,
@@ -210,15 +309,34 @@ pub fn type_block<'input>(
                                 &return_ability,
                                 stmt_span,
                             ),
+                            StmtKind::ForInStmt {
+                                binding,
+                                iterable,
+                                body,
+                            } => loops::type_for_in(
+                                &mut scope,
+                                binding,
+                                iterable,
+                                body,
+                                &return_ability,
+                                stmt_span,
+                            ),
                             StmtKind::FourStmt(body) => {
                                 loops::type_four(&scope, body, &return_ability, stmt_span)
                             }
+                            StmtKind::LabeledLoop { label, body } => loops::type_labeled_loop(
+                                &mut scope,
+                                label,
+                                *body,
+                                &return_ability,
+                                stmt_span,
+                            ),
 
                             StmtKind::BlockStmt(body) => {
                                 let typed_block = type_block(
                                     &scope,
                                     body.in_span(stmt_span),
-                                    can_use_break_continue,
+                                    break_continue,
                                     return_ability.clone().demote(),
                                 )?;
                                 let return_actuality = typed_block.return_actuality;
@@ -243,6 +361,9 @@ pub fn type_block<'input>(
                             StmtKind::ReturnStmt(value) => {
                                 let resolved_value =
                                     value.map(|expr| type_expr(&mut scope, expr)).transpose()?;
+                                if let Some(value) = &resolved_value {
+                                    warn_if_returning_local_address(&scope, value);
+                                }
 
                                 let inferred_return_type = resolved_value
                                     .clone()
@@ -296,6 +417,23 @@ pub fn type_block<'input>(
                                             BlockReturnActuality::AlwaysReturns,
                                         )))
                                     }
+
+                                    // return x; in fn whose return type is inferred
+                                    (return_value, BlockReturnAbility::Infer(returns)) => {
+                                        returns
+                                            .borrow_mut()
+                                            .push(inferred_return_type.in_span(stmt_span));
+
+                                        Ok(Some((
+                                            TypedStmt {
+                                                kind: TypedStmtKind::ReturnStmt(return_value)
+                                                    .in_span(stmt_span),
+                                                return_actuality:
+                                                    BlockReturnActuality::AlwaysReturns,
+                                            },
+                                            BlockReturnActuality::AlwaysReturns,
+                                        )))
+                                    }
                                 }
                             }
                         }
@@ -308,6 +446,14 @@ pub fn type_block<'input>(
         .into_iter()
         .unzip();
 
+    // the statements after a `return` are still type checked, but can never run
+    warn_if_unreachable(&scope, &tast_block);
+    warn_if_unused(&scope, &locals);
+
+    if !awaiting_assignment.is_empty() {
+        tast_block = fill_inferred_let_types(tast_block, &awaiting_assignment)?;
+    }
+
     let might_return = return_actualities.iter().any(|x| {
         matches!(
             x,
@@ -334,6 +480,8 @@ pub fn type_block<'input>(
             Ok(BlockReturnActuality::SometimesReturns)
         }
 
+        (BlockReturnAbility::Infer(_), return_actuality) => Ok(return_actuality),
+
         (
             BlockReturnAbility::MustReturn(_) | BlockReturnAbility::MayReturn(_),
             BlockReturnActuality::AlwaysReturns,
@@ -344,14 +492,18 @@ pub fn type_block<'input>(
             BlockReturnAbility::MustReturn(return_ty),
             BlockReturnActuality::SometimesReturns | BlockReturnActuality::NeverReturns,
         ) if return_ty == TastType::unit() => {
-            tast_block.push(TypedStmt {
-                kind: TypedStmtKind::ReturnStmt(None).in_span(Span::from_positions_and_file(
-                    input_block_span.end() - 1,
-                    input_block_span.end(),
-                    input_block_span.file_name(),
-                )),
-                return_actuality: BlockReturnActuality::AlwaysReturns,
-            });
+            // a block ending in something like an infinite loop never reaches its end, so
+            // a `return;` there would be dead code
+            if !tast_block.last().is_some_and(diverges) {
+                tast_block.push(TypedStmt {
+                    kind: TypedStmtKind::ReturnStmt(None).in_span(Span::from_positions_and_file(
+                        input_block_span.end() - 1,
+                        input_block_span.end(),
+                        input_block_span.file_name(),
+                    )),
+                    return_actuality: BlockReturnActuality::AlwaysReturns,
+                });
+            }
 
             Ok(BlockReturnActuality::AlwaysReturns)
         }
@@ -381,3 +533,142 @@ pub fn type_block<'input>(
         return_actuality,
     })
 }
+
+/// Fill in the types of the `let x;` declarations in `stmts` from the
+/// assignments they were inferred from, now that the block has been checked
+///
+/// # Errors
+/// Errors if one of the declarations was never assigned to.
+fn fill_inferred_let_types<'input>(
+    stmts: Vec<TypedStmt<'input>>,
+    awaiting_assignment: &[(Span, Rc<RefCell<ValueEntry<'input>>>)],
+) -> Result<Vec<TypedStmt<'input>>, Diagnostic> {
+    stmts
+        .into_iter()
+        .map(|stmt| {
+            let TypedStmtKind::DeclarationList(declarations) = stmt.kind.value() else {
+                return Ok(stmt);
+            };
+
+            let declarations = declarations
+                .iter()
+                .map(|declaration| {
+                    let Some((_, entry)) = awaiting_assignment
+                        .iter()
+                        .find(|(span, _)| *span == declaration.span())
+                    else {
+                        return Ok(declaration.clone());
+                    };
+
+                    let entry = entry.borrow();
+                    if entry.awaiting_assignment {
+                        return Err(DiagnosticKind::NoTypeNoValue
+                            .error_in(declaration.span())
+                            .with_label(GenericLabel::error(
+                                LabelKind::NoTypeNoValue.in_span(declaration.span()),
+                            )));
+                    }
+
+                    Ok(declaration.clone().map(|declaration| LetDeclaration {
+                        ty: entry.ty.clone(),
+                        ..declaration
+                    }))
+                })
+                .collect::<Result<Vec<_>, Diagnostic>>()?;
+
+            Ok(TypedStmt {
+                kind: TypedStmtKind::DeclarationList(declarations).in_span(stmt.kind.span()),
+                return_actuality: stmt.return_actuality,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::DiagnosticKind;
+
+    use crate::{
+        tast::stmt::{TypedDeclaration, TypedStmtKind},
+        typeck::{GlobalScope, type_program},
+    };
+
+    /// Type check `body` as the body of a function returning `i32`
+    fn type_check(body: &str) -> Result<(), DiagnosticKind> {
+        let source = format!("fn f() -> i32 {{ {body} }}");
+        type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(&source, "<test>").expect("parsing should succeed"),
+        )
+        .map(|_| ())
+        .map_err(|diagnostic| diagnostic.kind.into_value())
+    }
+
+    #[test]
+    fn variables_declared_in_a_block_are_not_visible_after_it() {
+        assert_eq!(
+            type_check("{ let x: i32 = 1; } return x;"),
+            Err(DiagnosticKind::UnableToResolveIdentifier("x".to_string()))
+        );
+        assert_eq!(
+            type_check("{ { let x: i32 = 1; } return x; }"),
+            Err(DiagnosticKind::UnableToResolveIdentifier("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn shadowing_in_a_block_ends_with_the_block() {
+        // the outer `x` is an `i32` again once the inner `bool` goes away
+        assert_eq!(
+            type_check("let x: i32 = 1; { let x: bool = true; } return x;"),
+            Ok(())
+        );
+    }
+
+    /// Returns whether an implicit `return;` is added to the end of `body`, as
+    /// the body of a function returning unit
+    fn has_implicit_return(body: &str) -> bool {
+        let source = format!("fn f() {{ {body} }}");
+        let program = type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(&source, "<test>").expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        let TypedDeclaration::FunctionDeclaration {
+            body: Some(body), ..
+        } = program[0].value()
+        else {
+            panic!("expected a function definition");
+        };
+        matches!(
+            body.value().stmts.last().map(|stmt| stmt.kind.value()),
+            Some(TypedStmtKind::ReturnStmt(None))
+        )
+    }
+
+    #[test]
+    fn no_implicit_return_follows_a_diverging_loop() {
+        assert!(has_implicit_return(""));
+        assert!(!has_implicit_return("while (true) {}"));
+        assert!(!has_implicit_return("for (;;) {}"));
+        assert!(!has_implicit_return(
+            "while (true) { while (true) { break; } }"
+        ));
+        assert!(!has_implicit_return(
+            "outer: while (true) { continue outer; }"
+        ));
+    }
+
+    #[test]
+    fn implicit_return_follows_a_loop_that_can_be_left() {
+        assert!(has_implicit_return("while (true) { break; }"));
+        assert!(has_implicit_return(
+            "while (true) { if (false) { break; } }"
+        ));
+        assert!(has_implicit_return(
+            "outer: while (true) { while (true) { break outer; } }"
+        ));
+        assert!(has_implicit_return("let x = true; while (x) {}"));
+    }
+}
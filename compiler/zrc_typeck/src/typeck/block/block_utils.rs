@@ -1,7 +1,27 @@
 //! Utilities for managing blocks
 
-use zrc_parser::ast::stmt::{Stmt, StmtKind};
-use zrc_utils::span::{Spannable, Spanned};
+use std::{cell::RefCell, iter, rc::Rc, slice};
+
+use zrc_diagnostics::{DiagnosticKind, HelpKind, LabelKind, NoteKind, diagnostic::GenericLabel};
+use zrc_parser::ast::{
+    expr::Expr,
+    stmt::{Stmt, StmtKind},
+    ty::{Type, TypeKind},
+};
+use zrc_utils::span::{Span, Spannable, Spanned};
+
+use crate::{
+    tast::{
+        expr::{Place, PlaceKind, TypedExpr, TypedExprKind},
+        stmt::{TypedStmt, TypedStmtKind},
+        ty::Type as TastType,
+    },
+    typeck::{
+        BlockReturnActuality,
+        expr::{fold_bool, leaked_number_expr},
+        scope::{Scope, ValueEntry},
+    },
+};
 
 /// Convert a single [AST statement](Stmt) like `x;` to a block statement `{ x;
 /// }` without converting `{ x; }` to `{ { x; } }`. This is preferred instead of
@@ -24,3 +44,397 @@ where
 {
     (1..slice.len()).any(|i| slice[i..].contains(&slice[i - 1]))
 }
+
+/// Build the literal for the enum discriminant `discriminant`, to compare with
+/// the `__discriminant__` of an enum whose tag is `tag`.
+///
+/// Negative discriminants are written as `-(1i8)` with the type of the tag, so
+/// the negation is not inferred as an `i32`.
+pub fn discriminant_literal<'input>(
+    discriminant: i128,
+    tag: &TastType<'_>,
+    span: Span,
+) -> Expr<'input> {
+    // the AST names the tag by a `&str`, which must live as long as the input
+    let tag = (discriminant < 0).then(|| {
+        let tag: &'input str = Box::leak(tag.to_string().into_boxed_str());
+        Type(TypeKind::Identifier(tag).in_span(span))
+    });
+    leaked_number_expr(discriminant, tag, span)
+}
+
+/// Warn if the condition of an `if` or a loop always has the same value, like
+/// `1 == 1` or `x == x`, which is usually a mistake.
+///
+/// A condition of just `true` or `false` is assumed to be intended, as in
+/// `while (true)`.
+pub fn warn_if_constant_condition(scope: &Scope<'_>, cond: &TypedExpr<'_>) {
+    if matches!(cond.kind.value(), TypedExprKind::BooleanLiteral(_)) {
+        return;
+    }
+
+    if let Some(value) = fold_bool(cond) {
+        scope.warn(
+            DiagnosticKind::ConstantCondition(value.to_string())
+                .warning_in(cond.kind.span())
+                .with_label(GenericLabel::warning(
+                    LabelKind::ConstantCondition(value.to_string()).in_span(cond.kind.span()),
+                ))
+                .with_help(HelpKind::WriteConstantCondition(value.to_string())),
+        );
+    }
+}
+
+/// Find the local variable that a place is (part of), if any
+///
+/// Memory reached through a pointer may outlive the function, so only
+/// variables and the fields and array elements stored inside of them count.
+#[expect(clippy::wildcard_enum_match_arm)]
+fn local_of_place<'input>(scope: &Scope<'input>, place: &Place<'input>) -> Option<&'input str> {
+    match place.kind.value() {
+        PlaceKind::Variable(x) => scope
+            .values
+            .resolve(x)
+            .is_some_and(|entry| entry.borrow().is_local)
+            .then_some(*x),
+        PlaceKind::Dot(inner, _) => local_of_place(scope, inner),
+        // arrays are indexed through a pointer to their own storage
+        PlaceKind::Index(base, _) => match base.kind.value() {
+            TypedExprKind::UnaryAddressOf(inner)
+                if matches!(inner.inferred_type, TastType::Array { .. }) =>
+            {
+                local_of_place(scope, inner)
+            }
+            _ => None,
+        },
+        PlaceKind::Deref(_) => None,
+    }
+}
+
+/// Find the local variable that a pointer obviously points into, like `x` for
+/// `&x.y` or `(&x as *u8) + 1`
+///
+/// Pointers stored in variables are not followed, so `let p = &x; return p;`
+/// is not caught.
+#[expect(clippy::wildcard_enum_match_arm)]
+fn local_pointed_to<'input>(
+    scope: &Scope<'input>,
+    expr: &TypedExpr<'input>,
+) -> Option<&'input str> {
+    match expr.kind.value() {
+        TypedExprKind::UnaryAddressOf(place) => local_of_place(scope, place),
+        // casts and pointer arithmetic derive a pointer into the same storage
+        TypedExprKind::Cast(inner, _) => local_pointed_to(scope, inner),
+        TypedExprKind::Arithmetic(_, lhs, _) if matches!(lhs.inferred_type, TastType::Ptr(_)) => {
+            local_pointed_to(scope, lhs)
+        }
+        TypedExprKind::Arithmetic(_, _, rhs) if matches!(rhs.inferred_type, TastType::Ptr(_)) => {
+            local_pointed_to(scope, rhs)
+        }
+        TypedExprKind::Ternary(_, if_true, if_false) | TypedExprKind::Elvis(if_true, if_false) => {
+            local_pointed_to(scope, if_true).or_else(|| local_pointed_to(scope, if_false))
+        }
+        TypedExprKind::Match(_, _, arms) => arms
+            .iter()
+            .find_map(|arm| local_pointed_to(scope, &arm.value)),
+        TypedExprKind::Comma(_, value) => local_pointed_to(scope, value),
+        _ => None,
+    }
+}
+
+/// Returns whether control can never continue past `stmt`, because it always
+/// jumps away with `break`, `continue` or `unreachable`, or is a loop that can
+/// only be left with a `return`.
+pub fn diverges(stmt: &TypedStmt<'_>) -> bool {
+    match stmt.kind.value() {
+        TypedStmtKind::BreakStmt(_)
+        | TypedStmtKind::ContinueStmt(_)
+        | TypedStmtKind::UnreachableStmt => true,
+        TypedStmtKind::LabeledLoop(label, inner) => loop_runs_forever(inner, Some(label)),
+        TypedStmtKind::WhileStmt(..)
+        | TypedStmtKind::DoWhileStmt(..)
+        | TypedStmtKind::ForStmt { .. } => loop_runs_forever(stmt, None),
+        TypedStmtKind::IfStmt(..)
+        | TypedStmtKind::FourStmt(_)
+        | TypedStmtKind::SwitchCase { .. }
+        | TypedStmtKind::BlockStmt(_)
+        | TypedStmtKind::ExprStmt(_)
+        | TypedStmtKind::ReturnStmt(_)
+        | TypedStmtKind::DeclarationList(_) => false,
+    }
+}
+
+/// Warn about the first statement of `stmts` that can never run, because a
+/// statement before it always returns or [diverges].
+///
+/// A statement that only sometimes returns, like an `if` without an `else`,
+/// does not make the statements after it unreachable.
+pub fn warn_if_unreachable(scope: &Scope<'_>, stmts: &[TypedStmt<'_>]) {
+    let Some([end, dead]) = stmts.windows(2).find(|pair| {
+        pair.first().is_some_and(|stmt| {
+            matches!(stmt.return_actuality, BlockReturnActuality::AlwaysReturns) || diverges(stmt)
+        })
+    }) else {
+        return;
+    };
+
+    scope.warn(
+        DiagnosticKind::UnreachableStatement
+            .warning_in(dead.kind.span())
+            .with_label(GenericLabel::warning(
+                LabelKind::UnreachableStatement.in_span(dead.kind.span()),
+            ))
+            .with_label(GenericLabel::note(
+                LabelKind::NeverCompletes.in_span(end.kind.span()),
+            )),
+    );
+}
+
+/// Warn about each of `locals`, the variables declared by a block or the
+/// parameters of a function, that is never read.
+///
+/// Names starting with `_` are assumed to be unused on purpose.
+pub fn warn_if_unused<'input>(
+    scope: &Scope<'input>,
+    locals: &[(&'input str, Rc<RefCell<ValueEntry<'input>>>)],
+) {
+    for (name, entry) in locals {
+        let entry = entry.borrow();
+        if name.starts_with('_') || !entry.referenced_spans.is_empty() {
+            continue;
+        }
+
+        let span = entry.declaration_span;
+        scope.warn(
+            DiagnosticKind::UnusedVariable((*name).to_string())
+                .warning_in(span)
+                .with_label(GenericLabel::warning(
+                    LabelKind::UnusedVariable.in_span(span),
+                ))
+                .with_help(HelpKind::PrefixWithUnderscore(format!("_{name}"))),
+        );
+    }
+}
+
+/// Returns whether the loop `stmt`, labeled `label`, has a constant `true`
+/// condition and no `break` that leaves it
+#[expect(clippy::wildcard_enum_match_arm)]
+fn loop_runs_forever(stmt: &TypedStmt<'_>, label: Option<&str>) -> bool {
+    let (cond, body) = match stmt.kind.value() {
+        TypedStmtKind::WhileStmt(cond, body) | TypedStmtKind::DoWhileStmt(body, cond) => {
+            (Some(cond), body)
+        }
+        TypedStmtKind::ForStmt { cond, body, .. } => (cond.as_ref(), body),
+        _ => return false,
+    };
+
+    cond.is_none_or(|cond| fold_bool(cond) == Some(true))
+        && !breaks_out(&body.value().stmts, label, false)
+}
+
+/// Returns whether `stmts`, the body of the loop labeled `label`, contain a
+/// `break` that leaves that loop. Inside of a `nested` loop, only a `break`
+/// naming `label` does.
+fn breaks_out(stmts: &[TypedStmt<'_>], label: Option<&str>, nested: bool) -> bool {
+    stmts.iter().any(|stmt| match stmt.kind.value() {
+        TypedStmtKind::BreakStmt(None) => !nested,
+        TypedStmtKind::BreakStmt(Some(target)) => Some(*target) == label,
+        TypedStmtKind::IfStmt(_, then, then_else) => {
+            breaks_out(&then.value().stmts, label, nested)
+                || then_else
+                    .as_ref()
+                    .is_some_and(|then_else| breaks_out(&then_else.value().stmts, label, nested))
+        }
+        TypedStmtKind::SwitchCase { default, cases, .. } => iter::once(default)
+            .chain(cases.iter().map(|(_, case)| case))
+            .any(|case| breaks_out(&case.stmts, label, nested)),
+        TypedStmtKind::BlockStmt(block) => breaks_out(&block.stmts, label, nested),
+        TypedStmtKind::WhileStmt(_, body)
+        | TypedStmtKind::DoWhileStmt(body, _)
+        | TypedStmtKind::ForStmt { body, .. }
+        | TypedStmtKind::FourStmt(body) => breaks_out(&body.value().stmts, label, true),
+        TypedStmtKind::LabeledLoop(_, inner) => breaks_out(slice::from_ref(&**inner), label, true),
+        TypedStmtKind::ContinueStmt(_)
+        | TypedStmtKind::ExprStmt(_)
+        | TypedStmtKind::ReturnStmt(_)
+        | TypedStmtKind::UnreachableStmt
+        | TypedStmtKind::DeclarationList(_) => false,
+    })
+}
+
+/// Warn if a `return` obviously returns a pointer to a local variable, which
+/// dangles as soon as the function returns. See [`local_pointed_to`] for
+/// which pointers are caught.
+pub fn warn_if_returning_local_address<'input>(scope: &Scope<'input>, value: &TypedExpr<'input>) {
+    if !matches!(value.inferred_type, TastType::Ptr(_)) {
+        return;
+    }
+
+    let Some(local) = local_pointed_to(scope, value) else {
+        return;
+    };
+    let mut warning = DiagnosticKind::ReturnsAddressOfLocal(local.to_string())
+        .warning_in(value.kind.span())
+        .with_label(GenericLabel::warning(
+            LabelKind::ReturnsAddressOfLocal(local.to_string()).in_span(value.kind.span()),
+        ));
+    if let Some(entry) = scope.values.resolve(local) {
+        warning = warning.with_label(GenericLabel::note(
+            LabelKind::LocalDeclaredHere(local.to_string())
+                .in_span(entry.borrow().declaration_span),
+        ));
+    }
+    scope.warn(warning.with_note(NoteKind::LocalsDoNotOutliveTheirFunction));
+}
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::DiagnosticKind;
+
+    use crate::typeck::{GlobalScope, type_program};
+
+    /// Type check `body` as the body of a function taking `a` and `b`, returning
+    /// the warnings it produces
+    fn warnings_in(body: &str) -> Vec<DiagnosticKind> {
+        let source = format!("fn f(a: i32, b: i32) {{ {body} }}");
+        let mut global_scope = GlobalScope::new();
+        type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program(&source, "<test>").expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        global_scope
+            .take_warnings()
+            .into_iter()
+            .map(|warning| warning.kind.into_value())
+            .collect()
+    }
+
+    #[test]
+    fn constant_conditions_are_warned_about() {
+        let always = |value: &str| vec![DiagnosticKind::ConstantCondition(value.to_string())];
+
+        // TEST: conditions that fold to a constant warn in every position
+        assert_eq!(warnings_in("if (1 == 1) return;"), always("true"));
+        assert_eq!(
+            warnings_in("if (2 < 1) return; else return;"),
+            always("false")
+        );
+        assert_eq!(warnings_in("while (a == a) {}"), always("true"));
+        assert_eq!(warnings_in("do {} while (a != a);"), always("false"));
+        assert_eq!(
+            warnings_in("for (let i = 0; !(1 > 0 && true); i += 1) {}"),
+            always("false")
+        );
+
+        // TEST: conditions that depend on runtime values do not
+        assert_eq!(warnings_in("if (a == b) return;"), vec![]);
+        assert_eq!(warnings_in("while (a < 10) {}"), vec![]);
+
+        // TEST: a literal `true` or `false` is assumed to be intended
+        assert_eq!(warnings_in("while (true) { break; }"), vec![]);
+        assert_eq!(warnings_in("if (false) return;"), vec![]);
+    }
+
+    #[test]
+    fn compile_warning_warns_and_continues() {
+        assert_eq!(
+            warnings_in("@compile_warning(\"x\"); let _c = a + b;"),
+            vec![DiagnosticKind::CompileWarning("x".to_string())]
+        );
+    }
+
+    #[test]
+    fn statements_after_a_guaranteed_return_are_unreachable() {
+        let unreachable = vec![DiagnosticKind::UnreachableStatement];
+
+        // TEST: only the first dead statement warns
+        assert_eq!(warnings_in("return; let _c = a; let _d = b;"), unreachable);
+        assert_eq!(
+            warnings_in("if (a == b) return; else { return; } let _c = a;"),
+            unreachable
+        );
+        assert_eq!(
+            warnings_in("while (a < 1) { break; let _c = a; }"),
+            unreachable
+        );
+        assert_eq!(warnings_in("for (;;) { continue; a; }"), unreachable);
+        assert_eq!(warnings_in("unreachable; let _c = a;"), unreachable);
+
+        // TEST: statements after one that only sometimes returns are reachable
+        assert_eq!(warnings_in("if (a == b) return; let _c = a;"), vec![]);
+        assert_eq!(warnings_in("while (a < b) return; let _c = a;"), vec![]);
+        assert_eq!(warnings_in("let _c = a; return;"), vec![]);
+    }
+
+    #[test]
+    fn unused_variables_are_warned_about() {
+        let unused = |name: &str| vec![DiagnosticKind::UnusedVariable(name.to_string())];
+
+        assert_eq!(warnings_in("let c = a + b;"), unused("c"));
+        assert_eq!(warnings_in("if (a == b) { let c = a; }"), unused("c"));
+        assert_eq!(warnings_in("let c = a; b = c;"), vec![]);
+
+        // TEST: assigning to a variable, or to one of its fields, does not read it
+        assert_eq!(warnings_in("let c = 2; c = 3;"), unused("c"));
+        assert_eq!(warnings_in("let c = a; c = b; a = c;"), vec![]);
+        assert_eq!(
+            warnings_in("let c: struct { x: i32 }; c.x = a;"),
+            unused("c")
+        );
+        assert_eq!(warnings_in("let c = a; c += b;"), vec![]);
+
+        // TEST: a shadowed variable is still checked
+        assert_eq!(warnings_in("let c = a; let c = b; a = c;"), unused("c"));
+
+        // TEST: names starting with `_` are exempt
+        assert_eq!(warnings_in("let _c = a;"), vec![]);
+    }
+
+    #[test]
+    fn returning_a_pointer_to_a_local_warns() {
+        let warnings_of = |source: &str| {
+            let mut global_scope = GlobalScope::new();
+            type_program(
+                &mut global_scope,
+                zrc_parser::parser::parse_program(source, "<test>")
+                    .expect("parsing should succeed"),
+            )
+            .expect("typeck should succeed");
+            global_scope
+                .take_warnings()
+                .into_iter()
+                .map(|warning| warning.kind.into_value())
+                .collect::<Vec<_>>()
+        };
+        let dangling = |local: &str| vec![DiagnosticKind::ReturnsAddressOfLocal(local.to_string())];
+
+        // TEST: the address of a local, or a pointer derived from it, warns
+        assert_eq!(
+            warnings_of("fn f() -> *i32 { let x = 1; return &x; }"),
+            dangling("x")
+        );
+        assert_eq!(
+            warnings_of("fn f(p: i32) -> *u8 { return &p as *u8 + 1; }"),
+            dangling("p")
+        );
+        assert_eq!(
+            warnings_of(
+                "struct S { v: [2]i32 }
+                fn f() -> *i32 { let s: S; return &s.v[1]; }"
+            ),
+            dangling("s")
+        );
+
+        // TEST: pointers to memory that outlives the function do not
+        assert_eq!(
+            warnings_of("fn f(p: *i32) -> *i32 { return &p[1]; }"),
+            vec![]
+        );
+        assert_eq!(
+            warnings_of("let g: i32 = 0;\nfn f() -> *i32 { return &g; }"),
+            vec![]
+        );
+    }
+}
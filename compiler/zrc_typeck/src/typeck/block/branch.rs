@@ -1,13 +1,16 @@
 //! Type checking for if statements.
 
-use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, diagnostic::GenericLabel};
-use zrc_parser::ast::{expr::Expr, stmt::Stmt};
+use zrc_diagnostics::{Diagnostic, DiagnosticKind, HelpKind, LabelKind, diagnostic::GenericLabel};
+use zrc_parser::ast::{
+    expr::Expr,
+    stmt::{Stmt, StmtKind},
+};
 use zrc_utils::span::{Span, Spannable};
 
 use super::{
     super::{scope::Scope, type_expr},
-    block_utils::coerce_stmt_into_block,
-    cfa::{BlockReturnAbility, BlockReturnActuality},
+    block_utils::{coerce_stmt_into_block, warn_if_constant_condition},
+    cfa::{BlockReturnAbility, BlockReturnActuality, BreakContinueAbility},
     type_block,
 };
 use crate::tast::{
@@ -15,6 +18,31 @@ use crate::tast::{
     ty::Type as TastType,
 };
 
+/// Warn if `branch`, the `then` or `else` branch of an `if` named by `name`,
+/// is an empty block or an empty statement.
+///
+/// An empty `then` branch is often a misplaced semicolon, as in `if (x);`,
+/// which makes the statement after it run unconditionally.
+fn warn_if_empty_branch(scope: &Scope<'_>, branch: &Stmt<'_>, name: &str) {
+    let is_stray_semicolon = matches!(branch.0.value(), StmtKind::EmptyStmt);
+    if !is_stray_semicolon
+        && !matches!(branch.0.value(), StmtKind::BlockStmt(stmts) if stmts.is_empty())
+    {
+        return;
+    }
+
+    let warning = DiagnosticKind::EmptyBranch(name.to_string())
+        .warning_in(branch.0.span())
+        .with_label(GenericLabel::warning(
+            LabelKind::EmptyBranch.in_span(branch.0.span()),
+        ));
+    scope.warn(if is_stray_semicolon && name == "then" {
+        warning.with_help(HelpKind::RemoveSemicolonAfterCondition)
+    } else {
+        warning
+    });
+}
+
 /// Type check an if statement.
 #[expect(clippy::needless_pass_by_value)]
 pub fn type_if<'input>(
@@ -22,7 +50,7 @@ pub fn type_if<'input>(
     cond: Expr<'input>,
     then: Box<Stmt<'input>>,
     then_else: Option<Box<Stmt<'input>>>,
-    can_use_break_continue: bool,
+    break_continue: BreakContinueAbility,
     return_ability: &BlockReturnAbility<'input>,
     stmt_span: Span,
 ) -> Result<Option<(TypedStmt<'input>, BlockReturnActuality)>, Diagnostic> {
@@ -52,10 +80,16 @@ pub fn type_if<'input>(
         )));
     }
 
+    warn_if_constant_condition(scope, &typed_cond);
+    warn_if_empty_branch(scope, &then, "then");
+    if let Some(then_else) = &then_else {
+        warn_if_empty_branch(scope, then_else, "else");
+    }
+
     let typed_then = type_block(
         scope,
         coerce_stmt_into_block(*then),
-        can_use_break_continue,
+        break_continue,
         return_ability.clone().demote(),
     )?;
     let then_act = typed_then.return_actuality;
@@ -66,7 +100,7 @@ pub fn type_if<'input>(
             type_block(
                 scope,
                 coerce_stmt_into_block(*then_else),
-                can_use_break_continue,
+                break_continue,
                 return_ability.clone().demote(),
             )
         })
@@ -91,3 +125,57 @@ pub fn type_if<'input>(
         return_actuality,
     )))
 }
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::{DiagnosticKind, HelpKind};
+
+    use crate::typeck::{GlobalScope, type_program};
+
+    /// Type check `body` as the body of a function taking `b`, returning the
+    /// warnings it produces with their help messages
+    fn warnings_in(body: &str) -> Vec<(DiagnosticKind, Option<HelpKind>)> {
+        let source = format!("fn take(x: i32);\nfn f(b: bool) {{ {body} }}");
+        let mut global_scope = GlobalScope::new();
+        type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program(&source, "<test>").expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        global_scope
+            .take_warnings()
+            .into_iter()
+            .map(|warning| (warning.kind.into_value(), warning.helps.first().cloned()))
+            .collect()
+    }
+
+    #[test]
+    fn empty_branches_are_warned_about() {
+        let empty = |branch: &str| DiagnosticKind::EmptyBranch(branch.to_string());
+
+        // TEST: an empty block or a stray `;` in either branch
+        assert_eq!(warnings_in("if (b) {}"), vec![(empty("then"), None)]);
+        assert_eq!(
+            warnings_in("if (b) take(1); else {}"),
+            vec![(empty("else"), None)]
+        );
+        assert_eq!(
+            warnings_in("if (b) ; else take(2);"),
+            vec![(empty("then"), Some(HelpKind::RemoveSemicolonAfterCondition))]
+        );
+
+        // TEST: the classic `if (x);` typo
+        assert_eq!(
+            warnings_in("if (b); take(1);"),
+            vec![(empty("then"), Some(HelpKind::RemoveSemicolonAfterCondition))]
+        );
+    }
+
+    #[test]
+    fn branches_that_do_something_are_not_warned_about() {
+        assert_eq!(warnings_in("if (b) take(1);"), vec![]);
+        assert_eq!(warnings_in("if (b) { take(1); } else take(2);"), vec![]);
+        assert_eq!(warnings_in("if (b) take(1); else if (!b) take(2);"), vec![]);
+    }
+}
@@ -1,7 +1,16 @@
 //! Defines the return status of a function
 
+use std::{cell::RefCell, rc::Rc};
+
+use serde::Serialize;
+use zrc_utils::span::Spanned;
+
 use crate::tast::ty::Type as TastType;
 
+/// The types returned by every `return` in a block typed with
+/// [`BlockReturnAbility::Infer`], in the order they appear
+pub type InferredReturns<'input> = Rc<RefCell<Vec<Spanned<TastType<'input>>>>>;
+
 /// Describes if a block MAY, MUST, or MUST NOT return.
 #[derive(Debug, Clone, PartialEq)]
 pub enum BlockReturnAbility<'input> {
@@ -17,6 +26,12 @@ pub enum BlockReturnAbility<'input> {
     ///
     /// Any sub-blocks of this block MAY return. At least one MUST return.
     MustReturn(TastType<'input>),
+
+    /// The block MAY return a value of any type. The type of every `return` is
+    /// recorded, so the return type of a function can be inferred from them.
+    ///
+    /// Any sub-blocks of this block record into the same list.
+    Infer(InferredReturns<'input>),
 }
 impl BlockReturnAbility<'_> {
     /// Determine the [`BlockReturnAbility`] of a sub-scope. `MustReturn`
@@ -26,10 +41,40 @@ impl BlockReturnAbility<'_> {
         match self {
             Self::MustNotReturn => Self::MustNotReturn,
             Self::MayReturn(x) | Self::MustReturn(x) => Self::MayReturn(x),
+            Self::Infer(returns) => Self::Infer(returns),
         }
     }
 }
 
+/// Describes which of `break` and `continue` may be used in a block, and what
+/// they jump out of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakContinueAbility {
+    /// The block is not inside a loop or `match`, so neither may be used.
+    Neither,
+
+    /// The block is inside a `match` which is not inside a loop. `break` exits
+    /// the `match`, and `continue` may not be used.
+    BreakFromMatch,
+
+    /// The block is inside a loop. `break` exits the loop and `continue` starts
+    /// its next iteration, even from within a `match`.
+    BreakAndContinue,
+}
+impl BreakContinueAbility {
+    /// Determine if `break` may be used.
+    #[must_use]
+    pub const fn can_break(self) -> bool {
+        matches!(self, Self::BreakFromMatch | Self::BreakAndContinue)
+    }
+
+    /// Determine if `continue` may be used.
+    #[must_use]
+    pub const fn can_continue(self) -> bool {
+        matches!(self, Self::BreakAndContinue)
+    }
+}
+
 /// Describes if a block labeled [MAY return](BlockReturnAbility::MayReturn)
 /// actually returns.
 ///
@@ -37,7 +82,7 @@ impl BlockReturnAbility<'_> {
 /// return](BlockReturnAbility::MustReturn) when a block contains a nested block
 /// (because the outer block must have at least *one* path which is guaranteed
 /// to return)
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize)]
 pub enum BlockReturnActuality {
     /// The block is guaranteed to never return on any path.
     NeverReturns,
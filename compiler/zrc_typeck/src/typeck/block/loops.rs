@@ -3,14 +3,15 @@
 use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, diagnostic::GenericLabel};
 use zrc_parser::ast::{
     expr::Expr,
-    stmt::{LetDeclaration, Stmt},
+    stmt::{LetDeclaration, Stmt, StmtKind},
+    ty::Type,
 };
 use zrc_utils::span::{Span, Spannable, Spanned};
 
 use super::{
-    super::{scope::Scope, type_expr},
-    block_utils::coerce_stmt_into_block,
-    cfa::{BlockReturnAbility, BlockReturnActuality},
+    super::{expr::leaked_number_expr, scope::Scope, type_expr},
+    block_utils::{coerce_stmt_into_block, discriminant_literal, warn_if_constant_condition},
+    cfa::{BlockReturnAbility, BlockReturnActuality, BreakContinueAbility},
     process_let_declaration, type_block,
 };
 use crate::tast::{
@@ -38,9 +39,14 @@ pub fn type_for<'input>(
 
     // if present, evaluate the declaration
     let typed_init = init
-        .map(|decl| process_let_declaration(&mut loop_scope, (*decl).into_value()))
+        .map(|decl| process_let_declaration(&mut loop_scope, (*decl).into_value(), false))
         .transpose()?;
 
+    // `break` and `continue` only belong in the body. The init, cond and post
+    // clauses are expressions, which cannot contain statements, so nothing can
+    // jump out of them. Should an expression ever contain statements, they must
+    // be typed with `BreakContinueAbility::Neither` here, not the ability of
+    // the body or of an enclosing loop.
     let cond_span = cond.as_ref().map(|inner| inner.0.span());
     let typed_cond = cond
         .map(|cond| type_expr(&mut loop_scope, cond))
@@ -67,6 +73,10 @@ pub fn type_for<'input>(
         )));
     }
 
+    if let Some(typed_cond) = &typed_cond {
+        warn_if_constant_condition(&loop_scope, typed_cond);
+    }
+
     let typed_post = post
         .map(|post| type_expr(&mut loop_scope, post))
         .transpose()?;
@@ -77,7 +87,7 @@ pub fn type_for<'input>(
     let body = type_block(
         &loop_scope,
         body_as_block,
-        true,
+        BreakContinueAbility::BreakAndContinue,
         return_ability.clone().demote(),
     )?;
     let ra = body.return_actuality;
@@ -114,7 +124,7 @@ pub fn type_four<'input>(
     let body = type_block(
         &loop_scope,
         body_as_block,
-        true,
+        BreakContinueAbility::BreakAndContinue,
         return_ability.clone().demote(),
     )?;
     let return_actuality = body.return_actuality;
@@ -159,10 +169,12 @@ pub fn type_while<'input>(
         )));
     }
 
+    warn_if_constant_condition(scope, &typed_cond);
+
     let body = type_block(
         scope,
         coerce_stmt_into_block(*body),
-        true,
+        BreakContinueAbility::BreakAndContinue,
         return_ability.clone().demote(),
     )?;
     let ra = body.return_actuality;
@@ -177,6 +189,287 @@ pub fn type_while<'input>(
     )))
 }
 
+/// The hidden variable a `while let` stores its scrutinee in for each
+/// iteration, so that it is only evaluated once
+const WHILE_LET_SCRUTINEE: &str = "__while_let_scrutinee__";
+
+/// Type check a `while let (Variant: binding = scrutinee) body` statement.
+///
+/// This desugars to:
+/// ```text
+/// while (true) {
+///     let __while_let_scrutinee__ = scrutinee;
+///     if (__while_let_scrutinee__.__discriminant__ != <Variant>) break;
+///     let binding = __while_let_scrutinee__.__value__.Variant;
+///     body
+/// }
+/// ```
+pub fn type_while_let<'input>(
+    scope: &mut Scope<'input>,
+    variant: Spanned<&'input str>,
+    binding: Spanned<&'input str>,
+    scrutinee: Expr<'input>,
+    body: Box<Stmt<'input>>,
+    return_ability: &BlockReturnAbility<'input>,
+    stmt_span: Span,
+) -> Result<Option<(TypedStmt<'input>, BlockReturnActuality)>, Diagnostic> {
+    let scrutinee_span = scrutinee.0.span();
+    let t_scrutinee = type_expr(scope, scrutinee.clone())?;
+    let Some(discriminants) = scope.enum_discriminants(&t_scrutinee.inferred_type) else {
+        return Err(
+            DiagnosticKind::MatchOnNonEnum(t_scrutinee.inferred_type.to_string())
+                .error_in(scrutinee_span)
+                .with_label(GenericLabel::error(
+                    LabelKind::MatchOnNonEnum(t_scrutinee.inferred_type.to_string())
+                        .in_span(scrutinee_span),
+                )),
+        );
+    };
+
+    let Some(discriminant) = discriminants
+        .into_iter()
+        .find(|(name, _)| name == variant.value())
+        .map(|(_, discriminant)| discriminant)
+    else {
+        return Err(DiagnosticKind::UnknownEnumVariant {
+            ty: t_scrutinee.inferred_type.to_string(),
+            variant: (*variant.value()).to_string(),
+        }
+        .error_in(variant.span())
+        .with_label(GenericLabel::error(
+            LabelKind::UnknownEnumVariant((*variant.value()).to_string()).in_span(variant.span()),
+        )));
+    };
+
+    let temporary = || Expr::build_ident(WHILE_LET_SCRUTINEE.in_span(scrutinee_span));
+    let field = |name: &'input str| name.in_span(scrutinee_span);
+    let declare = |name: Spanned<&'input str>, value: Expr<'input>| {
+        Stmt(
+            StmtKind::DeclarationList(
+                vec![
+                    LetDeclaration {
+                        name,
+                        ty: None,
+                        value: Some(value),
+                        is_constant: false,
+                    }
+                    .in_span(name.span()),
+                ]
+                .in_span(name.span()),
+            )
+            .in_span(name.span()),
+        )
+    };
+
+    let tag = t_scrutinee
+        .inferred_type
+        .enum_tag_type()
+        .expect("enum should have a tag");
+    let stop = Stmt(
+        StmtKind::IfStmt(
+            Expr::build_neq(
+                Expr::build_dot(temporary(), field("__discriminant__")),
+                discriminant_literal(discriminant, tag, scrutinee_span),
+            ),
+            Box::new(Stmt(StmtKind::BreakStmt(None).in_span(scrutinee_span))),
+            None,
+        )
+        .in_span(scrutinee_span),
+    );
+
+    let loop_body = Stmt(
+        StmtKind::BlockStmt(vec![
+            declare(field(WHILE_LET_SCRUTINEE), scrutinee),
+            stop,
+            declare(
+                binding,
+                Expr::build_dot(
+                    Expr::build_dot(temporary(), field("__value__")),
+                    field(variant.value()),
+                ),
+            ),
+            *body,
+        ])
+        .in_span(stmt_span),
+    );
+
+    type_while(
+        scope,
+        Expr::build_bool(true.in_span(stmt_span)),
+        Box::new(loop_body),
+        return_ability,
+        stmt_span,
+    )
+}
+
+/// The hidden copy of the array a `for (x in array)` iterates over, so that it
+/// is only evaluated once
+const FOR_IN_ARRAY: &str = "__for_in_array__";
+
+/// The hidden index of the element a `for (x in array)` is currently on
+const FOR_IN_INDEX: &str = "__for_in_index__";
+
+/// Type check a `for (binding in iterable) body` statement.
+///
+/// The array is copied once and its elements are copied into `binding` in
+/// turn, so assigning to `binding` never changes the array. This desugars to:
+/// ```text
+/// for (let __for_in_array__ = iterable, __for_in_index__: usize = 0;
+///      __for_in_index__ < len(iterable);
+///      __for_in_index__ += 1) {
+///     let binding = __for_in_array__[__for_in_index__];
+///     body
+/// }
+/// ```
+///
+/// The length is written as the constant `len` would produce, so it works
+/// even if `len` is shadowed.
+///
+/// # Errors
+/// Errors if `iterable` is not an array.
+pub fn type_for_in<'input>(
+    scope: &mut Scope<'input>,
+    binding: Spanned<&'input str>,
+    iterable: Expr<'input>,
+    body: Box<Stmt<'input>>,
+    return_ability: &BlockReturnAbility<'input>,
+    stmt_span: Span,
+) -> Result<Option<(TypedStmt<'input>, BlockReturnActuality)>, Diagnostic> {
+    let iterable_span = iterable.0.span();
+    let iterable_ty = type_expr(scope, iterable.clone())?.inferred_type;
+    let TastType::Array { size, .. } = iterable_ty else {
+        return Err(DiagnosticKind::ExpectedGot {
+            expected: "array".to_string(),
+            got: iterable_ty.to_string(),
+        }
+        .error_in(iterable_span)
+        .with_label(GenericLabel::error(
+            LabelKind::ExpectedGot {
+                expected: "array".to_string(),
+                got: iterable_ty.to_string(),
+            }
+            .in_span(iterable_span),
+        )));
+    };
+
+    let hidden = |name: &'input str| name.in_span(iterable_span);
+    let index = || Expr::build_ident(hidden(FOR_IN_INDEX));
+    let usize_type = || Type::build_ident(hidden("usize"));
+    let declaration = |name: Spanned<&'input str>, ty: Option<Type<'input>>, value| {
+        LetDeclaration {
+            name,
+            ty,
+            value: Some(value),
+            is_constant: false,
+        }
+        .in_span(name.span())
+    };
+
+    let init = vec![
+        declaration(hidden(FOR_IN_ARRAY), None, iterable),
+        declaration(
+            hidden(FOR_IN_INDEX),
+            Some(usize_type()),
+            Expr::build_number_dec(hidden("0"), None),
+        ),
+    ]
+    .in_span(iterable_span);
+    let cond = Expr::build_lt(
+        index(),
+        leaked_number_expr(i128::from(size), Some(usize_type()), iterable_span),
+    );
+    let post = Expr::build_add_assign(index(), Expr::build_number_dec(hidden("1"), None));
+
+    let element = Expr::build_index(
+        iterable_span,
+        Expr::build_ident(hidden(FOR_IN_ARRAY)),
+        index(),
+    );
+    let loop_body = Stmt(
+        StmtKind::BlockStmt(vec![
+            Stmt(
+                StmtKind::DeclarationList(
+                    vec![declaration(binding, None, element)].in_span(binding.span()),
+                )
+                .in_span(binding.span()),
+            ),
+            *body,
+        ])
+        .in_span(stmt_span),
+    );
+
+    type_for(
+        scope,
+        Some(Box::new(init)),
+        Some(cond),
+        Some(post),
+        Box::new(loop_body),
+        return_ability,
+        stmt_span,
+    )
+}
+
+/// Type check a `label: loop` statement, whose label `break label;` and
+/// `continue label;` may refer to anywhere in the loop, even from within
+/// nested loops.
+#[expect(clippy::wildcard_enum_match_arm)]
+pub fn type_labeled_loop<'input>(
+    scope: &mut Scope<'input>,
+    label: Spanned<&'input str>,
+    body: Stmt<'input>,
+    return_ability: &BlockReturnAbility<'input>,
+    stmt_span: Span,
+) -> Result<Option<(TypedStmt<'input>, BlockReturnActuality)>, Diagnostic> {
+    let loop_span = body.0.span();
+
+    scope.loop_labels.push(label.into_value());
+    let typed_loop = match body.0.into_value() {
+        StmtKind::WhileStmt(cond, body) => type_while(scope, cond, body, return_ability, loop_span),
+        StmtKind::WhileLetStmt {
+            variant,
+            binding,
+            scrutinee,
+            body,
+        } => type_while_let(
+            scope,
+            variant,
+            binding,
+            scrutinee,
+            body,
+            return_ability,
+            loop_span,
+        ),
+        StmtKind::DoWhileStmt(body, cond) => {
+            type_do_while(scope, body, cond, return_ability, loop_span)
+        }
+        StmtKind::ForStmt {
+            init,
+            cond,
+            post,
+            body,
+        } => type_for(scope, init, cond, post, body, return_ability, loop_span),
+        StmtKind::ForInStmt {
+            binding,
+            iterable,
+            body,
+        } => type_for_in(scope, binding, iterable, body, return_ability, loop_span),
+        StmtKind::FourStmt(body) => type_four(scope, body, return_ability, loop_span),
+        _ => unreachable!("the parser only allows labels on loops"),
+    };
+    scope.loop_labels.pop();
+
+    Ok(typed_loop?.map(|(typed_loop, return_actuality)| {
+        (
+            TypedStmt {
+                kind: TypedStmtKind::LabeledLoop(label.into_value(), Box::new(typed_loop))
+                    .in_span(stmt_span),
+                return_actuality,
+            },
+            return_actuality,
+        )
+    }))
+}
+
 /// Type check a do..while statement.
 pub fn type_do_while<'input>(
     scope: &mut Scope<'input>,
@@ -204,10 +497,12 @@ pub fn type_do_while<'input>(
         )));
     }
 
+    warn_if_constant_condition(scope, &typed_cond);
+
     let body = type_block(
         scope,
         coerce_stmt_into_block(*body),
-        true,
+        BreakContinueAbility::BreakAndContinue,
         return_ability.clone().demote(),
     )?;
     // Unlike `while`, a `do..while` loop is guaranteed to run at
@@ -223,3 +518,300 @@ pub fn type_do_while<'input>(
         return_actuality,
     )))
 }
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::DiagnosticKind;
+    use zrc_parser::parser::parse_program;
+
+    use crate::{
+        tast::stmt::TypedDeclaration,
+        typeck::{GlobalScope, type_program},
+    };
+
+    /// An enum and an iterator over it, for `while let` loops to consume
+    const ITERATOR: &str = "enum Opt { Some: i32, None: u8 }
+        fn next(i: *i32) -> Opt {
+            if (*i < 3) {
+                let value = *i;
+                *i += 1;
+                return Opt { Some: value };
+            }
+            return Opt { None: 0 };
+        }
+";
+
+    /// Type check [`ITERATOR`] followed by `code`, returning the last
+    /// function in the program in its alternate display form
+    fn type_check(code: &str) -> Result<String, DiagnosticKind> {
+        let source = format!("{ITERATOR}{code}");
+        let program = type_program(
+            &mut GlobalScope::new(),
+            parse_program(&source, "<test>").expect("parsing should succeed"),
+        )
+        .map_err(|diagnostic| diagnostic.kind.into_value())?;
+
+        let last = program.last().expect("program should not be empty").value();
+        assert!(matches!(last, TypedDeclaration::FunctionDeclaration { .. }));
+        Ok(format!("{last:#}"))
+    }
+
+    #[test]
+    fn while_let_loops_until_the_variant_does_not_match() {
+        let function = type_check(
+            "fn sum() -> i32 {
+                let i: i32 = 0;
+                let total: i32 = 0;
+                while let (Some: x = next(&i)) {
+                    if (x == 1) continue;
+                    total += x;
+                }
+                return total;
+            }",
+        )
+        .expect("typeck should succeed");
+
+        // TEST: `next` is called once per iteration, and the loop breaks on
+        // any tag other than `Some`'s, which is 1 as variants are sorted
+        assert!(
+            function.contains(
+                "= next(&i);
+        if (__while_let_scrutinee__.__discriminant__ != coerce<usize>(1)) {
+            break;
+        }
+        let x: i32 = __while_let_scrutinee__.__value__.Some;"
+            ),
+            "{function}"
+        );
+        assert_eq!(function.matches("next(&i)").count(), 1, "{function}");
+    }
+
+    #[test]
+    fn while_let_bindings_are_scoped_to_the_body() {
+        assert_eq!(
+            type_check(
+                "fn f() -> i32 {
+                    let i: i32 = 0;
+                    while let (Some: x = next(&i)) {}
+                    return x;
+                }"
+            ),
+            Err(DiagnosticKind::UnableToResolveIdentifier("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn invalid_while_let_loops_are_rejected() {
+        assert_eq!(
+            type_check(
+                "fn f() {
+                    let i: i32 = 0;
+                    while let (Other: x = next(&i)) {}
+                }"
+            ),
+            Err(DiagnosticKind::UnknownEnumVariant {
+                ty: "struct { __discriminant__: usize, __value__: union { Some: i32, None: u8 } }"
+                    .to_string(),
+                variant: "Other".to_string(),
+            })
+        );
+        assert_eq!(
+            type_check(
+                "fn f() {
+                    while let (Some: x = 4) {}
+                }"
+            ),
+            Err(DiagnosticKind::MatchOnNonEnum("{int}".to_string()))
+        );
+    }
+
+    #[test]
+    fn for_in_sums_the_elements_of_an_array() {
+        let function = type_check(
+            "fn sum() -> i32 {
+                let values: [3]i32 = [1, 2, 3];
+                let total: i32 = 0;
+                for (x in values) {
+                    total += x;
+                    x = 0;
+                }
+                return total;
+            }",
+        )
+        .expect("typeck should succeed");
+
+        // TEST: the array is copied once, then each element is copied into `x`
+        // in turn, so assigning to `x` leaves the array alone
+        assert!(
+            function.contains("let __for_in_array__: [3]i32 = values, __for_in_index__: usize"),
+            "{function}"
+        );
+        assert!(function.contains("__for_in_index__ < 3;"), "{function}");
+        assert!(
+            function.contains("let x: i32 = (coerce<*i32>(&__for_in_array__))[__for_in_index__];"),
+            "{function}"
+        );
+        assert_eq!(function.matches("values").count(), 2, "{function}");
+    }
+
+    #[test]
+    fn for_in_requires_an_array() {
+        assert_eq!(
+            type_check(
+                "fn f(values: *i32) {
+                    for (x in values) {}
+                }"
+            ),
+            Err(DiagnosticKind::ExpectedGot {
+                expected: "array".to_string(),
+                got: "*i32".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn control_flow_is_rejected_in_for_loop_clauses() {
+        // TEST: `break` and `continue` cannot be used in the init, cond or post
+        // of a `for`, even alongside an expression
+        for source in [
+            "fn f() { for (let i = 0; i < 3; continue) {} }",
+            "fn f() { for (let i = 0; i < 3; break) {} }",
+            "fn f() { for (let i = 0; i < 3; i += 1, continue) {} }",
+            "fn f() { for (let i = 0; break; i += 1) {} }",
+            "fn f() { for (continue; ; ) {} }",
+            "fn f() { while (true) { for (let i = 0; i < 3; continue) {} } }",
+        ] {
+            assert!(parse_program(source, "<test>").is_err(), "{source}");
+        }
+
+        // TEST: they are fine in the body of the same loop
+        assert!(
+            type_program(
+                &mut GlobalScope::new(),
+                parse_program(
+                    "fn f() { for (let i = 0; i < 3; i += 1) { if (i == 1) continue; break; } }",
+                    "<test>"
+                )
+                .expect("parsing should succeed"),
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn break_and_continue_in_desugared_loops_target_the_loop() {
+        let function = type_check(
+            "fn first_even(values: [4]i32) -> i32 {
+                let found: i32 = -1;
+                for (x in values) {
+                    if (x % 2 != 0) continue;
+                    found = x;
+                    break;
+                }
+                let i: i32 = 0;
+                while let (Some: y = next(&i)) {
+                    if (y == 0) continue;
+                    break;
+                }
+                return found;
+            }",
+        )
+        .expect("typeck should succeed");
+
+        // TEST: the `continue` and `break` of the `for (x in values)` are in the
+        // body, after `x` is declared, so they skip to the next element or
+        // stop the loop like they would in a plain `for`
+        assert!(
+            function.contains(
+                "let x: i32 = (coerce<*i32>(&__for_in_array__))[__for_in_index__];
+        {
+            if (x % coerce<i32>(2) != coerce<i32>(0)) {
+                continue;
+            }
+            found = x;
+            break;
+        }"
+            ),
+            "{function}"
+        );
+
+        // TEST: the `while let` keeps its own `break` for when the variant does
+        // not match, alongside the ones in its body
+        assert!(
+            function.contains(
+                "let y: i32 = __while_let_scrutinee__.__value__.Some;
+        {
+            if (y == coerce<i32>(0)) {
+                continue;
+            }
+            break;
+        }"
+            ),
+            "{function}"
+        );
+        assert_eq!(function.matches("continue;").count(), 2, "{function}");
+        assert_eq!(function.matches("break;").count(), 3, "{function}");
+    }
+
+    #[test]
+    fn break_and_continue_are_only_allowed_inside_desugared_loops() {
+        assert_eq!(
+            type_check(
+                "fn f(values: [2]i32) {
+                    for (x in values) {}
+                    break;
+                }"
+            ),
+            Err(DiagnosticKind::CannotUseBreakOutsideOfLoop)
+        );
+        assert_eq!(
+            type_check(
+                "fn f() {
+                    let i: i32 = 0;
+                    while let (Some: x = next(&i)) {}
+                    continue;
+                }"
+            ),
+            Err(DiagnosticKind::CannotUseContinueOutsideOfLoop)
+        );
+    }
+
+    #[test]
+    fn labeled_loops_can_be_broken_out_of_from_nested_loops() {
+        let function = type_check(
+            "fn find(grid: [3][3]i32) -> i32 {
+                let found: i32 = 0;
+                rows: for (row in grid) {
+                    four {
+                        for (cell in row) {
+                            if (cell == 0) continue rows;
+                            if (cell < 0) break rows;
+                            found += cell;
+                        }
+                    }
+                }
+                return found;
+            }",
+        )
+        .expect("typeck should succeed");
+
+        assert!(
+            function.contains("rows: for (let __for_in_array__"),
+            "{function}"
+        );
+        assert!(function.contains("continue rows;"), "{function}");
+        assert!(function.contains("break rows;"), "{function}");
+    }
+
+    #[test]
+    fn unknown_loop_labels_are_rejected() {
+        assert_eq!(
+            type_check("fn f() { outer: while (true) {} while (true) { break outer; } }"),
+            Err(DiagnosticKind::UnknownLoopLabel("outer".to_string()))
+        );
+        assert_eq!(
+            type_check("fn f() { outer: while (true) { continue inner; } }"),
+            Err(DiagnosticKind::UnknownLoopLabel("inner".to_string()))
+        );
+    }
+}
@@ -2,52 +2,88 @@
 
 use std::collections::HashMap;
 
-use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, diagnostic::GenericLabel};
+use zrc_diagnostics::{Diagnostic, DiagnosticKind, HelpKind, LabelKind, diagnostic::GenericLabel};
 use zrc_parser::ast::{
     expr::{Expr, ExprKind},
-    stmt::{LetDeclaration, MatchCase, Stmt, StmtKind, SwitchCase, SwitchTrigger},
+    stmt::{LetDeclaration, MatchCase, MatchPattern, Stmt, StmtKind, SwitchCase, SwitchTrigger},
+    ty::{Type, TypeKind},
 };
 use zrc_utils::span::{Span, Spannable, Spanned};
 
 use super::{
     super::{expr::try_coerce_to, scope::Scope, type_expr},
-    block_utils::{coerce_stmt_into_block, has_duplicates},
-    cfa::{BlockReturnAbility, BlockReturnActuality},
+    block_utils::{coerce_stmt_into_block, discriminant_literal, has_duplicates, warn_if_unused},
+    cfa::{BlockReturnAbility, BlockReturnActuality, BreakContinueAbility},
     type_block,
 };
 use crate::{
     tast::{
-        expr::TypedExpr,
-        stmt::{TypedStmt, TypedStmtKind},
+        expr::{MatchArm, TypedExpr, TypedExprKind},
+        stmt::{LetDeclaration as TastLetDeclaration, TypedStmt, TypedStmtKind},
         ty::Type as TastType,
     },
-    typeck::block::BlockMetadata,
+    typeck::{block::BlockMetadata, resolve_type, scope::ValueEntry},
 };
 
 /// Type check a switch case statement.
+///
+/// If `exits_on_break` is set, `break` in a case exits the switch instead of
+/// an enclosing loop. This is only used for the switch a `match` desugars to.
 #[expect(clippy::ptr_arg, clippy::too_many_lines)]
 pub fn type_switch_case<'input>(
     scope: &mut Scope<'input>,
     scrutinee: Expr<'input>,
     cases: &Vec<Spanned<SwitchCase<'input>>>,
+    break_continue: BreakContinueAbility,
+    exits_on_break: bool,
     return_ability: &BlockReturnAbility<'input>,
     stmt_span: Span,
 ) -> Result<Option<(TypedStmt<'input>, BlockReturnActuality)>, Diagnostic> {
     let mut cases = cases.clone();
-    let scrutinee = type_expr(scope, scrutinee)?;
+    let t_scrutinee = type_expr(scope, scrutinee.clone())?;
+    if t_scrutinee.inferred_type.enum_tag_type().is_some() {
+        return type_enum_switch(
+            scope,
+            scrutinee,
+            t_scrutinee,
+            &cases,
+            break_continue,
+            exits_on_break,
+            return_ability,
+            stmt_span,
+        );
+    }
+    let scrutinee = t_scrutinee;
     let scrutinee_ty = scrutinee.inferred_type.clone();
 
+    // a switch compares the scrutinee with each case, which codegen can only do
+    // for integers and by their contents for strings
+    if !(scrutinee_ty.is_integer() || scrutinee_ty == TastType::Bool || scrutinee_ty.is_string()) {
+        return Err(
+            DiagnosticKind::InvalidSwitchScrutinee(scrutinee_ty.to_string())
+                .error_in(scrutinee.kind.span())
+                .with_label(GenericLabel::error(
+                    LabelKind::InvalidSwitchScrutinee(scrutinee_ty.to_string())
+                        .in_span(scrutinee.kind.span()),
+                )),
+        );
+    }
+
     // The last entry in over MUST be the default case
     let maybe_default_case = cases.pop();
     let Some(maybe_default_case) = maybe_default_case else {
-        return Err(DiagnosticKind::SwitchCaseMissingTerminalDefault
+        // `switch (x) {}` handles no values at all, not even by default
+        return Err(DiagnosticKind::EmptySwitch(scrutinee_ty.to_string())
             .error_in(stmt_span)
             .with_label(GenericLabel::error(
-                LabelKind::SwitchCaseMissingTerminalDefault.in_span(stmt_span),
-            )));
+                LabelKind::EmptySwitch.in_span(stmt_span),
+            ))
+            .with_help(HelpKind::AddDefaultCase));
     };
 
-    let SwitchCase(SwitchTrigger::Default, default_stmt) = maybe_default_case.value() else {
+    let SwitchCase(SwitchTrigger::Default(default_binding), default_stmt) =
+        maybe_default_case.value()
+    else {
         return Err(DiagnosticKind::SwitchCaseMissingTerminalDefault
             .error_in(stmt_span)
             .with_label(GenericLabel::error(
@@ -58,7 +94,7 @@ pub fn type_switch_case<'input>(
     // Ensure no other default triggers exist (resolves ICE #654)
     if cases
         .iter()
-        .any(|case| matches!(case.value().0, SwitchTrigger::Default))
+        .any(|case| matches!(case.value().0, SwitchTrigger::Default(_)))
     {
         return Err(DiagnosticKind::MultipleDefaultCases
             .error_in(stmt_span)
@@ -67,10 +103,22 @@ pub fn type_switch_case<'input>(
             )));
     }
 
+    // `default(x) => ...` binds the scrutinee to `x` in the default case only
+    let mut default_scope = scope.clone();
+    if let Some(binding) = default_binding {
+        default_scope.values.insert(
+            binding.value(),
+            ValueEntry {
+                is_local: true,
+                ..ValueEntry::unused(scrutinee_ty.clone(), binding.span())
+            },
+        );
+    }
+
     let default_block = type_block(
-        scope,
+        &default_scope,
         coerce_stmt_into_block(default_stmt.clone()),
-        false,
+        break_continue,
         return_ability.clone().demote(),
     )?;
 
@@ -90,17 +138,38 @@ pub fn type_switch_case<'input>(
             )));
     }
 
+    // a switch over a string compares it against each case by its contents,
+    // so every case must be a string literal whose contents are known
+    let is_string_switch = scrutinee_ty.is_string();
+
     let cases = cases
         .into_iter()
         .map(|case| {
             let SwitchCase(trigger, exec) = case.into_value();
+            let trigger = trigger
+                .into_expr_value()
+                .expect("default was already popped/de-duped");
 
-            let trigger = type_expr(
-                scope,
-                trigger
-                    .into_expr_value()
-                    .expect("default was already popped/de-duped"),
-            )?;
+            if is_string_switch {
+                let ExprKind::StringLiteral(literal) = trigger.0.value() else {
+                    return Err(DiagnosticKind::StringSwitchCaseNotLiteral
+                        .error_in(trigger.0.span())
+                        .with_label(GenericLabel::error(
+                            LabelKind::StringSwitchCaseNotLiteral.in_span(trigger.0.span()),
+                        )));
+                };
+
+                // the scrutinee's length is found with `strlen`
+                if literal.as_bytes().contains('\0') {
+                    return Err(DiagnosticKind::StringSwitchCaseContainsNul
+                        .error_in(trigger.0.span())
+                        .with_label(GenericLabel::error(
+                            LabelKind::StringSwitchCaseContainsNul.in_span(trigger.0.span()),
+                        )));
+                }
+            }
+
+            let trigger = type_expr(scope, trigger)?;
 
             // Try to coerce trigger to scrutinee type if they don't
             // match
@@ -130,7 +199,7 @@ pub fn type_switch_case<'input>(
             let exec_block = type_block(
                 scope,
                 coerce_stmt_into_block(exec),
-                false,
+                break_continue,
                 return_ability.clone().demote(),
             )?;
             let return_status = exec_block.return_actuality;
@@ -152,7 +221,9 @@ pub fn type_switch_case<'input>(
             kind: (TypedStmtKind::SwitchCase {
                 scrutinee,
                 default: default_block,
+                default_binding: *default_binding,
                 cases,
+                exits_on_break,
             })
             .in_span(stmt_span),
             return_actuality,
@@ -161,13 +232,361 @@ pub fn type_switch_case<'input>(
     )))
 }
 
+/// Type check a switch over an enum, which desugars to a switch over its
+/// discriminant. Each case must be a path to a variant, like `E::A`, and
+/// becomes the discriminant of that variant. The scrutinee is stored once like
+/// that of a match, and `default(x)` binds `x` to the enum itself.
+///
+/// # Errors
+/// Errors if a case is not a variant of the scrutinee's enum, or two cases are
+/// the same variant.
+#[expect(clippy::too_many_arguments, clippy::too_many_lines)]
+fn type_enum_switch<'input>(
+    scope: &Scope<'input>,
+    scrutinee: Expr<'input>,
+    t_scrutinee: TypedExpr<'input>,
+    cases: &[Spanned<SwitchCase<'input>>],
+    break_continue: BreakContinueAbility,
+    exits_on_break: bool,
+    return_ability: &BlockReturnAbility<'input>,
+    stmt_span: Span,
+) -> Result<Option<(TypedStmt<'input>, BlockReturnActuality)>, Diagnostic> {
+    let scrutinee_ty = t_scrutinee.inferred_type.clone();
+    let tag = scrutinee_ty
+        .enum_tag_type()
+        .expect("enum should have a tag")
+        .clone();
+    let variant_to_discriminant: HashMap<&str, i128> = scope
+        .enum_discriminants(&scrutinee_ty)
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let mut switch_scope = scope.clone();
+    let (scrutinee, spill) = store_scrutinee(&mut switch_scope, scrutinee, t_scrutinee);
+
+    let mut switch_cases = Vec::with_capacity(cases.len());
+    let mut switched = Vec::new();
+    for case in cases {
+        let case_span = case.span();
+        let SwitchCase(trigger, body) = case.value().clone();
+
+        let case = match trigger {
+            SwitchTrigger::Expr(trigger) => {
+                let trigger_span = trigger.0.span();
+                let ExprKind::EnumVariant(ty, variant) = trigger.0.value() else {
+                    return Err(DiagnosticKind::EnumSwitchCaseNotVariant
+                        .error_in(trigger_span)
+                        .with_label(GenericLabel::error(
+                            LabelKind::EnumSwitchCaseNotVariant.in_span(trigger_span),
+                        )));
+                };
+
+                // only the discriminant is compared, so a variant that holds a
+                // value can be switched on too
+                let case_ty = resolve_type(
+                    &switch_scope,
+                    Type(TypeKind::Identifier(ty.value()).in_span(ty.span())),
+                )?;
+                if case_ty != scrutinee_ty {
+                    return Err(DiagnosticKind::ExpectedSameType(
+                        scrutinee_ty.to_string(),
+                        case_ty.to_string(),
+                    )
+                    .error_in(trigger_span)
+                    .with_label(GenericLabel::error(
+                        LabelKind::ExpectedSameType(scrutinee_ty.to_string(), case_ty.to_string())
+                            .in_span(trigger_span),
+                    )));
+                }
+                let Some(&discriminant) = variant_to_discriminant.get(variant.value()) else {
+                    return Err(DiagnosticKind::UnknownEnumVariant {
+                        ty: (*ty.value()).to_string(),
+                        variant: (*variant.value()).to_string(),
+                    }
+                    .error_in(variant.span())
+                    .with_label(GenericLabel::error(
+                        LabelKind::UnknownEnumVariant((*variant.value()).to_string())
+                            .in_span(variant.span()),
+                    )));
+                };
+
+                if switched.contains(&discriminant) {
+                    return Err(DiagnosticKind::MultipleCases
+                        .error_in(stmt_span)
+                        .with_label(GenericLabel::error(
+                            LabelKind::MultipleCases.in_span(stmt_span),
+                        )));
+                }
+                switched.push(discriminant);
+
+                SwitchCase(
+                    SwitchTrigger::Expr(discriminant_literal(discriminant, &tag, trigger_span)),
+                    body,
+                )
+            }
+            // Build block: { let <binding> = <scrutinee>; <body> }
+            SwitchTrigger::Default(Some(binding)) => {
+                let let_decl = LetDeclaration {
+                    name: binding,
+                    ty: None,
+                    value: Some(scrutinee.clone()),
+                    is_constant: false,
+                };
+                let let_stmt = Stmt(Spanned::from_span_and_value(
+                    case_span,
+                    StmtKind::DeclarationList(Spanned::from_span_and_value(
+                        case_span,
+                        vec![Spanned::from_span_and_value(case_span, let_decl)],
+                    )),
+                ));
+                SwitchCase(
+                    SwitchTrigger::Default(None),
+                    Stmt(Spanned::from_span_and_value(
+                        case_span,
+                        StmtKind::BlockStmt(vec![let_stmt, body]),
+                    )),
+                )
+            }
+            SwitchTrigger::Default(None) => SwitchCase(SwitchTrigger::Default(None), body),
+        };
+        switch_cases.push(Spanned::from_span_and_value(case_span, case));
+    }
+
+    let (typed_switch, switch_return_actuality) = type_switch_case(
+        &mut switch_scope,
+        scrutinee_field(scrutinee, "__discriminant__"),
+        &switch_cases,
+        break_continue,
+        exits_on_break,
+        return_ability,
+        stmt_span,
+    )?
+    .expect("switch statements always produce a statement");
+
+    let Some(spill) = spill else {
+        return Ok(Some((typed_switch, switch_return_actuality)));
+    };
+    Ok(Some((
+        TypedStmt {
+            kind: TypedStmtKind::BlockStmt(BlockMetadata {
+                stmts: vec![spill, typed_switch],
+                scope: switch_scope,
+                return_actuality: switch_return_actuality,
+            })
+            .in_span(stmt_span),
+            return_actuality: switch_return_actuality,
+        },
+        switch_return_actuality,
+    )))
+}
+
+/// Join `values` into a list of alternatives, like `` `A`, `B` or `C` ``
+fn list_alternatives(values: &[&str]) -> String {
+    match values {
+        [] => String::new(),
+        [only] => format!("`{only}`"),
+        [rest @ .., last] => format!(
+            "{} or `{last}`",
+            rest.iter()
+                .map(|value| format!("`{value}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// The values a match over `scrutinee` must handle, named by the patterns that
+/// match them: the variants of an enum, or `true` and `false`.
+///
+/// # Errors
+/// Errors if `scrutinee` is neither a `bool` nor an enum.
+fn match_values<'input>(scrutinee: &TypedExpr<'input>) -> Result<Vec<&'input str>, Diagnostic> {
+    let ty = &scrutinee.inferred_type;
+    match ty.enum_variants() {
+        Some(variants) => Ok(variants.iter().map(|(name, _)| name).collect()),
+        None if *ty == TastType::Bool => Ok(vec!["true", "false"]),
+        None => Err(DiagnosticKind::MatchOnNonEnum(ty.to_string())
+            .error_in(scrutinee.kind.span())
+            .with_label(GenericLabel::error(
+                LabelKind::MatchOnNonEnum(ty.to_string()).in_span(scrutinee.kind.span()),
+            ))),
+    }
+}
+
+/// Build `<scrutinee>.<field>`, spanned like the scrutinee
+fn scrutinee_field<'input>(scrutinee: Expr<'input>, field: &'input str) -> Expr<'input> {
+    let span = scrutinee.0.span();
+    Expr(Spanned::from_span_and_value(
+        span,
+        ExprKind::Dot(
+            Box::new(scrutinee),
+            Spanned::from_span_and_value(span, field),
+        ),
+    ))
+}
+
+/// The hidden variable a match over an enum stores its scrutinee in, so that
+/// it is only evaluated once
+const MATCH_SCRUTINEE: &str = "__match_scrutinee__";
+
+/// Whether the scrutinee of a match over an enum must be stored in
+/// [`MATCH_SCRUTINEE`] before it is read. A variable can be read again as
+/// often as needed, but anything else could have side effects.
+const fn needs_spill(scrutinee: &Expr<'_>) -> bool {
+    !matches!(scrutinee.0.value(), ExprKind::Identifier(_))
+}
+
+/// Declare [`MATCH_SCRUTINEE`] in `scope` to hold the value of `scrutinee`,
+/// returning an expression that reads it back
+fn spill_scrutinee<'input>(
+    scope: &mut Scope<'input>,
+    scrutinee: &TypedExpr<'input>,
+) -> Expr<'input> {
+    let span = scrutinee.kind.span();
+    scope.values.insert(
+        MATCH_SCRUTINEE,
+        ValueEntry {
+            is_local: true,
+            ..ValueEntry::unused(scrutinee.inferred_type.clone(), span)
+        },
+    );
+    Expr::build_ident(MATCH_SCRUTINEE.in_span(span))
+}
+
+/// Store the enum `scrutinee`, typed as `t_scrutinee`, in [`MATCH_SCRUTINEE`]
+/// if it [must be](needs_spill), returning an expression that reads it and the
+/// declaration of the variable. Anything else is read as it is.
+fn store_scrutinee<'input>(
+    scope: &mut Scope<'input>,
+    scrutinee: Expr<'input>,
+    t_scrutinee: TypedExpr<'input>,
+) -> (Expr<'input>, Option<TypedStmt<'input>>) {
+    if !needs_spill(&scrutinee) {
+        return (scrutinee, None);
+    }
+
+    let scrutinee_span = t_scrutinee.kind.span();
+    let stored = spill_scrutinee(scope, &t_scrutinee);
+    let spill = TypedStmt {
+        kind: TypedStmtKind::DeclarationList(vec![
+            TastLetDeclaration {
+                name: MATCH_SCRUTINEE.in_span(scrutinee_span),
+                ty: t_scrutinee.inferred_type.clone(),
+                value: Some(t_scrutinee),
+                is_constant: false,
+            }
+            .in_span(scrutinee_span),
+        ])
+        .in_span(scrutinee_span),
+        return_actuality: BlockReturnActuality::NeverReturns,
+    };
+    (stored, Some(spill))
+}
+
+/// Check the cases of a `match` over a value of type `ty`, which is one of
+/// `values`, returning the cases that can be reached.
+///
+/// A case that can never match, because the cases before it already match
+/// every value it would, is warned about and left out. This includes every
+/// case after a `default` case, so a reachable `default` case is always last.
+///
+/// # Errors
+/// Errors if a pattern cannot match a `ty`, or if there is no `default` case
+/// and some value is not matched by any case.
+fn reachable_cases<'input, B>(
+    scope: &Scope<'input>,
+    ty: &TastType<'input>,
+    values: &[&'input str],
+    cases: Vec<Spanned<MatchCase<'input, B>>>,
+    stmt_span: Span,
+) -> Result<Vec<Spanned<MatchCase<'input, B>>>, Diagnostic> {
+    let is_bool = *ty == TastType::Bool;
+    let mut matched = Vec::new();
+    let mut has_default = false;
+    let mut reachable = Vec::new();
+
+    for case in cases {
+        let case_span = case.span();
+
+        // the value this case matches, or `None` for `default`
+        let value = match case.value().pattern {
+            MatchPattern::Variant(variant, _) if !is_bool => {
+                if !values.contains(&variant) {
+                    return Err(DiagnosticKind::UnknownEnumVariant {
+                        ty: ty.to_string(),
+                        variant: variant.to_string(),
+                    }
+                    .error_in(case_span)
+                    .with_label(GenericLabel::error(
+                        LabelKind::UnknownEnumVariant(variant.to_string()).in_span(case_span),
+                    )));
+                }
+                Some(variant)
+            }
+            MatchPattern::Bool(value) if is_bool => Some(if value { "true" } else { "false" }),
+            MatchPattern::Default => None,
+            pattern @ (MatchPattern::Variant(_, _) | MatchPattern::Bool(_)) => {
+                return Err(DiagnosticKind::MatchPatternMismatch {
+                    pattern: pattern.to_string(),
+                    ty: ty.to_string(),
+                }
+                .error_in(case_span)
+                .with_label(GenericLabel::error(
+                    LabelKind::MatchPatternMismatch(ty.to_string()).in_span(case_span),
+                )));
+            }
+        };
+
+        let unreachable_because = match value {
+            _ if has_default => Some(LabelKind::MatchArmAlwaysHandled),
+            Some(value) if matched.contains(&value) => {
+                Some(LabelKind::MatchArmAlreadyHandled(value.to_string()))
+            }
+            None if matched.len() == values.len() => Some(LabelKind::MatchArmAlwaysHandled),
+            Some(_) | None => None,
+        };
+        if let Some(label) = unreachable_because {
+            scope.warn(
+                DiagnosticKind::UnreachableMatchArm
+                    .warning_in(case_span)
+                    .with_label(GenericLabel::warning(label.in_span(case_span))),
+            );
+            continue;
+        }
+
+        match value {
+            Some(value) => matched.push(value),
+            None => has_default = true,
+        }
+        reachable.push(case);
+    }
+
+    let missing = values
+        .iter()
+        .filter(|value| !matched.contains(value))
+        .copied()
+        .collect::<Vec<_>>();
+    if !has_default && !missing.is_empty() {
+        let missing = list_alternatives(&missing);
+        return Err(DiagnosticKind::NonExhaustiveMatch(missing.clone())
+            .error_in(stmt_span)
+            .with_label(GenericLabel::error(
+                LabelKind::NonExhaustiveMatch(missing).in_span(stmt_span),
+            ))
+            .with_help(HelpKind::AddDefaultCase));
+    }
+
+    Ok(reachable)
+}
+
 /// Desugar and type check a match statement.
-#[expect(clippy::too_many_lines, clippy::needless_pass_by_value)]
+#[expect(clippy::too_many_lines)]
 pub fn type_match<'input>(
     scope: &mut Scope<'input>,
     scrutinee: Expr<'input>,
     cases: Vec<Spanned<MatchCase<'input>>>,
-    can_use_break_continue: bool,
+    break_continue: BreakContinueAbility,
     return_ability: &BlockReturnAbility<'input>,
     stmt_span: Span,
 ) -> Result<Option<(TypedStmt<'input>, BlockReturnActuality)>, Diagnostic> {
@@ -205,9 +624,11 @@ pub fn type_match<'input>(
     // }
 
     // Semantic invariants:
-    // * The scrutinee must be of an enum type
-    // * There must be exactly one case per variant of the enum
-    // * Each case introduces a new variable into scope with the type of the variant
+    // * The scrutinee must be a `bool` or of an enum type
+    // * Every pattern must be able to match a value of that type
+    // * Every value must be matched by a case, unless there is a `default`
+    // * Each variant case introduces a new variable into scope with the type
+    //   of the variant
 
     // There is no TAST Enum type, it is simply represented as a Struct
     // with a hidden discriminant field
@@ -215,198 +636,142 @@ pub fn type_match<'input>(
 
     let t_scrutinee = type_expr(scope, scrutinee.clone())?;
     let scrutinee_ty = t_scrutinee.inferred_type.clone();
-    // Clone scrutinee for AST construction (never move original)
-    let scrutinee_ast = scrutinee.clone();
-
-    // * The scrutinee must be of an enum type
-    // (Bonus points: Extracts the internal `union` declaration for
-    // later use)
-    let enum_as_union_def = if let TastType::Struct(ref struct_def) = scrutinee_ty
-        && struct_def.get("__discriminant__").is_some()
-        && struct_def.get("__value__").is_some()
-    {
-        if let TastType::Union(union_def) = struct_def
-            .get("__value__")
-            .expect("value should exist")
-            .clone()
-        {
-            union_def
-        } else {
-            return Err(DiagnosticKind::MatchOnNonEnum(scrutinee_ty.to_string())
-                .error_in(t_scrutinee.kind.span())
-                .with_label(GenericLabel::error(
-                    LabelKind::MatchOnNonEnum(scrutinee_ty.to_string())
-                        .in_span(t_scrutinee.kind.span()),
-                )));
-        }
-    } else {
-        return Err(DiagnosticKind::MatchOnNonEnum(scrutinee_ty.to_string())
-            .error_in(t_scrutinee.kind.span())
-            .with_label(GenericLabel::error(
-                LabelKind::MatchOnNonEnum(scrutinee_ty.to_string())
-                    .in_span(t_scrutinee.kind.span()),
-            )));
-    };
 
-    // * There must be exactly one case per variant of the enum
-    if enum_as_union_def.len() != cases.len() {
-        return Err(DiagnosticKind::MatchCaseCountMismatch
-            .error_in(stmt_span)
-            .with_label(GenericLabel::error(
-                LabelKind::MatchCaseCountMismatch.in_span(stmt_span),
-            )));
-    }
-
-    // Ensure each enum variant is covered by exactly one case
-    // We do this by sorting both lists and comparing them
-    #[expect(clippy::useless_asref)]
-    let mut sorted_enum_variants: Vec<(&str, &TastType<'_>)> = enum_as_union_def
-        .iter()
-        .map(|(name, ty)| (name.as_ref(), ty))
-        .collect::<Vec<_>>();
+    // * The scrutinee must be a `bool` or of an enum type
+    let is_enum = scrutinee_ty.enum_variants().is_some();
+    let values = match_values(&t_scrutinee)?;
 
-    let mut sorted_case_variants = cases
-        .iter()
-        .map(|case| case.value().variant)
-        .collect::<Vec<_>>();
+    // An enum is stored once, and the switch and the bindings read it back
+    let mut switch_scope = scope.clone();
+    let (scrutinee_ast, spill) = if is_enum {
+        store_scrutinee(&mut switch_scope, scrutinee, t_scrutinee)
+    } else {
+        (scrutinee, None)
+    };
 
-    sorted_enum_variants.sort_unstable_by_key(|(a, _)| *a);
-    sorted_case_variants.sort_unstable();
+    let cases = reachable_cases(scope, &scrutinee_ty, &values, cases, stmt_span)?;
 
-    if sorted_enum_variants
-        .iter()
-        .map(|(name, _)| *name)
-        .ne(sorted_case_variants.iter().copied())
-    {
-        return Err(DiagnosticKind::NonExhaustiveMatchCases
-            .error_in(stmt_span)
-            .with_label(GenericLabel::error(
-                LabelKind::NonExhaustiveMatchCases.in_span(stmt_span),
-            )));
-    }
-
-    // Create discriminant mapping using ALPHABETICAL ORDER
-    // Both enum construction and match must use the same alphabetically sorted
-    // order
-    let variant_to_discriminant: HashMap<&str, usize> = sorted_enum_variants
-        .iter()
-        .enumerate()
-        .map(|(idx, (name, _))| (*name, idx))
+    // Create discriminant mapping, which enum construction must also use
+    let variant_to_discriminant: HashMap<&str, i128> = scope
+        .enum_discriminants(&scrutinee_ty)
+        .into_iter()
+        .flatten()
         .collect();
 
-    // The index into sorted_enum_variants is the discriminant value
-    // We sorted both, so the indices line up
-
     // Generate the literal AST for the desugared version
 
     // Desugar match into switch on discriminant (AST only)
 
     // Build the scrutinee for switch: <scrutinee>.__discriminant__ (AST
-    // Expr)
-    let discrim_access = Expr(Spanned::from_span_and_value(
-        t_scrutinee.kind.span(),
-        ExprKind::Dot(
-            Box::new(scrutinee_ast.clone()),
-            Spanned::from_span_and_value(t_scrutinee.kind.span(), "__discriminant__"),
-        ),
-    ));
+    // Expr), or the scrutinee itself when it is a `bool`
+    let switch_scrutinee = if is_enum {
+        scrutinee_field(scrutinee_ast.clone(), "__discriminant__")
+    } else {
+        scrutinee_ast.clone()
+    };
 
-    // Build switch cases for each variant
+    // Build switch cases for each pattern
     let mut switch_cases = Vec::new();
+    // without a `default` case every value is matched by another case
+    let mut default_body = Stmt(StmtKind::UnreachableStmt.in_span(stmt_span));
 
     for case in &cases {
-        let variant_name = case.value().variant;
-        let var_binding = case.value().var;
         let body = case.value().body.clone();
         let case_span = case.span();
 
-        let discriminant_idx = *variant_to_discriminant
-            .get(&variant_name)
-            .expect("variant should be present in discriminant map");
-
-        // Build let binding: let <var_binding> =
-        // <scrutinee>.__value__.<variant_name>;
-        let value_access = Expr(Spanned::from_span_and_value(
-            t_scrutinee.kind.span(),
-            ExprKind::Dot(
-                Box::new(Expr(Spanned::from_span_and_value(
-                    t_scrutinee.kind.span(),
-                    ExprKind::Dot(
-                        Box::new(scrutinee_ast.clone()),
-                        Spanned::from_span_and_value(t_scrutinee.kind.span(), "__value__"),
-                    ),
-                ))),
-                Spanned::from_span_and_value(t_scrutinee.kind.span(), variant_name),
+        let (trigger, block) = match case.value().pattern {
+            MatchPattern::Default => {
+                default_body = body;
+                continue;
+            }
+            MatchPattern::Bool(value) => (
+                Expr(ExprKind::BooleanLiteral(value).in_span(case_span)),
+                body,
             ),
-        ));
-        let let_decl = LetDeclaration {
-            name: Spanned::from_span_and_value(case_span, var_binding),
-            ty: None,
-            value: Some(value_access),
-            is_constant: false,
-        };
-        let let_stmt = Stmt(Spanned::from_span_and_value(
-            case_span,
-            StmtKind::DeclarationList(Spanned::from_span_and_value(
-                case_span,
-                vec![Spanned::from_span_and_value(case_span, let_decl)],
-            )),
-        ));
+            MatchPattern::Variant(variant_name, var_binding) => {
+                let discriminant_idx = *variant_to_discriminant
+                    .get(&variant_name)
+                    .expect("variant should be present in discriminant map");
 
-        // Build block: { let <var_binding> = ...; <body> }
-        let block_stmts = vec![let_stmt, body.clone()];
-        let block = Stmt(Spanned::from_span_and_value(
-            case_span,
-            StmtKind::BlockStmt(block_stmts),
-        ));
+                // Build block: { let <var_binding> =
+                // <scrutinee>.__value__.<variant_name>; <body> }, or just
+                // <body> if the value is not bound
+                let block = match var_binding {
+                    Some(var_binding) => {
+                        let let_decl = LetDeclaration {
+                            name: Spanned::from_span_and_value(case_span, var_binding),
+                            ty: None,
+                            value: Some(scrutinee_field(
+                                scrutinee_field(scrutinee_ast.clone(), "__value__"),
+                                variant_name,
+                            )),
+                            is_constant: false,
+                        };
+                        let let_stmt = Stmt(Spanned::from_span_and_value(
+                            case_span,
+                            StmtKind::DeclarationList(Spanned::from_span_and_value(
+                                case_span,
+                                vec![Spanned::from_span_and_value(case_span, let_decl)],
+                            )),
+                        ));
 
-        // Switch trigger: discriminant value
-        let trigger_expr = Expr(Spanned::from_span_and_value(
-            case_span,
-            ExprKind::NumberLiteral(
-                // SAFETY: We leak this string because the AST
-                // requires a &str for number literals and we need
-                // it to live long enough
-                zrc_parser::lexer::NumberLiteral::Decimal(Box::leak(Box::new(
-                    discriminant_idx.to_string(),
-                ))),
-                None,
-            ),
-        ));
-        let trigger = SwitchTrigger::Expr(trigger_expr);
+                        Stmt(Spanned::from_span_and_value(
+                            case_span,
+                            StmtKind::BlockStmt(vec![let_stmt, body]),
+                        ))
+                    }
+                    None => body,
+                };
+
+                // Switch trigger: discriminant value
+                let trigger = discriminant_literal(
+                    discriminant_idx,
+                    scrutinee_ty
+                        .enum_tag_type()
+                        .expect("enum should have a tag"),
+                    case_span,
+                );
+
+                (trigger, block)
+            }
+        };
 
         switch_cases.push(Spanned::from_span_and_value(
             case_span,
-            SwitchCase(trigger, block),
+            SwitchCase(SwitchTrigger::Expr(trigger), block),
         ));
     }
 
     switch_cases.push(Spanned::from_span_and_value(
         stmt_span,
-        SwitchCase(
-            SwitchTrigger::Default,
-            Stmt(StmtKind::UnreachableStmt.in_span(stmt_span)),
-        ),
+        SwitchCase(SwitchTrigger::Default(None), default_body),
     ));
 
-    // Build the switch statement AST
-    let switch_stmt = Stmt(Spanned::from_span_and_value(
-        stmt_span,
-        StmtKind::SwitchCase {
-            scrutinee: discrim_access,
-            cases: switch_cases,
-        },
-    ));
+    // `break` exits the loop around the match if there is one, and otherwise
+    // exits the match itself
+    let (break_continue, exits_on_break) = if break_continue.can_continue() {
+        (break_continue, false)
+    } else {
+        (BreakContinueAbility::BreakFromMatch, true)
+    };
 
-    // Recursively type check the desugared switch statement
-    let typed_switch_block = type_block(
-        scope,
-        Spanned::from_span_and_value(stmt_span, vec![switch_stmt.clone()]),
-        can_use_break_continue,
-        return_ability.clone().demote(),
-    )?;
+    // Type check the desugared switch statement in its own block
+    let (typed_switch, switch_return_actuality) = type_switch_case(
+        &mut switch_scope,
+        switch_scrutinee,
+        &switch_cases,
+        break_continue,
+        exits_on_break,
+        &return_ability.clone().demote(),
+        stmt_span,
+    )?
+    .expect("switch statements always produce a statement");
 
-    let switch_return_actuality = typed_switch_block.return_actuality;
+    let typed_switch_block = BlockMetadata {
+        stmts: spill.into_iter().chain([typed_switch]).collect(),
+        scope: switch_scope,
+        return_actuality: switch_return_actuality,
+    };
 
     Ok(Some((
         TypedStmt {
@@ -417,6 +782,177 @@ pub fn type_match<'input>(
     )))
 }
 
+/// Desugar and type check a match expression, which yields the value of the
+/// arm whose pattern matches the scrutinee.
+///
+/// Like a match statement this is a switch over the discriminant of an enum,
+/// or over a `bool` itself, but each arm is an expression. The values of the
+/// arms are unified to one type, which is the type of the match.
+///
+/// # Errors
+/// Errors if the scrutinee cannot be matched over, if some value is not
+/// matched by any arm, or if the values of the arms have no common type.
+#[expect(clippy::too_many_lines)]
+pub fn type_match_expr<'input>(
+    scope: &mut Scope<'input>,
+    expr_span: Span,
+    scrutinee: Expr<'input>,
+    arms: Vec<Spanned<MatchCase<'input, Expr<'input>>>>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    let t_scrutinee = type_expr(scope, scrutinee.clone())?;
+    let scrutinee_ty = t_scrutinee.inferred_type.clone();
+    let values = match_values(&t_scrutinee)?;
+    let arms = reachable_cases(scope, &scrutinee_ty, &values, arms, expr_span)?;
+
+    let variant_to_discriminant: HashMap<&str, i128> = scope
+        .enum_discriminants(&scrutinee_ty)
+        .into_iter()
+        .flatten()
+        .collect();
+
+    // An enum is stored once, and the switch and the bindings read it back
+    let is_enum = scrutinee_ty.enum_tag_type().is_some();
+    let mut match_scope = scope.clone();
+    let scope = &mut match_scope;
+    let (spilled, scrutinee) = if is_enum && needs_spill(&scrutinee) {
+        let stored = spill_scrutinee(scope, &t_scrutinee);
+        (
+            Some((MATCH_SCRUTINEE, Box::new(t_scrutinee.clone()))),
+            stored,
+        )
+    } else {
+        (None, scrutinee)
+    };
+
+    let switch_scrutinee = if is_enum {
+        type_expr(
+            scope,
+            scrutinee_field(scrutinee.clone(), "__discriminant__"),
+        )?
+    } else {
+        t_scrutinee
+    };
+
+    let mut typed_arms = Vec::new();
+    for arm in arms {
+        let arm_span = arm.span();
+        let MatchCase { pattern, body } = arm.into_value();
+
+        let (trigger, binding) = match pattern {
+            MatchPattern::Default => (None, None),
+            MatchPattern::Bool(value) => (
+                Some(type_expr(
+                    scope,
+                    Expr(ExprKind::BooleanLiteral(value).in_span(arm_span)),
+                )?),
+                None,
+            ),
+            MatchPattern::Variant(variant_name, binding) => {
+                let discriminant = *variant_to_discriminant
+                    .get(&variant_name)
+                    .expect("variant should be present in discriminant map");
+                let tag = scrutinee_ty
+                    .enum_tag_type()
+                    .expect("enum should have a tag");
+                let trigger = discriminant_literal(discriminant, tag, arm_span);
+                let trigger = try_coerce_to(type_expr(scope, trigger)?, tag);
+                let binding = binding
+                    .map(|binding| {
+                        let value = scrutinee_field(
+                            scrutinee_field(scrutinee.clone(), "__value__"),
+                            variant_name,
+                        );
+                        Ok((binding, type_expr(scope, value)?))
+                    })
+                    .transpose()?;
+                (Some(trigger), binding)
+            }
+        };
+
+        // each binding is a new variable in the scope of its arm only
+        let mut arm_scope = scope.clone();
+        if let Some((name, value)) = &binding {
+            arm_scope.values.insert(
+                name,
+                ValueEntry {
+                    is_local: true,
+                    ..ValueEntry::unused(value.inferred_type.clone(), arm_span)
+                },
+            );
+        }
+        let value = type_expr(&mut arm_scope, body)?;
+        if let Some((name, _)) = &binding {
+            warn_if_unused(
+                &arm_scope,
+                &[(
+                    *name,
+                    arm_scope
+                        .values
+                        .resolve(name)
+                        .expect("binding was just inserted"),
+                )],
+            );
+        }
+
+        typed_arms.push(MatchArm {
+            trigger,
+            binding,
+            value,
+        });
+    }
+
+    let result_type = unify_arms(&typed_arms)?;
+    let typed_arms = typed_arms
+        .into_iter()
+        .map(|arm| MatchArm {
+            value: try_coerce_to(arm.value, &result_type),
+            ..arm
+        })
+        .collect();
+
+    Ok(TypedExpr {
+        inferred_type: result_type,
+        kind: TypedExprKind::Match(spilled, Box::new(switch_scrutinee), typed_arms)
+            .in_span(expr_span),
+    })
+}
+
+/// Find the type the values of every arm of a match expression can be
+/// implicitly cast to, resolving `{int}` to `i32`. A match without arms
+/// yields the unit type.
+///
+/// # Errors
+/// Errors if the value of an arm cannot be cast to the type of the arms before
+/// it, nor they to its type.
+fn unify_arms<'input>(arms: &[MatchArm<'input>]) -> Result<TastType<'input>, Diagnostic> {
+    let mut unified: Option<TastType<'input>> = None;
+    for arm in arms {
+        let ty = &arm.value.inferred_type;
+        unified = Some(match unified {
+            None => ty.clone(),
+            Some(unified) if unified == *ty || ty.can_implicitly_cast_to(&unified) => unified,
+            Some(unified) if unified.can_implicitly_cast_to(ty) => ty.clone(),
+            Some(unified) => {
+                let span = arm.value.kind.span();
+                return Err(
+                    DiagnosticKind::ExpectedSameType(unified.to_string(), ty.to_string())
+                        .error_in(span)
+                        .with_label(GenericLabel::error(
+                            LabelKind::ExpectedSameType(unified.to_string(), ty.to_string())
+                                .in_span(span),
+                        )),
+                );
+            }
+        });
+    }
+
+    Ok(match unified {
+        None => TastType::unit(),
+        Some(TastType::Int) => TastType::I32,
+        Some(ty) => ty,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use zrc_utils::spanned_test;
@@ -436,7 +972,7 @@ mod tests {
         let tck_result = type_block(
             &gs.create_subscope(),
             block_ast,
-            false,
+            BreakContinueAbility::Neither,
             BlockReturnAbility::MustNotReturn,
         );
 
@@ -458,4 +994,503 @@ mod tests {
             )))
         );
     }
+
+    #[test]
+    fn break_and_continue_in_switches_follow_the_enclosing_loop() {
+        let gs = GlobalScope::default();
+
+        // TEST: break/continue inside a block inside an if inside a switch
+        // inside a loop refer to that loop
+        let in_loop = "while (true) {
+            switch (1 as i32) {
+                1 => if (true) { { break; } } else { { continue; } }
+                default => { if (false) continue; else break; }
+            }
+        }";
+        assert!(
+            type_block(
+                &gs.create_subscope(),
+                zrc_parser::parser::parse_stmt_list(in_loop, "<test>").expect("should parse"),
+                BreakContinueAbility::Neither,
+                BlockReturnAbility::MustNotReturn,
+            )
+            .is_ok()
+        );
+
+        // TEST: ...but a switch alone does not allow them
+        let outside_loop = "switch (1 as i32) { 1 => if (true) { { break; } } default => {} }";
+        assert_eq!(
+            type_block(
+                &gs.create_subscope(),
+                zrc_parser::parser::parse_stmt_list(outside_loop, "<test>").expect("should parse"),
+                BreakContinueAbility::Neither,
+                BlockReturnAbility::MustNotReturn,
+            )
+            .map(|_| ())
+            .map_err(|diagnostic| diagnostic.kind.into_value()),
+            Err(DiagnosticKind::CannotUseBreakOutsideOfLoop)
+        );
+    }
+
+    #[test]
+    fn empty_switch_is_not_exhaustive() {
+        let gs = GlobalScope::default();
+
+        let source = "switch (true) {}";
+
+        let block_ast =
+            zrc_parser::parser::parse_stmt_list(source, "<test>").expect("should parse");
+
+        let tck_result = type_block(
+            &gs.create_subscope(),
+            block_ast,
+            BreakContinueAbility::Neither,
+            BlockReturnAbility::MustNotReturn,
+        );
+
+        let Err(diagnostic) = tck_result else {
+            panic!("expected type checking to fail");
+        };
+
+        assert_eq!(
+            diagnostic,
+            Diagnostic::error(spanned_test!(
+                0,
+                DiagnosticKind::EmptySwitch("bool".to_string()),
+                16
+            ))
+            .with_label(GenericLabel::error(spanned_test!(
+                0,
+                LabelKind::EmptySwitch,
+                16
+            )))
+            .with_help(HelpKind::AddDefaultCase)
+        );
+    }
+
+    #[test]
+    fn empty_match_is_not_exhaustive() {
+        let result = crate::typeck::type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(
+                "enum E { A: i32, B: u8 }\nfn f(e: E) { match (e) {} }",
+                "<test>",
+            )
+            .expect("should parse"),
+        );
+
+        assert_eq!(
+            result.map_err(|diagnostic| diagnostic.kind.into_value()),
+            Err(DiagnosticKind::NonExhaustiveMatch("`A` or `B`".to_string()))
+        );
+    }
+
+    /// Type check `body` in a function taking a `bool` `b` and an enum `e`,
+    /// returning the warnings it produces
+    fn match_warnings(body: &str) -> Result<Vec<DiagnosticKind>, DiagnosticKind> {
+        let source = format!("enum E {{ A: i32, B: u8, C }}\nfn f(b: bool, e: E) {{ {body} }}");
+        let mut global_scope = GlobalScope::new();
+        crate::typeck::type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program(&source, "<test>").expect("should parse"),
+        )
+        .map_err(|diagnostic| diagnostic.kind.into_value())?;
+
+        Ok(global_scope
+            .take_warnings()
+            .into_iter()
+            .map(|warning| warning.kind.into_value())
+            .collect())
+    }
+
+    #[test]
+    fn matches_must_handle_every_value_without_a_default() {
+        // TEST: every variant or boolean value is handled
+        assert_eq!(
+            match_warnings("match (e) { C: _z => {} A: _x => {} B: _y => {} }"),
+            Ok(vec![])
+        );
+        assert_eq!(
+            match_warnings("match (b) { false => {} true => {} }"),
+            Ok(vec![])
+        );
+
+        // TEST: the values that are not handled are listed in order
+        assert_eq!(
+            match_warnings("match (e) { A: _x => {} }"),
+            Err(DiagnosticKind::NonExhaustiveMatch("`B` or `C`".to_string()))
+        );
+        assert_eq!(
+            match_warnings("match (b) { true => {} }"),
+            Err(DiagnosticKind::NonExhaustiveMatch("`false`".to_string()))
+        );
+
+        // TEST: `default` handles everything else
+        assert_eq!(
+            match_warnings("match (e) { B: _y => {} default => {} }"),
+            Ok(vec![])
+        );
+        assert_eq!(match_warnings("match (b) { default => {} }"), Ok(vec![]));
+    }
+
+    #[test]
+    fn match_patterns_must_fit_the_scrutinee() {
+        // `E` as the type checker sees it
+        let enum_ty =
+            "struct { __discriminant__: usize, __value__: union { A: i32, B: u8, C: struct {} } }";
+
+        assert_eq!(
+            match_warnings("match (e) { true => {} default => {} }"),
+            Err(DiagnosticKind::MatchPatternMismatch {
+                pattern: "true".to_string(),
+                ty: enum_ty.to_string(),
+            })
+        );
+        assert_eq!(
+            match_warnings("match (b) { A: x => {} default => {} }"),
+            Err(DiagnosticKind::MatchPatternMismatch {
+                pattern: "A: x".to_string(),
+                ty: "bool".to_string(),
+            })
+        );
+        assert_eq!(
+            match_warnings("match (e) { D: x => {} default => {} }"),
+            Err(DiagnosticKind::UnknownEnumVariant {
+                ty: enum_ty.to_string(),
+                variant: "D".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn match_arms_that_can_never_match_are_warned_about() {
+        let unreachable = |count| vec![DiagnosticKind::UnreachableMatchArm; count];
+
+        // TEST: a value handled by an earlier arm
+        assert_eq!(
+            match_warnings("match (e) { A: _x => {} A: _y => {} default => {} }"),
+            Ok(unreachable(1))
+        );
+        assert_eq!(
+            match_warnings("match (b) { true => {} false => {} true => {} }"),
+            Ok(unreachable(1))
+        );
+
+        // TEST: every arm after `default`
+        assert_eq!(
+            match_warnings("match (e) { default => {} A: _x => {} default => {} }"),
+            Ok(unreachable(2))
+        );
+
+        // TEST: a `default` after every value is handled
+        assert_eq!(
+            match_warnings("match (b) { true => {} false => {} default => {} }"),
+            Ok(unreachable(1))
+        );
+    }
+
+    #[test]
+    fn match_switches_on_explicit_discriminants() {
+        let program = crate::typeck::type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(
+                "enum E { A: i32 = 10, B: u8 = -2 }\nfn f(e: E) { match (e) { A: x => {} B: y => {} } }",
+                "<test>",
+            )
+            .expect("should parse"),
+        )
+        .expect("typeck should succeed");
+
+        let body = program[0].value().to_string();
+        assert!(
+            body.contains("switch ((e.__discriminant__ as i8))"),
+            "{body}"
+        );
+        assert!(body.contains("(10 as i8) =>"), "{body}");
+        assert!(body.contains("(-(2 as i8) as i8) =>"), "{body}");
+    }
+
+    #[test]
+    fn match_scrutinees_are_evaluated_once() {
+        let program = crate::typeck::type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(
+                "enum E { A: i32, B: u8 }\nfn g() -> E;\n\
+                 fn f() { match (g()) { A: _x => {} B: _y => {} } }",
+                "<test>",
+            )
+            .expect("should parse"),
+        )
+        .expect("typeck should succeed");
+
+        // TEST: the discriminant and both bindings read the stored value
+        let body = program[1].value().to_string();
+        assert!(body.contains("let __match_scrutinee__: "), "{body}");
+        assert!(
+            body.contains("let _x: i32 = (__match_scrutinee__.__value__.A as i32);"),
+            "{body}"
+        );
+        assert_eq!(body.matches("g()").count(), 1, "{body}");
+    }
+
+    #[test]
+    fn match_expressions_yield_the_value_of_the_matched_arm() {
+        let program = crate::typeck::type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(
+                "enum E { A: i32, B: u8, C }\n\
+                 fn f(e: E) -> u8 { return match (e) { A => 1, B: y => y, C => 2 }; }",
+                "<test>",
+            )
+            .expect("should parse"),
+        )
+        .expect("typeck should succeed");
+
+        // the arms are unified to `u8`, so the literals are too
+        let body = program[0].value().to_string();
+        assert!(
+            body.contains("match ((e.__discriminant__ as usize)) {"),
+            "{body}"
+        );
+        assert!(body.contains("(0 as usize) => (1 as u8)"), "{body}");
+        assert!(body.contains("(1 as usize): y => (y as u8)"), "{body}");
+
+        // TEST: arms of `{int}` default to `i32`, and must have a common type
+        assert_eq!(
+            match_warnings("let _x: i32 = match (b) { true => 1, false => 2 };"),
+            Ok(vec![])
+        );
+        assert_eq!(
+            match_warnings("let _x = match (b) { true => 1 as u8, default => true };"),
+            Err(DiagnosticKind::ExpectedSameType(
+                "u8".to_string(),
+                "bool".to_string()
+            ))
+        );
+
+        // TEST: bindings are local to their arm and warned about if unused
+        assert_eq!(
+            match_warnings("let _x = match (e) { A: x => 0, default => 1 };"),
+            Ok(vec![DiagnosticKind::UnusedVariable("x".to_string())])
+        );
+        assert_eq!(
+            match_warnings("let _x = match (e) { A: x => 0, default => x };"),
+            Err(DiagnosticKind::UnableToResolveIdentifier("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn match_expressions_must_handle_every_value() {
+        assert_eq!(
+            match_warnings("let _x = match (e) { A: x => x };"),
+            Err(DiagnosticKind::NonExhaustiveMatch("`B` or `C`".to_string()))
+        );
+        assert_eq!(
+            match_warnings("let _x = match (e) { C => 1, default => 2 };"),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn default_binding_holds_the_scrutinee() {
+        let parse =
+            |source| zrc_parser::parser::parse_program(source, "<test>").expect("should parse");
+
+        // TEST: `x` has the scrutinee's type inside the default arm
+        assert!(
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                parse(
+                    "fn compute() -> u8;\nfn consume(x: u8);\n\
+                     fn f() { switch (compute()) { 1 => {} default(x) => consume(x); } }"
+                )
+            )
+            .is_ok()
+        );
+
+        // TEST: `x` is not visible in the other arms
+        assert_eq!(
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                parse(
+                    "fn compute() -> u8;\nfn consume(x: u8);\n\
+                     fn f() { switch (compute()) { 1 => consume(x); default(x) => {} } }"
+                )
+            )
+            .map_err(|diagnostic| diagnostic.kind.into_value()),
+            Err(DiagnosticKind::UnableToResolveIdentifier("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn string_switch_cases_must_be_string_literals() {
+        let type_switch = |body: &str| {
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(
+                    &format!("fn run(); fn f(cmd: *u8, other: *u8) {{ {body} }}"),
+                    "<test>",
+                )
+                .expect("should parse"),
+            )
+            .map(|_| ())
+            .map_err(|diagnostic| diagnostic.kind.into_value())
+        };
+
+        assert_eq!(
+            type_switch("switch (cmd) { \"run\" => run(); \"\" => {} default => {} }"),
+            Ok(())
+        );
+        assert_eq!(
+            type_switch("switch (cmd) { \"run\" => run(); other => {} default => {} }"),
+            Err(DiagnosticKind::StringSwitchCaseNotLiteral)
+        );
+        assert_eq!(
+            type_switch("switch (cmd) { \"run\" => run(); }"),
+            Err(DiagnosticKind::SwitchCaseMissingTerminalDefault)
+        );
+
+        // TEST: `cmd` ends at its first NUL byte, so it could never match
+        assert_eq!(
+            type_switch("switch (cmd) { \"a\\0b\" => run(); default => {} }"),
+            Err(DiagnosticKind::StringSwitchCaseContainsNul)
+        );
+    }
+
+    #[test]
+    fn only_integers_bools_and_strings_can_be_switched_over() {
+        let type_switch = |body: &str| {
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(
+                    &format!(
+                        "fn f(p: *i32, q: *i32, b: bool, s: struct {{ x: i32 }}) {{ {body} }}"
+                    ),
+                    "<test>",
+                )
+                .expect("should parse"),
+            )
+            .map(|_| ())
+            .map_err(|diagnostic| diagnostic.kind.into_value())
+        };
+
+        assert_eq!(
+            type_switch("switch (b) { true => {} default => {} }"),
+            Ok(())
+        );
+        assert_eq!(
+            type_switch("switch (p) { q => {} default => {} }"),
+            Err(DiagnosticKind::InvalidSwitchScrutinee("*i32".to_string()))
+        );
+        assert_eq!(
+            type_switch("switch (s) { default => {} }"),
+            Err(DiagnosticKind::InvalidSwitchScrutinee(
+                "struct { x: i32 }".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn enums_are_switched_over_by_their_discriminants() {
+        let type_switch = |body: &str| {
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(
+                    &format!(
+                        "enum Color {{ Red, Green = 5, Blue: u8 }}\nenum Other {{ Red }}\n\
+                         fn get() -> Color;\nfn f(c: Color) {{ {body} }}"
+                    ),
+                    "<test>",
+                )
+                .expect("should parse"),
+            )
+            .map(|program| program.last().expect("f is declared").value().to_string())
+            .map_err(|diagnostic| diagnostic.kind.into_value())
+        };
+
+        // TEST: each case is the discriminant of its variant, even one that holds
+        // a value
+        let body = type_switch("switch (c) { Color::Green => {} Color::Blue => {} default => {} }")
+            .expect("typeck should succeed");
+        assert!(
+            body.contains("switch ((c.__discriminant__ as u8))"),
+            "{body}"
+        );
+        assert!(body.contains("(5 as u8) =>"), "{body}");
+        assert!(body.contains("(6 as u8) =>"), "{body}");
+
+        // TEST: the scrutinee is evaluated once, and `default(x)` binds the enum
+        let body = type_switch("switch (get()) { Color::Red => {} default(x) => { let _y = x; } }")
+            .expect("typeck should succeed");
+        assert_eq!(body.matches("get()").count(), 1, "{body}");
+        assert!(body.contains("let x: "), "{body}");
+
+        // TEST: every case must be a different variant of the same enum
+        assert_eq!(
+            type_switch("switch (c) { 5 => {} default => {} }"),
+            Err(DiagnosticKind::EnumSwitchCaseNotVariant)
+        );
+        assert_eq!(
+            type_switch("switch (c) { Color::Purple => {} default => {} }"),
+            Err(DiagnosticKind::UnknownEnumVariant {
+                ty: "Color".to_string(),
+                variant: "Purple".to_string(),
+            })
+        );
+        assert!(matches!(
+            type_switch("switch (c) { Other::Red => {} default => {} }"),
+            Err(DiagnosticKind::ExpectedSameType(_, _))
+        ));
+        assert_eq!(
+            type_switch("switch (c) { Color::Red => {} Color::Red => {} default => {} }"),
+            Err(DiagnosticKind::MultipleCases)
+        );
+    }
+
+    #[test]
+    fn break_in_a_match_exits_the_match_unless_in_a_loop() {
+        let type_with_enum = |body: &str| {
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(
+                    &format!("enum E {{ A: i32, B: u8 }}\nfn f(e: E) {{ {body} }}"),
+                    "<test>",
+                )
+                .expect("should parse"),
+            )
+            .map(|_| ())
+            .map_err(|diagnostic| diagnostic.kind.into_value())
+        };
+
+        // TEST: outside of a loop, `break` is allowed in a match arm...
+        assert_eq!(
+            type_with_enum("match (e) { A: x => { if (x > 0) break; } B: y => {} }"),
+            Ok(())
+        );
+
+        // TEST: ...but `continue` still is not, as there is nothing to continue
+        assert_eq!(
+            type_with_enum("match (e) { A: x => continue; B: y => {} }"),
+            Err(DiagnosticKind::CannotUseContinueOutsideOfLoop)
+        );
+
+        // TEST: `break` in a switch inside a match exits the match, but a switch
+        // alone still cannot be broken out of
+        assert_eq!(
+            type_with_enum(
+                "match (e) { A: x => switch (x) { 1 => break; default => {} } B: y => {} }"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            type_with_enum("switch (1 as i32) { 1 => break; default => {} }"),
+            Err(DiagnosticKind::CannotUseBreakOutsideOfLoop)
+        );
+
+        // TEST: inside a loop, both refer to the loop
+        assert_eq!(
+            type_with_enum("while (true) { match (e) { A: x => continue; B: y => break; } }"),
+            Ok(())
+        );
+    }
 }
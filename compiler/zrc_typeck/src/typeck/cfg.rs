@@ -0,0 +1,250 @@
+//! Conditional compilation with the `cfg` attribute
+//!
+//! A declaration written as `cfg(target_os = "linux") fn f();` is only type
+//! checked and compiled if the active target matches. Declarations that do not
+//! match are dropped before registration, as if they were never written.
+
+use std::env::consts;
+
+use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, diagnostic::GenericLabel};
+use zrc_parser::ast::{
+    expr::{Assignment, Expr, ExprKind},
+    stmt::{Attribute, Declaration as AstDeclaration},
+};
+use zrc_utils::span::{Spannable, Spanned};
+
+/// The properties of the active target that `cfg` attributes are checked
+/// against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CfgOptions {
+    /// The operating system, like `linux`, `windows` or `macos`
+    pub target_os: String,
+    /// The architecture, like `x86_64` or `aarch64`
+    pub target_arch: String,
+}
+impl CfgOptions {
+    /// Create the [`CfgOptions`] of the machine the compiler is running on
+    #[must_use]
+    pub fn host() -> Self {
+        Self {
+            target_os: consts::OS.to_string(),
+            target_arch: consts::ARCH.to_string(),
+        }
+    }
+
+    /// Create the [`CfgOptions`] for an LLVM target triple such as
+    /// `x86_64-unknown-linux-gnu`
+    #[must_use]
+    pub fn from_target_triple(triple: &str) -> Self {
+        let components = triple.split('-').collect::<Vec<_>>();
+
+        // `arch-os` has no vendor, but `arch-vendor-os(-env)` does
+        let os = match components.as_slice() {
+            [_, os] | [_, _, os, ..] => *os,
+            _ => "unknown",
+        };
+        let os = os.trim_end_matches(|ch: char| ch.is_ascii_digit() || ch == '.');
+
+        Self {
+            target_os: match os {
+                "darwin" | "macosx" => "macos",
+                os => os,
+            }
+            .to_string(),
+            target_arch: components.first().copied().unwrap_or("unknown").to_string(),
+        }
+    }
+}
+impl Default for CfgOptions {
+    fn default() -> Self {
+        Self::host()
+    }
+}
+
+/// Check if the `cfg` attribute of a declaration, if any, matches `options`
+///
+/// # Errors
+/// Errors if the `cfg` attribute is not a single `key = "value"` pair or uses
+/// an unknown key.
+pub fn is_enabled(
+    declaration: &AstDeclaration<'_>,
+    options: &CfgOptions,
+) -> Result<bool, Diagnostic> {
+    let AstDeclaration::FunctionDeclaration { attributes, .. } = declaration else {
+        return Ok(true);
+    };
+
+    attributes
+        .iter()
+        .filter(|attribute| *attribute.value().name.value() == "cfg")
+        .try_fold(true, |enabled, attribute| {
+            Ok(enabled && cfg_matches(attribute, options)?)
+        })
+}
+
+/// Evaluate a single `cfg(key = "value")` attribute against `options`
+fn cfg_matches(
+    attribute: &Spanned<Attribute<'_>>,
+    options: &CfgOptions,
+) -> Result<bool, Diagnostic> {
+    let invalid_arguments = |span| {
+        DiagnosticKind::InvalidAttributeArguments {
+            attribute: "cfg".to_string(),
+            expected: "a single `key = \"value\"` pair".to_string(),
+        }
+        .error_in(span)
+        .with_label(GenericLabel::error(
+            LabelKind::InvalidAttributeArguments("a single `key = \"value\"` pair".to_string())
+                .in_span(span),
+        ))
+    };
+
+    let Some(arguments) = &attribute.value().arguments else {
+        return Err(invalid_arguments(attribute.span()));
+    };
+
+    let [Expr(argument)] = arguments.value().as_slice() else {
+        return Err(invalid_arguments(arguments.span()));
+    };
+
+    let ExprKind::Assignment(Assignment::Standard, key, value) = argument.value() else {
+        return Err(invalid_arguments(arguments.span()));
+    };
+
+    let (ExprKind::Identifier(key), ExprKind::StringLiteral(value)) =
+        (key.0.value(), value.0.value())
+    else {
+        return Err(invalid_arguments(arguments.span()));
+    };
+
+    let actual = match *key {
+        "target_os" => &options.target_os,
+        "target_arch" => &options.target_arch,
+        _ => {
+            let key_span = argument.span();
+            return Err(DiagnosticKind::UnknownCfgKey((*key).to_string())
+                .error_in(key_span)
+                .with_label(GenericLabel::error(
+                    LabelKind::UnknownCfgKey.in_span(key_span),
+                )));
+        }
+    };
+
+    Ok(value.as_bytes() == *actual)
+}
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::DiagnosticKind;
+
+    use super::CfgOptions;
+    use crate::{
+        tast::stmt::TypedDeclaration,
+        typeck::{GlobalScope, type_program},
+    };
+
+    /// Type check a program for `triple` and return the names of the
+    /// functions that were kept
+    fn functions_for_target(input: &str, triple: &str) -> Result<Vec<String>, DiagnosticKind> {
+        let program = type_program(
+            &mut GlobalScope {
+                cfg: CfgOptions::from_target_triple(triple),
+                ..GlobalScope::new()
+            },
+            zrc_parser::parser::parse_program(input, "<test>").expect("parsing should succeed"),
+        )
+        .map_err(|diagnostic| diagnostic.kind.into_value())?;
+
+        Ok(program
+            .into_iter()
+            .filter_map(|declaration| match declaration.into_value() {
+                TypedDeclaration::FunctionDeclaration { name, .. } => {
+                    Some(name.into_value().to_string())
+                }
+                TypedDeclaration::GlobalLetDeclaration(_) => None,
+            })
+            .collect())
+    }
+
+    #[test]
+    fn target_triples_are_parsed() {
+        assert_eq!(
+            CfgOptions::from_target_triple("x86_64-unknown-linux-gnu"),
+            CfgOptions {
+                target_os: "linux".to_string(),
+                target_arch: "x86_64".to_string(),
+            }
+        );
+        assert_eq!(
+            CfgOptions::from_target_triple("aarch64-apple-darwin23.1.0"),
+            CfgOptions {
+                target_os: "macos".to_string(),
+                target_arch: "aarch64".to_string(),
+            }
+        );
+        assert_eq!(
+            CfgOptions::from_target_triple("x86_64-pc-windows-msvc"),
+            CfgOptions {
+                target_os: "windows".to_string(),
+                target_arch: "x86_64".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn linux_only_function_is_excluded_on_windows() {
+        let source = "cfg(target_os = \"linux\") fn foo();\nfn main() -> i32 { return 0; }";
+
+        assert_eq!(
+            functions_for_target(source, "x86_64-unknown-linux-gnu"),
+            Ok(vec!["foo".to_string(), "main".to_string()])
+        );
+        assert_eq!(
+            functions_for_target(source, "x86_64-pc-windows-msvc"),
+            Ok(vec!["main".to_string()])
+        );
+    }
+
+    #[test]
+    fn excluded_functions_cannot_be_called() {
+        assert_eq!(
+            functions_for_target(
+                "cfg(target_os = \"linux\") fn foo();\nfn main() -> i32 { foo(); return 0; }",
+                "x86_64-pc-windows-msvc"
+            ),
+            Err(DiagnosticKind::UnableToResolveIdentifier("foo".to_string()))
+        );
+    }
+
+    #[test]
+    fn invalid_cfg_attributes_are_rejected() {
+        assert_eq!(
+            functions_for_target(
+                "cfg(target_family = \"unix\") fn foo();",
+                "x86_64-unknown-linux-gnu"
+            ),
+            Err(DiagnosticKind::UnknownCfgKey("target_family".to_string()))
+        );
+        assert_eq!(
+            functions_for_target("cfg fn foo();", "x86_64-unknown-linux-gnu"),
+            Err(DiagnosticKind::InvalidAttributeArguments {
+                attribute: "cfg".to_string(),
+                expected: "a single `key = \"value\"` pair".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn compile_error_is_reported_only_in_enabled_declarations() {
+        let source = "cfg(target_os = \"windows\") fn main() -> i32 { @compile_error(\"x\"); }";
+
+        assert_eq!(
+            functions_for_target(source, "x86_64-pc-windows-msvc"),
+            Err(DiagnosticKind::CompileError("x".to_string()))
+        );
+        assert_eq!(
+            functions_for_target(source, "x86_64-unknown-linux-gnu"),
+            Ok(vec![])
+        );
+    }
+}
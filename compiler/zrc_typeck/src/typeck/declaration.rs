@@ -1,20 +1,33 @@
 //! For declarations
 
+mod attributes;
+mod comptime;
+mod enum_decl;
 mod func;
+mod impl_decl;
 mod let_decl;
 
+pub use attributes::{ParameterAttributes, resolve_parameter_attributes};
+pub use comptime::{is_comptime_parameter, specialize_function};
+pub use enum_decl::desugar_enum_declaration;
+pub use impl_decl::{check_method_types, desugar_impl_declaration};
 pub use let_decl::process_let_declaration;
 use zrc_diagnostics::{
     Diagnostic, DiagnosticKind, LabelKind, SpannedExt, diagnostic::GenericLabel,
 };
-use zrc_parser::ast::stmt::Declaration as AstDeclaration;
-use zrc_utils::span::Spannable;
+use zrc_parser::ast::stmt::{Declaration as AstDeclaration, LetDeclaration as AstLetDeclaration};
+use zrc_utils::span::{Spannable, Spanned};
 
-use super::{scope::GlobalScope, ty::resolve_type_with_self_reference, type_block};
+use super::{
+    expr::{fold_integer, leaked_number_literal},
+    scope::{GlobalScope, Scope},
+    ty::resolve_type_with_self_reference,
+    type_block,
+};
 use crate::{
     tast::{
         expr::{TypedExpr, TypedExprKind},
-        stmt::TypedDeclaration,
+        stmt::{LetDeclaration, TypedDeclaration},
     },
     typeck::scope::ValueEntry,
 };
@@ -22,8 +35,8 @@ use crate::{
 /// Check if an expression is a constant expression that can be evaluated at
 /// compile time.
 ///
-/// Currently, literal expressions and unary minus on literals are considered
-/// constant.
+/// Currently, literal expressions, unary minus on literals, and array literals
+/// of constants are considered constant.
 #[expect(clippy::wildcard_enum_match_arm)]
 pub fn is_constant_expr(expr: &TypedExpr) -> bool {
     match expr.kind.value() {
@@ -33,10 +46,97 @@ pub fn is_constant_expr(expr: &TypedExpr) -> bool {
         | TypedExprKind::CharLiteral(_) => true,
         // Unary minus on a constant is also a constant
         TypedExprKind::UnaryMinus(inner) => is_constant_expr(inner),
+        TypedExprKind::ArrayLiteral(elements) => elements.iter().all(is_constant_expr),
+        TypedExprKind::ArrayRepeat(value, _) => is_constant_expr(value),
         _ => false,
     }
 }
 
+/// Replace an integer initializer that can be [folded](fold_integer), like
+/// `4 * 1024`, with the literal it folds to, so that it is constant. Other
+/// initializers are returned as-is.
+fn fold_initializer(value: TypedExpr<'_>) -> TypedExpr<'_> {
+    if is_constant_expr(&value) || !value.inferred_type.is_integer() {
+        return value;
+    }
+    let Some(folded) = fold_integer(&value) else {
+        return value;
+    };
+
+    leaked_number_literal(folded, &value.inferred_type, value.kind.span())
+}
+
+/// Type check the declarations of a global `let`, `const` or `static`, whose
+/// initializers are [folded](fold_initializer) into constants.
+///
+/// A global is only in scope after its declaration, so initializers can never
+/// refer to each other in a cycle. The one way to try is for a global to use
+/// itself, which is reported as such rather than as an unknown identifier.
+///
+/// # Errors
+/// Errors if a declaration does not type check, if an initializer refers to
+/// the global it initializes, or if an initializer is not constant.
+fn process_global_let_declaration<'input>(
+    scope: &mut Scope<'input>,
+    declarations: Vec<Spanned<AstLetDeclaration<'input>>>,
+) -> Result<Vec<Spanned<LetDeclaration<'input>>>, Diagnostic> {
+    let initializers = declarations
+        .iter()
+        .filter_map(|declaration| {
+            let declaration = declaration.value();
+            Some((
+                *declaration.name.value(),
+                declaration.value.as_ref()?.0.span(),
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    let typed_declarations =
+        process_let_declaration(scope, declarations, false).map_err(|diagnostic| {
+            let span = diagnostic.kind.span();
+            let DiagnosticKind::UnableToResolveIdentifier(identifier) = diagnostic.kind.value()
+            else {
+                return diagnostic;
+            };
+            let is_own_initializer = initializers.iter().any(|(name, initializer)| {
+                name == identifier
+                    && initializer.start() <= span.start()
+                    && span.end() <= initializer.end()
+            });
+            if !is_own_initializer {
+                return diagnostic;
+            }
+
+            DiagnosticKind::GlobalInitializerReferencesItself(identifier.clone())
+                .error_in(span)
+                .with_label(GenericLabel::error(
+                    LabelKind::GlobalInitializerReferencesItself(identifier.clone()).in_span(span),
+                ))
+        })?;
+
+    typed_declarations
+        .into_iter()
+        .map(|declaration| {
+            declaration.map(|declaration| LetDeclaration {
+                value: declaration.value.map(fold_initializer),
+                ..declaration
+            })
+        })
+        .map(|declaration| {
+            if let Some(ref value) = declaration.value().value
+                && !is_constant_expr(value)
+            {
+                return Err(DiagnosticKind::GlobalInitializerMustBeConstant
+                    .error_in(value.kind.span())
+                    .with_label(GenericLabel::error(
+                        LabelKind::GlobalInitializerMustBeConstant.in_span(value.kind.span()),
+                    )));
+            }
+            Ok(declaration)
+        })
+        .collect()
+}
+
 /// Process a top-level [AST declaration](AstDeclaration), insert it into the
 /// scope, and return a [TAST declaration](TypedDeclaration).
 ///
@@ -53,6 +153,7 @@ pub fn process_declaration<'input>(
     // value (not a `Spanned` wrapper) because some callers pass the
     // unspanned enum directly in tests.
     register_declaration_value(global_scope, &declaration)?;
+    infer_declaration_return_type(global_scope, &declaration)?;
     finalize_declaration_value(global_scope, declaration)
 }
 
@@ -70,12 +171,14 @@ pub fn register_declaration_value<'input>(
             parameters,
             return_type,
             body,
+            attributes,
         } => func::register_function_declaration(
             global_scope,
             *name,
             parameters.clone(),
             return_type.clone(),
             body.clone(),
+            attributes,
         ),
 
         AstDeclaration::TypeAliasDeclaration { name, ty } => {
@@ -100,29 +203,90 @@ pub fn register_declaration_value<'input>(
         AstDeclaration::GlobalLetDeclaration(decls) => {
             let mut scope = global_scope.create_subscope();
             let typed_declarations =
-                process_let_declaration(&mut scope, decls.clone().into_value())?;
-
-            for decl in &typed_declarations {
-                if let Some(ref value) = decl.value().value
-                    && !is_constant_expr(value)
-                {
-                    return Err(DiagnosticKind::GlobalInitializerMustBeConstant
-                        .error_in(value.kind.span())
-                        .with_label(GenericLabel::error(
-                            LabelKind::GlobalInitializerMustBeConstant.in_span(value.kind.span()),
-                        )));
-                }
-            }
+                process_global_let_declaration(&mut scope, decls.clone().into_value())?;
 
             for decl in &typed_declarations {
                 global_scope.global_values.insert(
                     decl.value().name.value(),
-                    ValueEntry::unused(decl.value().ty.clone(), decl.span()),
+                    ValueEntry {
+                        is_constant: decl.value().is_constant,
+                        ..ValueEntry::unused(decl.value().ty.clone(), decl.span())
+                    },
                 );
             }
 
             Ok(())
         }
+
+        AstDeclaration::ImplDeclaration { .. } => {
+            unreachable!("impl declarations are desugared before registration")
+        }
+
+        AstDeclaration::EnumDeclaration { .. } => {
+            unreachable!("enum declarations are desugared before registration")
+        }
+    }
+}
+
+/// Infer the return type of a function defined without one, once every
+/// declaration is registered. Other declarations are left untouched.
+pub fn infer_declaration_return_type<'input>(
+    global_scope: &mut GlobalScope<'input>,
+    declaration: &AstDeclaration<'input>,
+) -> Result<(), Diagnostic> {
+    if let AstDeclaration::FunctionDeclaration {
+        name,
+        parameters,
+        return_type: None,
+        body: Some(body),
+        ..
+    } = declaration
+    {
+        func::infer_function_return_type(global_scope, *name, parameters, body)
+    } else {
+        Ok(())
+    }
+}
+
+/// Infer the return type of every function in `program` defined without one.
+///
+/// A function whose body uses another whose return type is not inferred yet
+/// is tried again after the rest, so each is inferred after those it calls.
+///
+/// # Errors
+/// Errors if a return type cannot be inferred, including with
+/// [`DiagnosticKind::ReturnTypeNotInferred`] if functions use each other's
+/// return types in a cycle.
+pub fn infer_return_types<'input>(
+    global_scope: &mut GlobalScope<'input>,
+    program: &[Spanned<AstDeclaration<'input>>],
+) -> Result<(), Diagnostic> {
+    let mut pending = program.iter().map(Spanned::value).collect::<Vec<_>>();
+    loop {
+        let mut deferred = Vec::new();
+        let mut first_error = None;
+        for declaration in &pending {
+            match infer_declaration_return_type(global_scope, declaration) {
+                Ok(()) => {}
+                Err(diagnostic)
+                    if matches!(
+                        diagnostic.kind.value(),
+                        DiagnosticKind::ReturnTypeNotInferred(_)
+                    ) =>
+                {
+                    first_error.get_or_insert(diagnostic);
+                    deferred.push(*declaration);
+                }
+                Err(diagnostic) => return Err(diagnostic),
+            }
+        }
+
+        match first_error {
+            None => return Ok(()),
+            // none were inferred, so they depend on each other in a cycle
+            Some(diagnostic) if deferred.len() == pending.len() => return Err(diagnostic),
+            Some(_) => pending = deferred,
+        }
     }
 }
 
@@ -134,20 +298,105 @@ pub fn finalize_declaration_value<'input>(
 ) -> Result<Option<TypedDeclaration<'input>>, Diagnostic> {
     match declaration {
         AstDeclaration::FunctionDeclaration {
+            attributes,
             name,
             parameters,
             return_type,
             body,
-        } => func::finalize_function_declaration(global_scope, name, parameters, return_type, body),
+        } => func::finalize_function_declaration(
+            global_scope,
+            &attributes,
+            name,
+            parameters,
+            return_type,
+            body,
+        ),
 
         AstDeclaration::TypeAliasDeclaration { .. } => Ok(None),
 
         AstDeclaration::GlobalLetDeclaration(decls) => {
             let mut scope = global_scope.create_subscope();
-            let typed_declarations = process_let_declaration(&mut scope, decls.into_value())?;
+            let typed_declarations =
+                process_global_let_declaration(&mut scope, decls.into_value())?;
             Ok(Some(TypedDeclaration::GlobalLetDeclaration(
                 typed_declarations,
             )))
         }
+
+        AstDeclaration::ImplDeclaration { .. } => {
+            unreachable!("impl declarations are desugared before finalization")
+        }
+
+        AstDeclaration::EnumDeclaration { .. } => {
+            unreachable!("enum declarations are desugared before finalization")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::DiagnosticKind;
+    use zrc_parser::parser::parse_program;
+
+    use crate::typeck::{GlobalScope, type_program};
+
+    /// Type check `source`, returning the first declaration in it
+    fn first_declaration(source: &str) -> Result<String, DiagnosticKind> {
+        let program = type_program(
+            &mut GlobalScope::new(),
+            parse_program(source, "<test>").expect("parsing should succeed"),
+        )
+        .map_err(|diagnostic| diagnostic.kind.into_value())?;
+
+        Ok(program
+            .first()
+            .expect("program should not be empty")
+            .value()
+            .to_string())
+    }
+
+    #[test]
+    fn global_initializers_are_folded_into_constants() {
+        // TEST: `static` declares a mutable global just like `let`
+        assert_eq!(
+            first_declaration(
+                "static counter: i32 = 4 * 1024;
+                fn next() -> i32 { counter += 1; return counter; }"
+            ),
+            Ok("let counter: i32 = (4096 as i32);".to_string())
+        );
+        assert_eq!(
+            first_declaration("const OFFSET: i64 = -(2i64 * 3);"),
+            Ok("let OFFSET: i64 = (-(6 as i64) as i64);".to_string())
+        );
+    }
+
+    #[test]
+    fn non_constant_global_initializers_are_rejected() {
+        assert_eq!(
+            first_declaration("fn f() -> i32; static x: i32 = f();"),
+            Err(DiagnosticKind::GlobalInitializerMustBeConstant)
+        );
+        assert_eq!(
+            first_declaration("const a: i32 = 1; static b: i32 = a;"),
+            Err(DiagnosticKind::GlobalInitializerMustBeConstant)
+        );
+    }
+
+    #[test]
+    fn globals_cannot_be_used_in_their_own_initializer() {
+        assert_eq!(
+            first_declaration("static x: i32 = x + 1;"),
+            Err(DiagnosticKind::GlobalInitializerReferencesItself(
+                "x".to_string()
+            ))
+        );
+
+        // TEST: a global declared later is not in scope yet, so it is unknown
+        // rather than part of a cycle
+        assert_eq!(
+            first_declaration("static x: i32 = y; static y: i32 = x;"),
+            Err(DiagnosticKind::UnableToResolveIdentifier("y".to_string()))
+        );
     }
 }
@@ -0,0 +1,587 @@
+//! Validate the attributes written before a declaration or parameter
+
+use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, diagnostic::GenericLabel};
+use zrc_parser::ast::{
+    expr::{Expr, ExprKind},
+    stmt::Attribute,
+};
+use zrc_utils::span::{Span, Spannable, Spanned};
+
+use crate::tast::{
+    stmt::{Constructor, Deprecation, FunctionAttributes},
+    ty::Type as TastType,
+};
+
+/// Resolve the [`Attribute`]s on a function declaration into
+/// [`FunctionAttributes`].
+///
+/// # Errors
+/// Errors if an attribute is unknown, repeated, given invalid arguments, or
+/// combined with an attribute it conflicts with.
+pub fn resolve_function_attributes(
+    attributes: &[Spanned<Attribute<'_>>],
+) -> Result<FunctionAttributes, Diagnostic> {
+    let mut resolved = FunctionAttributes::default();
+    let mut seen: Vec<Spanned<&str>> = Vec::new();
+
+    for attribute in attributes {
+        let name = attribute.value().name;
+
+        if seen.iter().any(|other| other.value() == name.value()) {
+            return Err(duplicate_attribute(name));
+        }
+        seen.push(name);
+
+        match *name.value() {
+            "inline" => {
+                expect_no_arguments(attribute)?;
+                resolved.inline = true;
+            }
+            "noinline" => {
+                expect_no_arguments(attribute)?;
+                resolved.noinline = true;
+            }
+            "optsize" => {
+                expect_no_arguments(attribute)?;
+                resolved.optsize = true;
+            }
+            "optnone" => {
+                expect_no_arguments(attribute)?;
+                resolved.optnone = true;
+            }
+            // evaluated before registration, see `typeck::cfg`
+            "cfg" => {}
+            "deprecated" => {
+                resolved.deprecated = Some(Deprecation {
+                    message: optional_message_argument(attribute)?,
+                });
+            }
+            "constructor" => {
+                resolved.constructor = Some(Constructor {
+                    priority: optional_priority_argument(attribute)?,
+                });
+            }
+            _ => return Err(unknown_attribute(name)),
+        }
+    }
+
+    // `optnone` keeps the function exactly as written, so it cannot also be
+    // inlined into its callers or optimized for size
+    if let Some(optnone) = seen.iter().find(|name| *name.value() == "optnone")
+        && let Some(other) = seen
+            .iter()
+            .find(|name| matches!(*name.value(), "inline" | "optsize"))
+    {
+        return Err(conflicting_attributes(*optnone, *other));
+    }
+
+    // `optnone` already implies `noinline`, so writing both is allowed
+    if let Some(noinline) = seen.iter().find(|name| *name.value() == "noinline")
+        && let Some(inline) = seen.iter().find(|name| *name.value() == "inline")
+    {
+        return Err(conflicting_attributes(*noinline, *inline));
+    }
+
+    Ok(resolved)
+}
+
+/// The attributes of a function parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParameterAttributes {
+    /// If the parameter is marked `noalias`
+    pub noalias: bool,
+    /// The span of the `comptime` attribute if the parameter is marked
+    /// `comptime`, so every call must pass it a constant
+    pub comptime: Option<Span>,
+}
+
+/// Resolve the [`Attribute`]s on a function parameter of type `ty`.
+///
+/// # Errors
+/// Errors if an attribute is unknown, repeated, or given arguments, if
+/// `noalias` is applied to a parameter that is not a pointer, or if `comptime`
+/// is applied to a parameter that is not an integer or `bool`.
+pub fn resolve_parameter_attributes(
+    attributes: &[Spanned<Attribute<'_>>],
+    ty: &Spanned<TastType<'_>>,
+) -> Result<ParameterAttributes, Diagnostic> {
+    let mut noalias = false;
+    let mut comptime = None;
+
+    for attribute in attributes {
+        let name = attribute.value().name;
+
+        match *name.value() {
+            "noalias" if noalias => return Err(duplicate_attribute(name)),
+            "noalias" => {
+                expect_no_arguments(attribute)?;
+                if !matches!(ty.value(), TastType::Ptr(_)) {
+                    return Err(DiagnosticKind::NoaliasOnNonPointer(ty.value().to_string())
+                        .error_in(name.span())
+                        .with_label(GenericLabel::error(
+                            LabelKind::NoaliasOnNonPointer(ty.value().to_string())
+                                .in_span(ty.span()),
+                        )));
+                }
+                noalias = true;
+            }
+            "comptime" if comptime.is_some() => return Err(duplicate_attribute(name)),
+            "comptime" => {
+                expect_no_arguments(attribute)?;
+                if !ty.value().is_integer() && *ty.value() != TastType::Bool {
+                    return Err(DiagnosticKind::InvalidComptimeParameterType(
+                        ty.value().to_string(),
+                    )
+                    .error_in(name.span())
+                    .with_label(GenericLabel::error(
+                        LabelKind::InvalidComptimeParameterType(ty.value().to_string())
+                            .in_span(ty.span()),
+                    )));
+                }
+                comptime = Some(name.span());
+            }
+            _ => return Err(unknown_attribute(name)),
+        }
+    }
+
+    Ok(ParameterAttributes { noalias, comptime })
+}
+
+/// The error for an attribute written twice on the same item
+fn duplicate_attribute(name: Spanned<&str>) -> Diagnostic {
+    DiagnosticKind::DuplicateAttribute(name.value().to_string())
+        .error_in(name.span())
+        .with_label(GenericLabel::error(
+            LabelKind::DuplicateAttribute(name.value().to_string()).in_span(name.span()),
+        ))
+}
+
+/// The error for `attribute` written on the same item as `other`, which it
+/// cannot be combined with
+fn conflicting_attributes(attribute: Spanned<&str>, other: Spanned<&str>) -> Diagnostic {
+    DiagnosticKind::ConflictingAttributes {
+        attribute: attribute.value().to_string(),
+        other: other.value().to_string(),
+    }
+    .error_in(attribute.span())
+    .with_label(GenericLabel::error(
+        LabelKind::ConflictingAttribute(other.value().to_string()).in_span(attribute.span()),
+    ))
+    .with_label(GenericLabel::note(
+        LabelKind::ConflictingAttributeSpecifiedHere(other.value().to_string())
+            .in_span(other.span()),
+    ))
+}
+
+/// The error for an attribute that does not exist
+fn unknown_attribute(name: Spanned<&str>) -> Diagnostic {
+    DiagnosticKind::UnknownAttribute(name.value().to_string())
+        .error_in(name.span())
+        .with_label(GenericLabel::error(
+            LabelKind::UnknownAttribute(name.value().to_string()).in_span(name.span()),
+        ))
+}
+
+/// Ensure an attribute was written without a parenthesized argument list
+fn expect_no_arguments(attribute: &Spanned<Attribute<'_>>) -> Result<(), Diagnostic> {
+    attribute
+        .value()
+        .arguments
+        .as_ref()
+        .map_or(Ok(()), |arguments| {
+            Err(DiagnosticKind::InvalidAttributeArguments {
+                attribute: attribute.value().name.value().to_string(),
+                expected: "no arguments".to_string(),
+            }
+            .error_in(arguments.span())
+            .with_label(GenericLabel::error(
+                LabelKind::InvalidAttributeArguments("no arguments".to_string())
+                    .in_span(arguments.span()),
+            )))
+        })
+}
+
+/// Resolve the arguments of an attribute that takes either no arguments or a
+/// single string literal, such as `deprecated("use g instead")`
+fn optional_message_argument(
+    attribute: &Spanned<Attribute<'_>>,
+) -> Result<Option<String>, Diagnostic> {
+    let Some(arguments) = &attribute.value().arguments else {
+        return Ok(None);
+    };
+
+    if let [Expr(message)] = arguments.value().as_slice()
+        && let ExprKind::StringLiteral(message) = message.value()
+    {
+        Ok(Some(message.as_bytes()))
+    } else {
+        Err(DiagnosticKind::InvalidAttributeArguments {
+            attribute: attribute.value().name.value().to_string(),
+            expected: "no arguments or a single string literal".to_string(),
+        }
+        .error_in(arguments.span())
+        .with_label(GenericLabel::error(
+            LabelKind::InvalidAttributeArguments(
+                "no arguments or a single string literal".to_string(),
+            )
+            .in_span(arguments.span()),
+        )))
+    }
+}
+
+/// Resolve the arguments of an attribute that takes either no arguments or a
+/// single unsuffixed integer from 0 to 65535, such as `constructor(101)`
+fn optional_priority_argument(
+    attribute: &Spanned<Attribute<'_>>,
+) -> Result<Option<u16>, Diagnostic> {
+    let Some(arguments) = &attribute.value().arguments else {
+        return Ok(None);
+    };
+
+    if let [Expr(priority)] = arguments.value().as_slice()
+        && let ExprKind::NumberLiteral(priority, None) = priority.value()
+        && let Ok(priority) =
+            u16::from_str_radix(&priority.text_content().replace('_', ""), priority.radix())
+    {
+        Ok(Some(priority))
+    } else {
+        Err(DiagnosticKind::InvalidAttributeArguments {
+            attribute: attribute.value().name.value().to_string(),
+            expected: "no arguments or a single priority from 0 to 65535".to_string(),
+        }
+        .error_in(arguments.span())
+        .with_label(GenericLabel::error(
+            LabelKind::InvalidAttributeArguments(
+                "no arguments or a single priority from 0 to 65535".to_string(),
+            )
+            .in_span(arguments.span()),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::{DiagnosticKind, NoteKind, Severity};
+
+    use crate::{
+        tast::stmt::{Constructor, Deprecation, FunctionAttributes, TypedDeclaration},
+        typeck::{GlobalScope, type_program},
+    };
+
+    /// Type check a program and return the attributes of its only function
+    fn attributes_of(input: &str) -> Result<FunctionAttributes, DiagnosticKind> {
+        let program = type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(input, "<test>").expect("parsing should succeed"),
+        )
+        .map_err(|diagnostic| diagnostic.kind.into_value())?;
+
+        match program
+            .into_iter()
+            .next()
+            .expect("program should have a declaration")
+            .into_value()
+        {
+            TypedDeclaration::FunctionDeclaration { attributes, .. } => Ok(attributes),
+            TypedDeclaration::GlobalLetDeclaration(_) => {
+                panic!("expected a function declaration")
+            }
+        }
+    }
+
+    #[test]
+    fn inline_attribute_is_resolved() {
+        assert_eq!(
+            attributes_of("inline fn f() {}"),
+            Ok(FunctionAttributes {
+                inline: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            attributes_of("fn f() {}"),
+            Ok(FunctionAttributes::default())
+        );
+    }
+
+    #[test]
+    fn invalid_attributes_are_rejected() {
+        assert_eq!(
+            attributes_of("inlin fn f() {}"),
+            Err(DiagnosticKind::UnknownAttribute("inlin".to_string()))
+        );
+        assert_eq!(
+            attributes_of("inline inline fn f() {}"),
+            Err(DiagnosticKind::DuplicateAttribute("inline".to_string()))
+        );
+        assert_eq!(
+            attributes_of("inline(1) fn f() {}"),
+            Err(DiagnosticKind::InvalidAttributeArguments {
+                attribute: "inline".to_string(),
+                expected: "no arguments".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn optimization_attributes_are_resolved() {
+        assert_eq!(
+            attributes_of("optsize fn f() {}"),
+            Ok(FunctionAttributes {
+                optsize: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            attributes_of("optnone fn f() {}"),
+            Ok(FunctionAttributes {
+                optnone: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            attributes_of("inline optsize fn f() {}"),
+            Ok(FunctionAttributes {
+                inline: true,
+                optsize: true,
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn optnone_conflicts_with_inline_and_optsize() {
+        assert_eq!(
+            attributes_of("optnone inline fn f() {}"),
+            Err(DiagnosticKind::ConflictingAttributes {
+                attribute: "optnone".to_string(),
+                other: "inline".to_string(),
+            })
+        );
+        assert_eq!(
+            attributes_of("optsize optnone fn f() {}"),
+            Err(DiagnosticKind::ConflictingAttributes {
+                attribute: "optnone".to_string(),
+                other: "optsize".to_string(),
+            })
+        );
+        assert_eq!(
+            attributes_of("optnone(1) fn f() {}"),
+            Err(DiagnosticKind::InvalidAttributeArguments {
+                attribute: "optnone".to_string(),
+                expected: "no arguments".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn noinline_conflicts_with_inline_but_not_optnone() {
+        assert_eq!(
+            attributes_of("noinline fn f() {}"),
+            Ok(FunctionAttributes {
+                noinline: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            attributes_of("optnone noinline fn f() {}"),
+            Ok(FunctionAttributes {
+                noinline: true,
+                optnone: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            attributes_of("inline noinline fn f() {}"),
+            Err(DiagnosticKind::ConflictingAttributes {
+                attribute: "noinline".to_string(),
+                other: "inline".to_string(),
+            })
+        );
+        assert_eq!(
+            attributes_of("noinline inline fn f() {}"),
+            Err(DiagnosticKind::ConflictingAttributes {
+                attribute: "noinline".to_string(),
+                other: "inline".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn deprecated_attribute_is_resolved() {
+        assert_eq!(
+            attributes_of("deprecated(\"use g instead\") fn f() {}"),
+            Ok(FunctionAttributes {
+                deprecated: Some(Deprecation {
+                    message: Some("use g instead".to_string())
+                }),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            attributes_of("deprecated(1) fn f() {}"),
+            Err(DiagnosticKind::InvalidAttributeArguments {
+                attribute: "deprecated".to_string(),
+                expected: "no arguments or a single string literal".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn constructor_attribute_is_resolved() {
+        assert_eq!(
+            attributes_of("constructor fn init() {}"),
+            Ok(FunctionAttributes {
+                constructor: Some(Constructor { priority: None }),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            attributes_of("constructor(101) fn init() {}"),
+            Ok(FunctionAttributes {
+                constructor: Some(Constructor {
+                    priority: Some(101)
+                }),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            attributes_of("constructor(65536) fn init() {}"),
+            Err(DiagnosticKind::InvalidAttributeArguments {
+                attribute: "constructor".to_string(),
+                expected: "no arguments or a single priority from 0 to 65535".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn constructors_must_not_take_parameters_or_return_values() {
+        assert_eq!(
+            attributes_of("constructor fn init(x: i32) {}"),
+            Err(DiagnosticKind::InvalidConstructorSignature(
+                "init".to_string()
+            ))
+        );
+        assert_eq!(
+            attributes_of("constructor fn init() -> i32 { return 0; }"),
+            Err(DiagnosticKind::InvalidConstructorSignature(
+                "init".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn calling_a_deprecated_function_warns() {
+        let mut global_scope = GlobalScope::new();
+        type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program(
+                "deprecated(\"use g instead\") fn f();\nfn g();\nfn main() -> i32 { f(); g(); return 0; }",
+                "<test>",
+            )
+            .expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        // TEST: only the call to `f` warns, with the deprecation message
+        let warnings = global_scope.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Severity::Warning);
+        assert_eq!(
+            *warnings[0].kind.value(),
+            DiagnosticKind::DeprecatedFunction("f".to_string())
+        );
+        assert_eq!(
+            warnings[0].notes,
+            vec![NoteKind::DeprecationMessage("use g instead".to_string())]
+        );
+    }
+
+    #[test]
+    fn calling_a_non_deprecated_function_does_not_warn() {
+        let mut global_scope = GlobalScope::new();
+        type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program(
+                "deprecated fn f();\nfn g();\nfn main() -> i32 { g(); return 0; }",
+                "<test>",
+            )
+            .expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        assert!(global_scope.take_warnings().is_empty());
+    }
+
+    /// Type check a program, returning the kinds of the warnings it produced
+    fn warnings_of(input: &str) -> Result<Vec<DiagnosticKind>, DiagnosticKind> {
+        let mut global_scope = GlobalScope::new();
+        type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program(input, "<test>").expect("parsing should succeed"),
+        )
+        .map_err(|diagnostic| diagnostic.kind.into_value())?;
+
+        Ok(global_scope
+            .take_warnings()
+            .into_iter()
+            .map(|warning| warning.kind.into_value())
+            .collect())
+    }
+
+    #[test]
+    fn noalias_is_only_allowed_on_pointer_parameters() {
+        assert_eq!(
+            warnings_of("fn f(noalias a: *i32, noalias b: *i32);"),
+            Ok(vec![])
+        );
+        assert_eq!(
+            warnings_of("fn f(noalias a: i32);"),
+            Err(DiagnosticKind::NoaliasOnNonPointer("i32".to_string()))
+        );
+        assert_eq!(
+            warnings_of("fn f(noalias noalias a: *i32);"),
+            Err(DiagnosticKind::DuplicateAttribute("noalias".to_string()))
+        );
+        assert_eq!(
+            warnings_of("fn f(inline a: *i32);"),
+            Err(DiagnosticKind::UnknownAttribute("inline".to_string()))
+        );
+    }
+
+    #[test]
+    fn passing_the_same_pointer_to_two_noalias_parameters_warns() {
+        let copy = "fn copy(noalias dst: *i32, noalias src: *i32);\n";
+
+        // TEST: the same variable, or the address of the same place, warns
+        assert_eq!(
+            warnings_of(&format!("{copy}fn f(p: *i32) {{ copy(p, p); }}")),
+            Ok(vec![DiagnosticKind::AliasedNoaliasArguments(
+                "p".to_string()
+            )])
+        );
+        assert_eq!(
+            warnings_of(&format!("{copy}fn f() {{ let x: i32 = 0; copy(&x, &x); }}")),
+            Ok(vec![DiagnosticKind::AliasedNoaliasArguments(
+                "&x".to_string()
+            )])
+        );
+
+        // TEST: distinct pointers, or pointers that cannot easily be compared,
+        // do not warn
+        assert_eq!(
+            warnings_of(&format!("{copy}fn f(p: *i32, q: *i32) {{ copy(p, q); }}")),
+            Ok(vec![])
+        );
+        assert_eq!(
+            warnings_of(&format!("{copy}fn f(p: *i32) {{ copy(p, p + 1); }}")),
+            Ok(vec![])
+        );
+
+        // TEST: only `noalias` parameters are checked
+        assert_eq!(
+            warnings_of("fn g(a: *i32, noalias b: *i32);\nfn f(p: *i32) { g(p, p); }"),
+            Ok(vec![])
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Specialization of functions with `comptime` parameters
+
+use zrc_parser::ast::{
+    expr::Expr,
+    stmt::{
+        ArgumentDeclaration, ArgumentDeclarationList, Declaration as AstDeclaration, LetDeclaration,
+    },
+    ty::Type,
+};
+use zrc_utils::span::{Span, Spannable, Spanned};
+
+use crate::typeck::{
+    expr::leaked_number_expr,
+    scope::{ComptimeFunction, ComptimeValue, Specialization},
+};
+
+/// Returns whether a parameter is marked `comptime`
+#[must_use]
+pub fn is_comptime_parameter(parameter: &ArgumentDeclaration<'_>) -> bool {
+    parameter
+        .attributes
+        .iter()
+        .any(|attribute| *attribute.value().name.value() == "comptime")
+}
+
+/// Returns whether any parameter in a list is marked `comptime`
+#[must_use]
+pub fn has_comptime_parameters(parameters: &ArgumentDeclarationList<'_>) -> bool {
+    let (ArgumentDeclarationList::NonVariadic(parameters)
+    | ArgumentDeclarationList::Variadic(parameters)) = parameters;
+
+    parameters
+        .iter()
+        .any(|parameter| is_comptime_parameter(parameter.value()))
+}
+
+/// Build the literal for a constant passed to a `comptime` parameter of type
+/// `ty`.
+///
+/// Integers are written with the type of the parameter, like `-(2i8)`, so the
+/// negation is not inferred as an `i32`.
+fn comptime_value_literal(value: ComptimeValue, ty: Type<'_>, span: Span) -> Expr<'_> {
+    match value {
+        ComptimeValue::Bool(value) => Expr::build_bool(value.in_span(span)),
+        ComptimeValue::Integer(value) => leaked_number_expr(value, Some(ty), span),
+    }
+}
+
+/// The constant each `comptime` parameter of `function` holds in
+/// `specialization`, declared like a `let` so the body can refer to it
+#[must_use]
+pub fn comptime_constants<'input>(
+    function: &ComptimeFunction<'input>,
+    specialization: &Specialization<'input>,
+) -> Vec<Spanned<LetDeclaration<'input>>> {
+    let (ArgumentDeclarationList::NonVariadic(parameters)
+    | ArgumentDeclarationList::Variadic(parameters)) = function.parameters.value();
+
+    parameters
+        .iter()
+        .filter(|parameter| is_comptime_parameter(parameter.value()))
+        .zip(&specialization.arguments)
+        .map(|(parameter, value)| {
+            let span = parameter.span();
+            let parameter = parameter.value().clone();
+            LetDeclaration {
+                name: parameter.name,
+                value: Some(comptime_value_literal(*value, parameter.ty.clone(), span)),
+                ty: Some(parameter.ty),
+                is_constant: true,
+            }
+            .in_span(span)
+        })
+        .collect()
+}
+
+/// Create the declaration of `specialization`, a copy of `function` named
+/// after the specialization without its `comptime` parameters. Its body
+/// declares them as the [constants](comptime_constants) they were passed
+/// when it is type checked.
+#[must_use]
+pub fn specialize_function<'input>(
+    function: &ComptimeFunction<'input>,
+    specialization: &Specialization<'input>,
+) -> AstDeclaration<'input> {
+    let (ArgumentDeclarationList::NonVariadic(parameters)
+    | ArgumentDeclarationList::Variadic(parameters)) = function.parameters.value();
+
+    let runtime_parameters = parameters
+        .iter()
+        .filter(|parameter| !is_comptime_parameter(parameter.value()))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    AstDeclaration::FunctionDeclaration {
+        attributes: function.attributes.clone(),
+        name: specialization.name.in_span(function.name.span()),
+        parameters: function
+            .parameters
+            .clone()
+            .map(|parameters| match parameters {
+                ArgumentDeclarationList::NonVariadic(_) => {
+                    ArgumentDeclarationList::NonVariadic(runtime_parameters)
+                }
+                ArgumentDeclarationList::Variadic(_) => {
+                    ArgumentDeclarationList::Variadic(runtime_parameters)
+                }
+            }),
+        return_type: function.return_type.clone(),
+        body: Some(function.body.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::DiagnosticKind;
+
+    use crate::{
+        tast::stmt::TypedDeclaration,
+        typeck::{GlobalScope, type_program},
+    };
+
+    /// Type check `source`, returning the name and body of every function it
+    /// generates
+    fn functions_in(source: &str) -> Result<Vec<(String, String)>, DiagnosticKind> {
+        Ok(type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(source, "<test>").expect("parsing should succeed"),
+        )
+        .map_err(|diagnostic| diagnostic.kind.into_value())?
+        .into_iter()
+        .filter_map(|declaration| match declaration.into_value() {
+            TypedDeclaration::FunctionDeclaration { name, body, .. } => Some((
+                (*name.value()).to_string(),
+                body.map(|body| format!("{:#}", body.value()))
+                    .unwrap_or_default(),
+            )),
+            TypedDeclaration::GlobalLetDeclaration(_) => None,
+        })
+        .collect())
+    }
+
+    #[test]
+    fn each_distinct_constant_produces_a_specialization() {
+        let functions = functions_in(
+            "fn shift(comptime n: u32, x: i32) -> i32 { return x + n as i32; }
+            fn main() -> i32 { return shift(1, 2) + shift(3, 4) + shift(1, 5); }",
+        )
+        .expect("typeck should succeed");
+
+        let names = functions
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["shift<1>", "shift<3>", "main"]);
+
+        let (_, first) = &functions[0];
+        assert!(first.contains("n: u32 = (1 as u32);"), "{first}");
+        let (_, main) = &functions[2];
+        assert!(main.contains("shift<1>((2 as i32))"), "{main}");
+        assert!(main.contains("shift<3>((4 as i32))"), "{main}");
+        assert!(main.contains("shift<1>((5 as i32))"), "{main}");
+    }
+
+    #[test]
+    fn comptime_parameters_accept_bools_and_negative_integers() {
+        let functions = functions_in(
+            "fn pick(comptime first: bool, comptime offset: i8) -> i8 {
+                if (first) { return offset; }
+                return 0i8;
+            }
+            fn main() -> i32 { pick(true, -2); pick(false, -2); return 0; }",
+        )
+        .expect("typeck should succeed");
+
+        let names = functions
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["pick<true, -2>", "pick<false, -2>", "main"]);
+    }
+
+    /// Type check `source`, returning the kind of every warning it produces
+    fn warnings_in(source: &str, warn_unused_parameters: bool) -> Vec<DiagnosticKind> {
+        let mut global_scope = GlobalScope {
+            warn_unused_parameters,
+            ..GlobalScope::new()
+        };
+        type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program(source, "<test>").expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+        global_scope
+            .take_warnings()
+            .into_iter()
+            .map(|warning| warning.kind.into_value())
+            .collect()
+    }
+
+    #[test]
+    fn comptime_parameters_are_warned_about_like_parameters() {
+        let source = "fn f(comptime n: u32) -> i32 { return 0; }
+            fn main() -> i32 { return f(1) + f(2); }";
+
+        // TEST: an unused `comptime` parameter is not an unused local
+        assert_eq!(warnings_in(source, false), vec![]);
+
+        // TEST: it is warned about once with the other parameters
+        assert_eq!(
+            warnings_in(source, true),
+            vec![DiagnosticKind::UnusedVariable("n".to_string())]
+        );
+    }
+
+    #[test]
+    fn specialized_bodies_are_warned_about_once() {
+        // TEST: the unused local and the deprecated call are in the body of
+        // both specializations, but each is only reported once
+        assert_eq!(
+            warnings_in(
+                "deprecated fn old();
+                fn f(comptime n: u32) -> u32 { let x = 1; old(); return n; }
+                fn main() -> i32 { f(1); f(2); return 0; }",
+                false
+            ),
+            vec![
+                DiagnosticKind::DeprecatedFunction("old".to_string()),
+                DiagnosticKind::UnusedVariable("x".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn comptime_arguments_must_be_constant() {
+        assert_eq!(
+            functions_in(
+                "fn shift(comptime n: u32, x: i32) -> i32 { return x; }
+                fn f(m: u32) -> i32 { return shift(m, 0); }"
+            ),
+            Err(DiagnosticKind::ComptimeArgumentNotConstant("n".to_string()))
+        );
+    }
+
+    #[test]
+    fn comptime_functions_can_only_be_called_directly() {
+        let not_called = Err(DiagnosticKind::ComptimeFunctionNotCalled(
+            "shift".to_string(),
+        ));
+        let shift = "fn shift(comptime n: u32, x: i32) -> i32 { return x; }\n";
+
+        // TEST: the unspecialized function is never emitted, so it cannot be
+        // pointed to
+        assert_eq!(
+            functions_in(&format!(
+                "{shift}fn f() -> i32 {{ let g = &shift; return g(1, 2); }}"
+            )),
+            not_called
+        );
+        assert_eq!(
+            functions_in(&format!("{shift}fn f() {{ let g = shift; }}")),
+            not_called
+        );
+
+        // TEST: a local of the same name is not the function
+        assert!(
+            functions_in(&format!(
+                "{shift}fn f() -> i32 {{ let shift = 1; return shift; }}"
+            ))
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn invalid_comptime_parameters_are_rejected() {
+        assert_eq!(
+            functions_in("fn f(comptime p: *u8) {}"),
+            Err(DiagnosticKind::InvalidComptimeParameterType(
+                "*u8".to_string()
+            ))
+        );
+        assert_eq!(
+            functions_in("fn f(comptime n: u32);"),
+            Err(DiagnosticKind::ComptimeParametersWithoutBody(
+                "f".to_string()
+            ))
+        );
+        assert_eq!(
+            functions_in("fn f(g: fn(comptime n: u32) -> i32) {}"),
+            Err(DiagnosticKind::ComptimeParameterInFunctionType)
+        );
+    }
+}
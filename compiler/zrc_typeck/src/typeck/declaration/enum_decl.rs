@@ -0,0 +1,335 @@
+//! Desugaring of named `enum` declarations
+
+use std::collections::HashMap;
+
+use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, diagnostic::GenericLabel};
+use zrc_parser::ast::{
+    stmt::{Declaration as AstDeclaration, EnumVariant},
+    ty::{KeyTypeMapping, Type, TypeKind},
+};
+use zrc_utils::span::{Span, Spannable, Spanned};
+
+use crate::typeck::scope::EnumEntry;
+
+/// The integer types a tag may be stored in and their ranges, from smallest
+/// to largest
+fn tag_types() -> [(&'static str, i128, i128); 8] {
+    [
+        ("u8", 0, u8::MAX.into()),
+        ("i8", i8::MIN.into(), i8::MAX.into()),
+        ("u16", 0, u16::MAX.into()),
+        ("i16", i16::MIN.into(), i16::MAX.into()),
+        ("u32", 0, u32::MAX.into()),
+        ("i32", i32::MIN.into(), i32::MAX.into()),
+        ("u64", 0, u64::MAX.into()),
+        ("i64", i64::MIN.into(), i64::MAX.into()),
+    ]
+}
+
+/// Compute the discriminant of every variant in declaration order, each
+/// variant without one taking the previous discriminant plus one. Every
+/// discriminant is returned along with the span it comes from.
+///
+/// # Errors
+/// Errors if two variants have the same discriminant or a discriminant does
+/// not fit in a 64-bit integer.
+fn compute_discriminants<'input>(
+    variants: &[Spanned<EnumVariant<'input>>],
+) -> Result<Vec<(&'input str, i128, Span)>, Diagnostic> {
+    let mut discriminants = Vec::with_capacity(variants.len());
+    let mut seen: HashMap<i128, &'input str> = HashMap::new();
+    let mut next = 0;
+    for variant in variants {
+        let EnumVariant {
+            name: variant_name,
+            discriminant: explicit,
+            ..
+        } = variant.value();
+        let (discriminant, span) = explicit.map_or((next, variant_name.span()), |explicit| {
+            (explicit.into_value(), explicit.span())
+        });
+
+        if let Some(other) = seen.insert(discriminant, variant_name.value()) {
+            return Err(DiagnosticKind::DuplicateEnumDiscriminant {
+                variant: (*variant_name.value()).to_string(),
+                other: other.to_string(),
+                discriminant,
+            }
+            .error_in(span)
+            .with_label(GenericLabel::error(
+                LabelKind::DuplicateEnumDiscriminant(discriminant).in_span(span),
+            )));
+        }
+
+        if !(i128::from(i64::MIN)..=i128::from(u64::MAX)).contains(&discriminant) {
+            return Err(out_of_range(discriminant, span));
+        }
+
+        discriminants.push((*variant_name.value(), discriminant, span));
+        next = discriminant + 1;
+    }
+
+    Ok(discriminants)
+}
+
+/// Create the error for a discriminant that does not fit in a 64-bit integer
+fn out_of_range(discriminant: i128, span: Span) -> Diagnostic {
+    DiagnosticKind::EnumDiscriminantOutOfRange(discriminant)
+        .error_in(span)
+        .with_label(GenericLabel::error(
+            LabelKind::EnumDiscriminantOutOfRange.in_span(span),
+        ))
+}
+
+/// Desugar an `enum E { ... }` declaration into a type alias.
+///
+/// Variants without a type hold `struct {}`. If no variant has an explicit
+/// discriminant, this is the same as `type E = enum { ... };`. Otherwise, the
+/// discriminants are [computed](compute_discriminants) and stored in
+/// `enum_entries`, and the tag is the smallest integer type that holds every
+/// discriminant. Such an enum also gets an `__enum__: struct { E: struct {} }`
+/// field, which takes no space but makes it a different type from any other
+/// enum with the same variants and tag, whose discriminants may differ.
+///
+/// # Errors
+/// Errors if two variants have the same discriminant or a discriminant does
+/// not fit in a 64-bit integer.
+pub fn desugar_enum_declaration<'input>(
+    enum_entries: &mut Vec<EnumEntry<'input>>,
+    span: Span,
+    name: Spanned<&'input str>,
+    variants: Spanned<Vec<Spanned<EnumVariant<'input>>>>,
+) -> Result<AstDeclaration<'input>, Diagnostic> {
+    let has_explicit_discriminants = variants
+        .value()
+        .iter()
+        .any(|variant| variant.value().discriminant.is_some());
+    let discriminants = compute_discriminants(variants.value())?;
+
+    let mapping = KeyTypeMapping(variants.map(|variants| {
+        variants
+            .into_iter()
+            .map(|variant| {
+                variant.map(|variant| {
+                    let ty = variant.ty.unwrap_or_else(|| {
+                        Type(
+                            TypeKind::Struct(KeyTypeMapping(
+                                Vec::new().in_span(variant.name.span()),
+                            ))
+                            .in_span(variant.name.span()),
+                        )
+                    });
+                    (variant.name, ty)
+                })
+            })
+            .collect()
+    }));
+
+    if !has_explicit_discriminants {
+        return Ok(AstDeclaration::TypeAliasDeclaration {
+            name,
+            ty: Type(TypeKind::Enum(mapping).in_span(span)),
+        });
+    }
+
+    let (min, max) = discriminants
+        .iter()
+        .fold((0, 0), |(min, max), (_, discriminant, _)| {
+            (min.min(*discriminant), max.max(*discriminant))
+        });
+    let Some((tag, _, _)) = tag_types()
+        .into_iter()
+        .find(|(_, lowest, highest)| *lowest <= min && max <= *highest)
+    else {
+        // each discriminant fits in `i64` or `u64`, but together they do not
+        let (_, _, span) = discriminants
+            .iter()
+            .find(|(_, discriminant, _)| *discriminant == max)
+            .expect("the largest discriminant should exist");
+        return Err(out_of_range(max, *span));
+    };
+
+    enum_entries.push(EnumEntry {
+        type_name: name,
+        discriminants: discriminants
+            .into_iter()
+            .map(|(variant, discriminant, _)| (variant, discriminant))
+            .collect(),
+    });
+
+    Ok(AstDeclaration::TypeAliasDeclaration {
+        name,
+        ty: Type(
+            TypeKind::Struct(KeyTypeMapping(
+                vec![
+                    (
+                        "__discriminant__".in_span(span),
+                        Type(TypeKind::Identifier(tag).in_span(span)),
+                    )
+                        .in_span(span),
+                    (
+                        "__value__".in_span(span),
+                        Type(TypeKind::Union(mapping).in_span(span)),
+                    )
+                        .in_span(span),
+                    (
+                        "__enum__".in_span(span),
+                        Type(
+                            TypeKind::Struct(KeyTypeMapping(
+                                vec![
+                                    (
+                                        name,
+                                        Type(
+                                            TypeKind::Struct(KeyTypeMapping(
+                                                Vec::new().in_span(span),
+                                            ))
+                                            .in_span(span),
+                                        ),
+                                    )
+                                        .in_span(span),
+                                ]
+                                .in_span(span),
+                            ))
+                            .in_span(span),
+                        ),
+                    )
+                        .in_span(span),
+                ]
+                .in_span(span),
+            ))
+            .in_span(span),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::DiagnosticKind;
+
+    use crate::typeck::{GlobalScope, type_program};
+
+    /// Type check `source` and return the discriminants of the enum `E`
+    fn discriminants_of_e(source: &str) -> Result<Vec<(&str, i128)>, DiagnosticKind> {
+        let mut global_scope = GlobalScope::new();
+        type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program(source, "<test>").expect("parsing should succeed"),
+        )
+        .map_err(|diagnostic| diagnostic.kind.into_value())?;
+
+        let ty = global_scope
+            .types
+            .resolve("E")
+            .expect("`E` should be declared")
+            .clone();
+        Ok(global_scope
+            .create_subscope()
+            .enum_discriminants(&ty)
+            .expect("`E` should be an enum"))
+    }
+
+    #[test]
+    fn explicit_discriminants_are_used() {
+        assert_eq!(
+            discriminants_of_e("enum E { A = 1, B: i32 = 5, C = -3 }"),
+            Ok(vec![("A", 1), ("B", 5), ("C", -3)])
+        );
+    }
+
+    #[test]
+    fn discriminants_auto_increment_from_the_previous_variant() {
+        assert_eq!(
+            discriminants_of_e("enum E { A = 1, B = 5, C }"),
+            Ok(vec![("A", 1), ("B", 5), ("C", 6)])
+        );
+        assert_eq!(
+            discriminants_of_e("enum E { A, B = 10, C, D }"),
+            Ok(vec![("A", 0), ("B", 10), ("C", 11), ("D", 12)])
+        );
+    }
+
+    #[test]
+    fn enums_without_explicit_discriminants_are_tagged_alphabetically() {
+        assert_eq!(
+            discriminants_of_e("enum E { B: i32, A: u8 }"),
+            Ok(vec![("A", 0), ("B", 1)])
+        );
+    }
+
+    #[test]
+    fn duplicate_discriminants_are_rejected() {
+        assert_eq!(
+            discriminants_of_e("enum E { A = 1, B = 0, C }"),
+            Err(DiagnosticKind::DuplicateEnumDiscriminant {
+                variant: "C".to_string(),
+                other: "A".to_string(),
+                discriminant: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn the_tag_is_the_smallest_integer_that_fits() {
+        let tag_of = |source: &str| {
+            let mut global_scope = GlobalScope::new();
+            type_program(
+                &mut global_scope,
+                zrc_parser::parser::parse_program(source, "<test>")
+                    .expect("parsing should succeed"),
+            )
+            .expect("typeck should succeed");
+            global_scope
+                .types
+                .resolve("E")
+                .and_then(|ty| ty.enum_tag_type())
+                .map(ToString::to_string)
+        };
+
+        assert_eq!(tag_of("enum E { A = 1, B = 255 }"), Some("u8".to_string()));
+        assert_eq!(tag_of("enum E { A = -1, B }"), Some("i8".to_string()));
+        assert_eq!(tag_of("enum E { A = 256 }"), Some("u16".to_string()));
+        assert_eq!(
+            tag_of("enum E { A: i32, B: u8 }"),
+            Some("usize".to_string())
+        );
+    }
+
+    #[test]
+    fn enums_of_the_same_shape_keep_their_own_discriminants() {
+        let source = "enum E { A = 1, B = 5 }\nenum F { A = 7, B = 9 }\n";
+        let mut global_scope = GlobalScope::new();
+        type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program(source, "<test>").expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        let e = global_scope
+            .types
+            .resolve("E")
+            .expect("`E` should be declared");
+        let f = global_scope
+            .types
+            .resolve("F")
+            .expect("`F` should be declared");
+        assert_ne!(e, f);
+
+        let scope = global_scope.create_subscope();
+        assert_eq!(scope.enum_discriminants(e), Some(vec![("A", 1), ("B", 5)]));
+        assert_eq!(scope.enum_discriminants(f), Some(vec![("A", 7), ("B", 9)]));
+
+        // TEST: a value of one enum is not a value of the other
+        assert!(matches!(
+            type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(
+                    "enum E { A = 1, B = 5 }\nenum F { A = 7, B = 9 }\nfn f() { let f: F = E::A; }",
+                    "<test>"
+                )
+                .expect("parsing should succeed"),
+            )
+            .map_err(|diagnostic| diagnostic.kind.into_value()),
+            Err(DiagnosticKind::InvalidAssignmentRightHandSideType { .. })
+        ));
+    }
+}
@@ -1,27 +1,117 @@
 //! Process function declarations
 
+use std::rc::Rc;
+
 use zrc_diagnostics::{
-    Diagnostic, DiagnosticKind, LabelKind, SpannedExt, diagnostic::GenericLabel,
+    Diagnostic, DiagnosticKind, HelpKind, LabelKind, SpannedExt, diagnostic::GenericLabel,
 };
 use zrc_parser::ast::{
-    stmt::{ArgumentDeclarationList, Stmt},
+    stmt::{ArgumentDeclarationList, Attribute, Stmt},
     ty::Type,
 };
 use zrc_utils::span::{Spannable, Spanned};
 
 use super::{
-    super::{block::BlockReturnAbility, resolve_type, scope::GlobalScope},
+    super::{
+        block::{
+            BlockReturnAbility, BlockReturnActuality, BreakContinueAbility, InferredReturns,
+            warn_if_unused,
+        },
+        resolve_type,
+        scope::{GlobalScope, Scope},
+    },
+    attributes::{resolve_function_attributes, resolve_parameter_attributes},
+    comptime::{comptime_constants, has_comptime_parameters},
+    let_decl::process_let_declaration,
     type_block,
 };
 use crate::{
     tast::{
         self,
-        stmt::{ArgumentDeclaration as TastArgumentDeclaration, TypedDeclaration},
+        stmt::{
+            ArgumentDeclaration as TastArgumentDeclaration, LetDeclaration as TastLetDeclaration,
+            TypedDeclaration, TypedStmt, TypedStmtKind,
+        },
         ty::{Fn, FunctionDeclarationGlobalMetadata, Type as TastType},
     },
-    typeck::scope::ValueEntry,
+    typeck::scope::{ComptimeFunction, ValueEntry},
 };
 
+/// Resolve the types and attributes of the parameters of a function
+fn resolve_parameters<'input>(
+    global_scope: &GlobalScope<'input>,
+    parameters: &Spanned<ArgumentDeclarationList<'input>>,
+) -> Result<Vec<TastArgumentDeclaration<'input>>, Diagnostic> {
+    let (ArgumentDeclarationList::NonVariadic(inner_params)
+    | ArgumentDeclarationList::Variadic(inner_params)) = parameters.value();
+
+    inner_params
+        .iter()
+        .map(|parameter| -> Result<TastArgumentDeclaration, Diagnostic> {
+            let ty = resolve_type(
+                &global_scope.create_subscope(),
+                parameter.value().ty.clone(),
+            )?
+            .in_span(parameter.span());
+            Ok(TastArgumentDeclaration {
+                noalias: resolve_parameter_attributes(&parameter.value().attributes, &ty)?.noalias,
+                name: parameter.value().name,
+                ty,
+            })
+        })
+        .collect()
+}
+
+/// Create the scope of a function body, containing its parameters
+fn create_function_scope<'input>(
+    global_scope: &GlobalScope<'input>,
+    parameters: Vec<TastArgumentDeclaration<'input>>,
+) -> Scope<'input> {
+    let mut function_scope = global_scope.create_subscope();
+    for param in parameters {
+        function_scope.values.insert(
+            param.name.value(),
+            ValueEntry {
+                is_local: true,
+                ..ValueEntry::unused(param.ty.into_value(), param.name.span())
+            },
+        );
+    }
+    function_scope
+}
+
+/// Declare the `comptime` parameters of `name` in `function_scope` if it is a
+/// specialization, as constants holding the values it was specialized for,
+/// returning their declarations. Like the other parameters, they belong to the
+/// function rather than its body, so they are only warned about when unused
+/// with [`GlobalScope::warn_unused_parameters`].
+fn declare_comptime_parameters<'input>(
+    global_scope: &GlobalScope<'input>,
+    function_scope: &mut Scope<'input>,
+    name: &str,
+) -> Result<Vec<Spanned<TastLetDeclaration<'input>>>, Diagnostic> {
+    let Some(specialization) = global_scope
+        .specializations
+        .borrow()
+        .iter()
+        .find(|specialization| specialization.name == name)
+        .cloned()
+    else {
+        return Ok(Vec::new());
+    };
+    let function = global_scope
+        .comptime_functions
+        .iter()
+        .find(|function| *function.name.value() == specialization.function)
+        .expect("specializations should only be made of comptime functions");
+
+    process_let_declaration(
+        function_scope,
+        comptime_constants(function, &specialization),
+        false,
+    )
+}
+
 /// Register the function signature and global entries into `global_scope`.
 /// This does not typecheck the function body; it only inserts the function
 /// into the global value and declaration tables so other declarations can
@@ -33,29 +123,36 @@ pub fn register_function_declaration<'input>(
     parameters: Spanned<ArgumentDeclarationList<'input>>,
     return_type: Option<Type<'input>>,
     body: Option<Spanned<Vec<Stmt<'input>>>>,
+    attributes: &[Spanned<Attribute<'input>>],
 ) -> Result<(), Diagnostic> {
+    let mut deprecated = resolve_function_attributes(attributes)?.deprecated;
+
     let resolved_return_type = return_type
         .clone()
         .map(|ty| resolve_type(&global_scope.create_subscope(), ty))
         .transpose()?
         .unwrap_or_else(TastType::unit);
 
-    let (ArgumentDeclarationList::NonVariadic(inner_params)
-    | ArgumentDeclarationList::Variadic(inner_params)) = parameters.value();
+    let resolved_parameters = resolve_parameters(global_scope, &parameters)?;
 
-    let resolved_parameters = inner_params
-        .iter()
-        .map(|parameter| -> Result<TastArgumentDeclaration, Diagnostic> {
-            Ok(TastArgumentDeclaration {
-                name: parameter.value().name,
-                ty: resolve_type(
-                    &global_scope.create_subscope(),
-                    parameter.value().ty.clone(),
-                )?
-                .in_span(parameter.span()),
-            })
-        })
-        .collect::<Result<Vec<_>, Diagnostic>>()?;
+    if has_comptime_parameters(parameters.value()) {
+        let Some(body) = body.clone() else {
+            return Err(
+                DiagnosticKind::ComptimeParametersWithoutBody(name.value().to_string())
+                    .error_in(name.span())
+                    .with_label(GenericLabel::error(
+                        LabelKind::ComptimeParametersWithoutBody.in_span(name.span()),
+                    )),
+            );
+        };
+        global_scope.comptime_functions.push(ComptimeFunction {
+            attributes: attributes.to_vec(),
+            name,
+            parameters: parameters.clone(),
+            return_type: return_type.clone(),
+            body,
+        });
+    }
 
     let fn_type = Fn {
         arguments: match parameters.value() {
@@ -95,6 +192,11 @@ pub fn register_function_declaration<'input>(
                         )));
                 }
 
+                // A deprecation on any declaration applies to the function
+                if deprecated.is_none() {
+                    deprecated.clone_from(&ty.deprecated);
+                }
+
                 if body.is_some() && canonical.has_implementation {
                     return Err(name
                         .error(|name| DiagnosticKind::ConflictingImplementations(name.to_string()))
@@ -117,9 +219,20 @@ pub fn register_function_declaration<'input>(
             false
         };
 
+    // a prototype does not give a return type to a function defined without one
+    let awaiting_return_type = (return_type.is_none() && body.is_some())
+        || global_scope
+            .global_values
+            .resolve(name.value())
+            .is_some_and(|entry| entry.borrow().awaiting_return_type);
+
     global_scope.global_values.insert(
         name.into_value(),
-        ValueEntry::unused(TastType::Fn(fn_type.clone()), name.span()),
+        ValueEntry {
+            deprecated,
+            awaiting_return_type,
+            ..ValueEntry::unused(TastType::Fn(fn_type.clone()), name.span())
+        },
     );
 
     global_scope.declarations.insert(
@@ -187,41 +300,146 @@ pub fn register_function_declaration<'input>(
     Ok(())
 }
 
+/// Infer the return type of a function declared without one from the `return`
+/// statements in its body, and update its signature in `global_scope`.
+///
+/// Every `return` must return the same type, up to implicit casts, and an
+/// `{int}` is returned as an `i32`. A body which only uses `return;`, or no
+/// `return` at all, returns `()`.
+///
+/// Until then, any use of the function is an error, so a function is inferred
+/// only after those it calls, and a recursive function must declare its
+/// return type.
+///
+/// # Errors
+/// Errors if the body does not type check or two `return`s conflict, and with
+/// [`DiagnosticKind::ReturnTypeNotInferred`] if the body uses a function whose
+/// return type is not inferred yet.
+pub fn infer_function_return_type<'input>(
+    global_scope: &mut GlobalScope<'input>,
+    name: Spanned<&'input str>,
+    parameters: &Spanned<ArgumentDeclarationList<'input>>,
+    body: &Spanned<Vec<Stmt<'input>>>,
+) -> Result<(), Diagnostic> {
+    let returns = InferredReturns::default();
+
+    let mut function_scope =
+        create_function_scope(global_scope, resolve_parameters(global_scope, parameters)?);
+    // the body is checked again once its signature is known, which is when its
+    // warnings are reported
+    function_scope.warnings = Rc::default();
+    declare_comptime_parameters(global_scope, &mut function_scope, name.value())?;
+    type_block(
+        &function_scope,
+        body.clone(),
+        BreakContinueAbility::Neither,
+        BlockReturnAbility::Infer(Rc::clone(&returns)),
+    )?;
+
+    let mut inferred: Option<Spanned<TastType<'input>>> = None;
+    for returned in returns.take() {
+        inferred = Some(match inferred {
+            None => returned,
+            Some(current)
+                if returned.value() == current.value()
+                    || returned.value().can_implicitly_cast_to(current.value()) =>
+            {
+                current
+            }
+            Some(current) if current.value().can_implicitly_cast_to(returned.value()) => returned,
+            Some(current) => {
+                return Err(DiagnosticKind::ConflictingReturnTypes(
+                    current.value().to_string(),
+                    returned.value().to_string(),
+                )
+                .error_in(returned.span())
+                .with_label(GenericLabel::error(
+                    LabelKind::ReturnsType(returned.value().to_string()).in_span(returned.span()),
+                ))
+                .with_label(GenericLabel::note(
+                    LabelKind::ReturnsType(current.value().to_string()).in_span(current.span()),
+                ))
+                .with_help(HelpKind::DeclareReturnType));
+            }
+        });
+    }
+
+    let inferred = match inferred.map(Spanned::into_value) {
+        Some(TastType::Int) => TastType::I32,
+        Some(ty) => ty,
+        None => TastType::unit(),
+    };
+
+    let declaration = global_scope
+        .declarations
+        .get_mut(name.value())
+        .expect("function should be registered before its return type is inferred");
+    *declaration.fn_type.returns = inferred;
+
+    let entry = global_scope
+        .global_values
+        .resolve(name.value())
+        .expect("function should be registered before its return type is inferred");
+    let mut entry = entry.borrow_mut();
+    entry.ty = TastType::Fn(declaration.fn_type.clone());
+    entry.awaiting_return_type = false;
+
+    Ok(())
+}
+
 /// Finalize the function declaration using only immutable access to the
 /// `GlobalScope`. This constructs the `TypedDeclaration` and typechecks the
 /// body (if any) using a subscope derived from `global_scope`.
+///
+/// Functions with `comptime` parameters produce no declaration, as only their
+/// specializations are generated.
 #[expect(clippy::needless_pass_by_value)]
 pub fn finalize_function_declaration<'input>(
     global_scope: &GlobalScope<'input>,
+    attributes: &[Spanned<Attribute<'input>>],
     name: Spanned<&'input str>,
     parameters: Spanned<ArgumentDeclarationList<'input>>,
     return_type: Option<Type<'input>>,
     body: Option<Spanned<Vec<Stmt<'input>>>>,
 ) -> Result<Option<TypedDeclaration<'input>>, Diagnostic> {
-    let resolved_return_type = return_type
-        .clone()
-        .map(|ty| resolve_type(&global_scope.create_subscope(), ty))
-        .transpose()?
-        .unwrap_or_else(TastType::unit);
+    if has_comptime_parameters(parameters.value()) {
+        return Ok(None);
+    }
+
+    // without a declared return type, use the one registered or inferred
+    let resolved_return_type = match return_type.clone() {
+        Some(ty) => resolve_type(&global_scope.create_subscope(), ty)?,
+        None => global_scope
+            .declarations
+            .get(name.value())
+            .map_or_else(TastType::unit, |declaration| {
+                (*declaration.fn_type.returns).clone()
+            }),
+    };
 
     let (ArgumentDeclarationList::NonVariadic(inner_params)
     | ArgumentDeclarationList::Variadic(inner_params)) = parameters.value();
 
-    let resolved_parameters = inner_params
-        .iter()
-        .map(|parameter| -> Result<TastArgumentDeclaration, Diagnostic> {
-            Ok(TastArgumentDeclaration {
-                name: parameter.value().name,
-                ty: resolve_type(
-                    &global_scope.create_subscope(),
-                    parameter.value().ty.clone(),
-                )?
-                .in_span(parameter.span()),
-            })
-        })
-        .collect::<Result<Vec<_>, Diagnostic>>()?;
+    let resolved_parameters = resolve_parameters(global_scope, &parameters)?;
+
+    let attributes = resolve_function_attributes(attributes)?;
+
+    if attributes.constructor.is_some()
+        && (!inner_params.is_empty()
+            || matches!(parameters.value(), ArgumentDeclarationList::Variadic(_))
+            || resolved_return_type != TastType::unit())
+    {
+        return Err(
+            DiagnosticKind::InvalidConstructorSignature(name.value().to_string())
+                .error_in(name.span())
+                .with_label(GenericLabel::error(
+                    LabelKind::InvalidConstructorSignature.in_span(name.span()),
+                )),
+        );
+    }
 
     Ok(Some(TypedDeclaration::FunctionDeclaration {
+        attributes,
         name,
         parameters: match parameters.value() {
             ArgumentDeclarationList::NonVariadic(_) => {
@@ -238,20 +456,49 @@ pub fn finalize_function_declaration<'input>(
                 .map_or_else(|| name.span(), |ty| ty.0.span()),
         ),
         body: if let Some(body) = body {
-            let mut function_scope = global_scope.create_subscope();
-            for param in resolved_parameters {
-                function_scope.values.insert(
-                    param.name.value(),
-                    ValueEntry::unused(param.ty.into_value(), param.name.span()),
-                );
-            }
-
-            Some(body.span().containing(type_block(
+            let body_span = body.span();
+            let mut function_scope = create_function_scope(global_scope, resolved_parameters);
+            let constants =
+                declare_comptime_parameters(global_scope, &mut function_scope, name.value())?;
+            let mut body = type_block(
                 &function_scope,
                 body,
-                false,
+                BreakContinueAbility::Neither,
                 BlockReturnAbility::MustReturn(resolved_return_type),
-            )?))
+            )?;
+
+            if global_scope.warn_unused_parameters {
+                warn_if_unused(
+                    &function_scope,
+                    &inner_params
+                        .iter()
+                        .map(|parameter| parameter.value().name.value())
+                        .chain(
+                            constants
+                                .iter()
+                                .map(|constant| constant.value().name.value()),
+                        )
+                        .filter_map(|name| {
+                            function_scope
+                                .values
+                                .resolve(name)
+                                .map(|entry| (*name, entry))
+                        })
+                        .collect::<Vec<_>>(),
+                );
+            }
+
+            if !constants.is_empty() {
+                body.stmts.insert(
+                    0,
+                    TypedStmt {
+                        kind: TypedStmtKind::DeclarationList(constants).in_span(body_span),
+                        return_actuality: BlockReturnActuality::NeverReturns,
+                    },
+                );
+            }
+
+            Some(body_span.containing(body))
         } else {
             None
         },
@@ -260,7 +507,7 @@ pub fn finalize_function_declaration<'input>(
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{collections::HashMap, rc::Rc};
 
     use tast::stmt::ArgumentDeclarationList as TastArgumentDeclarationList;
     use zrc_parser::ast::{
@@ -274,13 +521,25 @@ mod tests {
     use zrc_utils::spanned_test;
 
     use super::*;
-    use crate::typeck::scope::{TypeCtx, ValueCtx};
+    use crate::typeck::{
+        CfgOptions, DisabledBuiltins,
+        scope::{TypeCtx, ValueCtx},
+    };
 
     #[test]
     fn re_declaration_works_as_expected() {
         assert!(
             super::super::process_declaration(
                 &mut GlobalScope {
+                    warnings: Rc::default(),
+                    cfg: CfgOptions::host(),
+                    methods: Vec::new(),
+                    enums: Vec::new(),
+                    comptime_functions: Vec::new(),
+                    specializations: Rc::default(),
+                    overflow_checks: false,
+                    disabled_builtins: DisabledBuiltins::None,
+                    warn_unused_parameters: false,
                     global_values: ValueCtx::from_unused([(
                         "get_true",
                         TastType::Fn(Fn {
@@ -301,6 +560,7 @@ mod tests {
                     )])
                 },
                 AstDeclaration::FunctionDeclaration {
+                    attributes: vec![],
                     name: spanned_test!(0, "get_true", 0),
                     parameters: spanned_test!(
                         0,
@@ -333,15 +593,26 @@ mod tests {
 
         // First declaration at span 0..10
         let mut scope = GlobalScope {
+            warnings: Rc::default(),
+            cfg: CfgOptions::host(),
+            methods: Vec::new(),
+            enums: Vec::new(),
+            comptime_functions: Vec::new(),
+            specializations: Rc::default(),
+            overflow_checks: false,
+            disabled_builtins: DisabledBuiltins::None,
+            warn_unused_parameters: false,
             global_values: ValueCtx::from_unused([(
                 "read",
                 TastType::Fn(Fn {
                     arguments: TastArgumentDeclarationList::NonVariadic(vec![
                         TastArgumentDeclaration {
+                            noalias: false,
                             name: spanned_test!(5, "buffer", 11),
                             ty: spanned_test!(13, TastType::Ptr(Box::new(TastType::U8)), 16),
                         },
                         TastArgumentDeclaration {
+                            noalias: false,
                             name: spanned_test!(18, "start", 23),
                             ty: spanned_test!(25, TastType::Usize, 30),
                         },
@@ -356,10 +627,12 @@ mod tests {
                     fn_type: Fn {
                         arguments: TastArgumentDeclarationList::NonVariadic(vec![
                             TastArgumentDeclaration {
+                                noalias: false,
                                 name: spanned_test!(5, "buffer", 11),
                                 ty: spanned_test!(13, TastType::Ptr(Box::new(TastType::U8)), 16),
                             },
                             TastArgumentDeclaration {
+                                noalias: false,
                                 name: spanned_test!(18, "start", 23),
                                 ty: spanned_test!(25, TastType::Usize, 30),
                             },
@@ -375,6 +648,7 @@ mod tests {
         let result = super::super::process_declaration(
             &mut scope,
             AstDeclaration::FunctionDeclaration {
+                attributes: vec![],
                 name: spanned_test!(53, "read", 57),
                 parameters: spanned_test!(
                     58,
@@ -382,6 +656,7 @@ mod tests {
                         spanned_test!(
                             59,
                             zrc_parser::ast::stmt::ArgumentDeclaration {
+                                attributes: vec![],
                                 name: spanned_test!(60, "buffer", 66),
                                 ty: Type(spanned_test!(
                                     68,
@@ -398,6 +673,7 @@ mod tests {
                         spanned_test!(
                             74,
                             zrc_parser::ast::stmt::ArgumentDeclaration {
+                                attributes: vec![],
                                 name: spanned_test!(75, "start", 80),
                                 ty: Type(spanned_test!(82, TypeKind::Identifier("usize"), 87)),
                             },
@@ -414,4 +690,133 @@ mod tests {
         // Should succeed because the types are the same, even though the spans differ
         assert!(result.is_ok());
     }
+
+    /// Type check `source` as a program, returning the return type of every
+    /// function in order
+    fn return_types(source: &str) -> Result<Vec<String>, DiagnosticKind> {
+        crate::typeck::type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(source, "<test>").expect("parsing should succeed"),
+        )
+        .map(|program| {
+            program
+                .into_iter()
+                .filter_map(|declaration| match declaration.into_value() {
+                    TypedDeclaration::FunctionDeclaration { return_type, .. } => {
+                        Some(return_type.into_value().to_string())
+                    }
+                    TypedDeclaration::GlobalLetDeclaration(_) => None,
+                })
+                .collect()
+        })
+        .map_err(|diagnostic| diagnostic.kind.into_value())
+    }
+
+    #[test]
+    fn omitted_return_types_are_inferred_from_returns() {
+        // TEST: `{int}` unifies with `i32`, and callers see the inferred type
+        assert_eq!(
+            return_types(
+                "fn next(x: i32) { if (x > 99) return 0; return x + 1; }
+                fn use_next() -> i32 { return next(1) * 2; }"
+            ),
+            Ok(vec!["i32".to_string(), "i32".to_string()])
+        );
+
+        // TEST: a lone `{int}` becomes `i32`, and other types are kept
+        assert_eq!(
+            return_types("fn one() { return 1; } fn small() { return 1u8; }"),
+            Ok(vec!["i32".to_string(), "u8".to_string()])
+        );
+
+        // TEST: without any `return x;`, the function still returns `()`
+        assert_eq!(
+            return_types("fn nothing() {} fn early(b: bool) { if (b) return; }"),
+            Ok(vec!["struct {}".to_string(), "struct {}".to_string()])
+        );
+    }
+
+    #[test]
+    fn return_types_are_inferred_after_those_of_callees() {
+        // TEST: a function declared later is inferred first
+        assert_eq!(
+            return_types("fn a() { return b(); } fn b() { return 1; }"),
+            Ok(vec!["i32".to_string(), "i32".to_string()])
+        );
+        assert_eq!(
+            return_types(
+                "fn a() { return b() + 1u8; }
+                fn b() { return c(); }
+                fn c() { return 2u8; }"
+            ),
+            Ok(vec!["u8".to_string(), "u8".to_string(), "u8".to_string()])
+        );
+
+        // TEST: a recursive function cannot be inferred, directly or not
+        assert_eq!(
+            return_types("fn f(n: i32) { if (n == 0) return 1; return n * f(n - 1); }"),
+            Err(DiagnosticKind::ReturnTypeNotInferred("f".to_string()))
+        );
+        assert_eq!(
+            return_types("fn a() { return b(); } fn b() { return a(); }"),
+            Err(DiagnosticKind::ReturnTypeNotInferred("b".to_string()))
+        );
+
+        // TEST: declaring the return type breaks the cycle
+        assert_eq!(
+            return_types(
+                "fn f(n: i32) -> i32 { if (n == 0) return 1; return n * f(n - 1); }
+                fn g() { return f(3); }"
+            ),
+            Ok(vec!["i32".to_string(), "i32".to_string()])
+        );
+    }
+
+    #[test]
+    fn conflicting_returns_are_an_error() {
+        assert_eq!(
+            return_types("fn f(b: bool) { if (b) return 1; return true; }"),
+            Err(DiagnosticKind::ConflictingReturnTypes(
+                "{int}".to_string(),
+                "bool".to_string()
+            ))
+        );
+        assert_eq!(
+            return_types("fn f(b: bool) { if (b) return; return 4u8; }"),
+            Err(DiagnosticKind::ConflictingReturnTypes(
+                "struct {}".to_string(),
+                "u8".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn unused_parameters_warn_only_when_enabled() {
+        let warnings = |warn_unused_parameters: bool| {
+            let mut global_scope = GlobalScope {
+                warn_unused_parameters,
+                ..GlobalScope::new()
+            };
+            crate::typeck::type_program(
+                &mut global_scope,
+                zrc_parser::parser::parse_program(
+                    "fn f(a: i32, b: i32, _c: i32) -> i32 { return b; }",
+                    "<test>",
+                )
+                .expect("parsing should succeed"),
+            )
+            .expect("typeck should succeed");
+            global_scope
+                .take_warnings()
+                .into_iter()
+                .map(|warning| warning.kind.into_value())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(warnings(false), vec![]);
+        assert_eq!(
+            warnings(true),
+            vec![DiagnosticKind::UnusedVariable("a".to_string())]
+        );
+    }
 }
@@ -0,0 +1,266 @@
+//! Desugar `impl` blocks into plain functions
+//!
+//! A method `fn area(self) -> i32` in `impl Rect` becomes the function
+//! `Rect::area(self: Rect) -> i32`, and `*self` becomes `self: *Rect`. Calls
+//! like `r.area()` are then resolved to that function by the call type checker
+//! using the [`MethodEntry`] recorded here.
+
+use zrc_diagnostics::{Diagnostic, DiagnosticKind, HelpKind, LabelKind, diagnostic::GenericLabel};
+use zrc_parser::ast::{
+    stmt::{ArgumentDeclaration, ArgumentDeclarationList, Declaration as AstDeclaration, Method},
+    ty::{Type, TypeKind},
+};
+use zrc_utils::span::{Spannable, Spanned};
+
+use crate::{
+    tast::ty::Type as TastType,
+    typeck::scope::{GlobalScope, MethodEntry},
+};
+
+/// Desugar the methods of an `impl` block into function declarations,
+/// recording each in `method_entries` so calls to it can be resolved
+///
+/// # Errors
+/// Errors if a method's first parameter is not named `self`.
+pub fn desugar_impl_declaration<'input>(
+    method_entries: &mut Vec<MethodEntry<'input>>,
+    ty: Spanned<&'input str>,
+    methods: Vec<Spanned<Method<'input>>>,
+) -> Result<Vec<Spanned<AstDeclaration<'input>>>, Diagnostic> {
+    methods
+        .into_iter()
+        .map(|method| {
+            let method_span = method.span();
+            let method = method.into_value();
+
+            let receiver = method.receiver.value();
+            if *receiver.name.value() != "self" {
+                let span = receiver.name.span();
+                return Err(DiagnosticKind::InvalidMethodReceiver(
+                    (*receiver.name.value()).to_string(),
+                )
+                .error_in(span)
+                .with_label(GenericLabel::error(
+                    LabelKind::InvalidMethodReceiver.in_span(span),
+                )));
+            }
+
+            // Spans and names must outlive the input, so leak the combined name
+            let function: &'input str =
+                Box::leak(format!("{}::{}", ty.value(), method.name.value()).into_boxed_str());
+
+            method_entries.push(MethodEntry {
+                type_name: ty,
+                name: method.name.value(),
+                function,
+                by_pointer: receiver.by_pointer,
+            });
+
+            let self_ty = Type(ty.map(TypeKind::Identifier));
+            let self_parameter = ArgumentDeclaration {
+                attributes: vec![],
+                name: receiver.name,
+                ty: if receiver.by_pointer {
+                    Type(TypeKind::Ptr(Box::new(self_ty)).in_span(method.receiver.span()))
+                } else {
+                    self_ty
+                },
+            }
+            .in_span(method.receiver.span());
+
+            let parameters_span = method.parameters.span();
+            let parameters = match method.parameters.into_value() {
+                ArgumentDeclarationList::NonVariadic(rest) => ArgumentDeclarationList::NonVariadic(
+                    std::iter::once(self_parameter).chain(rest).collect(),
+                ),
+                ArgumentDeclarationList::Variadic(rest) => ArgumentDeclarationList::Variadic(
+                    std::iter::once(self_parameter).chain(rest).collect(),
+                ),
+            };
+
+            Ok(AstDeclaration::FunctionDeclaration {
+                attributes: method.attributes,
+                name: function.in_span(method.name.span()),
+                parameters: parameters.in_span(parameters_span),
+                return_type: method.return_type,
+                body: Some(method.body),
+            }
+            .in_span(method_span))
+        })
+        .collect()
+}
+
+/// Check that every `impl` block is written for a struct, once all types have
+/// been registered
+///
+/// Method calls are resolved by the type of the receiver, and structs are
+/// compared by their fields, so two structs with the same fields cannot both
+/// have methods: `b.m()` could not tell `B::m` from `A::m`.
+///
+/// # Errors
+/// Errors if a method is defined on a type that is not a struct, or on a struct
+/// with the same fields as another struct with methods.
+pub fn check_method_types(global_scope: &GlobalScope<'_>) -> Result<(), Diagnostic> {
+    for (index, method) in global_scope.methods.iter().enumerate() {
+        // unknown types have already been reported by the parameter of the
+        // desugared function
+        let Some(ty) = global_scope.types.resolve(method.type_name.value()) else {
+            continue;
+        };
+
+        let span = method.type_name.span();
+        if !matches!(ty, TastType::Struct(_)) {
+            return Err(DiagnosticKind::MethodsOnNonStruct(
+                (*method.type_name.value()).to_string(),
+            )
+            .error_in(span)
+            .with_label(GenericLabel::error(
+                LabelKind::MethodsOnNonStruct.in_span(span),
+            )));
+        }
+
+        if let Some(other) = global_scope.methods[..index].iter().find(|other| {
+            other.type_name.value() != method.type_name.value()
+                && global_scope.types.resolve(other.type_name.value()) == Some(ty)
+        }) {
+            return Err(DiagnosticKind::MethodsOnSameShapedTypes(
+                (*method.type_name.value()).to_string(),
+                (*other.type_name.value()).to_string(),
+            )
+            .error_in(span)
+            .with_label(GenericLabel::note(
+                LabelKind::MethodsOnSameShapedTypes((*other.type_name.value()).to_string())
+                    .in_span(other.type_name.span()),
+            ))
+            .with_help(HelpKind::GiveTypesDistinctFieldsForMethods));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::DiagnosticKind;
+    use zrc_parser::parser::parse_program;
+
+    use crate::{
+        tast::stmt::TypedDeclaration,
+        typeck::{GlobalScope, type_program},
+    };
+
+    /// The struct the methods in these tests are defined on
+    const RECT: &str = "struct Rect { w: i32, h: i32 }
+        impl Rect {
+            fn area(self) -> i32 { return self.w * self.h; }
+            fn grow(*self, by: i32) { self->w += by; }
+        }
+";
+
+    /// Type check [`RECT`] followed by `code`, returning the names of the
+    /// functions in the program
+    fn type_check(code: &str) -> Result<Vec<String>, DiagnosticKind> {
+        let source = format!("{RECT}{code}");
+        let program = type_program(
+            &mut GlobalScope::new(),
+            parse_program(&source, "<test>").expect("parsing should succeed"),
+        )
+        .map_err(|diagnostic| diagnostic.kind.into_value())?;
+
+        Ok(program
+            .into_iter()
+            .filter_map(|declaration| match declaration.into_value() {
+                TypedDeclaration::FunctionDeclaration { name, .. } => {
+                    Some(name.into_value().to_string())
+                }
+                TypedDeclaration::GlobalLetDeclaration(_) => None,
+            })
+            .collect())
+    }
+
+    #[test]
+    fn methods_desugar_to_functions() {
+        assert_eq!(
+            type_check(""),
+            Ok(vec!["Rect::area".to_string(), "Rect::grow".to_string()])
+        );
+    }
+
+    #[test]
+    fn methods_can_be_called_on_values_and_pointers() {
+        assert!(
+            type_check(
+                "fn main() -> i32 {
+                    let r = Rect { w: 2, h: 3 };
+                    r.grow(1);
+                    let p = &r;
+                    p.grow(1);
+                    return r.area() + p.area();
+                }"
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn method_arguments_are_type_checked() {
+        assert_eq!(
+            type_check(
+                "fn f() {
+                    let r = Rect { w: 2, h: 3 };
+                    r.grow(true);
+                }"
+            ),
+            Err(DiagnosticKind::FunctionArgumentTypeMismatch {
+                n: 1,
+                expected: "i32".to_string(),
+                got: "bool".to_string(),
+            })
+        );
+        assert_eq!(
+            type_check(
+                "fn main() -> i32 {
+                    let r = Rect { w: 2, h: 3 };
+                    return r.perimeter();
+                }"
+            ),
+            Err(DiagnosticKind::StructOrUnionDoesNotHaveMember(
+                "struct { w: i32, h: i32 }".to_string(),
+                "perimeter".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_impl_blocks_are_rejected() {
+        assert_eq!(
+            type_check("impl Rect { fn f(this) {} }"),
+            Err(DiagnosticKind::InvalidMethodReceiver("this".to_string()))
+        );
+        assert_eq!(
+            type_check("type Int = i32;\nimpl Int { fn f(self) {} }"),
+            Err(DiagnosticKind::MethodsOnNonStruct("Int".to_string()))
+        );
+    }
+
+    #[test]
+    fn structs_with_the_same_fields_cannot_both_have_methods() {
+        // TEST: `b.m()` could otherwise call `A::m`, as `A` and `B` are the same
+        // type
+        assert_eq!(
+            type_check(
+                "struct A { x: i32 }
+                struct B { x: i32 }
+                impl A { fn m(self) -> i32 { return 1; } }
+                impl B { fn m(self) -> i32 { return 2; } }"
+            ),
+            Err(DiagnosticKind::MethodsOnSameShapedTypes(
+                "B".to_string(),
+                "A".to_string()
+            ))
+        );
+
+        // TEST: more than one `impl` block for the same struct is fine
+        assert!(type_check("impl Rect { fn double(self) -> i32 { return 2 * self.w; } }").is_ok());
+    }
+}
@@ -4,7 +4,12 @@ use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, diagnostic::Generic
 use zrc_parser::ast::stmt::LetDeclaration as AstLetDeclaration;
 use zrc_utils::span::{Spannable, Spanned};
 
-use super::super::{expr::try_coerce_to, resolve_type, scope::Scope, type_expr};
+use super::super::{
+    expr::{check_literal_fits, coerce_array_literal, try_coerce_to, with_explicit_cast_help},
+    resolve_type,
+    scope::Scope,
+    type_expr,
+};
 use crate::{
     tast::{expr::TypedExpr, stmt::LetDeclaration as TastLetDeclaration, ty::Type as TastType},
     typeck::scope::ValueEntry,
@@ -14,12 +19,18 @@ use crate::{
 /// into the scope, returning a vector of [TAST let
 /// declarations](TastLetDeclaration).
 ///
+/// If `infer_from_assignment` is set, a declaration with neither a type nor a
+/// value, like `let x;`, takes the type of its first assignment. Its entry is
+/// inserted with [`ValueEntry::awaiting_assignment`] set and its TAST type is
+/// left as `()` for the enclosing block to fill in once the block is checked.
+///
 /// # Errors
 /// Errors with type checker errors.
 #[expect(clippy::too_many_lines)]
 pub fn process_let_declaration<'input>(
     scope: &mut Scope<'input>,
     declarations: Vec<Spanned<AstLetDeclaration<'input>>>,
+    infer_from_assignment: bool,
 ) -> Result<Vec<Spanned<TastLetDeclaration<'input>>>, Diagnostic> {
     declarations
         .into_iter()
@@ -39,7 +50,32 @@ pub fn process_let_declaration<'input>(
                     .map(|ty| resolve_type(scope, ty))
                     .transpose()?;
 
+                // untyped integer literals in an array literal take on the declared
+                // element type
+                let typed_expr = match (typed_expr, &resolved_ty) {
+                    (Some(expr), Some(ty)) => Some(coerce_array_literal(expr, ty)?),
+                    (typed_expr, _) => typed_expr,
+                };
+
                 let result_decl = match (typed_expr, resolved_ty) {
+                    (None, None) if infer_from_assignment => {
+                        scope.values.insert(
+                            let_declaration.name.value(),
+                            ValueEntry {
+                                is_constant: let_declaration.is_constant,
+                                awaiting_assignment: true,
+                                is_local: true,
+                                ..ValueEntry::unused(TastType::unit(), let_decl_span)
+                            },
+                        );
+                        return Ok(TastLetDeclaration {
+                            name: let_declaration.name,
+                            ty: TastType::unit(),
+                            value: None,
+                            is_constant: let_declaration.is_constant,
+                        }
+                        .in_span(let_decl_span));
+                    }
                     (None, None) => {
                         return Err(DiagnosticKind::NoTypeNoValue
                             .error_in(let_decl_span)
@@ -137,13 +173,12 @@ pub fn process_let_declaration<'input>(
                             }
                         } else if inferred_type.can_implicitly_cast_to(&resolved_ty) {
                             // Insert implicit cast (e.g., {int} -> i8)
-                            let value_coerced = try_coerce_to(
-                                TypedExpr {
-                                    inferred_type,
-                                    kind,
-                                },
-                                &resolved_ty,
-                            );
+                            let value = TypedExpr {
+                                inferred_type,
+                                kind,
+                            };
+                            check_literal_fits(&value, &resolved_ty)?;
+                            let value_coerced = try_coerce_to(value, &resolved_ty);
                             TastLetDeclaration {
                                 name: let_declaration.name,
                                 ty: resolved_ty,
@@ -151,22 +186,26 @@ pub fn process_let_declaration<'input>(
                                 is_constant: let_declaration.is_constant,
                             }
                         } else {
-                            return Err(DiagnosticKind::InvalidAssignmentRightHandSideType {
-                                expected: resolved_ty.to_string(),
-                                got: inferred_type.to_string(),
-                            }
-                            .error_in(let_decl_span)
-                            .with_label(GenericLabel::note(
-                                LabelKind::VariableDeclaredType(resolved_ty.to_string())
-                                    .in_span(ty_span.expect("ty_span should exist here")),
-                            ))
-                            .with_label(GenericLabel::error(
-                                LabelKind::InvalidAssignmentRightHandSideType {
+                            return Err(with_explicit_cast_help(
+                                DiagnosticKind::InvalidAssignmentRightHandSideType {
                                     expected: resolved_ty.to_string(),
                                     got: inferred_type.to_string(),
                                 }
-                                .in_span(kind.span()),
-                            )));
+                                .error_in(let_decl_span)
+                                .with_label(GenericLabel::note(
+                                    LabelKind::VariableDeclaredType(resolved_ty.to_string())
+                                        .in_span(ty_span.expect("ty_span should exist here")),
+                                ))
+                                .with_label(GenericLabel::error(
+                                    LabelKind::InvalidAssignmentRightHandSideType {
+                                        expected: resolved_ty.to_string(),
+                                        got: inferred_type.to_string(),
+                                    }
+                                    .in_span(kind.span()),
+                                )),
+                                &inferred_type,
+                                &resolved_ty,
+                            ));
                         }
                     }
                 };
@@ -178,6 +217,10 @@ pub fn process_let_declaration<'input>(
                         declaration_span: let_decl_span,
                         is_constant: let_declaration.is_constant,
                         referenced_spans: vec![],
+                        deprecated: None,
+                        awaiting_assignment: false,
+                        awaiting_return_type: false,
+                        is_local: true,
                     },
                 );
                 Ok(result_decl.in_span(let_decl_span))
@@ -188,7 +231,7 @@ pub fn process_let_declaration<'input>(
 
 #[cfg(test)]
 mod tests {
-    use zrc_diagnostics::{DiagnosticKind, Severity};
+    use zrc_diagnostics::{DiagnosticKind, HelpKind, Severity};
     use zrc_parser::parser::parse_program;
 
     use crate::typeck::{scope::GlobalScope, type_program};
@@ -236,4 +279,246 @@ mod tests {
             ));
         }
     }
+    /// Type check a program, returning the kind of diagnostic on failure
+    fn type_check(code: &str) -> Result<(), DiagnosticKind> {
+        type_program(
+            &mut GlobalScope::new(),
+            parse_program(code, "<test>").expect("parsing should succeed"),
+        )
+        .map(|_| ())
+        .map_err(|diagnostic| diagnostic.kind.into_value())
+    }
+
+    #[test]
+    fn untyped_literals_take_the_declared_type_if_they_fit() {
+        assert_eq!(type_check("fn f() { let x: u8 = 200; }"), Ok(()));
+        assert_eq!(type_check("fn f() { let x: i8 = 0x7f; }"), Ok(()));
+        assert_eq!(
+            type_check("fn f() { let x: u8 = 300; }"),
+            Err(DiagnosticKind::NumberLiteralOutOfBounds(
+                "300".to_string(),
+                "u8".to_string(),
+                "0".to_string(),
+                "255".to_string()
+            ))
+        );
+        assert_eq!(
+            type_check("fn f() { let x: i8 = 0x80; }"),
+            Err(DiagnosticKind::NumberLiteralOutOfBounds(
+                "128".to_string(),
+                "i8".to_string(),
+                "-128".to_string(),
+                "127".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn const_arrays_can_be_declared_and_read() {
+        assert_eq!(
+            type_check(
+                "const LUT: [4]u8 = [1, 2, 3, 4];
+                 fn get(i: usize) -> u8 { return LUT[i]; }
+"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn const_arrays_cannot_be_written_to() {
+        assert_eq!(
+            type_check(
+                "const LUT: [4]u8 = [1, 2, 3, 4];
+                 fn set() { LUT[0] = 5; }
+"
+            ),
+            Err(DiagnosticKind::AssignmentToConstant("LUT".to_string()))
+        );
+        assert_eq!(
+            type_check(
+                "const LUT: [4]u8 = [1, 2, 3, 4];
+                 fn set() { LUT = [5, 6, 7, 8]; }
+"
+            ),
+            Err(DiagnosticKind::AssignmentToConstant("LUT".to_string()))
+        );
+    }
+
+    #[test]
+    fn arrays_can_be_initialized_by_repeating_a_value() {
+        assert_eq!(
+            type_check(
+                "const ZEROS: [8]u8 = [0; 8];
+                 fn f() -> i32;
+                 fn g() {
+                     let zeros: [8]i32 = [0; 2 * 4];
+                     let calls = [f(); 4];
+                     let grid: [2][3]u8 = [[1; 3]; 2];
+                     let inferred: [4]i32 = calls;
+                 }
+"
+            ),
+            Ok(())
+        );
+
+        // TEST: the length must match the declared type
+        assert_eq!(
+            type_check("fn g() { let zeros: [4]i32 = [0; 8]; }"),
+            Err(DiagnosticKind::InvalidAssignmentRightHandSideType {
+                expected: "[4]i32".to_string(),
+                got: "[8]i32".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn array_repeat_lengths_must_be_constant() {
+        assert_eq!(
+            type_check("fn g(n: usize) { let zeros = [0; n]; }"),
+            Err(DiagnosticKind::NonConstantArrayRepeatCount)
+        );
+        assert_eq!(
+            type_check("fn g() { let zeros = [0; -1]; }"),
+            Err(DiagnosticKind::NonConstantArrayRepeatCount)
+        );
+        assert_eq!(
+            type_check("fn g() { let zeros = [0; true]; }"),
+            Err(DiagnosticKind::NonConstantArrayRepeatCount)
+        );
+        assert_eq!(
+            type_check("fn g() { let zeros = [0; 0]; }"),
+            Err(DiagnosticKind::EmptyArrayLiteral)
+        );
+    }
+
+    #[test]
+    fn nested_arrays_can_be_indexed() {
+        assert_eq!(
+            type_check(
+                "fn f(i: usize, j: usize) -> i32 {
+                     let m: [2][3]i32 = [[1, 2, 3], [4, 5, 6]];
+                     m[i][j] = m[1][2];
+                     return m[i][j];
+                 }
+"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            type_check(
+                "const M: [2][3]i32 = [[1, 2, 3], [4, 5, 6]];
+                 fn set() { M[1][2] = 0; }
+"
+            ),
+            Err(DiagnosticKind::AssignmentToConstant("M".to_string()))
+        );
+    }
+
+    #[test]
+    fn out_of_bounds_coerced_elements_are_reported_in_their_own_span() {
+        let source = "fn g() { let a: [3]u8 = [1, 256, 2]; }";
+        let diagnostic = type_program(
+            &mut GlobalScope::new(),
+            parse_program(source, "<test>").expect("parsing should succeed"),
+        )
+        .expect_err("256 does not fit in a u8");
+
+        // TEST: the error points at `256`, not the array or the declaration
+        let span = diagnostic.kind.span();
+        assert_eq!(&source[span.start()..span.end()], "256");
+        assert_eq!(
+            diagnostic.kind.into_value(),
+            DiagnosticKind::NumberLiteralOutOfBounds(
+                "256".to_string(),
+                "u8".to_string(),
+                "0".to_string(),
+                "255".to_string()
+            )
+        );
+
+        assert_eq!(
+            type_check("fn g() { let b: [2]u8 = [300; 2]; }"),
+            Err(DiagnosticKind::NumberLiteralOutOfBounds(
+                "300".to_string(),
+                "u8".to_string(),
+                "0".to_string(),
+                "255".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn comparisons_require_an_explicit_cast_to_become_integers() {
+        let diagnostic = type_program(
+            &mut GlobalScope::new(),
+            parse_program("fn f(a: i32, b: i32) { let x: i32 = a < b; }", "<test>")
+                .expect("parsing should succeed"),
+        )
+        .expect_err("implicit bool -> i32 should be rejected");
+
+        assert_eq!(
+            diagnostic.kind.into_value(),
+            DiagnosticKind::InvalidAssignmentRightHandSideType {
+                expected: "i32".to_string(),
+                got: "bool".to_string(),
+            }
+        );
+        assert_eq!(
+            diagnostic.helps,
+            vec![HelpKind::ConsiderCasting("i32".to_string())]
+        );
+
+        assert_eq!(
+            type_check("fn f(a: i32, b: i32) { let x: i32 = (a < b) as i32; }"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn untyped_let_takes_the_type_of_its_first_assignment() {
+        assert_eq!(
+            type_check("fn f() -> i32 { let x; x = 5; return x; }"),
+            Ok(())
+        );
+        assert_eq!(
+            type_check("fn f(b: bool) { let x; if (b) { x = true; } let y: bool = x; }"),
+            Ok(())
+        );
+        assert_eq!(
+            type_check("fn f() { let x; x = 5; let y: bool = x; }"),
+            Err(DiagnosticKind::InvalidAssignmentRightHandSideType {
+                expected: "bool".to_string(),
+                got: "i32".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn untyped_let_rejects_conflicting_assignments() {
+        assert_eq!(
+            type_check("fn f() { let x; x = 5; x = true; }"),
+            Err(DiagnosticKind::InvalidAssignmentRightHandSideType {
+                expected: "i32".to_string(),
+                got: "bool".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn untyped_let_must_be_assigned_before_use() {
+        assert_eq!(
+            type_check("fn f() -> i32 { let x; return x; }"),
+            Err(DiagnosticKind::UsedBeforeTypeInferred("x".to_string()))
+        );
+        assert_eq!(
+            type_check("fn f() { let x; x += 1; }"),
+            Err(DiagnosticKind::UsedBeforeTypeInferred("x".to_string()))
+        );
+        assert_eq!(
+            type_check("fn f() { let x; }"),
+            Err(DiagnosticKind::NoTypeNoValue)
+        );
+        assert_eq!(type_check("let x;"), Err(DiagnosticKind::NoTypeNoValue));
+    }
 }
@@ -3,17 +3,23 @@
 mod access;
 mod assignment;
 mod binary;
+mod builtins;
 mod call;
+mod consteval;
 mod helpers;
 mod literals;
 mod misc;
 mod unary;
 
-pub use helpers::try_coerce_to;
+pub use builtins::DisabledBuiltins;
+pub use consteval::{fold_bool, fold_integer, leaked_number_expr, leaked_number_literal};
+pub use helpers::{
+    check_literal_fits, coerce_array_literal, try_coerce_to, with_explicit_cast_help,
+};
 use zrc_diagnostics::Diagnostic;
 use zrc_parser::ast::expr::{Expr, ExprKind};
 
-use super::scope::Scope;
+use super::{block::type_match_expr, scope::Scope};
 use crate::tast::expr::TypedExpr;
 
 // FIXME: this NEEDS to be rewritten to use references almost everywhere and be
@@ -49,6 +55,9 @@ pub fn type_expr<'input>(
         ExprKind::Ternary(cond, if_true, if_false) => {
             misc::type_expr_ternary(scope, expr_span, *cond, *if_true, *if_false)?
         }
+        ExprKind::Elvis(value, fallback) => {
+            misc::type_expr_elvis(scope, expr_span, *value, *fallback)?
+        }
         ExprKind::Logical(op, lhs, rhs) => {
             binary::type_expr_logical(scope, expr_span, op, *lhs, *rhs)?
         }
@@ -72,24 +81,34 @@ pub fn type_expr<'input>(
         }
         ExprKind::StringLiteral(str) => literals::type_expr_string_literal(scope, expr_span, str),
         ExprKind::CharLiteral(ch) => literals::type_expr_char_literal(scope, expr_span, ch),
-        ExprKind::Identifier(i) => literals::type_expr_identifier(scope, expr_span, i)?,
+        ExprKind::Identifier(i) => {
+            call::reject_comptime_function_value(scope, expr_span, i)?;
+            literals::type_expr_identifier(scope, expr_span, i)?
+        }
         ExprKind::BooleanLiteral(value) => {
             literals::type_expr_boolean_literal(scope, expr_span, value)
         }
         ExprKind::StructConstruction(ty, fields) => {
             misc::type_expr_struct_construction(scope, expr_span, ty, &fields)?
         }
+        ExprKind::EnumVariant(ty, variant) => {
+            misc::type_expr_enum_variant(scope, expr_span, ty, variant)?
+        }
+        ExprKind::Match(scrutinee, arms) => type_match_expr(scope, expr_span, *scrutinee, arms)?,
         ExprKind::ArrayLiteral(elements) => {
             literals::type_expr_array_literal(scope, expr_span, elements)?
         }
+        ExprKind::ArrayRepeat(value, count) => {
+            literals::type_expr_array_repeat(scope, expr_span, *value, *count)?
+        }
     })
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{collections::HashMap, rc::Rc};
 
-    use zrc_diagnostics::DiagnosticKind;
+    use zrc_diagnostics::{DiagnosticKind, HelpKind, NoteKind};
     use zrc_utils::spanned_test;
 
     use super::*;
@@ -98,13 +117,22 @@ mod tests {
             stmt::{ArgumentDeclaration, ArgumentDeclarationList},
             ty::{Fn, OrderedTypeFields, Type as TastType},
         },
-        typeck::scope::{GlobalScope, TypeCtx, ValueCtx},
+        typeck::{
+            CfgOptions,
+            scope::{GlobalScope, TypeCtx, ValueCtx},
+        },
     };
 
     #[test]
     #[expect(clippy::too_many_lines)]
     fn various_expressions_infer_correctly() {
         let scope = GlobalScope {
+            warnings: Rc::default(),
+            cfg: CfgOptions::host(),
+            methods: Vec::new(),
+            enums: Vec::new(),
+            comptime_functions: Vec::new(),
+            specializations: Rc::default(),
             global_values: ValueCtx::from_unused_mappings(HashMap::from([
                 ("i8", TastType::I8),
                 ("u8", TastType::U8),
@@ -126,6 +154,7 @@ mod tests {
                     TastType::Fn(Fn {
                         arguments: ArgumentDeclarationList::NonVariadic(vec![
                             ArgumentDeclaration {
+                                noalias: false,
                                 name: spanned_test!(0, "x", 1),
                                 ty: spanned_test!(0, TastType::I32, 3),
                             },
@@ -137,6 +166,7 @@ mod tests {
                     "sink",
                     TastType::Fn(Fn {
                         arguments: ArgumentDeclarationList::Variadic(vec![ArgumentDeclaration {
+                            noalias: false,
                             name: spanned_test!(0, "i8", 3),
                             ty: spanned_test!(0, TastType::I8, 3),
                         }]),
@@ -148,6 +178,7 @@ mod tests {
                     TastType::Fn(Fn {
                         arguments: ArgumentDeclarationList::NonVariadic(vec![
                             ArgumentDeclaration {
+                                noalias: false,
                                 name: spanned_test!(0, "ptr", 3),
                                 ty: spanned_test!(0, TastType::Ptr(Box::new(TastType::unit())), 3),
                             },
@@ -236,6 +267,58 @@ mod tests {
                 )),
             ),
             ("(&s)->i8", Ok(TastType::I8)),
+            // Test division by constant zero
+            ("i32 / i32", Ok(TastType::I32)),
+            ("i32 % i32", Ok(TastType::I32)),
+            (
+                "i32 / 0",
+                Err(DiagnosticKind::DivisionByZero("division".to_string())),
+            ),
+            (
+                "i32 % 0",
+                Err(DiagnosticKind::DivisionByZero("remainder".to_string())),
+            ),
+            (
+                "i32 / (2 - 2)",
+                Err(DiagnosticKind::DivisionByZero("division".to_string())),
+            ),
+            (
+                "i32 / 0x0",
+                Err(DiagnosticKind::DivisionByZero("division".to_string())),
+            ),
+            (
+                "i32 / 0b0",
+                Err(DiagnosticKind::DivisionByZero("division".to_string())),
+            ),
+            (
+                "i32 / 0o0",
+                Err(DiagnosticKind::DivisionByZero("division".to_string())),
+            ),
+            (
+                "i32 / 0_0",
+                Err(DiagnosticKind::DivisionByZero("division".to_string())),
+            ),
+            (
+                "i32 / -0",
+                Err(DiagnosticKind::DivisionByZero("division".to_string())),
+            ),
+            ("i32 / 2", Ok(TastType::I32)),
+            ("i8 << i8", Ok(TastType::I8)),
+            ("i32 >> 2", Ok(TastType::I32)),
+            (
+                "i8 << i32",
+                Err(DiagnosticKind::ExpectedSameType(
+                    "i8".to_string(),
+                    "i32".to_string(),
+                )),
+            ),
+            (
+                "bool >> i8",
+                Err(DiagnosticKind::ExpectedGot {
+                    expected: "integer".to_string(),
+                    got: "bool".to_string(),
+                }),
+            ),
             ("get_bool()", Ok(TastType::Bool)),
             (
                 "get_bool(i32)",
@@ -277,6 +360,29 @@ mod tests {
                 "bool()",
                 Err(DiagnosticKind::CannotCallNonFunction("bool".to_string())),
             ),
+            // Test function pointers, which are called through
+            (
+                "&get_bool",
+                Ok(TastType::Ptr(Box::new(TastType::Fn(Fn {
+                    arguments: ArgumentDeclarationList::NonVariadic(vec![]),
+                    returns: Box::new(TastType::Bool),
+                })))),
+            ),
+            ("(*&get_bool)()", Ok(TastType::Bool)),
+            ("(&get_bool)()", Ok(TastType::Bool)),
+            ("(&id)(i32)", Ok(TastType::I32)),
+            (
+                "(&id)(i8)",
+                Err(DiagnosticKind::FunctionArgumentTypeMismatch {
+                    n: 0,
+                    expected: "i32".to_string(),
+                    got: "i8".to_string(),
+                }),
+            ),
+            (
+                "(&i8)()",
+                Err(DiagnosticKind::CannotCallNonFunction("*i8".to_string())),
+            ),
             ("bool ? i8 : i8", Ok(TastType::I8)),
             (
                 "i8 ? i8 : i8",
@@ -352,6 +458,22 @@ mod tests {
                 "(&i8) + (2 as usize)",
                 Ok(TastType::Ptr(Box::new(TastType::I8))),
             ),
+            ("(&i8) - 1", Ok(TastType::Ptr(Box::new(TastType::I8)))),
+            ("(&i8) - (&i8)", Ok(TastType::Isize)),
+            (
+                "(&i8) - (&u8)",
+                Err(DiagnosticKind::ExpectedSameType(
+                    "*i8".to_string(),
+                    "*u8".to_string(),
+                )),
+            ),
+            (
+                "(&i8) + (&i8)",
+                Err(DiagnosticKind::ExpectedGot {
+                    expected: "usize".to_string(),
+                    got: "*i8".to_string(),
+                }),
+            ),
             ("i8 as i32", Ok(TastType::I32)),
             ("(&i8) as *i32", Ok(TastType::Ptr(Box::new(TastType::I32)))),
             ("(&i8) as usize", Ok(TastType::Usize)),
@@ -399,4 +521,149 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn mixing_signedness_suggests_a_cast() {
+        let mixed = type_expr(
+            &mut GlobalScope::new().create_subscope(),
+            zrc_parser::parser::parse_expr("1i32 + 1u32", "<test>")
+                .expect("parsing should succeed"),
+        )
+        .expect_err("mixing signedness should fail");
+
+        assert_eq!(
+            mixed.kind.into_value(),
+            DiagnosticKind::ExpectedSameType("i32".to_string(), "u32".to_string())
+        );
+        assert_eq!(mixed.notes, vec![NoteKind::MixedSignedness]);
+        assert_eq!(
+            mixed.helps,
+            vec![HelpKind::ConsiderCasting("i32".to_string())]
+        );
+
+        // differently-sized integers of the same signedness get no suggestion
+        let same_signedness = type_expr(
+            &mut GlobalScope::new().create_subscope(),
+            zrc_parser::parser::parse_expr("1i32 + 1i8", "<test>").expect("parsing should succeed"),
+        )
+        .expect_err("mixing widths should fail");
+
+        assert!(same_signedness.notes.is_empty());
+        assert!(same_signedness.helps.is_empty());
+    }
+
+    #[test]
+    fn pointers_to_the_same_type_can_be_ordered() {
+        let type_check = |body: &str| {
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(
+                    &format!("fn f(p: *u8, q: *u8, r: *i32) -> bool {{ {body} }}"),
+                    "<test>",
+                )
+                .expect("parsing should succeed"),
+            )
+            .map(|_| ())
+            .map_err(|diagnostic| diagnostic.kind.into_value())
+        };
+
+        assert_eq!(type_check("return p < q;"), Ok(()));
+        assert_eq!(type_check("return p >= q;"), Ok(()));
+
+        // TEST: the pointee types must match
+        assert_eq!(
+            type_check("return p < r;"),
+            Err(DiagnosticKind::ExpectedSameType(
+                "*u8".to_string(),
+                "*i32".to_string()
+            ))
+        );
+
+        // TEST: a pointer cannot be ordered against an integer
+        assert!(type_check("return p < 4;").is_err());
+    }
+
+    #[test]
+    fn elvis_operands_unify_and_must_be_testable() {
+        let type_of = |expr: &str| {
+            type_expr(
+                &mut GlobalScope::new().create_subscope(),
+                zrc_parser::parser::parse_expr(expr, "<test>").expect("parsing should succeed"),
+            )
+            .map(|result| result.inferred_type.to_string())
+            .map_err(|diagnostic| diagnostic.kind.into_value())
+        };
+
+        assert_eq!(type_of("1u8 ?: 2"), Ok("u8".to_string()));
+        assert_eq!(type_of("0 ?: 2"), Ok("i32".to_string()));
+        assert_eq!(type_of("false ?: true"), Ok("bool".to_string()));
+        assert_eq!(
+            type_of("true ?: 1"),
+            Err(DiagnosticKind::ExpectedSameType(
+                "bool".to_string(),
+                "{int}".to_string()
+            ))
+        );
+
+        // TEST: the value must be comparable against zero
+        assert_eq!(
+            type_of("struct {} {} ?: struct {} {}"),
+            Err(DiagnosticKind::ElvisValueNotTestable(
+                "struct {}".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn shifts_out_of_range_are_warned_about() {
+        /// Type check `expr` with `x` as a `u8` and `y` as an `i8`, returning the
+        /// warnings it produces
+        fn warnings_in(expr: &'static str) -> Vec<DiagnosticKind> {
+            let global_scope = GlobalScope {
+                global_values: ValueCtx::from_unused_mappings(HashMap::from([
+                    ("x", TastType::U8),
+                    ("y", TastType::I8),
+                ])),
+                ..Default::default()
+            };
+            type_expr(
+                &mut global_scope.create_subscope(),
+                zrc_parser::parser::parse_expr(expr, "<test>").expect("parsing should succeed"),
+            )
+            .expect("typeck should succeed");
+            global_scope
+                .take_warnings()
+                .into_iter()
+                .map(|warning| warning.kind.into_value())
+                .collect()
+        }
+
+        assert_eq!(warnings_in("x << 7"), vec![]);
+        assert_eq!(
+            warnings_in("x >> 8"),
+            vec![DiagnosticKind::ShiftAmountTooLarge(
+                "8".to_string(),
+                "u8".to_string()
+            )]
+        );
+        // TEST: compound assignments shift too, and the amount is folded
+        assert_eq!(
+            warnings_in("x <<= 2u8 * 8"),
+            vec![DiagnosticKind::ShiftAmountTooLarge(
+                "16".to_string(),
+                "u8".to_string()
+            )]
+        );
+
+        // TEST: negative amounts are warned about too
+        assert_eq!(warnings_in("y >> 7"), vec![]);
+        assert_eq!(
+            warnings_in("y << -1i8"),
+            vec![DiagnosticKind::NegativeShiftAmount("-1".to_string())]
+        );
+        assert_eq!(
+            warnings_in("y >>= 2i8 - 3"),
+            vec![DiagnosticKind::NegativeShiftAmount("-1".to_string())]
+        );
+    }
 }
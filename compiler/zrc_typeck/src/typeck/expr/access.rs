@@ -8,7 +8,8 @@ use zrc_utils::span::{Span, Spannable, Spanned};
 
 use super::{
     super::scope::Scope,
-    helpers::{expr_to_place, try_coerce_to},
+    consteval::fold_integer,
+    helpers::{expr_to_readable_place, try_coerce_to},
     type_expr,
 };
 use crate::tast::{
@@ -57,10 +58,28 @@ pub fn type_expr_index<'input>(
             inferred_type: *points_to_ty,
             kind: TypedExprKind::Index(Box::new(ptr_t), Box::new(offset_final)).in_span(expr_span),
         })
-    } else if let TastType::Array { element_type, .. } = ptr_t.inferred_type.clone() {
+    } else if let TastType::Array { size, element_type } = ptr_t.inferred_type.clone() {
+        // a constant index can be checked now instead of reading out of bounds
+        if let Some(index) = fold_integer(&offset_final)
+            && u64::try_from(index).map_or(true, |index| index >= size)
+        {
+            let index_span = offset_final.kind.span();
+            return Err(DiagnosticKind::ConstantIndexOutOfBounds {
+                index: index.to_string(),
+                len: size,
+            }
+            .error_in(index_span)
+            .with_label(GenericLabel::error(
+                LabelKind::ConstantIndexOutOfBounds(size).in_span(index_span),
+            ))
+            .with_label(GenericLabel::note(
+                LabelKind::PlaceType(ptr_t.inferred_type.to_string()).in_span(ptr_span),
+            )));
+        }
+
         // Arrays decay to pointers when indexed
         // Convert the array to a pointer to its first element
-        let place = expr_to_place(scope, expr_span, ptr_t)?;
+        let place = expr_to_readable_place(expr_span, ptr_t)?;
         let array_ptr_expr = TypedExpr {
             inferred_type: TastType::Ptr(element_type.clone()),
             kind: TypedExprKind::UnaryAddressOf(Box::new(place)).in_span(expr_span),
@@ -100,7 +119,7 @@ pub fn type_expr_dot<'input>(
         if let Some(ty) = fields.get(key.value()) {
             Ok(TypedExpr {
                 inferred_type: ty.clone(),
-                kind: TypedExprKind::Dot(Box::new(expr_to_place(scope, obj_span, obj_t)?), key)
+                kind: TypedExprKind::Dot(Box::new(expr_to_readable_place(obj_span, obj_t)?), key)
                     .in_span(expr_span),
             })
         } else {
@@ -169,3 +188,87 @@ pub fn type_expr_arrow<'input>(
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::DiagnosticKind;
+    use zrc_parser::parser::parse_program;
+
+    use crate::typeck::{scope::GlobalScope, type_program};
+
+    /// Type check a program, returning the kind of diagnostic on failure
+    fn type_check(code: &str) -> Result<(), DiagnosticKind> {
+        type_program(
+            &mut GlobalScope::new(),
+            parse_program(code, "<test>").expect("parsing should succeed"),
+        )
+        .map(|_| ())
+        .map_err(|diagnostic| diagnostic.kind.into_value())
+    }
+
+    #[test]
+    fn constant_in_bounds_indices_are_allowed() {
+        assert_eq!(
+            type_check(
+                "fn f(i: usize) -> i32 {
+                     let a: [4]i32 = [1, 2, 3, 4];
+                     a[3] = a[0] + a[1usize + 1];
+                     return a[i];
+                 }"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn constant_out_of_bounds_indices_are_rejected() {
+        assert_eq!(
+            type_check(
+                "fn f() -> i32 {
+                     let a: [4]i32 = [1, 2, 3, 4];
+                     return a[4];
+                 }"
+            ),
+            Err(DiagnosticKind::ConstantIndexOutOfBounds {
+                index: "4".to_string(),
+                len: 4,
+            })
+        );
+        assert_eq!(
+            type_check(
+                "fn f() {
+                     let a: [4]i32 = [1, 2, 3, 4];
+                     a[2usize * 3] = 0;
+                 }"
+            ),
+            Err(DiagnosticKind::ConstantIndexOutOfBounds {
+                index: "6".to_string(),
+                len: 4,
+            })
+        );
+        assert_eq!(
+            type_check(
+                "fn f() -> i32 {
+                     let a: [4]i32 = [1, 2, 3, 4];
+                     return a[0usize - 1];
+                 }"
+            ),
+            Err(DiagnosticKind::ConstantIndexOutOfBounds {
+                index: "-1".to_string(),
+                len: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn constant_pointer_indices_are_not_bounds_checked() {
+        assert_eq!(
+            type_check(
+                "fn f(p: *i32) -> i32 {
+                     return p[0usize - 1] + p[100];
+                 }"
+            ),
+            Ok(())
+        );
+    }
+}
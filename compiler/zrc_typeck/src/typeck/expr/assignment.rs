@@ -1,15 +1,23 @@
 //! type checking for the assignment operators
 
+use std::{cell::RefCell, rc::Rc};
+
 use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, diagnostic::GenericLabel};
-use zrc_parser::ast::expr::{Assignment, Expr};
+use zrc_parser::ast::expr::{Assignment, Expr, ExprKind};
 use zrc_utils::span::{Span, Spannable};
 
 use super::{
     super::scope::Scope,
-    helpers::{desugar_assignment, expr_to_place, try_coerce_to},
+    helpers::{desugar_assignment, expr_to_place, try_coerce_to, with_explicit_cast_help},
     type_expr,
 };
-use crate::tast::expr::{TypedExpr, TypedExprKind};
+use crate::{
+    tast::{
+        expr::{Place, TypedExpr, TypedExprKind},
+        ty::Type as TastType,
+    },
+    typeck::scope::ValueEntry,
+};
 
 /// Typeck and desugar an assignment expr
 pub fn type_expr_assignment<'input>(
@@ -22,9 +30,20 @@ pub fn type_expr_assignment<'input>(
     // Desugar `x += y` to `x = x + y`.
     let (place, value) = desugar_assignment(mode, place, value);
 
-    let lvalue = type_expr(scope, place)?;
-    let place_t = expr_to_place(scope, expr_span, lvalue)?;
-    let value_t = type_expr(scope, value)?;
+    // `let x;` takes the type of its first assignment, so the value must be
+    // typed before `x` can be
+    let (place_t, value_t) = if let ExprKind::Identifier(name) = place.0.value()
+        && let Some(entry) = scope.values.resolve(name)
+        && entry.borrow().awaiting_assignment
+    {
+        let value_t = type_expr(scope, value)?;
+        infer_from_assignment(&entry, &value_t)?;
+
+        (type_written_place(scope, expr_span, place)?, value_t)
+    } else {
+        let place_t = type_written_place(scope, expr_span, place)?;
+        (place_t, type_expr(scope, value)?)
+    };
 
     if place_t.inferred_type == value_t.inferred_type {
         Ok(TypedExpr {
@@ -44,20 +63,85 @@ pub fn type_expr_assignment<'input>(
                 .in_span(expr_span),
         })
     } else {
-        Err(DiagnosticKind::InvalidAssignmentRightHandSideType {
-            expected: place_t.inferred_type.to_string(),
-            got: value_t.inferred_type.to_string(),
-        }
-        .error_in(expr_span)
-        .with_label(GenericLabel::note(
-            LabelKind::PlaceType(place_t.inferred_type.to_string()).in_span(place_t.kind.span()),
-        ))
-        .with_label(GenericLabel::error(
-            LabelKind::InvalidAssignmentRightHandSideType {
+        Err(with_explicit_cast_help(
+            DiagnosticKind::InvalidAssignmentRightHandSideType {
                 expected: place_t.inferred_type.to_string(),
                 got: value_t.inferred_type.to_string(),
             }
-            .in_span(value_t.kind.span()),
-        )))
+            .error_in(expr_span)
+            .with_label(GenericLabel::note(
+                LabelKind::PlaceType(place_t.inferred_type.to_string())
+                    .in_span(place_t.kind.span()),
+            ))
+            .with_label(GenericLabel::error(
+                LabelKind::InvalidAssignmentRightHandSideType {
+                    expected: place_t.inferred_type.to_string(),
+                    got: value_t.inferred_type.to_string(),
+                }
+                .in_span(value_t.kind.span()),
+            )),
+            &value_t.inferred_type,
+            &place_t.inferred_type,
+        ))
     }
 }
+
+/// Typeck the place an assignment writes to.
+///
+/// Writing to a variable, or to one of its fields with `.`, does not read it,
+/// so this does not count as a use of the variable for the unused variable
+/// warning. `x += 1` still reads `x`, in the desugared `x + 1`.
+fn type_written_place<'input>(
+    scope: &mut Scope<'input>,
+    expr_span: Span,
+    place: Expr<'input>,
+) -> Result<Place<'input>, Diagnostic> {
+    let written = written_variable(&place).and_then(|name| scope.values.resolve(name));
+    let reads_before = written
+        .as_ref()
+        .map(|entry| entry.borrow().referenced_spans.len());
+
+    let lvalue = type_expr(scope, place)?;
+
+    if let (Some(entry), Some(reads_before)) = (written, reads_before) {
+        entry.borrow_mut().referenced_spans.truncate(reads_before);
+    }
+
+    expr_to_place(scope, expr_span, lvalue)
+}
+
+/// The variable that `place` writes to without reading it, if it is a variable
+/// or a chain of `.` field accesses on one
+fn written_variable<'input>(place: &Expr<'input>) -> Option<&'input str> {
+    #[expect(clippy::wildcard_enum_match_arm)]
+    match place.0.value() {
+        ExprKind::Identifier(name) => Some(name),
+        ExprKind::Dot(object, _) => written_variable(object),
+        _ => None,
+    }
+}
+
+/// Fix the type of a `let x;` binding to the type of its first assignment
+fn infer_from_assignment<'input>(
+    entry: &Rc<RefCell<ValueEntry<'input>>>,
+    value: &TypedExpr<'input>,
+) -> Result<(), Diagnostic> {
+    if matches!(value.inferred_type, TastType::Fn(_)) {
+        return Err(DiagnosticKind::FunctionNotFirstClass
+            .error_in(value.kind.span())
+            .with_label(GenericLabel::error(
+                LabelKind::FunctionNotFirstClass.in_span(value.kind.span()),
+            )));
+    }
+
+    let mut entry = entry.borrow_mut();
+    // as with `let x = 5;`, untyped integers become `i32`
+    entry.ty = if value.inferred_type == TastType::Int {
+        TastType::I32
+    } else {
+        value.inferred_type.clone()
+    };
+    entry.awaiting_assignment = false;
+
+    Ok(())
+}
@@ -8,6 +8,7 @@ use zrc_utils::span::{Span, Spannable};
 
 use super::{
     super::scope::Scope,
+    consteval,
     helpers::{expect, expect_is_integer, resolve_binary_int_operands, try_coerce_to},
     type_expr,
 };
@@ -145,6 +146,10 @@ pub fn type_expr_binary_bitwise<'input>(
         )));
     }
 
+    if matches!(op, BinaryBitwise::Shl | BinaryBitwise::Shr) {
+        warn_if_shift_out_of_range(scope, &result_type, &final_rhs);
+    }
+
     Ok(TypedExpr {
         inferred_type: result_type,
         kind: TypedExprKind::BinaryBitwise(op, Box::new(final_lhs), Box::new(final_rhs))
@@ -165,11 +170,19 @@ pub fn type_expr_comparison<'input>(
     let rhs_span = rhs.0.span();
     let rhs_t = type_expr(scope, rhs)?;
 
-    expect_is_integer(&lhs_t.inferred_type, lhs_span)?;
-    expect_is_integer(&rhs_t.inferred_type, rhs_span)?;
+    let (final_lhs, final_rhs) = if let (TastType::Ptr(_), TastType::Ptr(_)) =
+        (&lhs_t.inferred_type, &rhs_t.inferred_type)
+    {
+        // pointers are ordered by their addresses
+        (lhs_t, rhs_t)
+    } else {
+        expect_is_integer(&lhs_t.inferred_type, lhs_span)?;
+        expect_is_integer(&rhs_t.inferred_type, rhs_span)?;
 
-    // Handle {int} type resolution
-    let (_, final_lhs, final_rhs) = resolve_binary_int_operands(lhs_t, rhs_t);
+        // Handle {int} type resolution
+        let (_, resolved_lhs, resolved_rhs) = resolve_binary_int_operands(lhs_t, rhs_t);
+        (resolved_lhs, resolved_rhs)
+    };
 
     // Check if types match after resolution
     if final_lhs.inferred_type != final_rhs.inferred_type {
@@ -195,6 +208,7 @@ pub fn type_expr_comparison<'input>(
 }
 
 /// Typeck an arithmetic expr
+#[expect(clippy::too_many_lines)]
 pub fn type_expr_arithmetic<'input>(
     scope: &mut Scope<'input>,
     expr_span: Span,
@@ -222,6 +236,31 @@ pub fn type_expr_arithmetic<'input>(
             );
         }
 
+        // `p - q` between two pointers of the same type yields the distance between
+        // them in elements
+        if op == Arithmetic::Subtraction && matches!(rhs_t.inferred_type, TastType::Ptr(_)) {
+            if lhs_t.inferred_type != rhs_t.inferred_type {
+                return Err(DiagnosticKind::ExpectedSameType(
+                    lhs_t.inferred_type.to_string(),
+                    rhs_t.inferred_type.to_string(),
+                )
+                .error_in(expr_span)
+                .with_label(GenericLabel::error(
+                    LabelKind::ExpectedSameType(
+                        lhs_t.inferred_type.to_string(),
+                        rhs_t.inferred_type.to_string(),
+                    )
+                    .in_span(expr_span),
+                )));
+            }
+
+            return Ok(TypedExpr {
+                inferred_type: TastType::Isize,
+                kind: TypedExprKind::Arithmetic(op, Box::new(lhs_t), Box::new(rhs_t))
+                    .in_span(expr_span),
+            });
+        }
+
         // For pointer arithmetic, rhs should be usize or {int} (which implicitly
         // converts to usize)
         let final_rhs = if rhs_t.inferred_type == TastType::Usize {
@@ -259,7 +298,7 @@ pub fn type_expr_arithmetic<'input>(
 
         // Check if types match after resolution
         if final_lhs.inferred_type != final_rhs.inferred_type {
-            return Err(DiagnosticKind::ExpectedSameType(
+            let diagnostic = DiagnosticKind::ExpectedSameType(
                 final_lhs.inferred_type.to_string(),
                 final_rhs.inferred_type.to_string(),
             )
@@ -270,13 +309,109 @@ pub fn type_expr_arithmetic<'input>(
                     final_rhs.inferred_type.to_string(),
                 )
                 .in_span(expr_span),
-            )));
+            ));
+
+            // mixing signedness is a common mistake, so point out how to fix it
+            let mixes_signedness = (final_lhs.inferred_type.is_signed_integer()
+                && final_rhs.inferred_type.is_unsigned_integer())
+                || (final_lhs.inferred_type.is_unsigned_integer()
+                    && final_rhs.inferred_type.is_signed_integer());
+
+            return Err(if mixes_signedness {
+                diagnostic.with_note(NoteKind::MixedSignedness).with_help(
+                    HelpKind::ConsiderCasting(final_lhs.inferred_type.to_string()),
+                )
+            } else {
+                diagnostic
+            });
         }
 
-        Ok(TypedExpr {
-            inferred_type: result_type,
-            kind: TypedExprKind::Arithmetic(op, Box::new(final_lhs), Box::new(final_rhs))
-                .in_span(expr_span),
-        })
+        check_division_by_zero(op, &final_rhs)?;
+
+        check_constant_overflow(
+            scope,
+            TypedExpr {
+                inferred_type: result_type,
+                kind: TypedExprKind::Arithmetic(op, Box::new(final_lhs), Box::new(final_rhs))
+                    .in_span(expr_span),
+            },
+        )
     }
 }
+
+/// Warn about a shift of a `ty` whose amount folds to a negative number or to
+/// at least the width of `ty`, like `x << -1` or `x << 32` on an `i32`, as
+/// LLVM leaves its result undefined
+fn warn_if_shift_out_of_range(scope: &Scope<'_>, ty: &TastType<'_>, amount: &TypedExpr<'_>) {
+    let (Some(width), Some(value)) = (ty.integer_bit_width(), consteval::fold_integer(amount))
+    else {
+        return;
+    };
+
+    let (kind, label, note) = if value < 0 {
+        (
+            DiagnosticKind::NegativeShiftAmount(value.to_string()),
+            LabelKind::NegativeShiftAmount,
+            NoteKind::NegativeShiftAmountIsUndefined,
+        )
+    } else if value >= i128::from(width) {
+        (
+            DiagnosticKind::ShiftAmountTooLarge(value.to_string(), ty.to_string()),
+            LabelKind::ShiftAmountTooLarge(ty.to_string(), width),
+            NoteKind::ShiftAmountTooLargeIsUndefined,
+        )
+    } else {
+        return;
+    };
+
+    let span = amount.kind.span();
+    scope.warn(
+        kind.warning_in(span)
+            .with_label(GenericLabel::warning(label.in_span(span)))
+            .with_note(note),
+    );
+}
+
+/// Reject a division or remainder whose divisor folds to zero, like `x / 0` or
+/// `x % (2 - 2)`. Divisors that are not constant are left to runtime.
+fn check_division_by_zero(op: Arithmetic, divisor: &TypedExpr<'_>) -> Result<(), Diagnostic> {
+    let operation = match op {
+        Arithmetic::Division => "division",
+        Arithmetic::Modulo => "remainder",
+        Arithmetic::Addition | Arithmetic::Subtraction | Arithmetic::Multiplication => {
+            return Ok(());
+        }
+    };
+
+    if consteval::fold_integer(divisor) == Some(0) {
+        let span = divisor.kind.span();
+        return Err(DiagnosticKind::DivisionByZero(operation.to_string())
+            .error_in(span)
+            .with_label(GenericLabel::error(LabelKind::DivisionByZero.in_span(span)))
+            .with_note(NoteKind::DivisionByZeroIsUndefined));
+    }
+
+    Ok(())
+}
+
+/// Reject `expr` if it is a constant that overflows its type and overflow
+/// checks are enabled. Otherwise, it wraps around like the generated code.
+fn check_constant_overflow<'input>(
+    scope: &Scope<'input>,
+    expr: TypedExpr<'input>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    if scope.overflow_checks
+        && let Some((_, true)) = consteval::overflowing_fold(&expr)
+    {
+        let span = expr.kind.span();
+        return Err(
+            DiagnosticKind::ConstantOverflow(expr.inferred_type.to_string())
+                .error_in(span)
+                .with_label(GenericLabel::error(
+                    LabelKind::ConstantOverflow(expr.inferred_type.to_string()).in_span(span),
+                )),
+        );
+    }
+
+    Ok(expr)
+}
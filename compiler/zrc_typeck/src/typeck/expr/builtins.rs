@@ -0,0 +1,636 @@
+//! type checking for compiler built-in functions
+//!
+//! Built-ins are written with normal call syntax (`truncate(x, u8)`), but are
+//! recognized by name before the callee is resolved. A user-declared value of
+//! the same name always takes precedence over the built-in, and built-ins
+//! disabled with `--no-builtin` are never recognized.
+
+use zrc_diagnostics::{
+    Diagnostic, DiagnosticKind, LabelKind, NoteKind, SpanExt, diagnostic::GenericLabel,
+};
+use zrc_parser::ast::{
+    expr::{Expr, ExprKind},
+    ty::Type,
+};
+use zrc_utils::span::{Span, Spannable, Spanned};
+
+use super::{
+    super::scope::Scope,
+    consteval::leaked_number_literal,
+    helpers::{expect_is_integer, try_coerce_to},
+    type_expr,
+};
+use crate::{
+    tast::{
+        expr::{IntegerConversion, Rotation, TypedExpr, TypedExprKind},
+        ty::Type as TastType,
+    },
+    typeck::resolve_type,
+};
+
+/// The built-in functions known to the type checker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Builtin {
+    /// `truncate(x, T)`
+    Truncate,
+    /// `sign_extend(x, T)`
+    SignExtend,
+    /// `zero_extend(x, T)`
+    ZeroExtend,
+    /// `alignof_expr(x)`
+    AlignOfExpr,
+    /// `rotate_left(x, n)`
+    RotateLeft,
+    /// `rotate_right(x, n)`
+    RotateRight,
+    /// `len(array)`
+    Len,
+}
+
+impl Builtin {
+    /// Look up a built-in by the identifier it is called with
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "truncate" => Some(Self::Truncate),
+            "sign_extend" => Some(Self::SignExtend),
+            "zero_extend" => Some(Self::ZeroExtend),
+            "alignof_expr" => Some(Self::AlignOfExpr),
+            "rotate_left" => Some(Self::RotateLeft),
+            "rotate_right" => Some(Self::RotateRight),
+            "len" => Some(Self::Len),
+            _ => None,
+        }
+    }
+
+    /// The identifier this built-in is called with
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Truncate => "truncate",
+            Self::SignExtend => "sign_extend",
+            Self::ZeroExtend => "zero_extend",
+            Self::AlignOfExpr => "alignof_expr",
+            Self::RotateLeft => "rotate_left",
+            Self::RotateRight => "rotate_right",
+            Self::Len => "len",
+        }
+    }
+
+    /// How this built-in is called, used in diagnostics
+    pub const fn signature(self) -> &'static str {
+        match self {
+            Self::Truncate => "truncate(value, IntegerType)",
+            Self::SignExtend => "sign_extend(value, IntegerType)",
+            Self::ZeroExtend => "zero_extend(value, IntegerType)",
+            Self::AlignOfExpr => "alignof_expr(value)",
+            Self::RotateLeft => "rotate_left(value, amount)",
+            Self::RotateRight => "rotate_right(value, amount)",
+            Self::Len => "len(array)",
+        }
+    }
+}
+
+/// Which built-ins are disabled with `--no-builtin`, so that calls to them are
+/// resolved like calls to any other function
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DisabledBuiltins {
+    /// Every built-in is recognized
+    #[default]
+    None,
+    /// No built-in is recognized
+    All,
+    /// The built-ins with these names are not recognized
+    Named(Vec<String>),
+}
+
+impl DisabledBuiltins {
+    /// Returns whether the built-in called `name` is disabled
+    #[must_use]
+    pub fn is_disabled(&self, name: &str) -> bool {
+        match self {
+            Self::None => false,
+            Self::All => true,
+            Self::Named(names) => names.iter().any(|disabled| disabled == name),
+        }
+    }
+}
+
+/// Determine if a call to `f` should be handled as a built-in, based on the
+/// callee's name and the current scope.
+#[expect(clippy::wildcard_enum_match_arm)]
+pub fn builtin_for_callee<'input>(scope: &Scope<'input>, f: &Expr<'input>) -> Option<Builtin> {
+    match f.0.value() {
+        ExprKind::Identifier(name)
+            if scope.values.resolve(name).is_none()
+                && !scope.disabled_builtins.is_disabled(name) =>
+        {
+            Builtin::from_name(name)
+        }
+        _ => None,
+    }
+}
+
+/// Typeck a call to a built-in
+pub fn type_builtin_call<'input>(
+    scope: &mut Scope<'input>,
+    expr_span: Span,
+    builtin: Builtin,
+    args: Spanned<Vec<Expr<'input>>>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    match builtin {
+        Builtin::Truncate => {
+            type_integer_conversion(scope, expr_span, builtin, IntegerConversion::Truncate, args)
+        }
+        Builtin::SignExtend => type_integer_conversion(
+            scope,
+            expr_span,
+            builtin,
+            IntegerConversion::SignExtend,
+            args,
+        ),
+        Builtin::ZeroExtend => type_integer_conversion(
+            scope,
+            expr_span,
+            builtin,
+            IntegerConversion::ZeroExtend,
+            args,
+        ),
+        Builtin::AlignOfExpr => type_align_of_expr(scope, expr_span, builtin, args),
+        Builtin::RotateLeft => type_rotate(scope, expr_span, builtin, Rotation::Left, args),
+        Builtin::RotateRight => type_rotate(scope, expr_span, builtin, Rotation::Right, args),
+        Builtin::Len => type_len(scope, expr_span, builtin, args),
+    }
+}
+
+/// Ensure a built-in was passed exactly `count` arguments
+fn expect_argument_count(
+    builtin: Builtin,
+    expr_span: Span,
+    args: &Spanned<Vec<Expr<'_>>>,
+    count: usize,
+) -> Result<(), Diagnostic> {
+    if args.value().len() == count {
+        Ok(())
+    } else {
+        Err(DiagnosticKind::FunctionArgumentCountMismatch {
+            expected: count.to_string(),
+            got: args.value().len().to_string(),
+        }
+        .error_in(expr_span)
+        .with_label(GenericLabel::error(
+            LabelKind::FunctionArgumentCountMismatch {
+                expected: count.to_string(),
+                got: args.value().len().to_string(),
+            }
+            .in_span(args.span()),
+        ))
+        .with_note(NoteKind::BuiltinSignature(builtin.signature().to_string())))
+    }
+}
+
+/// Resolve an expression that names a type, like the `T` in `truncate(x, T)`.
+#[expect(clippy::wildcard_enum_match_arm)]
+fn expr_as_type<'input>(
+    scope: &Scope<'input>,
+    expr: &Expr<'input>,
+) -> Result<Spanned<TastType<'input>>, Diagnostic> {
+    let span = expr.0.span();
+    match expr.0.value() {
+        ExprKind::Identifier(name) => {
+            let resolved = resolve_type(scope, Type::build_ident(span.containing(*name)))?;
+            Ok(span.containing(resolved))
+        }
+        _ => Err(span
+            .error(DiagnosticKind::ExpectedGot {
+                expected: "type name".to_string(),
+                got: expr.to_string(),
+            })
+            .with_label(GenericLabel::error(
+                LabelKind::ExpectedGot {
+                    expected: "type name".to_string(),
+                    got: expr.to_string(),
+                }
+                .in_span(span),
+            ))),
+    }
+}
+
+/// Get the bit width of a fixed-width integer type, producing a diagnostic for
+/// pointer-sized and non-integer types.
+fn fixed_integer_width(ty: &TastType<'_>, span: Span) -> Result<u32, Diagnostic> {
+    expect_is_integer(ty, span)?;
+
+    ty.integer_bit_width().ok_or_else(|| {
+        span.error(DiagnosticKind::ExpectedGot {
+            expected: "fixed-width integer".to_string(),
+            got: ty.to_string(),
+        })
+        .with_label(GenericLabel::error(
+            LabelKind::ExpectedGot {
+                expected: "fixed-width integer".to_string(),
+                got: ty.to_string(),
+            }
+            .in_span(span),
+        ))
+        .with_note(NoteKind::PointerSizedIntegerWidth)
+    })
+}
+
+/// Typeck a `truncate`, `sign_extend` or `zero_extend` call
+fn type_integer_conversion<'input>(
+    scope: &mut Scope<'input>,
+    expr_span: Span,
+    builtin: Builtin,
+    conversion: IntegerConversion,
+    args: Spanned<Vec<Expr<'input>>>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    expect_argument_count(builtin, expr_span, &args, 2)?;
+
+    let mut args = args.into_value().into_iter();
+    let x = args.next().expect("argument count was checked");
+    let ty = args.next().expect("argument count was checked");
+
+    let x_span = x.0.span();
+    let x_t = type_expr(scope, x)?;
+    expect_is_integer(&x_t.inferred_type, x_span)?;
+
+    // an untyped literal is treated as the default integer type
+    let x_t = if matches!(x_t.inferred_type, TastType::Int) {
+        TypedExpr {
+            inferred_type: TastType::I32,
+            kind: x_t.kind,
+        }
+    } else {
+        x_t
+    };
+
+    let target = expr_as_type(scope, &ty)?;
+
+    let source_width = fixed_integer_width(&x_t.inferred_type, x_span)?;
+    let target_width = fixed_integer_width(target.value(), target.span())?;
+
+    let (is_valid, relation) = match conversion {
+        IntegerConversion::Truncate => (target_width < source_width, "narrower"),
+        IntegerConversion::SignExtend | IntegerConversion::ZeroExtend => {
+            (target_width > source_width, "wider")
+        }
+    };
+
+    if !is_valid {
+        return Err(DiagnosticKind::InvalidIntegerConversion {
+            builtin: builtin.name().to_string(),
+            from: x_t.inferred_type.to_string(),
+            to: target.value().to_string(),
+            relation: relation.to_string(),
+        }
+        .error_in(expr_span)
+        .with_label(GenericLabel::note(
+            LabelKind::InferredType(x_t.inferred_type.to_string()).in_span(x_span),
+        ))
+        .with_label(GenericLabel::error(
+            LabelKind::InvalidIntegerConversion {
+                from: x_t.inferred_type.to_string(),
+                to: target.value().to_string(),
+                relation: relation.to_string(),
+            }
+            .in_span(target.span()),
+        )));
+    }
+
+    Ok(TypedExpr {
+        inferred_type: target.value().clone(),
+        kind: TypedExprKind::IntegerConversion(conversion, Box::new(x_t), target)
+            .in_span(expr_span),
+    })
+}
+
+/// Typeck an `alignof_expr` call
+fn type_align_of_expr<'input>(
+    scope: &mut Scope<'input>,
+    expr_span: Span,
+    builtin: Builtin,
+    args: Spanned<Vec<Expr<'input>>>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    expect_argument_count(builtin, expr_span, &args, 1)?;
+
+    let x = args
+        .into_value()
+        .into_iter()
+        .next()
+        .expect("argument count was checked");
+
+    // like `sizeof(expr)`, the expression is only used for its type and is never
+    // evaluated
+    let x_ty = type_expr(scope, x)?.inferred_type;
+
+    // an untyped literal is treated as the default integer type
+    let x_ty = if matches!(x_ty, TastType::Int) {
+        TastType::I32
+    } else {
+        x_ty
+    };
+
+    Ok(TypedExpr {
+        inferred_type: TastType::Usize,
+        kind: TypedExprKind::AlignOf(x_ty).in_span(expr_span),
+    })
+}
+
+/// Typeck a `rotate_left` or `rotate_right` call
+fn type_rotate<'input>(
+    scope: &mut Scope<'input>,
+    expr_span: Span,
+    builtin: Builtin,
+    rotation: Rotation,
+    args: Spanned<Vec<Expr<'input>>>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    expect_argument_count(builtin, expr_span, &args, 2)?;
+
+    let mut args = args.into_value().into_iter();
+    let x = args.next().expect("argument count was checked");
+    let amount = args.next().expect("argument count was checked");
+
+    let x_span = x.0.span();
+    let x_t = type_expr(scope, x)?;
+    expect_is_integer(&x_t.inferred_type, x_span)?;
+
+    let amount_span = amount.0.span();
+    let amount_t = type_expr(scope, amount)?;
+    expect_is_integer(&amount_t.inferred_type, amount_span)?;
+
+    // an untyped literal is treated as the default integer type
+    let x_t = if matches!(x_t.inferred_type, TastType::Int) {
+        TypedExpr {
+            inferred_type: TastType::I32,
+            kind: x_t.kind,
+        }
+    } else {
+        x_t
+    };
+
+    // the amount may be any integer type, as it is converted to the width of `x`
+    // when generating code, but an untyped amount simply takes the type of `x`
+    let amount_t = if matches!(amount_t.inferred_type, TastType::Int) {
+        try_coerce_to(amount_t, &x_t.inferred_type)
+    } else {
+        amount_t
+    };
+
+    Ok(TypedExpr {
+        inferred_type: x_t.inferred_type.clone(),
+        kind: TypedExprKind::Rotate(rotation, Box::new(x_t), Box::new(amount_t)).in_span(expr_span),
+    })
+}
+
+/// Typeck a `len` call, which is replaced by the number of elements of its
+/// array argument
+fn type_len<'input>(
+    scope: &mut Scope<'input>,
+    expr_span: Span,
+    builtin: Builtin,
+    args: Spanned<Vec<Expr<'input>>>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    expect_argument_count(builtin, expr_span, &args, 1)?;
+
+    let x = args
+        .into_value()
+        .into_iter()
+        .next()
+        .expect("argument count was checked");
+    let x_span = x.0.span();
+
+    // like `sizeof(expr)`, the length is known from the type alone, so the
+    // expression is never evaluated
+    let x_ty = type_expr(scope, x)?.inferred_type;
+    let TastType::Array { size, .. } = x_ty else {
+        return Err(x_span
+            .error(DiagnosticKind::ExpectedGot {
+                expected: "array".to_string(),
+                got: x_ty.to_string(),
+            })
+            .with_label(GenericLabel::error(
+                LabelKind::ExpectedGot {
+                    expected: "array".to_string(),
+                    got: x_ty.to_string(),
+                }
+                .in_span(x_span),
+            ))
+            .with_note(NoteKind::BuiltinSignature(builtin.signature().to_string())));
+    };
+
+    Ok(leaked_number_literal(
+        i128::from(size),
+        &TastType::Usize,
+        expr_span,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::DiagnosticKind;
+    use zrc_parser::lexer::NumberLiteral;
+
+    use super::*;
+    use crate::typeck::scope::GlobalScope;
+
+    /// Type check an expression in a fresh scope
+    fn type_in_fresh_scope(input: &str) -> Result<TastType<'_>, DiagnosticKind> {
+        type_expr(
+            &mut GlobalScope::new().create_subscope(),
+            zrc_parser::parser::parse_expr(input, "<test>").expect("parsing should succeed"),
+        )
+        .map(|result| result.inferred_type)
+        .map_err(|diagnostic| diagnostic.kind.into_value())
+    }
+
+    #[test]
+    fn integer_conversions_infer_target_type() {
+        assert_eq!(type_in_fresh_scope("truncate(1i32, u8)"), Ok(TastType::U8));
+        assert_eq!(
+            type_in_fresh_scope("sign_extend(1i8, i64)"),
+            Ok(TastType::I64)
+        );
+        assert_eq!(
+            type_in_fresh_scope("zero_extend(1u16, u32)"),
+            Ok(TastType::U32)
+        );
+        // untyped literals are treated as `i32`
+        assert_eq!(type_in_fresh_scope("truncate(300, i8)"), Ok(TastType::I8));
+    }
+
+    #[test]
+    fn integer_conversions_must_change_width_in_the_right_direction() {
+        assert_eq!(
+            type_in_fresh_scope("truncate(1i8, i32)"),
+            Err(DiagnosticKind::InvalidIntegerConversion {
+                builtin: "truncate".to_string(),
+                from: "i8".to_string(),
+                to: "i32".to_string(),
+                relation: "narrower".to_string(),
+            })
+        );
+        assert_eq!(
+            type_in_fresh_scope("sign_extend(1i32, i32)"),
+            Err(DiagnosticKind::InvalidIntegerConversion {
+                builtin: "sign_extend".to_string(),
+                from: "i32".to_string(),
+                to: "i32".to_string(),
+                relation: "wider".to_string(),
+            })
+        );
+        assert_eq!(
+            type_in_fresh_scope("zero_extend(1u64, u8)"),
+            Err(DiagnosticKind::InvalidIntegerConversion {
+                builtin: "zero_extend".to_string(),
+                from: "u64".to_string(),
+                to: "u8".to_string(),
+                relation: "wider".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn alignof_expr_is_usize() {
+        assert_eq!(
+            type_in_fresh_scope("alignof_expr(1i64)"),
+            Ok(TastType::Usize)
+        );
+        assert_eq!(type_in_fresh_scope("alignof_expr(7)"), Ok(TastType::Usize));
+        assert_eq!(
+            type_in_fresh_scope("alignof_expr(1i8, 2i8)"),
+            Err(DiagnosticKind::FunctionArgumentCountMismatch {
+                expected: "1".to_string(),
+                got: "2".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn rotates_keep_the_type_of_their_value() {
+        assert_eq!(
+            type_in_fresh_scope("rotate_left(1u32, 3u8)"),
+            Ok(TastType::U32)
+        );
+        assert_eq!(
+            type_in_fresh_scope("rotate_right(1u64, 3)"),
+            Ok(TastType::U64)
+        );
+        // untyped literals are treated as `i32`
+        assert_eq!(type_in_fresh_scope("rotate_left(1, 3)"), Ok(TastType::I32));
+    }
+
+    #[test]
+    fn rotates_reject_invalid_operands() {
+        assert_eq!(
+            type_in_fresh_scope("rotate_left(true, 1)"),
+            Err(DiagnosticKind::ExpectedGot {
+                expected: "integer".to_string(),
+                got: "bool".to_string(),
+            })
+        );
+        assert_eq!(
+            type_in_fresh_scope("rotate_right(1u8, false)"),
+            Err(DiagnosticKind::ExpectedGot {
+                expected: "integer".to_string(),
+                got: "bool".to_string(),
+            })
+        );
+        assert_eq!(
+            type_in_fresh_scope("rotate_left(1u8)"),
+            Err(DiagnosticKind::FunctionArgumentCountMismatch {
+                expected: "2".to_string(),
+                got: "1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn integer_conversions_reject_invalid_operands() {
+        assert_eq!(
+            type_in_fresh_scope("truncate(true, i8)"),
+            Err(DiagnosticKind::ExpectedGot {
+                expected: "integer".to_string(),
+                got: "bool".to_string(),
+            })
+        );
+        assert_eq!(
+            type_in_fresh_scope("truncate(1i32, bool)"),
+            Err(DiagnosticKind::ExpectedGot {
+                expected: "integer".to_string(),
+                got: "bool".to_string(),
+            })
+        );
+        assert_eq!(
+            type_in_fresh_scope("truncate(1u64, usize)"),
+            Err(DiagnosticKind::ExpectedGot {
+                expected: "fixed-width integer".to_string(),
+                got: "usize".to_string(),
+            })
+        );
+        assert_eq!(
+            type_in_fresh_scope("truncate(1i32)"),
+            Err(DiagnosticKind::FunctionArgumentCountMismatch {
+                expected: "2".to_string(),
+                got: "1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn len_is_the_element_count_of_an_array() {
+        let len = type_expr(
+            &mut GlobalScope::new().create_subscope(),
+            zrc_parser::parser::parse_expr("len([1, 2, 3, 4, 5])", "<test>")
+                .expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        assert_eq!(len.inferred_type, TastType::Usize);
+        assert_eq!(
+            len.kind.into_value(),
+            TypedExprKind::NumberLiteral(NumberLiteral::Decimal("5"), TastType::Usize)
+        );
+    }
+
+    #[test]
+    fn len_requires_an_array() {
+        assert_eq!(
+            type_in_fresh_scope("len(\"abc\")"),
+            Err(DiagnosticKind::ExpectedGot {
+                expected: "array".to_string(),
+                got: "*u8".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn disabled_builtins_resolve_like_other_identifiers() {
+        let type_with_disabled = |disabled_builtins, input| {
+            type_expr(
+                &mut GlobalScope {
+                    disabled_builtins,
+                    ..GlobalScope::new()
+                }
+                .create_subscope(),
+                zrc_parser::parser::parse_expr(input, "<test>").expect("parsing should succeed"),
+            )
+            .map(|result| result.inferred_type)
+            .map_err(|diagnostic| diagnostic.kind.into_value())
+        };
+
+        let only_len = DisabledBuiltins::Named(vec!["len".to_string()]);
+        assert_eq!(
+            type_with_disabled(only_len.clone(), "len([1, 2])"),
+            Err(DiagnosticKind::UnableToResolveIdentifier("len".to_string()))
+        );
+        assert_eq!(
+            type_with_disabled(only_len, "truncate(1i32, u8)"),
+            Ok(TastType::U8)
+        );
+        assert_eq!(
+            type_with_disabled(DisabledBuiltins::All, "truncate(1i32, u8)"),
+            Err(DiagnosticKind::UnableToResolveIdentifier(
+                "truncate".to_string()
+            ))
+        );
+    }
+}
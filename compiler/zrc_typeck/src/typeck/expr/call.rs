@@ -1,36 +1,354 @@
 //! type checking for call expressions
 
-use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, SpanExt, diagnostic::GenericLabel};
-use zrc_parser::ast::expr::Expr;
+use zrc_diagnostics::{
+    Diagnostic, DiagnosticKind, LabelKind, NoteKind, SpanExt, diagnostic::GenericLabel,
+};
+use zrc_parser::ast::{
+    expr::{Expr, ExprKind},
+    stmt::ArgumentDeclarationList as AstArgumentDeclarationList,
+};
 use zrc_utils::span::{Span, Spannable, Spanned};
 
 use super::{
-    super::scope::Scope,
+    super::{
+        declaration::is_comptime_parameter,
+        scope::{ComptimeValue, Scope},
+    },
+    builtins::{builtin_for_callee, type_builtin_call},
+    consteval::{fold_bool, fold_integer},
     helpers::{expr_to_place, try_coerce_to},
+    literals::type_expr_identifier,
     type_expr,
 };
 use crate::tast::{
-    expr::{TypedExpr, TypedExprKind},
-    stmt::ArgumentDeclarationList,
+    expr::{Place, PlaceKind, TypedExpr, TypedExprKind},
+    stmt::{ArgumentDeclaration, ArgumentDeclarationList, Deprecation},
     ty::{Fn, Type as TastType},
 };
 
+/// Warn if the callee of a call is a function marked `deprecated`
+fn warn_if_deprecated<'input>(scope: &Scope<'input>, callee: &TypedExpr<'input>) {
+    let TypedExprKind::Identifier(name) = *callee.kind.value() else {
+        return;
+    };
+
+    let Some(Deprecation { message }) = scope
+        .values
+        .resolve(name)
+        .and_then(|entry| entry.borrow().deprecated.clone())
+    else {
+        return;
+    };
+
+    let span = callee.kind.span();
+    let mut warning = DiagnosticKind::DeprecatedFunction(name.to_string())
+        .warning_in(span)
+        .with_label(GenericLabel::warning(
+            LabelKind::DeprecatedFunction(name.to_string()).in_span(span),
+        ));
+    if let Some(message) = message {
+        warning = warning.with_note(NoteKind::DeprecationMessage(message));
+    }
+
+    scope.warn(warning);
+}
+
+/// The path of `place` if it is a variable or a field of one, like `x.y`, which
+/// always refer to the same memory no matter how often they are evaluated
+fn fixed_place_path(place: &Place<'_>) -> Option<String> {
+    match place.kind.value() {
+        PlaceKind::Variable(name) => Some((*name).to_string()),
+        PlaceKind::Dot(inner, field) => Some(format!("{}.{field}", fixed_place_path(inner)?)),
+        PlaceKind::Deref(_) | PlaceKind::Index(..) => None,
+    }
+}
+
+/// The pointer an argument obviously evaluates to, like `p` or `&x.y`, as it
+/// is written.
+///
+/// Returns [`None`] where two arguments cannot easily be shown to be the same
+/// pointer, such as calls or indexing.
+#[expect(clippy::wildcard_enum_match_arm)]
+fn obvious_pointer(arg: &TypedExpr<'_>) -> Option<String> {
+    match arg.kind.value() {
+        TypedExprKind::Identifier(name) => Some((*name).to_string()),
+        TypedExprKind::UnaryAddressOf(place) => Some(format!("&{}", fixed_place_path(place)?)),
+        _ => None,
+    }
+}
+
+/// Warn if the same pointer is passed to two `noalias` parameters, like
+/// `f(p, p)`. Only obvious cases are caught, see [`obvious_pointer`].
+fn warn_if_noalias_arguments_alias<'input>(
+    scope: &Scope<'input>,
+    parameters: &[ArgumentDeclaration<'input>],
+    args: &[TypedExpr<'input>],
+) {
+    let noalias_args: Vec<(&TypedExpr, String)> = parameters
+        .iter()
+        .zip(args)
+        .filter(|(parameter, _)| parameter.noalias)
+        .filter_map(|(_, arg)| Some((arg, obvious_pointer(arg)?)))
+        .collect();
+
+    for (i, (first, pointer)) in noalias_args.iter().enumerate() {
+        // each later argument is reported against the first one it aliases
+        if noalias_args[..i]
+            .iter()
+            .any(|(_, earlier)| earlier == pointer)
+        {
+            continue;
+        }
+
+        for (second, _) in noalias_args[i + 1..]
+            .iter()
+            .filter(|(_, later)| later == pointer)
+        {
+            scope.warn(
+                DiagnosticKind::AliasedNoaliasArguments(pointer.clone())
+                    .warning_in(second.kind.span())
+                    .with_label(GenericLabel::warning(
+                        LabelKind::AliasedNoaliasArgument.in_span(first.kind.span()),
+                    ))
+                    .with_label(GenericLabel::warning(
+                        LabelKind::AliasedNoaliasArgument.in_span(second.kind.span()),
+                    ))
+                    .with_note(NoteKind::NoaliasArgumentsMustNotAlias),
+            );
+        }
+    }
+}
+
+/// The callee and arguments a method call is rewritten to
+type MethodCall<'input> = (Expr<'input>, Spanned<Vec<Expr<'input>>>);
+
+/// If `f` is `obj.name` and `name` is a method of the type of `obj`, rewrite
+/// `obj.name(args)` to a call of the function the method desugars to, passing
+/// `obj` (or `&obj` or `*obj`, to match the receiver) as the first argument
+fn resolve_method_call<'input>(
+    scope: &mut Scope<'input>,
+    f: &Expr<'input>,
+    args: &Spanned<Vec<Expr<'input>>>,
+) -> Result<Option<MethodCall<'input>>, Diagnostic> {
+    let ExprKind::Dot(obj, name) = f.0.value() else {
+        return Ok(None);
+    };
+    if !scope
+        .methods
+        .iter()
+        .any(|method| method.name == *name.value())
+    {
+        return Ok(None);
+    }
+
+    // `obj` is typed again as the receiver argument, this is only to find the
+    // type the method must be defined on
+    #[expect(clippy::wildcard_enum_match_arm)]
+    let (ty, is_pointer) = match type_expr(scope, (**obj).clone())?.inferred_type {
+        TastType::Ptr(pointee) => (*pointee, true),
+        ty => (ty, false),
+    };
+
+    // fields are accessed as usual, even if they share a name with a method
+    if let TastType::Struct(fields) = &ty
+        && fields.get(name.value()).is_some()
+    {
+        return Ok(None);
+    }
+
+    let Some(method) = scope.methods.iter().find(|method| {
+        method.name == *name.value() && scope.types.resolve(method.type_name.value()) == Some(&ty)
+    }) else {
+        return Ok(None);
+    };
+
+    let obj_span = obj.0.span();
+    let receiver = match (method.by_pointer, is_pointer) {
+        (true, false) => Expr(ExprKind::UnaryAddressOf(obj.clone()).in_span(obj_span)),
+        (false, true) => Expr(ExprKind::UnaryDereference(obj.clone()).in_span(obj_span)),
+        (true, true) | (false, false) => (**obj).clone(),
+    };
+
+    Ok(Some((
+        Expr(ExprKind::Identifier(method.function).in_span(f.0.span())),
+        std::iter::once(receiver)
+            .chain(args.value().iter().cloned())
+            .collect::<Vec<_>>()
+            .in_span(args.span()),
+    )))
+}
+
+/// Reject a use of the function `name` other than as the callee of a call, if
+/// it has `comptime` parameters. Only direct calls are rewritten to a
+/// specialization, and the function itself is never emitted.
+///
+/// # Errors
+/// Errors with [`DiagnosticKind::ComptimeFunctionNotCalled`] if `name` refers
+/// to a function with `comptime` parameters.
+pub fn reject_comptime_function_value<'input>(
+    scope: &Scope<'input>,
+    span: Span,
+    name: &'input str,
+) -> Result<(), Diagnostic> {
+    // a local of the same name shadows the function
+    let Some(entry) = scope.values.resolve(name) else {
+        return Ok(());
+    };
+    let declaration_span = entry.borrow().declaration_span;
+    if !scope
+        .comptime_functions
+        .iter()
+        .any(|function| *function.name.value() == name && function.name.span() == declaration_span)
+    {
+        return Ok(());
+    }
+
+    Err(DiagnosticKind::ComptimeFunctionNotCalled(name.to_string())
+        .error_in(span)
+        .with_label(GenericLabel::error(
+            LabelKind::ComptimeFunctionNotCalled.in_span(span),
+        )))
+}
+
+/// The callee, arguments and typed arguments of a call
+type CallParts<'input> = (
+    TypedExpr<'input>,
+    Spanned<Vec<Expr<'input>>>,
+    Vec<TypedExpr<'input>>,
+);
+
+/// If `ft` is a function with `comptime` parameters, check that the argument
+/// of each is a constant, then rewrite the call to the specialization of the
+/// function for those constants, passing only the other arguments
+fn specialize_comptime_call<'input>(
+    scope: &Scope<'input>,
+    ft: TypedExpr<'input>,
+    args: Spanned<Vec<Expr<'input>>>,
+    args_t: Vec<TypedExpr<'input>>,
+) -> Result<CallParts<'input>, Diagnostic> {
+    let TypedExprKind::Identifier(name) = *ft.kind.value() else {
+        return Ok((ft, args, args_t));
+    };
+    let Some(function) = scope
+        .comptime_functions
+        .iter()
+        .find(|function| *function.name.value() == name)
+    else {
+        return Ok((ft, args, args_t));
+    };
+    let TastType::Fn(Fn {
+        arguments: ArgumentDeclarationList::NonVariadic(parameters),
+        returns,
+    }) = &ft.inferred_type
+    else {
+        return Ok((ft, args, args_t));
+    };
+    // let the usual checks report the wrong number of arguments
+    if parameters.len() != args_t.len() {
+        return Ok((ft, args, args_t));
+    }
+
+    let (AstArgumentDeclarationList::NonVariadic(declared)
+    | AstArgumentDeclarationList::Variadic(declared)) = function.parameters.value();
+    let is_comptime = declared
+        .iter()
+        .map(|parameter| is_comptime_parameter(parameter.value()))
+        .collect::<Vec<_>>();
+
+    let mut constants = Vec::new();
+    for ((parameter, arg_t), _) in parameters
+        .iter()
+        .zip(&args_t)
+        .zip(&is_comptime)
+        .filter(|(_, is_comptime)| **is_comptime)
+    {
+        let value = if *parameter.ty.value() == TastType::Bool {
+            fold_bool(arg_t).map(ComptimeValue::Bool)
+        } else {
+            fold_integer(arg_t).map(ComptimeValue::Integer)
+        };
+        let Some(value) = value else {
+            let span = arg_t.kind.span();
+            return Err(DiagnosticKind::ComptimeArgumentNotConstant(
+                parameter.name.value().to_string(),
+            )
+            .error_in(span)
+            .with_label(GenericLabel::error(
+                LabelKind::ComptimeArgumentNotConstant.in_span(span),
+            )));
+        };
+        constants.push(value);
+    }
+
+    let runtime_parameters = parameters
+        .iter()
+        .zip(&is_comptime)
+        .filter(|(_, is_comptime)| !**is_comptime)
+        .map(|(parameter, _)| parameter.clone())
+        .collect();
+    let specialized = TypedExpr {
+        inferred_type: TastType::Fn(Fn {
+            arguments: ArgumentDeclarationList::NonVariadic(runtime_parameters),
+            returns: returns.clone(),
+        }),
+        kind: TypedExprKind::Identifier(scope.specialize(name, constants)).in_span(ft.kind.span()),
+    };
+
+    let args_span = args.span();
+    let (args, args_t) = args
+        .into_value()
+        .into_iter()
+        .zip(args_t)
+        .zip(&is_comptime)
+        .filter(|(_, is_comptime)| !**is_comptime)
+        .map(|(arg, _)| arg)
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+
+    Ok((specialized, args.in_span(args_span), args_t))
+}
+
 /// Typeck a call expr
-#[expect(clippy::needless_pass_by_value, clippy::too_many_lines)]
+#[expect(clippy::too_many_lines)]
 pub fn type_expr_call<'input>(
     scope: &mut Scope<'input>,
     expr_span: Span,
     f: Expr<'input>,
     args: Spanned<Vec<Expr<'input>>>,
 ) -> Result<TypedExpr<'input>, Diagnostic> {
+    if let Some(builtin) = builtin_for_callee(scope, &f) {
+        return type_builtin_call(scope, expr_span, builtin, args);
+    }
+
+    let (f, args) = resolve_method_call(scope, &f, &args)?.unwrap_or((f, args));
+
     let f_span = f.0.span();
-    let ft = type_expr(scope, f)?;
+    // a function with `comptime` parameters may only be named by a call
+    let ft = if let ExprKind::Identifier(name) = *f.0.value() {
+        type_expr_identifier(scope, f_span, name)?
+    } else {
+        type_expr(scope, f)?
+    };
+    warn_if_deprecated(scope, &ft);
+
+    // calling a function pointer like `p()` calls the function it points to,
+    // as if it were written `(*p)()`
+    let ft = if let TastType::Ptr(pointee) = &ft.inferred_type
+        && let TastType::Fn(_) = **pointee
+    {
+        TypedExpr {
+            inferred_type: (**pointee).clone(),
+            kind: TypedExprKind::UnaryDereference(Box::new(ft)).in_span(f_span),
+        }
+    } else {
+        ft
+    };
     let args_span = args.span();
     let args_t = args
         .value()
         .iter()
         .map(|x| type_expr(scope, x.clone()))
         .collect::<Result<Vec<TypedExpr>, Diagnostic>>()?;
+    let (ft, args, args_t) = specialize_comptime_call(scope, ft, args, args_t)?;
 
     #[expect(clippy::wildcard_enum_match_arm)]
     match ft.inferred_type.clone() {
@@ -74,6 +392,8 @@ pub fn type_expr_call<'input>(
                 }
             }
 
+            warn_if_noalias_arguments_alias(scope, &arg_types, &args_t);
+
             // Insert implicit casts where needed
             let args_with_casts = arg_types
                 .iter()
@@ -142,6 +462,8 @@ pub fn type_expr_call<'input>(
                 }
             }
 
+            warn_if_noalias_arguments_alias(scope, &beginning_arg_types, &args_t);
+
             // Insert implicit casts where needed for non-variadic arguments
             let mut args_with_casts = Vec::new();
             for (arg_type, arg_t) in beginning_arg_types.iter().zip(args_t.iter()) {
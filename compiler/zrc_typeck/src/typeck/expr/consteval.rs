@@ -0,0 +1,290 @@
+//! Folding of constant integer expressions
+//!
+//! Some checks, like rejecting `arr[4]` on a `[4]i32`, can only be made when a
+//! value is known at compile time. [`fold_integer`] computes such values from
+//! the TAST without generating any code.
+//!
+//! Folding follows the same two's complement semantics as the generated code,
+//! so `100i8 + 100i8` folds to `-56` like it would evaluate at runtime. Whether
+//! that wrap is allowed is decided by the caller through
+//! [`overflowing_fold`].
+
+use zrc_parser::{
+    ast::{
+        expr::{Arithmetic, Comparison, Equality, Expr, Logical},
+        ty::Type,
+    },
+    lexer::NumberLiteral,
+};
+use zrc_utils::span::{Span, Spannable};
+
+use crate::tast::{
+    expr::{TypedExpr, TypedExprKind},
+    ty::Type as TastType,
+};
+
+/// The range of values of an integer type, or [`None`] if it depends on the
+/// target (`usize` and `isize`) or is not yet known (`{int}`)
+#[expect(clippy::wildcard_enum_match_arm)]
+pub fn bounds(ty: &TastType<'_>) -> Option<(i128, i128)> {
+    match ty {
+        TastType::I8 => Some((i8::MIN.into(), i8::MAX.into())),
+        TastType::U8 => Some((u8::MIN.into(), u8::MAX.into())),
+        TastType::I16 => Some((i16::MIN.into(), i16::MAX.into())),
+        TastType::U16 => Some((u16::MIN.into(), u16::MAX.into())),
+        TastType::I32 => Some((i32::MIN.into(), i32::MAX.into())),
+        TastType::U32 => Some((u32::MIN.into(), u32::MAX.into())),
+        TastType::I64 => Some((i64::MIN.into(), i64::MAX.into())),
+        TastType::U64 => Some((u64::MIN.into(), u64::MAX.into())),
+        _ => None,
+    }
+}
+
+/// Wrap `value` around into the range of `ty`, as the generated code would
+fn wrap(value: i128, ty: &TastType<'_>) -> i128 {
+    let Some((min, max)) = bounds(ty) else {
+        return value;
+    };
+    (value - min).rem_euclid(max - min + 1) + min
+}
+
+/// The digits of the magnitude of `value`. Number literals hold their text as
+/// a `&str` borrowed from the source, so text made up by the compiler is leaked
+/// to live as long.
+fn leaked_digits<'input>(value: i128) -> &'input str {
+    Box::leak(value.unsigned_abs().to_string().into_boxed_str())
+}
+
+/// Build the literal `value` of type `ty`, which is the negation of its
+/// magnitude if it is negative. This is the inverse of [`fold_integer`].
+pub fn leaked_number_literal<'input>(
+    value: i128,
+    ty: &TastType<'input>,
+    span: Span,
+) -> TypedExpr<'input> {
+    let literal = TypedExpr {
+        inferred_type: ty.clone(),
+        kind: TypedExprKind::NumberLiteral(
+            NumberLiteral::Decimal(leaked_digits(value)),
+            ty.clone(),
+        )
+        .in_span(span),
+    };
+    if value < 0 {
+        TypedExpr {
+            inferred_type: ty.clone(),
+            kind: TypedExprKind::UnaryMinus(Box::new(literal)).in_span(span),
+        }
+    } else {
+        literal
+    }
+}
+
+/// Build the AST for the literal `value`, like [`leaked_number_literal`], for
+/// code that is desugared before it is type checked. The magnitude is written
+/// with the type `ty` if there is one, like `-(2i8)`, so the negation is not
+/// inferred as an `i32`.
+pub fn leaked_number_expr(value: i128, ty: Option<Type<'_>>, span: Span) -> Expr<'_> {
+    let literal = Expr::build_number_dec(
+        leaked_digits(value).in_span(span),
+        ty.map(|ty| ty.in_span(span)),
+    );
+    if value < 0 {
+        Expr::build_neg(span, literal)
+    } else {
+        literal
+    }
+}
+
+/// Evaluate `expr` if it is an integer expression built only from literals,
+/// unary minus and arithmetic, wrapping every intermediate result into its
+/// type.
+///
+/// Returns the value along with whether any step overflowed, like
+/// [`i32::overflowing_add`]. Values of `usize` and `isize` are not wrapped, as
+/// their width depends on the target. Returns [`None`] if `expr` is not
+/// constant or cannot be evaluated, such as on a division by zero.
+#[expect(clippy::wildcard_enum_match_arm)]
+pub fn overflowing_fold(expr: &TypedExpr<'_>) -> Option<(i128, bool)> {
+    let (exact, overflowed) = match expr.kind.value() {
+        TypedExprKind::NumberLiteral(n, _) => (literal_value(n)?, false),
+        // `-2147483648` is the minimum of an `i32`, even though its literal
+        // alone is out of range, so it is negated before it is wrapped
+        TypedExprKind::UnaryMinus(x) if let TypedExprKind::NumberLiteral(n, _) = x.kind.value() => {
+            (literal_value(n)?.checked_neg()?, false)
+        }
+        TypedExprKind::UnaryMinus(x) => {
+            let (x, overflowed) = overflowing_fold(x)?;
+            (x.checked_neg()?, overflowed)
+        }
+        TypedExprKind::Arithmetic(op, lhs, rhs) => {
+            let (lhs, lhs_overflowed) = overflowing_fold(lhs)?;
+            let (rhs, rhs_overflowed) = overflowing_fold(rhs)?;
+            let exact = match op {
+                Arithmetic::Addition => lhs.checked_add(rhs),
+                Arithmetic::Subtraction => lhs.checked_sub(rhs),
+                Arithmetic::Multiplication => lhs.checked_mul(rhs),
+                Arithmetic::Division => lhs.checked_div(rhs),
+                Arithmetic::Modulo => lhs.checked_rem(rhs),
+            }?;
+            (exact, lhs_overflowed || rhs_overflowed)
+        }
+        _ => return None,
+    };
+
+    let wrapped = wrap(exact, &expr.inferred_type);
+    Some((wrapped, overflowed || wrapped != exact))
+}
+
+/// The exact value of the number literal `n`, before it is wrapped into its
+/// type
+fn literal_value(n: &NumberLiteral<'_>) -> Option<i128> {
+    i128::from_str_radix(&n.text_content().replace('_', ""), n.radix()).ok()
+}
+
+/// Evaluate `expr` if it is an integer expression built only from literals,
+/// unary minus and arithmetic.
+///
+/// Overflow wraps around, see [`overflowing_fold`].
+pub fn fold_integer(expr: &TypedExpr<'_>) -> Option<i128> {
+    overflowing_fold(expr).map(|(value, _)| value)
+}
+
+/// Evaluate `expr` if it is a boolean expression whose value is known at
+/// compile time.
+///
+/// This covers boolean literals, `!`, `&&` and `||` on constants, comparisons
+/// of [constant integers](fold_integer), and comparisons of a variable with
+/// itself, like `x == x`.
+#[expect(clippy::wildcard_enum_match_arm)]
+pub fn fold_bool(expr: &TypedExpr<'_>) -> Option<bool> {
+    match expr.kind.value() {
+        TypedExprKind::BooleanLiteral(value) => Some(*value),
+        TypedExprKind::UnaryNot(x) => fold_bool(x).map(|x| !x),
+        TypedExprKind::Logical(op, lhs, rhs) => {
+            let (lhs, rhs) = (fold_bool(lhs)?, fold_bool(rhs)?);
+            Some(match op {
+                Logical::And => lhs && rhs,
+                Logical::Or => lhs || rhs,
+            })
+        }
+        TypedExprKind::Equality(op, lhs, rhs) => {
+            let equal = if is_same_variable(lhs, rhs) {
+                true
+            } else if let (Some(lhs), Some(rhs)) = (fold_integer(lhs), fold_integer(rhs)) {
+                lhs == rhs
+            } else {
+                fold_bool(lhs)? == fold_bool(rhs)?
+            };
+            Some(match op {
+                Equality::Eq => equal,
+                Equality::Neq => !equal,
+            })
+        }
+        TypedExprKind::Comparison(op, lhs, rhs) => {
+            let ordering = if is_same_variable(lhs, rhs) {
+                std::cmp::Ordering::Equal
+            } else {
+                fold_integer(lhs)?.cmp(&fold_integer(rhs)?)
+            };
+            Some(match op {
+                Comparison::Gt => ordering.is_gt(),
+                Comparison::Gte => ordering.is_ge(),
+                Comparison::Lt => ordering.is_lt(),
+                Comparison::Lte => ordering.is_le(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Determine if `lhs` and `rhs` are the same variable, which always compares
+/// equal to itself
+fn is_same_variable(lhs: &TypedExpr<'_>, rhs: &TypedExpr<'_>) -> bool {
+    matches!(
+        (lhs.kind.value(), rhs.kind.value()),
+        (TypedExprKind::Identifier(lhs), TypedExprKind::Identifier(rhs)) if lhs == rhs
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use zrc_diagnostics::DiagnosticKind;
+    use zrc_parser::parser::parse_expr;
+
+    use super::overflowing_fold;
+    use crate::{
+        tast::ty::Type as TastType,
+        typeck::{
+            GlobalScope,
+            expr::{try_coerce_to, type_expr},
+        },
+    };
+
+    /// Type check `code` as an expression with the given overflow checks mode,
+    /// then fold it
+    fn fold(code: &str, overflow_checks: bool) -> Result<Option<(i128, bool)>, DiagnosticKind> {
+        let global_scope = GlobalScope {
+            overflow_checks,
+            ..GlobalScope::new()
+        };
+        type_expr(
+            &mut global_scope.create_subscope(),
+            parse_expr(code, "<test>").expect("parsing should succeed"),
+        )
+        .map(|expr| overflowing_fold(&expr))
+        .map_err(|diagnostic| diagnostic.kind.into_value())
+    }
+
+    #[test]
+    fn constant_overflow_errors_under_checked_mode() {
+        assert_eq!(
+            fold("100i8 + 100i8", true),
+            Err(DiagnosticKind::ConstantOverflow("i8".to_string()))
+        );
+        assert_eq!(
+            fold("0u8 - 1", true),
+            Err(DiagnosticKind::ConstantOverflow("u8".to_string()))
+        );
+        assert_eq!(fold("100i8 + 27i8", true), Ok(Some((127, false))));
+    }
+
+    #[test]
+    fn negated_literals_are_folded_exactly() {
+        // TEST: the minimum of a type does not overflow, as its literal is
+        // negated before it is wrapped
+        assert_eq!(
+            fold("-2147483648 + 1", true),
+            Ok(Some((i128::from(i32::MIN) + 1, false)))
+        );
+        assert_eq!(
+            fold("-2147483648 - 1", true),
+            Err(DiagnosticKind::ConstantOverflow("i32".to_string()))
+        );
+
+        // TEST: so does `-128` once it is coerced to an `i8`
+        let min = try_coerce_to(
+            type_expr(
+                &mut GlobalScope::new().create_subscope(),
+                parse_expr("-128", "<test>").expect("parsing should succeed"),
+            )
+            .expect("typeck should succeed"),
+            &TastType::I8,
+        );
+        assert_eq!(overflowing_fold(&min), Some((i8::MIN.into(), false)));
+    }
+
+    #[test]
+    fn constant_overflow_wraps_under_unchecked_mode() {
+        assert_eq!(fold("100i8 + 100i8", false), Ok(Some((-56, true))));
+        assert_eq!(fold("0u8 - 1", false), Ok(Some((255, true))));
+        assert_eq!(
+            fold("(100i8 + 100i8) - 100i8", false),
+            Ok(Some((100, true)))
+        );
+        assert_eq!(
+            fold("2147483647 + 1", false),
+            Ok(Some((i32::MIN.into(), true)))
+        );
+    }
+}
@@ -1,6 +1,8 @@
 //! helper tools used in the type-checking of expressions
 
-use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, SpanExt, diagnostic::GenericLabel};
+use zrc_diagnostics::{
+    Diagnostic, DiagnosticKind, HelpKind, LabelKind, SpanExt, diagnostic::GenericLabel,
+};
 use zrc_parser::ast::expr::{Assignment, Expr, ExprKind};
 use zrc_utils::span::{Span, Spannable};
 
@@ -9,7 +11,10 @@ use crate::{
         expr::{Place, PlaceKind, TypedExpr, TypedExprKind},
         ty::Type as TastType,
     },
-    typeck::Scope,
+    typeck::{
+        Scope,
+        expr::consteval::{bounds, fold_integer},
+    },
 };
 
 /// Desugar an assignment like `x += y` to `x = x + y`.
@@ -41,12 +46,60 @@ pub fn desugar_assignment<'input>(
     }
 }
 
-/// Validate an expr into a place
+/// Validate an expr into a place that will be written to or have its address
+/// taken, rejecting places that are (part of) a constant.
 pub fn expr_to_place<'input>(
     scope: &Scope<'input>,
     span: Span,
     expr: TypedExpr<'input>,
 ) -> Result<Place<'input>, Diagnostic> {
+    let stringified = expr.inferred_type.to_string();
+    let place = expr_to_readable_place(span, expr)?;
+
+    if let Some(constant) = constant_of_place(scope, &place) {
+        let kind_span = place.kind.span();
+        return Err(DiagnosticKind::AssignmentToConstant(constant.to_string())
+            .error_in(kind_span)
+            .with_label(GenericLabel::error(
+                LabelKind::AssignmentToConstant(constant.to_string()).in_span(kind_span),
+            ))
+            .with_label(GenericLabel::note(
+                LabelKind::InferredType(stringified).in_span(span),
+            )));
+    }
+
+    Ok(place)
+}
+
+/// Find the constant that a place is (part of), if any
+///
+/// Writing through a pointer never modifies the pointer itself, so only
+/// variables and the fields and array elements stored inside of them count.
+#[expect(clippy::wildcard_enum_match_arm)]
+fn constant_of_place<'input>(scope: &Scope<'input>, place: &Place<'input>) -> Option<&'input str> {
+    match place.kind.value() {
+        PlaceKind::Variable(x) => scope
+            .values
+            .resolve(x)
+            .is_some_and(|entry| entry.borrow().is_constant)
+            .then_some(*x),
+        PlaceKind::Dot(inner, _) => constant_of_place(scope, inner),
+        // arrays are indexed through a pointer to their own storage
+        PlaceKind::Index(base, _) => match base.kind.value() {
+            TypedExprKind::UnaryAddressOf(inner)
+                if matches!(inner.inferred_type, TastType::Array { .. }) =>
+            {
+                constant_of_place(scope, inner)
+            }
+            _ => None,
+        },
+        PlaceKind::Deref(_) => None,
+    }
+}
+
+/// Validate an expr into a place that will only be read from, like the array in
+/// `x[y]` or the struct in `x.y`. Constants are allowed.
+pub fn expr_to_readable_place(span: Span, expr: TypedExpr<'_>) -> Result<Place<'_>, Diagnostic> {
     let kind_span = expr.kind.span();
     let stringified = expr.inferred_type.to_string();
 
@@ -56,22 +109,6 @@ pub fn expr_to_place<'input>(
             inferred_type: expr.inferred_type,
             kind: PlaceKind::Deref(x).in_span(kind_span),
         },
-        TypedExprKind::Identifier(x)
-            if scope
-                .values
-                .resolve(x)
-                .is_some_and(|entry| entry.borrow().is_constant) =>
-        {
-            return Err(DiagnosticKind::AssignmentToConstant(x.to_string())
-                .error_in(kind_span)
-                .with_label(GenericLabel::error(
-                    LabelKind::AssignmentToConstant(x.to_string()).in_span(kind_span),
-                ))
-                .with_label(GenericLabel::note(
-                    LabelKind::InferredType(stringified).in_span(span),
-                )));
-        }
-        // not constant
         TypedExprKind::Identifier(x) => Place {
             inferred_type: expr.inferred_type,
             kind: PlaceKind::Variable(x).in_span(kind_span),
@@ -95,6 +132,162 @@ pub fn expr_to_place<'input>(
     })
 }
 
+/// Determine if an expression is an integer literal written without a type
+/// suffix, which may take on any integer type.
+fn is_untyped_integer_literal(expr: &TypedExpr<'_>) -> bool {
+    #[expect(clippy::wildcard_enum_match_arm)]
+    match expr.kind.value() {
+        TypedExprKind::NumberLiteral(_, TastType::Int) => true,
+        TypedExprKind::UnaryMinus(inner) => is_untyped_integer_literal(inner),
+        _ => false,
+    }
+}
+
+/// Check that `expr`, if it is an [untyped integer
+/// literal](is_untyped_integer_literal), fits in the integer type `target`.
+///
+/// Without this, `let x: u8 = 300;` would silently wrap to `44`, as `{int}`
+/// implicitly casts to every integer type.
+///
+/// # Errors
+/// Errors with [`DiagnosticKind::NumberLiteralOutOfBounds`] if the literal does
+/// not fit in `target`.
+pub fn check_literal_fits(expr: &TypedExpr<'_>, target: &TastType<'_>) -> Result<(), Diagnostic> {
+    if !is_untyped_integer_literal(expr) {
+        return Ok(());
+    }
+    let (Some(value), Some((min, max))) = (fold_integer(expr), bounds(target)) else {
+        return Ok(());
+    };
+    if (min..=max).contains(&value) {
+        return Ok(());
+    }
+
+    let span = expr.kind.span();
+    Err(DiagnosticKind::NumberLiteralOutOfBounds(
+        value.to_string(),
+        target.to_string(),
+        min.to_string(),
+        max.to_string(),
+    )
+    .error_in(span)
+    .with_label(GenericLabel::error(
+        LabelKind::NumberLiteralOutOfBounds(
+            value.to_string(),
+            target.to_string(),
+            min.to_string(),
+            max.to_string(),
+        )
+        .in_span(span),
+    )))
+}
+
+/// Give an [untyped integer literal](is_untyped_integer_literal) the type `ty`.
+///
+/// Every node of a negated literal like `-1` is retyped, not only the outer
+/// one, as the code generator builds the negation in the type of its operand.
+fn retype_untyped_integer_literal<'input>(
+    expr: TypedExpr<'input>,
+    ty: &TastType<'input>,
+) -> TypedExpr<'input> {
+    let span = expr.kind.span();
+
+    #[expect(clippy::wildcard_enum_match_arm)]
+    let kind = match expr.kind.into_value() {
+        TypedExprKind::UnaryMinus(inner) => {
+            TypedExprKind::UnaryMinus(Box::new(retype_untyped_integer_literal(*inner, ty)))
+        }
+        kind => kind,
+    };
+
+    TypedExpr {
+        inferred_type: ty.clone(),
+        kind: kind.in_span(span),
+    }
+}
+
+/// Give the untyped integer literals of an array literal or array repeat
+/// expression the element type of `target`, so that `let x: [2]u8 = [1, 2];`
+/// is accepted even though `[1, 2]` alone is a `[2]i32`.
+///
+/// Expressions that are not array literals, or elements that cannot take on the
+/// element type, are left as-is so that the caller reports the mismatch. Like
+/// [`try_coerce_to`], every coerced element keeps its own span.
+///
+/// # Errors
+/// Errors with [`DiagnosticKind::NumberLiteralOutOfBounds`] in the span of the
+/// element if a literal does not fit in the element type.
+pub fn coerce_array_literal<'input>(
+    expr: TypedExpr<'input>,
+    target: &TastType<'input>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    let TastType::Array { element_type, .. } = target else {
+        return Ok(expr);
+    };
+    let span = expr.kind.span();
+
+    // an untyped literal element takes on the element type, if it fits in it
+    let coerce_element = |element: TypedExpr<'input>| {
+        if element_type.is_integer() && is_untyped_integer_literal(&element) {
+            check_literal_fits(&element, element_type)?;
+            Ok(retype_untyped_integer_literal(element, element_type))
+        } else {
+            coerce_array_literal(element, element_type)
+        }
+    };
+
+    #[expect(clippy::wildcard_enum_match_arm)]
+    Ok(match expr.kind.into_value() {
+        TypedExprKind::ArrayLiteral(elements) => {
+            let elements = elements
+                .into_iter()
+                .map(coerce_element)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let all_coerced = elements
+                .iter()
+                .all(|element| element.inferred_type == **element_type);
+
+            #[expect(clippy::wildcard_enum_match_arm)]
+            let inferred_type = match expr.inferred_type {
+                TastType::Array { size, .. } if all_coerced => TastType::Array {
+                    size,
+                    element_type: element_type.clone(),
+                },
+                other => other,
+            };
+
+            TypedExpr {
+                inferred_type,
+                kind: TypedExprKind::ArrayLiteral(elements).in_span(span),
+            }
+        }
+        TypedExprKind::ArrayRepeat(value, count) => {
+            let value = coerce_element(*value)?;
+
+            #[expect(clippy::wildcard_enum_match_arm)]
+            let inferred_type = match expr.inferred_type {
+                TastType::Array { size, .. } if value.inferred_type == **element_type => {
+                    TastType::Array {
+                        size,
+                        element_type: element_type.clone(),
+                    }
+                }
+                other => other,
+            };
+
+            TypedExpr {
+                inferred_type,
+                kind: TypedExprKind::ArrayRepeat(Box::new(value), count).in_span(span),
+            }
+        }
+        kind => TypedExpr {
+            inferred_type: expr.inferred_type,
+            kind: kind.in_span(span),
+        },
+    })
+}
+
 /// Assert two types are the same and produce a validation error otherwise
 #[cfg(test)]
 pub fn expect_identical_types<'a, 'input>(
@@ -182,6 +375,9 @@ pub fn expect_is_signed_integer(
 /// If the expression type is `{int}`, it will be resolved to the target type.
 /// Returns the coerced expression if successful, or the original if types
 /// already match.
+///
+/// No node is wrapped around a coerced expression, only its type changes, so it
+/// keeps its original span for diagnostics and debug info.
 pub fn try_coerce_to<'input>(
     expr: TypedExpr<'input>,
     target_type: &TastType<'input>,
@@ -198,6 +394,25 @@ pub fn try_coerce_to<'input>(
     }
 }
 
+/// Suggest an explicit cast on a diagnostic about a value of type `got` being
+/// used where `expected` is required, if the two are only compatible through
+/// `as`.
+///
+/// `bool` never implicitly becomes an integer, so `let x: i32 = a < b;` is
+/// pointed towards `(a < b) as i32`.
+#[must_use]
+pub fn with_explicit_cast_help(
+    diagnostic: Diagnostic,
+    got: &TastType<'_>,
+    expected: &TastType<'_>,
+) -> Diagnostic {
+    if *got == TastType::Bool && expected.is_integer() {
+        diagnostic.with_help(HelpKind::ConsiderCasting(expected.to_string()))
+    } else {
+        diagnostic
+    }
+}
+
 /// Resolve binary operands for operations that require matching types.
 /// Returns a tuple of (`result_type`, lhs, rhs) where both operands have been
 /// coerced to a compatible type. If both are `{int}`, they resolve to `i32`.
@@ -245,7 +460,7 @@ mod tests {
     };
 
     use super::*;
-    use crate::tast::ty::Type as TastType;
+    use crate::{tast::ty::Type as TastType, typeck::scope::GlobalScope};
 
     #[test]
     fn expect_identical_types_produces_proper_diagnostic() {
@@ -290,6 +505,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn coercion_keeps_the_original_span() {
+        let literal = TypedExpr {
+            inferred_type: TastType::Int,
+            kind: spanned_test!(
+                4,
+                TypedExprKind::NumberLiteral(
+                    zrc_parser::lexer::NumberLiteral::Decimal("7"),
+                    TastType::Int
+                ),
+                5
+            ),
+        };
+
+        let coerced = try_coerce_to(literal.clone(), &TastType::U8);
+        assert_eq!(coerced.inferred_type, TastType::U8);
+        assert_eq!(coerced.kind, literal.kind);
+    }
+
+    #[test]
+    fn negated_literals_in_arrays_are_retyped_throughout() {
+        /// Type `source` and coerce it to an array of `element_type`,
+        /// returning its first element
+        fn first_element<'input>(
+            source: &'input str,
+            element_type: TastType<'input>,
+        ) -> TypedExpr<'input> {
+            let global_scope = GlobalScope::new();
+            let array = crate::typeck::type_expr(
+                &mut global_scope.create_subscope(),
+                zrc_parser::parser::parse_expr(source, "<test>").expect("parsing should succeed"),
+            )
+            .expect("typeck should succeed");
+            let target = TastType::Array {
+                size: 2,
+                element_type: Box::new(element_type),
+            };
+
+            #[expect(clippy::wildcard_enum_match_arm)]
+            match coerce_array_literal(array, &target)
+                .expect("coercion should succeed")
+                .kind
+                .into_value()
+            {
+                TypedExprKind::ArrayLiteral(elements) => elements[0].clone(),
+                TypedExprKind::ArrayRepeat(value, _) => *value,
+                other => panic!("expected an array, got {other:?}"),
+            }
+        }
+
+        // TEST: both the negation and the literal it negates take on the element
+        // type, for literals and repeats alike
+        for (source, element_type) in [
+            ("[-1, 2]", TastType::I8),
+            ("[-1, 2]", TastType::I64),
+            ("[-1; 2]", TastType::I8),
+            ("[-1; 2]", TastType::I64),
+        ] {
+            let element = first_element(source, element_type.clone());
+            assert_eq!(element.inferred_type, element_type, "{source}");
+            let TypedExprKind::UnaryMinus(literal) = element.kind.into_value() else {
+                panic!("`-1` should be a negation");
+            };
+            assert_eq!(literal.inferred_type, element_type, "{source}");
+        }
+    }
+
     mod desugar_assignment {
         use super::*;
 
@@ -1,13 +1,15 @@
 //! type checking for literal expressions
 
-use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, NoteKind, diagnostic::GenericLabel};
+use zrc_diagnostics::{
+    Diagnostic, DiagnosticKind, HelpKind, LabelKind, NoteKind, diagnostic::GenericLabel,
+};
 use zrc_parser::{
     ast::{expr::Expr as AstExpr, ty::Type},
     lexer::{NumberLiteral, StringTok, ZrcString},
 };
 use zrc_utils::span::{Span, Spannable};
 
-use super::super::scope::Scope;
+use super::{super::scope::Scope, consteval::fold_integer, helpers::expr_to_place};
 use crate::{
     tast::{
         expr::{TypedExpr, TypedExprKind},
@@ -154,6 +156,23 @@ pub fn type_expr_identifier<'input>(
         base
     })?;
 
+    if ty_rc.borrow().awaiting_assignment {
+        return Err(DiagnosticKind::UsedBeforeTypeInferred(i.to_string())
+            .error_in(expr_span)
+            .with_label(GenericLabel::error(
+                LabelKind::UsedBeforeTypeInferred.in_span(expr_span),
+            )));
+    }
+
+    if ty_rc.borrow().awaiting_return_type {
+        return Err(DiagnosticKind::ReturnTypeNotInferred(i.to_string())
+            .error_in(expr_span)
+            .with_label(GenericLabel::error(
+                LabelKind::ReturnTypeNotInferred.in_span(expr_span),
+            ))
+            .with_help(HelpKind::DeclareReturnTypeOf(i.to_string())));
+    }
+
     // Mark as used by adding the reference span and clone the type to return. Use a
     // short-lived borrow so we don't keep the RefCell borrow across the
     // function return.
@@ -199,7 +218,8 @@ pub fn type_expr_array_literal<'input>(
 
     let mut typed_elements = Vec::with_capacity(elements_vec.len());
     for elem in elements_vec {
-        typed_elements.push(super::type_expr(scope, elem)?);
+        let elem = super::type_expr(scope, elem)?;
+        typed_elements.push(decay_function(scope, elem)?);
     }
 
     let mut element_type = typed_elements[0].inferred_type.clone();
@@ -242,6 +262,74 @@ pub fn type_expr_array_literal<'input>(
         kind: TypedExprKind::ArrayLiteral(typed_elements).in_span(expr_span),
     })
 }
+
+/// Decay an array element of function type to a pointer to the function, as
+/// if it were written `&f`, so `[f, g]` builds a table of function pointers.
+/// Functions themselves are not values that can be stored.
+fn decay_function<'input>(
+    scope: &Scope<'input>,
+    elem: TypedExpr<'input>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    if !matches!(elem.inferred_type, TastType::Fn(_)) {
+        return Ok(elem);
+    }
+
+    let span = elem.kind.span();
+    let place = expr_to_place(scope, span, elem)?;
+    Ok(TypedExpr {
+        inferred_type: TastType::Ptr(Box::new(place.inferred_type.clone())),
+        kind: TypedExprKind::UnaryAddressOf(Box::new(place)).in_span(span),
+    })
+}
+
+/// Typeck an array repeat expression like `[0; 8]`, an array of `count` copies
+/// of `value`
+pub fn type_expr_array_repeat<'input>(
+    scope: &mut Scope<'input>,
+    expr_span: Span,
+    value: AstExpr<'input>,
+    count: AstExpr<'input>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    let count_span = count.0.span();
+    let count = super::type_expr(scope, count)?;
+
+    let Some(size) = count
+        .inferred_type
+        .is_integer()
+        .then(|| fold_integer(&count))
+        .flatten()
+        .and_then(|size| u64::try_from(size).ok())
+    else {
+        return Err(DiagnosticKind::NonConstantArrayRepeatCount
+            .error_in(count_span)
+            .with_label(GenericLabel::error(
+                LabelKind::NonConstantArrayRepeatCount.in_span(count_span),
+            ))
+            .with_note(NoteKind::ArrayRepeatCountIsConstant));
+    };
+
+    if size == 0 {
+        return Err(DiagnosticKind::EmptyArrayLiteral
+            .error_in(expr_span)
+            .with_label(GenericLabel::error(
+                LabelKind::EmptyArrayLiteral.in_span(expr_span),
+            )));
+    }
+
+    let mut value = super::type_expr(scope, value)?;
+    if value.inferred_type == TastType::Int {
+        value = super::try_coerce_to(value, &TastType::I32);
+    }
+
+    Ok(TypedExpr {
+        inferred_type: TastType::Array {
+            size,
+            element_type: Box::new(value.inferred_type.clone()),
+        },
+        kind: TypedExprKind::ArrayRepeat(Box::new(value), size).in_span(expr_span),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use zrc_parser::{ast::ty::Type as AstType, lexer::NumberLiteral};
@@ -420,6 +508,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_number_literal_octal() {
+        let global_scope = GlobalScope::new();
+        let scope = global_scope.create_subscope();
+        let span = spanned_test!(0, (), 5).span();
+
+        // Test valid octal
+        assert!(
+            type_expr_number_literal(
+                &scope,
+                span,
+                NumberLiteral::Octal("377"),
+                Some(make_ast_type("u8"))
+            )
+            .is_ok()
+        );
+
+        // Test invalid octal
+        let result = type_expr_number_literal(
+            &scope,
+            span,
+            NumberLiteral::Octal("400"),
+            Some(make_ast_type("u8")),
+        );
+        if let Err(diagnostic) = result {
+            assert_eq!(
+                diagnostic.kind.into_value(),
+                DiagnosticKind::NumberLiteralOutOfBounds(
+                    "0o400".to_string(),
+                    "u8".to_string(),
+                    "0".to_string(),
+                    "255".to_string()
+                )
+            );
+        } else {
+            panic!("Expected error for octal u8 overflow");
+        }
+    }
+
     #[test]
     fn regression_656_invalid_number_literals_dont_panic() {
         let global_scope = GlobalScope::new();
@@ -442,4 +569,50 @@ mod tests {
             panic!("Expected error for invalid number literal");
         }
     }
+
+    #[test]
+    fn functions_in_array_literals_decay_to_function_pointers() {
+        let type_of_table = |source: &str| {
+            let program = crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(source, "<test>")
+                    .expect("parsing should succeed"),
+            )
+            .map_err(|diagnostic| diagnostic.kind.into_value())?;
+            Ok::<_, DiagnosticKind>(
+                crate::explain::explain_types(&program, "<test>", source)
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix("// table: "))
+                    .expect("the table should be explained")
+                    .to_string(),
+            )
+        };
+
+        // TEST: the table can be called through after indexing it
+        assert_eq!(
+            type_of_table(concat!(
+                "fn f() -> i32 { return 1; }\n",
+                "fn g() -> i32 { return 2; }\n",
+                "fn test(i: usize) -> i32 {\n",
+                "    let table: [2]*(fn() -> i32) = [f, g];\n",
+                "    return table[i]();\n",
+                "}\n",
+            )),
+            Ok("[2]*(fn() -> i32)".to_string())
+        );
+
+        // TEST: functions with different signatures do not unify
+        assert_eq!(
+            type_of_table(concat!(
+                "fn f() -> i32 { return 1; }\n",
+                "fn g(x: i32) -> i32 { return x; }\n",
+                "fn test() { let table = [f, g]; }\n",
+            )),
+            Err(DiagnosticKind::ArrayElementTypeMismatch {
+                expected: "*(fn() -> i32)".to_string(),
+                found: "*(fn(x: i32) -> i32)".to_string(),
+                index: 1,
+            })
+        );
+    }
 }
@@ -1,21 +1,24 @@
 //! type checking for misc expressions
 
-use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, NoteKind, diagnostic::GenericLabel};
-use zrc_parser::{
-    ast::{expr::Expr, ty::Type},
-    lexer::NumberLiteral,
+use zrc_diagnostics::{
+    Diagnostic, DiagnosticKind, HelpKind, LabelKind, NoteKind, diagnostic::GenericLabel,
 };
-use zrc_utils::span::{Span, Spannable};
+use zrc_parser::ast::{
+    expr::{Expr, ExprKind},
+    ty::{KeyTypeMapping, Type, TypeKind},
+};
+use zrc_utils::span::{Span, Spannable, Spanned};
 
 use super::{
     super::scope::Scope,
+    consteval::leaked_number_literal,
     helpers::{expect, try_coerce_to},
     type_expr,
 };
 use crate::{
     tast::{
         expr::{TypedExpr, TypedExprKind},
-        ty::{OrderedValueFields, Type as TastType},
+        ty::{OrderedTypeFields, OrderedValueFields, Type as TastType},
     },
     typeck::resolve_type,
 };
@@ -35,6 +38,62 @@ pub fn type_expr_comma<'input>(
     })
 }
 
+/// Unify the types of the two values a conditional expression may yield,
+/// coercing one to the type of the other if they differ. If both are `{int}`,
+/// they become `i32`.
+///
+/// # Errors
+/// Errors if neither type can be implicitly cast to the other.
+fn unify_branches<'input>(
+    expr_span: Span,
+    if_true_t: TypedExpr<'input>,
+    if_false_t: TypedExpr<'input>,
+) -> Result<(TastType<'input>, TypedExpr<'input>, TypedExpr<'input>), Diagnostic> {
+    if if_true_t.inferred_type == if_false_t.inferred_type {
+        // Both branches have the same type
+        if matches!(if_true_t.inferred_type, TastType::Int) {
+            // Both are {int}, resolve to i32
+            let if_true_resolved = try_coerce_to(if_true_t, &TastType::I32);
+            let if_false_resolved = try_coerce_to(if_false_t, &TastType::I32);
+            Ok((TastType::I32, if_true_resolved, if_false_resolved))
+        } else {
+            Ok((if_true_t.inferred_type.clone(), if_true_t, if_false_t))
+        }
+    } else if if_true_t
+        .inferred_type
+        .can_implicitly_cast_to(&if_false_t.inferred_type)
+    {
+        // if_true can coerce to if_false type
+        let if_true_coerced = try_coerce_to(if_true_t, &if_false_t.inferred_type);
+        Ok((
+            if_false_t.inferred_type.clone(),
+            if_true_coerced,
+            if_false_t,
+        ))
+    } else if if_false_t
+        .inferred_type
+        .can_implicitly_cast_to(&if_true_t.inferred_type)
+    {
+        // if_false can coerce to if_true type
+        let if_false_coerced = try_coerce_to(if_false_t, &if_true_t.inferred_type);
+        Ok((if_true_t.inferred_type.clone(), if_true_t, if_false_coerced))
+    } else {
+        // Types don't match and can't be implicitly cast
+        Err(DiagnosticKind::ExpectedSameType(
+            if_true_t.inferred_type.to_string(),
+            if_false_t.inferred_type.to_string(),
+        )
+        .error_in(expr_span)
+        .with_label(GenericLabel::error(
+            LabelKind::ExpectedSameType(
+                if_true_t.inferred_type.to_string(),
+                if_false_t.inferred_type.to_string(),
+            )
+            .in_span(expr_span),
+        )))
+    }
+}
+
 /// Typeck a ternary expr
 pub fn type_expr_ternary<'input>(
     scope: &mut Scope<'input>,
@@ -55,51 +114,8 @@ pub fn type_expr_ternary<'input>(
         cond_span,
     )?;
 
-    // Handle {int} type resolution in ternary branches
     let (result_type, if_true_final, if_false_final) =
-        if if_true_t.inferred_type == if_false_t.inferred_type {
-            // Both branches have the same type
-            if matches!(if_true_t.inferred_type, TastType::Int) {
-                // Both are {int}, resolve to i32
-                let if_true_resolved = try_coerce_to(if_true_t, &TastType::I32);
-                let if_false_resolved = try_coerce_to(if_false_t, &TastType::I32);
-                (TastType::I32, if_true_resolved, if_false_resolved)
-            } else {
-                (if_true_t.inferred_type.clone(), if_true_t, if_false_t)
-            }
-        } else if if_true_t
-            .inferred_type
-            .can_implicitly_cast_to(&if_false_t.inferred_type)
-        {
-            // if_true can coerce to if_false type
-            let if_true_coerced = try_coerce_to(if_true_t, &if_false_t.inferred_type);
-            (
-                if_false_t.inferred_type.clone(),
-                if_true_coerced,
-                if_false_t,
-            )
-        } else if if_false_t
-            .inferred_type
-            .can_implicitly_cast_to(&if_true_t.inferred_type)
-        {
-            // if_false can coerce to if_true type
-            let if_false_coerced = try_coerce_to(if_false_t, &if_true_t.inferred_type);
-            (if_true_t.inferred_type.clone(), if_true_t, if_false_coerced)
-        } else {
-            // Types don't match and can't be implicitly cast
-            return Err(DiagnosticKind::ExpectedSameType(
-                if_true_t.inferred_type.to_string(),
-                if_false_t.inferred_type.to_string(),
-            )
-            .error_in(expr_span)
-            .with_label(GenericLabel::error(
-                LabelKind::ExpectedSameType(
-                    if_true_t.inferred_type.to_string(),
-                    if_false_t.inferred_type.to_string(),
-                )
-                .in_span(expr_span),
-            )));
-        };
+        unify_branches(expr_span, if_true_t, if_false_t)?;
 
     Ok(TypedExpr {
         inferred_type: result_type,
@@ -112,7 +128,44 @@ pub fn type_expr_ternary<'input>(
     })
 }
 
+/// Typeck an Elvis expr, `value ?: fallback`
+pub fn type_expr_elvis<'input>(
+    scope: &mut Scope<'input>,
+    expr_span: Span,
+    value: Expr<'input>,
+    fallback: Expr<'input>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    let value_span = value.0.span();
+    let value_t = type_expr(scope, value)?;
+    let fallback_t = type_expr(scope, fallback)?;
+
+    // `value` is tested against zero, which only these types have
+    if !(value_t.inferred_type == TastType::Bool
+        || value_t.inferred_type.is_integer()
+        || matches!(value_t.inferred_type, TastType::Ptr(_)))
+    {
+        return Err(
+            DiagnosticKind::ElvisValueNotTestable(value_t.inferred_type.to_string())
+                .error_in(value_span)
+                .with_label(GenericLabel::error(
+                    LabelKind::ElvisValueNotTestable(value_t.inferred_type.to_string())
+                        .in_span(value_span),
+                )),
+        );
+    }
+
+    let (result_type, value_final, fallback_final) =
+        unify_branches(expr_span, value_t, fallback_t)?;
+
+    Ok(TypedExpr {
+        inferred_type: result_type,
+        kind: TypedExprKind::Elvis(Box::new(value_final), Box::new(fallback_final))
+            .in_span(expr_span),
+    })
+}
+
 /// Typeck a cast expr
+#[expect(clippy::too_many_lines)]
 pub fn type_expr_cast<'input>(
     scope: &mut Scope<'input>,
     expr_span: Span,
@@ -123,6 +176,22 @@ pub fn type_expr_cast<'input>(
     let ty_span = ty.0.span();
     let resolved_ty = resolve_type(scope, ty)?;
 
+    // int -> enum cast could produce a tag that is not a variant. This includes
+    // `{int}`, so it must be checked before `{int}` is resolved below
+    if x_t.inferred_type.is_integer() && resolved_ty.enum_variants().is_some() {
+        return Err(DiagnosticKind::InvalidCast(
+            x_t.inferred_type.to_string(),
+            resolved_ty.to_string(),
+        )
+        .error_in(expr_span)
+        .with_label(GenericLabel::error(
+            LabelKind::InvalidCast(x_t.inferred_type.to_string(), resolved_ty.to_string())
+                .in_span(expr_span),
+        ))
+        .with_note(NoteKind::IntegerToEnumCast)
+        .with_help(HelpKind::ConstructEnumVariant));
+    }
+
     // Handle {int} type resolution
     if matches!(x_t.inferred_type, TastType::Int) {
         if resolved_ty.is_integer() {
@@ -148,8 +217,21 @@ pub fn type_expr_cast<'input>(
 
     if x_t.inferred_type.is_integer() && resolved_ty.is_integer() {
         // int -> int cast is valid
-    } else if let (TastType::Ptr(_), TastType::Ptr(_)) = (&x_t.inferred_type, &resolved_ty) {
-        // *T -> *U cast is valid
+    } else if let (TastType::Ptr(from), TastType::Ptr(to)) = (&x_t.inferred_type, &resolved_ty) {
+        // *T -> *U cast is valid, but reading a struct as a larger one is a common source of
+        // memory corruption
+        if struct_sizes_differ(from, to) {
+            scope.warn(
+                DiagnosticKind::PointerCastBetweenDifferentlySizedStructs {
+                    from: x_t.inferred_type.to_string(),
+                    to: resolved_ty.to_string(),
+                }
+                .warning_in(expr_span)
+                .with_label(GenericLabel::warning(
+                    LabelKind::PointerCastBetweenDifferentlySizedStructs.in_span(expr_span),
+                )),
+            );
+        }
     } else if let (TastType::Ptr(_), _) | (_, TastType::Ptr(_)) = (&x_t.inferred_type, &resolved_ty)
     {
         // ensure one is an int
@@ -168,6 +250,60 @@ pub fn type_expr_cast<'input>(
         }
     } else if x_t.inferred_type == TastType::Bool && resolved_ty.is_integer() {
         // bool -> int cast is valid
+    } else if x_t
+        .inferred_type
+        .enum_tag_type()
+        .is_some_and(|tag| *tag != TastType::Usize)
+        && resolved_ty.is_integer()
+    {
+        // enums with explicit discriminants have a smaller tag than `usize`, and
+        // every discriminant must fit in the integer
+        if let Some((_, discriminant)) = scope
+            .enum_discriminants(&x_t.inferred_type)
+            .expect("enum should have discriminants")
+            .into_iter()
+            .find(|(_, discriminant)| !discriminant_fits_in(*discriminant, &resolved_ty))
+        {
+            return Err(DiagnosticKind::EnumDiscriminantDoesNotFit {
+                from: x_t.inferred_type.to_string(),
+                to: resolved_ty.to_string(),
+                discriminant,
+            }
+            .error_in(expr_span)
+            .with_label(GenericLabel::error(
+                LabelKind::EnumTagDoesNotFit(resolved_ty.to_string()).in_span(ty_span),
+            )));
+        }
+    } else if let Some(variants) = x_t.inferred_type.enum_variants()
+        && resolved_ty.is_integer()
+    {
+        // enum -> int cast yields the tag, so every tag must fit in the integer
+        if !tag_fits_in(variants.len(), &resolved_ty) {
+            return Err(DiagnosticKind::EnumTagDoesNotFit {
+                from: x_t.inferred_type.to_string(),
+                to: resolved_ty.to_string(),
+                variants: variants.len(),
+            }
+            .error_in(expr_span)
+            .with_label(GenericLabel::error(
+                LabelKind::EnumTagDoesNotFit(resolved_ty.to_string()).in_span(ty_span),
+            )));
+        }
+    } else if let (TastType::Struct(from), TastType::Struct(to)) =
+        (&x_t.inferred_type, &resolved_ty)
+    {
+        // struct -> struct cast is a reinterpret, so it is only valid if the layouts are the same
+        if let Some(field) = first_layout_difference(from, to) {
+            return Err(DiagnosticKind::StructLayoutMismatch {
+                from: x_t.inferred_type.to_string(),
+                to: resolved_ty.to_string(),
+                field: field.to_string(),
+            }
+            .error_in(expr_span)
+            .with_label(GenericLabel::error(
+                LabelKind::StructLayoutMismatch(field.to_string()).in_span(expr_span),
+            )));
+        }
     } else {
         return Err(DiagnosticKind::InvalidCast(
             x_t.inferred_type.to_string(),
@@ -186,6 +322,131 @@ pub fn type_expr_cast<'input>(
     })
 }
 
+/// Check if every tag of an enum with `variant_count` variants fits in the
+/// integer type `ty`
+fn tag_fits_in(variant_count: usize, ty: &TastType<'_>) -> bool {
+    // pointer-sized integers can index every variant that could exist
+    let Some(width) = ty.integer_bit_width() else {
+        return true;
+    };
+    let value_bits = if ty.is_signed_integer() {
+        width - 1
+    } else {
+        width
+    };
+
+    variant_count
+        .saturating_sub(1)
+        .checked_shr(value_bits)
+        .is_none_or(|overflow| overflow == 0)
+}
+
+/// Check if the explicit enum discriminant `discriminant` fits in the integer
+/// type `ty`
+fn discriminant_fits_in(discriminant: i128, ty: &TastType<'_>) -> bool {
+    // pointer-sized integers are assumed to hold any 64-bit discriminant
+    let Some(width) = ty.integer_bit_width() else {
+        return discriminant >= 0 || ty.is_signed_integer();
+    };
+
+    if ty.is_signed_integer() {
+        let half = 1_i128 << (width - 1);
+        (-half..half).contains(&discriminant)
+    } else {
+        (0..1_i128 << width).contains(&discriminant)
+    }
+}
+
+/// The size and alignment in bytes of `ty` on a target whose pointers are
+/// `pointer_size` bytes wide, laid out like a C struct as in code generation.
+/// Returns [`None`] for types without a size, like functions.
+fn size_and_alignment(ty: &TastType<'_>, pointer_size: u64) -> Option<(u64, u64)> {
+    match ty {
+        TastType::I8 | TastType::U8 | TastType::Bool => Some((1, 1)),
+        TastType::I16 | TastType::U16 => Some((2, 2)),
+        TastType::I32 | TastType::U32 => Some((4, 4)),
+        TastType::I64 | TastType::U64 => Some((8, 8)),
+        TastType::Usize | TastType::Isize | TastType::Ptr(_) => Some((pointer_size, pointer_size)),
+        TastType::Array { size, element_type } => {
+            let (element_size, alignment) = size_and_alignment(element_type, pointer_size)?;
+            Some((element_size.checked_mul(*size)?, alignment))
+        }
+        TastType::Struct(fields) => {
+            let (size, alignment) =
+                fields
+                    .iter()
+                    .try_fold((0, 1), |(offset, alignment): (u64, u64), (_, field)| {
+                        let (field_size, field_alignment) =
+                            size_and_alignment(field, pointer_size)?;
+                        Some((
+                            offset.next_multiple_of(field_alignment) + field_size,
+                            alignment.max(field_alignment),
+                        ))
+                    })?;
+            Some((size.next_multiple_of(alignment), alignment))
+        }
+        TastType::Union(fields) => {
+            let (size, alignment) =
+                fields
+                    .iter()
+                    .try_fold((0, 1), |(size, alignment): (u64, u64), (_, field)| {
+                        let (field_size, field_alignment) =
+                            size_and_alignment(field, pointer_size)?;
+                        Some((size.max(field_size), alignment.max(field_alignment)))
+                    })?;
+            Some((size.next_multiple_of(alignment), alignment))
+        }
+        TastType::Int | TastType::Fn(_) | TastType::Opaque(_) => None,
+    }
+}
+
+/// Check if `from` and `to` are both non-empty structs whose sizes differ on
+/// both 32-bit and 64-bit targets, so that a `*from` read as a `*to` is
+/// almost certainly a mistake.
+///
+/// `*struct {}` is used as an untyped pointer, so casts to and from it are
+/// never reported.
+fn struct_sizes_differ(from: &TastType<'_>, to: &TastType<'_>) -> bool {
+    let (TastType::Struct(from_fields), TastType::Struct(to_fields)) = (from, to) else {
+        return false;
+    };
+    if from_fields.is_empty() || to_fields.is_empty() {
+        return false;
+    }
+
+    [4, 8].into_iter().all(|pointer_size| {
+        match (
+            size_and_alignment(from, pointer_size),
+            size_and_alignment(to, pointer_size),
+        ) {
+            (Some((from_size, _)), Some((to_size, _))) => from_size != to_size,
+            _ => false,
+        }
+    })
+}
+
+/// Find the first field at which two struct layouts differ, or [`None`] if both
+/// have the same field types in the same order. Field names do not matter.
+fn first_layout_difference<'input>(
+    from: &OrderedTypeFields<'input>,
+    to: &OrderedTypeFields<'input>,
+) -> Option<&'input str> {
+    let mut from_fields = from.iter();
+    let mut to_fields = to.iter();
+
+    loop {
+        match (from_fields.next(), to_fields.next()) {
+            (None, None) => return None,
+            (Some((name, from_ty)), Some((_, to_ty))) => {
+                if from_ty != to_ty {
+                    return Some(name);
+                }
+            }
+            (Some((name, _)), None) | (None, Some((name, _))) => return Some(name),
+        }
+    }
+}
+
 /// Typeck a sizeof T expr
 pub fn type_expr_size_of_type<'input>(
     scope: &Scope<'input>,
@@ -220,9 +481,7 @@ pub fn type_expr_struct_construction<'input>(
     scope: &mut Scope<'input>,
     expr_span: Span,
     ty: Type<'input>,
-    fields: &zrc_utils::span::Spanned<
-        Vec<zrc_utils::span::Spanned<(zrc_utils::span::Spanned<&'input str>, Expr<'input>)>>,
-    >,
+    fields: &Spanned<Vec<Spanned<(Spanned<&'input str>, Expr<'input>)>>>,
 ) -> Result<TypedExpr<'input>, Diagnostic> {
     use zrc_parser::ast::ty::TypeKind as ParserTypeKind;
 
@@ -234,14 +493,7 @@ pub fn type_expr_struct_construction<'input>(
 
     // Check if the resolved type is an enum (desugared into a struct with
     // __discriminant__ and __value__)
-    let is_enum = is_enum_literal
-        || matches!(
-            &resolved_ty,
-            TastType::Struct(fields) if fields.len() == 2
-                && fields.contains_key("__discriminant__")
-                && fields.contains_key("__value__")
-                && matches!(fields.get("__value__"), Some(TastType::Union(_)))
-        );
+    let is_enum = is_enum_literal || resolved_ty.enum_variants().is_some();
 
     // Handle enum construction specially
     if is_enum {
@@ -295,15 +547,14 @@ pub fn type_expr_struct_construction<'input>(
         let (variant_name, variant_expr) = field_init.value();
         let variant_name_str = variant_name.value();
 
-        // Find the discriminant value using ALPHABETICAL ORDER
-        // Sort the variant names to match the discriminant assignment used in match
+        // Find the discriminant value, which must match the one used in match
         // statements
-        let mut sorted_variants: Vec<(&str, &TastType<'_>)> = variant_types.iter().collect();
-        sorted_variants.sort_unstable_by_key(|(name, _)| *name);
-
-        let discriminant = sorted_variants
-            .iter()
-            .position(|(name, _)| *name == *variant_name_str)
+        let discriminant = scope
+            .enum_discriminants(&resolved_ty)
+            .expect("enum should have discriminants")
+            .into_iter()
+            .find(|(name, _)| *name == *variant_name_str)
+            .map(|(_, discriminant)| discriminant)
             .ok_or_else(|| {
                 DiagnosticKind::StructOrUnionDoesNotHaveMember(
                     resolved_ty.to_string(),
@@ -351,18 +602,8 @@ pub fn type_expr_struct_construction<'input>(
         };
 
         // Create the discriminant literal
-        // We need to create a proper NumberLiteral from the lexer
-        // Use Box::leak to create a string with 'static lifetime that can be cast to
-        // 'input
-        let discriminant_str: &'input str = Box::leak(discriminant.to_string().into_boxed_str());
-        let discriminant_expr = TypedExpr {
-            inferred_type: TastType::Usize,
-            kind: TypedExprKind::NumberLiteral(
-                NumberLiteral::Decimal(discriminant_str),
-                TastType::Usize,
-            )
-            .in_span(variant_name.span()),
-        };
+        let tag_ty = resolved_ty.enum_tag_type().expect("enum should have a tag");
+        let discriminant_expr = leaked_number_literal(discriminant, tag_ty, variant_name.span());
 
         // Create the union construction for __value__
         let mut union_fields = OrderedValueFields::new();
@@ -525,6 +766,74 @@ pub fn type_expr_struct_construction<'input>(
     })
 }
 
+/// Typeck an `Enum::Variant` expr, which desugars to `Enum { Variant: struct
+/// {} {} }` for a variant that holds no value
+pub fn type_expr_enum_variant<'input>(
+    scope: &mut Scope<'input>,
+    expr_span: Span,
+    ty: Spanned<&'input str>,
+    variant: Spanned<&'input str>,
+) -> Result<TypedExpr<'input>, Diagnostic> {
+    let enum_ty = Type(TypeKind::Identifier(ty.value()).in_span(ty.span()));
+    let resolved_ty = resolve_type(scope, enum_ty.clone())?;
+
+    let Some(variants) = resolved_ty.enum_variants() else {
+        return Err(DiagnosticKind::VariantOfNonEnum((*ty.value()).to_string())
+            .error_in(expr_span)
+            .with_label(GenericLabel::error(
+                LabelKind::VariantOfNonEnum((*ty.value()).to_string()).in_span(ty.span()),
+            )));
+    };
+
+    let Some(variant_ty) = variants.get(variant.value()) else {
+        return Err(DiagnosticKind::UnknownEnumVariant {
+            ty: (*ty.value()).to_string(),
+            variant: (*variant.value()).to_string(),
+        }
+        .error_in(variant.span())
+        .with_label(GenericLabel::error(
+            LabelKind::UnknownEnumVariant((*variant.value()).to_string()).in_span(variant.span()),
+        )));
+    };
+
+    if !matches!(variant_ty, TastType::Struct(fields) if fields.is_empty()) {
+        return Err(DiagnosticKind::EnumVariantHoldsValue {
+            variant: (*variant.value()).to_string(),
+            ty: variant_ty.to_string(),
+        }
+        .error_in(expr_span)
+        .with_label(GenericLabel::error(
+            LabelKind::EnumVariantHoldsValue(variant_ty.to_string()).in_span(variant.span()),
+        ))
+        .with_help(HelpKind::ConstructEnumVariantWithValue {
+            ty: (*ty.value()).to_string(),
+            variant: (*variant.value()).to_string(),
+        }));
+    }
+
+    let empty_struct = Expr(
+        ExprKind::StructConstruction(
+            Type(
+                TypeKind::Struct(KeyTypeMapping(Vec::new().in_span(variant.span())))
+                    .in_span(variant.span()),
+            ),
+            Vec::new().in_span(variant.span()),
+        )
+        .in_span(variant.span()),
+    );
+
+    type_expr(
+        scope,
+        Expr(
+            ExprKind::StructConstruction(
+                enum_ty,
+                vec![(variant, empty_struct).in_span(expr_span)].in_span(expr_span),
+            )
+            .in_span(expr_span),
+        ),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use zrc_parser::{ast::expr::Expr, lexer::NumberLiteral};
@@ -559,4 +868,238 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn structs_with_identical_layouts_can_be_cast() {
+        let type_check = |code: &str| {
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(code, "<test>").expect("parsing should succeed"),
+            )
+            .map(|_| ())
+            .map_err(|diagnostic| diagnostic.kind.into_value())
+        };
+
+        assert_eq!(
+            type_check(
+                "struct Meters { value: i32, precision: u8 }
+                 struct Feet { amount: i32, digits: u8 }
+                 fn convert(m: Meters) -> Feet { return m as Feet; }"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            type_check(
+                "struct Meters { value: i32, precision: u8 }
+                 struct Wide { value: i64, precision: u8 }
+                 fn convert(m: Meters) -> Wide { return m as Wide; }"
+            ),
+            Err(DiagnosticKind::StructLayoutMismatch {
+                from: "struct { value: i32, precision: u8 }".to_string(),
+                to: "struct { value: i64, precision: u8 }".to_string(),
+                field: "value".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn enums_cast_to_integers_that_hold_their_tag() {
+        let type_check = |code: &str| {
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(code, "<test>").expect("parsing should succeed"),
+            )
+            .map(|_| ())
+            .map_err(|diagnostic| diagnostic.kind.into_value())
+        };
+
+        assert_eq!(
+            type_check(
+                "enum Color { Red: u8, Green: u8, Blue: u8 }
+                 fn tag(c: Color) -> u8 { return c as u8; }
+                 fn wide_tag(c: Color) -> i64 { return c as i64; }"
+            ),
+            Ok(())
+        );
+
+        // TEST: 200 variants need tags up to 199, which do not fit in an i8
+        let variants = (0..200)
+            .map(|n| format!("V{n}: u8"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let many_variants = format!(
+            "enum Many {{ {variants} }}
+             fn tag(m: Many) -> u8 {{ return m as u8; }}
+             fn signed_tag(m: Many) -> i8 {{ return m as i8; }}"
+        );
+        let Err(DiagnosticKind::EnumTagDoesNotFit { to, variants, .. }) =
+            type_check(&many_variants)
+        else {
+            panic!("expected the tag not to fit in an i8");
+        };
+        assert_eq!((to.as_str(), variants), ("i8", 200));
+    }
+
+    #[test]
+    fn enums_with_explicit_discriminants_cast_to_integers_that_hold_them() {
+        let type_check = |code: &str| {
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(code, "<test>").expect("parsing should succeed"),
+            )
+            .map(|_| ())
+            .map_err(|diagnostic| diagnostic.kind.into_value())
+        };
+
+        assert_eq!(
+            type_check(
+                "enum E { A = 1, B = 200 }
+                 fn tag(e: E) -> u8 { return e as u8; }"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            type_check(
+                "enum E { A = 1, B = 200 }
+                 fn tag(e: E) -> i8 { return e as i8; }"
+            ),
+            Err(DiagnosticKind::EnumDiscriminantDoesNotFit {
+                from: concat!(
+                    "struct { __discriminant__: u8, __value__: union { A: struct {}, B: struct {} }, ",
+                    "__enum__: struct { E: struct {} } }"
+                )
+                .to_string(),
+                to: "i8".to_string(),
+                discriminant: 200,
+            })
+        );
+    }
+
+    #[test]
+    fn enum_variant_paths_construct_variants_without_values() {
+        let type_check = |code: &str| {
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(code, "<test>").expect("parsing should succeed"),
+            )
+            .map(|program| {
+                program
+                    .iter()
+                    .map(|declaration| declaration.value().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .map_err(|diagnostic| diagnostic.kind.into_value())
+        };
+        let declarations = "enum Color { Red, Green = 5, Blue }
+            enum Status { Ok, Failed: *u8 }
+            struct Point { x: i32 }\n";
+
+        // TEST: `Color::Blue` is the same as constructing the variant with an
+        // empty struct
+        assert_eq!(
+            type_check(&format!(
+                "{declarations}fn f() -> Color {{ return Color::Blue; }}"
+            )),
+            type_check(&format!(
+                "{declarations}fn f() -> Color {{ return Color {{ Blue: struct {{}} {{}} }}; }}"
+            ))
+        );
+
+        assert_eq!(
+            type_check(&format!(
+                "{declarations}fn f() -> Color {{ return Color::Purple; }}"
+            )),
+            Err(DiagnosticKind::UnknownEnumVariant {
+                ty: "Color".to_string(),
+                variant: "Purple".to_string(),
+            })
+        );
+        assert_eq!(
+            type_check(&format!(
+                "{declarations}fn f() -> Status {{ return Status::Failed; }}"
+            )),
+            Err(DiagnosticKind::EnumVariantHoldsValue {
+                variant: "Failed".to_string(),
+                ty: "*u8".to_string(),
+            })
+        );
+        assert_eq!(
+            type_check(&format!("{declarations}fn f() {{ Point::x; }}")),
+            Err(DiagnosticKind::VariantOfNonEnum("Point".to_string()))
+        );
+    }
+
+    #[test]
+    fn integers_cannot_be_cast_to_enums() {
+        let diagnostic = crate::typeck::type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(
+                "enum Color { Red: u8, Green: u8 }
+                 fn from_tag(n: u8) -> Color { return n as Color; }",
+                "<test>",
+            )
+            .expect("parsing should succeed"),
+        )
+        .expect_err("casting an integer to an enum should fail");
+
+        assert!(matches!(
+            diagnostic.kind.value(),
+            DiagnosticKind::InvalidCast(from, _) if from == "u8"
+        ));
+        assert_eq!(diagnostic.notes, vec![NoteKind::IntegerToEnumCast]);
+
+        // TEST: integer literals are rejected too
+        assert!(
+            crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(
+                    "enum Color { Red: u8, Green: u8 }
+                     fn from_literal() -> Color { return 1 as Color; }",
+                    "<test>",
+                )
+                .expect("parsing should succeed"),
+            )
+            .is_err()
+        );
+    }
+
+    /// Type check `body` as the body of a function, returning the warnings it
+    /// produces
+    fn warnings_in(body: &str) -> Vec<DiagnosticKind> {
+        let source = format!(
+            "struct Small {{ x: i32 }}
+            struct Big {{ x: i32, y: i64 }}
+            struct Pair {{ a: u16, b: u16 }}
+            fn f(small: *Small, big: *Big, pair: *Pair, bytes: *u8) {{ {body} }}"
+        );
+        let mut global_scope = GlobalScope::new();
+        crate::typeck::type_program(
+            &mut global_scope,
+            zrc_parser::parser::parse_program(&source, "<test>").expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        global_scope
+            .take_warnings()
+            .into_iter()
+            .map(|warning| warning.kind.into_value())
+            .collect()
+    }
+
+    #[test]
+    fn casting_between_pointers_to_differently_sized_structs_warns() {
+        assert_eq!(
+            warnings_in("small as *Big;"),
+            vec![DiagnosticKind::PointerCastBetweenDifferentlySizedStructs {
+                from: "*struct { x: i32 }".to_string(),
+                to: "*struct { x: i32, y: i64 }".to_string(),
+            }]
+        );
+        assert_eq!(warnings_in("big as *Small;").len(), 1);
+
+        // TEST: same-size structs, primitives and untyped pointers stay quiet
+        assert_eq!(warnings_in("small as *Pair;"), vec![]);
+        assert_eq!(warnings_in("big as *u8; bytes as *Big;"), vec![]);
+        assert_eq!(warnings_in("(big as *struct {}) as *Small;"), vec![]);
+    }
 }
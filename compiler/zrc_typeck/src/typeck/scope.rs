@@ -6,9 +6,19 @@ use std::{
     rc::Rc,
 };
 
-use zrc_utils::span::Span;
+use derive_more::Display;
+use zrc_diagnostics::Diagnostic;
+use zrc_parser::ast::{
+    stmt::{ArgumentDeclarationList, Attribute, Stmt},
+    ty::Type,
+};
+use zrc_utils::span::{Span, Spanned};
 
-use crate::tast::ty::{FunctionDeclarationGlobalMetadata, OrderedTypeFields, Type as TastType};
+use super::{cfg::CfgOptions, expr::DisabledBuiltins};
+use crate::tast::{
+    stmt::Deprecation,
+    ty::{FunctionDeclarationGlobalMetadata, OrderedTypeFields, Type as TastType},
+};
 
 /// Represents a typing scope: a scope that contains the mapping from a type's
 /// name to its internal [`TastType`] representation.
@@ -93,6 +103,15 @@ impl<'input> TypeCtx<'input> {
     pub fn iter(&self) -> impl Iterator<Item = (&'input str, &TastType<'input>)> {
         self.mappings.iter().map(|(k, v)| (*k, v))
     }
+
+    /// Iterate over the types declared by the program, skipping the primitives
+    pub fn iter_declared(&self) -> impl Iterator<Item = (&'input str, &TastType<'input>)> {
+        self.iter().filter(|(name, _)| {
+            !all_namable_types()
+                .iter()
+                .any(|(primitive, _)| primitive == name)
+        })
+    }
 }
 impl Default for TypeCtx<'static> {
     fn default() -> Self {
@@ -112,6 +131,7 @@ where
 
 /// Represents a singular value in a value context.
 #[derive(Debug, Clone, PartialEq)]
+#[expect(clippy::struct_excessive_bools)] // independent facts about the value
 pub struct ValueEntry<'input> {
     /// The data type of the value
     pub ty: TastType<'input>,
@@ -123,6 +143,20 @@ pub struct ValueEntry<'input> {
     pub declaration_span: Span,
     /// If this value is a constant
     pub is_constant: bool,
+    /// If this value is a function marked `deprecated`, the deprecation to
+    /// warn about at each call site
+    pub deprecated: Option<Deprecation>,
+    /// If this value was declared with neither a type nor a value, and takes
+    /// the type of its first assignment. Until then, `ty` is meaningless.
+    pub awaiting_assignment: bool,
+    /// If this value is a function declared without a return type, which has
+    /// not been inferred from its body yet. Until then, its return type is
+    /// meaningless.
+    pub awaiting_return_type: bool,
+    /// If this value is stored in the stack frame of the function it is
+    /// declared in, like a `let` binding or a parameter, so pointers to it
+    /// dangle once the function returns
+    pub is_local: bool,
 }
 impl<'input> ValueEntry<'input> {
     /// Create a used value entry with an initial reference span
@@ -133,6 +167,10 @@ impl<'input> ValueEntry<'input> {
             referenced_spans: vec![reference_span],
             declaration_span,
             is_constant: false,
+            deprecated: None,
+            awaiting_assignment: false,
+            awaiting_return_type: false,
+            is_local: false,
         }
     }
 
@@ -144,6 +182,10 @@ impl<'input> ValueEntry<'input> {
             referenced_spans: Vec::new(),
             declaration_span,
             is_constant: false,
+            deprecated: None,
+            awaiting_assignment: false,
+            awaiting_return_type: false,
+            is_local: false,
         }
     }
 }
@@ -284,6 +326,68 @@ impl<'input> IntoIterator for ValueCtx<'input> {
     }
 }
 
+/// A method defined in an `impl` block, which is called as `s.name()` and
+/// desugars to a call to a plain function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodEntry<'input> {
+    /// The name of the struct the method is defined on
+    pub type_name: Spanned<&'input str>,
+    /// The name the method is called with
+    pub name: &'input str,
+    /// The name of the function the method desugars to
+    pub function: &'input str,
+    /// If the method takes `*self` instead of `self`
+    pub by_pointer: bool,
+}
+
+/// The discriminants of the variants of a named enum that declares them
+/// explicitly, like `enum E { A = 1, B = 5, C }`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumEntry<'input> {
+    /// The name of the enum
+    pub type_name: Spanned<&'input str>,
+    /// The discriminant of each variant, in declaration order
+    pub discriminants: Vec<(&'input str, i128)>,
+}
+
+/// A function with `comptime` parameters, like `fn shift(comptime n: u32, x:
+/// i32)`. It is not generated itself: every call is instead type checked
+/// against a [`Specialization`] for the constants it passes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComptimeFunction<'input> {
+    /// The attributes written before the function
+    pub attributes: Vec<Spanned<Attribute<'input>>>,
+    /// The name of the function
+    pub name: Spanned<&'input str>,
+    /// The parameters of the function, including the `comptime` ones
+    pub parameters: Spanned<ArgumentDeclarationList<'input>>,
+    /// The declared return type of the function, if any
+    pub return_type: Option<Type<'input>>,
+    /// The body of the function
+    pub body: Spanned<Vec<Stmt<'input>>>,
+}
+
+/// The constant passed to a `comptime` parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum ComptimeValue {
+    /// A constant integer
+    Integer(i128),
+    /// A constant `bool`
+    Bool(bool),
+}
+
+/// A [`ComptimeFunction`] specialized for the constants passed to its
+/// `comptime` parameters by some call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Specialization<'input> {
+    /// The name of the [`ComptimeFunction`]
+    pub function: &'input str,
+    /// The constant passed to each `comptime` parameter, in order
+    pub arguments: Vec<ComptimeValue>,
+    /// The name of the specialized function, like `shift<3>`
+    pub name: &'input str,
+}
+
 /// Represents the "global scope" of a single Zirco program.
 ///
 /// The global scope contains all of the things that must be defined at the
@@ -300,6 +404,37 @@ pub struct GlobalScope<'input> {
 
     /// Contains data about every global [`crate::tast::ty::Fn`]
     pub declarations: HashMap<&'input str, FunctionDeclarationGlobalMetadata<'input>>,
+
+    /// Warnings produced while type checking, shared with every subscope
+    pub warnings: Rc<RefCell<Vec<Diagnostic>>>,
+
+    /// The target that `cfg` attributes are checked against
+    pub cfg: CfgOptions,
+
+    /// Every method defined in an `impl` block
+    pub methods: Vec<MethodEntry<'input>>,
+
+    /// Every enum with explicit discriminants
+    pub enums: Vec<EnumEntry<'input>>,
+
+    /// Every function with `comptime` parameters
+    pub comptime_functions: Vec<ComptimeFunction<'input>>,
+
+    /// The specializations of [`Self::comptime_functions`] called so far,
+    /// shared with every subscope
+    pub specializations: Rc<RefCell<Vec<Specialization<'input>>>>,
+
+    /// Whether a constant integer expression that overflows its type is an
+    /// error, rather than wrapping around like at runtime
+    pub overflow_checks: bool,
+
+    /// The built-ins that are not recognized, so calls to them resolve to
+    /// normal functions
+    pub disabled_builtins: DisabledBuiltins,
+
+    /// Whether function parameters that are never read are warned about, like
+    /// unused local variables always are
+    pub warn_unused_parameters: bool,
 }
 impl<'input> GlobalScope<'input> {
     /// Create a new [`GlobalScope`] containing nothing -- not even primitives.
@@ -310,6 +445,15 @@ impl<'input> GlobalScope<'input> {
             types: TypeCtx::new_empty(),
             global_values: ValueCtx::new(),
             declarations: HashMap::new(),
+            warnings: Rc::default(),
+            cfg: CfgOptions::host(),
+            methods: Vec::new(),
+            enums: Vec::new(),
+            comptime_functions: Vec::new(),
+            specializations: Rc::default(),
+            overflow_checks: false,
+            disabled_builtins: DisabledBuiltins::None,
+            warn_unused_parameters: false,
         }
     }
 
@@ -321,6 +465,15 @@ impl<'input> GlobalScope<'input> {
             types: TypeCtx::new(),
             global_values: ValueCtx::new(),
             declarations: HashMap::new(),
+            warnings: Rc::default(),
+            cfg: CfgOptions::host(),
+            methods: Vec::new(),
+            enums: Vec::new(),
+            comptime_functions: Vec::new(),
+            specializations: Rc::default(),
+            overflow_checks: false,
+            disabled_builtins: DisabledBuiltins::None,
+            warn_unused_parameters: false,
         }
     }
 
@@ -329,6 +482,12 @@ impl<'input> GlobalScope<'input> {
     pub fn create_subscope<'gs>(&'gs self) -> Scope<'input> {
         Scope::from_global_scope(self)
     }
+
+    /// Take every warning produced so far, leaving none behind
+    #[must_use]
+    pub fn take_warnings(&self) -> Vec<Diagnostic> {
+        self.warnings.take()
+    }
 }
 impl Default for GlobalScope<'static> {
     fn default() -> Self {
@@ -353,6 +512,34 @@ pub struct Scope<'input> {
     /// Maps every type name from the parent [`GlobalScope`] to its
     /// representation
     pub types: TypeCtx<'input>,
+
+    /// Warnings produced while type checking, shared with the parent
+    /// [`GlobalScope`]
+    pub warnings: Rc<RefCell<Vec<Diagnostic>>>,
+
+    /// Every method from the parent [`GlobalScope`]
+    pub methods: Rc<[MethodEntry<'input>]>,
+
+    /// Every enum with explicit discriminants from the parent [`GlobalScope`]
+    pub enums: Rc<[EnumEntry<'input>]>,
+
+    /// Every function with `comptime` parameters from the parent
+    /// [`GlobalScope`]
+    pub comptime_functions: Rc<[ComptimeFunction<'input>]>,
+
+    /// The specializations called so far, shared with the parent
+    /// [`GlobalScope`]
+    pub specializations: Rc<RefCell<Vec<Specialization<'input>>>>,
+
+    /// Whether constant overflow is an error, from the parent [`GlobalScope`]
+    pub overflow_checks: bool,
+
+    /// The built-ins that are not recognized, from the parent [`GlobalScope`]
+    pub disabled_builtins: Rc<DisabledBuiltins>,
+
+    /// The labels of the loops this scope is inside of, innermost last, which
+    /// `break label;` and `continue label;` may refer to
+    pub loop_labels: Vec<&'input str>,
 }
 impl<'input> Scope<'input> {
     /// Creates a new [`Scope`] from a parent [`GlobalScope`]
@@ -360,6 +547,74 @@ impl<'input> Scope<'input> {
         Scope {
             values: global_scope.global_values.clone(),
             types: global_scope.types.clone(),
+            warnings: Rc::clone(&global_scope.warnings),
+            methods: global_scope.methods.clone().into(),
+            enums: global_scope.enums.clone().into(),
+            comptime_functions: global_scope.comptime_functions.clone().into(),
+            specializations: Rc::clone(&global_scope.specializations),
+            overflow_checks: global_scope.overflow_checks,
+            disabled_builtins: Rc::new(global_scope.disabled_builtins.clone()),
+            loop_labels: Vec::new(),
+        }
+    }
+
+    /// Report a warning, which does not stop type checking
+    pub fn warn(&self, warning: Diagnostic) {
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    /// Get the name of the specialization of the [`ComptimeFunction`]
+    /// `function` for `arguments`, recording it to be generated if no call has
+    /// needed it yet
+    pub fn specialize(&self, function: &'input str, arguments: Vec<ComptimeValue>) -> &'input str {
+        let mut specializations = self.specializations.borrow_mut();
+        if let Some(existing) = specializations
+            .iter()
+            .find(|existing| existing.function == function && existing.arguments == arguments)
+        {
+            return existing.name;
+        }
+
+        // the TAST names functions by a `&str`, which must live as long as the
+        // input
+        let name: &'input str = Box::leak(
+            format!(
+                "{function}<{}>",
+                arguments
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .into_boxed_str(),
+        );
+        specializations.push(Specialization {
+            function,
+            arguments,
+            name,
+        });
+        name
+    }
+
+    /// Get the discriminant of every variant of an enum type, or [`None`] if
+    /// `ty` is not an enum.
+    ///
+    /// Enums declared with explicit discriminants use those. Every other enum
+    /// is tagged in the alphabetical order of its variants, starting at 0.
+    #[must_use]
+    pub fn enum_discriminants(&self, ty: &TastType<'input>) -> Option<Vec<(&'input str, i128)>> {
+        let variants = ty.enum_variants()?;
+
+        if let Some(entry) = self
+            .enums
+            .iter()
+            .find(|entry| self.types.resolve(entry.type_name.value()) == Some(ty))
+        {
+            return Some(entry.discriminants.clone());
         }
+
+        let mut names = variants.iter().map(|(name, _)| name).collect::<Vec<_>>();
+        names.sort_unstable();
+        Some(names.into_iter().zip(0..).collect())
     }
 }
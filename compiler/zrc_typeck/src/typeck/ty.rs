@@ -2,7 +2,7 @@
 
 use zrc_diagnostics::{Diagnostic, DiagnosticKind, LabelKind, NoteKind, diagnostic::GenericLabel};
 use zrc_parser::ast::{
-    stmt::ArgumentDeclarationList as AstADL,
+    stmt::{ArgumentDeclarationList as AstADL, Attribute},
     ty::{KeyTypeMapping, Type as ParserType, TypeKind as ParserTypeKind},
 };
 use zrc_utils::{
@@ -10,12 +10,42 @@ use zrc_utils::{
     span::{Span, Spannable, Spanned},
 };
 
-use super::scope::Scope;
+use super::{
+    block::has_duplicates,
+    declaration::{ParameterAttributes, resolve_parameter_attributes},
+    scope::Scope,
+};
 use crate::tast::{
     stmt::{ArgumentDeclaration, ArgumentDeclarationList},
     ty::{Fn, OrderedTypeFields, Type as TastType},
 };
 
+/// Resolve the attributes of a parameter in a function type, returning whether
+/// it is `noalias`.
+///
+/// # Errors
+/// Errors like [`resolve_parameter_attributes`], or if the parameter is marked
+/// `comptime`, as a function pointer cannot be specialized for each value.
+fn resolve_function_type_parameter_attributes(
+    attributes: &[Spanned<Attribute<'_>>],
+    ty: &Spanned<TastType<'_>>,
+) -> Result<bool, Diagnostic> {
+    match resolve_parameter_attributes(attributes, ty)? {
+        ParameterAttributes {
+            noalias,
+            comptime: None,
+        } => Ok(noalias),
+        ParameterAttributes {
+            comptime: Some(span),
+            ..
+        } => Err(DiagnosticKind::ComptimeParameterInFunctionType
+            .error_in(span)
+            .with_label(GenericLabel::error(
+                LabelKind::ComptimeParameterInFunctionType.in_span(span),
+            ))),
+    }
+}
+
 /// Resolve an identifier to its corresponding [`TastType`].
 ///
 /// # Errors
@@ -77,12 +107,16 @@ pub fn resolve_type<'input>(
                 param_decls
                     .into_iter()
                     .map(|param| {
+                        let attributes = param.value().attributes.clone();
+                        let name = param.value().name;
+                        let ty = param
+                            .map(|param| resolve_type(scope, param.ty))
+                            .transpose()
+                            .map_err(Spanned::into_value)?;
                         Ok(ArgumentDeclaration {
-                            name: param.value().name,
-                            ty: param
-                                .map(|param| resolve_type(scope, param.ty))
-                                .transpose()
-                                .map_err(Spanned::into_value)?,
+                            noalias: resolve_function_type_parameter_attributes(&attributes, &ty)?,
+                            name,
+                            ty,
                         })
                     })
                     .collect::<Result<Vec<ArgumentDeclaration>, Diagnostic>>()?,
@@ -199,12 +233,16 @@ fn resolve_type_with_opaque<'input>(
                 param_decls
                     .into_iter()
                     .map(|param| {
+                        let attributes = param.value().attributes.clone();
+                        let name = param.value().name;
+                        let ty = param
+                            .map(|param| resolve_type_with_opaque(scope, param.ty, opaque_name))
+                            .transpose()
+                            .map_err(Spanned::into_value)?;
                         Ok(ArgumentDeclaration {
-                            name: param.value().name,
-                            ty: param
-                                .map(|param| resolve_type_with_opaque(scope, param.ty, opaque_name))
-                                .transpose()
-                                .map_err(Spanned::into_value)?,
+                            noalias: resolve_function_type_parameter_attributes(&attributes, &ty)?,
+                            name,
+                            ty,
                         })
                     })
                     .collect::<Result<Vec<ArgumentDeclaration>, Diagnostic>>()?,
@@ -327,6 +365,43 @@ fn replace_opaque_with_concrete<'input>(
     }
 }
 
+/// Ensure that no two fields of a struct or union share a name.
+///
+/// # Errors
+/// Errors on the second field of the first name used twice, pointing back to
+/// the first.
+fn check_unique_keys(members: &KeyTypeMapping<'_>) -> Result<(), Diagnostic> {
+    let members = members.0.value();
+    let keys = members
+        .iter()
+        .map(|member| *member.value().0.value())
+        .collect::<Vec<_>>();
+    if !has_duplicates(&keys) {
+        return Ok(());
+    }
+
+    let (duplicate, first) = members
+        .iter()
+        .enumerate()
+        .find_map(|(index, member)| {
+            members[..index]
+                .iter()
+                .find(|earlier| earlier.value().0.value() == member.value().0.value())
+                .map(|first| (member, first))
+        })
+        .expect("a duplicate key should exist");
+    let name = (*duplicate.value().0.value()).to_string();
+
+    Err(DiagnosticKind::DuplicateStructMember(name.clone())
+        .error_in(duplicate.span())
+        .with_label(GenericLabel::error(
+            LabelKind::DuplicateStructMember(name.clone()).in_span(duplicate.span()),
+        ))
+        .with_label(GenericLabel::note(
+            LabelKind::FieldFirstDeclaredHere(name).in_span(first.span()),
+        )))
+}
+
 /// Resolve the types within the fields used by
 /// [`ParserTypeKind::Struct`] and ensure keys are unique, returning the value
 /// to be passed to [`TastType::Struct`].
@@ -337,21 +412,11 @@ pub(super) fn resolve_key_type_mapping<'input>(
     scope: &Scope<'input>,
     members: KeyTypeMapping<'input>,
 ) -> Result<OrderedTypeFields<'input>, Diagnostic> {
+    check_unique_keys(&members)?;
+
     let mut fields = OrderedFields::new();
     for member in members.0.into_value() {
-        let span = member.span();
         let (key, ast_type) = member.into_value();
-
-        if fields.contains_key(key.value()) {
-            return Err(
-                DiagnosticKind::DuplicateStructMember(key.into_value().to_string())
-                    .error_in(span)
-                    .with_label(GenericLabel::error(
-                        LabelKind::DuplicateStructMember(key.into_value().to_string())
-                            .in_span(span),
-                    )),
-            );
-        }
         fields.insert(key.value(), resolve_type(scope, ast_type)?);
     }
     Ok(fields)
@@ -369,21 +434,13 @@ fn resolve_key_type_mapping_with_opaque<'input>(
     members: KeyTypeMapping<'input>,
     opaque_name: &'input str,
 ) -> Result<OrderedTypeFields<'input>, Diagnostic> {
+    check_unique_keys(&members)?;
+
     let mut fields = OrderedTypeFields::new();
     for member in members.0.into_value() {
         let span = member.span();
         let (key, ast_type) = member.into_value();
 
-        if fields.contains_key(key.value()) {
-            return Err(
-                DiagnosticKind::DuplicateStructMember(key.into_value().to_string())
-                    .error_in(span)
-                    .with_label(GenericLabel::error(
-                        LabelKind::DuplicateStructMember(key.into_value().to_string())
-                            .in_span(span),
-                    )),
-            );
-        }
         let resolved_type = resolve_type_with_opaque(scope, ast_type, opaque_name)?;
         // Check this specific field for invalid opaque references
         check_opaque_behind_pointer(&resolved_type, opaque_name, span)?;
@@ -592,10 +649,48 @@ mod tests {
                 17,
                 LabelKind::DuplicateStructMember("x".to_string()),
                 23
+            )))
+            .with_label(GenericLabel::note(spanned_test!(
+                9,
+                LabelKind::FieldFirstDeclaredHere("x".to_string()),
+                15
             ))))
         );
     }
 
+    #[test]
+    fn duplicate_fields_in_struct_and_union_declarations_cause_errors() {
+        for source in [
+            "struct S { x: i32, y: u8, x: i32 }",
+            "union U { x: i32, y: u8, x: u8 }",
+        ] {
+            let diagnostic = crate::typeck::type_program(
+                &mut GlobalScope::new(),
+                zrc_parser::parser::parse_program(source, "<test>")
+                    .expect("parsing should succeed"),
+            )
+            .expect_err("duplicate fields should be rejected");
+
+            assert_eq!(
+                diagnostic.kind.value(),
+                &DiagnosticKind::DuplicateStructMember("x".to_string()),
+                "{source}"
+            );
+            assert_eq!(
+                diagnostic
+                    .labels
+                    .iter()
+                    .map(|label| label.kind.value())
+                    .collect::<Vec<_>>(),
+                vec![
+                    &LabelKind::DuplicateStructMember("x".to_string()),
+                    &LabelKind::FieldFirstDeclaredHere("x".to_string())
+                ],
+                "{source}"
+            );
+        }
+    }
+
     #[test]
     fn self_referential_type_behind_pointer_resolves_correctly() {
         let gs = GlobalScope::new();
@@ -0,0 +1,261 @@
+//! Finding every distinct type a program uses
+//!
+//! This is what `--dump-llvm-types` reports the lowering of. A type is used if
+//! it is the type of a function parameter or return value, a declared
+//! variable, an expression or a place, or the operand of `sizeof` or
+//! `alignof`. Types are listed in the order they are first used in, without
+//! duplicates.
+
+use zrc_utils::span::Spanned;
+
+use crate::{
+    tast::{
+        expr::{Place, PlaceKind, TypedExpr, TypedExprKind},
+        stmt::{LetDeclaration, TypedDeclaration, TypedStmt, TypedStmtKind},
+        ty::Type,
+    },
+    typeck::BlockMetadata,
+};
+
+/// The types found while walking a program
+#[derive(Debug, Default)]
+struct UsedTypes<'input> {
+    /// Every type found, without duplicates
+    found: Vec<Type<'input>>,
+}
+
+impl<'input> UsedTypes<'input> {
+    /// Record that `ty` is used, if it has not been already
+    fn add(&mut self, ty: &Type<'input>) {
+        if !self.found.contains(ty) {
+            self.found.push(ty.clone());
+        }
+    }
+
+    /// Find the types used in a block
+    fn block(&mut self, block: &BlockMetadata<'input>) {
+        for stmt in &block.stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    /// Find the types used in let declarations
+    fn lets(&mut self, declarations: &[Spanned<LetDeclaration<'input>>]) {
+        for declaration in declarations {
+            self.add(&declaration.value().ty);
+            if let Some(value) = &declaration.value().value {
+                self.expr(value);
+            }
+        }
+    }
+
+    /// Find the types used in a statement
+    fn stmt(&mut self, stmt: &TypedStmt<'input>) {
+        match stmt.kind.value() {
+            TypedStmtKind::IfStmt(cond, then, otherwise) => {
+                self.expr(cond);
+                self.block(then.value());
+                if let Some(otherwise) = otherwise {
+                    self.block(otherwise.value());
+                }
+            }
+            TypedStmtKind::WhileStmt(cond, body) => {
+                self.expr(cond);
+                self.block(body.value());
+            }
+            TypedStmtKind::DoWhileStmt(body, cond) => {
+                self.block(body.value());
+                self.expr(cond);
+            }
+            TypedStmtKind::ForStmt {
+                init,
+                cond,
+                post,
+                body,
+            } => {
+                if let Some(init) = init {
+                    self.lets(init);
+                }
+                for expr in cond.iter().chain(post) {
+                    self.expr(expr);
+                }
+                self.block(body.value());
+            }
+            TypedStmtKind::FourStmt(body) => self.block(body.value()),
+            TypedStmtKind::LabeledLoop(_, body) => self.stmt(body),
+            TypedStmtKind::SwitchCase {
+                scrutinee,
+                default,
+                cases,
+                ..
+            } => {
+                self.expr(scrutinee);
+                for (value, body) in cases {
+                    self.expr(value);
+                    self.block(body);
+                }
+                self.block(default);
+            }
+            TypedStmtKind::BlockStmt(body) => self.block(body),
+            TypedStmtKind::ExprStmt(expr) | TypedStmtKind::ReturnStmt(Some(expr)) => {
+                self.expr(expr);
+            }
+            TypedStmtKind::DeclarationList(declarations) => self.lets(declarations),
+            TypedStmtKind::ContinueStmt(_)
+            | TypedStmtKind::BreakStmt(_)
+            | TypedStmtKind::ReturnStmt(None)
+            | TypedStmtKind::UnreachableStmt => {}
+        }
+    }
+
+    /// Find the types used in a place, including its own
+    fn place(&mut self, place: &Place<'input>) {
+        match place.kind.value() {
+            PlaceKind::Variable(_) => {}
+            PlaceKind::Deref(expr) => self.expr(expr),
+            PlaceKind::Index(array, index) => {
+                self.expr(array);
+                self.expr(index);
+            }
+            PlaceKind::Dot(place, _) => self.place(place),
+        }
+        self.add(&place.inferred_type);
+    }
+
+    /// Find the types used in an expression, including its own
+    fn expr(&mut self, expr: &TypedExpr<'input>) {
+        match expr.kind.value() {
+            TypedExprKind::Comma(lhs, rhs)
+            | TypedExprKind::BinaryBitwise(_, lhs, rhs)
+            | TypedExprKind::Logical(_, lhs, rhs)
+            | TypedExprKind::Equality(_, lhs, rhs)
+            | TypedExprKind::Comparison(_, lhs, rhs)
+            | TypedExprKind::Arithmetic(_, lhs, rhs)
+            | TypedExprKind::Index(lhs, rhs)
+            | TypedExprKind::Elvis(lhs, rhs)
+            | TypedExprKind::Rotate(_, lhs, rhs) => {
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            TypedExprKind::Assignment(place, value) => {
+                self.place(place);
+                self.expr(value);
+            }
+            TypedExprKind::UnaryNot(value)
+            | TypedExprKind::UnaryBitwiseNot(value)
+            | TypedExprKind::UnaryMinus(value)
+            | TypedExprKind::UnaryDereference(value)
+            | TypedExprKind::Cast(value, _)
+            | TypedExprKind::IntegerConversion(_, value, _)
+            | TypedExprKind::ArrayRepeat(value, _) => self.expr(value),
+            TypedExprKind::UnaryAddressOf(place)
+            | TypedExprKind::PrefixIncrement(place)
+            | TypedExprKind::PrefixDecrement(place)
+            | TypedExprKind::PostfixIncrement(place)
+            | TypedExprKind::PostfixDecrement(place)
+            | TypedExprKind::Dot(place, _) => self.place(place),
+            TypedExprKind::Call(callee, arguments) => {
+                self.place(callee);
+                for argument in arguments {
+                    self.expr(argument);
+                }
+            }
+            TypedExprKind::Ternary(cond, then, otherwise) => {
+                self.expr(cond);
+                self.expr(then);
+                self.expr(otherwise);
+            }
+            TypedExprKind::Match(spilled, scrutinee, arms) => {
+                if let Some((_, value)) = spilled {
+                    self.expr(value);
+                }
+                self.expr(scrutinee);
+                for arm in arms {
+                    let binding = arm.binding.as_ref().map(|(_, value)| value);
+                    for expr in arm.trigger.iter().chain(binding) {
+                        self.expr(expr);
+                    }
+                    self.expr(&arm.value);
+                }
+            }
+            TypedExprKind::StructConstruction(fields) => {
+                for (_, value) in fields.iter() {
+                    self.expr(value);
+                }
+            }
+            TypedExprKind::ArrayLiteral(elements) => {
+                for element in elements {
+                    self.expr(element);
+                }
+            }
+            TypedExprKind::SizeOf(ty) | TypedExprKind::AlignOf(ty) => self.add(ty),
+            TypedExprKind::NumberLiteral(_, _)
+            | TypedExprKind::StringLiteral(_)
+            | TypedExprKind::CharLiteral(_)
+            | TypedExprKind::Identifier(_)
+            | TypedExprKind::BooleanLiteral(_) => {}
+        }
+        self.add(&expr.inferred_type);
+    }
+}
+
+/// Every distinct type used by `program`, in the order they are first used in
+#[must_use]
+pub fn used_types<'input>(program: &[Spanned<TypedDeclaration<'input>>]) -> Vec<Type<'input>> {
+    let mut used = UsedTypes::default();
+    for declaration in program {
+        match declaration.value() {
+            TypedDeclaration::FunctionDeclaration {
+                parameters,
+                return_type,
+                body,
+                ..
+            } => {
+                for parameter in parameters.value().as_arguments() {
+                    used.add(parameter.ty.value());
+                }
+                used.add(return_type.value());
+                if let Some(body) = body {
+                    used.block(body.value());
+                }
+            }
+            TypedDeclaration::GlobalLetDeclaration(declarations) => used.lets(declarations),
+        }
+    }
+    used.found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::used_types;
+    use crate::typeck::{GlobalScope, type_program};
+
+    #[test]
+    fn each_type_is_listed_once_in_order_of_first_use() {
+        let program = type_program(
+            &mut GlobalScope::new(),
+            zrc_parser::parser::parse_program(
+                concat!(
+                    "struct P { x: i32, y: i32 }\n",
+                    "fn f(p: P, q: *P) -> usize { return sizeof u8; }\n",
+                ),
+                "<test>",
+            )
+            .expect("parsing should succeed"),
+        )
+        .expect("typeck should succeed");
+
+        assert_eq!(
+            used_types(&program)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "struct { x: i32, y: i32 }",
+                "*struct { x: i32, y: i32 }",
+                "usize",
+                "u8"
+            ]
+        );
+    }
+}
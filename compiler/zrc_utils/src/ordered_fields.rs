@@ -1,5 +1,7 @@
 //! The declaration ordered fields of a struct or union type or instantiation
 
+use serde::{Serialize, Serializer};
+
 /// The declaration ordered fields of a struct or union type or instantiation
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct OrderedFields<'input, T> {
@@ -69,6 +71,15 @@ impl<T> Default for OrderedFields<'_, T> {
         Self::new()
     }
 }
+/// Serialized as a map from each field name to its value, in declaration order
+impl<T> Serialize for OrderedFields<'_, T>
+where
+    T: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
 impl<'input, T> IntoIterator for OrderedFields<'input, T> {
     type Item = (&'input str, T);
     type IntoIter = std::vec::IntoIter<(&'input str, T)>;
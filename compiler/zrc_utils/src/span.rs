@@ -14,6 +14,8 @@ use std::{
     path::PathBuf,
 };
 
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+
 /// Represents the start and end of some segment of a string
 ///
 /// A span represents the start and end of some span within a string. It can be
@@ -119,6 +121,16 @@ impl Debug for Span {
         )
     }
 }
+/// Serialized as `{ "file": ..., "start": ..., "end": ... }`
+impl Serialize for Span {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut span = serializer.serialize_struct("Span", 3)?;
+        span.serialize_field("file", self.file_name())?;
+        span.serialize_field("start", &self.start())?;
+        span.serialize_field("end", &self.end())?;
+        span.end()
+    }
+}
 
 /// Represents something (`T`) contained within a [`Span`].
 ///
@@ -213,6 +225,18 @@ where
         write!(f, "{:?}: {:?}", self.span(), self.value())
     }
 }
+/// Serialized as `{ "span": ..., "value": ... }`
+impl<T> Serialize for Spanned<T>
+where
+    T: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut spanned = serializer.serialize_struct("Spanned", 2)?;
+        spanned.serialize_field("span", &self.span())?;
+        spanned.serialize_field("value", self.value())?;
+        spanned.end()
+    }
+}
 impl<T> Spanned<Option<T>> {
     /// Converts a [`Spanned<Option<T>>`] to a [`Option<Spanned<T>>`].
     /// Note: This is not reversible because if you wanted to create a
@@ -27,6 +27,11 @@ pub struct Cli {
     #[arg(long)]
     pub forbid_unlisted_includes: bool,
 
+    /// Enable an opt-in lint that is not run by default, like `-W
+    /// unused-result`
+    #[arg(short = 'W', long = "warn", action = clap::ArgAction::Append)]
+    pub warn: Vec<String>,
+
     /// Diagnostic output format
     #[arg(long)]
     #[clap(default_value = "human")]
@@ -17,10 +17,12 @@ pub enum LintDiagnosticKind {
     UnreachableCode,
     #[error("suspicious control flow")]
     SussyControlFlow,
-    #[error("division by constant zero")]
-    DivisionByConstantZero,
     #[error("unused statement")]
     UnusedStatement,
+    #[error("unused result")]
+    UnusedResult,
+    #[error("unused function declaration")]
+    UnusedDeclaration,
 }
 impl ErrorCode for LintDiagnosticKind {
     fn error_code(&self) -> &'static str {
@@ -30,8 +32,9 @@ impl ErrorCode for LintDiagnosticKind {
             Self::UnusedVariable => "unused_variable",
             Self::UnreachableCode => "unreachable_code",
             Self::SussyControlFlow => "suspicious_control_flow",
-            Self::DivisionByConstantZero => "division_by_constant_zero",
             Self::UnusedStatement => "unused_statement",
+            Self::UnusedResult => "unused_result",
+            Self::UnusedDeclaration => "unused_declaration",
         }
     }
 }
@@ -58,10 +61,12 @@ pub enum LintLabelKind {
     UnreachableCode,
     #[error("this statement ends execution of this block")]
     PriorControlFlow,
-    #[error("division by zero detected here")]
-    DivisionByConstantZero,
     #[error("this statement has no side effects")]
     UnusedStatement,
+    #[error("this call returns `{}`, but the result is discarded", .0)]
+    UnusedResult(String),
+    #[error("`{}` is declared here, but never defined or called", .0)]
+    UnusedDeclaration(String),
 }
 
 /// The list of possible notes on Zircop lints
@@ -70,8 +75,6 @@ pub enum LintLabelKind {
 pub enum LintNoteKind {
     #[error("to suppress this warning, rename the variable to `{}`", .0)]
     UnusedVariableSuppress(String),
-    #[error("this quotient may prove difficult to obtain")]
-    DivisionByConstantZero,
     #[error("this expression appears to exist solely to troll the processor")]
     UnusedStatement,
 }
@@ -90,6 +93,10 @@ pub enum LintHelpKind {
     RenameVariable(String),
     #[error("consider removing the variable declaration if it has no side effects")]
     RemoveVariableDeclaration,
+    #[error("consider using the result, or cast it with `as` to discard it explicitly")]
+    UseResult,
+    #[error("consider removing the declaration")]
+    RemoveDeclaration,
 }
 
 /// A Zircop lint
@@ -1,14 +1,15 @@
 //! List of lints provided by zircop
 
 mod bad_control_flow;
-mod division_by_constant_zero;
 mod empty_struct_used;
 mod underscore_variable_used;
 mod unreachable_code;
+mod unused_declarations;
+mod unused_result;
 mod unused_statement;
 mod unused_variables;
 
-use crate::pass::PassList;
+use crate::{lint::Lint, pass::PassList};
 
 /// Returns the default set of lints provided by Zircop
 #[must_use]
@@ -19,7 +20,17 @@ pub fn get_default_lints() -> PassList {
         unreachable_code::UnreachableCodeLint::init(),
         unused_variables::UnusedVariablesLint::init(),
         bad_control_flow::BadControlFlowLint::init(),
-        division_by_constant_zero::DivisionByConstantZero::init(),
         unused_statement::UnusedStatementLint::init(),
     ])
 }
+
+/// Returns the opt-in lint named `name`, which is enabled with `-W <name>`, or
+/// [`None`] if there is no such lint
+#[must_use]
+pub fn get_opt_in_lint(name: &str) -> Option<Box<dyn Lint>> {
+    match name {
+        "unused-result" => Some(unused_result::UnusedResultLint::init()),
+        "unused-declarations" => Some(unused_declarations::UnusedDeclarationsLint::init()),
+        _ => None,
+    }
+}
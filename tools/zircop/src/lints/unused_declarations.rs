@@ -0,0 +1,177 @@
+//! `unused_declarations`: Unused function declaration detection
+//!
+//! This opt-in lint, enabled with `-W unused-declarations`, checks for
+//! function declarations like `fn foo();` that are neither defined nor called
+//! anywhere in the program. These are usually left over from code that has
+//! since been removed.
+
+use zrc_diagnostics::diagnostic::GenericLabel;
+use zrc_typeck::tast::{
+    expr::{Place, PlaceKind, TypedExpr, TypedExprKind},
+    stmt::TypedDeclaration,
+};
+use zrc_utils::span::{Spannable, Spanned};
+
+use crate::{
+    diagnostic::{LintDiagnostic, LintDiagnosticKind, LintHelpKind, LintLabelKind},
+    lint::Lint,
+    visit::SemanticVisit,
+};
+
+/// `unused_declarations`: Unused function declaration detection
+///
+/// This lint collects every function declared without a body, then walks the
+/// program and reports those that are never referenced and never given a body
+/// by a later declaration. Functions with a body are never reported, as Zirco
+/// does not mangle names and so every defined function is visible to the
+/// linker.
+pub struct UnusedDeclarationsLint;
+impl UnusedDeclarationsLint {
+    /// Initialize this lint
+    pub fn init() -> Box<dyn Lint> {
+        Box::new(Self)
+    }
+}
+impl Lint for UnusedDeclarationsLint {
+    fn lint_tast(&self, program: Vec<Spanned<TypedDeclaration<'_>>>) -> Vec<LintDiagnostic> {
+        let mut vis = Visit { referenced: vec![] };
+        vis.visit_tc_program(&program);
+
+        let mut reported: Vec<&str> = vec![];
+        let mut diagnostics = vec![];
+        for declaration in &program {
+            let TypedDeclaration::FunctionDeclaration {
+                name, body: None, ..
+            } = declaration.value()
+            else {
+                continue;
+            };
+
+            let is_defined = program.iter().any(|other| {
+                matches!(
+                    other.value(),
+                    TypedDeclaration::FunctionDeclaration {
+                        name: other_name,
+                        body: Some(_),
+                        ..
+                    } if other_name.value() == name.value()
+                )
+            });
+
+            if is_defined
+                || vis.referenced.contains(name.value())
+                || reported.contains(name.value())
+            {
+                continue;
+            }
+
+            let span = name.span();
+            diagnostics.push(
+                LintDiagnostic::warning(LintDiagnosticKind::UnusedDeclaration.in_span(span))
+                    .with_label(GenericLabel::warning(
+                        LintLabelKind::UnusedDeclaration((*name.value()).to_string()).in_span(span),
+                    ))
+                    .with_help(LintHelpKind::RemoveDeclaration),
+            );
+            reported.push(name.value());
+        }
+
+        diagnostics
+    }
+}
+
+/// AST visitor for the `unused_declarations` lint
+struct Visit<'input> {
+    /// Every identifier referenced by the program
+    referenced: Vec<&'input str>,
+}
+
+impl<'input> SemanticVisit<'input, '_> for Visit<'input> {
+    fn visit_tc_expr(&mut self, expr: &TypedExpr<'input>) {
+        if let TypedExprKind::Identifier(name) = expr.kind.value() {
+            self.referenced.push(name);
+        }
+
+        SemanticVisit::walk_tc_expr(self, expr);
+    }
+
+    fn visit_place(&mut self, place: &Place<'input>) {
+        // the default traversal does not enter places, but calls are made
+        // through one
+        match place.kind.value() {
+            PlaceKind::Variable(name) => self.referenced.push(name),
+            PlaceKind::Deref(expr) => self.visit_tc_expr(expr),
+            PlaceKind::Index(array, index) => {
+                self.visit_tc_expr(array);
+                self.visit_tc_expr(index);
+            }
+            PlaceKind::Dot(place, _) => self.visit_place(place),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use zrc_utils::spanned_test;
+
+    use super::*;
+    use crate::{lints::get_opt_in_lint, pass::PassList, zircop_lint_test};
+
+    /// The passes run by these tests: only `unused_declarations`
+    fn passes() -> PassList {
+        PassList::new(vec![
+            get_opt_in_lint("unused-declarations").expect("unused-declarations should exist"),
+        ])
+    }
+
+    zircop_lint_test! {
+        name: unused_declaration_warns,
+        source: indoc!{"
+            fn stale();
+            fn f() {}
+        "},
+        passes: passes(),
+        diagnostics: vec![
+            LintDiagnostic::warning(
+                spanned_test!(
+                    3,
+                    LintDiagnosticKind::UnusedDeclaration,
+                    8
+                )
+            ).with_label(
+                GenericLabel::warning(
+                    spanned_test!(
+                        3,
+                        LintLabelKind::UnusedDeclaration("stale".to_string()),
+                        8
+                    )
+                )
+            ).with_help(
+                LintHelpKind::RemoveDeclaration
+            ),
+        ]
+    }
+
+    zircop_lint_test! {
+        name: called_or_defined_declarations_do_not_warn,
+        source: indoc!{"
+            fn puts(s: *u8) -> i32;
+            fn helper();
+            fn helper() {}
+            fn f() {
+                puts(\"hi\");
+            }
+        "},
+        passes: passes(),
+        diagnostics: vec![]
+    }
+
+    zircop_lint_test! {
+        name: unused_declarations_is_not_a_default_lint,
+        source: indoc!{"
+            fn stale();
+        "},
+        diagnostics: vec![]
+    }
+}
@@ -0,0 +1,163 @@
+//! `unused_result`: Discarded return value detection
+//!
+//! This opt-in lint, enabled with `-W unused-result`, checks for expression
+//! statements that call a function returning a value and then ignore it. Some
+//! projects treat every return value as must-use, as ignoring one often means
+//! an error code went unchecked.
+
+use zrc_diagnostics::diagnostic::GenericLabel;
+use zrc_typeck::{
+    tast::{expr::TypedExprKind, stmt::TypedDeclaration, ty::Type},
+    typeck::BlockMetadata,
+};
+use zrc_utils::span::{Spannable, Spanned};
+
+use crate::{
+    diagnostic::{LintDiagnostic, LintDiagnosticKind, LintHelpKind, LintLabelKind},
+    lint::Lint,
+    visit::SemanticVisit,
+};
+
+/// `unused_result`: Discarded return value detection
+///
+/// This lint walks through blocks and detects any expression statement that
+/// is a call to a function with a non-`void` return type. Casting the call or
+/// assigning its result suppresses the warning, as the statement is then no
+/// longer a bare call.
+pub struct UnusedResultLint;
+impl UnusedResultLint {
+    /// Initialize this lint
+    pub fn init() -> Box<dyn Lint> {
+        Box::new(Self)
+    }
+}
+impl Lint for UnusedResultLint {
+    fn lint_tast(&self, program: Vec<Spanned<TypedDeclaration<'_>>>) -> Vec<LintDiagnostic> {
+        let mut vis = Visit {
+            diagnostics: vec![],
+        };
+        vis.visit_tc_program(&program);
+
+        vis.diagnostics
+    }
+}
+
+/// AST visitor for the `unused_result` lint
+struct Visit {
+    /// The collected diagnostics
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+impl<'input> SemanticVisit<'input, '_> for Visit {
+    fn visit_tc_block(&mut self, block: &BlockMetadata<'input>) {
+        use zrc_typeck::tast::stmt::TypedStmtKind;
+
+        for stmt in &block.stmts {
+            if let TypedStmtKind::ExprStmt(expr) = stmt.kind.value()
+                && let TypedExprKind::Call(..) = expr.kind.value()
+                && !is_void(&expr.inferred_type)
+            {
+                let span = stmt.kind.span();
+                self.diagnostics.push(
+                    LintDiagnostic::warning(LintDiagnosticKind::UnusedResult.in_span(span))
+                        .with_label(GenericLabel::warning(
+                            LintLabelKind::UnusedResult(expr.inferred_type.to_string())
+                                .in_span(span),
+                        ))
+                        .with_help(LintHelpKind::UseResult),
+                );
+            }
+        }
+
+        // Walk into nested blocks
+        SemanticVisit::walk_tc_block(self, block);
+    }
+}
+
+/// Check if a type is `void`, the empty struct
+const fn is_void(ty: &Type<'_>) -> bool {
+    matches!(ty, Type::Struct(fields) if fields.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use zrc_utils::spanned_test;
+
+    use super::*;
+    use crate::{lints::get_opt_in_lint, pass::PassList, zircop_lint_test};
+
+    /// The passes run by these tests: only `unused_result`
+    fn passes() -> PassList {
+        PassList::new(vec![
+            get_opt_in_lint("unused-result").expect("unused-result should exist"),
+        ])
+    }
+
+    zircop_lint_test! {
+        name: ignored_non_void_call_warns,
+        source: indoc!{"
+            fn compute() -> i32 { return 1; }
+            fn f() {
+                compute();
+            }
+        "},
+        passes: passes(),
+        diagnostics: vec![
+            LintDiagnostic::warning(
+                spanned_test!(
+                    47,
+                    LintDiagnosticKind::UnusedResult,
+                    57
+                )
+            ).with_label(
+                GenericLabel::warning(
+                    spanned_test!(
+                        47,
+                        LintLabelKind::UnusedResult("i32".to_string()),
+                        57
+                    )
+                )
+            ).with_help(
+                LintHelpKind::UseResult
+            ),
+        ]
+    }
+
+    zircop_lint_test! {
+        name: void_call_does_not_warn,
+        source: indoc!{"
+            fn g() {}
+            fn f() {
+                g();
+            }
+        "},
+        passes: passes(),
+        diagnostics: vec![]
+    }
+
+    zircop_lint_test! {
+        name: used_or_cast_result_does_not_warn,
+        source: indoc!{"
+            fn compute() -> i32 { return 1; }
+            fn f() {
+                let x = compute();
+                x = compute();
+                compute() as i32;
+            }
+        "},
+        passes: passes(),
+        diagnostics: vec![]
+    }
+
+    zircop_lint_test! {
+        name: unused_result_is_not_a_default_lint,
+        source: indoc!{"
+            fn compute() -> i32 { return 1; }
+            fn f() {
+                compute();
+            }
+        "},
+        diagnostics: vec![]
+    }
+}
@@ -92,7 +92,9 @@ fn has_side_effects(expr: &TypedExprKind<'_>) -> bool {
         | TypedExprKind::Logical(_, left, right)
         | TypedExprKind::Equality(_, left, right)
         | TypedExprKind::Comparison(_, left, right)
-        | TypedExprKind::Arithmetic(_, left, right) => {
+        | TypedExprKind::Arithmetic(_, left, right)
+        | TypedExprKind::Rotate(_, left, right)
+        | TypedExprKind::Elvis(left, right) => {
             has_side_effects(left.kind.value()) || has_side_effects(right.kind.value())
         }
 
@@ -103,18 +105,31 @@ fn has_side_effects(expr: &TypedExprKind<'_>) -> bool {
                 || has_side_effects(else_expr.kind.value())
         }
 
+        // Match: check the scrutinee and every arm
+        TypedExprKind::Match(spilled, scrutinee, arms) => {
+            spilled
+                .as_ref()
+                .is_some_and(|(_, value)| has_side_effects(value.kind.value()))
+                || has_side_effects(scrutinee.kind.value())
+                || arms
+                    .iter()
+                    .any(|arm| has_side_effects(arm.value.kind.value()))
+        }
+
         // Binary operations: check both operands
         // Unary operations: check the operand
         TypedExprKind::UnaryNot(expr)
         | TypedExprKind::UnaryBitwiseNot(expr)
         | TypedExprKind::UnaryMinus(expr)
         | TypedExprKind::UnaryDereference(expr)
-        | TypedExprKind::Cast(expr, _) => has_side_effects(expr.kind.value()),
+        | TypedExprKind::Cast(expr, _)
+        | TypedExprKind::IntegerConversion(_, expr, _) => has_side_effects(expr.kind.value()),
 
         // these operations don't have side effects on their own
         TypedExprKind::UnaryAddressOf(_)
         | TypedExprKind::Dot(_, _)
         | TypedExprKind::SizeOf(_)
+        | TypedExprKind::AlignOf(_)
         | TypedExprKind::NumberLiteral(_, _)
         | TypedExprKind::StringLiteral(_)
         | TypedExprKind::CharLiteral(_)
@@ -132,6 +147,7 @@ fn has_side_effects(expr: &TypedExprKind<'_>) -> bool {
         TypedExprKind::ArrayLiteral(elements) => {
             elements.iter().any(|ex| has_side_effects(ex.kind.value()))
         }
+        TypedExprKind::ArrayRepeat(value, _) => has_side_effects(value.kind.value()),
 
         // Struct construction: check all field values
         TypedExprKind::StructConstruction(fields) => fields
@@ -222,8 +238,8 @@ mod tests {
         name: no_warning_for_assignment,
         source: indoc!{"
             fn f() -> void {
-                let x = 0;
-                x = 5;
+                let _x = 0;
+                _x = 5;
             }
         "},
         diagnostics: vec![]
@@ -284,8 +300,8 @@ mod tests {
         name: no_warning_for_comma_with_side_effects,
         source: indoc!{"
             fn f() -> void {
-                let x = 0;
-                1, x = 2;
+                let _x = 0;
+                1, _x = 2;
             }
         "},
         diagnostics: vec![]
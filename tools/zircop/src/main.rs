@@ -58,7 +58,7 @@ use std::{error::Error, fmt, path::Path, process};
 
 use clap::Parser;
 use cli::Cli;
-use zircop::runner;
+use zircop::{lints, runner};
 use zrc_utils::io;
 
 /// Get the current Zircop version.
@@ -93,12 +93,21 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut source_content = String::new();
     input.read_to_string(&mut source_content)?;
 
-    let diagnostics = runner::run_with_default_passes(
+    let mut passes = lints::get_default_lints();
+    for name in &cli.warn {
+        let Some(lint) = lints::get_opt_in_lint(name) else {
+            return Err(Box::new(CliError(format!("Unknown lint `{name}`."))));
+        };
+        passes.push(lint);
+    }
+
+    let diagnostics = runner::run(
         &cli::get_include_paths(&cli),
         Path::new(&directory_name),
         &file_name,
         &source_content,
         cli.forbid_unlisted_includes,
+        &passes,
     );
 
     match diagnostics {
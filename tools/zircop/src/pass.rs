@@ -17,6 +17,11 @@ impl PassList {
         Self(lints)
     }
 
+    /// Add a lint to the end of this passlist
+    pub fn push(&mut self, lint: Box<dyn Lint>) {
+        self.0.push(lint);
+    }
+
     /// Invoke all lints in this passlist on the given AST. This clones the
     /// program for each lint to allow multiple lints to consume it.
     #[must_use]
@@ -8,6 +8,9 @@
 
 /// Creates a Zircop test that runs the given source code and checks diagnostics
 /// against a list
+///
+/// The default lints are run unless a [`crate::pass::PassList`] is given as
+/// `passes`.
 #[macro_export]
 macro_rules! zircop_lint_test {
     (
@@ -30,6 +33,34 @@ macro_rules! zircop_lint_test {
             )
             .expect("Compilation should succeed");
 
+            assert_eq!(
+                lint_result, $diagnostics,
+                "Diagnostics did not match expected"
+            );
+        }
+    };
+    (
+        name: $name:ident,
+        source: $source:expr,
+        passes: $passes:expr,
+        diagnostics: $diagnostics:expr
+    ) => {
+        #[test]
+        fn $name() {
+            let include_paths = vec![];
+            let parent_directory = std::path::Path::new("");
+            let file_name = "<test>";
+
+            let lint_result = $crate::runner::run(
+                &include_paths,
+                parent_directory,
+                file_name,
+                $source,
+                false,
+                &$passes,
+            )
+            .expect("Compilation should succeed");
+
             assert_eq!(
                 lint_result, $diagnostics,
                 "Diagnostics did not match expected"
@@ -67,6 +67,10 @@ pub trait SyntacticVisit<'input> {
                 self.visit_expr(if_true.as_ref());
                 self.visit_expr(if_false.as_ref());
             }
+            AstExprKind::Elvis(value, fallback) => {
+                self.visit_expr(value.as_ref());
+                self.visit_expr(fallback.as_ref());
+            }
             AstExprKind::Cast(ex, ty) => {
                 self.visit_expr(ex.as_ref());
                 self.visit_type(ty);
@@ -80,17 +84,28 @@ pub trait SyntacticVisit<'input> {
                     self.visit_expr(&fv.1);
                 }
             }
+            AstExprKind::Match(scrutinee, arms) => {
+                self.visit_expr(scrutinee.as_ref());
+                for arm in arms {
+                    self.visit_expr(&arm.value().body);
+                }
+            }
             AstExprKind::ArrayLiteral(elements) => {
                 for elem in elements.value() {
                     self.visit_expr(elem);
                 }
             }
+            AstExprKind::ArrayRepeat(value, count) => {
+                self.visit_expr(value);
+                self.visit_expr(count);
+            }
             AstExprKind::NumberLiteral(_, ty) => {
                 if let Some(ty) = ty.as_ref() {
                     self.visit_type(ty);
                 }
             }
             AstExprKind::StringLiteral(_)
+            | AstExprKind::EnumVariant(_, _)
             | AstExprKind::CharLiteral(_)
             | AstExprKind::Identifier(_)
             | AstExprKind::BooleanLiteral(_) => {}
@@ -117,6 +132,12 @@ pub trait SyntacticVisit<'input> {
                 self.visit_expr(cond);
                 self.visit_stmt(body.as_ref());
             }
+            AstStmtKind::WhileLetStmt {
+                scrutinee, body, ..
+            } => {
+                self.visit_expr(scrutinee);
+                self.visit_stmt(body.as_ref());
+            }
             AstStmtKind::DoWhileStmt(body, cond) => {
                 self.visit_stmt(body.as_ref());
                 self.visit_expr(cond);
@@ -141,7 +162,11 @@ pub trait SyntacticVisit<'input> {
                 }
                 self.visit_stmt(body.as_ref());
             }
-            AstStmtKind::FourStmt(body) => {
+            AstStmtKind::ForInStmt { iterable, body, .. } => {
+                self.visit_expr(iterable);
+                self.visit_stmt(body.as_ref());
+            }
+            AstStmtKind::FourStmt(body) | AstStmtKind::LabeledLoop { body, .. } => {
                 self.visit_stmt(body.as_ref());
             }
             AstStmtKind::BlockStmt(stmts) => {
@@ -151,9 +176,11 @@ pub trait SyntacticVisit<'input> {
                 self.visit_expr(expr);
             }
             AstStmtKind::EmptyStmt
-            | AstStmtKind::ContinueStmt
-            | AstStmtKind::BreakStmt
-            | AstStmtKind::UnreachableStmt => {}
+            | AstStmtKind::ContinueStmt(_)
+            | AstStmtKind::BreakStmt(_)
+            | AstStmtKind::UnreachableStmt
+            | AstStmtKind::CompileErrorStmt(_)
+            | AstStmtKind::CompileWarningStmt(_) => {}
             AstStmtKind::ReturnStmt(opt) => {
                 if let Some(ex) = opt {
                     self.visit_expr(ex);
@@ -169,7 +196,7 @@ pub trait SyntacticVisit<'input> {
                 for case in cases {
                     match &case.value().0 {
                         SwitchTrigger::Expr(ex) => self.visit_expr(ex),
-                        SwitchTrigger::Default => {}
+                        SwitchTrigger::Default(_) => {}
                     }
                     self.visit_stmt(&case.value().1);
                 }
@@ -267,13 +294,26 @@ pub trait SyntacticVisit<'input> {
                 parameters,
                 return_type,
                 body,
+                ..
             } => {
                 self.visit_fn_decl(name, parameters, return_type, body);
             }
             AstDecl::TypeAliasDeclaration { name, ty } => {
                 self.visit_type_alias_decl(name, ty);
             }
+            AstDecl::EnumDeclaration { variants, .. } => {
+                for variant in variants.value() {
+                    if let Some(ty) = &variant.value().ty {
+                        self.visit_type(ty);
+                    }
+                }
+            }
             AstDecl::GlobalLetDeclaration(decls) => self.visit_global_let_decl(decls),
+            AstDecl::ImplDeclaration { methods, .. } => {
+                for method in methods {
+                    self.visit_block(method.value().body.value());
+                }
+            }
         }
     }
 
@@ -344,6 +384,7 @@ pub trait SemanticVisit<'input, 'gs> {
             | TcExprKind::Equality(_, lhs, rhs)
             | TcExprKind::Comparison(_, lhs, rhs)
             | TcExprKind::Arithmetic(_, lhs, rhs)
+            | TcExprKind::Rotate(_, lhs, rhs)
             | TcExprKind::Index(lhs, rhs) => {
                 self.visit_tc_expr(lhs.as_ref());
                 self.visit_tc_expr(rhs.as_ref());
@@ -359,21 +400,39 @@ pub trait SemanticVisit<'input, 'gs> {
                 self.visit_tc_expr(if_true.as_ref());
                 self.visit_tc_expr(if_false.as_ref());
             }
-            TcExprKind::Cast(ex, ty) => {
+            TcExprKind::Elvis(value, fallback) => {
+                self.visit_tc_expr(value.as_ref());
+                self.visit_tc_expr(fallback.as_ref());
+            }
+            TcExprKind::Cast(ex, ty) | TcExprKind::IntegerConversion(_, ex, ty) => {
                 self.visit_tc_expr(ex.as_ref());
                 self.visit_tc_type(ty.value());
             }
-            TcExprKind::SizeOf(ty) => self.visit_tc_type(ty),
+            TcExprKind::SizeOf(ty) | TcExprKind::AlignOf(ty) => self.visit_tc_type(ty),
             TcExprKind::StructConstruction(fields) => {
                 for (_name, expr) in fields.iter() {
                     self.visit_tc_expr(expr);
                 }
             }
+            TcExprKind::Match(spilled, scrutinee, arms) => {
+                if let Some((_, value)) = spilled {
+                    self.visit_tc_expr(value);
+                }
+                self.visit_tc_expr(scrutinee.as_ref());
+                for arm in arms {
+                    let binding = arm.binding.as_ref().map(|(_, value)| value);
+                    for expr in arm.trigger.iter().chain(binding) {
+                        self.visit_tc_expr(expr);
+                    }
+                    self.visit_tc_expr(&arm.value);
+                }
+            }
             TcExprKind::ArrayLiteral(elements) => {
                 for elem in elements {
                     self.visit_tc_expr(elem);
                 }
             }
+            TcExprKind::ArrayRepeat(value, _) => self.visit_tc_expr(value),
             TcExprKind::NumberLiteral(_, _)
             | TcExprKind::StringLiteral(_)
             | TcExprKind::CharLiteral(_)
@@ -427,10 +486,12 @@ pub trait SemanticVisit<'input, 'gs> {
             TcStmtKind::FourStmt(body) => {
                 self.visit_tc_block(body.value());
             }
+            TcStmtKind::LabeledLoop(_, body) => self.visit_tc_stmt(body),
             TcStmtKind::SwitchCase {
                 scrutinee,
                 default,
                 cases,
+                ..
             } => {
                 self.visit_tc_expr(scrutinee);
                 for cs in &default.stmts {
@@ -447,7 +508,9 @@ pub trait SemanticVisit<'input, 'gs> {
                 self.visit_tc_block(stmts);
             }
             TcStmtKind::ExprStmt(expr) => self.visit_tc_expr(expr),
-            TcStmtKind::ContinueStmt | TcStmtKind::BreakStmt | TcStmtKind::UnreachableStmt => {}
+            TcStmtKind::ContinueStmt(_)
+            | TcStmtKind::BreakStmt(_)
+            | TcStmtKind::UnreachableStmt => {}
             TcStmtKind::ReturnStmt(opt) => {
                 if let Some(ex) = opt {
                     self.visit_tc_expr(ex);
@@ -531,6 +594,7 @@ pub trait SemanticVisit<'input, 'gs> {
                 parameters,
                 return_type,
                 body,
+                ..
             } => {
                 self.visit_tc_fn_decl(name, parameters, return_type, body);
             }
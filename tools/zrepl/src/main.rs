@@ -307,7 +307,7 @@ fn handle_include(line: &str, include_paths: &'static [&Path], gs: &mut GlobalSc
 
     let mut ast = Vec::new();
     for chunk in chunks {
-        let decls = diag_wrapper(|| parser::parse_source_chunk(chunk), None);
+        let decls = diag_wrapper(|| parser::parse_source_chunk(chunk), Some(line));
         let Ok(decls) = decls else {
             // Repline does not like it when you Continue or Reject after printing
             return Response::Accept;
@@ -481,7 +481,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                             typeck::type_block(
                                 scope,
                                 stmts,
-                                false,
+                                typeck::BreakContinueAbility::Neither,
                                 typeck::BlockReturnAbility::MustNotReturn,
                             )
                         },
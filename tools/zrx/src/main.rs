@@ -205,6 +205,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             &file_name,
             &LineLookup::new(&source_content),
             typed_ast,
+            &zrc_codegen::CodegenOptions::default(),
         );
 
         jit_module.link_in_module(file_module)?;